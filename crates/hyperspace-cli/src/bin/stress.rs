@@ -1,6 +1,6 @@
 use hyperspace_proto::hyperspace::database_client::DatabaseClient;
 use hyperspace_proto::hyperspace::{
-    ConfigUpdate, CreateCollectionRequest, Empty, InsertRequest, SearchRequest,
+    ConfigUpdate, CreateCollectionRequest, InsertRequest, SearchRequest, SnapshotRequest,
 };
 use rand::Rng;
 use std::io::Write;
@@ -31,6 +31,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             name: COLLECTION_NAME.to_string(),
             dimension: 8,
             metric: "poincare".to_string(),
+            ..Default::default()
         })
         .await
         .ok(); // Ignore if exists
@@ -42,6 +43,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             ef_construction: Some(50),
             ef_search: None,
             collection: COLLECTION_NAME.to_string(),
+            wal_sync_mode: None,
         })
         .await?;
 
@@ -73,6 +75,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             origin_node_id: String::new(),
             logical_clock: 0,
             durability: 0,
+            vector_f32: Vec::new(),
+            write_mode: 0,
+            token_vectors: None,
         };
 
         client.insert(req).await?;
@@ -91,7 +96,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 3. Trigger Snapshot (Flush)
     println!("💾 Triggering Snapshot...");
-    let _ = client.trigger_snapshot(Empty {}).await?;
+    let _ = client.trigger_snapshot(SnapshotRequest::default()).await?;
 
     // 4. Search Benchmark
     println!("🔍 Running {SEARCH_QUERIES} Search Queries (top_k=10)...");
@@ -101,6 +106,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             ef_search: Some(100),
             ef_construction: None,
             collection: COLLECTION_NAME.to_string(),
+            wal_sync_mode: None,
         })
         .await?;
 
@@ -127,6 +133,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             use_wasserstein: false,
             bm25_options: None,
             collection: COLLECTION_NAME.to_string(),
+            vector_f32: Vec::new(),
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
         };
         client.search(req).await?;
     }
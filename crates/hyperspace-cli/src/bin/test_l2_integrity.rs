@@ -31,6 +31,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             name: COLLECTION_NAME.to_string(),
             dimension: 1024,
             metric: "l2".to_string(),
+            ..Default::default()
         })
         .await
         .ok();
@@ -48,6 +49,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             origin_node_id: String::new(),
             logical_clock: 0,
             durability: 0,
+            vector_f32: Vec::new(),
+            write_mode: 0,
+            token_vectors: None,
         })
         .await?;
 
@@ -64,6 +68,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             origin_node_id: String::new(),
             logical_clock: 0,
             durability: 0,
+            vector_f32: Vec::new(),
+            write_mode: 0,
+            token_vectors: None,
         })
         .await?;
 
@@ -80,6 +87,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             origin_node_id: String::new(),
             logical_clock: 0,
             durability: 0,
+            vector_f32: Vec::new(),
+            write_mode: 0,
+            token_vectors: None,
         })
         .await?;
 
@@ -98,6 +108,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             hybrid_alpha: None,
             use_wasserstein: false,
             bm25_options: None,
+            vector_f32: Vec::new(),
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
         })
         .await?;
 
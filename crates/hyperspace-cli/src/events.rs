@@ -0,0 +1,206 @@
+//! `events tail`: follows the `SubscribeToEvents` CDC stream.
+//!
+//! Prints one line per event, either human-readable or as JSON (one object
+//! per line, for piping into `jq`). Runs until the server closes the stream
+//! or the process is interrupted.
+
+use clap::{Args, ValueEnum};
+use hyperspace_proto::hyperspace::{
+    event_message::Payload, metadata_value, EventMessage, EventType, MetadataValue,
+};
+use hyperspace_sdk::Client;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Pretty,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct TailArgs {
+    /// Only events of this collection (all collections when omitted)
+    #[arg(long)]
+    collection: Option<String>,
+    /// Comma-separated event types: insert, delete, alert
+    #[arg(long, value_delimiter = ',', value_parser = parse_type)]
+    types: Vec<EventType>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Pretty)]
+    format: Format,
+}
+
+fn parse_type(s: &str) -> Result<EventType, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "insert" | "inserted" => Ok(EventType::VectorInserted),
+        "delete" | "deleted" => Ok(EventType::VectorDeleted),
+        "alert" | "storage_alert" => Ok(EventType::StorageAlert),
+        other => Err(format!(
+            "unknown event type '{other}', expected insert, delete or alert"
+        )),
+    }
+}
+
+pub async fn tail(client: &mut Client, args: TailArgs) -> Result<(), Box<dyn Error>> {
+    let mut stream = client
+        .subscribe_to_events(args.types, args.collection)
+        .await?;
+    while let Some(event) = stream.message().await? {
+        let line = match args.format {
+            Format::Pretty => format_pretty(&event),
+            Format::Json => format_json(&event).map(|v| v.to_string()),
+        };
+        if let Some(line) = line {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+fn typed_value(value: &MetadataValue) -> Value {
+    match &value.kind {
+        Some(metadata_value::Kind::StringValue(s)) => json!(s),
+        Some(metadata_value::Kind::IntValue(i)) => json!(i),
+        Some(metadata_value::Kind::DoubleValue(d)) => json!(d),
+        Some(metadata_value::Kind::BoolValue(b)) => json!(b),
+        None => Value::Null,
+    }
+}
+
+/// Typed values win over their string form; keys come out sorted.
+fn merged_metadata(
+    metadata: &HashMap<String, String>,
+    typed: &HashMap<String, MetadataValue>,
+) -> BTreeMap<String, Value> {
+    let mut merged: BTreeMap<String, Value> = metadata
+        .iter()
+        .map(|(k, v)| (k.clone(), json!(v)))
+        .collect();
+    for (k, v) in typed {
+        merged.insert(k.clone(), typed_value(v));
+    }
+    merged
+}
+
+fn format_json(event: &EventMessage) -> Option<Value> {
+    let value = match event.payload.as_ref()? {
+        Payload::VectorInserted(e) => json!({
+            "type": "insert",
+            "collection": e.collection,
+            "id": e.id,
+            "logical_clock": e.logical_clock,
+            "origin_node_id": e.origin_node_id,
+            "metadata": merged_metadata(&e.metadata, &e.typed_metadata),
+        }),
+        Payload::VectorDeleted(e) => json!({
+            "type": "delete",
+            "collection": e.collection,
+            "id": e.id,
+            "logical_clock": e.logical_clock,
+            "origin_node_id": e.origin_node_id,
+        }),
+        Payload::StorageAlert(e) => json!({
+            "type": "storage_alert",
+            "collection": e.collection,
+            "error": e.error,
+            "consecutive_failures": e.consecutive_failures,
+            "read_only": e.read_only,
+        }),
+    };
+    Some(value)
+}
+
+fn format_pretty(event: &EventMessage) -> Option<String> {
+    let now = chrono::Local::now().format("%H:%M:%S%.3f");
+    let line = match event.payload.as_ref()? {
+        Payload::VectorInserted(e) => {
+            let meta = merged_metadata(&e.metadata, &e.typed_metadata)
+                .into_iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "{now} INSERT {}#{} clock={} origin={} {meta}",
+                e.collection, e.id, e.logical_clock, e.origin_node_id
+            )
+        }
+        Payload::VectorDeleted(e) => format!(
+            "{now} DELETE {}#{} clock={} origin={}",
+            e.collection, e.id, e.logical_clock, e.origin_node_id
+        ),
+        Payload::StorageAlert(e) => format!(
+            "{now} ALERT {} failures={}{} {}",
+            e.collection,
+            e.consecutive_failures,
+            if e.read_only { " read-only" } else { "" },
+            e.error
+        ),
+    };
+    Some(line.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_proto::hyperspace::{VectorDeletedEvent, VectorInsertedEvent};
+
+    #[test]
+    fn types_parse_case_insensitively() {
+        assert_eq!(parse_type(" Insert"), Ok(EventType::VectorInserted));
+        assert_eq!(parse_type("deleted"), Ok(EventType::VectorDeleted));
+        assert_eq!(parse_type("alert"), Ok(EventType::StorageAlert));
+        assert!(parse_type("update").is_err());
+    }
+
+    #[test]
+    fn json_merges_typed_metadata() {
+        let insert = EventMessage {
+            r#type: EventType::VectorInserted as i32,
+            payload: Some(Payload::VectorInserted(VectorInsertedEvent {
+                id: 7,
+                collection: "docs".into(),
+                logical_clock: 3,
+                origin_node_id: "n1".into(),
+                metadata: HashMap::from([
+                    ("lang".to_string(), "en".to_string()),
+                    ("year".to_string(), "2024".to_string()),
+                ]),
+                typed_metadata: HashMap::from([(
+                    "year".to_string(),
+                    MetadataValue {
+                        kind: Some(metadata_value::Kind::IntValue(2024)),
+                    },
+                )]),
+                vector: Vec::new(),
+            })),
+        };
+        assert_eq!(
+            format_json(&insert).unwrap(),
+            json!({
+                "type": "insert",
+                "collection": "docs",
+                "id": 7,
+                "logical_clock": 3,
+                "origin_node_id": "n1",
+                "metadata": {"lang": "en", "year": 2024},
+            })
+        );
+        assert!(format_pretty(&insert)
+            .unwrap()
+            .ends_with("INSERT docs#7 clock=3 origin=n1 lang=\"en\" year=2024"));
+
+        let delete = EventMessage {
+            r#type: EventType::VectorDeleted as i32,
+            payload: Some(Payload::VectorDeleted(VectorDeletedEvent {
+                id: 7,
+                collection: "docs".into(),
+                logical_clock: 4,
+                origin_node_id: "n1".into(),
+            })),
+        };
+        assert_eq!(format_json(&delete).unwrap()["type"], "delete");
+        assert_eq!(format_json(&EventMessage::default()), None);
+    }
+}
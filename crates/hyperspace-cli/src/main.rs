@@ -1,14 +1,19 @@
 mod app;
+mod events;
+mod snapshot;
 mod ui;
 
 use app::{App, CurrentTab};
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use hyperspace_proto::hyperspace::database_client::DatabaseClient;
-use hyperspace_proto::hyperspace::{Empty, MonitorRequest, SystemStats};
+use hyperspace_proto::hyperspace::{
+    Empty, ListCollectionsRequest, MonitorRequest, SnapshotRequest, SystemStats,
+};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::error::Error;
 use std::io;
@@ -16,10 +21,62 @@ use std::time::Duration;
 use tonic::transport::Channel;
 use ui::ui;
 
+/// Terminal dashboard (no subcommand) and debugging tools for `HyperspaceDB`.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// gRPC address of the server
+    #[arg(long, env = "HYPERSPACE_ADDR", default_value = "http://[::1]:50051")]
+    addr: String,
+
+    /// API key sent as `x-api-key`
+    #[arg(long, env = "HYPERSPACE_API_KEY")]
+    api_key: Option<String>,
+
+    /// Tenant to act as
+    #[arg(long, env = "HYPERSPACE_USER_ID")]
+    user_id: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Change events (CDC)
+    Events {
+        #[command(subcommand)]
+        action: EventsCommand,
+    },
+    /// Collection backups
+    Snapshot {
+        #[command(subcommand)]
+        action: snapshot::SnapshotCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum EventsCommand {
+    /// Print events as they happen
+    Tail(events::TailArgs),
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        let mut client =
+            hyperspace_sdk::Client::connect(cli.addr, cli.api_key, cli.user_id).await?;
+        return match command {
+            Command::Events {
+                action: EventsCommand::Tail(args),
+            } => events::tail(&mut client, args).await,
+            Command::Snapshot { action } => snapshot::run(&mut client, action).await,
+        };
+    }
+
     // 1. Setup Network
-    let mut client = DatabaseClient::connect("http://[::1]:50051").await?;
+    let mut client = DatabaseClient::connect(cli.addr).await?;
 
     // Start Monitor Stream
     let mut monitor_stream = client.monitor(MonitorRequest {}).await?.into_inner();
@@ -77,7 +134,10 @@ fn run_app<B: ratatui::backend::Backend>(
     let mut client_col = client.clone();
     tokio::spawn(async move {
         loop {
-            if let Ok(resp) = client_col.list_collections(Empty {}).await {
+            if let Ok(resp) = client_col
+                .list_collections(ListCollectionsRequest::default())
+                .await
+            {
                 let list = resp.into_inner().collections;
                 if tx_col.send(list).await.is_err() {
                     break;
@@ -111,7 +171,7 @@ fn run_app<B: ratatui::backend::Backend>(
                     KeyCode::Char('s') => {
                         let mut c = client.clone();
                         tokio::spawn(async move {
-                            let _ = c.trigger_snapshot(Empty {}).await;
+                            let _ = c.trigger_snapshot(SnapshotRequest::default()).await;
                         });
                         app.logs.push("Snapshot triggered...".to_string());
                     }
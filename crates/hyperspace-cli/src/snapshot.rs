@@ -0,0 +1,156 @@
+//! `snapshot create` / `snapshot restore`: collection backups over gRPC.
+//!
+//! `create` writes a server-side snapshot; with `--output` it also downloads
+//! the collection as a bundle through `ExportCollection`. `restore` uploads
+//! such a bundle through `ReceiveCollection`, creating the collection. The
+//! progress bars count bundle bytes moved.
+
+use clap::{Args, Subcommand};
+use hyperspace_proto::hyperspace::{CollectionBundleChunk, ExportFormat};
+use hyperspace_sdk::Client;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const CHUNK: usize = 1 << 20;
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotCommand {
+    /// Snapshot a collection (all of them when omitted) on the server
+    Create(CreateArgs),
+    /// Create a collection from a bundle written by `create --output`
+    Restore(RestoreArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CreateArgs {
+    collection: Option<String>,
+    /// Also download the collection as a bundle to this file
+    #[arg(long, short, requires = "collection")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    bundle: PathBuf,
+    /// Name of the new collection (defaults to the file name without extension)
+    #[arg(long)]
+    collection: Option<String>,
+}
+
+pub async fn run(client: &mut Client, command: SnapshotCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        SnapshotCommand::Create(args) => match (args.collection, args.output) {
+            (Some(collection), Some(output)) => download(client, collection, &output).await,
+            (collection, _) => {
+                println!("{}", client.trigger_snapshot(collection).await?);
+                Ok(())
+            }
+        },
+        SnapshotCommand::Restore(args) => {
+            let name = match args.collection {
+                Some(name) => name,
+                None => default_name(&args.bundle)?,
+            };
+            upload(client, name, &args.bundle).await
+        }
+    }
+}
+
+fn default_name(bundle: &Path) -> Result<String, Box<dyn Error>> {
+    bundle
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Cannot derive a collection name from {}", bundle.display()).into())
+}
+
+fn progress(total: u64) -> ProgressBar {
+    let bar = ProgressBar::new(total);
+    if let Ok(style) = ProgressStyle::with_template(
+        "{spinner} [{bar:40}] {bytes}/{total_bytes} {bytes_per_sec} {msg}",
+    ) {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
+
+async fn download(
+    client: &mut Client,
+    collection: String,
+    output: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let mut stream = client.export_collection(collection.clone()).await?;
+    let mut file = tokio::fs::File::create(output).await?;
+    let bar = progress(0);
+    bar.set_message("downloading");
+    while let Some(chunk) = stream.message().await? {
+        if chunk.total_bytes > 0 {
+            bar.set_length(chunk.total_bytes);
+        }
+        file.write_all(&chunk.data).await?;
+        bar.inc(chunk.data.len() as u64);
+    }
+    file.flush().await?;
+    bar.finish_with_message("done");
+    println!(
+        "Collection '{collection}' saved to {} ({} bytes)",
+        output.display(),
+        bar.position()
+    );
+    Ok(())
+}
+
+async fn upload(client: &mut Client, name: String, bundle: &Path) -> Result<(), Box<dyn Error>> {
+    let mut file = tokio::fs::File::open(bundle).await?;
+    let bar = progress(file.metadata().await?.len());
+    bar.set_message("uploading");
+
+    // The reader runs ahead of the upload by at most a few chunks.
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    let reader_bar = bar.clone();
+    let reader = tokio::spawn(async move {
+        let mut first = Some(name);
+        loop {
+            let mut data = vec![0u8; CHUNK];
+            let n = file.read(&mut data).await?;
+            if n == 0 {
+                return Ok::<_, std::io::Error>(());
+            }
+            data.truncate(n);
+            let chunk = CollectionBundleChunk {
+                collection: first.take().unwrap_or_default(),
+                data,
+                total_bytes: 0,
+                format: ExportFormat::ExportBundle as i32,
+            };
+            if tx.send(chunk).await.is_err() {
+                return Ok(());
+            }
+            reader_bar.inc(n as u64);
+        }
+    });
+
+    let chunks = tonic::codegen::tokio_stream::wrappers::ReceiverStream::new(rx);
+    let status = client.import_collection(chunks).await;
+    reader.await??;
+    bar.finish_with_message("done");
+    println!("{}", status?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_name_defaults_to_file_stem() {
+        assert_eq!(
+            default_name(Path::new("/backups/docs.tar")).unwrap(),
+            "docs"
+        );
+        assert!(default_name(Path::new("/")).is_err());
+    }
+}
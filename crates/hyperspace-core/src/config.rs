@@ -25,11 +25,43 @@ pub struct GlobalConfig {
     /// Whether to apply expensive Anisotropic Coordinate Descent refinement during quantization
     pub anisotropic_refinement: AtomicBool,
 
+    /// HNSW heuristic `extendCandidates`: also consider the candidates'
+    /// own neighbours when choosing links. Helps on clustered data.
+    pub extend_candidates: AtomicBool,
+
+    /// HNSW heuristic `keepPrunedConnections`: fill remaining link slots
+    /// with candidates the heuristic discarded, nearest first.
+    pub keep_pruned_connections: AtomicBool,
+
+    /// Number of cluster medoids kept as extra layer-0 entry points for
+    /// strongly clustered data; 0 disables them.
+    pub entry_points: AtomicUsize,
+
+    /// How many of the medoids closest to the query seed layer-0 search.
+    pub entry_probes: AtomicUsize,
+
+    /// Number of IVF cells searches are routed through; 0 searches the
+    /// whole graph.
+    pub ivf_cells: AtomicUsize,
+
+    /// How many of the cells closest to the query a search considers.
+    pub ivf_probes: AtomicUsize,
+
+    /// Filtered searches whose allowed set holds at most this many points
+    /// scan it exactly instead of walking the graph.
+    pub filter_bruteforce_threshold: AtomicU64,
+
     /// BM25 scoring parameters
     pub bm25_params: std::sync::RwLock<crate::bm25::Bm25Params>,
 
     /// Fusion method ("rrf" or "weighted")
     pub fusion_method: std::sync::RwLock<String>,
+
+    /// Metadata keys stored with a point but not indexed
+    pub unindexed_keys: std::sync::RwLock<std::collections::HashSet<String>>,
+
+    /// Synonym groups of lexical search
+    pub synonyms: std::sync::RwLock<crate::SynonymMap>,
 }
 
 impl GlobalConfig {
@@ -42,8 +74,17 @@ impl GlobalConfig {
             m: AtomicUsize::new(16),
             gossip_enabled: AtomicBool::new(false),
             anisotropic_refinement: AtomicBool::new(true), // Default to true for quality, but can be disabled for speed
+            extend_candidates: AtomicBool::new(false),
+            keep_pruned_connections: AtomicBool::new(false),
+            entry_points: AtomicUsize::new(0),
+            entry_probes: AtomicUsize::new(3),
+            ivf_cells: AtomicUsize::new(0),
+            ivf_probes: AtomicUsize::new(8),
+            filter_bruteforce_threshold: AtomicU64::new(50_000),
             bm25_params: std::sync::RwLock::new(crate::bm25::Bm25Params::default()),
             fusion_method: std::sync::RwLock::new("rrf".to_string()),
+            unindexed_keys: std::sync::RwLock::new(std::collections::HashSet::new()),
+            synonyms: std::sync::RwLock::new(crate::SynonymMap::default()),
         }
     }
 
@@ -63,6 +104,63 @@ impl GlobalConfig {
         self.anisotropic_refinement.store(val, Ordering::Relaxed);
     }
 
+    pub fn is_extend_candidates_enabled(&self) -> bool {
+        self.extend_candidates.load(Ordering::Relaxed)
+    }
+
+    pub fn set_extend_candidates_enabled(&self, val: bool) {
+        self.extend_candidates.store(val, Ordering::Relaxed);
+    }
+
+    pub fn is_keep_pruned_enabled(&self) -> bool {
+        self.keep_pruned_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn set_keep_pruned_enabled(&self, val: bool) {
+        self.keep_pruned_connections.store(val, Ordering::Relaxed);
+    }
+
+    pub fn get_entry_points(&self) -> usize {
+        self.entry_points.load(Ordering::Relaxed)
+    }
+
+    pub fn set_entry_points(&self, val: usize) {
+        self.entry_points.store(val, Ordering::Relaxed);
+    }
+
+    pub fn get_entry_probes(&self) -> usize {
+        self.entry_probes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_entry_probes(&self, val: usize) {
+        self.entry_probes.store(val, Ordering::Relaxed);
+    }
+
+    pub fn get_ivf_cells(&self) -> usize {
+        self.ivf_cells.load(Ordering::Relaxed)
+    }
+
+    pub fn set_ivf_cells(&self, val: usize) {
+        self.ivf_cells.store(val, Ordering::Relaxed);
+    }
+
+    pub fn get_ivf_probes(&self) -> usize {
+        self.ivf_probes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_ivf_probes(&self, val: usize) {
+        self.ivf_probes.store(val, Ordering::Relaxed);
+    }
+
+    pub fn get_filter_bruteforce_threshold(&self) -> u64 {
+        self.filter_bruteforce_threshold.load(Ordering::Relaxed)
+    }
+
+    pub fn set_filter_bruteforce_threshold(&self, val: u64) {
+        self.filter_bruteforce_threshold
+            .store(val, Ordering::Relaxed);
+    }
+
     pub fn get_ef_search(&self) -> usize {
         self.ef_search.load(Ordering::Relaxed)
     }
@@ -128,6 +226,26 @@ impl GlobalConfig {
     pub fn set_fusion_method(&self, method: String) {
         *self.fusion_method.write().unwrap() = method;
     }
+
+    #[allow(clippy::missing_panics_doc)]
+    pub fn get_unindexed_keys(&self) -> std::collections::HashSet<String> {
+        self.unindexed_keys.read().unwrap().clone()
+    }
+
+    #[allow(clippy::missing_panics_doc)]
+    pub fn set_unindexed_keys(&self, keys: std::collections::HashSet<String>) {
+        *self.unindexed_keys.write().unwrap() = keys;
+    }
+
+    #[allow(clippy::missing_panics_doc)]
+    pub fn get_synonyms(&self) -> crate::SynonymMap {
+        self.synonyms.read().unwrap().clone()
+    }
+
+    #[allow(clippy::missing_panics_doc)]
+    pub fn set_synonyms(&self, synonyms: crate::SynonymMap) {
+        *self.synonyms.write().unwrap() = synonyms;
+    }
 }
 
 impl Default for GlobalConfig {
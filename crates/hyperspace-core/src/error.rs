@@ -0,0 +1,58 @@
+use crate::VectorViolation;
+
+/// Unified error type for the storage, index and collection layers.
+///
+/// Variants are coarse on purpose: they describe *how* a caller should react
+/// (retry, fix input, free space, give up) rather than every failure site.
+#[derive(Debug, thiserror::Error)]
+pub enum HyperspaceError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Data corruption: {0}")]
+    Corruption(String),
+    #[error("Validation failed: {0}")]
+    Validation(String),
+    #[error("Capacity exceeded: {0}")]
+    Capacity(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The collection is in a state the operation does not support.
+    #[error("Precondition failed: {0}")]
+    Precondition(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+pub type HyperspaceResult<T> = Result<T, HyperspaceError>;
+
+impl HyperspaceError {
+    /// Whether retrying the same operation later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::Capacity(_))
+    }
+}
+
+impl From<String> for HyperspaceError {
+    fn from(msg: String) -> Self {
+        Self::Internal(msg)
+    }
+}
+
+impl From<&str> for HyperspaceError {
+    fn from(msg: &str) -> Self {
+        Self::Internal(msg.to_string())
+    }
+}
+
+impl From<VectorViolation> for HyperspaceError {
+    fn from(v: VectorViolation) -> Self {
+        Self::Validation(v.to_string())
+    }
+}
+
+// Lets `?` keep working in the many call sites that still report `String`.
+impl From<HyperspaceError> for String {
+    fn from(e: HyperspaceError) -> Self {
+        e.to_string()
+    }
+}
@@ -0,0 +1,311 @@
+//! Human-friendly filter strings, e.g. `genre = "jazz" AND year >= 1990`.
+//!
+//! Grammar (keywords are case-insensitive):
+//!
+//! ```text
+//! query   := clause ( AND clause )*
+//! clause  := key op value | key BETWEEN number AND number
+//! op      := = | == | > | >= | < | <=
+//! value   := "string" | 'string' | number | bareword
+//! ```
+//!
+//! Numbers compare as ranges (so `"1990"` and `"1990.0"` both match
+//! `year = 1990`); strings and barewords compare exactly. Clauses are always
+//! conjunctive, matching how `FilterExpr` lists are evaluated.
+
+use crate::{FilterExpr, HyperspaceError};
+
+/// Why a filter string was rejected, with the byte offset of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+impl From<FilterParseError> for HyperspaceError {
+    fn from(e: FilterParseError) -> Self {
+        Self::Validation(format!("Invalid filter: {e}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Op(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterParseError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        match c {
+            b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+            b'"' | b'\'' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    let Some(ch) = input[i..].chars().next() else {
+                        return Err(FilterParseError {
+                            position: start,
+                            message: "Unterminated string".into(),
+                        });
+                    };
+                    i += ch.len_utf8();
+                    if ch as u32 == u32::from(c) {
+                        break;
+                    }
+                    if ch == '\\' {
+                        if let Some(escaped) = input[i..].chars().next() {
+                            i += escaped.len_utf8();
+                            value.push(escaped);
+                            continue;
+                        }
+                    }
+                    value.push(ch);
+                }
+                tokens.push((start, Token::Str(value)));
+            }
+            b'=' | b'>' | b'<' | b'!' => {
+                let two = input.get(i..i + 2).unwrap_or("");
+                let op = match two {
+                    "==" => "=",
+                    ">=" => ">=",
+                    "<=" => "<=",
+                    "!=" => {
+                        return Err(FilterParseError {
+                            position: start,
+                            message: "'!=' is not supported".into(),
+                        })
+                    }
+                    _ => "",
+                };
+                if op.is_empty() {
+                    let op = match c {
+                        b'=' => "=",
+                        b'>' => ">",
+                        b'<' => "<",
+                        _ => {
+                            return Err(FilterParseError {
+                                position: start,
+                                message: "Unexpected '!'".into(),
+                            })
+                        }
+                    };
+                    tokens.push((start, Token::Op(op)));
+                    i += 1;
+                } else {
+                    tokens.push((start, Token::Op(op)));
+                    i += 2;
+                }
+            }
+            b'0'..=b'9' | b'-' | b'+' | b'.' => {
+                while i < bytes.len()
+                    && matches!(bytes[i], b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+                {
+                    i += 1;
+                }
+                let raw = &input[start..i];
+                let num = raw.parse::<f64>().map_err(|_| FilterParseError {
+                    position: start,
+                    message: format!("Invalid number '{raw}'"),
+                })?;
+                tokens.push((start, Token::Num(num)));
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'.' | b'-'))
+                {
+                    i += 1;
+                }
+                tokens.push((start, Token::Ident(input[start..i].to_string())));
+            }
+            _ => {
+                return Err(FilterParseError {
+                    position: start,
+                    message: format!(
+                        "Unexpected character '{}'",
+                        input[start..].chars().next().unwrap_or(' ')
+                    ),
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_keyword(tok: Option<&(usize, Token)>, kw: &str) -> bool {
+    matches!(tok, Some((_, Token::Ident(s))) if s.eq_ignore_ascii_case(kw))
+}
+
+/// Parses a filter string into a conjunction of [`FilterExpr`]s.
+///
+/// An empty or whitespace-only string yields no filters.
+pub fn parse_filter(input: &str) -> Result<Vec<FilterExpr>, FilterParseError> {
+    let tokens = tokenize(input)?;
+    let mut exprs = Vec::new();
+    let mut pos = 0;
+    let end = input.len();
+    let at = |p: usize| tokens.get(p).map_or(end, |(o, _)| *o);
+
+    while pos < tokens.len() {
+        if !exprs.is_empty() {
+            if is_keyword(tokens.get(pos), "or") || is_keyword(tokens.get(pos), "not") {
+                return Err(FilterParseError {
+                    position: at(pos),
+                    message: "Only AND is supported between clauses".into(),
+                });
+            }
+            if !is_keyword(tokens.get(pos), "and") {
+                return Err(FilterParseError {
+                    position: at(pos),
+                    message: "Expected AND".into(),
+                });
+            }
+            pos += 1;
+        }
+
+        let key = match tokens.get(pos) {
+            Some((_, Token::Ident(k) | Token::Str(k))) => k.clone(),
+            _ => {
+                return Err(FilterParseError {
+                    position: at(pos),
+                    message: "Expected a metadata key".into(),
+                })
+            }
+        };
+        pos += 1;
+
+        if is_keyword(tokens.get(pos), "between") {
+            let lo = expect_number(&tokens, pos + 1, end)?;
+            if !is_keyword(tokens.get(pos + 2), "and") {
+                return Err(FilterParseError {
+                    position: at(pos + 2),
+                    message: "Expected AND in BETWEEN".into(),
+                });
+            }
+            let hi = expect_number(&tokens, pos + 3, end)?;
+            exprs.push(FilterExpr::Range {
+                key,
+                gte: Some(lo),
+                lte: Some(hi),
+            });
+            pos += 4;
+            continue;
+        }
+
+        let Some((_, Token::Op(op))) = tokens.get(pos) else {
+            return Err(FilterParseError {
+                position: at(pos),
+                message: "Expected an operator (=, >, >=, <, <=, BETWEEN)".into(),
+            });
+        };
+        let op = *op;
+        pos += 1;
+
+        let expr = match (op, tokens.get(pos)) {
+            ("=", Some((_, Token::Str(v) | Token::Ident(v)))) => FilterExpr::Match {
+                key,
+                value: v.clone(),
+            },
+            ("=", Some((_, Token::Num(n)))) => FilterExpr::Range {
+                key,
+                gte: Some(*n),
+                lte: Some(*n),
+            },
+            (_, Some((_, Token::Num(n)))) => {
+                let (gte, lte) = match op {
+                    ">=" => (Some(*n), None),
+                    ">" => (Some(n.next_up()), None),
+                    "<=" => (None, Some(*n)),
+                    _ => (None, Some(n.next_down())),
+                };
+                FilterExpr::Range { key, gte, lte }
+            }
+            ("=", _) => {
+                return Err(FilterParseError {
+                    position: at(pos),
+                    message: "Expected a value".into(),
+                })
+            }
+            _ => {
+                return Err(FilterParseError {
+                    position: at(pos),
+                    message: format!("'{op}' requires a number"),
+                })
+            }
+        };
+        exprs.push(expr);
+        pos += 1;
+    }
+    Ok(exprs)
+}
+
+fn expect_number(
+    tokens: &[(usize, Token)],
+    pos: usize,
+    end: usize,
+) -> Result<f64, FilterParseError> {
+    match tokens.get(pos) {
+        Some((_, Token::Num(n))) => Ok(*n),
+        other => Err(FilterParseError {
+            position: other.map_or(end, |(o, _)| *o),
+            message: "Expected a number".into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(expr: &FilterExpr) -> (&str, Option<f64>, Option<f64>) {
+        match expr {
+            FilterExpr::Range { key, gte, lte } => (key.as_str(), *gte, *lte),
+            other => panic!("expected range, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_conjunction_of_match_and_ranges() {
+        let exprs = parse_filter(r#"genre = "jazz" AND year >= 1990 and rating < 4.5"#).unwrap();
+        assert_eq!(exprs.len(), 3);
+        assert!(
+            matches!(&exprs[0], FilterExpr::Match { key, value } if key == "genre" && value == "jazz")
+        );
+        assert_eq!(range(&exprs[1]), ("year", Some(1990.0), None));
+        let (key, gte, lte) = range(&exprs[2]);
+        assert_eq!((key, gte), ("rating", None));
+        assert!(lte.unwrap() < 4.5 && lte.unwrap() > 4.499_999);
+    }
+
+    #[test]
+    fn parses_between_equality_and_barewords() {
+        let exprs = parse_filter("year BETWEEN 1990 AND 1999 AND id == 7 AND live = true").unwrap();
+        assert_eq!(range(&exprs[0]), ("year", Some(1990.0), Some(1999.0)));
+        assert_eq!(range(&exprs[1]), ("id", Some(7.0), Some(7.0)));
+        assert!(matches!(&exprs[2], FilterExpr::Match { value, .. } if value == "true"));
+        assert!(parse_filter("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_position_of_errors() {
+        let err = parse_filter("a = 1 OR b = 2").unwrap_err();
+        assert_eq!(err.position, 6);
+        assert_eq!(parse_filter(r#"name = "open"#).unwrap_err().position, 7);
+        assert_eq!(parse_filter("year >= \"x\"").unwrap_err().position, 8);
+        assert_eq!(parse_filter("year").unwrap_err().position, 4);
+    }
+}
@@ -11,15 +11,27 @@
 #![allow(clippy::needless_range_loop)]
 
 pub mod config;
+pub mod error;
+pub mod filter_query;
 pub mod fuzzy;
 pub mod gpu;
 pub mod gromov;
+pub mod normalization;
 pub mod optim;
 pub mod region;
+pub mod schema;
+pub mod synonyms;
+pub mod tokens;
 pub mod vector;
 pub mod wasserstein;
 
 pub use config::GlobalConfig;
+pub use error::{HyperspaceError, HyperspaceResult};
+pub use filter_query::{parse_filter, FilterParseError};
+pub use normalization::NormalizationPolicy;
+pub use schema::{MetadataSchema, MetadataType, MetadataViolation, SchemaMode};
+pub use synonyms::SynonymMap;
+pub use tokens::TokenMatrix;
 pub mod bm25;
 pub use bm25::*;
 use vector::{BinaryHyperVector, HyperVector, QuantizedHyperVector};
@@ -52,6 +64,82 @@ pub enum StorageMode {
     Tiered,      // LSM Mode: Frequent WAL-to-Chunk flushes, minimizes RAM.
 }
 
+/// Structured reason why a vector was rejected at insert time.
+///
+/// `Metric::validate` only reports human-readable strings; this type carries the
+/// machine-readable details (offending component, norm, dimensions) needed by the
+/// API layer to return typed error codes to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorViolation {
+    DimensionMismatch { expected: usize, actual: usize },
+    NonFinite { index: usize },
+    OutOfBall { norm_sq: f64 },
+    OffHyperboloid { minkowski_norm: f64 },
+    NotNormalized { norm: f64 },
+}
+
+impl std::fmt::Display for VectorViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DimensionMismatch { expected, actual } => write!(
+                f,
+                "Vector dimension mismatch. Expected {expected}, got {actual}"
+            ),
+            Self::NonFinite { index } => {
+                write!(f, "Vector contains non-finite value (NaN/Inf) at index {index}")
+            }
+            Self::OutOfBall { norm_sq } => write!(
+                f,
+                "Vector must be strictly inside the Poincaré ball (|x|^2 = {norm_sq})"
+            ),
+            Self::OffHyperboloid { minkowski_norm } => write!(
+                f,
+                "Lorentz vector is not on unit hyperboloid: -t^2+|x|^2={minkowski_norm}, expected -1"
+            ),
+            Self::NotNormalized { norm } => {
+                write!(f, "Vector must be unit length (|x| = {norm})")
+            }
+        }
+    }
+}
+
+/// Validates a raw vector against a collection's dimension and metric,
+/// mirroring the checks of the corresponding `Metric::validate` implementation.
+/// Accepts both `f64` and wire-level `f32` components.
+pub fn check_vector<T: Copy + Into<f64>>(
+    vector: &[T],
+    dimension: usize,
+    metric: &str,
+) -> Result<(), VectorViolation> {
+    if vector.len() != dimension {
+        return Err(VectorViolation::DimensionMismatch {
+            expected: dimension,
+            actual: vector.len(),
+        });
+    }
+    if let Some(index) = vector.iter().position(|&v| !v.into().is_finite()) {
+        return Err(VectorViolation::NonFinite { index });
+    }
+    match metric {
+        "poincare" => {
+            let norm_sq: f64 = vector.iter().map(|&x| x.into() * x.into()).sum();
+            if norm_sq >= 1.0 - 1e-9 {
+                return Err(VectorViolation::OutOfBall { norm_sq });
+            }
+        }
+        "lorentz" => {
+            let t: f64 = vector.first().map_or(0.0, |&x| x.into());
+            let spatial_sq: f64 = vector.iter().skip(1).map(|&x| x.into() * x.into()).sum();
+            let minkowski_norm = -t * t + spatial_sq;
+            if t <= 0.0 || (minkowski_norm + 1.0).abs() > 1e-6 {
+                return Err(VectorViolation::OffHyperboloid { minkowski_norm });
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 /// Metric abstraction for distance calculation
 pub struct PoincareMetric;
 
@@ -83,6 +171,11 @@ pub enum FilterExpr {
         center: Vec<f64>,
         radius: f64,
     },
+    /// Documents with a text field holding `phrase` (after tokenization)
+    /// contiguously and in order.
+    PhraseMatch {
+        phrase: String,
+    },
 }
 
 impl FilterExpr {
@@ -130,6 +223,8 @@ impl FilterExpr {
                 let region = region::BallRegion::new(center.clone(), *radius);
                 region.contains(vector)
             }
+            // Needs the collection's tokenizer; applied by the metadata index.
+            Self::PhraseMatch { .. } => true,
         }
     }
 }
@@ -143,10 +238,168 @@ pub struct SearchParams {
     pub use_wasserstein: bool,
     pub bm25_options: Option<crate::bm25::Bm25Params>,
     pub fusion_method: Option<String>,
+    /// Which documents the hybrid text query admits before fusion.
+    pub text_match: TextMatch,
+    /// Collects work counters of the search when set.
+    pub trace: Option<std::sync::Arc<SearchTrace>>,
+    /// Independent layer-0 searches, each from its own layer-1 entry, whose
+    /// results are merged. 0 and 1 search once.
+    pub num_probes: usize,
+}
+
+/// How the tokens of a hybrid text query gate the candidate set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TextMatch {
+    /// Tokens only score documents; any document may be returned.
+    #[default]
+    Any,
+    /// Only documents containing every query token are eligible.
+    All,
+    /// Only documents containing the query tokens contiguously and in order.
+    Phrase,
+}
+
+/// Work done by one search, filled in by the collection.
+#[derive(Debug, Default)]
+pub struct SearchTrace {
+    visited: std::sync::atomic::AtomicU64,
+    stats: std::sync::Mutex<SearchStats>,
+}
+
+impl SearchTrace {
+    pub fn add_visited(&self, n: u64) {
+        self.visited
+            .fetch_add(n, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Nodes whose distance to the query was computed.
+    pub fn visited(&self) -> u64 {
+        self.visited.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub fn record(&self, update: impl FnOnce(&mut SearchStats)) {
+        update(
+            &mut self
+                .stats
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        *self
+            .stats
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Per-phase breakdown of a traced search. Searches of several query vectors
+/// add up their phases; `layers` is the deepest walk.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchStats {
+    /// HNSW layers walked, the base layer included.
+    pub layers: u32,
+    /// Building the filter bitmap.
+    pub filter_time: std::time::Duration,
+    /// Greedy descent through the upper layers.
+    pub routing_time: std::time::Duration,
+    /// Base-layer search, or the scan of the filtered set.
+    pub layer0_time: std::time::Duration,
+    /// Rescoring and reranking of the retrieved candidates.
+    pub rerank_time: std::time::Duration,
+    /// Indexed vectors passing the filter, `None` without a filter.
+    pub filter_matches: Option<u64>,
+    /// Live vectors in the index when the filter ran.
+    pub indexed: u64,
+    /// The filtered set was scanned instead of walking the graph.
+    pub filter_bruteforce: bool,
+    /// Nothing passed the filter, so the graph was never touched.
+    pub filter_short_circuit: bool,
+}
+
+impl SearchStats {
+    /// Share of indexed vectors passing the filter.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn filter_selectivity(&self) -> Option<f64> {
+        self.filter_matches.map(|m| {
+            if self.indexed == 0 {
+                0.0
+            } else {
+                m as f64 / self.indexed as f64
+            }
+        })
+    }
 }
 
 pub type SearchResult = (u32, f64, std::collections::HashMap<String, String>);
 
+/// One HNSW layer: each node with its out-links and their distances.
+pub type GraphLayer = Vec<(u32, Vec<(u32, f64)>)>;
+
+/// How far asynchronous indexing has caught up with accepted writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexingProgress {
+    /// Every acknowledged write with a logical clock up to this one is
+    /// searchable.
+    pub indexed_clock: u64,
+    /// Highest logical clock the collection has accepted.
+    pub written_clock: u64,
+    /// Points still waiting for the indexer.
+    pub queue_depth: u64,
+}
+
+/// Findings of an integrity scrub of a collection's in-memory segment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ScrubReport {
+    /// Unix seconds when the scrub finished.
+    pub finished_at: u64,
+    pub duration_ms: u64,
+    /// Slots compared with their write-time checksum.
+    pub checked: u64,
+    /// Slots written before checksums existed that got one now.
+    pub sealed: u64,
+    /// User IDs whose stored vector no longer matches its checksum, or whose
+    /// graph node points past the end of storage.
+    pub corrupt: Vec<u32>,
+    /// Whether the corrupt points were deleted.
+    pub repaired: bool,
+    pub storage_count: u64,
+    pub graph_nodes: u64,
+}
+
+/// Result of a hard delete ([`Collection::purge`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PurgeOutcome {
+    /// User IDs erased from the graph, metadata, storage and WAL.
+    pub purged: Vec<u32>,
+    /// Insert records dropped from closed WAL segments.
+    pub wal_records_removed: u64,
+    /// Entries dropped from the replication journal.
+    pub journal_entries_removed: u64,
+    /// Snapshot generations deleted, since they predate the purge.
+    pub history_generations_removed: u64,
+}
+
+/// Size of the metadata index under one key, to spot keys whose values
+/// explode it (raw UUIDs as tags).
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MetadataKeyStats {
+    pub key: String,
+    /// `tag` for `key:value` bitmaps, `numeric` for range buckets, `text`
+    /// for BM25 tokens (`key` is `_txt`).
+    pub kind: String,
+    /// Bitmaps under the key: distinct values, buckets or tokens.
+    pub bitmaps: u64,
+    /// Sum of the bitmap cardinalities.
+    pub postings: u64,
+    /// Largest bitmap; over the point count this is the least selective
+    /// filter on the key.
+    pub max_cardinality: u64,
+    /// Serialized size of the bitmaps.
+    pub bytes: u64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Durability {
     Default,
@@ -182,39 +435,105 @@ pub trait Collection: Send + Sync + 'static {
         metadata: std::collections::HashMap<String, String>,
         clock: u64,
         durability: Durability,
-    ) -> Result<(), String>;
+    ) -> HyperspaceResult<()>;
 
     async fn insert_batch(
         &self,
         vectors: Vec<(Vec<f64>, u32, std::collections::HashMap<String, String>)>,
         clock: u64,
         durability: Durability,
-    ) -> Result<(), String> {
+    ) -> HyperspaceResult<()> {
         // Default implementation using single insert (slow fallback)
         for (vec, id, meta) in vectors {
             self.insert(&vec, id, meta, clock, durability).await?;
         }
         Ok(())
     }
-    fn delete(&self, id: u32) -> Result<(), String>;
+    /// Deletes `deletes`, then inserts `vectors`, all under `clock`. A crash
+    /// leaves either the whole batch or none of it (e.g. when re-chunking a
+    /// document). The default applies the operations one by one.
+    async fn apply_batch(
+        &self,
+        deletes: Vec<u32>,
+        vectors: Vec<(Vec<f64>, u32, std::collections::HashMap<String, String>)>,
+        clock: u64,
+        durability: Durability,
+    ) -> HyperspaceResult<()> {
+        for id in deletes {
+            self.delete(id, clock).await?;
+        }
+        self.insert_batch(vectors, clock, durability).await
+    }
+    /// Inserts a vector received as `f32` (packed wire format).
+    /// The default widens to `f64`; implementations may avoid the allocation.
+    async fn insert_f32(
+        &self,
+        vector: &[f32],
+        id: u32,
+        metadata: std::collections::HashMap<String, String>,
+        clock: u64,
+        durability: Durability,
+    ) -> HyperspaceResult<()> {
+        let widened: Vec<f64> = vector.iter().map(|&x| f64::from(x)).collect();
+        self.insert(&widened, id, metadata, clock, durability).await
+    }
+    /// Deletes a point; `clock` orders the tombstone against WAL inserts.
+    async fn delete(&self, id: u32, clock: u64) -> HyperspaceResult<()>;
+    /// Overwrites the coordinates at `indices` with `values` and stores the
+    /// patched vector under the same id and metadata. Small patches stay on
+    /// the fast-upsert path and skip relinking.
+    async fn update_vector_delta(
+        &self,
+        id: u32,
+        indices: &[u32],
+        values: &[f64],
+        clock: u64,
+        durability: Durability,
+    ) -> HyperspaceResult<()>;
     async fn search(
         &self,
         vector: &[f64],
         filter: &std::collections::HashMap<String, String>,
         complex_filters: &[FilterExpr],
         params: &SearchParams,
-    ) -> Result<Vec<SearchResult>, String>;
+    ) -> HyperspaceResult<Vec<SearchResult>>;
+    /// Searches with an `f32` query (packed wire format).
+    async fn search_f32(
+        &self,
+        vector: &[f32],
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<Vec<SearchResult>> {
+        let widened: Vec<f64> = vector.iter().map(|&x| f64::from(x)).collect();
+        self.search(&widened, filter, complex_filters, params).await
+    }
     fn count(&self) -> usize;
     fn dimension(&self) -> usize;
     fn metric_name(&self) -> &'static str;
     fn state_hash(&self) -> u64;
     fn buckets(&self) -> Vec<u64>; // New method
     fn queue_size(&self) -> u64; // Indexing queue size for eventual consistency
-    async fn optimize(&self) -> Result<(), String> {
+    fn indexing_progress(&self) -> IndexingProgress;
+    async fn optimize(&self) -> HyperspaceResult<()> {
         // Default: No-op for collections lacking optimization support.
         Ok(())
     }
-    async fn optimize_with_filter(&self, filter: Option<VacuumFilterQuery>) -> Result<(), String> {
+    /// Persists the index and collection state right now.
+    async fn snapshot(&self) -> HyperspaceResult<()> {
+        // Default: No-op for collections without on-disk snapshots.
+        Ok(())
+    }
+    /// Changes how the collection's WAL syncs to disk; `Default` restores the
+    /// process-wide mode.
+    async fn set_wal_sync_mode(&self, _mode: Durability) -> HyperspaceResult<()> {
+        // Default: No-op for collections without a WAL.
+        Ok(())
+    }
+    async fn optimize_with_filter(
+        &self,
+        filter: Option<VacuumFilterQuery>,
+    ) -> HyperspaceResult<()> {
         let _ = filter;
         self.optimize().await
     }
@@ -235,27 +554,129 @@ pub trait Collection: Send + Sync + 'static {
             .filter(|(id, _, _)| set.contains(&((*id as usize % 256) as u32)))
             .collect()
     }
-    fn graph_neighbors(&self, id: u32, layer: usize, limit: usize) -> Result<Vec<u32>, String>;
+    fn graph_neighbors(&self, id: u32, layer: usize, limit: usize) -> HyperspaceResult<Vec<u32>>;
     fn graph_neighbor_distances(
         &self,
         source_id: u32,
         neighbor_ids: &[u32],
-    ) -> Result<Vec<f64>, String>;
+    ) -> HyperspaceResult<Vec<f64>>;
     fn graph_traverse(
         &self,
         start_id: u32,
         layer: usize,
         max_depth: usize,
         max_nodes: usize,
-    ) -> Result<Vec<u32>, String>;
+    ) -> HyperspaceResult<Vec<u32>>;
     fn graph_clusters(
         &self,
         layer: usize,
         min_cluster_size: usize,
         max_clusters: usize,
         max_nodes: usize,
-    ) -> Result<Vec<Vec<u32>>, String>;
+    ) -> HyperspaceResult<Vec<Vec<u32>>>;
+    /// Highest HNSW layer of the collection's graph.
+    fn graph_max_layer(&self) -> usize;
+    /// Every live node on `layer` with its out-links and their distances.
+    fn graph_layer(&self, layer: usize) -> HyperspaceResult<GraphLayer>;
     fn metadata_by_id(&self, id: u32) -> std::collections::HashMap<String, String>;
+    /// User IDs of the points whose metadata `key` is `value`, ascending.
+    fn ids_with_metadata(&self, key: &str, value: &str) -> Vec<u32> {
+        let _ = (key, value);
+        Vec::new()
+    }
+    /// Vector of user ID `id` as stored (normalized for cosine, after
+    /// quantization), while it is in the in-memory segment.
+    fn vector_by_id(&self, id: u32) -> Option<Vec<f64>>;
+    /// Whether a point with user ID `id` is stored.
+    fn contains(&self, id: u32) -> bool;
+    /// Drift score of the latest window of inserts against the baseline,
+    /// when drift monitoring is enabled.
+    fn drift_score(&self) -> Option<f64> {
+        None
+    }
+    /// Current `ef_search` cap when it adapts to load (`HS_ADAPTIVE_EF`).
+    fn effective_ef_search(&self) -> Option<usize> {
+        None
+    }
+    /// Replaces the token-level vectors of user ID `id` used for
+    /// late-interaction scoring; `None` removes them.
+    fn put_token_vectors(&self, id: u32, tokens: Option<TokenMatrix>) -> HyperspaceResult<()> {
+        let _ = (id, tokens);
+        Err(HyperspaceError::Validation(
+            "Token vectors are not supported by this collection".into(),
+        ))
+    }
+    /// Reorders search hits by late-interaction (`MaxSim`) distance against
+    /// `query` and keeps `top_k`.
+    fn rerank_late_interaction(
+        &self,
+        hits: Vec<SearchResult>,
+        query: &TokenMatrix,
+        top_k: usize,
+    ) -> Vec<SearchResult> {
+        let _ = query;
+        hits.into_iter().take(top_k).collect()
+    }
+    /// Verifies stored vectors against their write-time checksums and the
+    /// graph's node count against storage. With `repair`, corrupt points are
+    /// deleted so searches stop returning them.
+    async fn scrub(&self, repair: bool) -> HyperspaceResult<ScrubReport> {
+        let _ = repair;
+        Ok(ScrubReport::default())
+    }
+    /// Hard-deletes every point matching `filter` and `complex_filters`
+    /// (at least one must be set): see [`Collection::purge_ids`].
+    async fn purge(
+        &self,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        clock: u64,
+    ) -> HyperspaceResult<PurgeOutcome> {
+        let _ = (filter, complex_filters, clock);
+        Err(HyperspaceError::Validation(
+            "Purge is not supported by this collection".into(),
+        ))
+    }
+    /// Erases user IDs `ids` beyond recovery: unlinks them from the graph,
+    /// zeroes their storage, drops their inserts from the WAL and the
+    /// replication journal and takes a snapshot. IDs that are not stored are
+    /// skipped.
+    async fn purge_ids(&self, ids: &[u32], clock: u64) -> HyperspaceResult<PurgeOutcome> {
+        let _ = (ids, clock);
+        Err(HyperspaceError::Validation(
+            "Purge is not supported by this collection".into(),
+        ))
+    }
+    /// Metadata index size per key, largest first.
+    fn metadata_key_stats(&self) -> Vec<MetadataKeyStats> {
+        Vec::new()
+    }
+    /// Estimated distinct values per metadata key.
+    fn metadata_distinct_values(&self) -> std::collections::HashMap<String, u64> {
+        std::collections::HashMap::new()
+    }
+    /// Replaces the synonym groups of lexical search.
+    fn set_synonyms(&self, _synonyms: SynonymMap) {
+        // Default: No-op for collections without a text index.
+    }
+    /// Findings of the latest scrub since the collection was opened.
+    fn scrub_report(&self) -> Option<ScrubReport> {
+        None
+    }
+    /// Whether writes are refused after repeated storage failures.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+    /// Accepts writes again after a switch to read-only.
+    fn set_writable(&self) {}
+    /// Declared metadata types, checked by the API layer before a write.
+    fn metadata_schema(&self) -> Option<&MetadataSchema> {
+        None
+    }
+    /// How writes outside the metric's canonical form are handled.
+    fn normalization(&self) -> NormalizationPolicy {
+        NormalizationPolicy::default_for(self.metric_name())
+    }
     fn quantization_mode(&self) -> QuantizationMode;
 }
 
@@ -264,13 +685,13 @@ pub trait Metric<const N: usize>: Send + Sync + 'static {
     fn distance(a: &[f64; N], b: &[f64; N]) -> f64;
 
     // Default valid verification: ensure all dimensions are finite
-    fn validate(vector: &[f64; N]) -> Result<(), String> {
+    fn validate(vector: &[f64; N]) -> HyperspaceResult<()> {
         for &val in vector {
             if !val.is_finite() {
-                return Err(format!(
+                return Err(HyperspaceError::Validation(format!(
                     "Vector contains non-finite values (NaN/Inf) for metric: {}",
                     Self::name()
-                ));
+                )));
             }
         }
         Ok(())
@@ -296,15 +717,19 @@ impl<const N: usize> Metric<N> for PoincareMetric {
         arg.acosh()
     }
 
-    fn validate(vector: &[f64; N]) -> Result<(), String> {
+    fn validate(vector: &[f64; N]) -> HyperspaceResult<()> {
         for &val in vector {
             if !val.is_finite() {
-                return Err("Poincaré vector contains non-finite values (NaN/Inf)".to_string());
+                return Err(HyperspaceError::Validation(
+                    "Poincaré vector contains non-finite values (NaN/Inf)".to_string(),
+                ));
             }
         }
         let sq_norm: f64 = vector.iter().map(|&x| x * x).sum();
         if sq_norm >= 1.0 - 1e-9 {
-            return Err("Vector must be strictly inside the Poincaré ball".to_string());
+            return Err(HyperspaceError::Validation(
+                "Vector must be strictly inside the Poincaré ball".to_string(),
+            ));
         }
         Ok(())
     }
@@ -335,12 +760,16 @@ impl<const N: usize> Metric<N> for LorentzMetric {
         arg.acosh()
     }
 
-    fn validate(vector: &[f64; N]) -> Result<(), String> {
+    fn validate(vector: &[f64; N]) -> HyperspaceResult<()> {
         if !vector.iter().all(|v| v.is_finite()) {
-            return Err("Lorentz vector contains non-finite values".to_string());
+            return Err(HyperspaceError::Validation(
+                "Lorentz vector contains non-finite values".to_string(),
+            ));
         }
         if vector[0] <= 0.0 {
-            return Err("Lorentz vector must be on the upper sheet (t > 0)".to_string());
+            return Err(HyperspaceError::Validation(
+                "Lorentz vector must be on the upper sheet (t > 0)".to_string(),
+            ));
         }
 
         // Point must satisfy: -t^2 + x1^2 + ... + xn^2 = -1
@@ -350,9 +779,9 @@ impl<const N: usize> Metric<N> for LorentzMetric {
         }
         let minkowski_norm = -vector[0] * vector[0] + spatial_sq;
         if (minkowski_norm + 1.0).abs() > 1e-6 {
-            return Err(format!(
+            return Err(HyperspaceError::Validation(format!(
                 "Lorentz vector is not on unit hyperboloid: -t^2+|x|^2={minkowski_norm}, expected -1"
-            ));
+            )));
         }
         Ok(())
     }
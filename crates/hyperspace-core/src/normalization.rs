@@ -0,0 +1,175 @@
+//! What a collection does with vectors outside its metric's canonical form.
+//!
+//! Cosine collections want unit vectors and Poincaré collections want points
+//! strictly inside the unit ball. The policy is chosen at creation:
+//! `normalize` scales every vector to unit length (the cosine default),
+//! `reject` fails the write instead (the default elsewhere), and `project`
+//! pulls Poincaré points on or past the boundary back to norm
+//! `1 - epsilon`. The API layer checks writes with
+//! [`NormalizationPolicy::check`] and the collection rewrites them with
+//! [`NormalizationPolicy::apply`] before indexing.
+
+use crate::{check_vector, VectorViolation};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Margin kept from the ball boundary by `project` when none is given.
+pub const DEFAULT_PROJECTION_EPSILON: f64 = 1e-5;
+
+/// Unit norm tolerance of `reject` on cosine collections.
+const UNIT_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum NormalizationPolicy {
+    /// Scale every non-zero vector to unit length.
+    Normalize,
+    /// Store vectors as given; fail writes that aren't in canonical form.
+    Reject,
+    /// Scale points at or past the Poincaré boundary to norm `1 - epsilon`.
+    Project {
+        #[serde(default = "default_epsilon")]
+        epsilon: f64,
+    },
+}
+
+fn default_epsilon() -> f64 {
+    DEFAULT_PROJECTION_EPSILON
+}
+
+impl NormalizationPolicy {
+    /// The policy of a collection created without one.
+    pub fn default_for(metric: &str) -> Self {
+        if metric == "cosine" {
+            Self::Normalize
+        } else {
+            Self::Reject
+        }
+    }
+
+    /// Checks that the policy makes sense for `metric`: `normalize` can't
+    /// produce Poincaré or Lorentz points and `project` is Poincaré only.
+    pub fn validate_for(self, metric: &str) -> Result<(), String> {
+        match self {
+            Self::Normalize if matches!(metric, "poincare" | "lorentz") => Err(format!(
+                "normalization 'normalize' is not valid for {metric} collections"
+            )),
+            Self::Project { .. } if metric != "poincare" => Err(format!(
+                "normalization 'project' is only valid for poincare collections, not {metric}"
+            )),
+            Self::Project { epsilon } if !(epsilon > 0.0 && epsilon < 1.0) => Err(format!(
+                "projection epsilon must be in (0, 1), got {epsilon}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// [`check_vector`] as seen through the policy: `project` admits points
+    /// outside the ball, and `reject` on a cosine collection refuses vectors
+    /// that aren't unit length.
+    pub fn check<T: Copy + Into<f64>>(
+        self,
+        vector: &[T],
+        dimension: usize,
+        metric: &str,
+    ) -> Result<(), VectorViolation> {
+        match (self, check_vector(vector, dimension, metric)) {
+            (Self::Project { .. }, Err(VectorViolation::OutOfBall { .. })) => Ok(()),
+            (Self::Reject, Ok(())) if metric == "cosine" => {
+                let norm = vector
+                    .iter()
+                    .map(|&x| x.into() * x.into())
+                    .sum::<f64>()
+                    .sqrt();
+                if (norm - 1.0).abs() > UNIT_TOLERANCE {
+                    return Err(VectorViolation::NotNormalized { norm });
+                }
+                Ok(())
+            }
+            (_, result) => result,
+        }
+    }
+
+    /// The vector to store; borrowed when already in canonical form.
+    pub fn apply(self, vector: &[f64]) -> Cow<'_, [f64]> {
+        let norm_sq: f64 = vector.iter().map(|x| x * x).sum();
+        let target = match self {
+            Self::Reject => return Cow::Borrowed(vector),
+            // Zero vectors have no direction to keep.
+            Self::Normalize if (norm_sq - 1.0).abs() < 1e-9 || norm_sq <= 1e-18 => {
+                return Cow::Borrowed(vector)
+            }
+            Self::Normalize => 1.0,
+            Self::Project { .. } if norm_sq < 1.0 - 1e-9 => return Cow::Borrowed(vector),
+            Self::Project { epsilon } => 1.0 - epsilon,
+        };
+        let scale = target / norm_sq.sqrt();
+        Cow::Owned(vector.iter().map(|x| x * scale).collect())
+    }
+
+    /// `normalize`, `reject` or `project`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Normalize => "normalize",
+            Self::Reject => "reject",
+            Self::Project { .. } => "project",
+        }
+    }
+
+    /// Boundary margin of `project`; `0` for the other policies.
+    pub fn epsilon(self) -> f64 {
+        match self {
+            Self::Project { epsilon } => epsilon,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_check_and_rewrite() {
+        let project = NormalizationPolicy::Project { epsilon: 0.01 };
+        assert!(project.check(&[0.8, 0.8], 2, "poincare").is_ok());
+        let inside = project.apply(&[0.8, 0.8]);
+        let norm = inside.iter().map(|x| x * x).sum::<f64>().sqrt();
+        assert!((norm - 0.99).abs() < 1e-12);
+        assert!(matches!(project.apply(&[0.1, 0.2]), Cow::Borrowed(_)));
+
+        let reject = NormalizationPolicy::Reject;
+        assert!(matches!(
+            reject.check(&[0.8, 0.8], 2, "poincare"),
+            Err(VectorViolation::OutOfBall { .. })
+        ));
+        assert!(matches!(
+            reject.check(&[3.0, 4.0], 2, "cosine"),
+            Err(VectorViolation::NotNormalized { norm }) if (norm - 5.0).abs() < 1e-12
+        ));
+        assert!(reject.check(&[0.6, 0.8], 2, "cosine").is_ok());
+
+        let normalize = NormalizationPolicy::Normalize;
+        assert!(normalize.check(&[3.0, 4.0], 2, "cosine").is_ok());
+        assert_eq!(normalize.apply(&[0.0, 2.0]).as_ref(), &[0.0, 1.0]);
+        assert!(matches!(normalize.apply(&[0.0, 0.0]), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn validate_rejects_mismatched_metrics() {
+        assert!(NormalizationPolicy::Normalize
+            .validate_for("poincare")
+            .is_err());
+        assert!(NormalizationPolicy::Normalize.validate_for("l2").is_ok());
+        let project = NormalizationPolicy::Project { epsilon: 1e-5 };
+        assert!(project.validate_for("cosine").is_err());
+        assert!(project.validate_for("poincare").is_ok());
+        assert!(NormalizationPolicy::Project { epsilon: 1.0 }
+            .validate_for("poincare")
+            .is_err());
+        assert_eq!(
+            NormalizationPolicy::default_for("cosine"),
+            NormalizationPolicy::Normalize
+        );
+    }
+}
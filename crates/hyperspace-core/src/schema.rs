@@ -0,0 +1,175 @@
+//! Declared metadata types of a collection, checked on insert.
+//!
+//! A schema maps metadata keys to the type their values must have. Keys it
+//! doesn't mention are free-form. In `strict` mode a write with a mistyped
+//! value (`year: "banana"`) is rejected; in `lenient` mode the offending keys
+//! are dropped and the rest of the write goes through. Either way the value
+//! never reaches the numeric index.
+//!
+//! Keys listed in `unindexed` are stored with the point and returned with it,
+//! but get no tag, numeric or BM25 token entries. Free-text blobs then cost
+//! neither index memory nor `_txt:` tokens, and filters on them match
+//! nothing.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl MetadataType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+        }
+    }
+
+    /// Whether `value`, as stored in metadata, has this type. Integers are
+    /// valid floats.
+    pub fn accepts(self, value: &str) -> bool {
+        match self {
+            Self::String => true,
+            Self::Int => value.parse::<i64>().is_ok(),
+            Self::Float => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Self::Bool => matches!(value, "true" | "false"),
+        }
+    }
+}
+
+/// What happens to a write whose metadata violates the schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaMode {
+    /// Reject the write.
+    #[default]
+    Strict,
+    /// Drop the mistyped keys and keep the rest.
+    Lenient,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataSchema {
+    #[serde(default)]
+    pub fields: BTreeMap<String, MetadataType>,
+    #[serde(default)]
+    pub mode: SchemaMode,
+    /// Keys stored but not indexed.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub unindexed: BTreeSet<String>,
+}
+
+/// A metadata value that doesn't have its declared type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataViolation {
+    pub key: String,
+    pub expected: MetadataType,
+    pub value: String,
+}
+
+impl std::fmt::Display for MetadataViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Metadata '{}' must be {}, got {:?}",
+            self.key,
+            self.expected.as_str(),
+            self.value
+        )
+    }
+}
+
+impl MetadataSchema {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.unindexed.is_empty()
+    }
+
+    /// Checks `metadata` (plain keys plus their typed shadows) against the
+    /// schema. Strict mode returns the first violation; lenient mode removes
+    /// the mistyped keys and returns what it removed.
+    pub fn check(
+        &self,
+        metadata: &mut HashMap<String, String>,
+    ) -> Result<Vec<MetadataViolation>, MetadataViolation> {
+        let mut dropped = Vec::new();
+        for (key, &expected) in &self.fields {
+            let Some(value) = metadata.get(key) else {
+                continue;
+            };
+            if expected.accepts(value) {
+                continue;
+            }
+            let violation = MetadataViolation {
+                key: key.clone(),
+                expected,
+                value: value.clone(),
+            };
+            if self.mode == SchemaMode::Strict {
+                return Err(violation);
+            }
+            metadata.remove(key);
+            metadata.remove(&format!("__hs_typed__{key}"));
+            dropped.push(violation);
+        }
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(mode: SchemaMode) -> MetadataSchema {
+        MetadataSchema {
+            fields: BTreeMap::from([
+                ("year".to_string(), MetadataType::Int),
+                ("score".to_string(), MetadataType::Float),
+                ("draft".to_string(), MetadataType::Bool),
+            ]),
+            mode,
+            unindexed: BTreeSet::new(),
+        }
+    }
+
+    fn meta(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn strict_rejects_mistyped_values() {
+        let schema = schema(SchemaMode::Strict);
+        let mut ok = meta(&[("year", "1999"), ("score", "3"), ("draft", "false")]);
+        assert_eq!(schema.check(&mut ok), Ok(Vec::new()));
+
+        let mut bad = meta(&[("year", "banana"), ("title", "x")]);
+        let err = schema.check(&mut bad).unwrap_err();
+        assert_eq!(err.key, "year");
+        assert_eq!(err.expected, MetadataType::Int);
+        assert_eq!(bad.len(), 2);
+    }
+
+    #[test]
+    fn lenient_drops_mistyped_keys() {
+        let schema = schema(SchemaMode::Lenient);
+        let mut m = meta(&[
+            ("year", "banana"),
+            ("__hs_typed__year", r#"{"t":"s","v":"banana"}"#),
+            ("score", "NaN"),
+            ("title", "x"),
+        ]);
+        let dropped = schema.check(&mut m).unwrap();
+        assert_eq!(dropped.len(), 2);
+        assert_eq!(m, meta(&[("title", "x")]));
+    }
+}
@@ -0,0 +1,56 @@
+//! Synonym groups of a collection's lexical search.
+//!
+//! Each group lists words or phrases that mean the same thing. A query token
+//! found in a group also searches for the other entries, so `car` finds
+//! documents that only say `automobile`. Entries are run through the
+//! collection's analyzer, so `cars` expands like `car`. A multi-word entry is
+//! reached from the other entries of its group but doesn't expand itself.
+//!
+//! Expansion happens at query time. With `expand_at_index` a document's
+//! tokens are expanded when it is indexed as well, so `all` and `phrase`
+//! text matches and `_txt:` tags see the synonyms too; changing the map
+//! then re-derives the text postings of every document.
+
+use crate::{HyperspaceError, HyperspaceResult};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SynonymMap {
+    #[serde(default)]
+    pub groups: Vec<Vec<String>>,
+    #[serde(default)]
+    pub expand_at_index: bool,
+}
+
+impl SynonymMap {
+    /// Groups one collection may define.
+    pub const MAX_GROUPS: usize = 100_000;
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Every group needs at least two non-blank entries.
+    pub fn validate(&self) -> HyperspaceResult<()> {
+        if self.groups.len() > Self::MAX_GROUPS {
+            return Err(HyperspaceError::Validation(format!(
+                "At most {} synonym groups are allowed, got {}",
+                Self::MAX_GROUPS,
+                self.groups.len()
+            )));
+        }
+        for (i, group) in self.groups.iter().enumerate() {
+            if group
+                .iter()
+                .filter(|entry| !entry.trim().is_empty())
+                .count()
+                < 2
+            {
+                return Err(HyperspaceError::Validation(format!(
+                    "Synonym group {i} needs at least two entries"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
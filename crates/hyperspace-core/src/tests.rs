@@ -285,3 +285,60 @@ fn test_lorentz_binary_still_panics() {
     let b = BinaryHyperVector::from_float(&v);
     let _ = LorentzMetric::distance_binary(&b, &v);
 }
+
+#[test]
+fn test_check_vector_violations() {
+    assert_eq!(
+        check_vector(&[0.1, 0.2, 0.3], 2, "l2"),
+        Err(VectorViolation::DimensionMismatch {
+            expected: 2,
+            actual: 3
+        })
+    );
+    assert_eq!(
+        check_vector(&[0.1, f64::NAN], 2, "cosine"),
+        Err(VectorViolation::NonFinite { index: 1 })
+    );
+    assert!(matches!(
+        check_vector(&[0.8, 0.8], 2, "poincare"),
+        Err(VectorViolation::OutOfBall { .. })
+    ));
+    assert!(matches!(
+        check_vector(&[1.0, 1.0], 2, "lorentz"),
+        Err(VectorViolation::OffHyperboloid { .. })
+    ));
+    assert!(check_vector(&[0.1, 0.2], 2, "poincare").is_ok());
+    assert!(check_vector(&[1.0, 0.0], 2, "lorentz").is_ok());
+}
+
+#[test]
+fn test_hyperspace_error_classification() {
+    let io: HyperspaceError = std::io::Error::other("disk").into();
+    assert!(io.is_retryable());
+
+    let err = PoincareMetric::validate(&[0.9, 0.9]).unwrap_err();
+    assert!(matches!(err, HyperspaceError::Validation(_)));
+    assert!(!err.is_retryable());
+
+    let msg: String = HyperspaceError::NotFound("id 7".into()).into();
+    assert_eq!(msg, "Not found: id 7");
+}
+
+#[test]
+fn test_binary_hamming_distance_word_kernel() {
+    use crate::vector::{BinaryHyperVector, HyperVector};
+    // 100 dims = 12 full bytes + 4-bit tail, exercising both the u64 and byte loops
+    let a = HyperVector::<100> {
+        coords: std::array::from_fn(|i| if i % 3 == 0 { 0.5 } else { -0.5 }),
+        alpha: 0.0,
+    };
+    let b = HyperVector::<100> {
+        coords: std::array::from_fn(|i| if i % 2 == 0 { 0.5 } else { -0.5 }),
+        alpha: 0.0,
+    };
+    let ba = BinaryHyperVector::from_float(&a);
+    let bb = BinaryHyperVector::from_float(&b);
+    let expected = (0..100).filter(|i| (i % 3 == 0) != (i % 2 == 0)).count() as u32;
+    assert_eq!(ba.hamming_distance(&bb), expected);
+    assert_eq!(ba.hamming_distance(&ba), 0);
+}
@@ -0,0 +1,85 @@
+//! Token-level vectors for late-interaction (ColBERT-style) scoring.
+
+use crate::{HyperspaceError, HyperspaceResult};
+
+/// Token vectors of one document or query, row-major.
+#[derive(Debug)]
+pub struct TokenMatrix {
+    dimension: usize,
+    values: Box<[f32]>,
+}
+
+impl TokenMatrix {
+    pub fn new(dimension: usize, values: Vec<f32>) -> HyperspaceResult<Self> {
+        if dimension == 0 || !values.len().is_multiple_of(dimension) {
+            return Err(HyperspaceError::Validation(format!(
+                "Token vectors: {} values do not split into rows of dimension {dimension}",
+                values.len()
+            )));
+        }
+        if let Some(i) = values.iter().position(|v| !v.is_finite()) {
+            return Err(HyperspaceError::Validation(format!(
+                "Token vectors contain a non-finite value at {i}"
+            )));
+        }
+        Ok(Self {
+            dimension,
+            values: values.into_boxed_slice(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.values.len() / self.dimension
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    fn token_rows(&self) -> std::slice::ChunksExact<'_, f32> {
+        self.values.chunks_exact(self.dimension)
+    }
+
+    /// `Σ (1 - max dot)` over the query rows; `None` when the dimensions
+    /// differ.
+    pub fn maxsim_distance(&self, query: &Self) -> Option<f64> {
+        if self.dimension != query.dimension {
+            return None;
+        }
+        let distance = query
+            .token_rows()
+            .map(|q| {
+                let best = self
+                    .token_rows()
+                    .map(|d| q.iter().zip(d).map(|(a, b)| f64::from(a * b)).sum::<f64>())
+                    .fold(f64::NEG_INFINITY, f64::max);
+                1.0 - best
+            })
+            .sum();
+        Some(distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_ragged_and_non_finite_values() {
+        assert!(TokenMatrix::new(2, vec![1.0, 0.0, 0.5]).is_err());
+        assert!(TokenMatrix::new(0, Vec::new()).is_err());
+        assert!(TokenMatrix::new(2, vec![1.0, f32::NAN]).is_err());
+
+        let doc = TokenMatrix::new(2, vec![1.0, 0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(doc.rows(), 2);
+        let query = TokenMatrix::new(2, vec![0.6, 0.8]).unwrap();
+        let distance = doc.maxsim_distance(&query).unwrap();
+        assert!((distance - 0.2).abs() < 1e-6);
+        let other = TokenMatrix::new(3, vec![1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(doc.maxsim_distance(&other), None);
+    }
+}
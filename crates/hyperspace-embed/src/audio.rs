@@ -0,0 +1,401 @@
+//! Audio decoding and CLAP preprocessing for [`crate::AudioVectorizer`].
+//!
+//! [`decode`] reads WAV files (integer PCM of 8 to 32 bits or IEEE float,
+//! plain or `WAVE_FORMAT_EXTENSIBLE`) and mixes them down to mono samples
+//! in `[-1, 1]`. [`clap_waveform`] resamples a clip to CLAP's rate and fits
+//! it to the model's window, and [`clap_features`] computes the log-mel
+//! spectrogram CLAP's feature extractor feeds the audio tower.
+
+use anyhow::{anyhow, bail, ensure, Result};
+use std::f64::consts::PI;
+
+/// Longest clip accepted, in samples per channel (about an hour at 48 kHz).
+const MAX_SAMPLES: usize = 1 << 28;
+
+/// CLAP's feature extractor settings (`laion/clap-htsat-*`).
+pub const CLAP_SAMPLE_RATE: u32 = 48_000;
+/// Ten seconds: longer clips keep their start, shorter ones are repeated.
+pub const CLAP_SAMPLES: usize = 480_000;
+const N_FFT: usize = 1024;
+const HOP: usize = 480;
+/// Mel bands per frame.
+pub const CLAP_MEL_BINS: usize = 64;
+const F_MIN: f64 = 50.0;
+const F_MAX: f64 = 14_000.0;
+const MEL_FLOOR: f64 = 1e-10;
+
+/// A mono clip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+/// Decodes a WAV file.
+///
+/// # Errors
+/// Returns an error for other formats, unsupported sample encodings and
+/// corrupt data.
+pub fn decode(bytes: &[u8]) -> Result<Audio> {
+    ensure!(
+        bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE",
+        "unsupported audio format, expected WAV"
+    );
+    let u16_at = |data: &[u8], at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let u32_at = |data: &[u8], at: usize| {
+        u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+    };
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let kind = &bytes[pos..pos + 4];
+        let len = u32_at(bytes, pos + 4) as usize;
+        let start = pos + 8;
+        // Streaming writers leave the data length unset; read to the end
+        let data = &bytes[start..start.saturating_add(len).min(bytes.len())];
+        pos = start.saturating_add(len).saturating_add(len & 1);
+        match kind {
+            b"fmt " => {
+                ensure!(data.len() >= 16, "bad WAV format chunk");
+                let mut tag = u16_at(data, 0);
+                if tag == 0xfffe {
+                    // WAVE_FORMAT_EXTENSIBLE: the real tag opens the sub-format GUID
+                    ensure!(data.len() >= 26, "bad WAV format chunk");
+                    tag = u16_at(data, 24);
+                }
+                format = Some((tag, u16_at(data, 2), u32_at(data, 4), u16_at(data, 14)));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) =
+                    format.ok_or_else(|| anyhow!("WAV data before its format chunk"))?;
+                return Ok(Audio {
+                    sample_rate,
+                    samples: mix_down(data, tag, usize::from(channels), bits)?,
+                });
+            }
+            _ => {}
+        }
+    }
+    bail!("WAV file has no data chunk")
+}
+
+/// Averages the channels of interleaved `data` into mono samples.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn mix_down(data: &[u8], tag: u16, channels: usize, bits: u16) -> Result<Vec<f32>> {
+    ensure!(channels > 0, "WAV file has no channels");
+    let width = usize::from(bits.div_ceil(8));
+    let sample: fn(&[u8]) -> f64 = match (tag, bits) {
+        (1, 8) => |b| (f64::from(b[0]) - 128.0) / 128.0,
+        (1, 16) => |b| f64::from(i16::from_le_bytes([b[0], b[1]])) / 32_768.0,
+        // Sign-extend from the top byte of a 32-bit word
+        (1, 24) => |b| f64::from(i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) / 8_388_608.0,
+        (1, 32) => |b| f64::from(i32::from_le_bytes([b[0], b[1], b[2], b[3]])) / 2_147_483_648.0,
+        (3, 32) => |b| f64::from(f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        (3, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+        _ => bail!("unsupported WAV encoding (format {tag}, {bits} bits)"),
+    };
+    let frame = width * channels;
+    let frames = data.len() / frame;
+    ensure!(frames > 0, "WAV file has no samples");
+    ensure!(
+        frames <= MAX_SAMPLES,
+        "audio is too long ({frames} samples)"
+    );
+
+    let scale = 1.0 / channels as f64;
+    Ok(data
+        .chunks_exact(frame)
+        .map(|samples| {
+            let sum: f64 = samples.chunks_exact(width).map(sample).sum();
+            (sum * scale).clamp(-1.0, 1.0) as f32
+        })
+        .collect())
+}
+
+/// `audio` at `sample_rate`, linearly interpolated.
+#[must_use]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn resample(audio: &Audio, sample_rate: u32) -> Vec<f32> {
+    if audio.sample_rate == sample_rate || audio.samples.is_empty() {
+        return audio.samples.clone();
+    }
+    let step = f64::from(audio.sample_rate) / f64::from(sample_rate);
+    let len = ((audio.samples.len() as f64 / step).round() as usize).max(1);
+    let last = audio.samples.len() - 1;
+    (0..len)
+        .map(|i| {
+            let at = i as f64 * step;
+            let left = (at.floor() as usize).min(last);
+            let right = (left + 1).min(last);
+            let t = (at - left as f64) as f32;
+            audio.samples[left] * (1.0 - t) + audio.samples[right] * t
+        })
+        .collect()
+}
+
+/// `audio` resampled to CLAP's rate and fitted to its ten second window:
+/// longer clips are cut to their start, shorter ones repeated whole as many
+/// times as fit, then padded with silence.
+#[must_use]
+pub fn clap_waveform(audio: &Audio) -> Vec<f32> {
+    let samples = resample(audio, CLAP_SAMPLE_RATE);
+    if samples.len() >= CLAP_SAMPLES {
+        return samples[..CLAP_SAMPLES].to_vec();
+    }
+    let mut out = samples.repeat(CLAP_SAMPLES / samples.len());
+    out.resize(CLAP_SAMPLES, 0.0);
+    out
+}
+
+/// Log-mel spectrogram of `waveform` in decibels, `frames x CLAP_MEL_BINS`
+/// row-major: centered Hann-windowed frames, power spectrum, Slaney mel
+/// filters between 50 Hz and 14 kHz.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+pub fn clap_features(waveform: &[f32]) -> Vec<f32> {
+    let filters = mel_filters();
+    let window: Vec<f64> = (0..N_FFT)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / N_FFT as f64).cos())
+        .collect();
+    let fft = Fft::new(N_FFT);
+    let frames = 1 + waveform.len() / HOP;
+    let half = N_FFT / 2;
+
+    let mut out = Vec::with_capacity(frames * CLAP_MEL_BINS);
+    let mut re = vec![0f64; N_FFT];
+    let mut im = vec![0f64; N_FFT];
+    let mut power = vec![0f64; half + 1];
+    for frame in 0..frames {
+        for (n, (re, im)) in re.iter_mut().zip(&mut im).enumerate() {
+            let at = reflect(frame * HOP + n, half, waveform.len());
+            *re = f64::from(waveform[at]) * window[n];
+            *im = 0.0;
+        }
+        fft.run(&mut re, &mut im);
+        for (k, power) in power.iter_mut().enumerate() {
+            *power = re[k] * re[k] + im[k] * im[k];
+        }
+        for filter in &filters {
+            let energy: f64 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+            out.push((10.0 * energy.max(MEL_FLOOR).log10()) as f32);
+        }
+    }
+    out
+}
+
+/// Index into a signal of `len` samples reflect-padded by `pad` on both
+/// sides (the edge sample is not repeated).
+fn reflect(padded: usize, pad: usize, len: usize) -> usize {
+    if len == 1 {
+        return 0;
+    }
+    let period = 2 * (len - 1);
+    // Shift by a whole number of periods so the index never goes negative
+    let shift = pad.div_ceil(period) * period;
+    let at = (padded + shift - pad) % period;
+    if at < len {
+        at
+    } else {
+        period - at
+    }
+}
+
+/// Hertz to mel on the Slaney scale: linear below 1 kHz, logarithmic above.
+fn hz_to_mel(hz: f64) -> f64 {
+    const LOG_STEP: f64 = 0.068_751_777_420_949_12; // ln(6.4) / 27
+    if hz >= 1000.0 {
+        15.0 + (hz / 1000.0).ln() / LOG_STEP
+    } else {
+        hz * 3.0 / 200.0
+    }
+}
+
+fn mel_to_hz(mel: f64) -> f64 {
+    const LOG_STEP: f64 = 0.068_751_777_420_949_12;
+    if mel >= 15.0 {
+        1000.0 * (LOG_STEP * (mel - 15.0)).exp()
+    } else {
+        mel * 200.0 / 3.0
+    }
+}
+
+/// Triangular filters over the `N_FFT / 2 + 1` frequency bins, each scaled
+/// to unit area (Slaney normalization).
+#[allow(clippy::cast_precision_loss)]
+fn mel_filters() -> Vec<Vec<f64>> {
+    let (low, high) = (hz_to_mel(F_MIN), hz_to_mel(F_MAX));
+    let edges: Vec<f64> = (0..CLAP_MEL_BINS + 2)
+        .map(|i| mel_to_hz(low + (high - low) * i as f64 / (CLAP_MEL_BINS + 1) as f64))
+        .collect();
+    let bins = N_FFT / 2 + 1;
+    let nyquist = f64::from(CLAP_SAMPLE_RATE) / 2.0;
+    (0..CLAP_MEL_BINS)
+        .map(|m| {
+            let (left, center, right) = (edges[m], edges[m + 1], edges[m + 2]);
+            let norm = 2.0 / (right - left);
+            (0..bins)
+                .map(|k| {
+                    let hz = nyquist * k as f64 / (bins - 1) as f64;
+                    let rising = (hz - left) / (center - left);
+                    let falling = (right - hz) / (right - center);
+                    rising.min(falling).max(0.0) * norm
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// In-place radix-2 complex FFT of a fixed power-of-two size.
+struct Fft {
+    twiddles: Vec<(f64, f64)>,
+}
+
+impl Fft {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(size: usize) -> Self {
+        debug_assert!(size.is_power_of_two());
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f64 / size as f64;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+        Self { twiddles }
+    }
+
+    fn run(&self, re: &mut [f64], im: &mut [f64]) {
+        let size = re.len();
+        let bits = size.trailing_zeros();
+        for i in 0..size {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= size {
+            let stride = size / len;
+            for start in (0..size).step_by(len) {
+                for k in 0..len / 2 {
+                    let (wr, wi) = self.twiddles[k * stride];
+                    let (a, b) = (start + k, start + k + len / 2);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(tag: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = b"RIFF\0\0\0\0WAVE".to_vec();
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        let block = channels * bits / 8;
+        out.extend_from_slice(&(rate * u32::from(block)).to_le_bytes());
+        out.extend_from_slice(&block.to_le_bytes());
+        out.extend_from_slice(&bits.to_le_bytes());
+        // An unknown, odd-sized chunk is skipped with its pad byte
+        out.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn decodes_wav_encodings() {
+        // Stereo 16-bit: channels are averaged
+        let data: Vec<u8> = [16_384i16, 0, -32_768, -32_768]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let audio = decode(&wav(1, 2, 8000, 16, &data)).unwrap();
+        assert_eq!(audio.sample_rate, 8000);
+        assert_eq!(audio.samples, [0.25, -1.0]);
+
+        let audio = decode(&wav(1, 1, 8000, 8, &[128, 192, 0])).unwrap();
+        assert_eq!(audio.samples, [0.0, 0.5, -1.0]);
+
+        let audio = decode(&wav(1, 1, 8000, 24, &[0, 0, 0xc0, 0, 0, 0x40])).unwrap();
+        assert_eq!(audio.samples, [-0.5, 0.5]);
+
+        let audio = decode(&wav(3, 1, 8000, 32, &0.75f32.to_le_bytes())).unwrap();
+        assert_eq!(audio.samples, [0.75]);
+
+        assert!(decode(&wav(2, 1, 8000, 4, &[0; 4])).is_err());
+        assert!(decode(b"fLaC\0\0\0\x22").is_err());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn fits_clap_window() {
+        let clip = Audio {
+            sample_rate: 24_000,
+            samples: vec![0.0, 1.0, 0.0],
+        };
+        assert_eq!(resample(&clip, 48_000), [0.0, 0.5, 1.0, 0.5, 0.0, 0.0]);
+
+        let short = Audio {
+            sample_rate: CLAP_SAMPLE_RATE,
+            samples: vec![1.0; 200_000],
+        };
+        let waveform = clap_waveform(&short);
+        assert_eq!(waveform.len(), CLAP_SAMPLES);
+        // Two whole repeats, then silence
+        assert_eq!(waveform[399_999], 1.0);
+        assert_eq!(waveform[400_000], 0.0);
+    }
+
+    #[test]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::float_cmp
+    )]
+    fn computes_log_mel_of_a_tone() {
+        // A 1 kHz tone lands in the mel band around 1 kHz
+        let tone: Vec<f32> = (0..CLAP_SAMPLES)
+            .map(|n| (2.0 * PI * 1000.0 * n as f64 / f64::from(CLAP_SAMPLE_RATE)).sin() as f32)
+            .collect();
+        let features = clap_features(&tone);
+        assert_eq!(features.len(), 1001 * CLAP_MEL_BINS);
+
+        let frame = &features[500 * CLAP_MEL_BINS..501 * CLAP_MEL_BINS];
+        let loudest = (0..CLAP_MEL_BINS)
+            .max_by(|&a, &b| frame[a].total_cmp(&frame[b]))
+            .unwrap();
+        let band = |m: usize| {
+            let (low, high) = (hz_to_mel(F_MIN), hz_to_mel(F_MAX));
+            mel_to_hz(low + (high - low) * m as f64 / (CLAP_MEL_BINS + 1) as f64)
+        };
+        assert!(band(loudest) < 1000.0 && 1000.0 < band(loudest + 2));
+
+        assert_eq!(clap_features(&vec![0.0; HOP])[0], -100.0);
+    }
+
+    #[test]
+    fn reflects_padding() {
+        // [a b c d] padded by 2: c b | a b c d | c b
+        let indices: Vec<usize> = (0..8).map(|i| reflect(i, 2, 4)).collect();
+        assert_eq!(indices, [2, 1, 0, 1, 2, 3, 2, 1]);
+    }
+}
@@ -0,0 +1,1792 @@
+//! Text extraction from PDF, HTML and Markdown documents.
+//!
+//! [`extract`] turns a raw document into cleaned plain text split into
+//! sections: one per page for PDF, one per heading for HTML and Markdown.
+//! Sections keep their heading and page, and [`Document::chunks`] carries
+//! them into the metadata of the chunks that get embedded.
+//!
+//! The PDF reader covers what text-bearing PDFs use in practice: the page
+//! tree, Flate-compressed and object streams, form `XObjects`, and fonts mapped
+//! through `ToUnicode` `CMaps` or single-byte encodings. Scanned pages (images
+//! only) yield no text, and encrypted files are refused.
+
+use crate::ChunkingConfig;
+use anyhow::Result;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Html,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    /// From a file name, extension or MIME type (`report.pdf`, `md`,
+    /// `text/html; charset=utf-8`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let name = name.split(';').next().unwrap_or_default().trim();
+        match name.rsplit(['.', '/']).next().unwrap_or(name) {
+            "pdf" => Some(Self::Pdf),
+            "html" | "htm" | "xhtml" | "xhtml+xml" => Some(Self::Html),
+            "md" | "markdown" | "x-markdown" => Some(Self::Markdown),
+            "txt" | "text" | "plain" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first bytes; anything not recognizably
+    /// PDF or HTML is taken as text.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"%PDF-") {
+            return Self::Pdf;
+        }
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_lowercase();
+        let head = head.trim_start_matches('\u{feff}').trim_start();
+        if head.starts_with("<!doctype html") || head.starts_with("<html") || head.contains("<body")
+        {
+            Self::Html
+        } else {
+            Self::Text
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Section {
+    pub heading: Option<String>,
+    /// Heading level, 1-6; 0 for text before the first heading and for pages.
+    pub level: u8,
+    /// 1-based page number (PDF).
+    pub page: Option<u32>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    pub title: Option<String>,
+    pub sections: Vec<Section>,
+}
+
+/// A piece of a document ready to embed, with the metadata to store next to
+/// it: `title`, `section`, `page` when known, and `chunk`, its position in
+/// the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentChunk {
+    pub text: String,
+    pub metadata: HashMap<String, String>,
+}
+
+impl Document {
+    /// The whole document as text, headings included.
+    #[must_use]
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            for part in [section.heading.as_deref(), Some(section.text.as_str())]
+                .into_iter()
+                .flatten()
+                .filter(|p| !p.is_empty())
+            {
+                if !out.is_empty() {
+                    out.push_str("\n\n");
+                }
+                out.push_str(part);
+            }
+        }
+        out
+    }
+
+    /// Splits each section with `config`, or keeps one chunk per section
+    /// without it. Sections without text are dropped.
+    #[must_use]
+    pub fn chunks(&self, config: Option<&ChunkingConfig>) -> Vec<DocumentChunk> {
+        let mut chunks = Vec::new();
+        for section in &self.sections {
+            if section.text.is_empty() {
+                continue;
+            }
+            let texts = match config {
+                Some(config) => config.split(&section.text),
+                None => vec![section.text.clone()],
+            };
+            for text in texts {
+                let mut metadata = HashMap::new();
+                if let Some(title) = &self.title {
+                    metadata.insert("title".to_string(), title.clone());
+                }
+                if let Some(heading) = &section.heading {
+                    metadata.insert("section".to_string(), heading.clone());
+                }
+                if let Some(page) = section.page {
+                    metadata.insert("page".to_string(), page.to_string());
+                }
+                metadata.insert("chunk".to_string(), chunks.len().to_string());
+                chunks.push(DocumentChunk { text, metadata });
+            }
+        }
+        chunks
+    }
+}
+
+/// Extracts the text of `bytes`.
+///
+/// # Errors
+/// Returns an error for a PDF that is encrypted or has no page tree.
+pub fn extract(bytes: &[u8], format: DocumentFormat) -> Result<Document> {
+    match format {
+        DocumentFormat::Pdf => pdf::extract(bytes),
+        DocumentFormat::Html => Ok(html(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Markdown => Ok(markdown(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Text => Ok(Document {
+            title: None,
+            sections: vec![Section {
+                text: tidy(&String::from_utf8_lossy(bytes)),
+                ..Section::default()
+            }],
+        }),
+    }
+}
+
+/// Trims lines, collapses runs of spaces and of blank lines.
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = false;
+    for line in text.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank { "\n\n" } else { "\n" });
+        }
+        blank = false;
+        out.push_str(&words.join(" "));
+    }
+    out
+}
+
+/// Collects sections as headings arrive.
+#[derive(Default)]
+struct Sections {
+    doc: Document,
+    current: Section,
+    text: String,
+}
+
+impl Sections {
+    fn heading(&mut self, level: u8, heading: &str) {
+        let heading = tidy(heading).replace('\n', " ");
+        if heading.is_empty() {
+            return;
+        }
+        self.flush();
+        if level == 1 && self.doc.title.is_none() {
+            self.doc.title = Some(heading.clone());
+        }
+        self.current = Section {
+            heading: Some(heading),
+            level,
+            ..Section::default()
+        };
+    }
+
+    fn flush(&mut self) {
+        let text = tidy(&std::mem::take(&mut self.text));
+        let section = std::mem::take(&mut self.current);
+        if !text.is_empty() || section.heading.is_some() {
+            self.doc.sections.push(Section { text, ..section });
+        }
+    }
+
+    fn finish(mut self) -> Document {
+        self.flush();
+        self.doc
+    }
+}
+
+// --- Markdown ---
+
+fn markdown(src: &str) -> Document {
+    let mut out = Sections::default();
+    let mut lines = src.lines().peekable();
+    // YAML front matter
+    if lines.peek().is_some_and(|l| l.trim_end() == "---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if matches!(line.trim_end(), "---" | "...") {
+                break;
+            }
+        }
+    }
+    let mut fence: Option<&str> = None;
+    while let Some(line) = lines.next() {
+        let t = line.trim();
+        if let Some(marker) = fence {
+            if t.starts_with(marker) {
+                fence = None;
+            } else {
+                out.text.push_str(line);
+                out.text.push('\n');
+            }
+            continue;
+        }
+        if t.starts_with("```") || t.starts_with("~~~") {
+            fence = Some(&t[..3]);
+            out.text.push('\n');
+            continue;
+        }
+        if let Some((level, heading)) = atx_heading(t) {
+            out.heading(level, &inline_markdown(heading));
+            continue;
+        }
+        let underline = lines.peek().map(|l| l.trim()).and_then(|u| {
+            if !u.is_empty() && u.chars().all(|c| c == '=') {
+                Some(1)
+            } else if u.len() >= 2 && u.chars().all(|c| c == '-') {
+                Some(2)
+            } else {
+                None
+            }
+        });
+        if let (Some(level), false) = (underline, t.is_empty() || list_item(t).is_some()) {
+            lines.next();
+            out.heading(level, &inline_markdown(t));
+            continue;
+        }
+        if is_rule(t) || is_link_definition(t) || is_table_separator(t) {
+            continue;
+        }
+        let mut body = t;
+        while let Some(rest) = body.strip_prefix('>') {
+            body = rest.trim_start();
+        }
+        let body = list_item(body).unwrap_or(body);
+        let body = if body.starts_with('|') {
+            body.replace('|', " ")
+        } else {
+            body.to_string()
+        };
+        out.text.push_str(&inline_markdown(&body));
+        out.text.push('\n');
+    }
+    out.finish()
+}
+
+fn atx_heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let heading = rest.trim().trim_end_matches('#').trim_end();
+    Some((u8::try_from(level).ok()?, heading))
+}
+
+/// The text after a list marker (`- `, `* `, `+ `, `1. `, `1) `, with an
+/// optional task box).
+fn list_item(line: &str) -> Option<&str> {
+    let rest = if let Some(rest) = ["- ", "* ", "+ "].iter().find_map(|m| line.strip_prefix(m)) {
+        rest
+    } else {
+        let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 || digits > 9 {
+            return None;
+        }
+        line[digits..]
+            .strip_prefix(". ")
+            .or_else(|| line[digits..].strip_prefix(") "))?
+    };
+    let rest = rest.trim_start();
+    Some(
+        ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .find_map(|m| rest.strip_prefix(m))
+            .unwrap_or(rest),
+    )
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&m| compact.chars().all(|c| c == m))
+}
+
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[') && line.contains("]:") && !line.starts_with("[^")
+}
+
+fn is_table_separator(line: &str) -> bool {
+    line.contains('-')
+        && line.contains('|')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+/// Drops inline markup: links and images keep their text, emphasis and code
+/// markers and HTML tags go.
+fn inline_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '!' if chars.get(i + 1) == Some(&'[') => i += 1,
+            '[' => {
+                // [text](url) or [text][ref]: keep the text
+                if let Some(close) = chars[i + 1..].iter().position(|&c| c == ']') {
+                    let end = i + 1 + close;
+                    let label: String = chars[i + 1..end].iter().collect();
+                    let after = match chars.get(end + 1) {
+                        Some('(') => chars[end + 1..]
+                            .iter()
+                            .position(|&c| c == ')')
+                            .map(|p| end + 1 + p),
+                        Some('[') => chars[end + 1..]
+                            .iter()
+                            .position(|&c| c == ']')
+                            .map(|p| end + 1 + p),
+                        _ => Some(end),
+                    };
+                    if let Some(after) = after {
+                        out.push_str(&inline_markdown(&label));
+                        i = after + 1;
+                        continue;
+                    }
+                }
+                out.push(c);
+                i += 1;
+            }
+            '<' => {
+                // Autolinks keep their target, HTML tags are dropped
+                if let Some(close) = chars[i + 1..].iter().position(|&c| c == '>') {
+                    let inner: String = chars[i + 1..i + 1 + close].iter().collect();
+                    if inner.contains("://") || inner.contains('@') {
+                        out.push_str(&inner);
+                    }
+                    i += close + 2;
+                    continue;
+                }
+                out.push(c);
+                i += 1;
+            }
+            '`' | '~' if c == '`' || chars.get(i + 1) == Some(&'~') => {
+                i += chars[i..].iter().take_while(|&&x| x == c).count();
+            }
+            '*' | '_' => {
+                let run = chars[i..].iter().take_while(|&&x| x == c).count();
+                let before = i.checked_sub(1).map(|p| chars[p]);
+                let after = chars.get(i + run).copied();
+                // snake_case and 2*3 keep their characters
+                if c == '_'
+                    && before.is_some_and(char::is_alphanumeric)
+                    && after.is_some_and(char::is_alphanumeric)
+                    || before.is_some_and(char::is_whitespace)
+                        && after.is_some_and(char::is_whitespace)
+                {
+                    out.extend(std::iter::repeat_n(c, run));
+                }
+                i += run;
+            }
+            '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// --- HTML ---
+
+/// Elements whose content is not text.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "math", "iframe", "object", "canvas",
+];
+
+/// Elements that start on a new line.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+fn html(src: &str) -> Document {
+    let mut out = Sections::default();
+    let mut title: Option<String> = None;
+    let mut in_title = false;
+    let mut heading: Option<(u8, String)> = None;
+    let mut skip_until: Option<String> = None;
+    let mut rest = src;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        if rest.starts_with('<') {
+            let end = rest.find('>').unwrap_or(rest.len() - 1);
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            let closing = tag.starts_with('/');
+            let name: String = tag
+                .trim_start_matches('/')
+                .chars()
+                .take_while(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase();
+            if let Some(skipped) = &skip_until {
+                if closing && *skipped == name {
+                    skip_until = None;
+                }
+                continue;
+            }
+            if SKIPPED_ELEMENTS.contains(&name.as_str()) && !closing && !tag.ends_with('/') {
+                skip_until = Some(name);
+                continue;
+            }
+            let level = match name.as_bytes() {
+                [b'h', d @ b'1'..=b'6'] => Some(d - b'0'),
+                _ => None,
+            };
+            match (name.as_str(), level) {
+                ("title", _) => {
+                    in_title = !closing;
+                    if closing {
+                        title = title.map(|t| tidy(&t)).filter(|t| !t.is_empty());
+                    }
+                }
+                (_, Some(level)) if !closing => heading = Some((level, String::new())),
+                (_, Some(_)) => {
+                    if let Some((level, text)) = heading.take() {
+                        out.heading(level, &text);
+                    }
+                }
+                (block, None)
+                    if BLOCK_ELEMENTS.contains(&block)
+                        && !out.text.trim_end_matches(' ').ends_with('\n') =>
+                {
+                    out.text.push('\n');
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let end = rest.find('<').unwrap_or(rest.len());
+        if skip_until.is_none() {
+            let text = decode_entities(&rest[..end]);
+            let target = if in_title {
+                title.get_or_insert_with(String::new)
+            } else if let Some((_, heading)) = &mut heading {
+                heading
+            } else {
+                &mut out.text
+            };
+            target.push_str(&text.replace(['\n', '\r', '\t'], " "));
+        }
+        rest = &rest[end..];
+    }
+    if let Some((level, text)) = heading {
+        out.heading(level, &text);
+    }
+    let mut doc = out.finish();
+    if title.is_some() {
+        doc.title = title;
+    }
+    doc
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((entity(&rest[1..=end])?, end + 2)));
+        if let Some((c, len)) = decoded {
+            out.push(c);
+            rest = &rest[len..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "euro" => '€',
+        _ => return None,
+    })
+}
+
+// --- PDF ---
+
+mod pdf {
+    use super::{tidy, Document, Section};
+    use anyhow::{bail, Result};
+    use std::collections::{HashMap, HashSet};
+    use std::io::Read;
+    use std::ops::Range;
+
+    type Dict = HashMap<String, Obj>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Obj {
+        Null,
+        Bool(bool),
+        Num(f64),
+        Str(Vec<u8>),
+        Name(String),
+        Array(Vec<Obj>),
+        Dict(Dict),
+        Ref(u32),
+        /// A keyword: a content stream operator, or `stream`, `R`, ...
+        Op(String),
+        /// Dictionary and the raw bytes of a stream in the file.
+        Stream(Dict, Range<usize>),
+    }
+
+    impl Obj {
+        fn as_name(&self) -> Option<&str> {
+            match self {
+                Self::Name(n) => Some(n),
+                _ => None,
+            }
+        }
+
+        fn as_num(&self) -> Option<f64> {
+            match self {
+                Self::Num(n) => Some(*n),
+                _ => None,
+            }
+        }
+
+        fn dict(&self) -> Option<&Dict> {
+            match self {
+                Self::Dict(d) | Self::Stream(d, _) => Some(d),
+                _ => None,
+            }
+        }
+    }
+
+    /// A count or offset read as a number; negatives and NaN become 0.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn to_index(n: f64) -> usize {
+        n as usize
+    }
+
+    fn is_whitespace(b: u8) -> bool {
+        matches!(b, 0 | b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+    }
+
+    fn is_delimiter(b: u8) -> bool {
+        matches!(
+            b,
+            b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+        )
+    }
+
+    struct Lexer<'a> {
+        data: &'a [u8],
+        pos: usize,
+        /// Whether `n g R` is an indirect reference (files, not content).
+        refs: bool,
+    }
+
+    impl<'a> Lexer<'a> {
+        fn new(data: &'a [u8], refs: bool) -> Self {
+            Self { data, pos: 0, refs }
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.data.get(self.pos).copied()
+        }
+
+        fn skip_whitespace(&mut self) {
+            while let Some(b) = self.peek() {
+                if is_whitespace(b) {
+                    self.pos += 1;
+                } else if b == b'%' {
+                    while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                        self.pos += 1;
+                    }
+                } else {
+                    break;
+                }
+            }
+        }
+
+        fn regular(&mut self) -> &'a [u8] {
+            let start = self.pos;
+            while self
+                .peek()
+                .is_some_and(|b| !is_whitespace(b) && !is_delimiter(b))
+            {
+                self.pos += 1;
+            }
+            &self.data[start..self.pos]
+        }
+
+        fn next_obj(&mut self) -> Option<Obj> {
+            self.next_obj_at(0)
+        }
+
+        fn next_obj_at(&mut self, depth: usize) -> Option<Obj> {
+            self.skip_whitespace();
+            let b = self.peek()?;
+            if depth > 64 {
+                self.pos = self.data.len();
+                return None;
+            }
+            Some(match b {
+                b'/' => {
+                    self.pos += 1;
+                    Obj::Name(self.name())
+                }
+                b'(' => {
+                    self.pos += 1;
+                    Obj::Str(self.literal_string())
+                }
+                b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                    self.pos += 2;
+                    let mut dict = Dict::new();
+                    loop {
+                        self.skip_whitespace();
+                        match self.peek() {
+                            None => break,
+                            Some(b'>') => {
+                                self.pos += 2;
+                                break;
+                            }
+                            _ => {}
+                        }
+                        match self.next_obj_at(depth + 1) {
+                            Some(Obj::Name(key)) => {
+                                let value = self.next_obj_at(depth + 1).unwrap_or(Obj::Null);
+                                dict.insert(key, value);
+                            }
+                            Some(_) => {}
+                            None => break,
+                        }
+                    }
+                    Obj::Dict(dict)
+                }
+                b'<' => {
+                    self.pos += 1;
+                    Obj::Str(self.hex_string())
+                }
+                b'[' => {
+                    self.pos += 1;
+                    let mut items = Vec::new();
+                    loop {
+                        self.skip_whitespace();
+                        match self.peek() {
+                            None => break,
+                            Some(b']') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => {}
+                        }
+                        match self.next_obj_at(depth + 1) {
+                            Some(item) => items.push(item),
+                            None => break,
+                        }
+                    }
+                    Obj::Array(items)
+                }
+                b'0'..=b'9' | b'+' | b'-' | b'.' => self.number(),
+                b if is_delimiter(b) => {
+                    // Stray `)`, `>`, `]`, `{` or `}`
+                    self.pos += 1;
+                    Obj::Op(char::from(b).to_string())
+                }
+                _ => match self.regular() {
+                    b"true" => Obj::Bool(true),
+                    b"false" => Obj::Bool(false),
+                    b"null" => Obj::Null,
+                    word => Obj::Op(String::from_utf8_lossy(word).into_owned()),
+                },
+            })
+        }
+
+        fn name(&mut self) -> String {
+            let raw = self.regular();
+            let mut name = Vec::with_capacity(raw.len());
+            let mut i = 0;
+            while i < raw.len() {
+                if raw[i] == b'#' && i + 2 < raw.len() + 1 {
+                    if let Some(b) = std::str::from_utf8(raw.get(i + 1..i + 3).unwrap_or_default())
+                        .ok()
+                        .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    {
+                        name.push(b);
+                        i += 3;
+                        continue;
+                    }
+                }
+                name.push(raw[i]);
+                i += 1;
+            }
+            String::from_utf8_lossy(&name).into_owned()
+        }
+
+        fn number(&mut self) -> Obj {
+            let word = self.regular();
+            let value = std::str::from_utf8(word)
+                .ok()
+                .and_then(|w| w.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            if self.refs && !word.is_empty() && word.iter().all(u8::is_ascii_digit) {
+                // `num gen R`
+                let save = self.pos;
+                self.skip_whitespace();
+                let generation = self.regular();
+                if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+                    self.skip_whitespace();
+                    if self.regular() == b"R" {
+                        return Obj::Ref(u32::try_from(to_index(value)).unwrap_or(u32::MAX));
+                    }
+                }
+                self.pos = save;
+            }
+            if word.is_empty() {
+                // A lone sign or dot
+                self.pos += 1;
+            }
+            Obj::Num(value)
+        }
+
+        fn literal_string(&mut self) -> Vec<u8> {
+            let mut out = Vec::new();
+            let mut depth = 1;
+            while let Some(b) = self.peek() {
+                self.pos += 1;
+                match b {
+                    b'(' => {
+                        depth += 1;
+                        out.push(b);
+                    }
+                    b')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                        out.push(b);
+                    }
+                    b'\\' => {
+                        let Some(e) = self.peek() else { break };
+                        self.pos += 1;
+                        match e {
+                            b'n' => out.push(b'\n'),
+                            b'r' => out.push(b'\r'),
+                            b't' => out.push(b'\t'),
+                            b'b' => out.push(0x08),
+                            b'f' => out.push(0x0c),
+                            b'0'..=b'7' => {
+                                let mut code = u32::from(e - b'0');
+                                for _ in 0..2 {
+                                    match self.peek() {
+                                        Some(d @ b'0'..=b'7') => {
+                                            code = code * 8 + u32::from(d - b'0');
+                                            self.pos += 1;
+                                        }
+                                        _ => break,
+                                    }
+                                }
+                                out.push((code & 0xff) as u8);
+                            }
+                            b'\r' => {
+                                if self.peek() == Some(b'\n') {
+                                    self.pos += 1;
+                                }
+                            }
+                            b'\n' => {}
+                            other => out.push(other),
+                        }
+                    }
+                    _ => out.push(b),
+                }
+            }
+            out
+        }
+
+        fn hex_string(&mut self) -> Vec<u8> {
+            let mut digits = Vec::new();
+            while let Some(b) = self.peek() {
+                self.pos += 1;
+                if b == b'>' {
+                    break;
+                }
+                if let Some(d) = char::from(b).to_digit(16) {
+                    digits.push(u8::try_from(d).unwrap_or_default());
+                }
+            }
+            if digits.len() % 2 == 1 {
+                digits.push(0);
+            }
+            digits.chunks(2).map(|p| p[0] << 4 | p[1]).collect()
+        }
+
+        /// Skips inline image data up to its `EI`.
+        fn skip_inline_image(&mut self) {
+            // The dictionary ends at `ID`, followed by one whitespace byte
+            while let Some(obj) = self.next_obj() {
+                if obj == Obj::Op("ID".into()) {
+                    break;
+                }
+            }
+            while self.pos + 2 < self.data.len() {
+                if is_whitespace(self.data[self.pos])
+                    && &self.data[self.pos + 1..self.pos + 3] == b"EI"
+                    && self
+                        .data
+                        .get(self.pos + 3)
+                        .is_none_or(|&b| is_whitespace(b) || is_delimiter(b))
+                {
+                    self.pos += 3;
+                    return;
+                }
+                self.pos += 1;
+            }
+            self.pos = self.data.len();
+        }
+    }
+
+    fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+        haystack
+            .get(from..)?
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .map(|p| p + from)
+    }
+
+    fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).rposition(|w| w == needle)
+    }
+
+    /// Text of a PDF text string: UTF-16BE with a byte order mark, else
+    /// treated as Latin-1.
+    fn text_string(bytes: &[u8]) -> String {
+        if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+            let units: Vec<u16> = utf16
+                .as_chunks::<2>()
+                .0
+                .iter()
+                .map(|&p| u16::from_be_bytes(p))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            bytes.iter().map(|&b| win_ansi(b)).collect()
+        }
+    }
+
+    /// `WinAnsiEncoding`: Latin-1 with typographic characters in 0x80-0x9f.
+    fn win_ansi(b: u8) -> char {
+        const HIGH: [char; 32] = [
+            '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+            '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ',
+            '\u{9d}', 'ž', 'Ÿ',
+        ];
+        match b {
+            0x80..=0x9f => HIGH[usize::from(b - 0x80)],
+            _ => char::from(b),
+        }
+    }
+
+    /// Character codes to Unicode, from a `ToUnicode` `CMap`.
+    #[derive(Debug, Default)]
+    struct CMap {
+        /// Bytes per character code.
+        width: usize,
+        map: HashMap<u32, String>,
+    }
+
+    fn code(bytes: &[u8]) -> u32 {
+        bytes
+            .iter()
+            .take(4)
+            .fold(0, |acc, &b| acc << 8 | u32::from(b))
+    }
+
+    fn utf16(bytes: &[u8]) -> String {
+        let units: Vec<u16> = bytes
+            .chunks(2)
+            .map(|p| u16::from_be_bytes([p[0], p.get(1).copied().unwrap_or(0)]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+
+    impl CMap {
+        fn parse(data: &[u8]) -> Self {
+            let mut cmap = Self::default();
+            let mut lexer = Lexer::new(data, false);
+            let mut operands = Vec::new();
+            while let Some(obj) = lexer.next_obj() {
+                let Obj::Op(op) = &obj else {
+                    operands.push(obj);
+                    continue;
+                };
+                match op.as_str() {
+                    "endcodespacerange" => {
+                        if let Some(Obj::Str(lo)) = operands.first() {
+                            cmap.width = cmap.width.max(lo.len());
+                        }
+                    }
+                    "endbfchar" => {
+                        for [src, dst] in operands.as_chunks::<2>().0 {
+                            if let (Obj::Str(src), Obj::Str(dst)) = (src, dst) {
+                                cmap.width = cmap.width.max(src.len());
+                                cmap.map.insert(code(src), utf16(dst));
+                            }
+                        }
+                    }
+                    "endbfrange" => {
+                        for [lo, hi, dst] in operands.as_chunks::<3>().0 {
+                            let (Obj::Str(lo), Obj::Str(hi)) = (lo, hi) else {
+                                continue;
+                            };
+                            cmap.width = cmap.width.max(lo.len());
+                            let (lo, hi) = (code(lo), code(hi));
+                            // A full 2-byte range is 64Ki entries; larger ones are bogus
+                            let hi = hi.min(lo.saturating_add(0xffff));
+                            match dst {
+                                Obj::Str(dst) if !dst.is_empty() => {
+                                    let mut units: Vec<u16> = dst
+                                        .chunks(2)
+                                        .map(|p| {
+                                            u16::from_be_bytes([
+                                                p[0],
+                                                p.get(1).copied().unwrap_or(0),
+                                            ])
+                                        })
+                                        .collect();
+                                    for c in lo..=hi {
+                                        cmap.map.insert(c, String::from_utf16_lossy(&units));
+                                        if let Some(last) = units.last_mut() {
+                                            *last = last.wrapping_add(1);
+                                        }
+                                    }
+                                }
+                                Obj::Array(dsts) => {
+                                    for (c, dst) in (lo..=hi).zip(dsts) {
+                                        if let Obj::Str(dst) = dst {
+                                            cmap.map.insert(c, utf16(dst));
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                operands.clear();
+            }
+            if cmap.width == 0 {
+                cmap.width = 1;
+            }
+            cmap
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Font {
+        cmap: Option<CMap>,
+        /// Composite (`Type0`) font: 2-byte codes, unreadable without a `CMap`.
+        composite: bool,
+    }
+
+    impl Font {
+        fn decode(&self, bytes: &[u8], out: &mut String) {
+            match &self.cmap {
+                Some(cmap) => {
+                    for chunk in bytes.chunks(cmap.width) {
+                        let code = code(chunk);
+                        match cmap.map.get(&code) {
+                            Some(s) => out.push_str(s),
+                            None if cmap.width == 1 => out.push(win_ansi(chunk[0])),
+                            None => {}
+                        }
+                    }
+                }
+                None if self.composite => {}
+                None => out.extend(bytes.iter().map(|&b| win_ansi(b))),
+            }
+        }
+    }
+
+    struct Pdf<'a> {
+        data: &'a [u8],
+        objects: HashMap<u32, Obj>,
+        trailer: Dict,
+    }
+
+    /// Extracted text accumulated across a page's content streams.
+    #[derive(Default)]
+    struct TextSink {
+        out: String,
+        last_y: Option<f64>,
+    }
+
+    impl TextSink {
+        fn space(&mut self) {
+            if !self.out.is_empty() && !self.out.ends_with(char::is_whitespace) {
+                self.out.push(' ');
+            }
+        }
+
+        fn newline(&mut self) {
+            while self.out.ends_with(' ') {
+                self.out.pop();
+            }
+            if !self.out.is_empty() && !self.out.ends_with('\n') {
+                self.out.push('\n');
+            }
+        }
+    }
+
+    pub(super) fn extract(data: &[u8]) -> Result<Document> {
+        if !data.starts_with(b"%PDF-") {
+            bail!("not a PDF file");
+        }
+        let pdf = Pdf::parse(data);
+        if pdf.trailer.contains_key("Encrypt") {
+            bail!("encrypted PDFs are not supported");
+        }
+        let pages = pdf.pages();
+        if pages.is_empty() {
+            bail!("PDF has no pages");
+        }
+        let title = pdf
+            .trailer
+            .get("Info")
+            .and_then(|info| pdf.resolve(info).dict())
+            .and_then(|info| match info.get("Title").map(|t| pdf.resolve(t)) {
+                Some(Obj::Str(s)) => Some(tidy(&text_string(s))),
+                _ => None,
+            })
+            .filter(|t| !t.is_empty());
+        let sections = pages
+            .iter()
+            .enumerate()
+            .map(|(i, (page, resources))| Section {
+                page: Some(u32::try_from(i + 1).unwrap_or(u32::MAX)),
+                text: pdf.page_text(page, resources.as_ref()),
+                ..Section::default()
+            })
+            .collect();
+        Ok(Document { title, sections })
+    }
+
+    impl<'a> Pdf<'a> {
+        fn parse(data: &'a [u8]) -> Self {
+            let mut pdf = Self {
+                data,
+                objects: HashMap::new(),
+                trailer: Dict::new(),
+            };
+            pdf.scan_objects();
+            pdf.unpack_object_streams();
+            pdf.find_trailer();
+            pdf
+        }
+
+        /// Finds every `num gen obj` in the file; later definitions win, as
+        /// they do with incremental updates.
+        fn scan_objects(&mut self) {
+            let data = self.data;
+            let mut from = 0;
+            while let Some(at) = find(data, b"obj", from) {
+                from = at + 3;
+                if data
+                    .get(at + 3)
+                    .is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b))
+                {
+                    continue;
+                }
+                let Some(num) = Self::object_number(data, at) else {
+                    continue;
+                };
+                let mut lexer = Lexer::new(data, true);
+                lexer.pos = at + 3;
+                let Some(obj) = lexer.next_obj() else {
+                    continue;
+                };
+                let obj = match obj {
+                    Obj::Dict(dict) => {
+                        let save = lexer.pos;
+                        lexer.skip_whitespace();
+                        if lexer.regular() == b"stream" {
+                            let range = Self::stream_range(data, lexer.pos, &dict);
+                            from = range.end;
+                            Obj::Stream(dict, range)
+                        } else {
+                            lexer.pos = save;
+                            Obj::Dict(dict)
+                        }
+                    }
+                    other => other,
+                };
+                self.objects.insert(num, obj);
+            }
+        }
+
+        /// The object number before `num gen obj`, where `at` is at `obj`.
+        fn object_number(data: &[u8], at: usize) -> Option<u32> {
+            let mut i = at;
+            let digits = |i: &mut usize| {
+                let end = *i;
+                while *i > 0 && data[*i - 1].is_ascii_digit() {
+                    *i -= 1;
+                }
+                (end > *i).then(|| &data[*i..end])
+            };
+            let whitespace = |i: &mut usize| {
+                let end = *i;
+                while *i > 0 && is_whitespace(data[*i - 1]) {
+                    *i -= 1;
+                }
+                end > *i
+            };
+            if !whitespace(&mut i) {
+                return None;
+            }
+            digits(&mut i)?;
+            if !whitespace(&mut i) {
+                return None;
+            }
+            let num = digits(&mut i)?;
+            if i > 0 && !is_whitespace(data[i - 1]) && !is_delimiter(data[i - 1]) {
+                return None;
+            }
+            std::str::from_utf8(num).ok()?.parse().ok()
+        }
+
+        fn stream_range(data: &[u8], mut start: usize, dict: &Dict) -> Range<usize> {
+            if data.get(start) == Some(&b'\r') {
+                start += 1;
+            }
+            if data.get(start) == Some(&b'\n') {
+                start += 1;
+            }
+            if let Some(len) = dict.get("Length").and_then(Obj::as_num) {
+                let end = start + to_index(len);
+                if end <= data.len() {
+                    let mut after = Lexer::new(data, false);
+                    after.pos = end;
+                    after.skip_whitespace();
+                    if after.regular() == b"endstream" {
+                        return start..end;
+                    }
+                }
+            }
+            // Missing or indirect /Length: up to `endstream`
+            let mut end = find(data, b"endstream", start).unwrap_or(data.len());
+            for &eol in b"\n\r" {
+                if end > start && data[end - 1] == eol {
+                    end -= 1;
+                }
+            }
+            start..end
+        }
+
+        /// Adds the objects packed in `/Type /ObjStm` streams.
+        fn unpack_object_streams(&mut self) {
+            let streams: Vec<(Dict, Range<usize>)> = self
+                .objects
+                .values()
+                .filter_map(|obj| match obj {
+                    Obj::Stream(dict, range)
+                        if dict.get("Type").and_then(Obj::as_name) == Some("ObjStm") =>
+                    {
+                        Some((dict.clone(), range.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for (dict, range) in streams {
+                let Some(data) = self.decode(&dict, &self.data[range]) else {
+                    continue;
+                };
+                let count = dict.get("N").and_then(Obj::as_num).map_or(0, to_index);
+                let first = dict.get("First").and_then(Obj::as_num).map_or(0, to_index);
+                let mut header = Lexer::new(&data, false);
+                let mut entries = Vec::with_capacity(count.min(100_000));
+                for _ in 0..count {
+                    match (header.next_obj(), header.next_obj()) {
+                        (Some(Obj::Num(num)), Some(Obj::Num(offset))) => {
+                            entries.push((
+                                u32::try_from(to_index(num)).unwrap_or(u32::MAX),
+                                to_index(offset),
+                            ));
+                        }
+                        _ => break,
+                    }
+                }
+                for (num, offset) in entries {
+                    if self.objects.contains_key(&num) {
+                        continue;
+                    }
+                    let mut lexer = Lexer::new(&data, true);
+                    lexer.pos = first + offset;
+                    if let Some(obj) = lexer.next_obj() {
+                        self.objects.insert(num, obj);
+                    }
+                }
+            }
+        }
+
+        /// The last `trailer` dictionary, or the dictionary of the last
+        /// cross-reference stream.
+        fn find_trailer(&mut self) {
+            if let Some(at) = rfind(self.data, b"trailer") {
+                let mut lexer = Lexer::new(self.data, true);
+                lexer.pos = at + 7;
+                if let Some(Obj::Dict(dict)) = lexer.next_obj() {
+                    self.trailer = dict;
+                    return;
+                }
+            }
+            let xref = self
+                .objects
+                .iter()
+                .filter(|(_, obj)| {
+                    obj.dict()
+                        .is_some_and(|d| d.get("Type").and_then(Obj::as_name) == Some("XRef"))
+                })
+                .max_by_key(|(num, _)| **num);
+            if let Some((_, obj)) = xref {
+                self.trailer = obj.dict().cloned().unwrap_or_default();
+            }
+        }
+
+        fn resolve<'o>(&'o self, obj: &'o Obj) -> &'o Obj {
+            let mut obj = obj;
+            for _ in 0..8 {
+                match obj {
+                    Obj::Ref(num) => obj = self.objects.get(num).unwrap_or(&Obj::Null),
+                    _ => break,
+                }
+            }
+            obj
+        }
+
+        /// Decoded stream data, or `None` for filters other than Flate.
+        fn decode(&self, dict: &Dict, raw: &[u8]) -> Option<Vec<u8>> {
+            let filters = match dict.get("Filter").map(|f| self.resolve(f)) {
+                None | Some(Obj::Null) => Vec::new(),
+                Some(Obj::Name(name)) => vec![name.as_str()],
+                Some(Obj::Array(names)) => names.iter().filter_map(Obj::as_name).collect(),
+                Some(_) => return None,
+            };
+            let mut data = raw.to_vec();
+            for filter in filters {
+                data = match filter {
+                    "FlateDecode" | "Fl" => {
+                        let mut out = Vec::new();
+                        let result =
+                            flate2::read::ZlibDecoder::new(data.as_slice()).read_to_end(&mut out);
+                        // Keep what inflated before a truncated or corrupt end
+                        if result.is_err() && out.is_empty() {
+                            return None;
+                        }
+                        out
+                    }
+                    _ => return None,
+                };
+            }
+            Some(data)
+        }
+
+        fn stream_data(&self, obj: &Obj) -> Option<Vec<u8>> {
+            match self.resolve(obj) {
+                Obj::Stream(dict, range) => self.decode(dict, &self.data[range.clone()]),
+                _ => None,
+            }
+        }
+
+        /// Pages in document order, each with its (possibly inherited)
+        /// resources.
+        fn pages(&self) -> Vec<(Dict, Option<Dict>)> {
+            let mut pages = Vec::new();
+            let root = self
+                .trailer
+                .get("Root")
+                .map(|r| self.resolve(r))
+                .and_then(Obj::dict)
+                .or_else(|| {
+                    self.objects
+                        .values()
+                        .filter_map(Obj::dict)
+                        .find(|d| d.get("Type").and_then(Obj::as_name) == Some("Catalog"))
+                });
+            if let Some(tree) = root.and_then(|root| root.get("Pages")) {
+                let mut seen = HashSet::new();
+                self.walk_pages(tree, None, &mut pages, &mut seen, 0);
+            }
+            if pages.is_empty() {
+                // Broken page tree: take page objects in object order
+                let mut nums: Vec<&u32> = self.objects.keys().collect();
+                nums.sort_unstable();
+                for num in nums {
+                    if let Some(dict) = self.objects[num].dict() {
+                        if dict.get("Type").and_then(Obj::as_name) == Some("Page") {
+                            let resources = dict
+                                .get("Resources")
+                                .and_then(|r| self.resolve(r).dict())
+                                .cloned();
+                            pages.push((dict.clone(), resources));
+                        }
+                    }
+                }
+            }
+            pages
+        }
+
+        fn walk_pages(
+            &self,
+            node: &Obj,
+            inherited: Option<&Dict>,
+            pages: &mut Vec<(Dict, Option<Dict>)>,
+            seen: &mut HashSet<u32>,
+            depth: usize,
+        ) {
+            if let Obj::Ref(num) = node {
+                if !seen.insert(*num) {
+                    return;
+                }
+            }
+            let Some(dict) = self.resolve(node).dict() else {
+                return;
+            };
+            if depth > 64 {
+                return;
+            }
+            let resources = dict
+                .get("Resources")
+                .and_then(|r| self.resolve(r).dict())
+                .or(inherited);
+            match self.resolve(dict.get("Kids").unwrap_or(&Obj::Null)) {
+                Obj::Array(kids) => {
+                    for kid in kids {
+                        self.walk_pages(kid, resources, pages, seen, depth + 1);
+                    }
+                }
+                _ => pages.push((dict.clone(), resources.cloned())),
+            }
+        }
+
+        fn fonts(&self, resources: Option<&Dict>) -> HashMap<String, Font> {
+            let mut fonts = HashMap::new();
+            let Some(entries) = resources
+                .and_then(|r| r.get("Font"))
+                .and_then(|f| self.resolve(f).dict())
+            else {
+                return fonts;
+            };
+            for (name, font) in entries {
+                let Some(font) = self.resolve(font).dict() else {
+                    continue;
+                };
+                let cmap = font
+                    .get("ToUnicode")
+                    .and_then(|t| self.stream_data(t))
+                    .map(|data| CMap::parse(&data));
+                let composite = font.get("Subtype").and_then(Obj::as_name) == Some("Type0");
+                fonts.insert(name.clone(), Font { cmap, composite });
+            }
+            fonts
+        }
+
+        fn page_text(&self, page: &Dict, resources: Option<&Dict>) -> String {
+            let mut content = Vec::new();
+            match page.get("Contents").map(|c| self.resolve(c)) {
+                Some(Obj::Array(parts)) => {
+                    for part in parts {
+                        if let Some(data) = self.stream_data(part) {
+                            content.extend_from_slice(&data);
+                            content.push(b'\n');
+                        }
+                    }
+                }
+                Some(stream @ Obj::Stream(..)) => {
+                    content = self.stream_data(stream).unwrap_or_default();
+                }
+                _ => {}
+            }
+            let mut sink = TextSink::default();
+            self.content_text(&content, resources, &mut sink, 0);
+            tidy(&sink.out)
+        }
+
+        fn content_text(
+            &self,
+            content: &[u8],
+            resources: Option<&Dict>,
+            sink: &mut TextSink,
+            depth: usize,
+        ) {
+            let fonts = self.fonts(resources);
+            let plain = Font::default();
+            let mut font = &plain;
+            let mut lexer = Lexer::new(content, false);
+            let mut operands: Vec<Obj> = Vec::new();
+            while let Some(obj) = lexer.next_obj() {
+                let Obj::Op(op) = obj else {
+                    operands.push(obj);
+                    continue;
+                };
+                let number = |i: usize| operands.get(i).and_then(Obj::as_num).unwrap_or(0.0);
+                match op.as_str() {
+                    "Tf" => {
+                        font = operands
+                            .first()
+                            .and_then(Obj::as_name)
+                            .and_then(|name| fonts.get(name))
+                            .unwrap_or(&plain);
+                    }
+                    "Tj" | "'" | "\"" => {
+                        if op != "Tj" {
+                            sink.newline();
+                        }
+                        if let Some(Obj::Str(s)) = operands.last() {
+                            font.decode(s, &mut sink.out);
+                        }
+                    }
+                    "TJ" => {
+                        if let Some(Obj::Array(items)) = operands.last() {
+                            for item in items {
+                                match item {
+                                    Obj::Str(s) => font.decode(s, &mut sink.out),
+                                    // A gap wider than about a space, in 1/1000 em
+                                    Obj::Num(n) if *n < -200.0 => sink.space(),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    "Td" | "TD" => {
+                        if number(1).abs() > 0.01 {
+                            sink.newline();
+                        } else if number(0) > 0.0 {
+                            sink.space();
+                        }
+                    }
+                    "T*" => sink.newline(),
+                    "Tm" => {
+                        let y = number(5);
+                        if sink.last_y.is_some_and(|last| (last - y).abs() > 1.0) {
+                            sink.newline();
+                        } else {
+                            sink.space();
+                        }
+                        sink.last_y = Some(y);
+                    }
+                    "ET" => sink.space(),
+                    "BI" => lexer.skip_inline_image(),
+                    "Do" if depth < 4 => {
+                        let form = operands.first().and_then(Obj::as_name).and_then(|name| {
+                            resources?
+                                .get("XObject")
+                                .and_then(|x| self.resolve(x).dict())?
+                                .get(name)
+                        });
+                        if let Some(Obj::Stream(dict, range)) = form.map(|f| self.resolve(f)) {
+                            if dict.get("Subtype").and_then(Obj::as_name) == Some("Form") {
+                                if let Some(data) = self.decode(dict, &self.data[range.clone()]) {
+                                    let own = dict
+                                        .get("Resources")
+                                        .and_then(|r| self.resolve(r).dict())
+                                        .or(resources);
+                                    sink.newline();
+                                    self.content_text(&data, own, sink, depth + 1);
+                                    sink.newline();
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                operands.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formats() {
+        assert_eq!(
+            DocumentFormat::from_name("Report.PDF"),
+            Some(DocumentFormat::Pdf)
+        );
+        assert_eq!(
+            DocumentFormat::from_name("text/html; charset=utf-8"),
+            Some(DocumentFormat::Html)
+        );
+        assert_eq!(
+            DocumentFormat::from_name("md"),
+            Some(DocumentFormat::Markdown)
+        );
+        assert_eq!(DocumentFormat::from_name("image/png"), None);
+        assert_eq!(DocumentFormat::sniff(b"%PDF-1.7\n"), DocumentFormat::Pdf);
+        assert_eq!(
+            DocumentFormat::sniff(b"\n<!DOCTYPE html><html>"),
+            DocumentFormat::Html
+        );
+        assert_eq!(DocumentFormat::sniff(b"# Notes"), DocumentFormat::Text);
+    }
+
+    #[test]
+    fn splits_markdown_into_sections() {
+        let doc = extract(
+            b"---\ntitle: ignored\n---\n\
+              Intro with **bold**, `code` and a [link](http://x.y).\n\n\
+              # Guide\n\n\
+              - item one\n\
+              1. step_two\n\
+              > quoted\n\n\
+              ```rust\nlet x = 1;\n```\n\n\
+              Setup\n-----\n\n\
+              | a | b |\n|---|---|\n| 1 | 2 |\n",
+            DocumentFormat::Markdown,
+        )
+        .unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Guide"));
+        let sections: Vec<(Option<&str>, u8, &str)> = doc
+            .sections
+            .iter()
+            .map(|s| (s.heading.as_deref(), s.level, s.text.as_str()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (None, 0, "Intro with bold, code and a link."),
+                (Some("Guide"), 1, "item one\nstep_two\nquoted\n\nlet x = 1;"),
+                (Some("Setup"), 2, "a b\n1 2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_html_markup() {
+        let doc = extract(
+            b"<!DOCTYPE html><html><head><title>Fish &amp; Chips</title>\
+              <style>p { color: red }</style><script>var a = '<p>';</script></head>\
+              <body><nav>Home</nav><h1>Menu</h1><p>Cod&nbsp;&#8211; \n fried</p>\
+              <!-- hidden --><h2>Sides <small>(extra)</small></h2><ul><li>Peas</li><li>Salad</li></ul>\
+              </body></html>",
+            DocumentFormat::Html,
+        )
+        .unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Fish & Chips"));
+        let sections: Vec<(Option<&str>, &str)> = doc
+            .sections
+            .iter()
+            .map(|s| (s.heading.as_deref(), s.text.as_str()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (None, "Home"),
+                (Some("Menu"), "Cod – fried"),
+                (Some("Sides (extra)"), "Peas\nSalad"),
+            ]
+        );
+    }
+
+    /// A PDF with `objects` as objects 1.., with a correct xref table.
+    fn pdf(objects: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = b"%PDF-1.5\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 2 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+        out
+    }
+
+    fn stream(data: &[u8], compress: bool) -> Vec<u8> {
+        use std::io::Write;
+        let (data, filter) = if compress {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).unwrap();
+            (encoder.finish().unwrap(), " /Filter /FlateDecode")
+        } else {
+            (data.to_vec(), "")
+        };
+        let mut out = format!("<< /Length {}{filter} >>\nstream\n", data.len()).into_bytes();
+        out.extend_from_slice(&data);
+        out.extend_from_slice(b"\nendstream");
+        out
+    }
+
+    #[test]
+    fn reads_pdf_pages_in_tree_order() {
+        let cmap = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+            1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            2 beginbfchar <0001> <0048> <0002> <0069> endbfchar\n\
+            1 beginbfrange <0010> <0012> <0061> endbfrange\n\
+            endcmap CMapName currentdict /CMap defineresource pop end end";
+        let objects = [
+            b"<< /Type /Catalog /Pages 3 0 R >>".to_vec(),
+            b"<< /Title (Quarterly \\(Q1\\) Report) >>".to_vec(),
+            // Pages listed in reverse object order to check tree order
+            b"<< /Type /Pages /Kids [5 0 R 4 0 R] /Count 2 /Resources << /Font << /F1 6 0 R /F2 7 0 R >> >> >>".to_vec(),
+            b"<< /Type /Page /Parent 3 0 R /Contents [9 0 R 10 0 R] >>".to_vec(),
+            b"<< /Type /Page /Parent 3 0 R /Contents 8 0 R >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type0 /Encoding /Identity-H /ToUnicode 11 0 R >>".to_vec(),
+            stream(
+                b"BT /F1 12 Tf 72 720 Td (First page) Tj 0 -14 Td [(Sec) -20 (ond) -300 (line)] TJ ET",
+                true,
+            ),
+            stream(b"BT /F2 12 Tf 1 0 0 1 72 720 Tm <00010002> Tj ET", true),
+            stream(
+                b"BT 1 0 0 1 72 700 Tm /F2 12 Tf <001000110012> Tj /F1 12 Tf ( \\223caf\\351\\224) Tj ET",
+                false,
+            ),
+            stream(cmap, true),
+        ];
+        let doc = extract(&pdf(&objects), DocumentFormat::Pdf).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Quarterly (Q1) Report"));
+        let pages: Vec<(Option<u32>, &str)> = doc
+            .sections
+            .iter()
+            .map(|s| (s.page, s.text.as_str()))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                (Some(1), "First page\nSecond line"),
+                (Some(2), "Hi\nabc “café”"),
+            ]
+        );
+
+        let chunks = doc.chunks(None);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].metadata["page"], "2");
+        assert_eq!(chunks[1].metadata["chunk"], "1");
+        assert_eq!(chunks[1].metadata["title"], "Quarterly (Q1) Report");
+
+        let mut encrypted = pdf(&objects);
+        encrypted.truncate(encrypted.len() - 30);
+        encrypted.extend_from_slice(b"trailer << /Root 1 0 R /Encrypt 12 0 R >>\n%%EOF\n");
+        assert!(extract(&encrypted, DocumentFormat::Pdf).is_err());
+    }
+
+    #[test]
+    fn chunks_sections_with_overlap() {
+        let doc = Document {
+            title: None,
+            sections: vec![Section {
+                heading: Some("Words".into()),
+                level: 2,
+                page: None,
+                text: "a b c d e f g h".into(),
+            }],
+        };
+        let config = ChunkingConfig {
+            chunk_size: 4,
+            overlap: 0.25,
+        };
+        let chunks = doc.chunks(Some(&config));
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["a b c d", "d e f g", "g h"]);
+        assert!(chunks.iter().all(|c| c.metadata["section"] == "Words"));
+    }
+}
@@ -0,0 +1,1020 @@
+//! Image decoding and CLIP preprocessing for [`crate::ImageVectorizer`].
+//!
+//! [`decode`] reads PNG (every color type and bit depth, interlaced or not)
+//! and baseline JPEG (grayscale or YCbCr, any chroma subsampling) into 8-bit
+//! RGB; transparent pixels are composited onto white. Progressive and CMYK
+//! JPEGs are refused with an error. [`clip_input`] then resizes, crops and
+//! normalizes an image the way CLIP's image processor does.
+
+use anyhow::{bail, ensure, Result};
+
+/// Largest image accepted, in pixels (about 8K x 8K).
+const MAX_PIXELS: usize = 1 << 26;
+
+/// CLIP's per-channel normalization.
+const CLIP_MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+const CLIP_STD: [f32; 3] = [0.268_629_54, 0.261_302_6, 0.275_777_1];
+
+/// An 8-bit RGB image, row-major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    fn new(width: usize, height: usize) -> Result<Self> {
+        ensure!(width > 0 && height > 0, "image has no pixels");
+        ensure!(
+            width.saturating_mul(height) <= MAX_PIXELS,
+            "image is too large ({width}x{height})"
+        );
+        Ok(Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+        })
+    }
+
+    fn put(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        let at = (y * self.width + x) * 3;
+        self.pixels[at..at + 3].copy_from_slice(&rgb);
+    }
+}
+
+/// Decodes a PNG or JPEG file.
+///
+/// # Errors
+/// Returns an error for other formats, unsupported JPEG variants and
+/// corrupt data.
+pub fn decode(bytes: &[u8]) -> Result<RgbImage> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png::decode(bytes)
+    } else if bytes.starts_with(&[0xff, 0xd8]) {
+        jpeg::decode(bytes)
+    } else {
+        bail!("unsupported image format, expected PNG or JPEG")
+    }
+}
+
+/// Model input for `image`: the shorter side scaled to `size`, the center
+/// `size` x `size` square, channels normalized with CLIP's mean and standard
+/// deviation, laid out channel-first.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn clip_input(image: &RgbImage, size: usize) -> Vec<f32> {
+    let (w, h) = (image.width as f64, image.height as f64);
+    let scale = size as f64 / w.min(h);
+    let (scaled_w, scaled_h) = ((w * scale).round(), (h * scale).round());
+    let x_taps = resample_taps(image.width, scaled_w, size);
+    let y_taps = resample_taps(image.height, scaled_h, size);
+
+    // Horizontal pass into `size` columns for every source row, then vertical
+    let mut rows = vec![0f32; image.height * size * 3];
+    for y in 0..image.height {
+        let src = &image.pixels[y * image.width * 3..(y + 1) * image.width * 3];
+        for (x, (start, weights)) in x_taps.iter().enumerate() {
+            let mut rgb = [0f32; 3];
+            for (i, weight) in weights.iter().enumerate() {
+                for (c, value) in rgb.iter_mut().enumerate() {
+                    *value += weight * f32::from(src[(start + i) * 3 + c]);
+                }
+            }
+            rows[(y * size + x) * 3..(y * size + x) * 3 + 3].copy_from_slice(&rgb);
+        }
+    }
+    let mut out = vec![0f32; 3 * size * size];
+    for (y, (start, weights)) in y_taps.iter().enumerate() {
+        for x in 0..size {
+            for c in 0..3 {
+                let value: f32 = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, weight)| weight * rows[((start + i) * size + x) * 3 + c])
+                    .sum();
+                out[(c * size + y) * size + x] = (value / 255.0 - CLIP_MEAN[c]) / CLIP_STD[c];
+            }
+        }
+    }
+    out
+}
+
+/// For each of the `count` centered output pixels along an axis scaled from
+/// `src` to `scaled` pixels: the first source pixel and the weights of the
+/// source pixels it averages (a box filter over the pixel's footprint).
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn resample_taps(src: usize, scaled: f64, count: usize) -> Vec<(usize, Vec<f32>)> {
+    let ratio = src as f64 / scaled;
+    let offset = ((scaled - count as f64) / 2.0).floor().max(0.0);
+    (0..count)
+        .map(|i| {
+            let from = ((offset + i as f64) * ratio).clamp(0.0, src as f64);
+            let to = ((offset + i as f64 + 1.0) * ratio).clamp(from, src as f64);
+            let first = (from.floor() as usize).min(src - 1);
+            let last = ((to.ceil() as usize).max(first + 1)).min(src);
+            let mut weights: Vec<f32> = (first..last)
+                .map(|p| {
+                    let overlap = (to.min(p as f64 + 1.0) - from.max(p as f64)).max(0.0);
+                    overlap as f32
+                })
+                .collect();
+            let total: f32 = weights.iter().sum();
+            if total > 0.0 {
+                for weight in &mut weights {
+                    *weight /= total;
+                }
+            } else {
+                // Upscaling: the footprint lies inside a single pixel
+                weights = vec![1.0];
+            }
+            (first, weights)
+        })
+        .collect()
+}
+
+/// `value` over a white background at opacity `alpha`.
+fn over_white(value: u8, alpha: u8) -> u8 {
+    let (v, a) = (u32::from(value), u32::from(alpha));
+    u8::try_from((v * a + 255 * (255 - a) + 127) / 255).unwrap_or(u8::MAX)
+}
+
+mod png {
+    use super::{over_white, RgbImage};
+    use anyhow::{anyhow, bail, ensure, Result};
+    use std::io::Read;
+
+    /// Adam7 passes: x and y of the first pixel, then the steps.
+    const ADAM7: [(usize, usize, usize, usize); 7] = [
+        (0, 0, 8, 8),
+        (4, 0, 8, 8),
+        (0, 4, 4, 8),
+        (2, 0, 4, 4),
+        (0, 2, 2, 4),
+        (1, 0, 2, 2),
+        (0, 1, 1, 2),
+    ];
+
+    struct Header {
+        width: usize,
+        height: usize,
+        depth: u8,
+        color: u8,
+        interlaced: bool,
+    }
+
+    impl Header {
+        fn channels(&self) -> usize {
+            match self.color {
+                2 => 3,
+                4 => 2,
+                6 => 4,
+                _ => 1,
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub(super) fn decode(bytes: &[u8]) -> Result<RgbImage> {
+        let mut header = None;
+        let mut palette: Vec<[u8; 3]> = Vec::new();
+        let mut palette_alpha: Vec<u8> = Vec::new();
+        let mut transparent: Option<[u16; 3]> = None;
+        let mut compressed = Vec::new();
+        let mut pos = 8;
+        while pos + 8 <= bytes.len() {
+            let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into()?) as usize;
+            let kind = &bytes[pos + 4..pos + 8];
+            let data = bytes
+                .get(pos + 8..pos + 8 + len)
+                .ok_or_else(|| anyhow!("truncated PNG chunk"))?;
+            pos += 12 + len;
+            match kind {
+                b"IHDR" => {
+                    ensure!(data.len() >= 13, "bad PNG header");
+                    let header_value = Header {
+                        width: u32::from_be_bytes(data[0..4].try_into()?) as usize,
+                        height: u32::from_be_bytes(data[4..8].try_into()?) as usize,
+                        depth: data[8],
+                        color: data[9],
+                        interlaced: data[12] == 1,
+                    };
+                    let valid = matches!(
+                        (header_value.color, header_value.depth),
+                        (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) | (2 | 4 | 6, 8 | 16)
+                    );
+                    ensure!(valid, "bad PNG color type or bit depth");
+                    header = Some(header_value);
+                }
+                b"PLTE" => {
+                    palette = data.as_chunks::<3>().0.to_vec();
+                }
+                b"tRNS" => match header.as_ref().map(|h| h.color) {
+                    Some(3) => palette_alpha = data.to_vec(),
+                    Some(0) if data.len() >= 2 => {
+                        let gray = u16::from_be_bytes([data[0], data[1]]);
+                        transparent = Some([gray; 3]);
+                    }
+                    Some(2) if data.len() >= 6 => {
+                        let sample = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+                        transparent = Some([sample(0), sample(2), sample(4)]);
+                    }
+                    _ => {}
+                },
+                b"IDAT" => compressed.extend_from_slice(data),
+                b"IEND" => break,
+                _ => {}
+            }
+        }
+        let header = header.ok_or_else(|| anyhow!("PNG has no header"))?;
+        ensure!(
+            header.color != 3 || !palette.is_empty(),
+            "PNG has no palette"
+        );
+        let mut data = Vec::new();
+        flate2::read::ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut data)
+            .map_err(|e| anyhow!("corrupt PNG data: {e}"))?;
+
+        let mut image = RgbImage::new(header.width, header.height)?;
+        let channels = header.channels();
+        let bits_per_pixel = channels * usize::from(header.depth);
+        // Filter unit: bytes per complete pixel, at least one
+        let unit = bits_per_pixel.div_ceil(8);
+        let passes: &[(usize, usize, usize, usize)] = if header.interlaced {
+            &ADAM7
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+        let mut offset = 0;
+        for &(x0, y0, dx, dy) in passes {
+            let pass_width = header.width.saturating_sub(x0).div_ceil(dx);
+            let pass_height = header.height.saturating_sub(y0).div_ceil(dy);
+            if pass_width == 0 || pass_height == 0 {
+                continue;
+            }
+            let stride = (pass_width * bits_per_pixel).div_ceil(8);
+            let mut previous = vec![0u8; stride];
+            for row in 0..pass_height {
+                let filter = *data
+                    .get(offset)
+                    .ok_or_else(|| anyhow!("truncated PNG data"))?;
+                let mut line = data
+                    .get(offset + 1..offset + 1 + stride)
+                    .ok_or_else(|| anyhow!("truncated PNG data"))?
+                    .to_vec();
+                offset += 1 + stride;
+                unfilter(filter, &mut line, &previous, unit)?;
+                for col in 0..pass_width {
+                    let sample = |c: usize| sample(&line, col * channels + c, header.depth);
+                    let max = (1u32 << header.depth) - 1;
+                    let to8 = |v: u16| u8::try_from(u32::from(v) * 255 / max).unwrap_or(u8::MAX);
+                    let (rgb, alpha) = match header.color {
+                        0 => {
+                            let g = sample(0);
+                            let alpha = if transparent.is_some_and(|t| t[0] == g) {
+                                0
+                            } else {
+                                255
+                            };
+                            ([to8(g); 3], alpha)
+                        }
+                        2 => {
+                            let raw = [sample(0), sample(1), sample(2)];
+                            let alpha = if transparent == Some(raw) { 0 } else { 255 };
+                            (raw.map(to8), alpha)
+                        }
+                        3 => {
+                            let index = usize::from(sample(0));
+                            let rgb = palette.get(index).copied().unwrap_or_default();
+                            (rgb, palette_alpha.get(index).copied().unwrap_or(255))
+                        }
+                        4 => ([to8(sample(0)); 3], to8(sample(1))),
+                        _ => (
+                            [to8(sample(0)), to8(sample(1)), to8(sample(2))],
+                            to8(sample(3)),
+                        ),
+                    };
+                    image.put(
+                        x0 + col * dx,
+                        y0 + row * dy,
+                        rgb.map(|v| over_white(v, alpha)),
+                    );
+                }
+                previous = line;
+            }
+        }
+        Ok(image)
+    }
+
+    /// The `index`th sample of a row, at its native bit depth.
+    fn sample(line: &[u8], index: usize, depth: u8) -> u16 {
+        match depth {
+            16 => u16::from_be_bytes([line[index * 2], line[index * 2 + 1]]),
+            8 => u16::from(line[index]),
+            _ => {
+                let depth = usize::from(depth);
+                let bit = index * depth;
+                let shift = 8 - depth - bit % 8;
+                u16::from((line[bit / 8] >> shift) & ((1 << depth) - 1))
+            }
+        }
+    }
+
+    fn unfilter(filter: u8, line: &mut [u8], previous: &[u8], unit: usize) -> Result<()> {
+        for i in 0..line.len() {
+            let left = if i >= unit { line[i - unit] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= unit { previous[i - unit] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => u8::try_from(u16::midpoint(u16::from(left), u16::from(up))).unwrap_or(0),
+                4 => paeth(left, up, up_left),
+                _ => bail!("bad PNG filter type {filter}"),
+            };
+            line[i] = line[i].wrapping_add(predicted);
+        }
+        Ok(())
+    }
+
+    fn paeth(a: u8, b: u8, c: u8) -> u8 {
+        let p = i16::from(a) + i16::from(b) - i16::from(c);
+        let (pa, pb, pc) = (
+            (p - i16::from(a)).abs(),
+            (p - i16::from(b)).abs(),
+            (p - i16::from(c)).abs(),
+        );
+        if pa <= pb && pa <= pc {
+            a
+        } else if pb <= pc {
+            b
+        } else {
+            c
+        }
+    }
+}
+
+mod jpeg {
+    use super::RgbImage;
+    use anyhow::{anyhow, bail, ensure, Result};
+
+    /// Natural (row-major) position of the `k`th coefficient in zigzag order.
+    const ZIGZAG: [usize; 64] = [
+        0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27,
+        20, 13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51,
+        58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+    ];
+
+    /// A Huffman table in canonical form.
+    #[derive(Clone, Default)]
+    struct Huffman {
+        /// Largest code of each length, or -1.
+        max_code: [i32; 17],
+        /// Index in `symbols` of the first code of each length, minus that
+        /// code.
+        offset: [i32; 17],
+        symbols: Vec<u8>,
+    }
+
+    impl Huffman {
+        fn new(counts: &[u8], symbols: &[u8]) -> Self {
+            let mut table = Self {
+                max_code: [-1; 17],
+                offset: [0; 17],
+                symbols: symbols.to_vec(),
+            };
+            let (mut code, mut index) = (0i32, 0i32);
+            for len in 1..=16 {
+                let count = i32::from(counts[len - 1]);
+                table.offset[len] = index - code;
+                if count > 0 {
+                    code += count;
+                    index += count;
+                    table.max_code[len] = code - 1;
+                }
+                code <<= 1;
+            }
+            table
+        }
+    }
+
+    struct Component {
+        id: u8,
+        h: usize,
+        v: usize,
+        quant: usize,
+        /// Blocks per line and column, padded to whole MCUs.
+        blocks_w: usize,
+        blocks_h: usize,
+        /// Decoded samples, `blocks_w * 8` wide.
+        plane: Vec<u8>,
+        dc_table: usize,
+        ac_table: usize,
+        dc_pred: i32,
+    }
+
+    /// Reads entropy-coded bits, undoing `FF 00` stuffing and stopping at
+    /// markers.
+    struct Bits<'a> {
+        data: &'a [u8],
+        pos: usize,
+        acc: u32,
+        count: u32,
+        at_marker: bool,
+    }
+
+    impl<'a> Bits<'a> {
+        fn new(data: &'a [u8], pos: usize) -> Self {
+            Self {
+                data,
+                pos,
+                acc: 0,
+                count: 0,
+                at_marker: false,
+            }
+        }
+
+        fn fill(&mut self) {
+            while self.count <= 24 {
+                let mut byte = 0;
+                if !self.at_marker {
+                    match self.data.get(self.pos) {
+                        Some(0xff) => {
+                            if self.data.get(self.pos + 1) == Some(&0) {
+                                byte = 0xff;
+                                self.pos += 2;
+                            } else {
+                                self.at_marker = true;
+                            }
+                        }
+                        Some(&b) => {
+                            byte = b;
+                            self.pos += 1;
+                        }
+                        None => self.at_marker = true,
+                    }
+                }
+                self.acc |= u32::from(byte) << (24 - self.count);
+                self.count += 8;
+            }
+        }
+
+        fn bits(&mut self, n: u32) -> u32 {
+            if n == 0 {
+                return 0;
+            }
+            self.fill();
+            let value = self.acc >> (32 - n);
+            self.acc <<= n;
+            self.count -= n;
+            value
+        }
+
+        fn decode(&mut self, table: &Huffman) -> Result<u8> {
+            let mut code = 0i32;
+            for len in 1..=16 {
+                code = (code << 1) | i32::from(self.bits(1) == 1);
+                if code <= table.max_code[len] {
+                    let index = usize::try_from(table.offset[len] + code)?;
+                    return table
+                        .symbols
+                        .get(index)
+                        .copied()
+                        .ok_or_else(|| anyhow!("bad Huffman code"));
+                }
+            }
+            bail!("bad Huffman code")
+        }
+
+        /// `n` bits as a signed coefficient (JPEG's EXTEND).
+        fn signed(&mut self, n: u8) -> i32 {
+            if n == 0 {
+                return 0;
+            }
+            let n = u32::from(n.min(16));
+            let value = i32::try_from(self.bits(n)).unwrap_or(0);
+            if value < 1 << (n - 1) {
+                value - (1 << n) + 1
+            } else {
+                value
+            }
+        }
+
+        /// Skips to just after the next restart marker.
+        fn restart(&mut self) {
+            self.acc = 0;
+            self.count = 0;
+            self.at_marker = false;
+            while self.pos + 1 < self.data.len() {
+                if self.data[self.pos] == 0xff && (0xd0..=0xd7).contains(&self.data[self.pos + 1]) {
+                    self.pos += 2;
+                    return;
+                }
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// `cos((2x + 1) u pi / 16) * C(u) / 2` for the inverse DCT.
+    fn idct_table() -> [[f32; 8]; 8] {
+        let mut table = [[0f32; 8]; 8];
+        for (x, row) in table.iter_mut().enumerate() {
+            for (u, value) in row.iter_mut().enumerate() {
+                let c = if u == 0 {
+                    std::f32::consts::FRAC_1_SQRT_2
+                } else {
+                    1.0
+                };
+                #[allow(clippy::cast_precision_loss)]
+                let angle = ((2 * x + 1) * u) as f32 * std::f32::consts::PI / 16.0;
+                *value = c * angle.cos() / 2.0;
+            }
+        }
+        table
+    }
+
+    fn idct(coefficients: &[f32; 64], table: &[[f32; 8]; 8], out: &mut [u8], stride: usize) {
+        let mut rows = [0f32; 64];
+        for v in 0..8 {
+            for x in 0..8 {
+                rows[v * 8 + x] = (0..8).map(|u| table[x][u] * coefficients[v * 8 + u]).sum();
+            }
+        }
+        for y in 0..8 {
+            for x in 0..8 {
+                let value: f32 = (0..8).map(|v| table[y][v] * rows[v * 8 + x]).sum();
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let sample = (value + 128.0).round().clamp(0.0, 255.0) as u8;
+                out[y * stride + x] = sample;
+            }
+        }
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> Result<usize> {
+        bytes
+            .get(at..at + 2)
+            .map(|b| usize::from(u16::from_be_bytes([b[0], b[1]])))
+            .ok_or_else(|| anyhow!("truncated JPEG"))
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub(super) fn decode(bytes: &[u8]) -> Result<RgbImage> {
+        let mut quant = [[0u16; 64]; 4];
+        let mut dc_tables = vec![Huffman::default(); 4];
+        let mut ac_tables = vec![Huffman::default(); 4];
+        let mut components: Vec<Component> = Vec::new();
+        let (mut width, mut height) = (0, 0);
+        let (mut h_max, mut v_max) = (1, 1);
+        let mut restart_interval = 0;
+        let mut adobe_transform: Option<u8> = None;
+        let table = idct_table();
+        let mut pos = 2;
+        loop {
+            // Fill bytes may precede a marker
+            while bytes.get(pos) == Some(&0xff) && bytes.get(pos + 1) == Some(&0xff) {
+                pos += 1;
+            }
+            ensure!(bytes.get(pos) == Some(&0xff), "corrupt JPEG marker");
+            let marker = *bytes
+                .get(pos + 1)
+                .ok_or_else(|| anyhow!("truncated JPEG"))?;
+            pos += 2;
+            if marker == 0xd9 {
+                break;
+            }
+            if (0xd0..=0xd7).contains(&marker) || marker == 0x01 {
+                continue;
+            }
+            let len = u16_at(bytes, pos)?;
+            let segment = bytes
+                .get(pos + 2..pos + len)
+                .ok_or_else(|| anyhow!("truncated JPEG segment"))?;
+            pos += len;
+            match marker {
+                0xdb => {
+                    let mut rest = segment;
+                    while let Some((&info, tail)) = rest.split_first() {
+                        let wide = info >> 4 == 1;
+                        let id = usize::from(info & 3);
+                        let size = if wide { 128 } else { 64 };
+                        ensure!(tail.len() >= size, "truncated JPEG quantization table");
+                        for k in 0..64 {
+                            quant[id][k] = if wide {
+                                u16::from_be_bytes([tail[k * 2], tail[k * 2 + 1]])
+                            } else {
+                                u16::from(tail[k])
+                            };
+                        }
+                        rest = &tail[size..];
+                    }
+                }
+                0xc4 => {
+                    let mut rest = segment;
+                    while rest.len() >= 17 {
+                        let (class, id) = (rest[0] >> 4, usize::from(rest[0] & 3));
+                        let counts = &rest[1..17];
+                        let total: usize = counts.iter().map(|&c| usize::from(c)).sum();
+                        let symbols = rest
+                            .get(17..17 + total)
+                            .ok_or_else(|| anyhow!("truncated JPEG Huffman table"))?;
+                        let huffman = Huffman::new(counts, symbols);
+                        if class == 0 {
+                            dc_tables[id] = huffman;
+                        } else {
+                            ac_tables[id] = huffman;
+                        }
+                        rest = &rest[17 + total..];
+                    }
+                }
+                0xc0 | 0xc1 => {
+                    ensure!(segment.len() >= 6, "truncated JPEG frame header");
+                    ensure!(segment[0] == 8, "only 8-bit JPEGs are supported");
+                    height = u16_at(segment, 1)?;
+                    width = u16_at(segment, 3)?;
+                    let count = usize::from(segment[5]);
+                    ensure!(
+                        count == 1 || count == 3,
+                        "only grayscale and YCbCr JPEGs are supported"
+                    );
+                    ensure!(height > 0, "JPEGs without a height are not supported");
+                    for i in 0..count {
+                        let spec = segment
+                            .get(6 + i * 3..9 + i * 3)
+                            .ok_or_else(|| anyhow!("truncated JPEG frame header"))?;
+                        let (h, v) = (usize::from(spec[1] >> 4), usize::from(spec[1] & 15));
+                        ensure!(
+                            (1..=4).contains(&h) && (1..=4).contains(&v),
+                            "bad JPEG sampling factors"
+                        );
+                        components.push(Component {
+                            id: spec[0],
+                            h,
+                            v,
+                            quant: usize::from(spec[2] & 3),
+                            blocks_w: 0,
+                            blocks_h: 0,
+                            plane: Vec::new(),
+                            dc_table: 0,
+                            ac_table: 0,
+                            dc_pred: 0,
+                        });
+                    }
+                    h_max = components.iter().map(|c| c.h).max().unwrap_or(1);
+                    v_max = components.iter().map(|c| c.v).max().unwrap_or(1);
+                    let mcus_x = width.div_ceil(8 * h_max);
+                    let mcus_y = height.div_ceil(8 * v_max);
+                    RgbImage::new(width, height)?;
+                    for c in &mut components {
+                        c.blocks_w = mcus_x * c.h;
+                        c.blocks_h = mcus_y * c.v;
+                        c.plane = vec![0; c.blocks_w * c.blocks_h * 64];
+                    }
+                }
+                0xc2 | 0xc6 | 0xca => bail!("progressive JPEGs are not supported"),
+                0xc3 | 0xc5 | 0xc7 | 0xc9 | 0xcb | 0xcd | 0xce | 0xcf => {
+                    bail!("lossless, hierarchical and arithmetic-coded JPEGs are not supported")
+                }
+                0xdd => restart_interval = u16_at(segment, 0)?,
+                0xee if segment.starts_with(b"Adobe") && segment.len() >= 12 => {
+                    adobe_transform = Some(segment[11]);
+                }
+                0xda => {
+                    ensure!(!components.is_empty(), "JPEG scan before frame header");
+                    let count = usize::from(*segment.first().unwrap_or(&0));
+                    let mut scan = Vec::with_capacity(count);
+                    for i in 0..count {
+                        let spec = segment
+                            .get(1 + i * 2..3 + i * 2)
+                            .ok_or_else(|| anyhow!("truncated JPEG scan header"))?;
+                        let index = components
+                            .iter()
+                            .position(|c| c.id == spec[0])
+                            .ok_or_else(|| anyhow!("JPEG scan names an unknown component"))?;
+                        components[index].dc_table = usize::from(spec[1] >> 4 & 3);
+                        components[index].ac_table = usize::from(spec[1] & 3);
+                        scan.push(index);
+                    }
+                    pos = decode_scan(
+                        bytes,
+                        pos,
+                        &mut components,
+                        &scan,
+                        &Tables {
+                            quant: &quant,
+                            dc: &dc_tables,
+                            ac: &ac_tables,
+                            idct: &table,
+                        },
+                        restart_interval,
+                        (width, height, h_max, v_max),
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        ensure!(!components.is_empty(), "JPEG has no frame header");
+
+        let mut image = RgbImage::new(width, height)?;
+        let sample = |c: &Component, x: usize, y: usize| {
+            let (sx, sy) = (x * c.h / h_max, y * c.v / v_max);
+            f32::from(c.plane[sy * c.blocks_w * 8 + sx])
+        };
+        // Three components are YCbCr unless an Adobe marker says RGB
+        let rgb_stored = adobe_transform == Some(0);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = if components.len() == 1 {
+                    let g = sample(&components[0], x, y);
+                    [g; 3]
+                } else if rgb_stored {
+                    [0, 1, 2].map(|i| sample(&components[i], x, y))
+                } else {
+                    let luma = sample(&components[0], x, y);
+                    let cb = sample(&components[1], x, y) - 128.0;
+                    let cr = sample(&components[2], x, y) - 128.0;
+                    [
+                        luma + 1.402 * cr,
+                        luma - 0.344_136 * cb - 0.714_136 * cr,
+                        luma + 1.772 * cb,
+                    ]
+                };
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                image.put(x, y, pixel.map(|v| v.round().clamp(0.0, 255.0) as u8));
+            }
+        }
+        Ok(image)
+    }
+
+    struct Tables<'a> {
+        quant: &'a [[u16; 64]; 4],
+        dc: &'a [Huffman],
+        ac: &'a [Huffman],
+        idct: &'a [[f32; 8]; 8],
+    }
+
+    /// Decodes the entropy-coded data of a scan starting at `pos`; returns
+    /// the position of the marker that ends it.
+    fn decode_scan(
+        bytes: &[u8],
+        pos: usize,
+        components: &mut [Component],
+        scan: &[usize],
+        tables: &Tables,
+        restart_interval: usize,
+        (width, height, h_max, v_max): (usize, usize, usize, usize),
+    ) -> Result<usize> {
+        let mut bits = Bits::new(bytes, pos);
+        for &c in scan {
+            components[c].dc_pred = 0;
+        }
+        // A single-component scan covers only that component's own blocks
+        let (mcus_x, mcus_y) = if let [only] = scan {
+            let c = &components[*only];
+            (
+                (width * c.h).div_ceil(h_max).div_ceil(8),
+                (height * c.v).div_ceil(v_max).div_ceil(8),
+            )
+        } else {
+            (width.div_ceil(8 * h_max), height.div_ceil(8 * v_max))
+        };
+        let mut coefficients = [0f32; 64];
+        for mcu in 0..mcus_x * mcus_y {
+            if restart_interval > 0 && mcu > 0 && mcu % restart_interval == 0 {
+                bits.restart();
+                for &c in scan {
+                    components[c].dc_pred = 0;
+                }
+            }
+            let (mx, my) = (mcu % mcus_x, mcu / mcus_x);
+            for &c in scan {
+                let component = &mut components[c];
+                let (h, v) = if scan.len() == 1 {
+                    (1, 1)
+                } else {
+                    (component.h, component.v)
+                };
+                for by in 0..v {
+                    for bx in 0..h {
+                        coefficients.fill(0.0);
+                        let quant = &tables.quant[component.quant];
+                        let category = bits.decode(&tables.dc[component.dc_table])?;
+                        component.dc_pred += bits.signed(category);
+                        #[allow(clippy::cast_precision_loss)]
+                        {
+                            coefficients[0] = (component.dc_pred * i32::from(quant[0])) as f32;
+                        }
+                        let mut k = 1;
+                        while k < 64 {
+                            let rs = bits.decode(&tables.ac[component.ac_table])?;
+                            let (run, size) = (usize::from(rs >> 4), rs & 15);
+                            if size == 0 {
+                                if run == 15 {
+                                    k += 16;
+                                    continue;
+                                }
+                                break;
+                            }
+                            k += run;
+                            if k > 63 {
+                                break;
+                            }
+                            #[allow(clippy::cast_precision_loss)]
+                            {
+                                coefficients[ZIGZAG[k]] =
+                                    (bits.signed(size) * i32::from(quant[k])) as f32;
+                            }
+                            k += 1;
+                        }
+                        let (block_x, block_y) = (mx * h + bx, my * v + by);
+                        if block_x < component.blocks_w && block_y < component.blocks_h {
+                            let stride = component.blocks_w * 8;
+                            let start = block_y * 8 * stride + block_x * 8;
+                            idct(
+                                &coefficients,
+                                tables.idct,
+                                &mut component.plane[start..],
+                                stride,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        // Continue at the marker that ends the scan
+        let mut end = bits.pos;
+        while end + 1 < bytes.len() {
+            if bytes[end] == 0xff && bytes[end + 1] != 0 && !(0xd0..=0xd7).contains(&bytes[end + 1])
+            {
+                return Ok(end);
+            }
+            end += 1;
+        }
+        bail!("truncated JPEG scan")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        out.extend_from_slice(&u32::try_from(data.len()).unwrap().to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        // The decoder does not check CRCs
+        out.extend_from_slice(&[0; 4]);
+    }
+
+    #[test]
+    fn decodes_png_palette_with_filters_and_transparency() {
+        // 3x2, 2 bits per pixel: red, green, clear / blue, red, green
+        let header = [0, 0, 0, 3, 0, 0, 0, 2, 2, 3, 0, 0, 0];
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        // Row 0 unfiltered, row 1 with the "up" filter
+        let row0 = 0b0001_1100u8;
+        let row1 = 0b1000_0100u8;
+        encoder
+            .write_all(&[0, row0, 2, row1.wrapping_sub(row0)])
+            .unwrap();
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(
+            &mut png,
+            b"PLTE",
+            &[255, 0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0],
+        );
+        chunk(&mut png, b"tRNS", &[255, 255, 255, 0]);
+        chunk(&mut png, b"IDAT", &encoder.finish().unwrap());
+        chunk(&mut png, b"IEND", &[]);
+
+        let image = decode(&png).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(
+            image.pixels,
+            [
+                255, 0, 0, 0, 255, 0, 255, 255, 255, // row 0
+                0, 0, 255, 255, 0, 0, 0, 255, 0, // row 1
+            ]
+        );
+    }
+
+    /// A baseline JPEG of 8x8 blocks carrying only a DC coefficient, so each
+    /// block decodes to one flat `level`. All components sample 1x1 and
+    /// `levels` lists the blocks in scan order.
+    fn flat_jpeg(width: u16, height: u16, components: u8, levels: &[i32]) -> Vec<u8> {
+        let mut out = vec![0xff, 0xd8];
+        let mut segment = |marker: u8, data: &[u8]| {
+            out.extend_from_slice(&[0xff, marker]);
+            out.extend_from_slice(&u16::try_from(data.len() + 2).unwrap().to_be_bytes());
+            out.extend_from_slice(data);
+        };
+        let mut quant = vec![0];
+        quant.extend_from_slice(&[1; 64]);
+        segment(0xdb, &quant);
+        let mut frame = vec![8];
+        frame.extend_from_slice(&height.to_be_bytes());
+        frame.extend_from_slice(&width.to_be_bytes());
+        frame.push(components);
+        for id in 1..=components {
+            frame.extend_from_slice(&[id, 0x11, 0]);
+        }
+        segment(0xc0, &frame);
+        // DC categories 0-11 all get 4-bit codes equal to the category
+        let mut dc = vec![0x00, 0, 0, 0, 12];
+        dc.extend_from_slice(&[0; 12]);
+        dc.extend(0..12);
+        segment(0xc4, &dc);
+        // The only AC symbol is end-of-block, coded as a single 0 bit
+        let mut ac = vec![0x10, 1];
+        ac.extend_from_slice(&[0; 15]);
+        ac.push(0);
+        segment(0xc4, &ac);
+        let mut scan = vec![components];
+        for id in 1..=components {
+            scan.extend_from_slice(&[id, 0x00]);
+        }
+        scan.extend_from_slice(&[0, 63, 0]);
+        segment(0xda, &scan);
+
+        let mut bits: Vec<bool> = Vec::new();
+        let mut push = |value: u32, count: u32| {
+            bits.extend((0..count).rev().map(|i| value >> i & 1 == 1));
+        };
+        let mut predictions = vec![0; usize::from(components)];
+        for (i, level) in levels.iter().enumerate() {
+            let dc = 8 * (level - 128);
+            let component = i % predictions.len();
+            let diff = dc - predictions[component];
+            predictions[component] = dc;
+            let category = 32 - diff.unsigned_abs().leading_zeros();
+            push(category, 4);
+            let value = if diff < 0 {
+                diff + (1 << category) - 1
+            } else {
+                diff
+            };
+            push(u32::try_from(value).unwrap(), category);
+            push(0, 1);
+        }
+        bits.resize(bits.len().div_ceil(8) * 8, true);
+        for byte in bits.chunks(8) {
+            let byte = byte.iter().fold(0u8, |acc, &bit| acc << 1 | u8::from(bit));
+            out.push(byte);
+            if byte == 0xff {
+                out.push(0);
+            }
+        }
+        out.extend_from_slice(&[0xff, 0xd9]);
+        out
+    }
+
+    #[test]
+    fn decodes_baseline_jpeg() {
+        let gray = decode(&flat_jpeg(16, 8, 1, &[200, 50])).unwrap();
+        assert_eq!((gray.width, gray.height), (16, 8));
+        assert_eq!(&gray.pixels[..3], &[200; 3]);
+        assert_eq!(&gray.pixels[8 * 3..8 * 3 + 3], &[50; 3]);
+        assert_eq!(&gray.pixels[(7 * 16 + 15) * 3..], &[50; 3]);
+
+        // Y = 128, Cb = 128, Cr = 200 is a saturated red
+        let color = decode(&flat_jpeg(5, 3, 3, &[128, 128, 200])).unwrap();
+        assert_eq!((color.width, color.height), (5, 3));
+        assert_eq!(&color.pixels[..3], &[229, 77, 128]);
+
+        let mut progressive = flat_jpeg(8, 8, 1, &[0]);
+        let sof = progressive.windows(2).position(|w| w == [0xff, 0xc0]);
+        progressive[sof.unwrap() + 1] = 0xc2;
+        assert!(decode(&progressive).is_err());
+        assert!(decode(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn prepares_clip_input() {
+        // A 4x2 image: left half black, right half white
+        let mut image = RgbImage::new(4, 2).unwrap();
+        for y in 0..2 {
+            for x in 2..4 {
+                image.put(x, y, [255; 3]);
+            }
+        }
+        let input = clip_input(&image, 2);
+        assert_eq!(input.len(), 3 * 2 * 2);
+        // The center crop keeps the middle two columns
+        for c in 0..3 {
+            let plane = &input[c * 4..c * 4 + 4];
+            let black = -CLIP_MEAN[c] / CLIP_STD[c];
+            let white = (1.0 - CLIP_MEAN[c]) / CLIP_STD[c];
+            for (value, expected) in plane.iter().zip([black, white, black, white]) {
+                assert!((value - expected).abs() < 1e-5, "{value} != {expected}");
+            }
+        }
+    }
+}
@@ -11,6 +11,10 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokenizers::Tokenizer;
 
+pub mod audio;
+pub mod extract;
+pub mod image;
+
 // --- Config Types ---
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -50,6 +54,43 @@ impl ChunkingConfig {
             overlap,
         })
     }
+
+    /// Splits `text` into windows of `chunk_size` words overlapping by
+    /// `overlap`; text that fits in one window comes back whole.
+    #[must_use]
+    pub fn split(&self, text: &str) -> Vec<String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let chunk_size = self.chunk_size;
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let overlap_size = (chunk_size as f64 * self.overlap) as usize;
+        let step_size = chunk_size.saturating_sub(overlap_size);
+
+        if words.len() <= chunk_size {
+            // No chunking needed
+            return vec![text.to_string()];
+        }
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < words.len() {
+            let end = (start + chunk_size).min(words.len());
+            let chunk = words[start..end].join(" ");
+            chunks.push(chunk);
+
+            if end == words.len() {
+                break;
+            }
+
+            start += step_size;
+        }
+
+        chunks
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -92,6 +133,11 @@ pub trait Vectorizer: Send + Sync {
 
 pub struct MultiVectorizer {
     pub models: HashMap<String, Arc<dyn Vectorizer>>,
+    /// Image models, keyed like `models`. An image model must embed into the
+    /// same space as the text model of its metric.
+    pub images: HashMap<String, Arc<ImageVectorizer>>,
+    /// Audio models, keyed and sharing spaces like `images`.
+    pub audio: HashMap<String, Arc<AudioVectorizer>>,
 }
 
 impl MultiVectorizer {
@@ -99,6 +145,8 @@ impl MultiVectorizer {
     pub fn new() -> Self {
         Self {
             models: HashMap::new(),
+            images: HashMap::new(),
+            audio: HashMap::new(),
         }
     }
 }
@@ -114,18 +162,30 @@ impl MultiVectorizer {
         self.models.insert(metric.to_string(), vectorizer);
     }
 
-    /// Vectorizes text using a specific metric (routes to the correct internal model).
-    ///
-    /// # Errors
-    /// Returns an error if no vectorizer is available or if vectorization fails.
-    pub async fn vectorize_for(&self, texts: Vec<String>, metric: &str) -> Result<Vec<Vec<f64>>> {
-        let metric_key = match metric.to_lowercase().as_str() {
+    pub fn add_image(&mut self, metric: &str, vectorizer: Arc<ImageVectorizer>) {
+        self.images.insert(metric.to_string(), vectorizer);
+    }
+
+    pub fn add_audio(&mut self, metric: &str, vectorizer: Arc<AudioVectorizer>) {
+        self.audio.insert(metric.to_string(), vectorizer);
+    }
+
+    fn metric_key(metric: &str) -> &str {
+        match metric.to_lowercase().as_str() {
             "l2" | "euclidean" => "l2",
             "cosine" => "cosine",
             "poincare" => "poincare",
             "lorentz" => "lorentz",
             _ => metric,
-        };
+        }
+    }
+
+    /// Vectorizes text using a specific metric (routes to the correct internal model).
+    ///
+    /// # Errors
+    /// Returns an error if no vectorizer is available or if vectorization fails.
+    pub async fn vectorize_for(&self, texts: Vec<String>, metric: &str) -> Result<Vec<Vec<f64>>> {
+        let metric_key = Self::metric_key(metric);
 
         if let Some(v) = self.models.get(metric_key) {
             v.vectorize(texts).await
@@ -142,6 +202,44 @@ impl MultiVectorizer {
             }
         }
     }
+
+    /// Vectorizes images with the image model for `metric`, falling back
+    /// like [`Self::vectorize_for`].
+    ///
+    /// # Errors
+    /// Returns an error if no image model is configured or inference fails.
+    pub async fn vectorize_images_for(
+        &self,
+        images: Vec<image::RgbImage>,
+        metric: &str,
+    ) -> Result<Vec<Vec<f64>>> {
+        let vectorizer = self
+            .images
+            .get(Self::metric_key(metric))
+            .or_else(|| self.images.get("l2"))
+            .or_else(|| self.images.values().next())
+            .ok_or_else(|| anyhow!("No image vectorizer available"))?;
+        vectorizer.vectorize(images).await
+    }
+
+    /// Vectorizes audio clips with the audio model for `metric`, falling
+    /// back like [`Self::vectorize_for`].
+    ///
+    /// # Errors
+    /// Returns an error if no audio model is configured or inference fails.
+    pub async fn vectorize_audio_for(
+        &self,
+        clips: Vec<audio::Audio>,
+        metric: &str,
+    ) -> Result<Vec<Vec<f64>>> {
+        let vectorizer = self
+            .audio
+            .get(Self::metric_key(metric))
+            .or_else(|| self.audio.get("l2"))
+            .or_else(|| self.audio.values().next())
+            .ok_or_else(|| anyhow!("No audio vectorizer available"))?;
+        vectorizer.vectorize(clips).await
+    }
 }
 
 // --- Local ONNX Vectorizer ---
@@ -329,20 +427,20 @@ impl OnnxVectorizer {
         })
     }
 
-    fn normalize(&self, vec: &mut Vec<f64>) {
+    fn normalize(vec: &mut Vec<f64>, metric: Metric, dimension: usize) {
         const EPSILON: f64 = 1e-12; // Use stricter epsilon for hyperbolic geometry
         let mut norm_sq: f64 = vec.iter().map(|x| x * x).sum();
         let mut norm = norm_sq.sqrt();
 
-        match self.metric {
+        match metric {
             Metric::Poincare => {
                 // Task: Project to Poincare Ball (Dimension N)
                 // Case 1: Model outputs N+1 dims (Lorentz Point/Hyperboloid)
                 // Result: Dimensionality reduction (e.g. 129 -> 128)
-                if vec.len() == self.dimension + 1 {
+                if vec.len() == dimension + 1 {
                     let x0 = vec[0];
                     let denom = (1.0 + x0).max(EPSILON);
-                    let mut projected = Vec::with_capacity(self.dimension);
+                    let mut projected = Vec::with_capacity(dimension);
                     for &x_val in vec.iter().skip(1) {
                         projected.push(x_val / denom);
                     }
@@ -379,13 +477,13 @@ impl OnnxVectorizer {
 
                 // Case 1: Model outputs N-1 dims (Spatial/Tangent vector)
                 // Result: Dimension expansion (e.g. 128 -> 129)
-                if vec.len() == self.dimension - 1 {
+                if vec.len() == dimension - 1 {
                     let spatial_norm_sq = norm_sq;
                     let x0 = (1.0 + spatial_norm_sq).sqrt();
                     vec.insert(0, x0);
                 }
                 // Case 2: Model outputs N dims (already Hyperboloid format)
-                else if vec.len() == self.dimension {
+                else if vec.len() == dimension {
                     // Constraint: -x0^2 + |x|^2 = -1  =>  x0 = sqrt(1 + |x|^2)
                     let spatial_norm_sq: f64 = vec[1..].iter().map(|x| x * x).sum();
                     vec[0] = (1.0 + spatial_norm_sq).sqrt(); // Enforce upper sheet constraint
@@ -409,41 +507,6 @@ impl OnnxVectorizer {
         }
     }
 
-    /// Split text into chunks with overlap (word-based for simplicity)
-    fn split_into_chunks(text: &str, config: &ChunkingConfig) -> Vec<String> {
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let chunk_size = config.chunk_size;
-        #[allow(
-            clippy::cast_possible_truncation,
-            clippy::cast_sign_loss,
-            clippy::cast_precision_loss
-        )]
-        let overlap_size = (chunk_size as f64 * config.overlap) as usize;
-        let step_size = chunk_size.saturating_sub(overlap_size);
-
-        if words.len() <= chunk_size {
-            // No chunking needed
-            return vec![text.to_string()];
-        }
-
-        let mut chunks = Vec::new();
-        let mut start = 0;
-
-        while start < words.len() {
-            let end = (start + chunk_size).min(words.len());
-            let chunk = words[start..end].join(" ");
-            chunks.push(chunk);
-
-            if end == words.len() {
-                break;
-            }
-
-            start += step_size;
-        }
-
-        chunks
-    }
-
     /// Aggregate chunk embeddings via mean pooling
     fn aggregate_embeddings(chunk_embeddings: &[Vec<f64>]) -> Vec<f64> {
         if chunk_embeddings.is_empty() {
@@ -523,7 +586,8 @@ impl Vectorizer for OnnxVectorizer {
                 .into_iter()
                 .enumerate()
                 .flat_map(|(text_idx, text)| {
-                    OnnxVectorizer::split_into_chunks(&text, config)
+                    config
+                        .split(&text)
                         .into_iter()
                         .map(move |chunk| (text_idx, chunk))
                 })
@@ -708,13 +772,335 @@ impl OnnxVectorizer {
         }
 
         for vec in &mut final_vectors {
-            self.normalize(vec);
+            Self::normalize(vec, self.metric, self.dimension);
         }
 
         Ok(final_vectors)
     }
 }
 
+// --- Local ONNX Image Vectorizer (CLIP vision tower) ---
+
+/// Embeds images with the vision half of a CLIP-style ONNX model.
+///
+/// Paired with an [`OnnxVectorizer`] running the matching text tower, text
+/// queries retrieve images stored in the same collection.
+pub struct ImageVectorizer {
+    session: Mutex<Session>,
+    dimension: usize,
+    metric: Metric,
+}
+
+impl ImageVectorizer {
+    /// Input side length used when the model does not fix one.
+    const DEFAULT_IMAGE_SIZE: usize = 224;
+
+    /// Creates a new `ImageVectorizer` from a local ONNX vision model.
+    ///
+    /// # Errors
+    /// Returns error if model loading fails.
+    pub fn new(model_path: &str, dimension: usize, metric: Metric) -> Result<Self> {
+        let session = Session::builder()
+            .map_err(|e| anyhow::anyhow!("Ort session builder failed: {e}"))?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(|e| anyhow::anyhow!("Ort optimization failure: {e}"))?
+            .with_intra_threads(4)
+            .map_err(|e| anyhow::anyhow!("Ort thread configuration failure: {e}"))?
+            .commit_from_file(model_path)
+            .map_err(|e| anyhow::anyhow!("Ort session commit failed for path {model_path}: {e}"))?;
+
+        Ok(Self {
+            session: Mutex::new(session),
+            dimension,
+            metric,
+        })
+    }
+
+    /// Downloads the vision model `model_file` (default
+    /// `onnx/vision_model.onnx`) of `model_id` from the HF Hub.
+    ///
+    /// # Errors
+    /// Returns error if the download or model loading fails.
+    pub fn new_from_hf(
+        model_id: &str,
+        hf_token: Option<&str>,
+        dimension: usize,
+        metric: Metric,
+        model_file: Option<String>,
+    ) -> Result<Self> {
+        use hf_hub::api::sync::ApiBuilder;
+
+        let mut builder = ApiBuilder::new().with_progress(false);
+        if let Some(token) = hf_token {
+            if !token.is_empty() {
+                builder = builder.with_token(Some(token.to_string()));
+            }
+        }
+        let api = builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("HF API error: {e}"))?;
+        let repo = api.model(model_id.to_string());
+
+        let filename = model_file.unwrap_or_else(|| "onnx/vision_model.onnx".to_string());
+        let model_path = repo
+            .get(&filename)
+            .map_err(|e| anyhow::anyhow!("Failed to download {filename}: {e}"))?;
+        for suffix in &[".data", "_data"] {
+            let _ = repo.get(&format!("{filename}{suffix}"));
+        }
+
+        let vectorizer = Self::new(
+            model_path
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid model path"))?,
+            dimension,
+            metric,
+        )?;
+        eprintln!("🚀 Image model activated: {model_id} ({dimension}d, metric={metric:?})");
+        Ok(vectorizer)
+    }
+
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Embeds images decoded with [`image::decode`], one vector per image.
+    ///
+    /// # Errors
+    /// Returns an error if inference fails.
+    #[allow(clippy::unused_async)] // Async like `Vectorizer::vectorize`
+    pub async fn vectorize(&self, images: Vec<image::RgbImage>) -> Result<Vec<Vec<f64>>> {
+        if images.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut session_guard = self
+            .session
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Session lock poisoned"))?;
+        let input = session_guard
+            .inputs()
+            .iter()
+            .find(|input| input.name() == "pixel_values")
+            .or_else(|| session_guard.inputs().first())
+            .ok_or_else(|| anyhow::anyhow!("Image model has no inputs"))?;
+        let input_name = input.name().to_string();
+        let size = match input.dtype() {
+            ort::value::ValueType::Tensor { shape, .. } => shape
+                .last()
+                .and_then(|&side| usize::try_from(side).ok())
+                .filter(|&side| side > 0),
+            _ => None,
+        }
+        .unwrap_or(Self::DEFAULT_IMAGE_SIZE);
+
+        let batch_size = images.len();
+        let mut pixels = Vec::with_capacity(batch_size * 3 * size * size);
+        for decoded in &images {
+            pixels.extend(image::clip_input(decoded, size));
+        }
+        let pixel_values = Array::from_shape_vec((batch_size, 3, size, size), pixels)?;
+
+        let outputs = session_guard.run(vec![(
+            input_name,
+            Value::from_array(pixel_values)?.into_dyn(),
+        )])?;
+        // Projected embeddings when exported, otherwise the CLS token of the
+        // last hidden state
+        let output = outputs.get("image_embeds").unwrap_or(&outputs[0]);
+        let (shape, data) = output.try_extract_tensor::<f32>()?;
+        first_token_rows(shape, data, self.metric, self.dimension)
+    }
+}
+
+/// One normalized vector per batch row of a `[batch, width]` output, or of
+/// the first token of a `[batch, tokens, width]` one.
+fn first_token_rows(
+    shape: &[i64],
+    data: &[f32],
+    metric: Metric,
+    dimension: usize,
+) -> Result<Vec<Vec<f64>>> {
+    let (rows, stride, width) = match *shape {
+        [rows, width] => (rows, width, width),
+        [rows, tokens, width] => (rows, tokens * width, width),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unexpected output dimension: {}",
+                shape.len()
+            ))
+        }
+    };
+    let (rows, stride, width) = (
+        usize::try_from(rows)?,
+        usize::try_from(stride)?,
+        usize::try_from(width)?,
+    );
+
+    let mut vectors = Vec::with_capacity(rows);
+    for row in 0..rows {
+        let mut vec: Vec<f64> = data[row * stride..row * stride + width]
+            .iter()
+            .map(|&x| f64::from(x))
+            .collect();
+        OnnxVectorizer::normalize(&mut vec, metric, dimension);
+        vectors.push(vec);
+    }
+    Ok(vectors)
+}
+
+// --- Local ONNX Audio Vectorizer (CLAP audio tower) ---
+
+/// Embeds audio clips with the audio half of a CLAP-style ONNX model.
+///
+/// Paired with an [`OnnxVectorizer`] running the matching text tower, text
+/// queries retrieve sounds stored in the same collection. Models taking
+/// `input_features` get CLAP's log-mel spectrogram; any other input gets the
+/// raw 48 kHz waveform.
+pub struct AudioVectorizer {
+    session: Mutex<Session>,
+    dimension: usize,
+    metric: Metric,
+}
+
+impl AudioVectorizer {
+    /// Creates a new `AudioVectorizer` from a local ONNX audio model.
+    ///
+    /// # Errors
+    /// Returns error if model loading fails.
+    pub fn new(model_path: &str, dimension: usize, metric: Metric) -> Result<Self> {
+        let session = Session::builder()
+            .map_err(|e| anyhow::anyhow!("Ort session builder failed: {e}"))?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(|e| anyhow::anyhow!("Ort optimization failure: {e}"))?
+            .with_intra_threads(4)
+            .map_err(|e| anyhow::anyhow!("Ort thread configuration failure: {e}"))?
+            .commit_from_file(model_path)
+            .map_err(|e| anyhow::anyhow!("Ort session commit failed for path {model_path}: {e}"))?;
+
+        Ok(Self {
+            session: Mutex::new(session),
+            dimension,
+            metric,
+        })
+    }
+
+    /// Downloads the audio model `model_file` (default
+    /// `onnx/audio_model.onnx`) of `model_id` from the HF Hub.
+    ///
+    /// # Errors
+    /// Returns error if the download or model loading fails.
+    pub fn new_from_hf(
+        model_id: &str,
+        hf_token: Option<&str>,
+        dimension: usize,
+        metric: Metric,
+        model_file: Option<String>,
+    ) -> Result<Self> {
+        use hf_hub::api::sync::ApiBuilder;
+
+        let mut builder = ApiBuilder::new().with_progress(false);
+        if let Some(token) = hf_token {
+            if !token.is_empty() {
+                builder = builder.with_token(Some(token.to_string()));
+            }
+        }
+        let api = builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("HF API error: {e}"))?;
+        let repo = api.model(model_id.to_string());
+
+        let filename = model_file.unwrap_or_else(|| "onnx/audio_model.onnx".to_string());
+        let model_path = repo
+            .get(&filename)
+            .map_err(|e| anyhow::anyhow!("Failed to download {filename}: {e}"))?;
+        for suffix in &[".data", "_data"] {
+            let _ = repo.get(&format!("{filename}{suffix}"));
+        }
+
+        let vectorizer = Self::new(
+            model_path
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid model path"))?,
+            dimension,
+            metric,
+        )?;
+        eprintln!("🚀 Audio model activated: {model_id} ({dimension}d, metric={metric:?})");
+        Ok(vectorizer)
+    }
+
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Embeds clips decoded with [`audio::decode`], one vector per clip.
+    ///
+    /// # Errors
+    /// Returns an error if inference fails.
+    #[allow(clippy::unused_async)] // Async like `Vectorizer::vectorize`
+    pub async fn vectorize(&self, clips: Vec<audio::Audio>) -> Result<Vec<Vec<f64>>> {
+        if clips.is_empty() {
+            return Ok(vec![]);
+        }
+        let batch_size = clips.len();
+        let waveforms: Vec<Vec<f32>> = clips.iter().map(audio::clap_waveform).collect();
+
+        let mut session_guard = self
+            .session
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Session lock poisoned"))?;
+        let mut inputs: Vec<(String, Value)> = Vec::new();
+        let uses_features = session_guard
+            .inputs()
+            .iter()
+            .any(|input| input.name() == "input_features");
+        for input in session_guard.inputs() {
+            let name = input.name();
+            let rank = match input.dtype() {
+                ort::value::ValueType::Tensor { shape, .. } => shape.len(),
+                _ => 0,
+            };
+            match name {
+                "input_features" => {
+                    let mut features = Vec::new();
+                    for waveform in &waveforms {
+                        features.extend(audio::clap_features(waveform));
+                    }
+                    let frames = features.len() / (batch_size * audio::CLAP_MEL_BINS);
+                    let shape = if rank == 3 {
+                        vec![batch_size, frames, audio::CLAP_MEL_BINS]
+                    } else {
+                        vec![batch_size, 1, frames, audio::CLAP_MEL_BINS]
+                    };
+                    let arr = ArrayD::from_shape_vec(shape, features)?;
+                    inputs.push((name.to_string(), Value::from_array(arr)?.into_dyn()));
+                }
+                // Clips are cut to the window rather than fused from crops
+                "is_longer" => {
+                    let arr = Array2::from_elem((batch_size, 1), false);
+                    inputs.push((name.to_string(), Value::from_array(arr)?.into_dyn()));
+                }
+                _ if !uses_features && inputs.is_empty() => {
+                    let samples = waveforms.concat();
+                    let arr = Array2::from_shape_vec((batch_size, audio::CLAP_SAMPLES), samples)?;
+                    inputs.push((name.to_string(), Value::from_array(arr)?.into_dyn()));
+                }
+                _ => {}
+            }
+        }
+        if inputs.is_empty() {
+            return Err(anyhow::anyhow!("Audio model has no inputs"));
+        }
+
+        let outputs = session_guard.run(inputs)?;
+        let output = outputs.get("audio_embeds").unwrap_or(&outputs[0]);
+        let (shape, data) = output.try_extract_tensor::<f32>()?;
+        first_token_rows(shape, data, self.metric, self.dimension)
+    }
+}
+
 // --- Remote API Vectorizer ---
 
 pub struct RemoteVectorizer {
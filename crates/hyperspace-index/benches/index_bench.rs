@@ -0,0 +1,139 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hyperspace_core::{EuclideanMetric, GlobalConfig, QuantizationMode, SearchParams};
+use hyperspace_index::test_utils::{
+    build_index, build_index_with_metadata, clustered_gaussians, Dataset,
+};
+use std::collections::HashMap;
+
+const DIM: usize = 64;
+const POINTS: usize = 10_000;
+const QUERIES: usize = 200;
+const M: usize = 16;
+const EF_CONSTRUCTION: usize = 100;
+const TOP_K: usize = 10;
+
+fn dataset() -> Dataset {
+    clustered_gaussians(42, POINTS, QUERIES, DIM, 50, 0.3)
+}
+
+fn config() -> GlobalConfig {
+    let config = GlobalConfig::default();
+    config.set_m(M);
+    config.set_ef_construction(EF_CONSTRUCTION);
+    config
+}
+
+fn params(ef_search: usize) -> SearchParams {
+    SearchParams {
+        top_k: TOP_K,
+        ef_search,
+        ..Default::default()
+    }
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let data = clustered_gaussians(42, 2_000, 0, DIM, 50, 0.3);
+    let mut group = c.benchmark_group("insert");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(data.points.len() as u64));
+    group.bench_function("build_2000", |b| {
+        b.iter(|| {
+            black_box(build_index::<DIM, EuclideanMetric>(
+                &data.points,
+                M,
+                EF_CONSTRUCTION,
+            ))
+        })
+    });
+    group.finish();
+}
+
+fn bench_search_ef(c: &mut Criterion) {
+    let data = dataset();
+    let built = build_index::<DIM, EuclideanMetric>(&data.points, M, EF_CONSTRUCTION);
+    let no_filter = HashMap::new();
+    let mut group = c.benchmark_group("search_ef");
+    for ef in [16, 32, 64, 128, 256] {
+        let params = params(ef);
+        group.bench_with_input(BenchmarkId::from_parameter(ef), &params, |b, params| {
+            let mut queries = data.queries.iter().cycle();
+            b.iter(|| {
+                let query = queries.next().unwrap();
+                black_box(built.index.search(query, &no_filter, &[], params))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_filtered(c: &mut Criterion) {
+    let data = dataset();
+    // Point i carries every tag, so `mod10` keeps 10% and `mod100` 1% of it
+    let built = build_index_with_metadata::<DIM, EuclideanMetric>(
+        &data.points,
+        config(),
+        QuantizationMode::None,
+        |i| {
+            HashMap::from([
+                ("mod10".to_string(), (i % 10).to_string()),
+                ("mod100".to_string(), (i % 100).to_string()),
+            ])
+        },
+    );
+    let params = params(64);
+    let mut group = c.benchmark_group("search_filtered");
+    for (name, filter) in [
+        ("none", HashMap::new()),
+        (
+            "10pct",
+            HashMap::from([("mod10".to_string(), "3".to_string())]),
+        ),
+        (
+            "1pct",
+            HashMap::from([("mod100".to_string(), "7".to_string())]),
+        ),
+    ] {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &filter, |b, filter| {
+            let mut queries = data.queries.iter().cycle();
+            b.iter(|| {
+                let query = queries.next().unwrap();
+                black_box(built.index.search(query, filter, &[], &params))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_quantization(c: &mut Criterion) {
+    let data = dataset();
+    let params = params(64);
+    let no_filter = HashMap::new();
+    let mut group = c.benchmark_group("search_quantization");
+    for (name, mode) in [
+        ("none", QuantizationMode::None),
+        ("scalar_i8", QuantizationMode::ScalarI8),
+        ("binary", QuantizationMode::Binary),
+    ] {
+        let built =
+            build_index_with_metadata::<DIM, EuclideanMetric>(&data.points, config(), mode, |_| {
+                HashMap::new()
+            });
+        group.bench_function(name, |b| {
+            let mut queries = data.queries.iter().cycle();
+            b.iter(|| {
+                let query = queries.next().unwrap();
+                black_box(built.index.search(query, &no_filter, &[], &params))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_search_ef,
+    bench_filtered,
+    bench_quantization
+);
+criterion_main!(benches);
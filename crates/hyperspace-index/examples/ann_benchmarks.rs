@@ -0,0 +1,252 @@
+//! Recall/QPS runs in the format of ann-benchmarks.
+//!
+//! Builds an index over a dataset's train vectors, then runs its test queries
+//! once per `--ef` value and prints one CSV row per run with the columns of
+//! ann-benchmarks' `data_export.py`: `k-nn` is recall@k, `qps` queries per
+//! second, `p50`..`p999` query latency in milliseconds and `build` the build
+//! time in seconds. The rows can be plotted next to ann-benchmarks' own
+//! exports.
+//!
+//! ```text
+//! cargo run --release -p hyperspace-index --example ann_benchmarks -- \
+//!     --train sift_base.fvecs --test sift_query.fvecs \
+//!     --neighbors sift_groundtruth.ivecs --metric euclidean \
+//!     --dataset sift-128-euclidean --ef 10,20,40,80,160,320 > sift.csv
+//! ```
+//!
+//! Datasets are read as `.fvecs` (neighbours as `.ivecs`), the format the
+//! SIFT and GIST sets ship in. Without `--neighbors` the ground truth is
+//! computed by brute force. Without `--train` a synthetic 32-dimensional
+//! set is used. `--metric angular` normalises every vector and searches by
+//! cosine.
+
+use hyperspace_core::{CosineMetric, EuclideanMetric, GlobalConfig, Metric, SearchParams};
+use hyperspace_index::test_utils::{brute_force, build_index_with, clustered_gaussians, Dataset};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::time::Instant;
+
+struct Options {
+    train: Option<String>,
+    test: Option<String>,
+    neighbors: Option<String>,
+    metric: String,
+    dataset: Option<String>,
+    count: usize,
+    m: usize,
+    ef_construction: usize,
+    ef: Vec<usize>,
+}
+
+impl Options {
+    fn parse() -> Result<Self, String> {
+        let mut options = Self {
+            train: None,
+            test: None,
+            neighbors: None,
+            metric: "euclidean".to_string(),
+            dataset: None,
+            count: 10,
+            m: 16,
+            ef_construction: 200,
+            ef: vec![10, 20, 40, 80, 120, 200, 400, 800],
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+            let number = |v: &str| v.parse::<usize>().map_err(|e| format!("{flag}: {e}"));
+            match flag.as_str() {
+                "--train" => options.train = Some(value),
+                "--test" => options.test = Some(value),
+                "--neighbors" => options.neighbors = Some(value),
+                "--metric" => options.metric = value,
+                "--dataset" => options.dataset = Some(value),
+                "--count" => options.count = number(&value)?,
+                "--m" => options.m = number(&value)?,
+                "--ef-construction" => options.ef_construction = number(&value)?,
+                "--ef" => {
+                    options.ef = value.split(',').map(number).collect::<Result<_, _>>()?;
+                }
+                _ => return Err(format!("Unknown option {flag}")),
+            }
+        }
+        if !matches!(options.metric.as_str(), "euclidean" | "angular") {
+            return Err(format!(
+                "--metric must be euclidean or angular, got {}",
+                options.metric
+            ));
+        }
+        if options.train.is_some() != options.test.is_some() {
+            return Err("--train and --test go together".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Vectors of a `.fvecs` file (`.ivecs` with `int`): each one is its
+/// dimension as a little-endian `i32` followed by that many 4-byte values.
+fn read_vecs<T>(path: &str, decode: fn([u8; 4]) -> T) -> Result<Vec<Vec<T>>, String> {
+    let mut data = Vec::new();
+    std::fs::File::open(Path::new(path))
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("{path}: {e}"))?;
+    let word = |at: usize| -> Option<[u8; 4]> { data.get(at..at + 4)?.try_into().ok() };
+    let mut vectors = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let dim = word(pos)
+            .map(i32::from_le_bytes)
+            .filter(|&d| d > 0)
+            .ok_or_else(|| format!("{path}: bad vector header at byte {pos}"))?
+            as usize;
+        let vector = (0..dim)
+            .map(|i| word(pos + 4 + 4 * i).map(decode))
+            .collect::<Option<Vec<T>>>()
+            .ok_or_else(|| format!("{path}: truncated vector at byte {pos}"))?;
+        vectors.push(vector);
+        pos += 4 + 4 * dim;
+    }
+    Ok(vectors)
+}
+
+fn normalise(v: &mut [f64]) {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+fn percentile(sorted_ms: &[f64], p: f64) -> f64 {
+    let at = ((sorted_ms.len() as f64 - 1.0) * p).round() as usize;
+    sorted_ms.get(at).copied().unwrap_or(0.0)
+}
+
+/// Builds the index and prints one row per `ef`.
+fn run<const N: usize, Mt: Metric<N>>(
+    options: &Options,
+    dataset_name: &str,
+    data: &Dataset,
+    truth: Option<Vec<Vec<u32>>>,
+) {
+    let k = options.count;
+    let truth = truth.unwrap_or_else(|| {
+        eprintln!(
+            "Computing ground truth for {} queries...",
+            data.queries.len()
+        );
+        data.queries
+            .iter()
+            .map(|q| brute_force::<N, Mt>(&data.points, q, k))
+            .collect()
+    });
+
+    let config = GlobalConfig::default();
+    config.set_m(options.m);
+    config.set_ef_construction(options.ef_construction);
+    eprintln!("Building index over {} points...", data.points.len());
+    let started = Instant::now();
+    let built = build_index_with::<N, Mt>(&data.points, config);
+    let build_secs = started.elapsed().as_secs_f64();
+
+    println!("algorithm,parameters,dataset,count,k-nn,qps,p50,p95,p99,p999,build");
+    let no_filter = HashMap::new();
+    for &ef in &options.ef {
+        let params = SearchParams {
+            top_k: k,
+            ef_search: ef,
+            ..Default::default()
+        };
+        let mut latencies_ms = Vec::with_capacity(data.queries.len());
+        let mut recall = 0.0;
+        let total = Instant::now();
+        for (query, expected) in data.queries.iter().zip(&truth) {
+            let started = Instant::now();
+            let found = built.index.search(query, &no_filter, &[], &params);
+            latencies_ms.push(started.elapsed().as_secs_f64() * 1000.0);
+            let expected = &expected[..expected.len().min(k)];
+            let hits = found.iter().filter(|(id, _)| expected.contains(id)).count();
+            recall += hits as f64 / expected.len().max(1) as f64;
+        }
+        let elapsed = total.elapsed().as_secs_f64();
+        latencies_ms.sort_by(f64::total_cmp);
+        let queries = data.queries.len().max(1) as f64;
+        println!(
+            "hyperspace-hnsw,\"HyperspaceHnsw(m={}, ef_construction={}, ef_search={ef})\",{dataset_name},{k},{:.6},{:.3},{:.4},{:.4},{:.4},{:.4},{build_secs:.3}",
+            options.m,
+            options.ef_construction,
+            recall / queries,
+            queries / elapsed.max(f64::EPSILON),
+            percentile(&latencies_ms, 0.50),
+            percentile(&latencies_ms, 0.95),
+            percentile(&latencies_ms, 0.99),
+            percentile(&latencies_ms, 0.999),
+        );
+    }
+}
+
+/// Calls `run` with the index dimension fixed at compile time.
+macro_rules! dispatch {
+    ($dim:expr, $metric:ty, $args:tt, [$($n:literal),*]) => {
+        match $dim {
+            $($n => run::<$n, $metric> $args,)*
+            other => return Err(format!(
+                "Dimension {other} is not built in; add it to the list in ann_benchmarks.rs"
+            )),
+        }
+    };
+}
+
+fn main() -> Result<(), String> {
+    let options = Options::parse()?;
+    let (mut data, truth, default_name) = match (&options.train, &options.test) {
+        (Some(train), Some(test)) => {
+            let decode = |b: [u8; 4]| f64::from(f32::from_le_bytes(b));
+            let data = Dataset {
+                points: read_vecs(train, decode)?,
+                queries: read_vecs(test, decode)?,
+            };
+            let truth = options
+                .neighbors
+                .as_deref()
+                .map(|path| read_vecs(path, |b| i32::from_le_bytes(b) as u32))
+                .transpose()?;
+            (data, truth, "custom")
+        }
+        _ => (
+            clustered_gaussians(42, 20_000, 1_000, 32, 50, 0.3),
+            None,
+            "synthetic-32",
+        ),
+    };
+    if options.metric == "angular" {
+        data.points.iter_mut().for_each(|v| normalise(v));
+        data.queries.iter_mut().for_each(|v| normalise(v));
+    }
+    let dim = data.points.first().map_or(0, Vec::len);
+    if data.queries.iter().any(|q| q.len() != dim) {
+        return Err("Train and test vectors differ in dimension".to_string());
+    }
+    let name = options
+        .dataset
+        .clone()
+        .unwrap_or_else(|| format!("{default_name}-{}", options.metric));
+
+    // The dimensions of the ann-benchmarks datasets, and the synthetic set
+    if options.metric == "angular" {
+        dispatch!(
+            dim,
+            CosineMetric,
+            (&options, &name, &data, truth),
+            [25, 32, 50, 64, 96, 100, 128, 200, 256, 784, 960]
+        );
+    } else {
+        dispatch!(
+            dim,
+            EuclideanMetric,
+            (&options, &name, &data, truth),
+            [25, 32, 50, 64, 96, 100, 128, 200, 256, 784, 960]
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Extra layer-0 entry points for strongly clustered data.
+//!
+//! The greedy descent through the upper layers ends in one basin; when the
+//! data falls into well-separated clusters that basin can be the wrong one
+//! and a small `ef` never leaves it. With `GlobalConfig::entry_points` set to
+//! `k`, the index keeps the medoids of `k` clusters of a node sample and
+//! starts layer-0 search from the descent result plus the few medoids closest
+//! to the query. The medoids are rebuilt whenever the index has doubled in
+//! size since the last build.
+
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Sample size per requested entry point.
+pub const SAMPLE_PER_POINT: usize = 32;
+
+/// k-medoids refinement rounds after farthest-point seeding.
+const ROUNDS: usize = 3;
+
+#[derive(Debug, Default)]
+struct Seeds {
+    ids: Vec<u32>,
+    k: usize,
+    built_at: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct EntryPoints {
+    seeds: RwLock<Seeds>,
+    rebuilding: AtomicBool,
+}
+
+impl EntryPoints {
+    pub fn get(&self) -> Vec<u32> {
+        self.seeds.read().ids.clone()
+    }
+
+    /// Claims the rebuild when the seeds are missing, built for another `k`,
+    /// or built when the index had under half of `count` nodes. The caller
+    /// must follow up with [`EntryPoints::set`].
+    pub fn claim_rebuild(&self, count: usize, k: usize) -> bool {
+        let due = {
+            let seeds = self.seeds.read();
+            seeds.k != k || count >= seeds.built_at.max(1) * 2
+        };
+        due && !self.rebuilding.swap(true, Ordering::AcqRel)
+    }
+
+    pub fn set(&self, ids: Vec<u32>, k: usize, count: usize) {
+        *self.seeds.write() = Seeds {
+            ids,
+            k,
+            built_at: count,
+        };
+        self.rebuilding.store(false, Ordering::Release);
+    }
+}
+
+/// Picks up to `k` medoids among `n` points: farthest-point seeding, then a
+/// few rounds of assigning points to their nearest medoid and moving each
+/// medoid to the member with the least total distance to its cluster.
+/// Returns point positions.
+pub fn medoids(n: usize, k: usize, dist: impl Fn(usize, usize) -> f64) -> Vec<usize> {
+    if n == 0 || k == 0 {
+        return Vec::new();
+    }
+    let mut chosen = vec![0];
+    let mut nearest: Vec<f64> = (0..n).map(|i| dist(i, 0)).collect();
+    while chosen.len() < k.min(n) {
+        let (far, d) = nearest
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or((0, 0.0), |(i, d)| (i, *d));
+        if d <= 0.0 {
+            break; // Every point coincides with a medoid already.
+        }
+        chosen.push(far);
+        for (i, best) in nearest.iter_mut().enumerate() {
+            *best = best.min(dist(i, far));
+        }
+    }
+
+    for _ in 0..ROUNDS {
+        let mut clusters = vec![Vec::new(); chosen.len()];
+        for i in 0..n {
+            let c = (0..chosen.len())
+                .min_by(|&a, &b| dist(i, chosen[a]).total_cmp(&dist(i, chosen[b])))
+                .unwrap_or(0);
+            clusters[c].push(i);
+        }
+        let mut moved = false;
+        for (medoid, members) in chosen.iter_mut().zip(&clusters) {
+            let cost = |m: usize| members.iter().map(|&j| dist(m, j)).sum::<f64>();
+            let best = members
+                .iter()
+                .copied()
+                .min_by(|&a, &b| cost(a).total_cmp(&cost(b)))
+                .unwrap_or(*medoid);
+            if best != *medoid {
+                *medoid = best;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn medoids_land_one_per_cluster() {
+        // Three tight clusters on a line, listed interleaved.
+        let points: Vec<f64> = (0..30)
+            .map(|i| f64::from(i % 3) * 100.0 + f64::from(i / 3) * 0.1)
+            .collect();
+        let dist = |a: usize, b: usize| (points[a] - points[b]).abs();
+        let mut picked: Vec<i64> = medoids(points.len(), 3, dist)
+            .into_iter()
+            .map(|i| (points[i] / 100.0).round() as i64)
+            .collect();
+        picked.sort_unstable();
+        assert_eq!(picked, vec![0, 1, 2]);
+        assert_eq!(medoids(0, 3, dist), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn rebuild_is_claimed_once_per_doubling() {
+        let seeds = EntryPoints::default();
+        assert!(seeds.claim_rebuild(10, 4));
+        assert!(!seeds.claim_rebuild(10, 4));
+        seeds.set(vec![1, 2], 4, 10);
+        assert!(!seeds.claim_rebuild(19, 4));
+        assert!(seeds.claim_rebuild(20, 4));
+        seeds.set(vec![1, 2], 4, 20);
+        assert!(seeds.claim_rebuild(20, 8));
+    }
+}
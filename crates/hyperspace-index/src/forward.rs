@@ -0,0 +1,198 @@
+//! Compact per-node metadata (the forward store).
+//!
+//! A `HashMap<String, String>` per node repeats every key string in every
+//! node and pays a hash table per node. Here keys are interned once per
+//! index, and a node's metadata is one sorted run of `(key id, value end)`
+//! pairs plus all of its values packed into one string: two allocations per
+//! node however many keys it has. [`ForwardStore::get`] still hands out the
+//! map for callers that want one.
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+type KeyId = u32;
+
+#[derive(Debug, Default)]
+struct Row {
+    /// Sorted by key id; the second field is where the value ends in `values`.
+    fields: Box<[(KeyId, u32)]>,
+    values: Box<str>,
+}
+
+#[derive(Debug, Default)]
+pub struct ForwardStore {
+    /// Interned keys, append-only so ids stay valid without a lock.
+    names: boxcar::Vec<Arc<str>>,
+    ids: DashMap<Arc<str>, KeyId>,
+    rows: DashMap<u32, Row>,
+}
+
+/// Borrowed view of one node's metadata.
+pub struct RowView<'a> {
+    store: &'a ForwardStore,
+    row: &'a Row,
+}
+
+impl RowView<'_> {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key_id = *self.store.ids.get(key)?;
+        let i = self
+            .row
+            .fields
+            .binary_search_by_key(&key_id, |&(id, _)| id)
+            .ok()?;
+        Some(self.value_at(i))
+    }
+
+    fn value_at(&self, i: usize) -> &str {
+        let start = if i == 0 {
+            0
+        } else {
+            self.row.fields[i - 1].1 as usize
+        };
+        &self.row.values[start..self.row.fields[i].1 as usize]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.row
+            .fields
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(key_id, _))| {
+                let name = self.store.names.get(key_id as usize)?;
+                Some((&**name, self.value_at(i)))
+            })
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+}
+
+impl ForwardStore {
+    fn intern(&self, key: &str) -> KeyId {
+        if let Some(id) = self.ids.get(key) {
+            return *id;
+        }
+        // The entry lock keeps two writers from pushing the same key.
+        *self
+            .ids
+            .entry(Arc::from(key))
+            .or_insert_with(|| self.names.push(Arc::from(key)) as KeyId)
+    }
+
+    /// Replaces the metadata of node `id`.
+    pub fn insert(&self, id: u32, meta: HashMap<String, String>) {
+        let mut fields: Vec<(KeyId, String)> = meta
+            .into_iter()
+            .map(|(k, v)| (self.intern(&k), v))
+            .collect();
+        fields.sort_unstable_by_key(|&(key_id, _)| key_id);
+
+        let mut values = String::with_capacity(fields.iter().map(|(_, v)| v.len()).sum());
+        let fields = fields
+            .into_iter()
+            .map(|(key_id, v)| {
+                values.push_str(&v);
+                (key_id, values.len() as u32)
+            })
+            .collect();
+        self.rows.insert(
+            id,
+            Row {
+                fields,
+                values: values.into_boxed_str(),
+            },
+        );
+    }
+
+    pub fn remove(&self, id: u32) {
+        self.rows.remove(&id);
+    }
+
+    pub fn get(&self, id: u32) -> Option<HashMap<String, String>> {
+        let row = self.rows.get(&id)?;
+        Some(
+            RowView {
+                store: self,
+                row: &row,
+            }
+            .to_map(),
+        )
+    }
+
+    /// Runs `f` on the metadata of node `id` without copying it.
+    pub fn with<R>(&self, id: u32, f: impl FnOnce(&RowView<'_>) -> R) -> Option<R> {
+        let row = self.rows.get(&id)?;
+        Some(f(&RowView {
+            store: self,
+            row: &row,
+        }))
+    }
+
+    /// Visits every node's metadata. `f` must not write to the store.
+    pub fn for_each(&self, mut f: impl FnMut(u32, &RowView<'_>)) {
+        for item in &self.rows {
+            f(
+                *item.key(),
+                &RowView {
+                    store: self,
+                    row: item.value(),
+                },
+            );
+        }
+    }
+
+    pub fn ids(&self) -> Vec<u32> {
+        self.rows.iter().map(|item| *item.key()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_and_share_keys() {
+        let store = ForwardStore::default();
+        let a = HashMap::from([
+            ("lang".to_string(), "en".to_string()),
+            ("title".to_string(), "Häring".to_string()),
+            ("empty".to_string(), String::new()),
+        ]);
+        let b = HashMap::from([("lang".to_string(), "de".to_string())]);
+        store.insert(1, a.clone());
+        store.insert(2, b.clone());
+        store.insert(3, HashMap::new());
+
+        assert_eq!(store.get(1), Some(a));
+        assert_eq!(store.get(2), Some(b));
+        assert_eq!(store.get(3), Some(HashMap::new()));
+        assert_eq!(store.get(4), None);
+        assert_eq!(
+            store.with(1, |row| row.get("title").map(str::to_string)),
+            Some(Some("Häring".to_string()))
+        );
+        assert_eq!(store.with(2, |row| row.get("title").is_none()), Some(true));
+        // "lang" is stored once for both nodes.
+        assert_eq!(store.names.count(), 3);
+
+        store.insert(2, HashMap::from([("lang".to_string(), "fr".to_string())]));
+        assert_eq!(
+            store.with(2, |row| row.get("lang") == Some("fr")),
+            Some(true)
+        );
+        assert_eq!(store.len(), 3);
+    }
+}
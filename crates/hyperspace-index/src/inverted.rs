@@ -0,0 +1,213 @@
+//! Tag bitmaps of the metadata index (`key:value` and `_txt:token`).
+//!
+//! A snapshot of a tag-heavy collection holds millions of serialized
+//! bitmaps, and deserializing all of them dominated load time. After a load
+//! they stay frozen in the snapshot bytes (the memory-mapped file for
+//! on-disk snapshots) and a bitmap is only materialized the first time it is
+//! looked up or written. Saving copies still-frozen bitmaps byte for byte.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+use roaring::RoaringBitmap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Snapshot bytes that frozen bitmaps point into.
+pub type FrozenBytes = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+#[derive(Default)]
+pub struct InvertedIndex {
+    live: DashMap<String, RoaringBitmap>,
+    /// Not yet materialized: where the serialized bitmap sits in `bytes`.
+    frozen: DashMap<String, Range<usize>>,
+    bytes: Option<FrozenBytes>,
+}
+
+impl std::fmt::Debug for InvertedIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InvertedIndex")
+            .field("live", &self.live.len())
+            .field("frozen", &self.frozen.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl InvertedIndex {
+    /// An index whose bitmaps are serialized at `entries` in `bytes`. A key
+    /// listed twice keeps its last range.
+    pub fn frozen(
+        bytes: FrozenBytes,
+        entries: impl IntoIterator<Item = (String, Range<usize>)>,
+    ) -> Self {
+        Self {
+            live: DashMap::new(),
+            frozen: entries.into_iter().collect(),
+            bytes: Some(bytes),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Ref<'_, String, RoaringBitmap>> {
+        self.thaw(key);
+        self.live.get(key)
+    }
+
+    pub fn get_mut(&self, key: &str) -> Option<RefMut<'_, String, RoaringBitmap>> {
+        self.thaw(key);
+        self.live.get_mut(key)
+    }
+
+    pub fn entry(&self, key: String) -> Entry<'_, String, RoaringBitmap> {
+        self.thaw(&key);
+        self.live.entry(key)
+    }
+
+    /// Replaces the bitmap of `key`, frozen or not.
+    pub fn insert(&self, key: String, bitmap: RoaringBitmap) {
+        let entry = self.live.entry(key);
+        self.frozen.remove(entry.key());
+        entry.insert(bitmap);
+    }
+
+    /// Drops the bitmap of `key`, frozen or not.
+    pub fn remove(&self, key: &str) {
+        self.frozen.remove(key);
+        self.live.remove(key);
+    }
+
+    /// Drops every bitmap whose key starts with `prefix`, frozen or not.
+    pub fn remove_prefix(&self, prefix: &str) {
+        self.frozen.retain(|key, _| !key.starts_with(prefix));
+        self.live.retain(|key, _| !key.starts_with(prefix));
+    }
+
+    pub fn len(&self) -> usize {
+        self.live.len() + self.frozen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bitmaps turned into `RoaringBitmap`s so far.
+    pub fn materialized(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Key, cardinality and serialized size of every bitmap. Frozen bitmaps
+    /// are decoded to count them but stay frozen.
+    pub fn bitmap_sizes(&self) -> Vec<(String, u64, u64)> {
+        let mut out = Vec::with_capacity(self.len());
+        if let Some(bytes) = &self.bytes {
+            let bytes = (**bytes).as_ref();
+            for item in &self.frozen {
+                let range = item.value().clone();
+                let len =
+                    RoaringBitmap::deserialize_from(&bytes[range.clone()]).map_or(0, |b| b.len());
+                out.push((item.key().clone(), len, range.len() as u64));
+            }
+        }
+        for item in &self.live {
+            let bitmap = item.value();
+            out.push((
+                item.key().clone(),
+                bitmap.len(),
+                bitmap.serialized_size() as u64,
+            ));
+        }
+        out
+    }
+
+    /// Every bitmap in the portable roaring format.
+    pub fn serialize(&self) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+        let mut out = Vec::with_capacity(self.len());
+        // Frozen first: a bitmap thawed meanwhile then shows up again, newer,
+        // instead of being missed.
+        if let Some(bytes) = &self.bytes {
+            let bytes = (**bytes).as_ref();
+            for item in &self.frozen {
+                out.push((item.key().clone(), bytes[item.value().clone()].to_vec()));
+            }
+        }
+        for item in &self.live {
+            let mut buf = Vec::with_capacity(item.value().serialized_size());
+            item.value().serialize_into(&mut buf)?;
+            out.push((item.key().clone(), buf));
+        }
+        Ok(out)
+    }
+
+    fn thaw(&self, key: &str) {
+        if self.frozen.is_empty() || !self.frozen.contains_key(key) {
+            return;
+        }
+        // The shard stays locked until the bitmap is in, so a concurrent
+        // lookup either thaws it itself or waits for this one.
+        if let Entry::Vacant(slot) = self.live.entry(key.to_owned()) {
+            if let (Some((_, range)), Some(bytes)) = (self.frozen.remove(key), &self.bytes) {
+                let bitmap =
+                    RoaringBitmap::deserialize_from(&(**bytes).as_ref()[range]).unwrap_or_default();
+                slot.insert(bitmap);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frozen_index(bitmaps: &[(&str, &[u32])]) -> InvertedIndex {
+        let mut bytes = Vec::new();
+        let mut entries = Vec::new();
+        for (key, ids) in bitmaps {
+            let start = bytes.len();
+            ids.iter()
+                .copied()
+                .collect::<RoaringBitmap>()
+                .serialize_into(&mut bytes)
+                .unwrap();
+            entries.push(((*key).to_string(), start..bytes.len()));
+        }
+        InvertedIndex::frozen(Arc::new(bytes), entries)
+    }
+
+    #[test]
+    fn materializes_only_touched_bitmaps() {
+        let index = frozen_index(&[("color:red", &[1, 2]), ("color:blue", &[3])]);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.materialized(), 0);
+
+        assert!(index.get("color:red").unwrap().contains(2));
+        assert!(index.get("color:green").is_none());
+        assert_eq!(index.materialized(), 1);
+
+        index.entry("color:blue".into()).or_default().insert(4);
+        assert_eq!(
+            index.get("color:blue").unwrap().iter().collect::<Vec<_>>(),
+            [3, 4]
+        );
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    fn serialize_keeps_frozen_and_live_bitmaps() {
+        let index = frozen_index(&[("a:1", &[1]), ("b:2", &[2])]);
+        index.insert("a:1".into(), RoaringBitmap::from_iter([5]));
+
+        let mut saved = index.serialize().unwrap();
+        saved.sort();
+        let decoded: Vec<(String, Vec<u32>)> = saved
+            .into_iter()
+            .map(|(k, v)| {
+                let bitmap = RoaringBitmap::deserialize_from(&v[..]).unwrap();
+                (k, bitmap.iter().collect())
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            [("a:1".to_string(), vec![5]), ("b:2".to_string(), vec![2])]
+        );
+        assert_eq!(index.materialized(), 1);
+    }
+}
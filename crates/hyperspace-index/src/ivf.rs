@@ -0,0 +1,184 @@
+//! Coarse routing cells (IVF) in front of the graph.
+//!
+//! With `GlobalConfig::ivf_cells` set to `k`, the index keeps `k` centroids
+//! (the medoids of a node sample, copied so they outlive deletes) and puts
+//! every node in the cell of its nearest centroid. A search ranks the
+//! centroids against the query and only considers the points of the
+//! `ivf_probes` closest cells, intersected with its filter: layer 0 then
+//! walks that set like any filtered search, or scans it when it is under
+//! `HS_FILTER_BRUTEFORCE_THRESHOLD`. Cells are built once the index holds
+//! [`MIN_CELL_POINTS`] points per cell and rebuilt whenever it has doubled
+//! since; nodes indexed in between join their nearest existing cell.
+
+use parking_lot::RwLock;
+use roaring::RoaringBitmap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Points per cell below which cells are not worth building.
+pub const MIN_CELL_POINTS: usize = 32;
+
+#[derive(Debug)]
+struct Cells<const N: usize> {
+    centroids: Vec<[f64; N]>,
+    members: Vec<RoaringBitmap>,
+    k: usize,
+    built_at: usize,
+}
+
+impl<const N: usize> Default for Cells<N> {
+    fn default() -> Self {
+        Self {
+            centroids: Vec::new(),
+            members: Vec::new(),
+            k: 0,
+            built_at: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct IvfCells<const N: usize> {
+    cells: RwLock<Cells<N>>,
+    rebuilding: AtomicBool,
+}
+
+impl<const N: usize> IvfCells<N> {
+    /// Claims the rebuild when `count` points fill `k` cells and the cells
+    /// are missing, built for another `k`, or built when the index had under
+    /// half of `count` nodes. The caller must follow up with
+    /// [`IvfCells::set`].
+    pub fn claim_rebuild(&self, count: usize, k: usize) -> bool {
+        if count < k * MIN_CELL_POINTS {
+            return false;
+        }
+        let due = {
+            let cells = self.cells.read();
+            cells.k != k || count >= cells.built_at.max(1) * 2
+        };
+        due && !self.rebuilding.swap(true, Ordering::AcqRel)
+    }
+
+    /// Installs freshly built cells; `members[i]` holds the nodes nearest to
+    /// `centroids[i]`.
+    pub fn set(
+        &self,
+        centroids: Vec<[f64; N]>,
+        members: Vec<RoaringBitmap>,
+        k: usize,
+        count: usize,
+    ) {
+        *self.cells.write() = Cells {
+            centroids,
+            members,
+            k,
+            built_at: count,
+        };
+        self.rebuilding.store(false, Ordering::Release);
+    }
+
+    /// Gives up a claimed rebuild without new cells.
+    pub fn release(&self) {
+        self.rebuilding.store(false, Ordering::Release);
+    }
+
+    /// Adds node `id` to the cell whose centroid is nearest by `dist`. A
+    /// no-op before the cells are built.
+    pub fn assign(&self, id: u32, dist: impl Fn(&[f64; N]) -> f64) {
+        let Some(cell) = nearest(&self.cells.read().centroids, dist) else {
+            return;
+        };
+        // A rebuild may have swapped the cells in between.
+        if let Some(members) = self.cells.write().members.get_mut(cell) {
+            members.insert(id);
+        }
+    }
+
+    /// Nodes of the `probes` cells whose centroids are nearest by `dist`;
+    /// `None` before the cells are built.
+    pub fn probe(&self, probes: usize, dist: impl Fn(&[f64; N]) -> f64) -> Option<RoaringBitmap> {
+        let cells = self.cells.read();
+        if cells.centroids.is_empty() {
+            return None;
+        }
+        let mut ranked: Vec<(f64, usize)> = cells
+            .centroids
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (dist(c), i))
+            .collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut out = RoaringBitmap::new();
+        for (_, cell) in ranked.into_iter().take(probes.max(1)) {
+            out |= &cells.members[cell];
+        }
+        Some(out)
+    }
+
+    /// Number of nodes per cell, deleted ones included; empty before the
+    /// cells are built.
+    pub fn sizes(&self) -> Vec<u64> {
+        self.cells
+            .read()
+            .members
+            .iter()
+            .map(RoaringBitmap::len)
+            .collect()
+    }
+}
+
+/// Position of the centroid nearest by `dist`.
+pub fn nearest<const N: usize>(
+    centroids: &[[f64; N]],
+    dist: impl Fn(&[f64; N]) -> f64,
+) -> Option<usize> {
+    centroids
+        .iter()
+        .map(&dist)
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l1(a: [f64; 1], b: f64) -> f64 {
+        (a[0] - b).abs()
+    }
+
+    #[test]
+    fn probe_returns_nearest_cells() {
+        let ivf = IvfCells::<1>::default();
+        assert!(ivf.probe(1, |c| l1(*c, 0.0)).is_none());
+
+        ivf.set(
+            vec![[0.0], [10.0], [20.0]],
+            vec![
+                RoaringBitmap::from_iter([1, 2]),
+                RoaringBitmap::from_iter([3]),
+                RoaringBitmap::from_iter([4]),
+            ],
+            3,
+            4,
+        );
+        ivf.assign(5, |c| l1(*c, 19.0));
+        assert_eq!(ivf.sizes(), vec![2, 1, 2]);
+
+        let near = ivf.probe(1, |c| l1(*c, 18.0)).unwrap();
+        assert_eq!(near.iter().collect::<Vec<_>>(), vec![4, 5]);
+        let two = ivf.probe(2, |c| l1(*c, 12.0)).unwrap();
+        assert_eq!(two.iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn rebuild_waits_for_enough_points() {
+        let ivf = IvfCells::<1>::default();
+        assert!(!ivf.claim_rebuild(4 * MIN_CELL_POINTS - 1, 4));
+        assert!(ivf.claim_rebuild(4 * MIN_CELL_POINTS, 4));
+        assert!(!ivf.claim_rebuild(4 * MIN_CELL_POINTS, 4));
+        ivf.set(Vec::new(), Vec::new(), 4, 4 * MIN_CELL_POINTS);
+        assert!(!ivf.claim_rebuild(6 * MIN_CELL_POINTS, 4));
+        assert!(ivf.claim_rebuild(8 * MIN_CELL_POINTS, 4));
+    }
+}
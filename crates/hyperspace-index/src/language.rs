@@ -0,0 +1,210 @@
+//! Per-document language detection for `auto` analyzers.
+//!
+//! With the BM25 language set to [`AUTO`], every document is tokenized with
+//! the analyzer (stemmer and stopwords) of its own language, recorded under
+//! [`LANGUAGE_KEY`] in its metadata. Detection needs no model: the writing
+//! system settles Cyrillic, Arabic, Devanagari and Tamil text, and Latin
+//! text goes to the language whose most frequent words it uses most. Too
+//! little evidence leaves a text undetected.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// BM25 language that selects an analyzer per document and query.
+pub const AUTO: &str = "auto";
+
+/// Metadata key holding a document's detected language. Set it on insert
+/// to skip detection.
+pub const LANGUAGE_KEY: &str = "__hs_lang";
+
+/// Function words a Latin-script text needs before it is attributed.
+const MIN_WORD_HITS: usize = 2;
+
+/// The most frequent function words of each Latin-script language; their
+/// share of a text identifies it better than the (stemmer-oriented)
+/// stopword lists do.
+const LATIN: &[(&str, &[&str])] = &[
+    (
+        "english",
+        &[
+            "the", "and", "of", "to", "is", "in", "that", "it", "was", "for", "with", "are",
+            "this", "on", "be", "not", "have",
+        ],
+    ),
+    (
+        "german",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "den", "mit", "sich",
+            "des", "auf", "im", "sind", "von",
+        ],
+    ),
+    (
+        "french",
+        &[
+            "le", "la", "les", "et", "est", "des", "un", "une", "du", "que", "pas", "pour", "dans",
+            "avec", "sur", "il", "nous",
+        ],
+    ),
+    (
+        "spanish",
+        &[
+            "el", "los", "las", "y", "es", "del", "una", "que", "por", "con", "para", "no", "se",
+            "como", "pero", "está", "muy",
+        ],
+    ),
+    (
+        "italian",
+        &[
+            "il", "gli", "e", "è", "di", "che", "una", "per", "con", "non", "sono", "della", "nel",
+            "anche", "come", "questo", "ma",
+        ],
+    ),
+    (
+        "portuguese",
+        &[
+            "o", "os", "e", "é", "do", "da", "que", "não", "uma", "com", "para", "em", "um", "mas",
+            "são", "muito", "foi",
+        ],
+    ),
+    (
+        "dutch",
+        &[
+            "de", "het", "een", "en", "is", "van", "niet", "dat", "op", "zijn", "met", "voor",
+            "ik", "ook", "maar", "wel", "er",
+        ],
+    ),
+    (
+        "swedish",
+        &[
+            "och", "att", "det", "är", "som", "en", "på", "för", "med", "inte", "jag", "har", "av",
+            "till", "den", "ett", "om",
+        ],
+    ),
+    (
+        "norwegian",
+        &[
+            "og", "det", "er", "ikke", "som", "på", "en", "jeg", "til", "med", "har", "av", "for",
+            "den", "et", "var", "seg",
+        ],
+    ),
+    (
+        "danish",
+        &[
+            "og", "det", "er", "ikke", "at", "som", "på", "en", "jeg", "til", "med", "har", "af",
+            "for", "den", "et", "var",
+        ],
+    ),
+    (
+        "finnish",
+        &[
+            "ja", "on", "ei", "se", "että", "oli", "hän", "mutta", "kun", "ole", "myös", "tai",
+            "niin", "joka", "ovat", "tämä", "vain",
+        ],
+    ),
+    (
+        "hungarian",
+        &[
+            "a", "az", "és", "hogy", "nem", "egy", "is", "van", "meg", "de", "ez", "csak", "már",
+            "volt", "még", "mint", "vagy",
+        ],
+    ),
+    (
+        "romanian",
+        &[
+            "și", "în", "este", "nu", "cu", "pe", "un", "o", "care", "de", "la", "să", "mai",
+            "din", "pentru", "sunt", "dar",
+        ],
+    ),
+    (
+        "turkish",
+        &[
+            "ve", "bir", "bu", "da", "de", "için", "ile", "çok", "ne", "gibi", "daha", "olarak",
+            "ama", "değil", "var", "kadar", "sonra",
+        ],
+    ),
+];
+
+fn latin_words() -> &'static [(&'static str, HashSet<&'static str>)] {
+    static SETS: OnceLock<Vec<(&str, HashSet<&str>)>> = OnceLock::new();
+    SETS.get_or_init(|| {
+        LATIN
+            .iter()
+            .map(|&(language, words)| (language, words.iter().copied().collect()))
+            .collect()
+    })
+}
+
+/// Language of `text` as an analyzer name, or `None` when it can't tell.
+pub fn detect(text: &str) -> Option<&'static str> {
+    let (mut letters, mut cyrillic, mut arabic, mut devanagari, mut tamil) = (0, 0, 0, 0, 0);
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        match c {
+            '\u{0400}'..='\u{04FF}' => cyrillic += 1,
+            '\u{0600}'..='\u{06FF}' => arabic += 1,
+            '\u{0900}'..='\u{097F}' => devanagari += 1,
+            '\u{0B80}'..='\u{0BFF}' => tamil += 1,
+            _ => {}
+        }
+    }
+    if letters == 0 {
+        return None;
+    }
+    for (count, language) in [
+        (cyrillic, "russian"),
+        (arabic, "arabic"),
+        (devanagari, "hindi"),
+        (tamil, "tamil"),
+    ] {
+        if count * 2 > letters {
+            return Some(language);
+        }
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut best: Option<(&str, usize)> = None;
+    let mut tied = false;
+    for (language, set) in latin_words() {
+        let hits = words.iter().filter(|w| set.contains(w.as_str())).count();
+        match best {
+            Some((_, top)) if hits < top => {}
+            Some((_, top)) if hits == top => tied = true,
+            _ => {
+                best = Some((language, hits));
+                tied = false;
+            }
+        }
+    }
+    match best {
+        Some((language, hits)) if hits >= MIN_WORD_HITS && !tied => Some(language),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_script_and_stopwords() {
+        assert_eq!(detect("Привет, как дела у тебя"), Some("russian"));
+        assert_eq!(
+            detect("The cat sat on the mat and it was happy"),
+            Some("english")
+        );
+        assert_eq!(
+            detect("Der Hund und die Katze sind nicht im Haus"),
+            Some("german")
+        );
+        assert_eq!(
+            detect("Le chat est sur la table avec les enfants"),
+            Some("french")
+        );
+        assert_eq!(detect("hyperbolic embeddings"), None);
+        assert_eq!(detect("1234"), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Flat layer-0 adjacency storage.
+//!
+//! Layer 0 holds every node and is where search spends almost all of its time,
+//! so its neighbor lists live in one contiguous, fixed-stride arena instead of
+//! a heap-allocated `Vec` behind a lock per node. Each row is
+//! `[len, n_0, n_1, ..., n_{degree-1}]` and rows of consecutive ids sit next
+//! to each other in memory.
+//!
+//! Readers are lock-free: they load `len` and then the slots. Writers on the
+//! same row are serialized through a striped mutex. Slots only ever hold ids
+//! of existing nodes, so a reader racing a rewrite sees a valid (if mixed)
+//! neighbor set, which graph search tolerates.
+
+use crate::NodeId;
+use parking_lot::{Mutex, RwLockReadGuard};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Rows per allocated chunk (power of two).
+const CHUNK_SHIFT: usize = 12;
+const CHUNK_ROWS: usize = 1 << CHUNK_SHIFT;
+const CHUNK_MASK: usize = CHUNK_ROWS - 1;
+
+/// Number of writer stripes. Rows map to stripes by id.
+const WRITE_STRIPES: usize = 1024;
+
+pub(crate) struct Layer0Arena {
+    degree: usize,
+    chunks: boxcar::Vec<Box<[AtomicU32]>>,
+    grow_lock: Mutex<()>,
+    stripes: Box<[Mutex<()>]>,
+}
+
+impl std::fmt::Debug for Layer0Arena {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Layer0Arena")
+            .field("degree", &self.degree)
+            .field("chunks", &self.chunks.count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Layer0Arena {
+    /// Creates an arena whose rows hold up to `degree` neighbors.
+    pub(crate) fn new(degree: usize) -> Self {
+        Self {
+            degree: degree.max(1),
+            chunks: boxcar::Vec::new(),
+            grow_lock: Mutex::new(()),
+            stripes: (0..WRITE_STRIPES).map(|_| Mutex::new(())).collect(),
+        }
+    }
+
+    /// Row capacity for an index built with `m` links per upper layer.
+    ///
+    /// Layer 0 keeps up to `2 * m` links after pruning; the extra `2 * m`
+    /// slots absorb back-links pushed between prunes.
+    pub(crate) fn degree_for_m(m: usize) -> usize {
+        (m * 4).max(32)
+    }
+
+    #[inline]
+    fn stride(&self) -> usize {
+        self.degree + 1
+    }
+
+    #[inline]
+    fn row(&self, id: NodeId) -> Option<&[AtomicU32]> {
+        let id = id as usize;
+        let chunk = self.chunks.get(id >> CHUNK_SHIFT)?;
+        let start = (id & CHUNK_MASK) * self.stride();
+        Some(&chunk[start..start + self.stride()])
+    }
+
+    fn row_or_alloc(&self, id: NodeId) -> &[AtomicU32] {
+        let chunk_idx = id as usize >> CHUNK_SHIFT;
+        if self.chunks.count() <= chunk_idx {
+            let _guard = self.grow_lock.lock();
+            while self.chunks.count() <= chunk_idx {
+                let chunk: Box<[AtomicU32]> = (0..CHUNK_ROWS * self.stride())
+                    .map(|_| AtomicU32::new(0))
+                    .collect();
+                self.chunks.push(chunk);
+            }
+        }
+        self.row(id).unwrap_or(&[])
+    }
+
+    /// Lock-free view of the current neighbor list of `id`.
+    #[inline]
+    pub(crate) fn neighbors(&self, id: NodeId) -> &[AtomicU32] {
+        match self.row(id) {
+            Some(row) => {
+                let len = (row[0].load(Ordering::Acquire) as usize).min(self.degree);
+                &row[1..=len]
+            }
+            None => &[],
+        }
+    }
+
+    pub(crate) fn to_vec(&self, id: NodeId) -> Vec<NodeId> {
+        self.neighbors(id)
+            .iter()
+            .map(|slot| slot.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Appends one link. Returns `false` when the row is already full.
+    pub(crate) fn push(&self, id: NodeId, dst: NodeId) -> bool {
+        let row = self.row_or_alloc(id);
+        let _guard = self.stripe(id).lock();
+        let len = row[0].load(Ordering::Relaxed) as usize;
+        if len >= self.degree {
+            return false;
+        }
+        row[len + 1].store(dst, Ordering::Relaxed);
+        row[0].store(len as u32 + 1, Ordering::Release);
+        true
+    }
+
+    /// Replaces the neighbor list of `id`, truncating to the row capacity.
+    pub(crate) fn set(&self, id: NodeId, links: &[NodeId]) {
+        let row = self.row_or_alloc(id);
+        let _guard = self.stripe(id).lock();
+        Self::write_row(row, links, self.degree);
+    }
+
+    /// Read-modify-write of a row under its writer stripe.
+    pub(crate) fn update(&self, id: NodeId, f: impl FnOnce(&mut Vec<NodeId>)) {
+        let row = self.row_or_alloc(id);
+        let _guard = self.stripe(id).lock();
+        let len = (row[0].load(Ordering::Relaxed) as usize).min(self.degree);
+        let mut links: Vec<NodeId> = row[1..=len]
+            .iter()
+            .map(|slot| slot.load(Ordering::Relaxed))
+            .collect();
+        f(&mut links);
+        Self::write_row(row, &links, self.degree);
+    }
+
+    /// Hints the CPU to start loading the row of `id`.
+    #[inline]
+    pub(crate) fn prefetch(&self, id: NodeId) {
+        if let Some(row) = self.row(id) {
+            crate::prefetch(row);
+        }
+    }
+
+    fn write_row(row: &[AtomicU32], links: &[NodeId], degree: usize) {
+        let len = links.len().min(degree);
+        for (slot, &n) in row[1..=len].iter().zip(links) {
+            slot.store(n, Ordering::Relaxed);
+        }
+        row[0].store(len as u32, Ordering::Release);
+    }
+
+    #[inline]
+    fn stripe(&self, id: NodeId) -> &Mutex<()> {
+        &self.stripes[id as usize % WRITE_STRIPES]
+    }
+}
+
+/// Neighbor list of one node on one layer.
+pub(crate) enum Links<'a> {
+    Flat(&'a [AtomicU32]),
+    Locked(RwLockReadGuard<'a, Vec<NodeId>>),
+}
+
+impl Links<'_> {
+    pub(crate) fn iter(&self) -> LinksIter<'_> {
+        match self {
+            Links::Flat(slots) => LinksIter::Flat(slots.iter()),
+            Links::Locked(guard) => LinksIter::Locked(guard.iter()),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Links::Flat(slots) => slots.len(),
+            Links::Locked(guard) => guard.len(),
+        }
+    }
+}
+
+pub(crate) enum LinksIter<'a> {
+    Flat(std::slice::Iter<'a, AtomicU32>),
+    Locked(std::slice::Iter<'a, NodeId>),
+}
+
+impl Iterator for LinksIter<'_> {
+    type Item = NodeId;
+
+    #[inline]
+    fn next(&mut self) -> Option<NodeId> {
+        match self {
+            LinksIter::Flat(it) => it.next().map(|slot| slot.load(Ordering::Relaxed)),
+            LinksIter::Locked(it) => it.next().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_span_chunks_and_respect_capacity() {
+        let arena = Layer0Arena::new(4);
+        let far = (CHUNK_ROWS + 3) as NodeId;
+        assert!(arena.neighbors(far).is_empty());
+
+        for n in 0..4 {
+            assert!(arena.push(far, n));
+        }
+        assert!(!arena.push(far, 99));
+        assert_eq!(arena.to_vec(far), vec![0, 1, 2, 3]);
+
+        arena.update(far, |links| links.retain(|&n| n % 2 == 0));
+        assert_eq!(arena.to_vec(far), vec![0, 2]);
+
+        arena.set(1, &[7, 8, 9, 10, 11]);
+        assert_eq!(arena.to_vec(1), vec![7, 8, 9, 10]);
+        assert!(arena.to_vec(0).is_empty());
+    }
+}
@@ -8,12 +8,21 @@
 #![allow(clippy::too_many_lines)]
 #![allow(clippy::cast_possible_truncation)]
 
+mod cardinality;
+mod entry_points;
+mod forward;
+mod inverted;
+mod ivf;
+pub mod language;
+mod layer0;
+mod metadata;
+mod numeric;
 pub mod stopwords;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
 pub mod tokenizer;
 
-use dashmap::DashMap;
 use parking_lot::{Mutex, RwLock};
-use rand::Rng;
 use rayon::prelude::*;
 #[cfg(feature = "persistence")]
 use rkyv::ser::Serializer;
@@ -24,18 +33,23 @@ use std::cmp::Ordering as CmpOrdering;
 use std::collections::{BinaryHeap, HashMap};
 #[cfg(feature = "persistence")]
 use std::fs::File;
-#[cfg(feature = "persistence")]
-use std::io::Write;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 
 // Imports
+use entry_points::EntryPoints;
+pub use forward::{ForwardStore, RowView};
 use hyperspace_core::vector::{
     BinaryHyperVector, HyperVector, HyperVectorF32, QuantizedHyperVector,
 };
 use hyperspace_core::QuantizationMode;
-use hyperspace_core::{GlobalConfig, Metric};
+use hyperspace_core::{GlobalConfig, HyperspaceError, HyperspaceResult, Metric};
 use hyperspace_store::VectorStore;
+pub use inverted::{FrozenBytes, InvertedIndex};
+use ivf::IvfCells;
+use layer0::{Layer0Arena, Links};
+pub use metadata::MetadataIndex;
+pub use numeric::NumericIndex;
 use std::marker::PhantomData;
 
 #[derive(Archive, Deserialize, Serialize)]
@@ -61,7 +75,8 @@ pub type KeyedBitmaps = Vec<(i64, Vec<u8>)>;
 pub struct SnapshotMetadata {
     // Key -> Serialized RoaringBitmap
     pub inverted: Vec<(String, Vec<u8>)>,
-    // Key -> [(Value, Serialized RoaringBitmap)]
+    // Key -> [(Value, Serialized RoaringBitmap)]; bucketed keys carry the
+    // bucket shift in the key name and bucket numbers as values
     pub numeric: Vec<(String, KeyedBitmaps)>,
     // Serialized RoaringBitmap for deleted items
     pub deleted: Vec<u8>,
@@ -69,120 +84,224 @@ pub struct SnapshotMetadata {
     pub forward: Vec<(u32, Vec<(String, String)>)>,
 }
 
+/// Metadata written on its own, between graph snapshots.
+#[derive(Archive, Deserialize, Serialize)]
+#[archive(check_bytes)]
+pub struct MetadataSnapshotData {
+    // Graph size when taken; the file only applies to a graph of this size
+    pub nodes: u32,
+    pub metadata: SnapshotMetadata,
+}
+
 // Constants are defined later in the file.
 
 use hyperspace_core::FilterExpr;
 
-#[derive(Debug)]
-pub struct MetadataIndex {
-    pub inverted: DashMap<String, RoaringBitmap>,
-    pub numeric: DashMap<String, crossbeam_skiplist::SkipMap<i64, RwLock<RoaringBitmap>>>,
-    pub deleted: RwLock<RoaringBitmap>,
-    pub forward: DashMap<u32, std::collections::HashMap<String, String>>,
-    pub token_df: DashMap<String, u32>,
-    pub doc_token_len: DashMap<u32, u32>,
-    pub term_doc_freq: DashMap<String, Vec<(u32, u16)>>,
-    pub total_token_len: AtomicU64,
-}
-
-impl Default for MetadataIndex {
-    fn default() -> Self {
-        Self {
-            inverted: DashMap::new(),
-            numeric: DashMap::new(),
-            deleted: RwLock::new(RoaringBitmap::new()),
-            forward: DashMap::new(),
-            token_df: DashMap::new(),
-            doc_token_len: DashMap::new(),
-            term_doc_freq: DashMap::new(),
-            total_token_len: AtomicU64::new(0),
-        }
-    }
-}
-
 impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
-    fn metadata_numeric_value(
-        meta: &std::collections::HashMap<String, String>,
-        key: &str,
-    ) -> Option<f64> {
-        if let Some(raw) = meta.get(key) {
-            return raw.parse::<f64>().ok();
+    fn snapshot_node(&self, node: &Node) -> SnapshotNode {
+        let mut layers = Vec::with_capacity(node.upper.len() + 1);
+        layers.push(self.layer0.to_vec(node.id));
+        for layer in &node.upper {
+            layers.push(layer.read().clone());
+        }
+        SnapshotNode {
+            id: node.id,
+            layers,
         }
-        let typed_key = format!("__hs_typed__{key}");
-        let raw_typed = meta.get(&typed_key)?;
-        let parsed = serde_json::from_str::<serde_json::Value>(raw_typed).ok()?;
-        parsed.get("v")?.as_f64()
     }
 
-    #[cfg(feature = "persistence")]
-    pub fn save_snapshot(&self, path: &std::path::Path) -> Result<(), String> {
-        let max_layer = self.max_layer.load(Ordering::Relaxed);
-        let entry_point = self.entry_point.load(Ordering::Relaxed);
-
-        let nodes_count = self.nodes.count();
-        let mut snapshot_nodes = Vec::with_capacity(nodes_count);
+    /// Sizes the layer-0 arena so no snapshot row gets truncated on restore.
+    fn layer0_for_snapshot(nodes: &[SnapshotNode], config: &GlobalConfig) -> Layer0Arena {
+        let widest = nodes
+            .iter()
+            .filter_map(|n| n.layers.first())
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        Layer0Arena::new(widest.max(Layer0Arena::degree_for_m(config.m.load(Ordering::Relaxed))))
+    }
 
-        for (_, node) in &self.nodes {
-            let mut layers = Vec::new();
-            for layer_lock in &node.layers {
-                layers.push(layer_lock.read().clone());
-            }
-            snapshot_nodes.push(SnapshotNode {
-                id: node.id,
-                layers,
-            });
+    fn restore_node(arena: &Layer0Arena, s_node: SnapshotNode) -> Node {
+        let mut layers = s_node.layers.into_iter();
+        if let Some(l0) = layers.next() {
+            arena.set(s_node.id, &l0);
+        }
+        Node {
+            id: s_node.id,
+            upper: layers.map(RwLock::new).collect(),
         }
+    }
 
-        let mut inverted_vec = Vec::new();
-        for item in &self.metadata.inverted {
-            let mut buf = Vec::new();
-            item.value()
-                .serialize_into(&mut buf)
-                .map_err(|e| e.to_string())?;
-            inverted_vec.push((item.key().clone(), buf));
+    /// Neighbor list of `node` on `level`, or `None` if the node does not reach it.
+    #[inline]
+    fn links<'a>(&'a self, node: &'a Node, level: usize) -> Option<Links<'a>> {
+        if level == 0 {
+            Some(Links::Flat(self.layer0.neighbors(node.id)))
+        } else {
+            node.upper.get(level - 1).map(|l| Links::Locked(l.read()))
         }
+    }
 
-        let mut numeric_vec = Vec::new();
+    fn snapshot_metadata(&self) -> HyperspaceResult<SnapshotMetadata> {
+        let inverted = self
+            .metadata
+            .inverted
+            .serialize()
+            .map_err(|e| e.to_string())?;
+
+        let mut numeric = Vec::new();
         for item in &self.metadata.numeric {
-            let mut inner_vec = Vec::new();
-            for entry in item.value() {
-                let mut buf = Vec::new();
-                entry
-                    .value()
-                    .read()
-                    .serialize_into(&mut buf)
-                    .map_err(|e| e.to_string())?;
-                inner_vec.push((*entry.key(), buf));
-            }
-            numeric_vec.push((item.key().clone(), inner_vec));
+            let (shift, buckets) = item.value().serialize().map_err(|e| e.to_string())?;
+            numeric.push((numeric::snapshot_key(item.key(), shift), buckets));
         }
 
-        let mut deleted_buf = Vec::new();
+        let mut deleted = Vec::new();
         self.metadata
             .deleted
             .read()
-            .serialize_into(&mut deleted_buf)
+            .serialize_into(&mut deleted)
             .map_err(|e| e.to_string())?;
 
-        let mut forward_vec = Vec::new();
-        for item in &self.metadata.forward {
-            let mut map_vec = Vec::new();
-            for (k, v) in item.value() {
-                map_vec.push((k.clone(), v.clone()));
+        let mut forward = Vec::with_capacity(self.metadata.forward.len());
+        self.metadata.forward.for_each(|id, row| {
+            let map_vec = row
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            forward.push((id, map_vec));
+        });
+
+        Ok(SnapshotMetadata {
+            inverted,
+            numeric,
+            deleted,
+            forward,
+        })
+    }
+
+    /// Rebuilds the metadata index from an archived snapshot living in
+    /// `base`. Tag bitmaps stay frozen in `bytes`, which must hold the same
+    /// data as `base`. Lexical stats are left empty for
+    /// `rebuild_lexical_stats`. Also reports whether any point carries
+    /// metadata.
+    fn restore_metadata(
+        snapshot: &ArchivedSnapshotMetadata,
+        base: &[u8],
+        bytes: FrozenBytes,
+    ) -> (MetadataIndex, bool) {
+        let base = base.as_ptr() as usize;
+        let frozen = snapshot.inverted.iter().map(|(k, v)| {
+            let start = v.as_ptr() as usize - base;
+            (k.as_str().to_owned(), start..start + v.len())
+        });
+        let metadata = MetadataIndex {
+            inverted: InvertedIndex::frozen(bytes, frozen),
+            ..MetadataIndex::default()
+        };
+
+        let numeric: Vec<(String, KeyedBitmaps)> =
+            snapshot.numeric.deserialize(&mut rkyv::Infallible).unwrap();
+        for (k, v) in numeric {
+            let (key, shift) = numeric::parse_snapshot_key(&k);
+            let buckets = v.into_iter().map(|(bucket, bitmap_bytes)| {
+                let bitmap = RoaringBitmap::deserialize_from(&bitmap_bytes[..]).unwrap_or_default();
+                (bucket, bitmap)
+            });
+            metadata
+                .numeric
+                .insert(key.to_string(), NumericIndex::from_buckets(shift, buckets));
+        }
+
+        *metadata.deleted.write() =
+            RoaringBitmap::deserialize_from(&snapshot.deleted[..]).unwrap_or_default();
+
+        let forward: Vec<(u32, Vec<(String, String)>)> =
+            snapshot.forward.deserialize(&mut rkyv::Infallible).unwrap();
+        let mut has_nonempty_metadata = false;
+        for (k, v) in forward {
+            let attributes: std::collections::HashMap<_, _> = v.into_iter().collect();
+            if !attributes.is_empty() {
+                has_nonempty_metadata = true;
             }
-            forward_vec.push((*item.key(), map_vec));
+            metadata.forward.insert(k, attributes);
+        }
+        (metadata, has_nonempty_metadata)
+    }
+
+    /// Writes only the metadata index (filters, payloads, tombstones), far
+    /// cheaper than [`Self::save_snapshot`] for large graphs. The file is
+    /// replaced atomically, and only if the graph still has `nodes` nodes
+    /// once the metadata has been captured; returns whether it was written.
+    #[cfg(feature = "persistence")]
+    pub fn save_metadata_snapshot(
+        &self,
+        path: &std::path::Path,
+        nodes: usize,
+    ) -> HyperspaceResult<bool> {
+        let metadata = self.snapshot_metadata()?;
+        if self.graph_len() != nodes {
+            return Ok(false);
+        }
+        let data = MetadataSnapshotData {
+            nodes: nodes as u32,
+            metadata,
+        };
+        let bytes =
+            rkyv::to_bytes::<_, 1024>(&data).map_err(|e| format!("Serialization error: {e}"))?;
+
+        let tmp = path.with_extension("snap.tmp");
+        hyperspace_store::io::write_file(&tmp, &bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(true)
+    }
+
+    /// Replaces the metadata index with a newer one written by
+    /// [`Self::save_metadata_snapshot`]. Returns `false` and leaves the index
+    /// untouched when the file was taken against a graph of another size.
+    #[cfg(feature = "persistence")]
+    pub fn apply_metadata_snapshot(&mut self, path: &std::path::Path) -> HyperspaceResult<bool> {
+        let file = File::open(path)?;
+        let mmap = Arc::new(unsafe { memmap2::Mmap::map(&file)? });
+        let archived =
+            rkyv::check_archived_root::<MetadataSnapshotData>(&mmap[..]).map_err(|e| {
+                HyperspaceError::Corruption(format!("Metadata snapshot corruption: {e}"))
+            })?;
+        if archived.nodes as usize != self.graph_len() {
+            return Ok(false);
+        }
+
+        let (metadata, has_nonempty_metadata) =
+            Self::restore_metadata(&archived.metadata, &mmap[..], mmap.clone());
+        metadata.set_unindexed_keys(self.config.get_unindexed_keys());
+        metadata.set_synonyms(self.config.get_synonyms());
+        self.metadata = metadata;
+        self.has_nonempty_metadata
+            .store(has_nonempty_metadata, Ordering::Relaxed);
+        if self.entry_point().is_none() {
+            self.reelect_entry_point();
+        }
+        self.metadata
+            .rebuild_lexical_stats(&self.config.get_bm25_params());
+        Ok(true)
+    }
+
+    #[cfg(feature = "persistence")]
+    pub fn save_snapshot(&self, path: &std::path::Path) -> HyperspaceResult<()> {
+        let max_layer = self.max_layer.load(Ordering::Relaxed);
+        let entry_point = self.entry_point.load(Ordering::Relaxed);
+
+        let nodes_count = self.nodes.count();
+        let mut snapshot_nodes = Vec::with_capacity(nodes_count);
+
+        for (_, node) in &self.nodes {
+            snapshot_nodes.push(self.snapshot_node(node));
         }
 
         let data = SnapshotData {
             max_layer,
             entry_point,
             nodes: snapshot_nodes,
-            metadata: SnapshotMetadata {
-                inverted: inverted_vec,
-                numeric: numeric_vec,
-                deleted: deleted_buf,
-                forward: forward_vec,
-            },
+            metadata: self.snapshot_metadata()?,
         };
 
         // Serialize
@@ -194,12 +313,15 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
 
         if let Some(parent) = path.parent() {
             if !parent.exists() {
-                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                std::fs::create_dir_all(parent)?;
             }
         }
 
-        let mut file = File::create(path).map_err(|e| e.to_string())?;
-        file.write_all(&bytes).map_err(|e| e.to_string())?;
+        // Written aside and renamed over: the loaded index may still have
+        // its tag bitmaps mapped from the old file.
+        let tmp = path.with_extension("snap.tmp");
+        hyperspace_store::io::write_file(&tmp, &bytes)?;
+        std::fs::rename(&tmp, path)?;
 
         Ok(())
     }
@@ -210,7 +332,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         storage: Arc<VectorStore>,
         mode: QuantizationMode,
         config: Arc<GlobalConfig>,
-    ) -> Result<Self, String> {
+    ) -> HyperspaceResult<Self> {
         Self::load_snapshot_with_storage_precision(path, storage, mode, config, false)
     }
 
@@ -221,7 +343,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         mode: QuantizationMode,
         config: Arc<GlobalConfig>,
         storage_f32: bool,
-    ) -> Result<Self, String> {
+    ) -> HyperspaceResult<Self> {
         use std::time::Instant;
         let start = Instant::now();
 
@@ -229,25 +351,26 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
 
         // Memory-map the snapshot file for zero-copy access.
         let file = File::open(path).map_err(|e| format!("Failed to open snapshot: {e}"))?;
-        let file_size = file.metadata().map_err(|e| e.to_string())?.len();
+        let file_size = file.metadata()?.len();
         println!("   File size: {:.2} MB", file_size as f64 / 1024.0 / 1024.0);
 
-        let mmap = unsafe {
+        let mmap = Arc::new(unsafe {
             memmap2::MmapOptions::new()
                 .map(&file)
                 .map_err(|e| format!("Failed to mmap snapshot: {e}"))?
-        };
+        });
         let mmap_time = start.elapsed();
         println!("   ✓ Memory-mapped in {:.3}s", mmap_time.as_secs_f64());
 
         // 2. Validate archived data
-        let archived = rkyv::check_archived_root::<SnapshotData>(&mmap)
-            .map_err(|e| format!("Snapshot corruption: {e}"))?;
+        let archived = rkyv::check_archived_root::<SnapshotData>(&mmap[..])
+            .map_err(|e| HyperspaceError::Corruption(format!("Snapshot corruption: {e}")))?;
         let validate_time = start.elapsed();
         println!("   ✓ Validated in {:.3}s", validate_time.as_secs_f64());
 
-        // 3. Deserialize
-        let deserialized: SnapshotData = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        // 3. Deserialize the graph; tag bitmaps stay in the mapping
+        let snapshot_nodes: Vec<SnapshotNode> =
+            archived.nodes.deserialize(&mut rkyv::Infallible).unwrap();
         let deserialize_time = start.elapsed();
         println!(
             "   ✓ Deserialized in {:.3}s",
@@ -255,8 +378,9 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         );
 
         // 4. Reconstruct Graph with progress
-        let total_nodes = deserialized.nodes.len();
+        let total_nodes = snapshot_nodes.len();
         let nodes_bc: boxcar::Vec<Node> = boxcar::Vec::with_capacity(total_nodes);
+        let layer0 = Self::layer0_for_snapshot(&snapshot_nodes, &config);
 
         println!("   ⏳ Reconstructing HNSW graph: {total_nodes} nodes...");
 
@@ -266,7 +390,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             10_000
         };
 
-        for (i, s_node) in deserialized.nodes.into_iter().enumerate() {
+        for (i, s_node) in snapshot_nodes.into_iter().enumerate() {
             // Progress reporting
             if i > 0 && i % progress_interval == 0 {
                 let elapsed = start.elapsed().as_secs_f64();
@@ -278,16 +402,8 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 );
             }
 
-            // Reconstruct node
-            let mut layers = Vec::with_capacity(s_node.layers.len());
-            for s_layer in s_node.layers {
-                layers.push(RwLock::new(s_layer));
-            }
             // boxcar::Vec — push in order; index == s_node.id is guaranteed by sequential snapshot
-            nodes_bc.push(Node {
-                id: s_node.id,
-                layers,
-            });
+            nodes_bc.push(Self::restore_node(&layer0, s_node));
         }
 
         // Sync storage count
@@ -303,37 +419,8 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
 
         println!("   📦 Restoring Metadata Index...");
 
-        let inverted = DashMap::new();
-        for (k, v) in deserialized.metadata.inverted {
-            let bitmap = RoaringBitmap::deserialize_from(&v[..]).unwrap_or_default();
-            inverted.insert(k, bitmap);
-        }
-
-        let numeric = DashMap::new();
-        for (k, v) in deserialized.metadata.numeric {
-            let inner_map = crossbeam_skiplist::SkipMap::new();
-            for (val, bitmap_bytes) in v {
-                let bitmap = RoaringBitmap::deserialize_from(&bitmap_bytes[..]).unwrap_or_default();
-                inner_map.insert(val, RwLock::new(bitmap));
-            }
-            numeric.insert(k, inner_map);
-        }
-
-        let deleted =
-            RoaringBitmap::deserialize_from(&deserialized.metadata.deleted[..]).unwrap_or_default();
-
-        let forward = DashMap::new();
-        let mut has_nonempty_metadata = false;
-        for (k, v) in deserialized.metadata.forward {
-            let mut attributes = std::collections::HashMap::new();
-            for (mk, mv) in v {
-                attributes.insert(mk, mv);
-            }
-            if !attributes.is_empty() {
-                has_nonempty_metadata = true;
-            }
-            forward.insert(k, attributes);
-        }
+        let (metadata, has_nonempty_metadata) =
+            Self::restore_metadata(&archived.metadata, &mmap[..], mmap.clone());
 
         let fast_routing = std::env::var("HS_FAST_ROUTING")
             .is_ok_and(|v| v.to_lowercase() == "true")
@@ -348,19 +435,13 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         let node_count = storage.count();
         let index = Self {
             nodes: nodes_bc,
+            layer0,
             append_lock: Mutex::new(()),
-            metadata: MetadataIndex {
-                inverted,
-                numeric,
-                deleted: RwLock::new(deleted),
-                forward,
-                token_df: DashMap::new(),
-                doc_token_len: DashMap::new(),
-                term_doc_freq: DashMap::new(),
-                total_token_len: AtomicU64::new(0),
-            },
-            entry_point: AtomicU32::new(deserialized.entry_point),
-            max_layer: AtomicU32::new(deserialized.max_layer),
+            metadata,
+            entry_point: AtomicU32::new(archived.entry_point),
+            max_layer: AtomicU32::new(archived.max_layer),
+            entry_points: EntryPoints::default(),
+            ivf: IvfCells::default(),
             storage,
             mode,
             storage_f32,
@@ -373,76 +454,35 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             node_counter: AtomicU32::new(node_count as u32),
             _marker: PhantomData,
         };
-        index.rebuild_lexical_stats();
+        // Snapshots written before deletes re-elected the entry point may
+        // still point at a deleted node.
+        if index.entry_point().is_none() {
+            index.reelect_entry_point();
+        }
+        index
+            .metadata
+            .set_unindexed_keys(index.config.get_unindexed_keys());
+        index.metadata.set_synonyms(index.config.get_synonyms());
+        index
+            .metadata
+            .rebuild_lexical_stats(&index.config.get_bm25_params());
         Ok(index)
     }
-    pub fn save_to_bytes(&self) -> Result<Vec<u8>, String> {
+    pub fn save_to_bytes(&self) -> HyperspaceResult<Vec<u8>> {
         let max_layer = self.max_layer.load(Ordering::Relaxed);
         let entry_point = self.entry_point.load(Ordering::Relaxed);
 
         let nodes_count = self.nodes.count();
         let mut snapshot_nodes = Vec::with_capacity(nodes_count);
         for (_, node) in &self.nodes {
-            let mut layers = Vec::new();
-            for layer in &node.layers {
-                layers.push(layer.read().clone());
-            }
-            snapshot_nodes.push(SnapshotNode {
-                id: node.id,
-                layers,
-            });
-        }
-
-        let mut inverted_vec = Vec::new();
-        for item in &self.metadata.inverted {
-            let mut buf = Vec::new();
-            item.value()
-                .serialize_into(&mut buf)
-                .map_err(|e| e.to_string())?;
-            inverted_vec.push((item.key().clone(), buf));
-        }
-
-        let mut numeric_vec = Vec::new();
-        for item in &self.metadata.numeric {
-            let mut inner_vec = Vec::new();
-            for entry in item.value() {
-                let mut buf = Vec::new();
-                entry
-                    .value()
-                    .read()
-                    .serialize_into(&mut buf)
-                    .map_err(|e| e.to_string())?;
-                inner_vec.push((*entry.key(), buf));
-            }
-            numeric_vec.push((item.key().clone(), inner_vec));
-        }
-
-        let mut deleted_buf = Vec::new();
-        self.metadata
-            .deleted
-            .read()
-            .serialize_into(&mut deleted_buf)
-            .map_err(|e| e.to_string())?;
-
-        let mut forward_vec = Vec::new();
-        for item in &self.metadata.forward {
-            let mut map_vec = Vec::new();
-            for (k, v) in item.value() {
-                map_vec.push((k.clone(), v.clone()));
-            }
-            forward_vec.push((*item.key(), map_vec));
+            snapshot_nodes.push(self.snapshot_node(node));
         }
 
         let snapshot = SnapshotData {
             max_layer,
             entry_point,
             nodes: snapshot_nodes,
-            metadata: SnapshotMetadata {
-                inverted: inverted_vec,
-                numeric: numeric_vec,
-                deleted: deleted_buf,
-                forward: forward_vec,
-            },
+            metadata: self.snapshot_metadata()?,
         };
 
         let bytes = rkyv::to_bytes::<_, 1024>(&snapshot)
@@ -456,58 +496,25 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         storage: Arc<VectorStore>,
         mode: QuantizationMode,
         config: Arc<GlobalConfig>,
-    ) -> Result<Self, String> {
+    ) -> HyperspaceResult<Self> {
         let archived = unsafe { rkyv::archived_root::<SnapshotData>(data) };
 
-        let deserialized: SnapshotData = archived
-            .deserialize(&mut rkyv::Infallible)
-            .map_err(|e| format!("Deserialization error: {e}"))?;
+        let snapshot_nodes: Vec<SnapshotNode> =
+            archived
+                .nodes
+                .deserialize(&mut rkyv::Infallible)
+                .map_err(|e| HyperspaceError::Corruption(format!("Deserialization error: {e}")))?;
 
-        let nodes_bc: boxcar::Vec<Node> = boxcar::Vec::with_capacity(deserialized.nodes.len());
-        for s_node in deserialized.nodes {
-            let mut layers = Vec::new();
-            for s_layer in s_node.layers {
-                layers.push(RwLock::new(s_layer));
-            }
-            nodes_bc.push(Node {
-                id: s_node.id,
-                layers,
-            });
+        let nodes_bc: boxcar::Vec<Node> = boxcar::Vec::with_capacity(snapshot_nodes.len());
+        let layer0 = Self::layer0_for_snapshot(&snapshot_nodes, &config);
+        for s_node in snapshot_nodes {
+            nodes_bc.push(Self::restore_node(&layer0, s_node));
         }
 
         storage.set_count(nodes_bc.count());
 
-        let inverted = DashMap::new();
-        for (k, v) in deserialized.metadata.inverted {
-            let bitmap = RoaringBitmap::deserialize_from(&v[..]).unwrap_or_default();
-            inverted.insert(k, bitmap);
-        }
-
-        let numeric = DashMap::new();
-        for (k, v) in deserialized.metadata.numeric {
-            let inner_map = crossbeam_skiplist::SkipMap::new();
-            for (val, bitmap_bytes) in v {
-                let bitmap = RoaringBitmap::deserialize_from(&bitmap_bytes[..]).unwrap_or_default();
-                inner_map.insert(val, RwLock::new(bitmap));
-            }
-            numeric.insert(k, inner_map);
-        }
-
-        let deleted =
-            RoaringBitmap::deserialize_from(&deserialized.metadata.deleted[..]).unwrap_or_default();
-
-        let forward = DashMap::new();
-        let mut has_nonempty_metadata = false;
-        for (k, v) in deserialized.metadata.forward {
-            let mut attributes = std::collections::HashMap::new();
-            for (mk, mv) in v {
-                attributes.insert(mk, mv);
-            }
-            if !attributes.is_empty() {
-                has_nonempty_metadata = true;
-            }
-            forward.insert(k, attributes);
-        }
+        let (metadata, has_nonempty_metadata) =
+            Self::restore_metadata(&archived.metadata, data, Arc::new(data.to_vec()));
 
         let fast_routing = std::env::var("HS_FAST_ROUTING")
             .is_ok_and(|v| v.to_lowercase() == "true")
@@ -522,19 +529,13 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         let node_count = storage.count();
         let index = Self {
             nodes: nodes_bc,
+            layer0,
             append_lock: Mutex::new(()),
-            metadata: MetadataIndex {
-                inverted,
-                numeric,
-                deleted: RwLock::new(deleted),
-                forward,
-                token_df: DashMap::new(),
-                doc_token_len: DashMap::new(),
-                term_doc_freq: DashMap::new(),
-                total_token_len: AtomicU64::new(0),
-            },
-            entry_point: AtomicU32::new(deserialized.entry_point),
-            max_layer: AtomicU32::new(deserialized.max_layer),
+            metadata,
+            entry_point: AtomicU32::new(archived.entry_point),
+            max_layer: AtomicU32::new(archived.max_layer),
+            entry_points: EntryPoints::default(),
+            ivf: IvfCells::default(),
             storage,
             mode,
             storage_f32: false,
@@ -547,7 +548,18 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             node_counter: AtomicU32::new(node_count as u32),
             _marker: PhantomData,
         };
-        index.rebuild_lexical_stats();
+        // Snapshots written before deletes re-elected the entry point may
+        // still point at a deleted node.
+        if index.entry_point().is_none() {
+            index.reelect_entry_point();
+        }
+        index
+            .metadata
+            .set_unindexed_keys(index.config.get_unindexed_keys());
+        index.metadata.set_synonyms(index.config.get_synonyms());
+        index
+            .metadata
+            .rebuild_lexical_stats(&index.config.get_bm25_params());
         Ok(index)
     }
 
@@ -561,6 +573,9 @@ pub type NodeId = u32;
 
 const MAX_LAYERS: usize = 16;
 
+/// Neighbors scored per `dist_many` call in the layer-0 hot loop.
+const DIST_BATCH: usize = 16;
+
 #[derive(Debug)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct HnswIndex<const N: usize, M: Metric<N>> {
@@ -570,6 +585,9 @@ pub struct HnswIndex<const N: usize, M: Metric<N>> {
     // wired in index_node(). Reads are lock-free O(1).
     nodes: boxcar::Vec<Node>,
 
+    // Layer-0 adjacency in one contiguous fixed-stride arena (index == NodeId).
+    layer0: Layer0Arena,
+
     // Guard for sequential pushes in both VectorStore and boxcar
     append_lock: Mutex<()>,
 
@@ -582,6 +600,12 @@ pub struct HnswIndex<const N: usize, M: Metric<N>> {
     // Current max layer
     max_layer: AtomicU32,
 
+    // Cluster medoids used as extra layer-0 starts (see `entry_points`)
+    entry_points: EntryPoints,
+
+    // Coarse cells searches are narrowed to (see `ivf`)
+    ivf: IvfCells<N>,
+
     // Reference to data (raw vectors)
     storage: Arc<VectorStore>,
 
@@ -611,9 +635,17 @@ pub struct HnswIndex<const N: usize, M: Metric<N>> {
 #[derive(Debug, Default)]
 struct Node {
     id: NodeId,
-    // Neighbor lists by layer.
-    // layers[0] - detailed layer.
-    layers: Vec<RwLock<Vec<NodeId>>>,
+    // Neighbor lists for layers 1..=top; upper[l - 1] holds layer `l`.
+    // Layer 0 lives in the flat `HnswIndex::layer0` arena.
+    upper: Vec<RwLock<Vec<NodeId>>>,
+}
+
+impl Node {
+    /// Highest layer this node participates in.
+    #[inline]
+    fn top_layer(&self) -> usize {
+        self.upper.len()
+    }
 }
 
 #[derive(Default)]
@@ -624,6 +656,9 @@ struct VisitedScratch {
     results_l0: BinaryHeap<std::cmp::Reverse<Candidate>>,
     candidates_layer: BinaryHeap<Candidate>,
     results_layer: BinaryHeap<Candidate>,
+    /// Nodes whose distance searches on this thread computed; see
+    /// [`take_visited_count`].
+    visited: u64,
 }
 
 impl VisitedScratch {
@@ -652,10 +687,84 @@ fn mark_visited(marks: &mut [u32], generation: u32, id: u32) -> bool {
     }
 }
 
+/// Hints the CPU to pull the first cache lines of `data` into L1.
+#[inline]
+fn prefetch<T>(data: &[T]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let ptr = data.as_ptr().cast::<u8>();
+        for offset in (0..std::mem::size_of_val(data).min(256)).step_by(64) {
+            // SAFETY: prefetch is a hint and never faults; `offset` stays inside `data`.
+            unsafe {
+                std::arch::x86_64::_mm_prefetch(
+                    ptr.add(offset).cast::<i8>(),
+                    std::arch::x86_64::_MM_HINT_T0,
+                );
+            }
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = data;
+}
+
 thread_local! {
     static VISITED_SCRATCH: RefCell<VisitedScratch> = RefCell::new(VisitedScratch::default());
 }
 
+/// Returns and resets how many nodes searches on the current thread have
+/// scored since the last call. Read it right before and after a search to
+/// attribute the work to that search.
+pub fn take_visited_count() -> u64 {
+    VISITED_SCRATCH.with(|scratch| std::mem::take(&mut scratch.borrow_mut().visited))
+}
+
+/// Expected graph memory per node of an index built with `m`: its layer-0
+/// row, the node itself and its upper layers. Levels are geometric with
+/// p = 1/2, so a node has one upper layer on average, holding up to `m`
+/// links in a vector grown by doubling.
+pub fn graph_bytes_per_node(m: usize) -> usize {
+    let link = std::mem::size_of::<NodeId>();
+    let layer0 = (Layer0Arena::degree_for_m(m) + 1) * link;
+    // boxcar keeps a ready flag beside each slot
+    let node = std::mem::size_of::<Node>() + std::mem::align_of::<Node>();
+    let upper = std::mem::size_of::<RwLock<Vec<NodeId>>>() + (m + 1).next_power_of_two() * link;
+    layer0 + node + upper
+}
+
+/// Expected `index.snap` bytes per node with full neighbor lists: `2m`
+/// links on layer 0 and `m` on the average single upper layer.
+pub fn snapshot_bytes_per_node(m: usize) -> usize {
+    std::mem::size_of::<ArchivedSnapshotNode>()
+        + 2 * std::mem::size_of::<rkyv::Archived<Vec<u32>>>()
+        + 3 * m * std::mem::size_of::<u32>()
+}
+
+/// Times the phases of a traced search; does nothing without a trace.
+struct PhaseClock<'a> {
+    trace: Option<&'a hyperspace_core::SearchTrace>,
+    mark: std::time::Instant,
+}
+
+impl<'a> PhaseClock<'a> {
+    fn new(trace: Option<&'a hyperspace_core::SearchTrace>) -> Self {
+        Self {
+            trace,
+            mark: std::time::Instant::now(),
+        }
+    }
+
+    /// Hands the time since the previous lap to `record`.
+    fn lap(&mut self, record: impl FnOnce(&mut hyperspace_core::SearchStats, std::time::Duration)) {
+        let Some(trace) = self.trace else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        let elapsed = now - self.mark;
+        self.mark = now;
+        trace.record(|stats| record(stats, elapsed));
+    }
+}
+
 /// Nearest Neighbor Candidate
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct Candidate {
@@ -704,13 +813,19 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             std::env::var("HS_DENSITY_PRUNING").is_ok_and(|v| v.to_lowercase() == "true");
         let zonal =
             std::env::var("HS_ZONAL_QUANTIZATION").is_ok_and(|v| v.to_lowercase() == "true");
+        let metadata = MetadataIndex::default();
+        metadata.set_unindexed_keys(config.get_unindexed_keys());
+        metadata.set_synonyms(config.get_synonyms());
 
         Self {
             nodes: boxcar::Vec::new(),
+            layer0: Layer0Arena::new(Layer0Arena::degree_for_m(config.m.load(Ordering::Relaxed))),
             append_lock: Mutex::new(()),
-            metadata: MetadataIndex::default(),
+            metadata,
             entry_point: AtomicU32::new(0),
             max_layer: AtomicU32::new(0),
+            entry_points: EntryPoints::default(),
+            ivf: IvfCells::default(),
             storage,
             mode,
             storage_f32,
@@ -735,6 +850,18 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         self.storage.count()
     }
 
+    /// Replaces the synonym groups of hybrid search. Maps expanded at index
+    /// time re-derive the text postings of every point.
+    pub fn set_synonyms(&self, synonyms: hyperspace_core::SynonymMap) {
+        let reindex = synonyms.expand_at_index || self.config.get_synonyms().expand_at_index;
+        self.config.set_synonyms(synonyms.clone());
+        self.metadata.set_synonyms(synonyms);
+        if reindex {
+            self.metadata
+                .rebuild_lexical_stats(&self.config.get_bm25_params());
+        }
+    }
+
     fn build_allowed_bitmap(
         &self,
         filter: &std::collections::HashMap<String, String>,
@@ -763,7 +890,12 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             deleted_guard.as_deref().unwrap()
         };
 
-        let mut bitmap: Option<RoaringBitmap> = None;
+        let mut bitmap =
+            self.metadata
+                .filter_bitmap(filter, complex_filters, &self.config.get_bm25_params());
+        if bitmap.as_ref().is_some_and(RoaringBitmap::is_empty) {
+            return bitmap;
+        }
 
         let mut apply_mask = |mask: &RoaringBitmap| {
             if let Some(ref mut bm) = bitmap {
@@ -773,65 +905,12 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
         };
 
-        if !filter.is_empty() {
-            for (key, val) in filter {
-                let tag = format!("{key}:{val}");
-                if let Some(tag_bitmap) = self.metadata.inverted.get(&tag) {
-                    apply_mask(&tag_bitmap);
-                } else {
-                    return Some(RoaringBitmap::new());
-                }
-            }
-        }
-
         for expr in complex_filters {
             match expr {
-                FilterExpr::Match { key, value } => {
-                    let tag = format!("{key}:{value}");
-                    if let Some(tag_bitmap) = self.metadata.inverted.get(&tag) {
-                        apply_mask(&tag_bitmap);
-                    } else {
-                        return Some(RoaringBitmap::new());
-                    }
-                }
-                FilterExpr::Range { key, gte, lte } => {
-                    let mut range_union = RoaringBitmap::new();
-
-                    if let Some(tree) = self.metadata.numeric.get(key) {
-                        let start = gte.map_or(i64::MIN, |x| x.ceil() as i64);
-                        let end = lte.map_or(i64::MAX, |x| x.floor() as i64);
-                        if start <= end {
-                            for entry in tree.range(start..=end) {
-                                range_union |= &*entry.value().read();
-                            }
-                        }
-                    }
-
-                    for item in &self.metadata.forward {
-                        if range_union.contains(*item.key()) {
-                            continue;
-                        }
-                        let Some(num) = Self::metadata_numeric_value(item.value(), key) else {
-                            continue;
-                        };
-                        if let Some(min) = gte {
-                            if num < *min {
-                                continue;
-                            }
-                        }
-                        if let Some(max) = lte {
-                            if num > *max {
-                                continue;
-                            }
-                        }
-                        range_union.insert(*item.key());
-                    }
-
-                    if range_union.is_empty() {
-                        return Some(RoaringBitmap::new());
-                    }
-                    apply_mask(&range_union);
-                }
+                // Applied by the metadata index
+                FilterExpr::Match { .. }
+                | FilterExpr::Range { .. }
+                | FilterExpr::PhraseMatch { .. } => {}
                 FilterExpr::InBox {
                     min_bounds,
                     max_bounds,
@@ -889,26 +968,291 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                     if ball_match.is_empty() {
                         return Some(RoaringBitmap::new());
                     }
-                    apply_mask(&ball_match);
+                    apply_mask(&ball_match);
+                }
+            }
+        }
+
+        match bitmap {
+            Some(mut bm) => {
+                bm -= deleted;
+                Some(bm)
+            }
+            None => None,
+        }
+    }
+
+    // Support Soft Delete
+    pub fn delete(&self, id: NodeId) {
+        self.metadata.deleted.write().insert(id);
+        // Searches would otherwise keep descending from a ghost.
+        if id == self.entry_point.load(Ordering::Relaxed) {
+            self.reelect_entry_point();
+        }
+    }
+
+    /// Ids of live nodes matching `filter` and `complex_filters`, `None`
+    /// when both are empty.
+    pub fn matching_ids(
+        &self,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+    ) -> Option<RoaringBitmap> {
+        if filter.is_empty() && complex_filters.is_empty() {
+            return None;
+        }
+        self.build_allowed_bitmap(filter, complex_filters)
+    }
+
+    /// Erases nodes `ids` for good, unlike [`HnswIndex::delete`]: unlinks
+    /// them from every layer, drops their metadata, tags and lexical stats
+    /// and zeroes their storage slots. In-neighbors inherit their links so
+    /// the graph stays connected. The ids stay tombstoned and are never
+    /// reused.
+    pub fn purge(&self, ids: &[NodeId]) -> HyperspaceResult<()> {
+        if let Some(id) = ids.iter().find(|&&id| id as usize >= self.nodes.count()) {
+            return Err(HyperspaceError::NotFound(format!("Node {id}")));
+        }
+        for &id in ids {
+            self.delete(id);
+        }
+        self.unlink(&ids.iter().copied().collect());
+
+        let params = self.config.get_bm25_params();
+        for &id in ids {
+            self.metadata.remove_doc(id, &params);
+        }
+
+        if self.entry_points().iter().any(|id| ids.contains(id)) {
+            self.refresh_entry_points();
+        }
+
+        for &id in ids {
+            if self.zonal {
+                self.zonal_storage.remove(&id);
+            } else {
+                let zeros = vec![0u8; self.storage.get(id).len()];
+                self.storage.update(id, &zeros)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops every link to and from `ids`. A node that linked to one of them
+    /// takes over its out-links on that layer, pruned back to the layer's
+    /// limit.
+    fn unlink(&self, ids: &RoaringBitmap) {
+        let m = self.config.get_m();
+        let top = ids
+            .iter()
+            .filter_map(|id| self.nodes.get(id as usize))
+            .map(Node::top_layer)
+            .max()
+            .unwrap_or(0);
+        for level in 0..=top {
+            let mut inherited: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+            for id in ids {
+                let Some(node) = self.nodes.get(id as usize) else {
+                    continue;
+                };
+                if let Some(links) = self.links(node, level) {
+                    let out = links.iter().filter(|&n| !ids.contains(n)).collect();
+                    inherited.insert(id, out);
+                }
+            }
+            let m_max = if level == 0 { m * 2 } else { m };
+            for other in 0..self.nodes.count() as NodeId {
+                let Some(other_node) = self.nodes.get(other as usize) else {
+                    continue;
+                };
+                if ids.contains(other) || other_node.top_layer() < level {
+                    continue;
+                }
+                let linked = self
+                    .links(other_node, level)
+                    .is_some_and(|links| links.iter().any(|n| ids.contains(n)));
+                if !linked {
+                    continue;
+                }
+                let relink = |links: &mut Vec<NodeId>| {
+                    let dropped: Vec<NodeId> =
+                        links.iter().copied().filter(|&n| ids.contains(n)).collect();
+                    links.retain(|&n| !ids.contains(n));
+                    for n in dropped.iter().filter_map(|d| inherited.get(d)).flatten() {
+                        if *n != other && !links.contains(n) {
+                            links.push(*n);
+                        }
+                    }
+                };
+                if level == 0 {
+                    self.layer0.update(other, relink);
+                } else if let Some(links) = other_node.upper.get(level - 1) {
+                    relink(&mut links.write());
+                }
+                if self.links(other_node, level).map_or(0, |links| links.len()) > m_max {
+                    self.prune_connections(other, level, m_max, None);
+                }
+            }
+            for id in inherited.keys() {
+                if level == 0 {
+                    self.layer0.set(*id, &[]);
+                } else if let Some(links) = self
+                    .nodes
+                    .get(*id as usize)
+                    .and_then(|node| node.upper.get(level - 1))
+                {
+                    links.write().clear();
+                }
+            }
+        }
+    }
+
+    /// Current cluster medoids used as extra layer-0 entry points.
+    pub fn entry_points(&self) -> Vec<NodeId> {
+        self.entry_points.get()
+    }
+
+    /// Recomputes the medoids of `GlobalConfig::entry_points` clusters over a
+    /// sample of live nodes.
+    pub fn refresh_entry_points(&self) {
+        let k = self.config.get_entry_points();
+        let count = self.nodes.count();
+        let sample: Vec<NodeId> = {
+            let deleted = self.metadata.deleted.read();
+            let stride = (count / (k * entry_points::SAMPLE_PER_POINT).max(1)).max(1);
+            (0..count as u32)
+                .step_by(stride)
+                .filter(|id| !deleted.contains(*id))
+                .collect()
+        };
+        let vectors: Vec<HyperVector<N>> = sample.iter().map(|&id| self.get_vector(id)).collect();
+        let picked = entry_points::medoids(sample.len(), k, |a, b| {
+            M::distance(&vectors[a].coords, &vectors[b].coords)
+        });
+        self.entry_points
+            .set(picked.into_iter().map(|i| sample[i]).collect(), k, count);
+    }
+
+    /// Medoid entry points for a search, rebuilding them first when the
+    /// index has doubled since the last build. Empty when disabled.
+    fn routing_entry_points(&self) -> Vec<NodeId> {
+        let k = self.config.get_entry_points();
+        if k == 0 {
+            return Vec::new();
+        }
+        if self.entry_points.claim_rebuild(self.nodes.count(), k) {
+            self.refresh_entry_points();
+        }
+        self.entry_points.get()
+    }
+
+    /// Rebuilds the `GlobalConfig::ivf_cells` cells: medoids of a sample of
+    /// live nodes as centroids, then every live node in the cell of its
+    /// nearest centroid.
+    pub fn refresh_ivf(&self) {
+        let k = self.config.get_ivf_cells();
+        let count = self.nodes.count();
+        let live: Vec<NodeId> = {
+            let deleted = self.metadata.deleted.read();
+            (0..count as u32)
+                .filter(|id| !deleted.contains(*id))
+                .collect()
+        };
+        let stride = (live.len() / (k * entry_points::SAMPLE_PER_POINT).max(1)).max(1);
+        let sample: Vec<HyperVector<N>> = live
+            .iter()
+            .step_by(stride)
+            .map(|&id| self.get_vector(id))
+            .collect();
+        let picked = entry_points::medoids(sample.len(), k, |a, b| {
+            M::distance(&sample[a].coords, &sample[b].coords)
+        });
+        if picked.is_empty() {
+            self.ivf.release();
+            return;
+        }
+        let centroids: Vec<[f64; N]> = picked.into_iter().map(|i| sample[i].coords).collect();
+        let members = live
+            .par_iter()
+            .fold(
+                || vec![RoaringBitmap::new(); centroids.len()],
+                |mut cells, &id| {
+                    let v = self.get_vector(id);
+                    if let Some(cell) = ivf::nearest(&centroids, |c| M::distance(c, &v.coords)) {
+                        cells[cell].insert(id);
+                    }
+                    cells
+                },
+            )
+            .reduce(
+                || vec![RoaringBitmap::new(); centroids.len()],
+                |mut a, b| {
+                    for (x, y) in a.iter_mut().zip(b) {
+                        *x |= y;
+                    }
+                    a
+                },
+            );
+        self.ivf.set(centroids, members, k, count);
+    }
+
+    /// Nodes of the IVF cells nearest to `query`, rebuilding the cells first
+    /// when due. `None` when IVF is off or the cells are not built yet.
+    fn ivf_candidates(&self, query: &HyperVector<N>) -> Option<RoaringBitmap> {
+        let k = self.config.get_ivf_cells();
+        if k == 0 {
+            return None;
+        }
+        if self.ivf.claim_rebuild(self.nodes.count(), k) {
+            self.refresh_ivf();
+        }
+        self.ivf.probe(self.config.get_ivf_probes(), |c| {
+            M::distance(c, &query.coords)
+        })
+    }
+
+    /// Nodes per IVF cell; empty when IVF is off or not built yet.
+    pub fn ivf_cell_sizes(&self) -> Vec<u64> {
+        self.ivf.sizes()
+    }
+
+    /// Live entry point of the graph, `None` when empty or all deleted.
+    pub fn entry_point(&self) -> Option<NodeId> {
+        let ep = self.entry_point.load(Ordering::Relaxed);
+        ((ep as usize) < self.nodes.count() && !self.metadata.deleted.read().contains(ep))
+            .then_some(ep)
+    }
+
+    /// Moves the entry point to a live node on the highest layer that still
+    /// has one, lowering `max_layer` to match. Leaves both alone when no live
+    /// node is left.
+    fn reelect_entry_point(&self) {
+        let max_layer = self.max_layer.load(Ordering::Relaxed) as usize;
+        let mut best: Option<(usize, NodeId)> = None;
+        {
+            let deleted = self.metadata.deleted.read();
+            for id in 0..self.nodes.count() as u32 {
+                if deleted.contains(id) {
+                    continue;
+                }
+                let Some(node) = self.nodes.get(id as usize) else {
+                    continue;
+                };
+                let top = node.top_layer();
+                if best.is_none_or(|(best_top, _)| top > best_top) {
+                    best = Some((top, id));
+                    if top >= max_layer {
+                        break;
+                    }
                 }
             }
         }
-
-        match bitmap {
-            Some(mut bm) => {
-                bm -= deleted;
-                Some(bm)
-            }
-            None => None,
+        if let Some((top, id)) = best {
+            self.max_layer.store(top as u32, Ordering::SeqCst);
+            self.entry_point.store(id, Ordering::SeqCst);
         }
     }
 
-    // Support Soft Delete
-    pub fn delete(&self, id: NodeId) {
-        let mut del = self.metadata.deleted.write();
-        del.insert(id);
-    }
-
     #[allow(clippy::too_many_arguments)]
     pub fn search(
         &self,
@@ -921,8 +1265,35 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         if let Some(text) = params.hybrid_query.as_deref() {
             return self.search_hybrid(query, filter, complex_filters, text, params);
         }
+        self.search_within(query, filter, complex_filters, params, None)
+    }
 
-        let allowed_bitmap = self.build_allowed_bitmap(filter, complex_filters);
+    /// Vector search over the points the filters allow, narrowed to `within`
+    /// when given.
+    fn search_within(
+        &self,
+        query: &[f64],
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &hyperspace_core::SearchParams,
+        within: Option<&RoaringBitmap>,
+    ) -> Vec<(NodeId, f64)> {
+        let mut phase = PhaseClock::new(params.trace.as_deref());
+        let allowed_bitmap = match (self.build_allowed_bitmap(filter, complex_filters), within) {
+            (Some(bm), Some(within)) => Some(bm & within),
+            (None, Some(within)) => Some(within - &*self.metadata.deleted.read()),
+            (allowed, None) => allowed,
+        };
+        phase.lap(|stats, elapsed| {
+            stats.filter_time += elapsed;
+            if let Some(bm) = &allowed_bitmap {
+                let live =
+                    (self.nodes.count() as u64).saturating_sub(self.metadata.deleted.read().len());
+                *stats.filter_matches.get_or_insert(0) += bm.len();
+                stats.indexed += live;
+                stats.filter_short_circuit |= bm.is_empty();
+            }
+        });
         if allowed_bitmap
             .as_ref()
             .is_some_and(roaring::RoaringBitmap::is_empty)
@@ -943,6 +1314,25 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         M::validate(&aligned_query).expect("Invalid Query Vector for this Metric");
         let q_vec = HyperVector::new_unchecked(aligned_query);
 
+        // IVF: only the points of the cells nearest to the query, unless the
+        // filter alone is small enough to scan.
+        let allowed_bitmap = match allowed_bitmap {
+            Some(bm) if bm.len() <= self.filtered_bruteforce_threshold() => Some(bm),
+            allowed => match self.ivf_candidates(&q_vec) {
+                Some(cells) => Some(match allowed {
+                    Some(bm) => bm & cells,
+                    None => cells - &*self.metadata.deleted.read(),
+                }),
+                None => allowed,
+            },
+        };
+        if allowed_bitmap
+            .as_ref()
+            .is_some_and(roaring::RoaringBitmap::is_empty)
+        {
+            return Vec::new();
+        }
+
         let entry_node = self.entry_point.load(Ordering::Relaxed);
 
         // FIX #3: Merge both checks into a single lock-free call.
@@ -953,7 +1343,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
             self.nodes
                 .get(entry_node as usize)
-                .map_or(0, |n| n.layers.len().saturating_sub(1))
+                .map_or(0, Node::top_layer)
         };
 
         let query_klein = if self.fast_routing {
@@ -962,7 +1352,15 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             None
         };
 
-        let mut curr_dist = self.dist_upper(entry_node, &q_vec, query_klein.as_ref());
+        // Binary mode: navigate with popcount Hamming instead of decoding bits to floats.
+        let query_bits = (self.mode == QuantizationMode::Binary && Self::binary_hamming_enabled())
+            .then(|| BinaryHyperVector::from_float(&q_vec));
+        let route_dist = |id: NodeId| match &query_bits {
+            Some(qb) => self.dist_hamming(id, qb),
+            None => self.dist_upper(id, &q_vec, query_klein.as_ref()),
+        };
+
+        let mut curr_dist = route_dist(entry_node);
         let mut curr_node = entry_node;
 
         // 1. Zoom-in phase: Greedy search from top to layer 1.
@@ -979,12 +1377,11 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                     let Some(node) = self.nodes.get(curr_node as usize) else {
                         break;
                     };
-                    if node.layers.len() <= level {
+                    let Some(neighbors) = self.links(node, level) else {
                         break;
-                    }
-                    let neighbors = node.layers[level].read();
-                    for &neighbor in neighbors.iter() {
-                        let d = self.dist_upper(neighbor, &q_vec, query_klein.as_ref());
+                    };
+                    for neighbor in neighbors.iter() {
+                        let d = route_dist(neighbor);
                         if d < curr_dist {
                             curr_dist = d;
                             curr_node = neighbor;
@@ -995,14 +1392,73 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
         }
 
+        // Clustered data: also start from the medoids closest to the query.
+        let mut seeds = vec![curr_node];
+        let medoids = self.routing_entry_points();
+        if !medoids.is_empty() {
+            let mut scored: Vec<(f64, NodeId)> =
+                medoids.into_iter().map(|id| (route_dist(id), id)).collect();
+            scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let probes = self.config.get_entry_probes().max(1);
+            seeds.extend(scored.into_iter().take(probes).map(|(_, id)| id));
+        }
+
+        phase.lap(|stats, elapsed| {
+            stats.routing_time += elapsed;
+            stats.layers = stats.layers.max(start_layer as u32 + 1);
+        });
+
         // 2. Local search phase: Layer 0 with Filter
-        let mut candidates = self.search_layer0(
-            curr_node,
-            &q_vec,
-            params.top_k,
-            params.ef_search,
-            allowed_bitmap.as_ref(),
-        );
+        let hamming = query_bits.is_some();
+        let rescore = hamming && Self::binary_rescore_enabled();
+        let k = if rescore {
+            params.ef_search.max(params.top_k)
+        } else {
+            params.top_k
+        };
+        // A filter small enough to scan gives every probe the same answer
+        let probe = params.num_probes > 1
+            && start_layer > 0
+            && allowed_bitmap
+                .as_ref()
+                .is_none_or(|bm| bm.len() > self.filtered_bruteforce_threshold());
+        let mut candidates = if probe {
+            self.search_probes(
+                &seeds,
+                curr_node,
+                &q_vec,
+                query_klein.as_ref(),
+                query_bits.as_ref(),
+                k,
+                params,
+                allowed_bitmap.as_ref(),
+            )
+        } else {
+            self.search_layer0(
+                &seeds,
+                &q_vec,
+                query_bits.as_ref(),
+                k,
+                params.ef_search,
+                allowed_bitmap.as_ref(),
+            )
+        };
+        phase.lap(|stats, elapsed| {
+            stats.layer0_time += elapsed;
+            stats.filter_bruteforce |= allowed_bitmap
+                .as_ref()
+                .is_some_and(|bm| bm.len() <= self.filtered_bruteforce_threshold());
+        });
+
+        // Asymmetric rescoring: re-rank the Hamming shortlist with float-query
+        // distances. Always done, so results never carry Hamming counts.
+        if hamming {
+            for cand in &mut candidates {
+                cand.1 = self.dist(cand.0, &q_vec);
+            }
+            candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+            candidates.truncate(params.top_k);
+        }
 
         if params.use_wasserstein {
             for cand in &mut candidates {
@@ -1014,6 +1470,9 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             // Ensure we keep only top k
             candidates.truncate(params.top_k);
         }
+        if hamming || params.use_wasserstein {
+            phase.lap(|stats, elapsed| stats.rerank_time += elapsed);
+        }
 
         candidates
     }
@@ -1047,12 +1506,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
 
             let vec = self.get_vector(id).coords.to_vec();
-            let meta = self
-                .metadata
-                .forward
-                .get(&id)
-                .map(|m| m.clone())
-                .unwrap_or_default();
+            let meta = self.metadata.forward.get(id).unwrap_or_default();
             result.push((id, vec, meta));
         }
         result
@@ -1072,12 +1526,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 continue;
             }
             let vec = self.get_vector(id).coords.to_vec();
-            let meta = self
-                .metadata
-                .forward
-                .get(&id)
-                .map(|m| m.clone())
-                .unwrap_or_default();
+            let meta = self.metadata.forward.get(id).unwrap_or_default();
             result.push((id, vec, meta));
         }
         result
@@ -1091,7 +1540,11 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             return f64::MAX;
         }
 
-        let bytes = self.storage.get(node_id);
+        self.dist_bytes(self.storage.get(node_id), query)
+    }
+
+    #[inline]
+    fn dist_bytes(&self, bytes: &[u8], query: &HyperVector<N>) -> f64 {
         match self.mode {
             QuantizationMode::ScalarI8 => {
                 let q = QuantizedHyperVector::<N>::from_bytes(bytes);
@@ -1114,6 +1567,65 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         }
     }
 
+    /// Scores a batch of nodes against the query in two passes: first resolve
+    /// every storage row and issue prefetch hints, then compute the distances.
+    /// The row loads for a whole neighbor list overlap instead of stalling one
+    /// at a time. `out[i]` receives the distance of `ids[i]`.
+    fn dist_many(
+        &self,
+        ids: &[NodeId],
+        query: &HyperVector<N>,
+        query_bits: Option<&BinaryHyperVector<N>>,
+        out: &mut [f64],
+    ) {
+        let count = self.storage.count();
+        for (ids, out) in ids.chunks(DIST_BATCH).zip(out.chunks_mut(DIST_BATCH)) {
+            let mut rows: [&[u8]; DIST_BATCH] = [&[]; DIST_BATCH];
+            for (row, &id) in rows.iter_mut().zip(ids) {
+                if (id as usize) < count {
+                    *row = self.storage.get(id);
+                    prefetch(row);
+                }
+            }
+            for ((d, row), &id) in out.iter_mut().zip(&rows).zip(ids) {
+                *d = if (id as usize) >= count {
+                    f64::MAX
+                } else if let Some(qb) = query_bits {
+                    f64::from(BinaryHyperVector::<N>::from_bytes(row).hamming_distance(qb))
+                } else {
+                    self.dist_bytes(row, query)
+                };
+            }
+        }
+    }
+
+    /// Hamming distance between a stored binary code and the query's code.
+    #[inline]
+    fn dist_hamming(&self, node_id: NodeId, query_bits: &BinaryHyperVector<N>) -> f64 {
+        if node_id as usize >= self.storage.count() {
+            return f64::MAX;
+        }
+        let b = BinaryHyperVector::<N>::from_bytes(self.storage.get(node_id));
+        f64::from(b.hamming_distance(query_bits))
+    }
+
+    fn binary_hamming_enabled() -> bool {
+        static ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *ENABLED.get_or_init(|| {
+            std::env::var("HS_BINARY_HAMMING").map_or(true, |v| v != "false" && v != "0")
+        })
+    }
+
+    /// Whether Binary-mode search widens its Hamming shortlist to `ef_search`
+    /// before rescoring. With `HS_BINARY_RESCORE=false` only the `top_k`
+    /// Hamming hits are rescored, which is cheaper but less accurate.
+    fn binary_rescore_enabled() -> bool {
+        static ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *ENABLED.get_or_init(|| {
+            std::env::var("HS_BINARY_RESCORE").map_or(true, |v| v != "false" && v != "0")
+        })
+    }
+
     // Distance calculation helper specifically for upper routing layers
     // It takes an optional `query_klein` buffer to perform fast euclidean chord distance in Klein mode.
     #[inline]
@@ -1140,16 +1652,8 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         self.dist(node_id, query)
     }
 
-    fn filtered_bruteforce_threshold() -> u64 {
-        // FIX #7: Cache via OnceLock — env::var() is a syscall with a global mutex.
-        // Without caching this is called on every filtered search.
-        static THRESHOLD: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
-        *THRESHOLD.get_or_init(|| {
-            std::env::var("HS_FILTER_BRUTEFORCE_THRESHOLD")
-                .ok()
-                .and_then(|v| v.parse::<u64>().ok())
-                .unwrap_or(50_000)
-        })
+    fn filtered_bruteforce_threshold(&self) -> u64 {
+        self.config.get_filter_bruteforce_threshold()
     }
 
     fn search_bruteforce_bitmap(
@@ -1170,15 +1674,65 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
             out.push((id, self.dist(id, query)));
         }
+        VISITED_SCRATCH.with(|scratch| scratch.borrow_mut().visited += out.len() as u64);
         out.sort_by(|a, b| a.1.total_cmp(&b.1));
         out.truncate(k);
         out
     }
 
+    /// Multi-probe layer 0: one search from `seeds` and `num_probes - 1`
+    /// more, in parallel, each from one of the layer-1 nodes nearest to the
+    /// query around `entry`. Separate beams reach regions a single beam of
+    /// the same `ef` prunes away; the merged list keeps the best `k`.
+    #[allow(clippy::too_many_arguments)]
+    fn search_probes(
+        &self,
+        seeds: &[NodeId],
+        entry: NodeId,
+        query: &HyperVector<N>,
+        query_klein: Option<&HyperVector<N>>,
+        query_bits: Option<&BinaryHyperVector<N>>,
+        k: usize,
+        params: &hyperspace_core::SearchParams,
+        allowed: Option<&RoaringBitmap>,
+    ) -> Vec<(NodeId, f64)> {
+        let probes = params.num_probes;
+        let mut starts = self
+            .search_layer_candidates(entry, query, query_klein, 1, probes)
+            .into_vec();
+        starts.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        let mut runs = vec![seeds.to_vec()];
+        runs.extend(
+            starts
+                .into_iter()
+                .filter(|c| c.id != entry)
+                .take(probes - 1)
+                .map(|c| vec![c.id]),
+        );
+
+        let (lists, visited): (Vec<_>, Vec<u64>) = runs
+            .par_iter()
+            .map(|seeds| {
+                let found =
+                    self.search_layer0(seeds, query, query_bits, k, params.ef_search, allowed);
+                // Counted on the worker; summed back into this thread below
+                (found, take_visited_count())
+            })
+            .unzip();
+        VISITED_SCRATCH.with(|scratch| scratch.borrow_mut().visited += visited.iter().sum::<u64>());
+
+        let mut merged: Vec<(NodeId, f64)> = lists.into_iter().flatten().collect();
+        merged.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        merged.dedup_by_key(|c| c.0);
+        merged.truncate(k);
+        merged
+    }
+
     fn search_layer0(
         &self,
-        start_node: NodeId,
+        seeds: &[NodeId],
         query: &HyperVector<N>,
+        query_bits: Option<&BinaryHyperVector<N>>,
         k: usize,
         ef: usize,
         allowed: Option<&RoaringBitmap>,
@@ -1204,16 +1758,24 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         };
 
         if let Some(allowed_bitmap) = allowed {
-            if allowed_bitmap.len() <= Self::filtered_bruteforce_threshold() {
+            if allowed_bitmap.len() <= self.filtered_bruteforce_threshold() {
                 return self.search_bruteforce_bitmap(query, k, allowed_bitmap);
             }
         }
 
-        // Safety check start_node
-        if (start_node as usize) >= nodes_count {
+        // Safety check the primary start
+        if seeds.first().is_none_or(|&id| (id as usize) >= nodes_count) {
             return vec![];
         }
 
+        let dist_of = |id: NodeId| match query_bits {
+            Some(qb) => self.dist_hamming(id, qb),
+            None => self.dist(id, query),
+        };
+
+        let mut batch_ids = [0 as NodeId; DIST_BATCH];
+        let mut batch_dists = [0.0f64; DIST_BATCH];
+
         VISITED_SCRATCH.with(|scratch_cell| {
             let mut scratch = scratch_cell.borrow_mut();
             let generation = scratch.prepare(nodes_count);
@@ -1231,17 +1793,23 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 results.reserve(ef_capacity - results.capacity());
             }
 
-            let d = self.dist(start_node, query);
-            let first = Candidate {
-                id: start_node,
-                distance: d,
-            };
-
-            candidates.push(first);
-            if is_valid(start_node) {
-                results.push(std::cmp::Reverse(first));
+            let mut visited = 0u64;
+            for &start in seeds {
+                if (start as usize) >= nodes_count
+                    || !mark_visited(&mut scratch.marks, generation, start)
+                {
+                    continue;
+                }
+                visited += 1;
+                let first = Candidate {
+                    id: start,
+                    distance: dist_of(start),
+                };
+                candidates.push(first);
+                if is_valid(start) {
+                    results.push(std::cmp::Reverse(first));
+                }
             }
-            let _ = mark_visited(&mut scratch.marks, generation, start_node);
 
             while let Some(cand) = candidates.pop() {
                 // Lower Bound Pruning:
@@ -1255,40 +1823,45 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                     continue;
                 }
 
-                // LOCK-FREE: boxcar get()
-                let Some(node) = self.nodes.get(cand.id as usize) else {
-                    continue;
-                };
-                if node.layers.is_empty() {
-                    continue;
+                // Warm the adjacency row most likely to be expanded next.
+                if let Some(next) = candidates.peek() {
+                    self.layer0.prefetch(next.id);
                 }
 
-                let neighbors = node.layers[0].read();
-                for &neighbor in neighbors.iter() {
-                    if !mark_visited(&mut scratch.marks, generation, neighbor) {
-                        continue;
-                    }
-
-                    let dist = self.dist(neighbor, query);
-
-                    let mut add_to_candidates = true;
-                    if let Some(std::cmp::Reverse(worst)) = results.peek() {
-                        if results.len() >= ef && dist > worst.distance {
-                            add_to_candidates = false;
+                // LOCK-FREE: flat arena row
+                let neighbors = self.layer0.neighbors(cand.id);
+                for chunk in neighbors.chunks(DIST_BATCH) {
+                    let mut n = 0;
+                    for slot in chunk {
+                        let neighbor = slot.load(Ordering::Relaxed);
+                        if mark_visited(&mut scratch.marks, generation, neighbor) {
+                            batch_ids[n] = neighbor;
+                            n += 1;
                         }
                     }
+                    visited += n as u64;
+                    self.dist_many(&batch_ids[..n], query, query_bits, &mut batch_dists[..n]);
+
+                    for (&neighbor, &dist) in batch_ids[..n].iter().zip(&batch_dists[..n]) {
+                        let mut add_to_candidates = true;
+                        if let Some(std::cmp::Reverse(worst)) = results.peek() {
+                            if results.len() >= ef && dist > worst.distance {
+                                add_to_candidates = false;
+                            }
+                        }
 
-                    if add_to_candidates {
-                        let c = Candidate {
-                            id: neighbor,
-                            distance: dist,
-                        };
-                        candidates.push(c);
-
-                        if is_valid(neighbor) {
-                            results.push(std::cmp::Reverse(c));
-                            if results.len() > ef {
-                                results.pop();
+                        if add_to_candidates {
+                            let c = Candidate {
+                                id: neighbor,
+                                distance: dist,
+                            };
+                            candidates.push(c);
+
+                            if is_valid(neighbor) {
+                                results.push(std::cmp::Reverse(c));
+                                if results.len() > ef {
+                                    results.pop();
+                                }
                             }
                         }
                     }
@@ -1306,6 +1879,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             results.clear();
             scratch.candidates_l0 = candidates;
             scratch.results_l0 = results;
+            scratch.visited += visited;
             output
         })
     }
@@ -1328,7 +1902,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         let Some(start_node_ref) = self.nodes.get(start_node as usize) else {
             return BinaryHeap::new();
         };
-        if start_node_ref.layers.len() <= level {
+        if start_node_ref.top_layer() < level {
             return BinaryHeap::new();
         }
 
@@ -1358,6 +1932,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             candidates.push(first);
             results.push(first);
             let _ = mark_visited(&mut scratch.marks, generation, start_node);
+            let mut visited = 1u64;
 
             while let Some(cand) = candidates.pop() {
                 let curr_worst = results.peek().unwrap().distance;
@@ -1373,15 +1948,14 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 let Some(node) = self.nodes.get(cand.id as usize) else {
                     continue;
                 };
-                if node.layers.len() <= level {
+                let Some(neighbors) = self.links(node, level) else {
                     continue;
-                }
-
-                let neighbors = node.layers[level].read();
-                for &neighbor in neighbors.iter() {
+                };
+                for neighbor in neighbors.iter() {
                     if !mark_visited(&mut scratch.marks, generation, neighbor) {
                         continue;
                     }
+                    visited += 1;
 
                     let dist = self.dist_upper(neighbor, query, query_klein);
 
@@ -1401,6 +1975,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
             candidates.clear();
             scratch.candidates_layer = candidates;
+            scratch.visited += visited;
             let out = std::mem::take(&mut results);
             results.clear();
             scratch.results_layer = results;
@@ -1409,12 +1984,40 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
     }
 
     /// HNSW Heuristic for neighbor selection
+    /// Picks up to `m` links for `node_id` on `level` from `candidates` with
+    /// the HNSW heuristic, plus the paper's `extendCandidates` and
+    /// `keepPrunedConnections` options when enabled in the config.
     fn select_neighbors(
         &self,
-        _query_vec: &HyperVector<N>,
-        candidates: BinaryHeap<Candidate>,
+        node_id: NodeId,
+        query_vec: &HyperVector<N>,
+        mut candidates: BinaryHeap<Candidate>,
         m: usize,
+        level: usize,
     ) -> Vec<NodeId> {
+        if self.config.is_extend_candidates_enabled() {
+            let mut seen: std::collections::HashSet<NodeId> =
+                candidates.iter().map(|c| c.id).collect();
+            seen.insert(node_id);
+            let mut extra = Vec::new();
+            for cand in &candidates {
+                let Some(node) = self.nodes.get(cand.id as usize) else {
+                    continue;
+                };
+                let Some(links) = self.links(node, level) else {
+                    continue;
+                };
+                for n in links.iter() {
+                    if seen.insert(n) {
+                        let distance = M::distance(&query_vec.coords, &self.get_vector(n).coords);
+                        extra.push(Candidate { id: n, distance });
+                    }
+                }
+            }
+            candidates.extend(extra);
+        }
+        let keep_pruned = self.config.is_keep_pruned_enabled();
+
         let mut sorted_candidates = candidates.into_sorted_vec();
 
         // Task 6.4: Density-based HNSW graph pruning
@@ -1436,12 +2039,17 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         }
 
         let mut result = Vec::with_capacity(actual_m);
+        let mut pruned = Vec::new();
 
         while let Some(cand) = sorted_candidates.pop() {
             // Gets closest (sorted vec is ascending, pop from end)
             if result.len() >= actual_m {
                 break;
             }
+            // The node is already stored, so a search from it finds itself
+            if cand.id == node_id {
+                continue;
+            }
 
             let mut is_good = true;
             let cand_vec = self.get_vector(cand.id);
@@ -1458,8 +2066,12 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
 
             if is_good {
                 result.push(cand.id);
+            } else if keep_pruned {
+                pruned.push(cand.id);
             }
         }
+        let free = actual_m - result.len();
+        result.extend(pruned.into_iter().take(free));
         result
     }
 
@@ -1555,10 +2167,13 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
     }
 
     // Insert with Metadata
-    pub fn insert_to_storage(&self, vector: &[f64]) -> Result<u32, String> {
+    pub fn insert_to_storage(&self, vector: &[f64]) -> HyperspaceResult<u32> {
         let mut arr = [0.0; N];
         if vector.len() != N {
-            return Err("Dim mismatch".into());
+            return Err(HyperspaceError::Validation(format!(
+                "Dimension mismatch. Expected {N}, got {}",
+                vector.len()
+            )));
         }
         arr.copy_from_slice(vector);
 
@@ -1610,12 +2225,9 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             let _g = self.append_lock.lock();
             let id = self.storage.append(&q_bytes)?;
 
-            let new_level = self.random_level();
-            let mut layers = Vec::with_capacity(new_level + 1);
-            for _ in 0..=new_level {
-                layers.push(RwLock::new(Vec::new()));
-            }
-            let pushed_id = self.nodes.push(Node { id, layers });
+            let new_level = Self::node_level(id);
+            let upper = (0..new_level).map(|_| RwLock::new(Vec::new())).collect();
+            let pushed_id = self.nodes.push(Node { id, upper });
             debug_assert_eq!(id as usize, pushed_id);
             id
         };
@@ -1624,10 +2236,13 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
     }
 
     /// Update existing vector in storage (for upsert)
-    pub fn update_storage(&self, id: u32, vector: &[f64]) -> Result<u32, String> {
+    pub fn update_storage(&self, id: u32, vector: &[f64]) -> HyperspaceResult<u32> {
         let mut arr = [0.0; N];
         if vector.len() != N {
-            return Err("Dim mismatch".into());
+            return Err(HyperspaceError::Validation(format!(
+                "Dimension mismatch. Expected {N}, got {}",
+                vector.len()
+            )));
         }
         arr.copy_from_slice(vector);
 
@@ -1676,50 +2291,22 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         &self,
         id: NodeId,
         meta: std::collections::HashMap<String, String>,
-    ) -> Result<(), String> {
+    ) -> HyperspaceResult<()> {
         if !meta.is_empty() {
             self.has_nonempty_metadata.store(true, Ordering::Relaxed);
         }
 
         // 1. Index Metadata
-        for (key, val) in &meta {
-            // A. Inverted Index (Text)
-            let tag = format!("{key}:{val}");
-            self.metadata.inverted.entry(tag).or_default().insert(id);
-
-            // B. Numeric Index (i64)
-            // Try parsing
-            if let Ok(num) = val.parse::<i64>() {
-                let tree = self.metadata.numeric.entry(key.clone()).or_default();
-                let has_entry = {
-                    if let Some(entry) = tree.get(&num) {
-                        entry.value().write().insert(id);
-                        true
-                    } else {
-                        false
-                    }
-                };
-                if !has_entry {
-                    let mut bm = RoaringBitmap::new();
-                    bm.insert(id);
-                    tree.insert(num, RwLock::new(bm));
-                }
-            }
-        }
-
-        // Store full metadata for lookup (Data Explorer)
-        self.upsert_doc_lexical_stats(id, &meta);
-        self.metadata.forward.insert(id, meta);
+        self.metadata
+            .index_doc(id, meta, &self.config.get_bm25_params());
 
         let q_vec = self.get_vector(id); // Helper reads from storage
+        self.ivf.assign(id, |c| M::distance(c, &q_vec.coords));
 
         let max_layer = self.max_layer.load(Ordering::Relaxed);
         let entry_point = self.entry_point.load(Ordering::Relaxed);
 
-        let new_level = self
-            .nodes
-            .get(id as usize)
-            .map_or(0, |n| n.layers.len().saturating_sub(1));
+        let new_level = self.nodes.get(id as usize).map_or(0, Node::top_layer);
 
         // Determine safe start layer for search
         let start_layer = {
@@ -1729,7 +2316,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             } else {
                 self.nodes
                     .get(entry_point as usize)
-                    .map_or(0, |n| n.layers.len().saturating_sub(1))
+                    .map_or(0, Node::top_layer)
             }
         };
 
@@ -1763,13 +2350,12 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 let Some(node) = self.nodes.get(curr_obj as usize) else {
                     break;
                 };
-                if node.layers.len() <= level {
+                let Some(neighbors) = self.links(node, level) else {
                     break;
-                }
+                };
                 let best_n = {
-                    let neighbors = node.layers[level].read();
                     let mut best = None;
-                    for &n in neighbors.iter() {
+                    for n in neighbors.iter() {
                         let d = self.dist_upper(n, &q_vec, query_klein.as_ref());
                         if d < curr_dist {
                             curr_dist = d;
@@ -1811,21 +2397,22 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 );
 
                 // b) Select neighbors with heuristic (using layer-specific M)
-                let selected_neighbors = self.select_neighbors(&q_vec, candidates_heap, m_max);
+                let selected_neighbors =
+                    self.select_neighbors(id, &q_vec, candidates_heap, m_max, level);
 
                 // c) Bidirectional connect
                 for &neighbor_id in &selected_neighbors {
-                    self.add_link(id, neighbor_id, level);
-                    self.add_link(neighbor_id, id, level);
+                    self.add_link(id, neighbor_id, level, m_max);
+                    self.add_link(neighbor_id, id, level, m_max);
 
                     // d) Pruning
                     let neighbor_layer_len = self
                         .nodes
                         .get(neighbor_id as usize)
-                        .and_then(|n| n.layers.get(level))
-                        .map_or(0, |l| l.read().len());
+                        .and_then(|n| self.links(n, level))
+                        .map_or(0, |l| l.len());
                     if neighbor_layer_len > m_max {
-                        self.prune_connections(neighbor_id, level, m_max);
+                        self.prune_connections(neighbor_id, level, m_max, None);
                     }
                 }
 
@@ -1835,8 +2422,9 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             }
         }
 
-        // Update global entry point if needed
-        if (new_level as u32) > max_layer {
+        // Update global entry point if needed. A deleted entry point is only
+        // left in place when no live node remained, so the new node takes over
+        if (new_level as u32) > max_layer || self.entry_point().is_none() {
             self.max_layer.store(new_level as u32, Ordering::SeqCst);
             self.entry_point.store(id, Ordering::SeqCst);
         }
@@ -1849,41 +2437,54 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         &self,
         vector: &[f64],
         meta: std::collections::HashMap<String, String>,
-    ) -> Result<u32, String> {
+    ) -> HyperspaceResult<u32> {
         let new_id = self.insert_to_storage(vector)?;
         self.index_node(new_id, meta)?;
         Ok(new_id)
     }
 
-    fn add_link(&self, src: NodeId, dst: NodeId, level: usize) {
+    fn add_link(&self, src: NodeId, dst: NodeId, level: usize, max_links: usize) {
         // LOCK-FREE node access via boxcar::Vec
         let Some(node) = self.nodes.get(src as usize) else {
             return;
         };
-        if node.layers.len() <= level {
-            return;
-        }
-        let mut links = node.layers[level].write();
         // FIX #4: Remove O(M) linear scan. prune_connections handles dedup when len > m_max.
-        links.push(dst);
+        if level == 0 {
+            // A full row means many back-links landed before any prune ran;
+            // the new link competes with them instead of being dropped.
+            if !self.layer0.push(src, dst) {
+                self.prune_connections(src, level, max_links, Some(dst));
+            }
+        } else if let Some(links) = node.upper.get(level - 1) {
+            links.write().push(dst);
+        }
     }
 
-    fn prune_connections(&self, node_id: NodeId, level: usize, max_links: usize) {
+    /// Keeps the best `max_links` neighbors of `node_id`, weighing `extra`
+    /// as if it were already linked.
+    fn prune_connections(
+        &self,
+        node_id: NodeId,
+        level: usize,
+        max_links: usize,
+        extra: Option<NodeId>,
+    ) {
         // 1. Snapshot current links (LOCK-FREE boxcar get + inner read lock)
         let initial_links: Vec<u32> = {
             let Some(node) = self.nodes.get(node_id as usize) else {
                 return;
             };
-            if node.layers.len() <= level {
+            let Some(links) = self.links(node, level) else {
                 return;
-            }
-            node.layers[level].read().clone()
+            };
+            links.iter().collect()
         };
 
         // 2. Heavy work: calculate distances (NO LOCKS HELD)
         let node_vec = self.get_vector(node_id);
         let mut candidates = Vec::new();
-        for &n in &initial_links {
+        let extra = extra.filter(|id| *id != node_id && !initial_links.contains(id));
+        for n in initial_links.iter().copied().chain(extra) {
             let n_vec = self.get_vector(n);
             let d = M::distance(&node_vec.coords, &n_vec.coords);
             candidates.push(Candidate { id: n, distance: d });
@@ -1891,31 +2492,45 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
 
         // Select best from snapshot
         let heap = BinaryHeap::from(candidates);
-        let mut keepers = self.select_neighbors(&node_vec, heap, max_links);
+        let mut keepers = self.select_neighbors(node_id, &node_vec, heap, max_links, level);
 
         // 3. Atomic update merge (Write per-slot only, no global lock)
         let Some(node) = self.nodes.get(node_id as usize) else {
             return;
         };
-        let mut links_lock = node.layers[level].write();
-
-        // RACE CONDITION CHECK:
-        // If length changed (someone added a link while we calculated),
-        // we must preserve those new links!
-        if links_lock.len() > initial_links.len() {
-            // Find new elements strictly added after our snapshot
-            for &id in links_lock.iter() {
-                if !initial_links.contains(&id) {
-                    // Simple strategy: always keep new links to avoid graph tearing.
-                    // Even if we exceed M slightly, it's safer than losing connectivity.
-                    if keepers.len() < max_links {
-                        keepers.push(id);
+        let mut merge = |links: &mut Vec<NodeId>| {
+            // RACE CONDITION CHECK:
+            // If length changed (someone added a link while we calculated),
+            // we must preserve those new links!
+            if links.len() > initial_links.len() {
+                // Find new elements strictly added after our snapshot
+                for &id in links.iter() {
+                    if !initial_links.contains(&id) {
+                        // Simple strategy: always keep new links to avoid graph tearing.
+                        // Even if we exceed M slightly, it's safer than losing connectivity.
+                        if keepers.len() < max_links {
+                            keepers.push(id);
+                        }
                     }
                 }
             }
+            *links = std::mem::take(&mut keepers);
+        };
+        if level == 0 {
+            self.layer0.update(node_id, merge);
+        } else if let Some(links) = node.upper.get(level - 1) {
+            merge(&mut links.write());
         }
+    }
+
+    /// Nodes in the graph, deleted ones included; matches the storage count
+    /// unless the two drifted apart.
+    pub fn graph_len(&self) -> usize {
+        self.nodes.count()
+    }
 
-        *links_lock = keepers;
+    pub fn is_deleted(&self, id: NodeId) -> bool {
+        self.metadata.deleted.read().contains(id)
     }
 
     pub fn count_nodes(&self) -> usize {
@@ -1938,13 +2553,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         for i in 0..num_nodes {
             let node_vec = self.get_vector(i);
 
-            let candidates: Vec<u32> = {
-                self.nodes
-                    .get(i as usize)
-                    .filter(|n| !n.layers.is_empty())
-                    .map(|n| n.layers[0].read().clone())
-                    .unwrap_or_default()
-            };
+            let candidates: Vec<u32> = self.layer0.to_vec(i);
 
             if candidates.is_empty() {
                 continue;
@@ -1989,11 +2598,8 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 }
             }
 
-            if let Some(node) = self.nodes.get(i as usize) {
-                if !node.layers.is_empty() {
-                    let mut l0 = node.layers[0].write();
-                    *l0 = new_neighbors;
-                }
+            if self.nodes.get(i as usize).is_some() {
+                self.layer0.set(i, &new_neighbors);
             }
         }
         println!("✅ Graph optimization complete.");
@@ -2008,18 +2614,20 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         node_id: NodeId,
         layer: usize,
         limit: usize,
-    ) -> Result<Vec<NodeId>, String> {
+    ) -> HyperspaceResult<Vec<NodeId>> {
         let Some(node) = self.nodes.get(node_id as usize) else {
-            return Err(format!("Node {node_id} not found"));
+            return Err(HyperspaceError::NotFound(format!(
+                "Node {node_id} not found"
+            )));
+        };
+        let Some(links) = self.links(node, layer) else {
+            return Err(HyperspaceError::Validation(format!(
+                "Layer {layer} is out of bounds for node {node_id}"
+            )));
         };
-        if node.layers.len() <= layer {
-            return Err(format!("Layer {layer} is out of bounds for node {node_id}"));
-        }
         let deleted = self.metadata.deleted.read();
-        let out = node.layers[layer]
-            .read()
+        let out = links
             .iter()
-            .copied()
             .filter(|id| !deleted.contains(*id))
             .take(limit)
             .collect();
@@ -2032,17 +2640,19 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         layer: usize,
         max_depth: usize,
         max_nodes: usize,
-    ) -> Result<Vec<NodeId>, String> {
+    ) -> HyperspaceResult<Vec<NodeId>> {
         if max_nodes == 0 {
             return Ok(Vec::new());
         }
         let Some(start) = self.nodes.get(start_id as usize) else {
-            return Err(format!("Start node {start_id} not found"));
+            return Err(HyperspaceError::NotFound(format!(
+                "Start node {start_id} not found"
+            )));
         };
-        if start.layers.len() <= layer {
-            return Err(format!(
+        if start.top_layer() < layer {
+            return Err(HyperspaceError::Validation(format!(
                 "Layer {layer} is out of bounds for node {start_id}"
-            ));
+            )));
         }
         let deleted = self.metadata.deleted.read();
         if deleted.contains(start_id) {
@@ -2064,10 +2674,10 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 continue;
             }
             if let Some(node) = self.nodes.get(node_id as usize) {
-                if node.layers.len() <= layer {
+                let Some(links) = self.links(node, layer) else {
                     continue;
-                }
-                for &next in node.layers[layer].read().iter() {
+                };
+                for next in links.iter() {
                     if deleted.contains(next) {
                         continue;
                     }
@@ -2103,7 +2713,7 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             let Some(node) = self.nodes.get(node_id as usize) else {
                 continue;
             };
-            if node.layers.len() <= layer {
+            if node.top_layer() < layer {
                 continue;
             }
 
@@ -2118,10 +2728,10 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
                 let Some(curr_node) = self.nodes.get(curr as usize) else {
                     continue;
                 };
-                if curr_node.layers.len() <= layer {
+                let Some(links) = self.links(curr_node, layer) else {
                     continue;
-                }
-                for &next in curr_node.layers[layer].read().iter() {
+                };
+                for next in links.iter() {
                     if deleted.contains(next) {
                         continue;
                     }
@@ -2142,143 +2752,57 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         clusters
     }
 
-    pub fn metadata_by_id(&self, id: NodeId) -> std::collections::HashMap<String, String> {
-        self.metadata
-            .forward
-            .get(&id)
-            .map_or_else(std::collections::HashMap::new, |m| m.clone())
-    }
-
-    pub fn storage_stats(&self) -> (usize, usize) {
-        (
-            self.storage.segment_count(),
-            self.storage.total_size_bytes(),
-        )
-    }
-
-    #[allow(clippy::unused_self)]
-    fn random_level(&self) -> usize {
-        let mut rng = rand::thread_rng();
-        let mut level = 0;
-        while rng.gen::<f64>() < 0.5 && level < MAX_LAYERS - 1 {
-            level += 1;
-        }
-        level
-    }
-
-    fn get_tokenizer(
-        language: &str,
-    ) -> dashmap::mapref::one::Ref<'static, String, crate::tokenizer::Tokenizer> {
-        static TOKENIZERS: std::sync::OnceLock<
-            dashmap::DashMap<String, crate::tokenizer::Tokenizer>,
-        > = std::sync::OnceLock::new();
-
-        let map = TOKENIZERS.get_or_init(dashmap::DashMap::new);
-        if !map.contains_key(language) {
-            let tok = crate::tokenizer::Tokenizer::builder()
-                .language(language)
-                .build()
-                .unwrap_or_else(|_| crate::tokenizer::Tokenizer::default());
-            map.insert(language.to_string(), tok);
-        }
-        map.get(language).unwrap()
-    }
-
-    fn tokenize(text: &str, config: &GlobalConfig) -> Vec<String> {
-        let params = config.get_bm25_params();
-        let mut tokens = Self::get_tokenizer(&params.language).tokenize(text);
-
-        if params.ngrams > 1 {
-            let limit = params.ngrams as usize;
-            let unigrams = tokens.clone();
-            for n in 2..=limit {
-                for window in unigrams.windows(n) {
-                    tokens.push(window.join("_"));
-                }
-            }
-        }
-        tokens
+    /// Highest layer of the graph.
+    pub fn max_layer(&self) -> usize {
+        self.max_layer.load(Ordering::Relaxed) as usize
     }
 
-    fn build_doc_term_stats(
-        meta: &HashMap<String, String>,
-        config: &GlobalConfig,
-    ) -> (HashMap<String, u16>, u32) {
-        let mut term_freq = HashMap::new();
-        let mut doc_len: u32 = 0;
-
-        for (key, value) in meta {
-            if key.starts_with("__hs_") {
+    /// Every live node on `layer` with its live out-links and their
+    /// distances, in ID order. Empty above the top layer.
+    pub fn graph_layer(&self, layer: usize) -> Vec<(NodeId, Vec<(NodeId, f64)>)> {
+        let deleted = self.metadata.deleted.read();
+        let mut out = Vec::new();
+        for node_id in 0..self.nodes.count() as u32 {
+            if deleted.contains(node_id) {
                 continue;
             }
-            for token in Self::tokenize(value, config) {
-                doc_len = doc_len.saturating_add(1);
-                let entry = term_freq.entry(token).or_insert(0_u16);
-                *entry = (*entry).saturating_add(1_u16);
-            }
+            let Some(node) = self.nodes.get(node_id as usize) else {
+                continue;
+            };
+            let Some(links) = self.links(node, layer) else {
+                continue;
+            };
+            let source = self.get_vector(node_id);
+            let edges = links
+                .iter()
+                .filter(|id| !deleted.contains(*id))
+                .map(|id| (id, M::distance(&source.coords, &self.get_vector(id).coords)))
+                .collect();
+            out.push((node_id, edges));
         }
-        (term_freq, doc_len)
+        out
     }
 
-    fn remove_doc_lexical_stats(&self, id: NodeId) {
-        if let Some((_, old_len)) = self.metadata.doc_token_len.remove(&id) {
-            self.metadata
-                .total_token_len
-                .fetch_sub(u64::from(old_len), Ordering::Relaxed);
-        }
-        if let Some(old_meta) = self.metadata.forward.get(&id) {
-            let (term_freq, _) = Self::build_doc_term_stats(old_meta.value(), &self.config);
-            for token in term_freq.keys() {
-                let token_key = format!("_txt:{token}");
-                if let Some(mut bitmap) = self.metadata.inverted.get_mut(&token_key) {
-                    bitmap.remove(id);
-                }
-                if let Some(mut df_ref) = self.metadata.token_df.get_mut(token) {
-                    if *df_ref > 1 {
-                        *df_ref -= 1;
-                    } else {
-                        drop(df_ref);
-                        self.metadata.token_df.remove(token);
-                    }
-                }
-                if let Some(mut tdf_ref) = self.metadata.term_doc_freq.get_mut(token) {
-                    tdf_ref.retain(|(doc_id, _)| *doc_id != id);
-                }
-            }
-        }
+    pub fn metadata_by_id(&self, id: NodeId) -> std::collections::HashMap<String, String> {
+        self.metadata.forward.get(id).unwrap_or_default()
     }
 
-    fn upsert_doc_lexical_stats(&self, id: NodeId, meta: &HashMap<String, String>) {
-        self.remove_doc_lexical_stats(id);
-        let (term_freq, doc_len) = Self::build_doc_term_stats(meta, &self.config);
-        self.metadata.doc_token_len.insert(id, doc_len);
-        self.metadata
-            .total_token_len
-            .fetch_add(u64::from(doc_len), Ordering::Relaxed);
-        for (token, tf) in term_freq {
-            let token_key = format!("_txt:{token}");
-            self.metadata
-                .inverted
-                .entry(token_key)
-                .or_default()
-                .insert(id);
-            *self.metadata.token_df.entry(token.clone()).or_insert(0) += 1;
-            self.metadata
-                .term_doc_freq
-                .entry(token)
-                .or_default()
-                .push((id, tf));
-        }
+    pub fn storage_stats(&self) -> (usize, usize) {
+        (
+            self.storage.segment_count(),
+            self.storage.total_size_bytes(),
+        )
     }
 
-    fn rebuild_lexical_stats(&self) {
-        self.metadata.token_df.clear();
-        self.metadata.doc_token_len.clear();
-        self.metadata.term_doc_freq.clear();
-        self.metadata.total_token_len.store(0, Ordering::Relaxed);
-        for item in &self.metadata.forward {
-            self.upsert_doc_lexical_stats(*item.key(), item.value());
-        }
+    /// Top layer for node `id`: geometric with p = 1/2, drawn from a hash of
+    /// the ID so the same insert sequence always builds the same graph.
+    fn node_level(id: NodeId) -> usize {
+        // splitmix64 finalizer
+        let mut z = u64::from(id).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z.trailing_ones() as usize).min(MAX_LAYERS - 1)
     }
 
     // RRF Fusion Logic
@@ -2297,10 +2821,29 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         inner_params.hybrid_query = None;
         inner_params.top_k = vec_k;
 
-        let vector_results = self.search(query, filter, complex_filters, &inner_params);
+        // `All` and `Phrase` leave only the documents matching the text
+        // eligible, on both sides of the fusion.
+        let index_params = self.config.get_bm25_params();
+        let text_matches = self
+            .metadata
+            .text_match_bitmap(text, params.text_match, &index_params);
+        if text_matches
+            .as_ref()
+            .is_some_and(roaring::RoaringBitmap::is_empty)
+        {
+            return Vec::new();
+        }
+
+        let vector_results = self.search_within(
+            query,
+            filter,
+            complex_filters,
+            &inner_params,
+            text_matches.as_ref(),
+        );
 
         // 2. BM25 lexical ranking over the same filtered space.
-        let tokens = Self::tokenize(text, &self.config);
+        let tokens = self.metadata.query_tokens(text, &index_params);
         if tokens.is_empty() {
             return vector_results.into_iter().take(params.top_k).collect();
         }
@@ -2309,7 +2852,14 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
             uniq_tokens.insert(token);
         }
 
-        let allowed_bitmap = self.build_allowed_bitmap(filter, complex_filters);
+        let allowed_bitmap = match (
+            self.build_allowed_bitmap(filter, complex_filters),
+            text_matches,
+        ) {
+            (Some(bm), Some(matches)) => Some(bm & matches),
+            (allowed, None) => allowed,
+            (None, matches) => matches,
+        };
         if allowed_bitmap
             .as_ref()
             .is_some_and(roaring::RoaringBitmap::is_empty)
@@ -2329,67 +2879,13 @@ impl<const N: usize, M: Metric<N>> HnswIndex<N, M> {
         };
 
         let global_docs = self.nodes.count().saturating_sub(deleted.len() as usize);
-        let total_docs = global_docs.max(1) as f64;
-        let avgdl = if global_docs == 0 {
-            1.0
-        } else {
-            self.metadata.total_token_len.load(Ordering::Relaxed) as f64 / total_docs
-        }
-        .max(1.0);
         let bm25_params = params
             .bm25_options
             .clone()
             .unwrap_or_else(|| self.config.get_bm25_params());
-
-        let keyword_scores = uniq_tokens
-            .into_par_iter()
-            .fold(HashMap::new, |mut acc, token| {
-                if let Some(doc_freqs) = self.metadata.term_doc_freq.get(&token) {
-                    let df = self
-                        .metadata
-                        .token_df
-                        .get(&token)
-                        .map_or_else(|| doc_freqs.value().len() as u32, |v| *v)
-                        .max(1);
-
-                    for &(id, tf) in doc_freqs.value() {
-                        if !is_allowed(id) {
-                            continue;
-                        }
-                        let tf_f32 = f32::from(tf);
-                        let dl = self
-                            .metadata
-                            .doc_token_len
-                            .get(&id)
-                            .map_or(0.0, |v| *v as f32)
-                            .max(1.0);
-
-                        let score = f64::from(hyperspace_core::bm25::score(
-                            bm25_params.method,
-                            tf_f32,
-                            dl,
-                            avgdl as f32,
-                            global_docs as u32, // Note: not total_docs (f64)
-                            df,
-                            bm25_params.k1,
-                            bm25_params.b,
-                            bm25_params.delta,
-                        ));
-
-                        *acc.entry(id).or_insert(0.0) += score;
-                    }
-                }
-                acc
-            })
-            .reduce(HashMap::new, |mut a, b| {
-                for (k, v) in b {
-                    *a.entry(k).or_insert(0.0) += v;
-                }
-                a
-            });
-
-        let mut keyword_results: Vec<(u32, f64)> = keyword_scores.into_iter().collect();
-        keyword_results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let keyword_results =
+            self.metadata
+                .bm25_scores(uniq_tokens, &bm25_params, global_docs, is_allowed);
 
         // 3. Fusion
         let mut final_scores: std::collections::HashMap<u32, f32> =
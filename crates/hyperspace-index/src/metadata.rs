@@ -0,0 +1,779 @@
+//! Metadata side of an index: `key:value` tags, numeric ranges, stored rows
+//! and BM25 statistics. [`crate::HnswIndex`] keeps one next to its graph;
+//! collections without vectors use one on its own.
+
+use crate::cardinality::HyperLogLog;
+use crate::forward::{ForwardStore, RowView};
+use crate::inverted::InvertedIndex;
+use crate::language;
+use crate::numeric::NumericIndex;
+use dashmap::DashMap;
+use hyperspace_core::bm25::Bm25Params;
+use hyperspace_core::{FilterExpr, MetadataKeyStats, SynonymMap, TextMatch};
+use parking_lot::RwLock;
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Whether `key`, or the key a typed shadow `__hs_typed__{key}` belongs to,
+/// is stored without being indexed.
+fn is_unindexed(unindexed: &HashSet<String>, key: &str) -> bool {
+    !unindexed.is_empty()
+        && (unindexed.contains(key)
+            || key
+                .strip_prefix("__hs_typed__")
+                .is_some_and(|key| unindexed.contains(key)))
+}
+
+#[derive(Debug)]
+pub struct MetadataIndex {
+    pub inverted: InvertedIndex,
+    pub numeric: DashMap<String, NumericIndex>,
+    pub deleted: RwLock<RoaringBitmap>,
+    pub forward: ForwardStore,
+    pub token_df: DashMap<String, u32>,
+    pub doc_token_len: DashMap<u32, u32>,
+    pub term_doc_freq: DashMap<String, Vec<(u32, u16)>>,
+    pub total_token_len: AtomicU64,
+    /// Positions of each unigram per document, for phrase queries. A
+    /// document's fields are numbered one after the other with a gap, so a
+    /// phrase never spans two fields.
+    pub term_positions: DashMap<String, HashMap<u32, Vec<u32>>>,
+    /// Documents per analyzer language when the language is
+    /// [`language::AUTO`]; undetected ones count as `auto`.
+    pub languages: DashMap<String, u32>,
+    /// Keys kept in `forward` only: no tags, numeric buckets or tokens.
+    pub(crate) unindexed: RwLock<HashSet<String>>,
+    pub(crate) synonyms: RwLock<SynonymMap>,
+    /// Per analyzer language: each single-token synonym entry to the tokens
+    /// of the other entries of its groups. Built on first use.
+    pub(crate) synonym_tables: DashMap<String, Arc<HashMap<String, Vec<String>>>>,
+    /// Distinct-value sketch per user key, unindexed keys included.
+    pub(crate) distinct: DashMap<String, HyperLogLog>,
+}
+
+impl Default for MetadataIndex {
+    fn default() -> Self {
+        Self {
+            inverted: InvertedIndex::default(),
+            numeric: DashMap::new(),
+            deleted: RwLock::new(RoaringBitmap::new()),
+            forward: ForwardStore::default(),
+            token_df: DashMap::new(),
+            doc_token_len: DashMap::new(),
+            term_doc_freq: DashMap::new(),
+            total_token_len: AtomicU64::new(0),
+            term_positions: DashMap::new(),
+            languages: DashMap::new(),
+            unindexed: RwLock::new(HashSet::new()),
+            synonyms: RwLock::new(SynonymMap::default()),
+            synonym_tables: DashMap::new(),
+            distinct: DashMap::new(),
+        }
+    }
+}
+
+impl MetadataIndex {
+    pub(crate) fn numeric_value(meta: &RowView<'_>, key: &str) -> Option<f64> {
+        if let Some(raw) = meta.get(key) {
+            return raw.parse::<f64>().ok();
+        }
+        let typed_key = format!("__hs_typed__{key}");
+        let raw_typed = meta.get(&typed_key)?;
+        let parsed = serde_json::from_str::<serde_json::Value>(raw_typed).ok()?;
+        parsed.get("v")?.as_f64()
+    }
+
+    fn tokenizer(
+        language: &str,
+    ) -> dashmap::mapref::one::Ref<'static, String, crate::tokenizer::Tokenizer> {
+        static TOKENIZERS: std::sync::OnceLock<
+            dashmap::DashMap<String, crate::tokenizer::Tokenizer>,
+        > = std::sync::OnceLock::new();
+
+        let map = TOKENIZERS.get_or_init(dashmap::DashMap::new);
+        if !map.contains_key(language) {
+            let tok = crate::tokenizer::Tokenizer::builder()
+                .language(language)
+                .build()
+                .unwrap_or_else(|_| crate::tokenizer::Tokenizer::default());
+            map.insert(language.to_string(), tok);
+        }
+        map.get(language).unwrap()
+    }
+
+    /// Tokens of `text` in `params.language`, plus n-grams up to
+    /// `params.ngrams`.
+    pub fn tokenize(text: &str, params: &Bm25Params) -> Vec<String> {
+        let mut tokens = Self::tokenizer(&params.language).tokenize(text);
+
+        if params.ngrams > 1 {
+            let limit = params.ngrams as usize;
+            let unigrams = tokens.clone();
+            for n in 2..=limit {
+                for window in unigrams.windows(n) {
+                    tokens.push(window.join("_"));
+                }
+            }
+        }
+        tokens
+    }
+
+    /// Stores `keys` without indexing them from now on. Set it before any
+    /// document is indexed: removal skips the same keys.
+    pub fn set_unindexed_keys(&self, keys: HashSet<String>) {
+        *self.unindexed.write() = keys;
+    }
+
+    /// Replaces the synonym groups. With `expand_at_index` on either side
+    /// the indexed tokens no longer match them; see
+    /// [`Self::rebuild_lexical_stats`].
+    pub fn set_synonyms(&self, synonyms: SynonymMap) {
+        *self.synonyms.write() = synonyms;
+        self.synonym_tables.clear();
+    }
+
+    pub fn synonyms(&self) -> SynonymMap {
+        self.synonyms.read().clone()
+    }
+
+    fn synonym_table(&self, language: &str) -> Arc<HashMap<String, Vec<String>>> {
+        if let Some(table) = self.synonym_tables.get(language) {
+            return table.clone();
+        }
+        let tokenizer = Self::tokenizer(language);
+        let mut table: HashMap<String, Vec<String>> = HashMap::new();
+        for group in &self.synonyms.read().groups {
+            let entries: Vec<Vec<String>> = group.iter().map(|e| tokenizer.tokenize(e)).collect();
+            for (i, entry) in entries.iter().enumerate() {
+                let [token] = entry.as_slice() else {
+                    continue;
+                };
+                let expansions = table.entry(token.clone()).or_default();
+                for other in entries.iter().enumerate().filter(|&(j, _)| j != i) {
+                    for synonym in other.1 {
+                        if synonym != token && !expansions.contains(synonym) {
+                            expansions.push(synonym.clone());
+                        }
+                    }
+                }
+            }
+        }
+        let table = Arc::new(table);
+        self.synonym_tables
+            .insert(language.to_string(), table.clone());
+        table
+    }
+
+    /// `tokens` followed by the synonyms of each, in `language`.
+    fn expand_synonyms(&self, tokens: Vec<String>, language: &str) -> Vec<String> {
+        if self.synonyms.read().is_empty() {
+            return tokens;
+        }
+        let table = self.synonym_table(language);
+        let expansions: Vec<String> = tokens
+            .iter()
+            .filter_map(|token| table.get(token))
+            .flatten()
+            .cloned()
+            .collect();
+        let mut tokens = tokens;
+        tokens.extend(expansions);
+        tokens
+    }
+
+    /// `params` with the analyzer of the document `meta`: under
+    /// [`language::AUTO`], the one of its recorded language.
+    fn doc_params<'a>(
+        meta: &HashMap<String, String>,
+        params: &'a Bm25Params,
+    ) -> Cow<'a, Bm25Params> {
+        match meta.get(language::LANGUAGE_KEY) {
+            Some(lang) if params.language == language::AUTO => Cow::Owned(Bm25Params {
+                language: lang.clone(),
+                ..params.clone()
+            }),
+            _ => Cow::Borrowed(params),
+        }
+    }
+
+    /// Analyzers to read a query with. Under [`language::AUTO`] that is its
+    /// detected language plus the one of undetected documents, or every
+    /// language indexed so far when the query is too short to tell.
+    fn query_params(&self, text: &str, params: &Bm25Params) -> Vec<Bm25Params> {
+        if params.language != language::AUTO {
+            return vec![params.clone()];
+        }
+        let mut languages: Vec<String> = match language::detect(text) {
+            Some(detected) => vec![detected.to_string(), language::AUTO.to_string()],
+            None => self.languages.iter().map(|e| e.key().clone()).collect(),
+        };
+        if languages.is_empty() {
+            languages.push(language::AUTO.to_string());
+        }
+        languages
+            .into_iter()
+            .map(|language| Bm25Params {
+                language,
+                ..params.clone()
+            })
+            .collect()
+    }
+
+    /// Tokens of the query `text` as [`Self::tokenize`] gives them, in every
+    /// analyzer of [`Self::query_params`], and their synonyms.
+    pub fn query_tokens(&self, text: &str, params: &Bm25Params) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.query_params(text, params)
+            .iter()
+            .flat_map(|params| self.expand_synonyms(Self::tokenize(text, params), &params.language))
+            .filter(|token| seen.insert(token.clone()))
+            .collect()
+    }
+
+    fn doc_term_stats(
+        &self,
+        meta: &HashMap<String, String>,
+        params: &Bm25Params,
+    ) -> (HashMap<String, u16>, u32) {
+        let params = &*Self::doc_params(meta, params);
+        let mut term_freq = HashMap::new();
+        let mut doc_len: u32 = 0;
+        let unindexed = self.unindexed.read();
+        let table = self
+            .synonyms
+            .read()
+            .expand_at_index
+            .then(|| self.synonym_table(&params.language));
+
+        for (key, value) in meta {
+            if key.starts_with("__hs_") || is_unindexed(&unindexed, key) {
+                continue;
+            }
+            for token in Self::tokenize(value, params) {
+                doc_len = doc_len.saturating_add(1);
+                // Synonyms count as occurrences but not towards the length.
+                let synonyms = table.as_ref().and_then(|table| table.get(&token));
+                for token in synonyms.into_iter().flatten().cloned().chain([token]) {
+                    let entry = term_freq.entry(token).or_insert(0_u16);
+                    *entry = (*entry).saturating_add(1_u16);
+                }
+            }
+        }
+        (term_freq, doc_len)
+    }
+
+    fn doc_positions(
+        &self,
+        meta: &HashMap<String, String>,
+        params: &Bm25Params,
+    ) -> HashMap<String, Vec<u32>> {
+        let tokenizer = Self::tokenizer(&Self::doc_params(meta, params).language);
+        let unindexed = self.unindexed.read();
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut next: u32 = 0;
+
+        for (key, value) in meta {
+            if key.starts_with("__hs_") || is_unindexed(&unindexed, key) {
+                continue;
+            }
+            for token in tokenizer.tokenize(value) {
+                positions.entry(token).or_default().push(next);
+                next = next.saturating_add(1);
+            }
+            next = next.saturating_add(1);
+        }
+        positions
+    }
+
+    /// Tags, range-indexes and stores `meta` as document `id`, and adds it to
+    /// the BM25 statistics. Tags of a previous version of `id` are kept; see
+    /// [`Self::remove_doc`].
+    pub fn index_doc(&self, id: u32, mut meta: HashMap<String, String>, params: &Bm25Params) {
+        let unindexed = self.unindexed.read();
+        if params.language == language::AUTO && !meta.contains_key(language::LANGUAGE_KEY) {
+            let text = meta
+                .iter()
+                .filter(|(key, _)| !key.starts_with("__hs_") && !is_unindexed(&unindexed, key))
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if let Some(detected) = language::detect(&text) {
+                meta.insert(language::LANGUAGE_KEY.to_string(), detected.to_string());
+            }
+        }
+        for (key, val) in meta
+            .iter()
+            .filter(|(key, _)| !is_unindexed(&unindexed, key))
+        {
+            // A. Inverted Index (Text)
+            let tag = format!("{key}:{val}");
+            self.inverted.entry(tag).or_default().insert(id);
+
+            // B. Numeric Index (i64)
+            if let Ok(num) = val.parse::<i64>() {
+                self.numeric.entry(key.clone()).or_default().insert(num, id);
+            }
+        }
+
+        drop(unindexed);
+
+        self.count_distinct(&meta);
+        // Store full metadata for lookup (Data Explorer)
+        self.upsert_lexical_stats(id, &meta, params);
+        self.forward.insert(id, meta);
+    }
+
+    /// Drops every trace of document `id`: tags, numeric entries, lexical
+    /// statistics and its stored row. Tag keys embed the values themselves,
+    /// so keys left without documents are removed too.
+    pub fn remove_doc(&self, id: u32, params: &Bm25Params) {
+        let Some(meta) = self.forward.get(id) else {
+            return;
+        };
+        let (term_freq, _) = self.doc_term_stats(&meta, params);
+        self.remove_lexical_stats(id, params);
+        for token in term_freq.keys() {
+            self.remove_tag(&format!("_txt:{token}"), id);
+            self.term_doc_freq
+                .remove_if(token, |_, docs| docs.is_empty());
+        }
+        let unindexed = self.unindexed.read();
+        for (key, val) in meta
+            .iter()
+            .filter(|(key, _)| !is_unindexed(&unindexed, key))
+        {
+            self.remove_tag(&format!("{key}:{val}"), id);
+            if let Ok(num) = val.parse::<i64>() {
+                if let Some(index) = self.numeric.get(key) {
+                    index.remove(num, id);
+                }
+            }
+        }
+        self.forward.remove(id);
+    }
+
+    fn count_distinct(&self, meta: &HashMap<String, String>) {
+        for (key, val) in meta.iter().filter(|(key, _)| !key.starts_with("__hs_")) {
+            self.distinct.entry(key.clone()).or_default().insert(val);
+        }
+    }
+
+    /// Estimated distinct values per metadata key. Values of removed
+    /// documents keep counting until [`Self::rebuild_lexical_stats`].
+    pub fn distinct_values(&self) -> HashMap<String, u64> {
+        self.distinct
+            .iter()
+            .map(|item| (item.key().clone(), item.value().estimate()))
+            .collect()
+    }
+
+    fn remove_tag(&self, tag: &str, id: u32) {
+        let emptied = self.inverted.get_mut(tag).is_some_and(|mut ids| {
+            ids.remove(id);
+            ids.is_empty()
+        });
+        if emptied {
+            self.inverted.remove(tag);
+        }
+    }
+
+    fn remove_lexical_stats(&self, id: u32, params: &Bm25Params) {
+        if let Some((_, old_len)) = self.doc_token_len.remove(&id) {
+            self.total_token_len
+                .fetch_sub(u64::from(old_len), Ordering::Relaxed);
+        }
+        if let Some(old_meta) = self.forward.get(id) {
+            if params.language == language::AUTO {
+                let language = Self::doc_params(&old_meta, params).language.clone();
+                let emptied = self.languages.get_mut(&language).is_some_and(|mut docs| {
+                    *docs = docs.saturating_sub(1);
+                    *docs == 0
+                });
+                if emptied {
+                    self.languages.remove_if(&language, |_, docs| *docs == 0);
+                }
+            }
+            let (term_freq, _) = self.doc_term_stats(&old_meta, params);
+            for token in term_freq.keys() {
+                let token_key = format!("_txt:{token}");
+                if let Some(mut bitmap) = self.inverted.get_mut(&token_key) {
+                    bitmap.remove(id);
+                }
+                if let Some(mut df_ref) = self.token_df.get_mut(token) {
+                    if *df_ref > 1 {
+                        *df_ref -= 1;
+                    } else {
+                        drop(df_ref);
+                        self.token_df.remove(token);
+                    }
+                }
+                if let Some(mut tdf_ref) = self.term_doc_freq.get_mut(token) {
+                    tdf_ref.retain(|(doc_id, _)| *doc_id != id);
+                }
+                let emptied = self.term_positions.get_mut(token).is_some_and(|mut docs| {
+                    docs.remove(&id);
+                    docs.is_empty()
+                });
+                if emptied {
+                    self.term_positions
+                        .remove_if(token, |_, docs| docs.is_empty());
+                }
+            }
+        }
+    }
+
+    fn upsert_lexical_stats(&self, id: u32, meta: &HashMap<String, String>, params: &Bm25Params) {
+        self.remove_lexical_stats(id, params);
+        let (term_freq, doc_len) = self.doc_term_stats(meta, params);
+        if params.language == language::AUTO {
+            let language = Self::doc_params(meta, params).language.clone();
+            *self.languages.entry(language).or_insert(0) += 1;
+        }
+        self.doc_token_len.insert(id, doc_len);
+        self.total_token_len
+            .fetch_add(u64::from(doc_len), Ordering::Relaxed);
+        for (token, tf) in term_freq {
+            let token_key = format!("_txt:{token}");
+            self.inverted.entry(token_key).or_default().insert(id);
+            *self.token_df.entry(token.clone()).or_insert(0) += 1;
+            self.term_doc_freq.entry(token).or_default().push((id, tf));
+        }
+        for (token, positions) in self.doc_positions(meta, params) {
+            self.term_positions
+                .entry(token)
+                .or_default()
+                .insert(id, positions);
+        }
+    }
+
+    /// Recomputes the BM25 statistics, `_txt:` tags and distinct-value
+    /// sketches from the stored rows, e.g. after a snapshot restore or a
+    /// change of synonyms.
+    pub fn rebuild_lexical_stats(&self, params: &Bm25Params) {
+        self.inverted.remove_prefix("_txt:");
+        self.token_df.clear();
+        self.doc_token_len.clear();
+        self.term_doc_freq.clear();
+        self.term_positions.clear();
+        self.languages.clear();
+        self.distinct.clear();
+        self.total_token_len.store(0, Ordering::Relaxed);
+        for id in self.forward.ids() {
+            if let Some(meta) = self.forward.get(id) {
+                self.count_distinct(&meta);
+                self.upsert_lexical_stats(id, &meta, params);
+            }
+        }
+    }
+
+    /// Index size per metadata key, largest first.
+    pub fn key_stats(&self) -> Vec<MetadataKeyStats> {
+        let mut by_key: HashMap<(String, &str), MetadataKeyStats> = HashMap::new();
+        let mut add = |key: &str, kind: &'static str, len: u64, bytes: u64| {
+            let stats = by_key
+                .entry((key.to_string(), kind))
+                .or_insert_with(|| MetadataKeyStats {
+                    key: key.to_string(),
+                    kind: kind.to_string(),
+                    ..Default::default()
+                });
+            stats.bitmaps += 1;
+            stats.postings += len;
+            stats.max_cardinality = stats.max_cardinality.max(len);
+            stats.bytes += bytes;
+        };
+        for (tag, len, bytes) in self.inverted.bitmap_sizes() {
+            let key = tag.split_once(':').map_or(tag.as_str(), |(key, _)| key);
+            let kind = if key == "_txt" { "text" } else { "tag" };
+            add(key, kind, len, bytes);
+        }
+        for item in &self.numeric {
+            for (len, bytes) in item.value().bucket_sizes() {
+                add(item.key(), "numeric", len, bytes);
+            }
+        }
+        let mut stats: Vec<MetadataKeyStats> = by_key.into_values().collect();
+        stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
+        stats
+    }
+
+    /// Documents matching `filter` and the `Match`/`Range` expressions of
+    /// `complex_filters`; `None` when there are none. Geometric expressions
+    /// need vectors and are left to the caller, as are deleted ids.
+    pub fn filter_bitmap(
+        &self,
+        filter: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &Bm25Params,
+    ) -> Option<RoaringBitmap> {
+        let mut bitmap: Option<RoaringBitmap> = None;
+
+        let mut apply_mask = |mask: &RoaringBitmap| {
+            if let Some(ref mut bm) = bitmap {
+                *bm &= mask;
+            } else {
+                bitmap = Some(mask.clone());
+            }
+        };
+
+        for (key, val) in filter {
+            let tag = format!("{key}:{val}");
+            if let Some(tag_bitmap) = self.inverted.get(&tag) {
+                apply_mask(&tag_bitmap);
+            } else {
+                return Some(RoaringBitmap::new());
+            }
+        }
+
+        for expr in complex_filters {
+            match expr {
+                FilterExpr::Match { key, value } => {
+                    let tag = format!("{key}:{value}");
+                    if let Some(tag_bitmap) = self.inverted.get(&tag) {
+                        apply_mask(&tag_bitmap);
+                    } else {
+                        return Some(RoaringBitmap::new());
+                    }
+                }
+                FilterExpr::Range { key, gte, lte } => {
+                    let mut range_union = RoaringBitmap::new();
+
+                    // Ids in buckets straddling a bound are checked below
+                    // together with values the index does not hold.
+                    if let Some(index) = self.numeric.get(key) {
+                        let start = gte.map_or(i64::MIN, |x| x.ceil() as i64);
+                        let end = lte.map_or(i64::MAX, |x| x.floor() as i64);
+                        range_union = index.range(start, end);
+                    }
+
+                    let mut matched = RoaringBitmap::new();
+                    self.forward.for_each(|id, row| {
+                        if range_union.contains(id) {
+                            return;
+                        }
+                        let Some(num) = Self::numeric_value(row, key) else {
+                            return;
+                        };
+                        if gte.is_some_and(|min| num < min) || lte.is_some_and(|max| num > max) {
+                            return;
+                        }
+                        matched.insert(id);
+                    });
+                    range_union |= matched;
+
+                    if range_union.is_empty() {
+                        return Some(RoaringBitmap::new());
+                    }
+                    apply_mask(&range_union);
+                }
+                FilterExpr::PhraseMatch { phrase } => {
+                    if let Some(docs) = self.phrase_bitmap(phrase, params) {
+                        if docs.is_empty() {
+                            return Some(docs);
+                        }
+                        apply_mask(&docs);
+                    }
+                }
+                FilterExpr::InBox { .. }
+                | FilterExpr::InCone { .. }
+                | FilterExpr::InBall { .. } => {}
+            }
+        }
+        bitmap
+    }
+
+    /// Documents `text` admits under `mode`, and containing each of its
+    /// double-quoted phrases; `None` when it admits all of them. `All` needs
+    /// every token of `text`, `Phrase` the whole of `text` as one phrase.
+    pub fn text_match_bitmap(
+        &self,
+        text: &str,
+        mode: TextMatch,
+        params: &Bm25Params,
+    ) -> Option<RoaringBitmap> {
+        let mut matched = match mode {
+            TextMatch::Any => None,
+            TextMatch::All => {
+                self.match_in_any_language(text, params, |tokens| self.all_tokens_bitmap(tokens))
+            }
+            TextMatch::Phrase => self.phrase_bitmap(text, params),
+        };
+        for phrase in text.split('"').skip(1).step_by(2) {
+            let Some(docs) = self.phrase_bitmap(phrase, params) else {
+                continue;
+            };
+            match &mut matched {
+                Some(bm) => *bm &= docs,
+                None => matched = Some(docs),
+            }
+        }
+        matched
+    }
+
+    /// Documents with one field holding the tokens of `phrase` contiguously
+    /// and in order; `None` when `phrase` has no tokens, e.g. only stop
+    /// words.
+    pub fn phrase_bitmap(&self, phrase: &str, params: &Bm25Params) -> Option<RoaringBitmap> {
+        self.match_in_any_language(phrase, params, |tokens| self.phrase_tokens_bitmap(tokens))
+    }
+
+    /// Union of `docs` over the unigrams of `text` in each analyzer of
+    /// [`Self::query_params`]; `None` when none of them leaves a token.
+    fn match_in_any_language(
+        &self,
+        text: &str,
+        params: &Bm25Params,
+        docs: impl Fn(&[String]) -> Option<RoaringBitmap>,
+    ) -> Option<RoaringBitmap> {
+        let mut matched: Option<RoaringBitmap> = None;
+        for params in self.query_params(text, params) {
+            let tokens = Self::tokenizer(&params.language).tokenize(text);
+            if let Some(found) = docs(&tokens) {
+                *matched.get_or_insert_with(RoaringBitmap::new) |= found;
+            }
+        }
+        matched
+    }
+
+    fn phrase_tokens_bitmap(&self, tokens: &[String]) -> Option<RoaringBitmap> {
+        let candidates = self.all_tokens_bitmap(tokens)?;
+        if tokens.len() < 2 || candidates.is_empty() {
+            return Some(candidates);
+        }
+
+        // Copy the candidates' positions so no two map guards are held at once.
+        let mut postings: Vec<HashMap<u32, Vec<u32>>> = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let Some(docs) = self.term_positions.get(token) else {
+                return Some(RoaringBitmap::new());
+            };
+            postings.push(
+                candidates
+                    .iter()
+                    .filter_map(|id| Some((id, docs.get(&id)?.clone())))
+                    .collect(),
+            );
+        }
+        let (first, rest) = postings.split_first()?;
+        Some(
+            candidates
+                .iter()
+                .filter(|id| {
+                    first.get(id).is_some_and(|starts| {
+                        starts.iter().any(|&start| {
+                            rest.iter().zip(1..).all(|(docs, offset)| {
+                                docs.get(id)
+                                    .is_some_and(|pos| pos.binary_search(&(start + offset)).is_ok())
+                            })
+                        })
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether one text field of `meta` holds `phrase`, for rows checked
+    /// outside an index.
+    pub fn contains_phrase(
+        meta: &HashMap<String, String>,
+        phrase: &str,
+        params: &Bm25Params,
+    ) -> bool {
+        let tokenizer = Self::tokenizer(&Self::doc_params(meta, params).language);
+        let tokens = tokenizer.tokenize(phrase);
+        tokens.is_empty()
+            || meta.iter().any(|(key, value)| {
+                !key.starts_with("__hs_")
+                    && tokenizer
+                        .tokenize(value)
+                        .windows(tokens.len())
+                        .any(|window| window == tokens.as_slice())
+            })
+    }
+
+    /// Documents containing every one of `tokens`; `None` without tokens.
+    fn all_tokens_bitmap(&self, tokens: &[String]) -> Option<RoaringBitmap> {
+        let mut matched: Option<RoaringBitmap> = None;
+        for token in tokens {
+            let Some(docs) = self.inverted.get(&format!("_txt:{token}")) else {
+                return Some(RoaringBitmap::new());
+            };
+            match &mut matched {
+                Some(bm) => *bm &= &*docs,
+                None => matched = Some(docs.clone()),
+            }
+        }
+        matched
+    }
+
+    /// BM25 score of every document `allowed` lets through that contains
+    /// one of `tokens`, best first. `docs` is the number of live documents.
+    pub fn bm25_scores(
+        &self,
+        tokens: HashSet<String>,
+        params: &Bm25Params,
+        docs: usize,
+        allowed: impl Fn(u32) -> bool + Sync,
+    ) -> Vec<(u32, f64)> {
+        let total_docs = docs.max(1) as f64;
+        let avgdl = if docs == 0 {
+            1.0
+        } else {
+            self.total_token_len.load(Ordering::Relaxed) as f64 / total_docs
+        }
+        .max(1.0);
+
+        let keyword_scores = tokens
+            .into_par_iter()
+            .fold(HashMap::new, |mut acc, token| {
+                if let Some(doc_freqs) = self.term_doc_freq.get(&token) {
+                    let df = self
+                        .token_df
+                        .get(&token)
+                        .map_or_else(|| doc_freqs.value().len() as u32, |v| *v)
+                        .max(1);
+
+                    for &(id, tf) in doc_freqs.value() {
+                        if !allowed(id) {
+                            continue;
+                        }
+                        let tf_f32 = f32::from(tf);
+                        let dl = self
+                            .doc_token_len
+                            .get(&id)
+                            .map_or(0.0, |v| *v as f32)
+                            .max(1.0);
+
+                        let score = f64::from(hyperspace_core::bm25::score(
+                            params.method,
+                            tf_f32,
+                            dl,
+                            avgdl as f32,
+                            docs as u32,
+                            df,
+                            params.k1,
+                            params.b,
+                            params.delta,
+                        ));
+
+                        *acc.entry(id).or_insert(0.0) += score;
+                    }
+                }
+                acc
+            })
+            .reduce(HashMap::new, |mut a, b| {
+                for (k, v) in b {
+                    *a.entry(k).or_insert(0.0) += v;
+                }
+                a
+            });
+
+        let mut keyword_results: Vec<(u32, f64)> = keyword_scores.into_iter().collect();
+        keyword_results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        keyword_results
+    }
+}
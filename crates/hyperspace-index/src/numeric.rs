@@ -0,0 +1,215 @@
+//! Numeric metadata index for range filters.
+//!
+//! Each key keeps a histogram of its integer values: one bitmap of node ids
+//! per bucket of `2^shift` consecutive values. While a key has few distinct
+//! values the width is 1, so every bucket is one exact value. Once a key
+//! passes `HS_NUMERIC_MAX_BUCKETS` buckets (millisecond timestamps, counters)
+//! the width doubles and neighbouring buckets merge, so memory is bounded by
+//! the bucket limit instead of the cardinality. A range query takes the
+//! buckets inside the range whole; the ids of a partly covered edge bucket
+//! are left to the caller's check against the stored value.
+
+use crate::KeyedBitmaps;
+use parking_lot::RwLock;
+use roaring::RoaringBitmap;
+use std::collections::BTreeMap;
+
+/// Separates the key from the bucket shift in snapshot key names. Snapshots
+/// of older versions only hold exact (shift 0) keys, which are stored under
+/// the plain key; they see a bucketed key as an unknown one and fall back to
+/// scanning metadata.
+const SHIFT_SEPARATOR: char = '\u{1f}';
+
+fn max_buckets() -> usize {
+    static MAX: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+    *MAX.get_or_init(|| {
+        std::env::var("HS_NUMERIC_MAX_BUCKETS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(4096)
+    })
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    shift: u32,
+    buckets: BTreeMap<i64, RoaringBitmap>,
+}
+
+#[derive(Debug)]
+pub struct NumericIndex {
+    histogram: RwLock<Histogram>,
+    max_buckets: usize,
+}
+
+impl Default for NumericIndex {
+    fn default() -> Self {
+        Self::with_max_buckets(max_buckets())
+    }
+}
+
+impl NumericIndex {
+    pub fn with_max_buckets(max_buckets: usize) -> Self {
+        Self {
+            histogram: RwLock::new(Histogram::default()),
+            max_buckets: max_buckets.max(2),
+        }
+    }
+
+    /// Restores an index saved by [`NumericIndex::serialize`].
+    pub fn from_buckets(
+        shift: u32,
+        buckets: impl IntoIterator<Item = (i64, RoaringBitmap)>,
+    ) -> Self {
+        let index = Self::default();
+        {
+            let mut h = index.histogram.write();
+            h.shift = shift.min(63);
+            h.buckets = buckets.into_iter().collect();
+            index.coarsen(&mut h);
+        }
+        index
+    }
+
+    pub fn insert(&self, value: i64, id: u32) {
+        let mut h = self.histogram.write();
+        let bucket = value >> h.shift;
+        h.buckets.entry(bucket).or_default().insert(id);
+        if h.buckets.len() > self.max_buckets {
+            self.coarsen(&mut h);
+        }
+    }
+
+    /// Removes `id` from the bucket of `value`, dropping the bucket once it
+    /// is empty.
+    pub fn remove(&self, value: i64, id: u32) {
+        let mut h = self.histogram.write();
+        let bucket = value >> h.shift;
+        if let Some(ids) = h.buckets.get_mut(&bucket) {
+            ids.remove(id);
+            if ids.is_empty() {
+                h.buckets.remove(&bucket);
+            }
+        }
+    }
+
+    /// Doubles the bucket width until the bucket limit holds.
+    fn coarsen(&self, h: &mut Histogram) {
+        while h.buckets.len() > self.max_buckets && h.shift < 63 {
+            let mut merged: BTreeMap<i64, RoaringBitmap> = BTreeMap::new();
+            for (bucket, ids) in std::mem::take(&mut h.buckets) {
+                *merged.entry(bucket >> 1).or_default() |= ids;
+            }
+            h.buckets = merged;
+            h.shift += 1;
+        }
+    }
+
+    /// Bucket width as a power of two; 0 while values are exact.
+    pub fn shift(&self) -> u32 {
+        self.histogram.read().shift
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        self.histogram.read().buckets.len()
+    }
+
+    /// Cardinality and serialized size of every bucket bitmap.
+    pub fn bucket_sizes(&self) -> Vec<(u64, u64)> {
+        let h = self.histogram.read();
+        h.buckets
+            .values()
+            .map(|bitmap| (bitmap.len(), bitmap.serialized_size() as u64))
+            .collect()
+    }
+
+    /// Ids whose value is certainly within `start..=end`. Ids of a bucket
+    /// that straddles either bound are not included.
+    pub fn range(&self, start: i64, end: i64) -> RoaringBitmap {
+        let mut ids = RoaringBitmap::new();
+        if start > end {
+            return ids;
+        }
+        let h = self.histogram.read();
+        // Two's complement keeps `lo | mask` inside the bucket for negative
+        // values too, without overflowing at the ends of the i64 range.
+        let mask = (1i64 << h.shift) - 1;
+        for (&bucket, bitmap) in h.buckets.range((start >> h.shift)..=(end >> h.shift)) {
+            let lo = bucket << h.shift;
+            if start <= lo && lo | mask <= end {
+                ids |= bitmap;
+            }
+        }
+        ids
+    }
+
+    /// The bucket shift and the serialized bucket bitmaps.
+    pub fn serialize(&self) -> std::io::Result<(u32, KeyedBitmaps)> {
+        let h = self.histogram.read();
+        let mut buckets = Vec::with_capacity(h.buckets.len());
+        for (&bucket, bitmap) in &h.buckets {
+            let mut buf = Vec::new();
+            bitmap.serialize_into(&mut buf)?;
+            buckets.push((bucket, buf));
+        }
+        Ok((h.shift, buckets))
+    }
+}
+
+/// Key name under which `key` is stored in a snapshot.
+pub fn snapshot_key(key: &str, shift: u32) -> String {
+    if shift == 0 {
+        key.to_string()
+    } else {
+        format!("{key}{SHIFT_SEPARATOR}{shift}")
+    }
+}
+
+/// Inverse of [`snapshot_key`].
+pub fn parse_snapshot_key(name: &str) -> (&str, u32) {
+    name.rsplit_once(SHIFT_SEPARATOR)
+        .and_then(|(key, shift)| Some((key, shift.parse().ok()?)))
+        .unwrap_or((name, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_merge_past_the_limit() {
+        let index = NumericIndex::with_max_buckets(8);
+        for id in 0..100u32 {
+            index.insert(1_700_000_000_000 + i64::from(id), id);
+        }
+        assert!(index.bucket_count() <= 8);
+        assert!(index.shift() > 0);
+
+        // Whole buckets only: every returned id is in range, and a range
+        // covering everything returns everything.
+        let ids = index.range(1_700_000_000_010, 1_700_000_000_089);
+        assert!(ids.iter().all(|id| (10..90).contains(&id)));
+        assert!(ids.len() >= 40);
+        assert_eq!(index.range(i64::MIN, i64::MAX).len(), 100);
+    }
+
+    #[test]
+    fn exact_values_are_exact() {
+        let index = NumericIndex::with_max_buckets(16);
+        for id in 0u32..10 {
+            index.insert(i64::from(id) - 5, id);
+        }
+        assert_eq!(index.shift(), 0);
+        assert_eq!(
+            index.range(-2, 1).iter().collect::<Vec<_>>(),
+            vec![3, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn snapshot_keys_round_trip() {
+        assert_eq!(parse_snapshot_key(&snapshot_key("ts", 12)), ("ts", 12));
+        assert_eq!(parse_snapshot_key(&snapshot_key("ts", 0)), ("ts", 0));
+        assert_eq!(snapshot_key("ts", 0), "ts");
+    }
+}
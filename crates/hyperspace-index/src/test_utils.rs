@@ -0,0 +1,290 @@
+//! Recall harness and graph checks for regression tests (feature `test-utils`).
+//!
+//! Generates seeded synthetic datasets, builds an index over them and
+//! measures recall@k against brute force. Everything is deterministic: the
+//! same seed gives the same points, node levels come from the node ID, and
+//! [`build_index`] inserts on one thread, so a recall number only moves when
+//! the index code does. [`check_graph`] checks the structural invariants
+//! the property and fuzz tests rely on.
+//!
+//! ```ignore
+//! let data = test_utils::clustered_gaussians(7, 2_000, 50, 16, 20, 0.05);
+//! let built = test_utils::build_index::<16, EuclideanMetric>(&data.points, 16, 100);
+//! assert!(test_utils::recall_at_k(&built.index, &data, 10, 64) >= 0.95);
+//! ```
+
+use crate::HnswIndex;
+use hyperspace_core::{GlobalConfig, Metric, QuantizationMode, SearchParams};
+use hyperspace_store::VectorStore;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Points to index and queries to run against them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    pub points: Vec<Vec<f64>>,
+    pub queries: Vec<Vec<f64>>,
+}
+
+/// Standard normal sample (Box-Muller).
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+fn unit(v: &mut [f64]) {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        for x in v {
+            *x /= norm;
+        }
+    }
+}
+
+/// `n` points around `clusters` centres drawn from `[-1, 1]^dim`, with
+/// per-axis standard deviation `sigma`. Queries come from the same mixture.
+pub fn clustered_gaussians(
+    seed: u64,
+    n: usize,
+    queries: usize,
+    dim: usize,
+    clusters: usize,
+    sigma: f64,
+) -> Dataset {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let centres: Vec<Vec<f64>> = (0..clusters.max(1))
+        .map(|_| (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect())
+        .collect();
+    let sample = |rng: &mut StdRng| {
+        let centre = &centres[rng.gen_range(0..centres.len())];
+        centre
+            .iter()
+            .map(|c| c + sigma * gaussian(rng))
+            .collect::<Vec<f64>>()
+    };
+    Dataset {
+        points: (0..n).map(|_| sample(&mut rng)).collect(),
+        queries: (0..queries).map(|_| sample(&mut rng)).collect(),
+    }
+}
+
+/// `n` nodes of a tree with the given branching factor embedded in the
+/// Poincaré ball: the root sits at the origin and each level lies one
+/// hyperbolic step further out, children fanning around their parent's
+/// direction. Queries are jittered copies of random nodes.
+pub fn hyperbolic_tree(
+    seed: u64,
+    n: usize,
+    queries: usize,
+    dim: usize,
+    branching: usize,
+) -> Dataset {
+    const STEP: f64 = 0.6;
+    const MAX_NORM: f64 = 0.95;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let branching = branching.max(1);
+    let mut nodes: Vec<(Vec<f64>, usize)> = Vec::with_capacity(n);
+    let mut points = Vec::with_capacity(n);
+    for i in 0..n {
+        if i == 0 {
+            nodes.push((vec![0.0; dim], 0));
+            points.push(vec![0.0; dim]);
+            continue;
+        }
+        let (parent_dir, parent_depth) = &nodes[(i - 1) / branching];
+        let mut dir: Vec<f64> = parent_dir
+            .iter()
+            .map(|d| d + 0.3 * gaussian(&mut rng))
+            .collect();
+        unit(&mut dir);
+        let depth = parent_depth + 1;
+        let rho = depth as f64 * STEP + 0.1 * gaussian(&mut rng);
+        let r = (rho.abs() / 2.0).tanh().min(MAX_NORM);
+        points.push(dir.iter().map(|d| d * r).collect());
+        nodes.push((dir, depth));
+    }
+    let queries = (0..queries)
+        .map(|_| {
+            let p = &points[rng.gen_range(0..points.len())];
+            let mut q: Vec<f64> = p.iter().map(|x| x + 0.01 * gaussian(&mut rng)).collect();
+            let norm = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm > MAX_NORM {
+                for x in &mut q {
+                    *x *= MAX_NORM / norm;
+                }
+            }
+            q
+        })
+        .collect();
+    Dataset { points, queries }
+}
+
+/// An index over a temporary directory, removed on drop.
+pub struct TestIndex<const N: usize, M: Metric<N>> {
+    pub index: HnswIndex<N, M>,
+    _dir: tempfile::TempDir,
+}
+
+/// Inserts `points` in order on one thread; point `i` gets ID `i`.
+pub fn build_index<const N: usize, M: Metric<N>>(
+    points: &[Vec<f64>],
+    m: usize,
+    ef_construction: usize,
+) -> TestIndex<N, M> {
+    let config = GlobalConfig::default();
+    config.set_m(m);
+    config.set_ef_construction(ef_construction);
+    build_index_with(points, config)
+}
+
+/// [`build_index`] with a full config, e.g. to turn on heuristic options.
+pub fn build_index_with<const N: usize, M: Metric<N>>(
+    points: &[Vec<f64>],
+    config: GlobalConfig,
+) -> TestIndex<N, M> {
+    build_index_with_metadata(points, config, QuantizationMode::None, |_| HashMap::new())
+}
+
+/// [`build_index_with`] storing vectors in `mode` and giving point `i` the
+/// metadata `metadata(i)`, e.g. tags to filter on.
+pub fn build_index_with_metadata<const N: usize, M: Metric<N>>(
+    points: &[Vec<f64>],
+    config: GlobalConfig,
+    mode: QuantizationMode,
+    metadata: impl Fn(usize) -> HashMap<String, String>,
+) -> TestIndex<N, M> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = Arc::new(config);
+    let element_size = match mode {
+        QuantizationMode::None => hyperspace_core::vector::HyperVector::<N>::SIZE,
+        QuantizationMode::ScalarI8 => hyperspace_core::vector::QuantizedHyperVector::<N>::SIZE,
+        QuantizationMode::Binary => hyperspace_core::vector::BinaryHyperVector::<N>::SIZE,
+    };
+    let storage = Arc::new(VectorStore::new(&dir.path().join("vectors"), element_size));
+    let index = HnswIndex::new(storage, mode, config);
+    for (i, p) in points.iter().enumerate() {
+        let id = index.insert(p, metadata(i)).expect("insert");
+        assert_eq!(id as usize, i, "IDs must follow insert order");
+    }
+    TestIndex { index, _dir: dir }
+}
+
+fn coords<const N: usize>(v: &[f64]) -> [f64; N] {
+    v.try_into().expect("vector dimension must match N")
+}
+
+/// IDs of the `k` points nearest to `query`, by exhaustive scan.
+pub fn brute_force<const N: usize, M: Metric<N>>(
+    points: &[Vec<f64>],
+    query: &[f64],
+    k: usize,
+) -> Vec<u32> {
+    let q = coords::<N>(query);
+    let mut scored: Vec<(f64, u32)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (M::distance(&coords::<N>(p), &q), i as u32))
+        .collect();
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().take(k).map(|(_, id)| id).collect()
+}
+
+/// Mean recall@k of `index` over the dataset's queries: the share of each
+/// query's true `k` nearest points the index returns in its top `k`.
+pub fn recall_at_k<const N: usize, M: Metric<N>>(
+    index: &HnswIndex<N, M>,
+    dataset: &Dataset,
+    k: usize,
+    ef_search: usize,
+) -> f64 {
+    let params = SearchParams {
+        top_k: k,
+        ef_search,
+        ..Default::default()
+    };
+    recall_with(index, dataset, &params)
+}
+
+/// [`recall_at_k`] with every search parameter given; `k` is `params.top_k`.
+pub fn recall_with<const N: usize, M: Metric<N>>(
+    index: &HnswIndex<N, M>,
+    dataset: &Dataset,
+    params: &SearchParams,
+) -> f64 {
+    let k = params.top_k;
+    if dataset.queries.is_empty() || k == 0 {
+        return 1.0;
+    }
+    let mut total = 0.0;
+    for query in &dataset.queries {
+        let truth = brute_force::<N, M>(&dataset.points, query, k);
+        let found: Vec<u32> = index
+            .search(query, &HashMap::new(), &[], params)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let hits = truth.iter().filter(|id| found.contains(id)).count();
+        total += hits as f64 / truth.len().max(1) as f64;
+    }
+    total / dataset.queries.len() as f64
+}
+
+/// Checks the structural invariants of `index`'s graph and returns the first
+/// one broken:
+///
+/// - with any live node, the entry point is live and on the top layer
+/// - a node on a layer is on every layer below it
+/// - every link joins two distinct live nodes of the same layer, so no link
+///   dangles into another layer, a purged node or past the end
+/// - no node keeps more links than its layer allows after pruning: `2 * m`
+///   on layer 0, `m` above
+///
+/// Links are directed (a pruned list drops reverse edges), so each end of a
+/// link is checked for layer membership rather than for a reverse link.
+pub fn check_graph<const N: usize, M: Metric<N>>(index: &HnswIndex<N, M>) -> Result<(), String> {
+    let m = index.config.get_m();
+    let top = index.max_layer();
+    let mut below: Option<HashSet<u32>> = None;
+    for layer in 0..=top {
+        let nodes = index.graph_layer(layer);
+        let on_layer: HashSet<u32> = nodes.iter().map(|(id, _)| *id).collect();
+        if let Some(below) = &below {
+            if let Some(id) = on_layer.iter().find(|id| !below.contains(id)) {
+                return Err(format!("Node {id} is on layer {layer} but not below it"));
+            }
+        }
+        let m_max = if layer == 0 { 2 * m } else { m };
+        for (id, links) in &nodes {
+            if links.len() > m_max {
+                return Err(format!(
+                    "Node {id} has {} links on layer {layer}, at most {m_max} allowed",
+                    links.len()
+                ));
+            }
+            for (target, _) in links {
+                if target == id {
+                    return Err(format!("Node {id} links to itself on layer {layer}"));
+                }
+                if !on_layer.contains(target) {
+                    return Err(format!(
+                        "Node {id} links to {target} on layer {layer}, which is not on it"
+                    ));
+                }
+            }
+        }
+        below = Some(on_layer);
+    }
+
+    let live = index.graph_layer(0);
+    match index.entry_point() {
+        None if !live.is_empty() => Err(format!("No entry point, with {} live nodes", live.len())),
+        Some(ep) if !index.graph_layer(top).iter().any(|(id, _)| *id == ep) => {
+            Err(format!("Entry point {ep} is not on the top layer {top}"))
+        }
+        _ => Ok(()),
+    }
+}
@@ -12,7 +12,8 @@ fn test_concurrent_indexing_stress() {
     config.set_m(16);
     config.set_ef_construction(100);
 
-    let storage = Arc::new(VectorStore::new(std::path::Path::new("mem"), 4)); // 1 float
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(VectorStore::new(dir.path(), 4)); // 1 float
     let index: Arc<HnswIndex<1, EuclideanMetric>> =
         Arc::new(HnswIndex::new(storage, QuantizationMode::None, config));
 
@@ -51,3 +51,142 @@ fn test_graph_traversal_api_basics() {
     assert!(!clusters.is_empty(), "clusters should not be empty");
     assert!(clusters.iter().all(|c| c.len() >= 3));
 }
+
+#[test]
+fn test_graph_layer_export_matches_neighbors() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = Arc::new(GlobalConfig::default());
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        hyperspace_core::vector::HyperVector::<2>::SIZE,
+    ));
+    let index: HnswIndex<2, EuclideanMetric> =
+        HnswIndex::new(storage, QuantizationMode::None, config);
+    for i in 0..200u32 {
+        let x = f64::from(i) * 0.01;
+        index.insert(&[x, 1.0 - x], HashMap::new()).expect("insert");
+    }
+    index.delete(7);
+
+    let layer0 = index.graph_layer(0);
+    assert_eq!(layer0.len(), 199);
+    assert!(layer0.iter().all(|(id, _)| *id != 7));
+    let (id, edges) = &layer0[0];
+    let targets: Vec<u32> = edges.iter().map(|(t, _)| *t).collect();
+    assert_eq!(targets, index.graph_neighbors(*id, 0, usize::MAX).unwrap());
+    for (t, d) in edges {
+        let expected = (f64::from(*t) * 0.01 - f64::from(*id) * 0.01).powi(2) * 2.0;
+        assert!((d - expected).abs() < 1e-9, "{d} vs {expected}");
+    }
+
+    // Upper layers hold a subset of the nodes; above the top there are none.
+    let top = index.max_layer();
+    assert!(index.graph_layer(top).len() <= layer0.len());
+    assert_eq!(index.graph_layer(top + 1), Vec::new());
+}
+
+#[test]
+fn test_deleting_entry_point_reelects_and_lowers_max_layer() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        hyperspace_core::vector::HyperVector::<2>::SIZE,
+    ));
+    let index: HnswIndex<2, EuclideanMetric> = HnswIndex::new(
+        storage,
+        QuantizationMode::None,
+        Arc::new(GlobalConfig::default()),
+    );
+    for i in 0..300u32 {
+        let x = f64::from(i) * 0.01;
+        index.insert(&[x, -x], HashMap::new()).expect("insert");
+    }
+    let top = index.max_layer();
+    assert!(top >= 2, "test needs a few layers, got {top}");
+
+    let old = index.entry_point().unwrap();
+    index.delete(old);
+    let new = index.entry_point().expect("a live entry point");
+    assert_ne!(new, old);
+    assert!(index
+        .graph_layer(index.max_layer())
+        .iter()
+        .any(|(id, _)| *id == new));
+
+    // Emptying the top layer moves the graph's top down.
+    for (id, _) in index.graph_layer(index.max_layer()) {
+        index.delete(id);
+    }
+    assert!(index.max_layer() < top);
+    assert!(index.entry_point().is_some());
+    let params = hyperspace_core::SearchParams {
+        top_k: 5,
+        ef_search: 50,
+        ..Default::default()
+    };
+    let hits = index.search(&[1.0, -1.0], &HashMap::new(), &[], &params);
+    assert_eq!(hits.len(), 5);
+    assert!(hits.iter().all(|(id, _)| *id != old));
+}
+
+#[test]
+fn test_purge_erases_node_and_keeps_graph_connected() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        hyperspace_core::vector::HyperVector::<2>::SIZE,
+    ));
+    let index: HnswIndex<2, EuclideanMetric> = HnswIndex::new(
+        storage,
+        QuantizationMode::None,
+        Arc::new(GlobalConfig::default()),
+    );
+    for i in 0..300u32 {
+        let x = f64::from(i) * 0.01;
+        let mut meta = HashMap::new();
+        meta.insert("owner".to_string(), format!("user{}", i % 10));
+        meta.insert("year".to_string(), (2000 + i % 10).to_string());
+        meta.insert("note".to_string(), format!("private note {i}"));
+        index.insert(&[x, -x], meta).expect("insert");
+    }
+
+    let mut filter = HashMap::new();
+    filter.insert("owner".to_string(), "user3".to_string());
+    let doomed: Vec<u32> = index.matching_ids(&filter, &[]).unwrap().iter().collect();
+    assert_eq!(doomed.len(), 30);
+    assert!(index.matching_ids(&HashMap::new(), &[]).is_none());
+    assert!(index.metadata.inverted.get("_txt:33").is_some());
+    index.purge(&doomed).expect("purge");
+
+    let storage = index.get_storage();
+    for &id in &doomed {
+        assert!(index.is_deleted(id));
+        assert!(index.metadata_by_id(id).is_empty());
+        assert!(storage.get(id).iter().all(|&b| b == 0));
+        assert!(index.graph_neighbors(id, 0, usize::MAX).unwrap().is_empty());
+    }
+    assert!(index.metadata.inverted.get("owner:user3").is_none());
+    assert!(index.metadata.inverted.get("_txt:33").is_none());
+    let range = [hyperspace_core::FilterExpr::Range {
+        key: "year".to_string(),
+        gte: Some(2003.0),
+        lte: Some(2003.0),
+    }];
+    assert!(index
+        .matching_ids(&HashMap::new(), &range)
+        .unwrap()
+        .is_empty());
+    assert!(index.purge(&[10_000]).is_err());
+
+    // Every survivor is still reachable.
+    let params = hyperspace_core::SearchParams {
+        top_k: 1,
+        ef_search: 64,
+        ..Default::default()
+    };
+    for i in (0..300u32).filter(|i| i % 10 != 3) {
+        let x = f64::from(i) * 0.01;
+        let hits = index.search(&[x, -x], &HashMap::new(), &[], &params);
+        assert_eq!(hits[0].0, i);
+    }
+}
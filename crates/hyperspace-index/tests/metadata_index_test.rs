@@ -0,0 +1,347 @@
+use hyperspace_core::bm25::Bm25Params;
+use hyperspace_core::FilterExpr;
+use hyperspace_index::MetadataIndex;
+use std::collections::{HashMap, HashSet};
+
+fn doc(body: &str, year: &str) -> HashMap<String, String> {
+    HashMap::from([
+        ("body".to_string(), body.to_string()),
+        ("year".to_string(), year.to_string()),
+    ])
+}
+
+fn tokens(text: &str, params: &Bm25Params) -> HashSet<String> {
+    MetadataIndex::tokenize(text, params).into_iter().collect()
+}
+
+#[test]
+fn test_standalone_keyword_search() {
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.index_doc(1, doc("hyperbolic embeddings for trees", "2019"), &params);
+    index.index_doc(2, doc("trees and forests", "2021"), &params);
+    index.index_doc(3, doc("euclidean baselines", "2021"), &params);
+
+    let hits = index.bm25_scores(tokens("trees", &params), &params, 3, |_| true);
+    let mut ids: Vec<u32> = hits.iter().map(|&(id, _)| id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2]);
+    // The shorter document ranks first.
+    assert_eq!(hits[0].0, 2);
+
+    let recent = index
+        .filter_bitmap(
+            &HashMap::new(),
+            &[FilterExpr::Range {
+                key: "year".into(),
+                gte: Some(2020.0),
+                lte: None,
+            }],
+            &params,
+        )
+        .unwrap();
+    let hits = index.bm25_scores(tokens("trees", &params), &params, 3, |id| {
+        recent.contains(id)
+    });
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].0, 2);
+
+    assert!(index.filter_bitmap(&HashMap::new(), &[], &params).is_none());
+}
+
+#[test]
+fn test_remove_doc_forgets_everything() {
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.index_doc(7, doc("unique snowflake", "2020"), &params);
+    index.remove_doc(7, &params);
+
+    assert!(index.forward.get(7).is_none());
+    assert!(index.inverted.is_empty());
+    assert!(index.doc_token_len.is_empty());
+    assert!(index
+        .bm25_scores(tokens("snowflake", &params), &params, 0, |_| true)
+        .is_empty());
+    let filter = HashMap::from([("year".to_string(), "2020".to_string())]);
+    assert!(index
+        .filter_bitmap(&filter, &[], &params)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_key_stats_flags_exploding_keys() {
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    for id in 0..100u32 {
+        let meta = HashMap::from([
+            ("trace".to_string(), format!("{id:08x}-uuid")),
+            (
+                "lang".to_string(),
+                if id % 2 == 0 { "en" } else { "de" }.to_string(),
+            ),
+            ("year".to_string(), (2000 + id % 4).to_string()),
+        ]);
+        index.index_doc(id, meta, &params);
+    }
+
+    let stats = index.key_stats();
+    let get = |key: &str, kind: &str| {
+        stats
+            .iter()
+            .find(|s| s.key == key && s.kind == kind)
+            .unwrap_or_else(|| panic!("no {kind} stats for {key}"))
+    };
+    let trace = get("trace", "tag");
+    assert_eq!(
+        (trace.bitmaps, trace.postings, trace.max_cardinality),
+        (100, 100, 1)
+    );
+    let lang = get("lang", "tag");
+    assert_eq!(
+        (lang.bitmaps, lang.postings, lang.max_cardinality),
+        (2, 100, 50)
+    );
+    assert_eq!(get("year", "numeric").postings, 100);
+    assert!(get("_txt", "text").bitmaps > 100);
+    // Largest first: one bitmap per point costs the most.
+    let first_tag = stats.iter().find(|s| s.kind == "tag").unwrap();
+    assert_eq!(first_tag.key, "trace");
+}
+
+#[test]
+fn test_unindexed_keys_are_stored_only() {
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.set_unindexed_keys(HashSet::from(["blob".to_string()]));
+    let meta = HashMap::from([
+        ("blob".to_string(), "zebra quokka 42".to_string()),
+        (
+            "__hs_typed__blob".to_string(),
+            r#"{"t":"s","v":"x"}"#.to_string(),
+        ),
+        ("lang".to_string(), "en".to_string()),
+    ]);
+    index.index_doc(1, meta, &params);
+
+    assert_eq!(index.forward.get(1).unwrap()["blob"], "zebra quokka 42");
+    assert!(index
+        .bm25_scores(tokens("zebra", &params), &params, 1, |_| true)
+        .is_empty());
+    assert!(index.numeric.get("blob").is_none());
+    assert!(index
+        .key_stats()
+        .iter()
+        .all(|s| s.key == "lang" || s.key == "_txt"));
+    let filter = HashMap::from([("lang".to_string(), "en".to_string())]);
+    assert_eq!(index.filter_bitmap(&filter, &[], &params).unwrap().len(), 1);
+
+    index.remove_doc(1, &params);
+    assert!(index.inverted.is_empty());
+}
+
+#[test]
+fn test_text_match_all_and_phrase() {
+    use hyperspace_core::TextMatch;
+
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.index_doc(1, doc("hyperbolic trees embed well", "2019"), &params);
+    index.index_doc(2, doc("trees are hyperbolic", "2020"), &params);
+    index.index_doc(3, doc("hyperbolic geometry", "2021"), &params);
+
+    let ids = |mode| {
+        index
+            .text_match_bitmap("hyperbolic trees", mode, &params)
+            .map(|bm| bm.iter().collect::<Vec<_>>())
+    };
+    assert_eq!(ids(TextMatch::Any), None);
+    assert_eq!(ids(TextMatch::All), Some(vec![1, 2]));
+    assert_eq!(ids(TextMatch::Phrase), Some(vec![1]));
+
+    let missing = index.text_match_bitmap("hyperbolic forests", TextMatch::All, &params);
+    assert!(missing.is_some_and(|bm| bm.is_empty()));
+}
+
+#[test]
+fn test_positional_phrase_queries() {
+    use hyperspace_core::TextMatch;
+
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.index_doc(1, doc("machine learning at scale", "2019"), &params);
+    index.index_doc(2, doc("learning about the machine", "2020"), &params);
+    let split = HashMap::from([
+        ("title".to_string(), "deep machine".to_string()),
+        ("body".to_string(), "learning systems".to_string()),
+    ]);
+    index.index_doc(3, split, &params);
+
+    let phrase = [FilterExpr::PhraseMatch {
+        phrase: "machine learning".into(),
+    }];
+    let ids: Vec<u32> = index
+        .filter_bitmap(&HashMap::new(), &phrase, &params)
+        .unwrap()
+        .iter()
+        .collect();
+    // Document 3 has both tokens, but in different fields.
+    assert_eq!(ids, vec![1]);
+
+    let quoted = index
+        .text_match_bitmap("\"machine learning\" scale", TextMatch::Any, &params)
+        .unwrap();
+    assert_eq!(quoted.iter().collect::<Vec<_>>(), vec![1]);
+
+    index.remove_doc(1, &params);
+    assert!(index
+        .text_match_bitmap("\"machine learning\"", TextMatch::Any, &params)
+        .unwrap()
+        .is_empty());
+    index.remove_doc(2, &params);
+    index.remove_doc(3, &params);
+    assert!(index.term_positions.is_empty());
+}
+
+#[test]
+fn test_auto_language_analyzes_each_document() {
+    use hyperspace_index::language::{AUTO, LANGUAGE_KEY};
+
+    let params = Bm25Params {
+        language: AUTO.to_string(),
+        ..Bm25Params::default()
+    };
+    let index = MetadataIndex::default();
+    index.index_doc(
+        1,
+        doc("the runners were running in the park", "2020"),
+        &params,
+    );
+    index.index_doc(
+        2,
+        doc("die Kinder spielen und laufen im Garten", "2021"),
+        &params,
+    );
+    index.index_doc(3, doc("Собаки бегают по парку", "2022"), &params);
+
+    let lang = |id| index.forward.get(id).unwrap()[LANGUAGE_KEY].clone();
+    assert_eq!(lang(1), "english");
+    assert_eq!(lang(2), "german");
+    assert_eq!(lang(3), "russian");
+    // Each document is stemmed by its own language's analyzer.
+    assert!(index.term_doc_freq.contains_key("run"));
+    assert!(index.term_doc_freq.contains_key("spiel"));
+
+    // A one-word query is read in every indexed language.
+    let hits = index.bm25_scores(
+        index.query_tokens("running", &params).into_iter().collect(),
+        &params,
+        3,
+        |_| true,
+    );
+    assert_eq!(hits.first().map(|&(id, _)| id), Some(1));
+    let hits = index.bm25_scores(
+        index.query_tokens("парки", &params).into_iter().collect(),
+        &params,
+        3,
+        |_| true,
+    );
+    assert_eq!(hits.first().map(|&(id, _)| id), Some(3));
+
+    index.remove_doc(2, &params);
+    assert!(!index.languages.contains_key("german"));
+}
+
+#[test]
+fn test_synonyms_expand_queries_and_documents() {
+    use hyperspace_core::{SynonymMap, TextMatch};
+
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.index_doc(1, doc("a reliable automobile for sale", "2020"), &params);
+    index.index_doc(2, doc("a bicycle for sale", "2021"), &params);
+
+    let search = |text: &str| -> Vec<u32> {
+        index
+            .bm25_scores(
+                index.query_tokens(text, &params).into_iter().collect(),
+                &params,
+                2,
+                |_| true,
+            )
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    };
+    assert!(search("cars").is_empty());
+
+    index.set_synonyms(SynonymMap {
+        groups: vec![vec!["car".into(), "automobile".into(), "motor car".into()]],
+        expand_at_index: false,
+    });
+    // Entries go through the analyzer, so the plural expands too.
+    assert_eq!(search("cars"), vec![1]);
+    assert!(index
+        .text_match_bitmap("car", TextMatch::All, &params)
+        .is_some_and(|docs| docs.is_empty()));
+
+    index.set_synonyms(SynonymMap {
+        groups: vec![vec!["car".into(), "automobile".into()]],
+        expand_at_index: true,
+    });
+    index.rebuild_lexical_stats(&params);
+    let docs = index
+        .text_match_bitmap("car sale", TextMatch::All, &params)
+        .unwrap();
+    assert_eq!(docs.iter().collect::<Vec<_>>(), vec![1]);
+
+    index.set_synonyms(SynonymMap::default());
+    index.rebuild_lexical_stats(&params);
+    assert!(index.inverted.get("_txt:car").is_none());
+    index.remove_doc(1, &params);
+    assert!(index.inverted.get("_txt:automobil").is_none());
+}
+
+#[test]
+fn test_distinct_values_are_estimated_per_key() {
+    let params = Bm25Params::default();
+    let index = MetadataIndex::default();
+    index.set_unindexed_keys(HashSet::from(["session".to_string()]));
+    for id in 0..10_000u32 {
+        let meta = HashMap::from([
+            ("user_id".to_string(), format!("u{id}")),
+            ("session".to_string(), format!("s{}", id % 2_000)),
+            (
+                "plan".to_string(),
+                ["free", "pro", "team"][id as usize % 3].to_string(),
+            ),
+            (
+                "__hs_typed__plan".to_string(),
+                r#"{"t":"s","v":"x"}"#.to_string(),
+            ),
+        ]);
+        index.index_doc(id, meta, &params);
+    }
+
+    let distinct = index.distinct_values();
+    let within = |key: &str, exact: f64| {
+        let estimate = distinct[key] as f64;
+        assert!(
+            (estimate - exact).abs() / exact < 0.05,
+            "{key}: {estimate} vs {exact}"
+        );
+    };
+    within("user_id", 10_000.0);
+    // Unindexed keys are sketched too.
+    within("session", 2_000.0);
+    assert_eq!(distinct["plan"], 3);
+    assert!(!distinct.contains_key("__hs_typed__plan"));
+
+    // Removed values keep counting until the rows are scanned again.
+    for id in 3..10_000u32 {
+        index.remove_doc(id, &params);
+    }
+    within("user_id", 10_000.0);
+    index.rebuild_lexical_stats(&params);
+    assert_eq!(index.distinct_values()["user_id"], 3);
+}
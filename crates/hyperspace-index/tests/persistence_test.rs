@@ -102,3 +102,59 @@ fn test_index_save_load() {
         .unwrap()
         .contains(1));
 }
+
+#[test]
+fn test_tag_bitmaps_load_lazily_and_survive_resave() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.snap");
+    let config = Arc::new(GlobalConfig::default());
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        hyperspace_core::vector::HyperVector::<2>::SIZE,
+    ));
+    let index: HnswIndex<2, EuclideanMetric> =
+        HnswIndex::new(storage.clone(), QuantizationMode::None, config.clone());
+    for i in 0..20u32 {
+        let mut meta = std::collections::HashMap::new();
+        meta.insert("shard".to_string(), format!("s{}", i % 4));
+        let id = index
+            .insert_to_storage(&[f64::from(i) * 0.01, 0.0])
+            .unwrap();
+        index.index_node(id, meta).unwrap();
+    }
+    index.save_snapshot(&path).unwrap();
+
+    let loaded: HnswIndex<2, EuclideanMetric> = HnswIndex::load_snapshot(
+        &path,
+        storage.clone(),
+        QuantizationMode::None,
+        config.clone(),
+    )
+    .unwrap();
+    let tags = index.metadata.inverted.len();
+    assert_eq!(loaded.metadata.inverted.len(), tags);
+    // Only the text tokens rebuilt on load are materialized.
+    let materialized = loaded.metadata.inverted.materialized();
+    assert!(materialized < tags);
+    let shard = loaded.metadata.inverted.get("shard:s1").unwrap().clone();
+    assert_eq!(shard.iter().collect::<Vec<_>>(), [1, 5, 9, 13, 17]);
+    assert_eq!(loaded.metadata.inverted.materialized(), materialized + 1);
+
+    // Re-saving over the mapped file keeps the bitmaps never touched.
+    loaded.save_snapshot(&path).unwrap();
+    assert!(loaded
+        .metadata
+        .inverted
+        .get("shard:s2")
+        .unwrap()
+        .contains(2));
+    let reloaded: HnswIndex<2, EuclideanMetric> =
+        HnswIndex::load_snapshot(&path, storage, QuantizationMode::None, config).unwrap();
+    assert_eq!(reloaded.metadata.inverted.len(), tags);
+    assert!(reloaded
+        .metadata
+        .inverted
+        .get("shard:s3")
+        .unwrap()
+        .contains(19));
+}
@@ -67,6 +67,9 @@ proptest! {
                 use_wasserstein: false,
                 bm25_options: None,
                 fusion_method: None,
+                text_match: hyperspace_core::TextMatch::Any,
+                trace: None,
+                num_probes: 0,
             };
             let results = index.search(vec, &empty_filter, &[], &search_params);
 
@@ -0,0 +1,238 @@
+//! Property tests for index invariants: graph structure under inserts,
+//! deletes and purges, snapshot round-trips, and filters against a naive
+//! evaluator.
+
+use hyperspace_core::vector::HyperVector;
+use hyperspace_core::{EuclideanMetric, FilterExpr, GlobalConfig, QuantizationMode, SearchParams};
+use hyperspace_index::test_utils::check_graph;
+use hyperspace_index::HnswIndex;
+use hyperspace_store::VectorStore;
+use proptest::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tempfile::{tempdir, TempDir};
+
+const D: usize = 4;
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+type Index = HnswIndex<D, EuclideanMetric>;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Insert {
+        vector: Vec<f64>,
+        color: usize,
+        n: i32,
+    },
+    Delete(usize),
+    Purge(usize),
+}
+
+fn arb_vector() -> impl Strategy<Value = Vec<f64>> {
+    proptest::collection::vec(-10.0..10.0, D)
+}
+
+fn arb_op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        6 => (arb_vector(), 0..COLORS.len(), -5..5i32)
+            .prop_map(|(vector, color, n)| Op::Insert { vector, color, n }),
+        2 => any::<usize>().prop_map(Op::Delete),
+        1 => any::<usize>().prop_map(Op::Purge),
+    ]
+}
+
+/// A small `m` so that most nodes go through a prune.
+fn new_index(dir: &TempDir) -> Index {
+    let config = GlobalConfig::default();
+    config.set_m(4);
+    config.set_ef_construction(32);
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        HyperVector::<D>::SIZE,
+    ));
+    HnswIndex::new(storage, QuantizationMode::None, Arc::new(config))
+}
+
+fn metadata(color: usize, n: i32) -> HashMap<String, String> {
+    HashMap::from([
+        ("color".to_string(), COLORS[color].to_string()),
+        // Half steps put values between the numeric index's buckets
+        ("n".to_string(), (f64::from(n) / 2.0).to_string()),
+    ])
+}
+
+/// Applies `ops`, deleting and purging by position among the inserted ids.
+/// Returns the ids inserted.
+fn apply(index: &Index, ops: &[Op]) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for op in ops {
+        match op {
+            Op::Insert { vector, color, n } => {
+                ids.push(index.insert(vector, metadata(*color, *n)).unwrap());
+            }
+            Op::Delete(at) if !ids.is_empty() => index.delete(ids[at % ids.len()]),
+            Op::Purge(at) if !ids.is_empty() => index.purge(&[ids[at % ids.len()]]).unwrap(),
+            _ => {}
+        }
+    }
+    ids
+}
+
+fn params(top_k: usize) -> SearchParams {
+    SearchParams {
+        top_k,
+        ef_search: 64,
+        ..Default::default()
+    }
+}
+
+/// The ids a filter keeps, evaluated row by row.
+fn naive_filter(
+    index: &Index,
+    ids: &[u32],
+    filter: &HashMap<String, String>,
+    complex: &[FilterExpr],
+) -> BTreeSet<u32> {
+    ids.iter()
+        .copied()
+        .filter(|&id| !index.is_deleted(id))
+        .filter(|&id| {
+            let meta = index.metadata_by_id(id);
+            let exact = filter.iter().all(|(k, v)| meta.get(k) == Some(v));
+            let complex = complex.iter().all(|expr| match expr {
+                FilterExpr::Match { key, value } => meta.get(key) == Some(value),
+                FilterExpr::Range { key, gte, lte } => meta
+                    .get(key)
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .is_some_and(|v| gte.is_none_or(|g| v >= g) && lte.is_none_or(|l| v <= l)),
+                _ => unreachable!("only metadata filters are generated"),
+            });
+            exact && complex
+        })
+        .collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn graph_invariants_hold_after_inserts_deletes_and_purges(
+        ops in proptest::collection::vec(arb_op(), 1..120)
+    ) {
+        let dir = tempdir().unwrap();
+        let index = new_index(&dir);
+        let ids = apply(&index, &ops);
+        if let Err(e) = check_graph(&index) {
+            panic!("{e}");
+        }
+
+        let live: BTreeSet<u32> = ids.iter().copied().filter(|&id| !index.is_deleted(id)).collect();
+        if let Some(query) = ops.iter().find_map(|op| match op {
+            Op::Insert { vector, .. } => Some(vector.clone()),
+            _ => None,
+        }) {
+            let found = index.search(&query, &HashMap::new(), &[], &params(10));
+            prop_assert!(found.iter().all(|(id, _)| live.contains(id)));
+            prop_assert_eq!(found.is_empty(), live.is_empty());
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip_preserves_graph_and_metadata(
+        ops in proptest::collection::vec(arb_op(), 1..80),
+        query in arb_vector(),
+    ) {
+        let dir = tempdir().unwrap();
+        let index = new_index(&dir);
+        let ids = apply(&index, &ops);
+
+        let path = dir.path().join("index.snap");
+        index.save_snapshot(&path).unwrap();
+        let bytes = index.save_to_bytes().unwrap();
+        let from_file = Index::load_snapshot(
+            &path,
+            index.get_storage(),
+            QuantizationMode::None,
+            index.config.clone(),
+        )
+        .unwrap();
+        let from_bytes = Index::load_from_bytes(
+            &bytes,
+            index.get_storage(),
+            QuantizationMode::None,
+            index.config.clone(),
+        )
+        .unwrap();
+
+        let filter = HashMap::from([("color".to_string(), "red".to_string())]);
+        for loaded in [&from_file, &from_bytes] {
+            prop_assert_eq!(loaded.max_layer(), index.max_layer());
+            prop_assert_eq!(loaded.entry_point(), index.entry_point());
+            for layer in 0..=index.max_layer() {
+                prop_assert_eq!(loaded.graph_layer(layer), index.graph_layer(layer));
+            }
+            for &id in &ids {
+                prop_assert_eq!(loaded.is_deleted(id), index.is_deleted(id));
+                prop_assert_eq!(loaded.metadata_by_id(id), index.metadata_by_id(id));
+            }
+            prop_assert_eq!(
+                loaded.matching_ids(&filter, &[]),
+                index.matching_ids(&filter, &[])
+            );
+            prop_assert_eq!(
+                loaded.search(&query, &HashMap::new(), &[], &params(5)),
+                index.search(&query, &HashMap::new(), &[], &params(5))
+            );
+            if let Err(e) = check_graph(loaded) {
+                panic!("{e}");
+            }
+        }
+    }
+
+    #[test]
+    fn filters_agree_with_naive_evaluation(
+        ops in proptest::collection::vec(arb_op(), 1..80),
+        color in proptest::option::of(0..COLORS.len()),
+        matched in proptest::option::of(0..COLORS.len()),
+        gte in proptest::option::of(-6.0..6.0f64),
+        lte in proptest::option::of(-6.0..6.0f64),
+        query in arb_vector(),
+    ) {
+        let dir = tempdir().unwrap();
+        let index = new_index(&dir);
+        let ids = apply(&index, &ops);
+
+        let filter: HashMap<String, String> = color
+            .map(|c| ("color".to_string(), COLORS[c].to_string()))
+            .into_iter()
+            .collect();
+        let mut complex = Vec::new();
+        if let Some(c) = matched {
+            complex.push(FilterExpr::Match {
+                key: "color".into(),
+                value: COLORS[c].into(),
+            });
+        }
+        if gte.is_some() || lte.is_some() {
+            complex.push(FilterExpr::Range {
+                key: "n".into(),
+                gte,
+                lte,
+            });
+        }
+        let expected = naive_filter(&index, &ids, &filter, &complex);
+
+        let matching = index.matching_ids(&filter, &complex);
+        if filter.is_empty() && complex.is_empty() {
+            prop_assert!(matching.is_none());
+        } else {
+            let matching: BTreeSet<u32> = matching.unwrap().iter().collect();
+            prop_assert_eq!(&matching, &expected);
+        }
+
+        let found = index.search(&query, &filter, &complex, &params(ids.len().max(1)));
+        for (id, _) in &found {
+            prop_assert!(expected.contains(id), "search returned {} outside the filter", id);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use hyperspace_core::{
+    EuclideanMetric, GlobalConfig, PoincareMetric, QuantizationMode, SearchParams, SearchTrace,
+};
+use hyperspace_index::take_visited_count;
+use hyperspace_index::test_utils::{
+    brute_force, build_index, build_index_with, build_index_with_metadata, clustered_gaussians,
+    hyperbolic_tree, recall_at_k, recall_with,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Small M and ef keep these sensitive: the harness is deterministic, so the
+// measured values (0.986 and 0.932) only change with the index code.
+
+#[test]
+fn test_recall_clustered_gaussians() {
+    let data = clustered_gaussians(7, 2_000, 50, 32, 20, 0.5);
+    let built = build_index::<32, EuclideanMetric>(&data.points, 8, 40);
+    let recall = recall_at_k(&built.index, &data, 10, 32);
+    assert!(recall >= 0.97, "recall@10 dropped to {recall:.4}");
+}
+
+#[test]
+fn test_recall_hyperbolic_tree() {
+    let data = hyperbolic_tree(11, 2_000, 50, 8, 4);
+    let built = build_index::<8, PoincareMetric>(&data.points, 8, 40);
+    let recall = recall_at_k(&built.index, &data, 10, 32);
+    assert!(recall >= 0.90, "recall@10 dropped to {recall:.4}");
+}
+
+#[test]
+fn test_harness_is_deterministic() {
+    assert_eq!(
+        hyperbolic_tree(3, 300, 10, 4, 3),
+        hyperbolic_tree(3, 300, 10, 4, 3)
+    );
+    let data = clustered_gaussians(3, 300, 10, 4, 5, 0.1);
+    let a = build_index::<4, EuclideanMetric>(&data.points, 8, 50);
+    let b = build_index::<4, EuclideanMetric>(&data.points, 8, 50);
+    assert_eq!(a.index.max_layer(), b.index.max_layer());
+    for layer in 0..=a.index.max_layer() {
+        assert_eq!(a.index.graph_layer(layer), b.index.graph_layer(layer));
+    }
+    assert_eq!(
+        recall_at_k(&a.index, &data, 5, 32).to_bits(),
+        recall_at_k(&b.index, &data, 5, 32).to_bits()
+    );
+}
+
+#[test]
+fn test_heuristic_options_keep_recall_and_fill_links() {
+    let data = clustered_gaussians(5, 1_000, 50, 32, 20, 0.5);
+    let build = |extend, keep_pruned| {
+        let config = GlobalConfig::default();
+        config.set_m(8);
+        config.set_ef_construction(40);
+        config.set_extend_candidates_enabled(extend);
+        config.set_keep_pruned_enabled(keep_pruned);
+        build_index_with::<32, EuclideanMetric>(&data.points, config)
+    };
+    let base = recall_at_k(&build(false, false).index, &data, 10, 32);
+
+    let extended = recall_at_k(&build(true, false).index, &data, 10, 32);
+    assert!(
+        extended >= base,
+        "extendCandidates: {extended:.4} < {base:.4}"
+    );
+
+    // Pruned candidates fill every layer-0 row up to 2 * M.
+    let kept = build(false, true);
+    assert!(kept
+        .index
+        .graph_layer(0)
+        .iter()
+        .all(|(_, links)| links.len() == 16));
+    // Filling rows with pruned links buys no recall on this set; allow a
+    // couple of the 500 neighbours to move either way.
+    let recall = recall_at_k(&kept.index, &data, 10, 32);
+    assert!(
+        recall + 0.005 >= base,
+        "keepPrunedConnections: {recall:.4} < {base:.4}"
+    );
+}
+
+#[test]
+fn test_medoid_entry_points_help_on_many_clusters() {
+    // Many tight clusters and a sparse graph: the descent often ends in the
+    // wrong cluster.
+    let data = clustered_gaussians(9, 2_000, 100, 16, 200, 0.01);
+    let build = |entry_points| {
+        let config = GlobalConfig::default();
+        config.set_m(4);
+        config.set_ef_construction(40);
+        config.set_entry_points(entry_points);
+        build_index_with::<16, EuclideanMetric>(&data.points, config)
+    };
+    let single = recall_at_k(&build(0).index, &data, 10, 8);
+    let multi = build(200);
+    let recall = recall_at_k(&multi.index, &data, 10, 8);
+    assert_eq!(multi.index.entry_points().len(), 200);
+    assert!(
+        recall > single + 0.03,
+        "medoid entry points: {recall:.4} vs {single:.4}"
+    );
+}
+
+#[test]
+fn test_multi_probe_raises_recall_at_fixed_ef() {
+    // Many tight clusters and a sparse graph: one small beam often stays in
+    // the wrong cluster.
+    let data = clustered_gaussians(9, 2_000, 100, 16, 200, 0.01);
+    let config = GlobalConfig::default();
+    config.set_m(4);
+    config.set_ef_construction(40);
+    let built = build_index_with::<16, EuclideanMetric>(&data.points, config);
+    let probed = |num_probes| {
+        let params = SearchParams {
+            top_k: 10,
+            ef_search: 8,
+            num_probes,
+            ..Default::default()
+        };
+        recall_with(&built.index, &data, &params)
+    };
+    let single = probed(1);
+    assert_eq!(probed(0).to_bits(), single.to_bits());
+    let multi = probed(8);
+    assert!(
+        multi > single + 0.03,
+        "multi-probe: {multi:.4} vs {single:.4}"
+    );
+}
+
+#[test]
+fn test_ivf_cells_keep_recall_on_clusters() {
+    let data = clustered_gaussians(13, 2_000, 50, 32, 20, 0.5);
+    let config = GlobalConfig::default();
+    config.set_m(8);
+    config.set_ef_construction(40);
+    config.set_ivf_cells(20);
+    config.set_ivf_probes(4);
+    let built = build_index_with::<32, EuclideanMetric>(&data.points, config);
+    // The first search builds the cells.
+    let recall = recall_at_k(&built.index, &data, 10, 32);
+    let sizes = built.index.ivf_cell_sizes();
+    assert_eq!(sizes.len(), 20);
+    assert_eq!(sizes.iter().sum::<u64>(), 2_000);
+    assert!(recall >= 0.9, "recall@10 with IVF: {recall:.4}");
+}
+
+#[test]
+fn test_selective_filter_scans_below_configured_threshold() {
+    // One point in 100 carries the tag, spread over many tight clusters: a
+    // small beam through the sparse graph reaches few of them.
+    let data = clustered_gaussians(17, 2_000, 20, 16, 200, 0.01);
+    let config = GlobalConfig::default();
+    config.set_m(4);
+    config.set_ef_construction(40);
+    let built = build_index_with_metadata::<16, EuclideanMetric>(
+        &data.points,
+        config,
+        QuantizationMode::None,
+        |i| {
+            if i % 100 == 0 {
+                HashMap::from([("rare".to_string(), "1".to_string())])
+            } else {
+                HashMap::new()
+            }
+        },
+    );
+    let tagged: Vec<Vec<f64>> = data.points.iter().step_by(100).cloned().collect();
+    let filter = HashMap::from([("rare".to_string(), "1".to_string())]);
+    let search = |query: &[f64]| {
+        let trace = Arc::new(SearchTrace::default());
+        let params = SearchParams {
+            top_k: 10,
+            ef_search: 8,
+            trace: Some(trace.clone()),
+            ..Default::default()
+        };
+        take_visited_count();
+        let ids: Vec<u32> = built
+            .index
+            .search(query, &filter, &[], &params)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        (ids, trace.stats().filter_bruteforce, take_visited_count())
+    };
+    let recall = || {
+        let mut hits = 0;
+        for query in &data.queries {
+            let truth: Vec<u32> = brute_force::<16, EuclideanMetric>(&tagged, query, 10)
+                .into_iter()
+                .map(|i| i * 100)
+                .collect();
+            let (ids, scanned, visited) = search(query);
+            hits += truth.iter().filter(|id| ids.contains(id)).count();
+            let scan = built.index.config.get_filter_bruteforce_threshold() >= 20;
+            assert_eq!(scanned, scan);
+            // A scan scores exactly the 20 tagged points; a walk goes past them.
+            if scan {
+                assert_eq!(visited, 20);
+            } else {
+                assert!(visited > 20, "walk scored only {visited} nodes");
+            }
+        }
+        hits as f64 / (10 * data.queries.len()) as f64
+    };
+
+    assert_eq!(recall(), 1.0);
+    // One below the match count: the planner walks the graph instead.
+    built.index.config.set_filter_bruteforce_threshold(19);
+    recall();
+}
+
+#[test]
+fn test_binary_search_returns_metric_distances() {
+    use hyperspace_core::vector::{BinaryHyperVector, HyperVector};
+    use hyperspace_core::Metric;
+
+    let data = clustered_gaussians(23, 500, 10, 16, 10, 0.5);
+    let config = GlobalConfig::default();
+    config.set_m(8);
+    config.set_ef_construction(40);
+    let built = build_index_with_metadata::<16, EuclideanMetric>(
+        &data.points,
+        config,
+        QuantizationMode::Binary,
+        |_| HashMap::new(),
+    );
+    let coords = |v: &[f64]| HyperVector::<16>::new_unchecked(v.try_into().unwrap());
+    for query in &data.queries {
+        let params = SearchParams {
+            top_k: 10,
+            ef_search: 10,
+            ..Default::default()
+        };
+        let hits = built.index.search(query, &HashMap::new(), &[], &params);
+        assert_eq!(hits.len(), 10);
+        for (id, dist) in hits {
+            let stored = BinaryHyperVector::from_float(&coords(&data.points[id as usize]));
+            let expected = EuclideanMetric::distance_binary(&stored, &coords(query));
+            assert!((dist - expected).abs() < 1e-9, "{dist} is not {expected}");
+        }
+    }
+}
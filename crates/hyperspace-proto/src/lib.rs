@@ -4,4 +4,104 @@
 
 pub mod hyperspace {
     tonic::include_proto!("hyperspace");
+
+    /// `google.rpc.Status`, the payload of the `grpc-status-details-bin` trailer.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RpcStatus {
+        #[prost(int32, tag = "1")]
+        code: i32,
+        #[prost(string, tag = "2")]
+        message: String,
+        #[prost(message, repeated, tag = "3")]
+        details: Vec<RpcAny>,
+    }
+
+    /// `google.protobuf.Any`.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    struct RpcAny {
+        #[prost(string, tag = "1")]
+        type_url: String,
+        #[prost(bytes = "vec", tag = "2")]
+        value: Vec<u8>,
+    }
+
+    fn type_url(type_name: &str) -> String {
+        format!("type.googleapis.com/hyperspace.{type_name}")
+    }
+
+    /// A status whose details are a `google.rpc.Status` holding `detail` as
+    /// an `Any` of type `hyperspace.<type_name>`.
+    fn status_with_detail(
+        code: tonic::Code,
+        message: String,
+        type_name: &str,
+        detail: &impl prost::Message,
+    ) -> tonic::Status {
+        use prost::Message;
+        let details = RpcStatus {
+            code: code as i32,
+            message: message.clone(),
+            details: vec![RpcAny {
+                type_url: type_url(type_name),
+                value: detail.encode_to_vec(),
+            }],
+        };
+        tonic::Status::with_details(code, message, details.encode_to_vec().into())
+    }
+
+    /// The `hyperspace.<type_name>` detail of a status, if it carries one.
+    fn detail_from_status<T: prost::Message + Default>(
+        status: &tonic::Status,
+        type_name: &str,
+    ) -> Option<T> {
+        use prost::Message;
+        let url = type_url(type_name);
+        RpcStatus::decode(status.details())
+            .ok()?
+            .details
+            .into_iter()
+            .find(|any| any.type_url == url)
+            .and_then(|any| T::decode(any.value.as_slice()).ok())
+    }
+
+    impl InsertErrorDetail {
+        /// Wraps the detail into an `INVALID_ARGUMENT` status whose details
+        /// are a `google.rpc.Status` carrying it as an `Any`.
+        pub fn into_status(self) -> tonic::Status {
+            let message = self.message.clone();
+            status_with_detail(
+                tonic::Code::InvalidArgument,
+                message,
+                "InsertErrorDetail",
+                &self,
+            )
+        }
+
+        /// Extracts a structured insert error from a status, if present.
+        pub fn from_status(status: &tonic::Status) -> Option<Self> {
+            detail_from_status(status, "InsertErrorDetail")
+        }
+    }
+
+    impl FilterSyntaxError {
+        /// Wraps the error into an `INVALID_ARGUMENT` status whose details
+        /// are a `google.rpc.Status` carrying it as an `Any`.
+        pub fn into_status(self) -> tonic::Status {
+            let message = format!(
+                "Invalid filter_expr: {} at position {}",
+                self.message, self.position
+            );
+            status_with_detail(
+                tonic::Code::InvalidArgument,
+                message,
+                "FilterSyntaxError",
+                &self,
+            )
+        }
+
+        /// Extracts a structured filter syntax error from a status, if present.
+        pub fn from_status(status: &tonic::Status) -> Option<Self> {
+            detail_from_status(status, "FilterSyntaxError")
+        }
+    }
 }
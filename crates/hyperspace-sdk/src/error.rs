@@ -0,0 +1,192 @@
+use hyperspace_proto::hyperspace::{InsertErrorCode, InsertErrorDetail};
+
+/// Typed reason for a rejected insert, decoded from the server's status details.
+#[derive(Debug, Clone)]
+pub enum InsertError {
+    /// Vector length does not match the collection dimension.
+    DimensionMismatch {
+        id: u32,
+        field: String,
+        expected: u32,
+        actual: u32,
+    },
+    /// Vector lies outside the Poincaré ball (or off the Lorentz hyperboloid).
+    OutOfBall { id: u32, field: String, norm: f64 },
+    /// Vector contains NaN/Inf; `field` points at the offending component.
+    NanValues { id: u32, field: String },
+    /// The same id appears more than once in a batch.
+    DuplicateId { id: u32, field: String },
+    /// An `InsertOnly` write targeted an id that is already stored.
+    IdExists { id: u32, field: String },
+    /// A metadata value doesn't have the type the collection's schema
+    /// declares; `field` is e.g. `metadata.year`.
+    MetadataTypeMismatch {
+        id: u32,
+        field: String,
+        expected: String,
+    },
+    /// Vector isn't unit length and the cosine collection's normalization
+    /// policy is `reject`.
+    NotNormalized { id: u32, field: String, norm: f64 },
+    /// Any other insert failure (network, server-side, unknown code).
+    Other(tonic::Status),
+}
+
+impl InsertError {
+    /// Maps a gRPC status to a typed insert error.
+    pub fn from_status(status: tonic::Status) -> Self {
+        let Some(detail) = InsertErrorDetail::from_status(&status) else {
+            return Self::Other(status);
+        };
+        let code = detail.code();
+        let InsertErrorDetail {
+            id,
+            field,
+            expected_dimension,
+            actual_dimension,
+            norm,
+            expected_type,
+            ..
+        } = detail;
+        match code {
+            InsertErrorCode::DimensionMismatch => Self::DimensionMismatch {
+                id,
+                field,
+                expected: expected_dimension,
+                actual: actual_dimension,
+            },
+            InsertErrorCode::OutOfBall => Self::OutOfBall { id, field, norm },
+            InsertErrorCode::NanValues => Self::NanValues { id, field },
+            InsertErrorCode::DuplicateId => Self::DuplicateId { id, field },
+            InsertErrorCode::IdExists => Self::IdExists { id, field },
+            InsertErrorCode::MetadataTypeMismatch => Self::MetadataTypeMismatch {
+                id,
+                field,
+                expected: expected_type,
+            },
+            InsertErrorCode::NotNormalized => Self::NotNormalized { id, field, norm },
+            InsertErrorCode::InsertErrorUnspecified => Self::Other(status),
+        }
+    }
+}
+
+impl From<tonic::Status> for InsertError {
+    fn from(status: tonic::Status) -> Self {
+        Self::from_status(status)
+    }
+}
+
+impl std::fmt::Display for InsertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DimensionMismatch {
+                id,
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "id {id}: dimension mismatch at '{field}' (expected {expected}, got {actual})"
+            ),
+            Self::OutOfBall { id, field, norm } => {
+                write!(
+                    f,
+                    "id {id}: '{field}' outside the model domain (norm {norm})"
+                )
+            }
+            Self::NanValues { id, field } => write!(f, "id {id}: non-finite value at '{field}'"),
+            Self::DuplicateId { id, field } => write!(f, "duplicate id {id} at '{field}'"),
+            Self::IdExists { id, field } => write!(f, "id {id} at '{field}' already exists"),
+            Self::MetadataTypeMismatch {
+                id,
+                field,
+                expected,
+            } => write!(f, "id {id}: '{field}' must be {expected}"),
+            Self::NotNormalized { id, field, norm } => {
+                write!(f, "id {id}: '{field}' is not unit length (norm {norm})")
+            }
+            Self::Other(status) => write!(f, "{status}"),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// Builds the `DIMENSION_MISMATCH` status the server returns, for checks the
+/// client can make before sending.
+pub(crate) fn dimension_mismatch(
+    id: u32,
+    field: &str,
+    expected: u32,
+    actual: u32,
+) -> tonic::Status {
+    let mut detail = InsertErrorDetail {
+        id,
+        field: field.to_string(),
+        message: format!("Vector dimension mismatch. Expected {expected}, got {actual}"),
+        expected_dimension: expected,
+        actual_dimension: actual,
+        ..Default::default()
+    };
+    detail.set_code(InsertErrorCode::DimensionMismatch);
+    detail.into_status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_error_roundtrip() {
+        let mut detail = InsertErrorDetail {
+            id: 7,
+            field: "vector".to_string(),
+            message: "bad".to_string(),
+            expected_dimension: 8,
+            actual_dimension: 3,
+            ..Default::default()
+        };
+        detail.set_code(InsertErrorCode::DimensionMismatch);
+        let err = InsertError::from(detail.into_status());
+        assert!(matches!(
+            err,
+            InsertError::DimensionMismatch {
+                id: 7,
+                expected: 8,
+                actual: 3,
+                ..
+            }
+        ));
+
+        let local = InsertError::from(dimension_mismatch(3, "vectors[1].vector", 8, 4));
+        assert!(matches!(
+            local,
+            InsertError::DimensionMismatch {
+                id: 3,
+                expected: 8,
+                actual: 4,
+                ..
+            }
+        ));
+
+        let plain = InsertError::from(tonic::Status::internal("boom"));
+        assert!(matches!(plain, InsertError::Other(_)));
+
+        // Details of another type are not read as an insert error.
+        let bare = tonic::Status::with_details(
+            tonic::Code::InvalidArgument,
+            "bad filter",
+            vec![0x08, 0x05, 0x12, 0x03, b'b', b'a', b'd'].into(),
+        );
+        assert!(matches!(InsertError::from(bare), InsertError::Other(_)));
+        let filter = hyperspace_proto::hyperspace::FilterSyntaxError {
+            position: 5,
+            message: "bad".to_string(),
+            expression: "a = ".to_string(),
+        };
+        assert!(matches!(
+            InsertError::from(filter.into_status()),
+            InsertError::Other(_)
+        ));
+    }
+}
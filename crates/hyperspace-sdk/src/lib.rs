@@ -1,20 +1,31 @@
 pub use hyperspace_proto::hyperspace::database_client::DatabaseClient;
 pub use hyperspace_proto::hyperspace::{
-    BatchInsertRequest, BatchSearchRequest, CollectionSummary, DurabilityLevel, EventMessage,
-    EventSubscriptionRequest, EventType, FindSemanticClustersRequest, FindSemanticClustersResponse,
+    BatchInsertRequest, BatchSearchRequest, CollectionBundleChunk, CollectionSpec,
+    CollectionSummary, DurabilityLevel, EventMessage, EventSubscriptionRequest, EventType,
+    ExportFormat, FindSemanticClustersRequest, FindSemanticClustersResponse,
     GetConceptParentsRequest, GetConceptParentsResponse, GetNeighborsRequest, GetNeighborsResponse,
-    GetNodeRequest, GraphNode, InsertRequest, InsertTextRequest, SearchRequest, SearchResponse,
-    SearchResult, SearchResult as ResultItem, SearchTextRequest, TraverseRequest, TraverseResponse,
-    VectorData, VectorizeRequest, VectorizeResponse,
+    GetNodeRequest, GraphNode, HnswParams, IndexingProgress, InsertAudioRequest,
+    InsertImageRequest, InsertRequest, InsertStreamAck, InsertTextRequest, MetadataJoin,
+    MultiSearchHit, MultiSearchRequest, ParentHit, ParentSearch, PurgeReport,
+    RunQueryTemplateRequest, SearchRequest, SearchResponse, SearchResult,
+    SearchResult as ResultItem, SearchTextRequest, TraverseRequest, TraverseResponse, VectorData,
+    VectorizeRequest, VectorizeResponse, WatchIndexingProgressRequest, WriteMode,
 };
 use tonic::codegen::InterceptedService;
 use tonic::service::Interceptor;
 use tonic::transport::Channel;
 use tonic::{Request, Status};
 
+mod error;
 pub mod fuzzy;
 pub mod gromov;
+mod local_cache;
 pub mod math;
+pub mod router;
+
+pub use error::InsertError;
+pub use local_cache::LocalCache;
+pub use router::{HashRing, ShardRouter};
 
 #[cfg(feature = "embedders")]
 mod embedder;
@@ -47,14 +58,44 @@ impl Interceptor for AuthInterceptor {
 
 pub struct Client {
     inner: DatabaseClient<InterceptedService<Channel, AuthInterceptor>>,
+    /// Collection dimensions learned from create/stats/list calls, used to
+    /// reject wrong-size vectors before they reach the network.
+    dimensions: std::collections::HashMap<String, u32>,
     #[cfg(feature = "embedders")]
     embedder: Option<Box<dyn Embedder>>,
 }
 
 impl Client {
     #[inline]
-    fn vec_f32_to_f64(vector: &[f32]) -> Vec<f64> {
-        vector.iter().map(|&x| f64::from(x)).collect()
+    fn f32_search_request(vector_f32: Vec<f32>, top_k: u32, collection: String) -> SearchRequest {
+        SearchRequest {
+            vector: Vec::new(),
+            top_k,
+            filter: std::collections::HashMap::default(),
+            filters: vec![],
+            hybrid_query: None,
+            hybrid_alpha: None,
+            use_wasserstein: false,
+            collection,
+            bm25_options: None,
+            vector_f32,
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
+        }
     }
 
     /// Connects to the `HyperspaceDB` server.
@@ -81,6 +122,7 @@ impl Client {
 
         Ok(Self {
             inner: client,
+            dimensions: std::collections::HashMap::new(),
             #[cfg(feature = "embedders")]
             embedder: None,
         })
@@ -91,6 +133,28 @@ impl Client {
         self.embedder = Some(embedder);
     }
 
+    /// Rejects a vector locally with the server's `DIMENSION_MISMATCH` detail
+    /// when the collection's dimension is already known.
+    fn check_dimension(
+        &self,
+        collection: &str,
+        id: u32,
+        field: &str,
+        len: usize,
+    ) -> Result<(), tonic::Status> {
+        let key = if collection.is_empty() {
+            "default"
+        } else {
+            collection
+        };
+        match self.dimensions.get(key) {
+            Some(&expected) if expected as usize != len => {
+                Err(error::dimension_mismatch(id, field, expected, len as u32))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Creates a new collection.
     ///
     /// # Errors
@@ -101,12 +165,90 @@ impl Client {
         dimension: u32,
         metric: String,
     ) -> Result<String, tonic::Status> {
-        let req = hyperspace_proto::hyperspace::CreateCollectionRequest {
+        self.create_collection_with_labels(
             name,
             dimension,
             metric,
+            String::new(),
+            std::collections::HashMap::new(),
+        )
+        .await
+    }
+
+    /// Creates a collection carrying a description and labels, which
+    /// `list_collections` and `get_collection_stats` return.
+    ///
+    /// # Errors
+    /// Returns error if the collection already exists or the name is invalid.
+    pub async fn create_collection_with_labels(
+        &mut self,
+        name: String,
+        dimension: u32,
+        metric: String,
+        description: String,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::CreateCollectionRequest {
+            name: name.clone(),
+            dimension,
+            metric,
+            description,
+            labels,
+            ..Default::default()
+        };
+        let resp = self.inner.create_collection(req).await?;
+        self.dimensions.insert(name, dimension);
+        Ok(resp.into_inner().status)
+    }
+
+    /// Creates a collection whose metadata keys in `schema` must have the
+    /// declared types. `mode` decides whether a mistyped write is rejected
+    /// (`METADATA_TYPE_MISMATCH`) or loses the offending keys.
+    ///
+    /// # Errors
+    /// Returns error if the collection already exists or the name is invalid.
+    pub async fn create_collection_with_schema(
+        &mut self,
+        name: String,
+        dimension: u32,
+        metric: String,
+        schema: std::collections::HashMap<String, hyperspace_proto::hyperspace::MetadataFieldType>,
+        mode: hyperspace_proto::hyperspace::SchemaMode,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::CreateCollectionRequest {
+            name: name.clone(),
+            dimension,
+            metric,
+            metadata_schema: schema.into_iter().map(|(k, t)| (k, t as i32)).collect(),
+            schema_mode: mode as i32,
+            ..Default::default()
+        };
+        let resp = self.inner.create_collection(req).await?;
+        self.dimensions.insert(name, dimension);
+        Ok(resp.into_inner().status)
+    }
+
+    /// Creates a collection of chunks whose `parent_id` metadata points at
+    /// documents of `parent`, for [`Self::search_parents`].
+    ///
+    /// # Errors
+    /// Returns error if the collection already exists or `parent` is missing.
+    pub async fn create_chunk_collection(
+        &mut self,
+        name: String,
+        dimension: u32,
+        metric: String,
+        parent: String,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::CreateCollectionRequest {
+            name: name.clone(),
+            dimension,
+            metric,
+            parent_collection: parent,
+            ..Default::default()
         };
         let resp = self.inner.create_collection(req).await?;
+        self.dimensions.insert(name, dimension);
         Ok(resp.into_inner().status)
     }
 
@@ -115,6 +257,7 @@ impl Client {
     /// # Errors
     /// Returns error if the collection does not exist cancellation.
     pub async fn delete_collection(&mut self, name: String) -> Result<String, tonic::Status> {
+        self.dimensions.remove(&name);
         let req = hyperspace_proto::hyperspace::DeleteCollectionRequest { name };
         let resp = self.inner.delete_collection(req).await?;
         Ok(resp.into_inner().status)
@@ -125,9 +268,109 @@ impl Client {
     /// # Errors
     /// Returns error on network failure.
     pub async fn list_collections(&mut self) -> Result<Vec<CollectionSummary>, tonic::Status> {
-        let req = hyperspace_proto::hyperspace::Empty {};
+        self.list_collections_with_labels(std::collections::HashMap::new())
+            .await
+    }
+
+    /// Lists the collections carrying every one of `labels`.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn list_collections_with_labels(
+        &mut self,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Result<Vec<CollectionSummary>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ListCollectionsRequest { labels };
         let resp = self.inner.list_collections(req).await?;
-        Ok(resp.into_inner().collections)
+        let collections = resp.into_inner().collections;
+        for c in &collections {
+            self.dimensions.insert(c.name.clone(), c.dimension);
+        }
+        Ok(collections)
+    }
+
+    /// Lists the collections under a namespace such as `team/project`.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn list_namespace(
+        &mut self,
+        namespace: String,
+    ) -> Result<Vec<CollectionSummary>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::NamespaceRequest { namespace };
+        let resp = self.inner.list_namespace(req).await?;
+        let collections = resp.into_inner().collections;
+        for c in &collections {
+            self.dimensions.insert(c.name.clone(), c.dimension);
+        }
+        Ok(collections)
+    }
+
+    /// Converges the server to `spec`: creates the collection if missing,
+    /// otherwise updates its description, labels and aliases. With `dry_run`
+    /// only the planned changes are returned.
+    ///
+    /// # Errors
+    /// Returns `InvalidArgument` if the spec is invalid or changes the
+    /// dimension, metric, quantization or HNSW parameters of an existing
+    /// collection.
+    pub async fn apply_collection_spec(
+        &mut self,
+        spec: CollectionSpec,
+        dry_run: bool,
+    ) -> Result<hyperspace_proto::hyperspace::ApplyCollectionSpecResponse, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ApplyCollectionSpecRequest {
+            spec: Some(spec),
+            dry_run,
+        };
+        Ok(self.inner.apply_collection_spec(req).await?.into_inner())
+    }
+
+    /// Exports the current state of the collections under `namespace` (empty
+    /// for all) as specs that `apply_collection_spec` accepts.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn list_collection_specs(
+        &mut self,
+        namespace: String,
+    ) -> Result<Vec<CollectionSpec>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ListCollectionSpecsRequest { namespace };
+        Ok(self
+            .inner
+            .list_collection_specs(req)
+            .await?
+            .into_inner()
+            .specs)
+    }
+
+    /// Deletes every collection under a namespace and returns their names.
+    ///
+    /// # Errors
+    /// Returns error if the namespace is empty or deletion fails.
+    pub async fn delete_namespace(
+        &mut self,
+        namespace: String,
+    ) -> Result<Vec<String>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::NamespaceRequest { namespace };
+        let deleted = self.inner.delete_namespace(req).await?.into_inner().deleted;
+        for name in &deleted {
+            self.dimensions.remove(name);
+        }
+        Ok(deleted)
+    }
+
+    /// Rolls up collection count, vectors and disk usage under a namespace.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn get_namespace_stats(
+        &mut self,
+        namespace: String,
+    ) -> Result<hyperspace_proto::hyperspace::NamespaceStatsResponse, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::NamespaceRequest { namespace };
+        let resp = self.inner.get_namespace_stats(req).await?;
+        Ok(resp.into_inner())
     }
 
     /// Gets statistics for a collection.
@@ -138,9 +381,22 @@ impl Client {
         &mut self,
         name: String,
     ) -> Result<hyperspace_proto::hyperspace::CollectionStatsResponse, tonic::Status> {
-        let req = hyperspace_proto::hyperspace::CollectionStatsRequest { name };
-        let resp = self.inner.get_collection_stats(req).await?;
-        Ok(resp.into_inner())
+        let req = hyperspace_proto::hyperspace::CollectionStatsRequest { name: name.clone() };
+        let stats = self.inner.get_collection_stats(req).await?.into_inner();
+        self.dimensions.insert(name, stats.dimension);
+        Ok(stats)
+    }
+
+    /// Lists the cluster members the server knows over gossip, itself first.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn get_topology(
+        &mut self,
+    ) -> Result<Vec<hyperspace_proto::hyperspace::ClusterNode>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::TopologyRequest {};
+        let resp = self.inner.get_topology(req).await?;
+        Ok(resp.into_inner().nodes)
     }
 
     /// Rebuilds the index for a collection. This is a resource-intensive operation.
@@ -175,6 +431,173 @@ impl Client {
         Ok(resp.into_inner().status)
     }
 
+    /// Lists deleted collections still waiting in the trash.
+    ///
+    /// # Errors
+    /// Returns error on network failure.
+    pub async fn list_trash(
+        &mut self,
+    ) -> Result<Vec<hyperspace_proto::hyperspace::TrashedCollection>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::Empty {};
+        let resp = self.inner.list_trash(req).await?;
+        Ok(resp.into_inner().collections)
+    }
+
+    /// Restores the most recently deleted copy of a collection from the trash.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` if it is not in the trash, `ALREADY_EXISTS` if a
+    /// collection with that name exists again.
+    pub async fn restore_collection(&mut self, name: String) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::RestoreCollectionRequest { name };
+        let resp = self.inner.restore_collection(req).await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Snapshot generations a collection keeps (see `snapshot_history`),
+    /// oldest first.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` if the collection does not exist.
+    pub async fn list_collection_versions(
+        &mut self,
+        name: String,
+    ) -> Result<Vec<hyperspace_proto::hyperspace::CollectionVersion>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ListCollectionVersionsRequest { name };
+        let resp = self.inner.list_collection_versions(req).await?;
+        Ok(resp.into_inner().versions)
+    }
+
+    /// Switches a collection back to one of its versions. The state it
+    /// replaces is kept as the newest version.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` for an unknown version, `RESOURCE_EXHAUSTED` while
+    /// the collection is busy.
+    pub async fn rollback_collection(
+        &mut self,
+        name: String,
+        version: u64,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::RollbackCollectionRequest { name, version };
+        let resp = self.inner.rollback_collection(req).await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Turns a warm standby (`--standby-of`) into a writable leader once its
+    /// current WAL pull is applied.
+    ///
+    /// # Errors
+    /// Returns `FAILED_PRECONDITION` if the server is not a standby.
+    pub async fn promote_standby(&mut self) -> Result<String, tonic::Status> {
+        let resp = self
+            .inner
+            .promote_standby(hyperspace_proto::hyperspace::Empty {})
+            .await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Moves a collection to another server (`target_addr`, e.g.
+    /// `http://10.0.0.7:50051`) while it keeps taking writes. An empty
+    /// `target_collection` keeps the name. Afterwards this server answers
+    /// requests for it with `NOT_FOUND` and an `x-hyperspace-moved-to` header.
+    ///
+    /// # Errors
+    /// Returns `ABORTED` if the transfer or the catch-up replay fails.
+    pub async fn migrate_collection(
+        &mut self,
+        collection: String,
+        target_addr: String,
+        target_collection: String,
+    ) -> Result<hyperspace_proto::hyperspace::MigrateCollectionResponse, tonic::Status> {
+        self.dimensions.remove(&collection);
+        let req = hyperspace_proto::hyperspace::MigrateCollectionRequest {
+            collection,
+            target_addr,
+            target_collection,
+        };
+        let resp = self.inner.migrate_collection(req).await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Writes a snapshot of `collection`, or of every collection when `None`.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` for an unknown collection.
+    pub async fn trigger_snapshot(
+        &mut self,
+        collection: Option<String>,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::SnapshotRequest {
+            collection: collection.unwrap_or_default(),
+        };
+        let resp = self.inner.trigger_snapshot(req).await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Snapshots `collection` and streams it as a bundle. The first chunk
+    /// carries `total_bytes`.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` for an unknown collection.
+    pub async fn export_collection(
+        &mut self,
+        collection: String,
+    ) -> Result<tonic::Streaming<CollectionBundleChunk>, tonic::Status> {
+        self.export_collection_as(collection, ExportFormat::ExportBundle)
+            .await
+    }
+
+    /// Streams `collection` in `format`: a bundle, or an Arrow IPC or Parquet
+    /// file with one row per point. The first chunk carries `total_bytes`
+    /// and the format.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` for an unknown collection, and `INVALID_ARGUMENT`
+    /// for Arrow IPC or Parquet on a server built without `arrow-export`.
+    pub async fn export_collection_as(
+        &mut self,
+        collection: String,
+        format: ExportFormat,
+    ) -> Result<tonic::Streaming<CollectionBundleChunk>, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ExportCollectionRequest {
+            collection,
+            format: format as i32,
+        };
+        let resp = self.inner.export_collection(req).await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Loads chunks of [`Client::export_collection_as`] in any format. The
+    /// first chunk names the collection and the format. A bundle creates
+    /// the collection; Arrow IPC or Parquet rows are upserted into it,
+    /// creating it from the file's dimension and metric if needed.
+    ///
+    /// # Errors
+    /// Returns `INVALID_ARGUMENT` for a malformed file or a bundle whose
+    /// collection exists.
+    pub async fn import_collection_as<S>(&mut self, chunks: S) -> Result<String, tonic::Status>
+    where
+        S: tonic::codegen::tokio_stream::Stream<Item = CollectionBundleChunk> + Send + 'static,
+    {
+        let resp = self.inner.import_collection(chunks).await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Creates a collection from bundle chunks, e.g. those of
+    /// [`Client::export_collection`]. The first chunk names the collection.
+    ///
+    /// # Errors
+    /// Returns `INVALID_ARGUMENT` if the collection exists or the bundle is
+    /// malformed.
+    pub async fn import_collection<S>(&mut self, chunks: S) -> Result<String, tonic::Status>
+    where
+        S: tonic::codegen::tokio_stream::Stream<Item = CollectionBundleChunk> + Send + 'static,
+    {
+        let resp = self.inner.receive_collection(chunks).await?;
+        Ok(resp.into_inner().status)
+    }
+
     /// Triggers memory cleanup (Vacuum).
     ///
     /// # Errors
@@ -207,7 +630,8 @@ impl Client {
     /// Inserts a vector into the collection.
     ///
     /// # Errors
-    /// Returns error if insertion fails.
+    /// Returns error if insertion fails. Validation failures carry structured
+    /// details; convert with [`InsertError::from`] to match on them.
     pub async fn insert(
         &mut self,
         id: u32,
@@ -215,21 +639,45 @@ impl Client {
         metadata: std::collections::HashMap<String, String>,
         collection: Option<String>,
     ) -> Result<bool, tonic::Status> {
+        self.insert_with_mode(id, vector, metadata, collection, WriteMode::UpsertReplace)
+            .await
+    }
+
+    /// Inserts a vector with an explicit [`WriteMode`] for existing ids:
+    /// `InsertOnly` fails with [`InsertError::IdExists`], `UpsertMerge` keeps
+    /// stored metadata keys that `metadata` does not override.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn insert_with_mode(
+        &mut self,
+        id: u32,
+        vector: Vec<f64>,
+        metadata: std::collections::HashMap<String, String>,
+        collection: Option<String>,
+        mode: WriteMode,
+    ) -> Result<bool, tonic::Status> {
+        let collection = collection.unwrap_or_default();
+        self.check_dimension(&collection, id, "vector", vector.len())?;
         let req = InsertRequest {
             id,
             vector,
             metadata,
             typed_metadata: std::collections::HashMap::new(),
-            collection: collection.unwrap_or_default(),
+            collection,
             origin_node_id: String::new(),
             logical_clock: 0,
             durability: 0,
+            vector_f32: Vec::new(),
+            write_mode: mode as i32,
+            token_vectors: None,
         };
         let resp = self.inner.insert(req).await?;
         Ok(resp.into_inner().success)
     }
 
-    /// Inserts a vector from f32 input (client-side conversion to protocol f64).
+    /// Inserts a vector from f32 input, sent as a packed f32 payload
+    /// (half the wire size of the f64 field).
     ///
     /// # Errors
     /// Returns error if insertion fails.
@@ -240,8 +688,50 @@ impl Client {
         metadata: std::collections::HashMap<String, String>,
         collection: Option<String>,
     ) -> Result<bool, tonic::Status> {
-        self.insert(id, Self::vec_f32_to_f64(vector), metadata, collection)
-            .await
+        let collection = collection.unwrap_or_default();
+        self.check_dimension(&collection, id, "vector", vector.len())?;
+        let req = InsertRequest {
+            id,
+            vector: Vec::new(),
+            metadata,
+            typed_metadata: std::collections::HashMap::new(),
+            collection,
+            origin_node_id: String::new(),
+            logical_clock: 0,
+            durability: 0,
+            vector_f32: vector.to_vec(),
+            write_mode: 0,
+            token_vectors: None,
+        };
+        let resp = self.inner.insert(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
+    /// Inserts a pooled vector together with the document's token-level
+    /// vectors, used to rerank searches that carry query tokens.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails or the token vectors are malformed.
+    pub async fn insert_with_tokens(
+        &mut self,
+        id: u32,
+        vector: Vec<f64>,
+        tokens: hyperspace_proto::hyperspace::TokenVectors,
+        metadata: std::collections::HashMap<String, String>,
+        collection: Option<String>,
+    ) -> Result<bool, tonic::Status> {
+        let collection = collection.unwrap_or_default();
+        self.check_dimension(&collection, id, "vector", vector.len())?;
+        let req = InsertRequest {
+            id,
+            vector,
+            metadata,
+            collection,
+            token_vectors: Some(tokens),
+            ..Default::default()
+        };
+        let resp = self.inner.insert(req).await?;
+        Ok(resp.into_inner().success)
     }
 
     /// Inserts text that will be vectorized on the server side.
@@ -266,138 +756,794 @@ impl Client {
         Ok(resp.into_inner().success)
     }
 
-    /// Vectoize text using the server-side embedding engine.
+    /// Inserts a PNG or JPEG image that will be vectorized on the server side
+    /// by the image model for the collection's metric.
     ///
     /// # Errors
-    /// Returns error if vectorization fails.
-    pub async fn vectorize(
+    /// Returns error if the image is invalid or insertion/vectorization fails.
+    pub async fn insert_image(
         &mut self,
-        text: String,
-        metric: String,
-    ) -> Result<Vec<f64>, tonic::Status> {
-        let req = VectorizeRequest { text, metric };
-        let resp = self.inner.vectorize(req).await?;
-        Ok(resp.into_inner().vector)
+        id: u32,
+        image: Vec<u8>,
+        metadata: std::collections::HashMap<String, String>,
+        collection: Option<String>,
+    ) -> Result<bool, tonic::Status> {
+        let req = InsertImageRequest {
+            id,
+            image,
+            metadata,
+            collection: collection.unwrap_or_default(),
+            durability: 0,
+        };
+        let resp = self.inner.insert_image(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
+    /// Inserts a WAV clip that will be vectorized on the server side by the
+    /// audio model for the collection's metric.
+    ///
+    /// # Errors
+    /// Returns error if the audio is invalid or insertion/vectorization fails.
+    pub async fn insert_audio(
+        &mut self,
+        id: u32,
+        audio: Vec<u8>,
+        metadata: std::collections::HashMap<String, String>,
+        collection: Option<String>,
+    ) -> Result<bool, tonic::Status> {
+        let req = InsertAudioRequest {
+            id,
+            audio,
+            metadata,
+            collection: collection.unwrap_or_default(),
+            durability: 0,
+        };
+        let resp = self.inner.insert_audio(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
+    /// Vectoize text using the server-side embedding engine.
+    ///
+    /// # Errors
+    /// Returns error if vectorization fails.
+    pub async fn vectorize(
+        &mut self,
+        text: String,
+        metric: String,
+    ) -> Result<Vec<f64>, tonic::Status> {
+        let req = VectorizeRequest { text, metric };
+        let resp = self.inner.vectorize(req).await?;
+        Ok(resp.into_inner().vector)
+    }
+
+    /// Batch inserts multiple vectors.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn batch_insert(
+        &mut self,
+        items: Vec<(u32, Vec<f64>, std::collections::HashMap<String, String>)>,
+        collection: Option<String>,
+        durability: DurabilityLevel,
+    ) -> Result<bool, tonic::Status> {
+        self.batch_insert_with_mode(items, collection, durability, WriteMode::UpsertReplace)
+            .await
+    }
+
+    /// Batch inserts with an explicit [`WriteMode`]. With `InsertOnly` the
+    /// whole batch is rejected if any id already exists.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn batch_insert_with_mode(
+        &mut self,
+        items: Vec<(u32, Vec<f64>, std::collections::HashMap<String, String>)>,
+        collection: Option<String>,
+        durability: DurabilityLevel,
+        mode: WriteMode,
+    ) -> Result<bool, tonic::Status> {
+        let collection = collection.unwrap_or_default();
+        for (i, (id, vector, _)) in items.iter().enumerate() {
+            self.check_dimension(
+                &collection,
+                *id,
+                &format!("vectors[{i}].vector"),
+                vector.len(),
+            )?;
+        }
+        let vectors = items
+            .into_iter()
+            .map(|(id, vector, metadata)| VectorData {
+                id,
+                vector,
+                metadata,
+                typed_metadata: std::collections::HashMap::new(),
+                vector_f32: Vec::new(),
+                token_vectors: None,
+            })
+            .collect();
+        let req = BatchInsertRequest {
+            collection,
+            vectors,
+            origin_node_id: String::new(),
+            logical_clock: 0,
+            durability: durability as i32,
+            write_mode: mode as i32,
+            delete_ids: Vec::new(),
+        };
+        let resp = self.inner.batch_insert(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
+    /// Streams `BatchInsert` messages over one call, for loads too large for
+    /// a single message. Each message is written as `BatchInsert` and acked
+    /// in order; the server holds its ack while the collection's indexing
+    /// queue is over its limit, which slows the stream down to the indexer.
+    ///
+    /// # Errors
+    /// Returns error if the call cannot start. A message that fails ends the
+    /// ack stream with an error whose `x-hyperspace-sequence` header gives
+    /// its position.
+    pub async fn insert_stream<S>(
+        &mut self,
+        chunks: S,
+    ) -> Result<tonic::Streaming<InsertStreamAck>, tonic::Status>
+    where
+        S: tonic::codegen::tokio_stream::Stream<Item = BatchInsertRequest> + Send + 'static,
+    {
+        let resp = self.inner.insert_stream(chunks).await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Deletes `delete_ids` and inserts `items` as one atomic batch, e.g. to
+    /// replace the chunks of a re-chunked document. After a crash the server
+    /// recovers either all of it or none of it.
+    ///
+    /// # Errors
+    /// Returns error if the batch is rejected.
+    pub async fn replace_batch(
+        &mut self,
+        delete_ids: Vec<u32>,
+        items: Vec<(u32, Vec<f64>, std::collections::HashMap<String, String>)>,
+        collection: Option<String>,
+        durability: DurabilityLevel,
+    ) -> Result<u64, tonic::Status> {
+        let collection = collection.unwrap_or_default();
+        for (i, (id, vector, _)) in items.iter().enumerate() {
+            self.check_dimension(
+                &collection,
+                *id,
+                &format!("vectors[{i}].vector"),
+                vector.len(),
+            )?;
+        }
+        let vectors = items
+            .into_iter()
+            .map(|(id, vector, metadata)| VectorData {
+                id,
+                vector,
+                metadata,
+                typed_metadata: std::collections::HashMap::new(),
+                vector_f32: Vec::new(),
+                token_vectors: None,
+            })
+            .collect();
+        let req = BatchInsertRequest {
+            collection,
+            vectors,
+            durability: durability as i32,
+            delete_ids,
+            ..Default::default()
+        };
+        let resp = self.inner.batch_insert(req).await?;
+        Ok(resp.into_inner().logical_clock)
+    }
+
+    /// Batch inserts multiple vectors from f32 input (packed f32 payload).
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn batch_insert_f32(
+        &mut self,
+        items: Vec<(u32, Vec<f32>, std::collections::HashMap<String, String>)>,
+        collection: Option<String>,
+        durability: DurabilityLevel,
+    ) -> Result<bool, tonic::Status> {
+        let collection = collection.unwrap_or_default();
+        for (i, (id, vector, _)) in items.iter().enumerate() {
+            self.check_dimension(
+                &collection,
+                *id,
+                &format!("vectors[{i}].vector"),
+                vector.len(),
+            )?;
+        }
+        let vectors = items
+            .into_iter()
+            .map(|(id, vector_f32, metadata)| VectorData {
+                id,
+                vector: Vec::new(),
+                metadata,
+                typed_metadata: std::collections::HashMap::new(),
+                vector_f32,
+                token_vectors: None,
+            })
+            .collect();
+        let req = BatchInsertRequest {
+            collection,
+            vectors,
+            origin_node_id: String::new(),
+            logical_clock: 0,
+            durability: durability as i32,
+            write_mode: 0,
+            delete_ids: Vec::new(),
+        };
+        let resp = self.inner.batch_insert(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
+    /// Searches for nearest neighbors.
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            filter: std::collections::HashMap::default(),
+            filters: vec![],
+            hybrid_query: None,
+            hybrid_alpha: None,
+            use_wasserstein: false,
+            collection: collection.unwrap_or_default(),
+            bm25_options: None,
+            vector_f32: Vec::new(),
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Searches using an f32 query vector, sent as a packed f32 payload.
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_f32(
+        &mut self,
+        vector: &[f32],
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = Self::f32_search_request(vector.to_vec(), top_k, collection.unwrap_or_default());
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Searches using text that will be vectorized on the server side.
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_text(
+        &mut self,
+        text: String,
+        top_k: u32,
+        collection: Option<String>,
+        bm25_options: Option<hyperspace_proto::hyperspace::Bm25Options>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchTextRequest {
+            text,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            filter: std::collections::HashMap::new(),
+            filters: vec![],
+            bm25_options,
+            hybrid_alpha: None,
+        };
+        let resp = self.inner.search_text(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Performs search utilizing the Wasserstein distance (Cross-Feature Matching Metric).
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_wasserstein(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            filter: std::collections::HashMap::default(),
+            filters: vec![],
+            hybrid_query: None,
+            hybrid_alpha: None,
+            use_wasserstein: true,
+            collection: collection.unwrap_or_default(),
+            bm25_options: None,
+            vector_f32: Vec::new(),
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Batch search for multiple vectors in a single RPC.
+    ///
+    /// # Errors
+    /// Returns error if the batch search fails.
+    pub async fn search_batch(
+        &mut self,
+        vectors: Vec<Vec<f64>>,
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<Vec<SearchResult>>, tonic::Status> {
+        let collection_name = collection.unwrap_or_default();
+        let searches = vectors
+            .into_iter()
+            .map(|vector| SearchRequest {
+                vector,
+                top_k,
+                filter: std::collections::HashMap::default(),
+                filters: vec![],
+                hybrid_query: None,
+                hybrid_alpha: None,
+                use_wasserstein: false,
+                collection: collection_name.clone(),
+                bm25_options: None,
+                vector_f32: Vec::new(),
+                filter_expr: None,
+                with_vector: false,
+                with_payload: Vec::new(),
+                score_threshold: None,
+                queries: Vec::new(),
+                query_fusion: 0,
+                query_tokens: None,
+                rerank_candidates: 0,
+                session_key: String::new(),
+                explain: false,
+                text_match: 0,
+                joins: Vec::new(),
+                parents: None,
+                snapshot_id: None,
+                as_of: None,
+                num_probes: 0,
+            })
+            .collect();
+
+        let req = BatchSearchRequest { searches };
+        let resp = self.inner.search_batch(req).await?;
+        Ok(resp
+            .into_inner()
+            .responses
+            .into_iter()
+            .map(|SearchResponse { results, .. }| results)
+            .collect())
+    }
+
+    /// Batch search from f32 vectors, sent as packed f32 payloads.
+    ///
+    /// # Errors
+    /// Returns error if the batch search fails.
+    pub async fn search_batch_f32(
+        &mut self,
+        vectors: &[Vec<f32>],
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<Vec<SearchResult>>, tonic::Status> {
+        let collection_name = collection.unwrap_or_default();
+        let searches = vectors
+            .iter()
+            .map(|v| Self::f32_search_request(v.clone(), top_k, collection_name.clone()))
+            .collect();
+        let req = BatchSearchRequest { searches };
+        let resp = self.inner.search_batch(req).await?;
+        Ok(resp
+            .into_inner()
+            .responses
+            .into_iter()
+            .map(|SearchResponse { results, .. }| results)
+            .collect())
+    }
+
+    /// Multi-Geometry Benchmark Endpoint (10.3)
+    /// Performs identical searches across multiple collections in parallel (via Batch Search).
+    /// Typically used by the Frontend to compare L2, Cosine, Poincare and Lorentz results directly.
+    ///
+    /// # Errors
+    /// Returns error if the batch search RPC fails.
+    pub async fn search_multi_collection(
+        &mut self,
+        vector: Vec<f64>,
+        collections: Vec<String>,
+        top_k: u32,
+    ) -> Result<std::collections::HashMap<String, Vec<SearchResult>>, tonic::Status> {
+        let searches = collections
+            .iter()
+            .map(|col_name| SearchRequest {
+                vector: vector.clone(),
+                top_k,
+                filter: std::collections::HashMap::default(),
+                filters: vec![],
+                hybrid_query: None,
+                hybrid_alpha: None,
+                use_wasserstein: false,
+                collection: col_name.clone(),
+                bm25_options: None,
+                vector_f32: Vec::new(),
+                filter_expr: None,
+                with_vector: false,
+                with_payload: Vec::new(),
+                score_threshold: None,
+                queries: Vec::new(),
+                query_fusion: 0,
+                query_tokens: None,
+                rerank_candidates: 0,
+                session_key: String::new(),
+                explain: false,
+                text_match: 0,
+                joins: Vec::new(),
+                parents: None,
+                snapshot_id: None,
+                as_of: None,
+                num_probes: 0,
+            })
+            .collect();
+
+        let req = BatchSearchRequest { searches };
+        let resp = self.inner.search_batch(req).await?;
+
+        let mut result_map = std::collections::HashMap::new();
+        for (col_name, response) in collections.into_iter().zip(resp.into_inner().responses) {
+            result_map.insert(col_name, response.results);
+        }
+
+        Ok(result_map)
+    }
+
+    /// [`Client::search_multi_collection`] with an f32 vector, sent as a
+    /// packed f32 payload.
+    ///
+    /// # Errors
+    /// Returns error if the batch search RPC fails.
+    pub async fn search_multi_collection_f32(
+        &mut self,
+        vector: &[f32],
+        collections: Vec<String>,
+        top_k: u32,
+    ) -> Result<std::collections::HashMap<String, Vec<SearchResult>>, tonic::Status> {
+        let searches = collections
+            .iter()
+            .map(|col_name| Self::f32_search_request(vector.to_vec(), top_k, col_name.clone()))
+            .collect();
+        let req = BatchSearchRequest { searches };
+        let resp = self.inner.search_batch(req).await?;
+        Ok(collections
+            .into_iter()
+            .zip(resp.into_inner().responses)
+            .map(|(col_name, response)| (col_name, response.results))
+            .collect())
+    }
+
+    /// Federated search: runs `text` against every collection, embedded with
+    /// each collection's model, and fuses the hits with reciprocal rank
+    /// fusion. Each hit names the collection it came from.
+    ///
+    /// # Errors
+    /// Returns error if a collection is missing or the search fails.
+    pub async fn multi_search(
+        &mut self,
+        text: String,
+        collections: Vec<String>,
+        top_k: u32,
+    ) -> Result<Vec<MultiSearchHit>, tonic::Status> {
+        let req = MultiSearchRequest {
+            collections,
+            text,
+            vector: Vec::new(),
+            top_k,
+            filter: std::collections::HashMap::default(),
+            filters: vec![],
+            hybrid_alpha: None,
+            dedupe_key: String::new(),
+            vector_f32: Vec::new(),
+        };
+        let resp = self.inner.multi_search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Advanced search with filters and hybrid query.
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_advanced(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        filters: Vec<hyperspace_proto::hyperspace::Filter>,
+        hybrid: Option<(String, f32)>,
+        bm25_options: Option<hyperspace_proto::hyperspace::Bm25Options>,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let (hybrid_query, hybrid_alpha) = match hybrid {
+            Some((q, a)) => (Some(q), Some(a)),
+            None => (None, None),
+        };
+
+        let req = SearchRequest {
+            vector,
+            top_k,
+            filter: std::collections::HashMap::default(),
+            filters,
+            hybrid_query,
+            hybrid_alpha,
+            use_wasserstein: false,
+            collection: collection.unwrap_or_default(),
+            bm25_options,
+            vector_f32: Vec::new(),
+            filter_expr: None,
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Search that returns only the metadata keys in `with_payload` (all of
+    /// them when empty) and, with `with_vector`, each hit's stored vector.
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_projected(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        with_payload: Vec<String>,
+        with_vector: bool,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            with_vector,
+            with_payload,
+            ..Default::default()
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Search whose hits carry the metadata of related points in other
+    /// collections, e.g. chunks with the fields of their parent document.
+    ///
+    /// # Errors
+    /// Returns error if search fails or a joined collection is missing.
+    pub async fn search_joined(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        joins: Vec<MetadataJoin>,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            joins,
+            ..Default::default()
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
+    }
+
+    /// Searches a chunk collection and returns its parent documents, each
+    /// once, with up to `chunks_per_parent` of their best chunks (0 keeps 3).
+    ///
+    /// # Errors
+    /// Returns error if search fails or the collection has no parent
+    /// collection.
+    pub async fn search_parents(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        chunks_per_parent: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<ParentHit>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            parents: Some(ParentSearch { chunks_per_parent }),
+            ..Default::default()
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().parents)
     }
 
-    /// Batch inserts multiple vectors.
+    /// Searches the collection as it was at the newest snapshot generation
+    /// taken at or before `as_of` (Unix seconds), e.g. to reproduce an
+    /// earlier retrieval. The collection must keep a snapshot history.
     ///
     /// # Errors
-    /// Returns error if insertion fails.
-    pub async fn batch_insert(
+    /// Returns error if search fails or no generation is old enough.
+    pub async fn search_as_of(
         &mut self,
-        items: Vec<(u32, Vec<f64>, std::collections::HashMap<String, String>)>,
+        vector: Vec<f64>,
+        top_k: u32,
+        as_of: u64,
         collection: Option<String>,
-        durability: DurabilityLevel,
-    ) -> Result<bool, tonic::Status> {
-        let vectors = items
-            .into_iter()
-            .map(|(id, vector, metadata)| VectorData {
-                id,
-                vector,
-                metadata,
-                typed_metadata: std::collections::HashMap::new(),
-            })
-            .collect();
-        let req = BatchInsertRequest {
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
             collection: collection.unwrap_or_default(),
-            vectors,
-            origin_node_id: String::new(),
-            logical_clock: 0,
-            durability: durability as i32,
+            as_of: Some(as_of),
+            ..Default::default()
         };
-        let resp = self.inner.batch_insert(req).await?;
-        Ok(resp.into_inner().success)
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
     }
 
-    /// Batch inserts multiple vectors from f32 input.
+    /// Search that drops hits whose distance is above `score_threshold`, so
+    /// fewer than `top_k` results may come back.
     ///
     /// # Errors
-    /// Returns error if insertion fails.
-    pub async fn batch_insert_f32(
+    /// Returns error if search fails.
+    pub async fn search_within(
         &mut self,
-        items: Vec<(u32, Vec<f32>, std::collections::HashMap<String, String>)>,
+        vector: Vec<f64>,
+        top_k: u32,
+        score_threshold: f64,
         collection: Option<String>,
-        durability: DurabilityLevel,
-    ) -> Result<bool, tonic::Status> {
-        let items_f64 = items
-            .into_iter()
-            .map(|(id, v, m)| (id, Self::vec_f32_to_f64(&v), m))
-            .collect();
-        self.batch_insert(items_f64, collection, durability).await
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            score_threshold: Some(score_threshold),
+            ..Default::default()
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
     }
 
-    /// Searches for nearest neighbors.
+    /// Search that runs `num_probes` layer-0 searches in parallel, each from
+    /// a different layer-1 entry, and merges them: higher recall at the cost
+    /// of roughly `num_probes` times the work. The server caps it at 16.
     ///
     /// # Errors
     /// Returns error if search fails.
-    pub async fn search(
+    pub async fn search_probed(
         &mut self,
         vector: Vec<f64>,
         top_k: u32,
+        num_probes: u32,
         collection: Option<String>,
     ) -> Result<Vec<SearchResult>, tonic::Status> {
         let req = SearchRequest {
             vector,
             top_k,
-            filter: std::collections::HashMap::default(),
-            filters: vec![],
-            hybrid_query: None,
-            hybrid_alpha: None,
-            use_wasserstein: false,
             collection: collection.unwrap_or_default(),
-            bm25_options: None,
+            num_probes,
+            ..Default::default()
         };
         let resp = self.inner.search(req).await?;
         Ok(resp.into_inner().results)
     }
 
-    /// Searches using f32 query vector (converted to protocol f64 once).
+    /// Searches several query vectors at once (e.g. HyDE expansions) and
+    /// returns one ranking fused on the server.
     ///
     /// # Errors
-    /// Returns error if search fails.
-    pub async fn search_f32(
+    /// Returns error if search fails or more than 32 queries are given.
+    pub async fn search_multi(
         &mut self,
-        vector: &[f32],
+        queries: Vec<Vec<f64>>,
         top_k: u32,
+        fusion: hyperspace_proto::hyperspace::QueryFusion,
         collection: Option<String>,
     ) -> Result<Vec<SearchResult>, tonic::Status> {
-        self.search(Self::vec_f32_to_f64(vector), top_k, collection)
-            .await
+        let req = SearchRequest {
+            top_k,
+            collection: collection.unwrap_or_default(),
+            queries: queries
+                .into_iter()
+                .map(|values| hyperspace_proto::hyperspace::QueryVector { values })
+                .collect(),
+            query_fusion: fusion as i32,
+            ..Default::default()
+        };
+        let resp = self.inner.search(req).await?;
+        Ok(resp.into_inner().results)
     }
 
-    /// Searches using text that will be vectorized on the server side.
+    /// Retrieves `rerank_candidates` hits by the pooled `vector` (0 for the
+    /// server default) and returns the best `top_k` by MaxSim against
+    /// `query_tokens`.
     ///
     /// # Errors
-    /// Returns error if search fails.
-    pub async fn search_text(
+    /// Returns error if search fails or the token vectors are malformed.
+    pub async fn search_late_interaction(
         &mut self,
-        text: String,
+        vector: Vec<f64>,
+        query_tokens: hyperspace_proto::hyperspace::TokenVectors,
         top_k: u32,
+        rerank_candidates: u32,
         collection: Option<String>,
-        bm25_options: Option<hyperspace_proto::hyperspace::Bm25Options>,
     ) -> Result<Vec<SearchResult>, tonic::Status> {
-        let req = SearchTextRequest {
-            text,
+        let req = SearchRequest {
+            vector,
             top_k,
             collection: collection.unwrap_or_default(),
-            filter: std::collections::HashMap::new(),
-            filters: vec![],
-            bm25_options,
-            hybrid_alpha: None,
+            query_tokens: Some(query_tokens),
+            rerank_candidates,
+            ..Default::default()
         };
-        let resp = self.inner.search_text(req).await?;
+        let resp = self.inner.search(req).await?;
         Ok(resp.into_inner().results)
     }
 
-    /// Performs search utilizing the Wasserstein distance (Cross-Feature Matching Metric).
+    /// Search with a textual filter such as `genre = "jazz" AND year >= 1990`.
     ///
     /// # Errors
-    /// Returns error if search fails.
-    pub async fn search_wasserstein(
+    /// Returns `InvalidArgument` if the expression does not parse; the status
+    /// details decode to [`hyperspace_proto::hyperspace::FilterSyntaxError`].
+    pub async fn search_expr(
         &mut self,
         vector: Vec<f64>,
         top_k: u32,
+        filter_expr: &str,
         collection: Option<String>,
     ) -> Result<Vec<SearchResult>, tonic::Status> {
         let req = SearchRequest {
@@ -407,136 +1553,150 @@ impl Client {
             filters: vec![],
             hybrid_query: None,
             hybrid_alpha: None,
-            use_wasserstein: true,
+            use_wasserstein: false,
             collection: collection.unwrap_or_default(),
             bm25_options: None,
+            vector_f32: Vec::new(),
+            filter_expr: Some(filter_expr.to_string()),
+            with_vector: false,
+            with_payload: Vec::new(),
+            score_threshold: None,
+            queries: Vec::new(),
+            query_fusion: 0,
+            query_tokens: None,
+            rerank_candidates: 0,
+            session_key: String::new(),
+            explain: false,
+            text_match: 0,
+            joins: Vec::new(),
+            parents: None,
+            snapshot_id: None,
+            as_of: None,
+            num_probes: 0,
         };
         let resp = self.inner.search(req).await?;
         Ok(resp.into_inner().results)
     }
 
-    /// Batch search for multiple vectors in a single RPC.
+    /// Runs a named query template registered on the collection, filling its
+    /// `$placeholders` from `params`. `top_k` of 0 uses the template's value.
     ///
     /// # Errors
-    /// Returns error if the batch search fails.
-    pub async fn search_batch(
+    /// Returns `NotFound` if the template does not exist and `InvalidArgument`
+    /// if a placeholder has no value.
+    pub async fn run_query(
         &mut self,
-        vectors: Vec<Vec<f64>>,
+        name: &str,
+        vector: Vec<f64>,
+        params: std::collections::HashMap<String, String>,
         top_k: u32,
         collection: Option<String>,
-    ) -> Result<Vec<Vec<SearchResult>>, tonic::Status> {
-        let collection_name = collection.unwrap_or_default();
-        let searches = vectors
-            .into_iter()
-            .map(|vector| SearchRequest {
-                vector,
-                top_k,
-                filter: std::collections::HashMap::default(),
-                filters: vec![],
-                hybrid_query: None,
-                hybrid_alpha: None,
-                use_wasserstein: false,
-                collection: collection_name.clone(),
-                bm25_options: None,
-            })
-            .collect();
-
-        let req = BatchSearchRequest { searches };
-        let resp = self.inner.search_batch(req).await?;
-        Ok(resp
-            .into_inner()
-            .responses
-            .into_iter()
-            .map(|SearchResponse { results }| results)
-            .collect())
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = RunQueryTemplateRequest {
+            collection: collection.unwrap_or_default(),
+            name: name.to_string(),
+            vector,
+            params,
+            top_k,
+            vector_f32: Vec::new(),
+        };
+        let resp = self.inner.run_query_template(req).await?;
+        Ok(resp.into_inner().results)
     }
 
-    /// Batch search from f32 vectors (converted to protocol f64 once).
+    /// [`Client::run_query`] with an f32 vector, sent as a packed f32 payload.
     ///
     /// # Errors
-    /// Returns error if the batch search fails.
-    pub async fn search_batch_f32(
+    /// Returns `NotFound` if the template does not exist and `InvalidArgument`
+    /// if a placeholder has no value.
+    pub async fn run_query_f32(
         &mut self,
-        vectors: &[Vec<f32>],
+        name: &str,
+        vector: &[f32],
+        params: std::collections::HashMap<String, String>,
         top_k: u32,
         collection: Option<String>,
-    ) -> Result<Vec<Vec<SearchResult>>, tonic::Status> {
-        let vectors_f64 = vectors
-            .iter()
-            .map(|v| Self::vec_f32_to_f64(v))
-            .collect::<Vec<_>>();
-        self.search_batch(vectors_f64, top_k, collection).await
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
+        let req = RunQueryTemplateRequest {
+            collection: collection.unwrap_or_default(),
+            name: name.to_string(),
+            vector: Vec::new(),
+            params,
+            top_k,
+            vector_f32: vector.to_vec(),
+        };
+        let resp = self.inner.run_query_template(req).await?;
+        Ok(resp.into_inner().results)
     }
 
-    /// Multi-Geometry Benchmark Endpoint (10.3)
-    /// Performs identical searches across multiple collections in parallel (via Batch Search).
-    /// Typically used by the Frontend to compare L2, Cosine, Poincare and Lorentz results directly.
+    /// Search routed through the collection's A/B experiment by `session_key`.
+    /// The response's `experiment_arm` tells which arm served it (empty when
+    /// no experiment is running).
     ///
     /// # Errors
-    /// Returns error if the batch search RPC fails.
-    pub async fn search_multi_collection(
+    /// Returns error if search fails.
+    pub async fn search_in_session(
         &mut self,
         vector: Vec<f64>,
-        collections: Vec<String>,
         top_k: u32,
-    ) -> Result<std::collections::HashMap<String, Vec<SearchResult>>, tonic::Status> {
-        let searches = collections
-            .iter()
-            .map(|col_name| SearchRequest {
-                vector: vector.clone(),
-                top_k,
-                filter: std::collections::HashMap::default(),
-                filters: vec![],
-                hybrid_query: None,
-                hybrid_alpha: None,
-                use_wasserstein: false,
-                collection: col_name.clone(),
-                bm25_options: None,
-            })
-            .collect();
-
-        let req = BatchSearchRequest { searches };
-        let resp = self.inner.search_batch(req).await?;
-
-        let mut result_map = std::collections::HashMap::new();
-        for (col_name, response) in collections.into_iter().zip(resp.into_inner().responses) {
-            result_map.insert(col_name, response.results);
-        }
-
-        Ok(result_map)
+        session_key: &str,
+        collection: Option<String>,
+    ) -> Result<SearchResponse, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            session_key: session_key.to_string(),
+            ..Default::default()
+        };
+        Ok(self.inner.search(req).await?.into_inner())
     }
 
-    /// Advanced search with filters and hybrid query.
+    /// Search that also returns `SearchResponse.explain`: nodes visited,
+    /// layers walked, time per phase and how the filter was applied.
     ///
     /// # Errors
     /// Returns error if search fails.
-    pub async fn search_advanced(
+    pub async fn search_explain(
         &mut self,
         vector: Vec<f64>,
         top_k: u32,
-        filters: Vec<hyperspace_proto::hyperspace::Filter>,
-        hybrid: Option<(String, f32)>,
-        bm25_options: Option<hyperspace_proto::hyperspace::Bm25Options>,
+        filter_expr: Option<String>,
         collection: Option<String>,
-    ) -> Result<Vec<SearchResult>, tonic::Status> {
-        let (hybrid_query, hybrid_alpha) = match hybrid {
-            Some((q, a)) => (Some(q), Some(a)),
-            None => (None, None),
+    ) -> Result<SearchResponse, tonic::Status> {
+        let req = SearchRequest {
+            vector,
+            top_k,
+            filter_expr,
+            collection: collection.unwrap_or_default(),
+            explain: true,
+            ..Default::default()
         };
+        Ok(self.inner.search(req).await?.into_inner())
+    }
 
+    /// Hybrid search that only returns documents containing every token of
+    /// `text` (`MatchAll`) or `text` as a phrase (`MatchPhrase`).
+    ///
+    /// # Errors
+    /// Returns error if search fails.
+    pub async fn search_text_match(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        text: String,
+        mode: hyperspace_proto::hyperspace::TextMatch,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, tonic::Status> {
         let req = SearchRequest {
             vector,
             top_k,
-            filter: std::collections::HashMap::default(),
-            filters,
-            hybrid_query,
-            hybrid_alpha,
-            use_wasserstein: false,
+            hybrid_query: Some(text),
+            text_match: mode.into(),
             collection: collection.unwrap_or_default(),
-            bm25_options,
+            ..Default::default()
         };
-        let resp = self.inner.search(req).await?;
-        Ok(resp.into_inner().results)
+        Ok(self.inner.search(req).await?.into_inner().results)
     }
 
     /// High-level hybrid search combining vector (semantic) and lexical (BM25) ranking.
@@ -580,6 +1740,49 @@ impl Client {
         Ok(resp.into_inner().success)
     }
 
+    /// Hard-deletes the points matching `filter_expr` (GDPR erasure) and
+    /// returns the signed report of the erased IDs.
+    ///
+    /// # Errors
+    /// Returns `INVALID_ARGUMENT` without a filter, and `FAILED_PRECONDITION`
+    /// when the collection has points in immutable chunks.
+    pub async fn purge(
+        &mut self,
+        filter_expr: String,
+        collection: Option<String>,
+    ) -> Result<PurgeReport, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::PurgeRequest {
+            collection: collection.unwrap_or_default(),
+            filter_expr: Some(filter_expr),
+            ..Default::default()
+        };
+        let resp = self.inner.purge(req).await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Overwrites the coordinates at `indices` with `values`, keeping the
+    /// rest of the stored vector and its metadata.
+    ///
+    /// # Errors
+    /// Returns error if the id is unknown or an index is out of range.
+    pub async fn update_vector_delta(
+        &mut self,
+        id: u32,
+        indices: Vec<u32>,
+        values: Vec<f64>,
+        collection: Option<String>,
+    ) -> Result<bool, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::UpdateVectorDeltaRequest {
+            collection: collection.unwrap_or_default(),
+            id,
+            indices,
+            values,
+            durability: 0,
+        };
+        let resp = self.inner.update_vector_delta(req).await?;
+        Ok(resp.into_inner().success)
+    }
+
     /// Returns a graph node with adjacency on a specific layer.
     ///
     /// # Errors
@@ -702,11 +1905,50 @@ impl Client {
         let req = EventSubscriptionRequest {
             types: types.into_iter().map(|t| t as i32).collect(),
             collection,
+            include_vectors: false,
         };
         let resp = self.inner.subscribe_to_events(req).await?;
         Ok(resp.into_inner())
     }
 
+    /// Streams the indexed-clock watermark of a collection: one message now,
+    /// then one whenever the watermark or the indexer queue moves.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` if the collection does not exist.
+    pub async fn watch_indexing_progress(
+        &mut self,
+        collection: String,
+    ) -> Result<tonic::Streaming<IndexingProgress>, tonic::Status> {
+        let req = WatchIndexingProgressRequest { collection };
+        let resp = self.inner.watch_indexing_progress(req).await?;
+        Ok(resp.into_inner())
+    }
+
+    /// Waits until every write the collection had accepted when this was
+    /// called is searchable, e.g. after a bulk load. Returns the progress
+    /// that satisfied the wait.
+    ///
+    /// # Errors
+    /// Returns `NOT_FOUND` if the collection does not exist or is removed
+    /// meanwhile.
+    pub async fn wait_until_indexed(
+        &mut self,
+        collection: String,
+    ) -> Result<IndexingProgress, tonic::Status> {
+        let mut stream = self.watch_indexing_progress(collection).await?;
+        let mut target = None;
+        while let Some(progress) = stream.message().await? {
+            let target = *target.get_or_insert(progress.written_clock);
+            if progress.indexed_clock >= target {
+                return Ok(progress);
+            }
+        }
+        Err(tonic::Status::unavailable(
+            "Indexing progress stream ended early",
+        ))
+    }
+
     /// Configures collection parameters.
     ///
     /// # Errors
@@ -721,6 +1963,27 @@ impl Client {
             ef_search,
             ef_construction,
             collection: collection.unwrap_or_default(),
+            wal_sync_mode: None,
+        };
+        let resp = self.inner.configure(req).await?;
+        Ok(resp.into_inner().status)
+    }
+
+    /// Sets a collection's WAL sync mode (`strict`, `batch`, `async`, or
+    /// `default` for the server-wide setting). Persisted on the server.
+    ///
+    /// # Errors
+    /// Returns error if the mode is unknown or the collection does not exist.
+    pub async fn set_wal_sync_mode(
+        &mut self,
+        collection: String,
+        mode: &str,
+    ) -> Result<String, tonic::Status> {
+        let req = hyperspace_proto::hyperspace::ConfigUpdate {
+            collection,
+            ef_search: None,
+            ef_construction: None,
+            wal_sync_mode: Some(mode.to_string()),
         };
         let resp = self.inner.configure(req).await?;
         Ok(resp.into_inner().status)
@@ -0,0 +1,334 @@
+//! Read-through local mirror of a small collection.
+//!
+//! [`LocalCache`] loads every point of one collection with `SyncPull` and then
+//! follows the CDC stream (`SubscribeToEvents` with vectors attached) to stay
+//! current. Searches scan the local copy exactly, which for the few thousand
+//! points this is meant for costs less than one network round trip. Writes go
+//! to the server and reach the mirror through the stream, so a search right
+//! after a write may not see it yet. While the stream is down, searches read
+//! through to the server until [`LocalCache::resync`] succeeds.
+
+use crate::Client;
+use hyperspace_proto::hyperspace::{
+    event_message, EventMessage, EventSubscriptionRequest, EventType, SearchResult,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use tokio::task::JoinHandle;
+use tonic::Status;
+
+/// Metadata keys the server uses to carry typed values; not user metadata.
+const TYPED_META_PREFIX: &str = "__hs_typed__";
+
+/// Number of sync buckets on the server (`id % 256`).
+const SYNC_BUCKETS: u32 = 256;
+
+struct CachedPoint {
+    vector: Vec<f64>,
+    metadata: HashMap<String, String>,
+}
+
+type Points = Arc<RwLock<HashMap<u32, CachedPoint>>>;
+
+pub struct LocalCache {
+    client: Client,
+    collection: String,
+    metric: String,
+    max_points: u64,
+    points: Points,
+    live: Arc<AtomicBool>,
+    follower: Option<JoinHandle<()>>,
+}
+
+impl LocalCache {
+    /// Mirrors `collection` and starts following its changes.
+    ///
+    /// # Errors
+    /// Returns `FAILED_PRECONDITION` if the collection holds more than
+    /// `max_points` points, or the error of the initial load.
+    pub async fn open(client: Client, collection: String, max_points: u64) -> Result<Self, Status> {
+        let mut cache = Self {
+            client,
+            collection,
+            metric: String::new(),
+            max_points,
+            points: Arc::default(),
+            live: Arc::new(AtomicBool::new(false)),
+            follower: None,
+        };
+        cache.resync().await?;
+        Ok(cache)
+    }
+
+    /// Reloads the mirror from scratch and restarts the change stream.
+    ///
+    /// # Errors
+    /// Same as [`LocalCache::open`]; the cache keeps reading through to the
+    /// server after a failure.
+    pub async fn resync(&mut self) -> Result<(), Status> {
+        if let Some(follower) = self.follower.take() {
+            follower.abort();
+        }
+        self.live.store(false, Ordering::Release);
+
+        let stats = self
+            .client
+            .get_collection_stats(self.collection.clone())
+            .await?;
+        if stats.count > self.max_points {
+            return Err(Status::failed_precondition(format!(
+                "Collection '{}' has {} points, over the local cache limit of {}",
+                self.collection, stats.count, self.max_points
+            )));
+        }
+        self.metric = stats.metric;
+
+        // Subscribe before the bulk load so writes in between are not lost;
+        // replaying them over the loaded points converges.
+        let mut events = self
+            .client
+            .inner
+            .subscribe_to_events(EventSubscriptionRequest {
+                types: vec![
+                    EventType::VectorInserted as i32,
+                    EventType::VectorDeleted as i32,
+                ],
+                collection: Some(self.collection.clone()),
+                include_vectors: true,
+            })
+            .await?
+            .into_inner();
+
+        let mut pull = self
+            .client
+            .sync_pull(self.collection.clone(), (0..SYNC_BUCKETS).collect())
+            .await?;
+        let mut loaded = HashMap::new();
+        while let Some(point) = pull.message().await? {
+            loaded.insert(
+                point.id,
+                CachedPoint {
+                    vector: point.vector,
+                    metadata: user_metadata(point.metadata),
+                },
+            );
+        }
+        *self.points.write().unwrap_or_else(PoisonError::into_inner) = loaded;
+
+        let points = self.points.clone();
+        let live = self.live.clone();
+        live.store(true, Ordering::Release);
+        self.follower = Some(tokio::spawn(async move {
+            while let Ok(Some(event)) = events.message().await {
+                apply_event(&points, event);
+            }
+            live.store(false, Ordering::Release);
+        }));
+        Ok(())
+    }
+
+    /// Whether the change stream is running and local searches are current.
+    pub fn is_live(&self) -> bool {
+        self.live.load(Ordering::Acquire)
+    }
+
+    /// Number of mirrored points.
+    pub fn len(&self) -> usize {
+        self.points
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Underlying client, for calls the cache does not wrap.
+    pub fn client(&mut self) -> &mut Client {
+        &mut self.client
+    }
+
+    /// Searches the local mirror, or the server while the stream is down.
+    ///
+    /// # Errors
+    /// Returns error only when reading through to the server fails.
+    pub async fn search(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+    ) -> Result<Vec<SearchResult>, Status> {
+        if self.is_live() {
+            return Ok(self.search_local(&vector, top_k as usize));
+        }
+        self.client
+            .search(vector, top_k, Some(self.collection.clone()))
+            .await
+    }
+
+    /// Exact search over the local mirror, regardless of stream state.
+    pub fn search_local(&self, vector: &[f64], top_k: usize) -> Vec<SearchResult> {
+        let query = if self.metric == "cosine" {
+            normalized(vector)
+        } else {
+            vector.to_vec()
+        };
+        let points = self.points.read().unwrap_or_else(PoisonError::into_inner);
+        let mut scored: Vec<(f64, u32)> = points
+            .iter()
+            .filter(|(_, p)| p.vector.len() == query.len())
+            .map(|(&id, p)| (distance(&self.metric, &query, &p.vector), id))
+            .collect();
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        scored
+            .into_iter()
+            .take(top_k)
+            .map(|(distance, id)| SearchResult {
+                id,
+                distance,
+                metadata: points[&id].metadata.clone(),
+                typed_metadata: HashMap::new(),
+                vector: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Inserts on the server; the mirror picks it up from the stream.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn insert(
+        &mut self,
+        id: u32,
+        vector: Vec<f64>,
+        metadata: HashMap<String, String>,
+    ) -> Result<bool, Status> {
+        self.client
+            .insert(id, vector, metadata, Some(self.collection.clone()))
+            .await
+    }
+
+    /// Deletes on the server; the mirror picks it up from the stream.
+    ///
+    /// # Errors
+    /// Returns error if deletion fails.
+    pub async fn delete(&mut self, id: u32) -> Result<bool, Status> {
+        self.client.delete(id, Some(self.collection.clone())).await
+    }
+}
+
+impl Drop for LocalCache {
+    fn drop(&mut self) {
+        if let Some(follower) = self.follower.take() {
+            follower.abort();
+        }
+    }
+}
+
+fn apply_event(points: &Points, event: EventMessage) {
+    let mut points = points.write().unwrap_or_else(PoisonError::into_inner);
+    match event.payload {
+        Some(event_message::Payload::VectorInserted(e)) if !e.vector.is_empty() => {
+            points.insert(
+                e.id,
+                CachedPoint {
+                    vector: e.vector,
+                    metadata: user_metadata(e.metadata),
+                },
+            );
+        }
+        Some(event_message::Payload::VectorDeleted(e)) => {
+            points.remove(&e.id);
+        }
+        _ => {}
+    }
+}
+
+fn user_metadata(mut metadata: HashMap<String, String>) -> HashMap<String, String> {
+    metadata.retain(|k, _| !k.starts_with(TYPED_META_PREFIX));
+    metadata
+}
+
+fn normalized(v: &[f64]) -> Vec<f64> {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        v.iter().map(|x| x / norm).collect()
+    } else {
+        v.to_vec()
+    }
+}
+
+/// Same distances the server reports for each metric.
+fn distance(metric: &str, a: &[f64], b: &[f64]) -> f64 {
+    match metric {
+        "poincare" => {
+            let norm_u_sq: f64 = a.iter().map(|x| x * x).sum();
+            let norm_v_sq: f64 = b.iter().map(|x| x * x).sum();
+            let diff_sq: f64 = a.iter().zip(b).map(|(u, v)| (u - v).powi(2)).sum();
+            let denom = (1.0 - norm_u_sq) * (1.0 - norm_v_sq);
+            (1.0 + 2.0 * diff_sq / denom.max(1e-9)).acosh()
+        }
+        "lorentz" => {
+            let inner: f64 =
+                -a[0] * b[0] + a[1..].iter().zip(&b[1..]).map(|(u, v)| u * v).sum::<f64>();
+            (-inner).max(1.0 + 1e-12).acosh()
+        }
+        // L2 and cosine (on normalized vectors) report squared Euclidean.
+        _ => a.iter().zip(b).map(|(u, v)| (u - v).powi(2)).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_proto::hyperspace::{VectorDeletedEvent, VectorInsertedEvent};
+
+    fn inserted(id: u32, vector: Vec<f64>) -> EventMessage {
+        EventMessage {
+            r#type: EventType::VectorInserted as i32,
+            payload: Some(event_message::Payload::VectorInserted(
+                VectorInsertedEvent {
+                    id,
+                    vector,
+                    ..Default::default()
+                },
+            )),
+        }
+    }
+
+    #[test]
+    fn test_events_update_mirror() {
+        let points = Points::default();
+        apply_event(&points, inserted(1, vec![0.0, 0.0]));
+        apply_event(&points, inserted(2, vec![1.0, 0.0]));
+        apply_event(&points, inserted(1, vec![0.5, 0.0]));
+        // Events without vectors cannot be mirrored and are skipped.
+        apply_event(&points, inserted(3, Vec::new()));
+        apply_event(
+            &points,
+            EventMessage {
+                r#type: EventType::VectorDeleted as i32,
+                payload: Some(event_message::Payload::VectorDeleted(VectorDeletedEvent {
+                    id: 2,
+                    ..Default::default()
+                })),
+            },
+        );
+
+        let points = points.read().unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[&1].vector, vec![0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_distance_matches_server_metrics() {
+        assert!((distance("l2", &[0.0, 0.0], &[3.0, 4.0]) - 25.0).abs() < 1e-12);
+        assert!(distance("poincare", &[0.1, 0.0], &[0.1, 0.0]).abs() < 1e-6);
+        let d = distance("poincare", &[0.0, 0.0], &[0.5, 0.0]);
+        assert!((d - 3.0_f64.ln()).abs() < 1e-9, "{d}");
+        let origin = [1.0, 0.0];
+        let p = [2.0_f64.cosh(), 2.0_f64.sinh()];
+        assert!((distance("lorentz", &origin, &p) - 2.0).abs() < 1e-9);
+    }
+}
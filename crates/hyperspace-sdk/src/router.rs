@@ -0,0 +1,442 @@
+//! Client-side sharding across independent servers.
+//!
+//! [`HashRing`] places ids on shards by consistent hashing: each shard owns
+//! `vnodes` points on a 64-bit ring and an id belongs to the first point at
+//! or after its hash. Adding a shard to `n` existing ones moves only the ids
+//! the new shard's points take over, about `1 / (n + 1)` of them.
+//!
+//! [`ShardRouter`] keeps one [`Client`] per shard. Writes go to the shard
+//! owning the id; searches run on every shard concurrently and the lists are
+//! merged by distance (smaller is better everywhere). The servers know
+//! nothing about each other, so collections have to exist on every shard.
+//! [`ShardRouter::discover`] finds the shards from one node's gossip
+//! topology instead of a fixed address list.
+
+use crate::{Client, DurabilityLevel, SearchRequest, SearchResult};
+use std::collections::HashMap;
+use tonic::Status;
+
+/// Ring points per shard when the caller doesn't pick a number.
+pub const DEFAULT_VNODES: usize = 128;
+
+/// Points copied to a new shard per `BatchInsert` during a rebalance.
+const REBALANCE_BATCH: usize = 512;
+
+/// Number of sync buckets on the server (`id % 256`).
+const SYNC_BUCKETS: u32 = 256;
+
+#[derive(Debug, Clone)]
+pub struct HashRing {
+    shards: Vec<String>,
+    vnodes: usize,
+    /// `(position, shard index)`, sorted by position.
+    points: Vec<(u64, usize)>,
+}
+
+/// An id whose owner changes between two rings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardMove {
+    pub id: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl HashRing {
+    /// A ring over `shards`, named by anything stable such as their address.
+    pub fn new<S: Into<String>>(shards: impl IntoIterator<Item = S>, vnodes: usize) -> Self {
+        let mut ring = Self {
+            shards: Vec::new(),
+            vnodes: vnodes.max(1),
+            points: Vec::new(),
+        };
+        for shard in shards {
+            ring.push(shard.into());
+        }
+        ring
+    }
+
+    fn push(&mut self, shard: String) {
+        let index = self.shards.len();
+        self.points
+            .extend((0..self.vnodes).map(|v| (hash(format!("{shard}#{v}").as_bytes()), index)));
+        self.points.sort_unstable();
+        self.shards.push(shard);
+    }
+
+    pub fn shards(&self) -> &[String] {
+        &self.shards
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    /// Index of the shard owning `id`, `None` on an empty ring.
+    pub fn shard_for(&self, id: u32) -> Option<usize> {
+        let position = hash(&id.to_le_bytes());
+        let next = self.points.partition_point(|p| p.0 < position);
+        self.points
+            .get(next)
+            .or_else(|| self.points.first())
+            .map(|p| p.1)
+    }
+
+    /// This ring with `shard` added; existing shards keep their indices.
+    pub fn with_shard(&self, shard: impl Into<String>) -> Self {
+        let mut next = self.clone();
+        next.push(shard.into());
+        next
+    }
+
+    /// The ids among `ids` that `next` places on another shard.
+    pub fn moves(&self, next: &Self, ids: impl IntoIterator<Item = u32>) -> Vec<ShardMove> {
+        ids.into_iter()
+            .filter_map(|id| match (self.shard_for(id), next.shard_for(id)) {
+                (Some(from), Some(to)) if from != to => Some(ShardMove { id, from, to }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// FNV-1a with a `SplitMix64` finalizer: stable across builds and platforms,
+/// unlike `std`'s hasher, so every client agrees on placement.
+fn hash(bytes: &[u8]) -> u64 {
+    let mut h = 0xcbf2_9ce4_8422_2325_u64;
+    for &b in bytes {
+        h = (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+pub struct ShardRouter {
+    ring: HashRing,
+    clients: Vec<Client>,
+}
+
+impl ShardRouter {
+    /// Connects to every address; the addresses name the shards on the ring.
+    ///
+    /// # Errors
+    /// Returns error if any connection fails.
+    pub async fn connect(
+        addrs: Vec<String>,
+        api_key: Option<String>,
+        user_id: Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut shards = Vec::with_capacity(addrs.len());
+        for addr in addrs {
+            let client = Client::connect(addr.clone(), api_key.clone(), user_id.clone()).await?;
+            shards.push((addr, client));
+        }
+        Ok(Self::new(shards))
+    }
+
+    /// Connects to every healthy leader or standalone node in `seed`'s
+    /// topology; followers only replicate, so they are left out.
+    ///
+    /// # Errors
+    /// Returns error if the topology can't be fetched or a connection fails.
+    pub async fn discover(
+        seed: String,
+        api_key: Option<String>,
+        user_id: Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut client = Client::connect(seed.clone(), api_key.clone(), user_id.clone()).await?;
+        let mut addrs = Vec::new();
+        for node in client.get_topology().await? {
+            if !node.healthy || node.role == "Follower" {
+                continue;
+            }
+            if node.local && node.grpc_addr.is_empty() {
+                addrs.push(seed.clone());
+            } else if !node.grpc_addr.is_empty() {
+                addrs.push(node.grpc_addr);
+            }
+        }
+        Self::connect(addrs, api_key, user_id).await
+    }
+
+    /// A router over already connected clients, with [`DEFAULT_VNODES`].
+    pub fn new(shards: Vec<(String, Client)>) -> Self {
+        let (names, clients): (Vec<_>, Vec<_>) = shards.into_iter().unzip();
+        Self {
+            ring: HashRing::new(names, DEFAULT_VNODES),
+            clients,
+        }
+    }
+
+    pub fn ring(&self) -> &HashRing {
+        &self.ring
+    }
+
+    /// Client of the shard owning `id`, for calls the router does not wrap.
+    ///
+    /// # Errors
+    /// Returns `FAILED_PRECONDITION` when the router has no shards.
+    pub fn shard_mut(&mut self, id: u32) -> Result<&mut Client, Status> {
+        let shard = self
+            .ring
+            .shard_for(id)
+            .ok_or_else(|| Status::failed_precondition("Router has no shards"))?;
+        Ok(&mut self.clients[shard])
+    }
+
+    /// Creates the collection on every shard.
+    ///
+    /// # Errors
+    /// Returns the first shard's error; shards before it keep the collection.
+    pub async fn create_collection(
+        &mut self,
+        name: String,
+        dimension: u32,
+        metric: String,
+    ) -> Result<(), Status> {
+        for client in &mut self.clients {
+            client
+                .create_collection(name.clone(), dimension, metric.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts on the shard owning `id`.
+    ///
+    /// # Errors
+    /// Returns error if insertion fails.
+    pub async fn insert(
+        &mut self,
+        id: u32,
+        vector: Vec<f64>,
+        metadata: HashMap<String, String>,
+        collection: Option<String>,
+    ) -> Result<bool, Status> {
+        self.shard_mut(id)?
+            .insert(id, vector, metadata, collection)
+            .await
+    }
+
+    /// Splits `items` by owning shard and sends one batch to each.
+    ///
+    /// # Errors
+    /// Returns the first failing shard's error; other shards may have
+    /// applied their part.
+    pub async fn batch_insert(
+        &mut self,
+        items: Vec<(u32, Vec<f64>, HashMap<String, String>)>,
+        collection: Option<String>,
+        durability: DurabilityLevel,
+    ) -> Result<bool, Status> {
+        let mut per_shard: Vec<Vec<_>> = vec![Vec::new(); self.clients.len()];
+        for item in items {
+            let shard = self
+                .ring
+                .shard_for(item.0)
+                .ok_or_else(|| Status::failed_precondition("Router has no shards"))?;
+            per_shard[shard].push(item);
+        }
+        let mut success = true;
+        for (client, batch) in self.clients.iter_mut().zip(per_shard) {
+            if !batch.is_empty() {
+                success &= client
+                    .batch_insert(batch, collection.clone(), durability)
+                    .await?;
+            }
+        }
+        Ok(success)
+    }
+
+    /// Deletes from the shard owning `id`.
+    ///
+    /// # Errors
+    /// Returns error if deletion fails.
+    pub async fn delete(&mut self, id: u32, collection: Option<String>) -> Result<bool, Status> {
+        self.shard_mut(id)?.delete(id, collection).await
+    }
+
+    /// Searches every shard and merges the results.
+    ///
+    /// # Errors
+    /// Returns error if any shard's search fails.
+    pub async fn search(
+        &mut self,
+        vector: Vec<f64>,
+        top_k: u32,
+        collection: Option<String>,
+    ) -> Result<Vec<SearchResult>, Status> {
+        self.search_request(SearchRequest {
+            vector,
+            top_k,
+            collection: collection.unwrap_or_default(),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Sends `req` to every shard concurrently and keeps the best `top_k`
+    /// hits. Filters and other settings apply on each shard as usual.
+    ///
+    /// # Errors
+    /// Returns error if any shard's search fails.
+    pub async fn search_request(
+        &mut self,
+        req: SearchRequest,
+    ) -> Result<Vec<SearchResult>, Status> {
+        let top_k = req.top_k as usize;
+        let mut searches = tokio::task::JoinSet::new();
+        for client in &self.clients {
+            let (mut inner, req) = (client.inner.clone(), req.clone());
+            searches.spawn(async move { inner.search(req).await });
+        }
+        let mut lists = Vec::with_capacity(self.clients.len());
+        while let Some(joined) = searches.join_next().await {
+            let resp = joined.map_err(|e| Status::internal(format!("Shard search failed: {e}")))?;
+            lists.push(resp?.into_inner().results);
+        }
+        Ok(merge(lists, top_k))
+    }
+
+    /// Adds a shard and moves the ids it takes over in `collections` to it:
+    /// each point is copied to the new shard, then deleted from its old one.
+    /// The collections must already exist on the new shard. Returns the
+    /// number of points moved.
+    ///
+    /// Pause writes while this runs. A write that reaches an old shard after
+    /// its id was copied is deleted with the original.
+    ///
+    /// # Errors
+    /// Returns `INVALID_ARGUMENT` if the name is already on the ring, or the
+    /// first failing call. The ring only changes once every point has moved;
+    /// on error the copies already made are harmless duplicates, since
+    /// searches drop repeated ids.
+    pub async fn add_shard(
+        &mut self,
+        name: String,
+        mut client: Client,
+        collections: &[String],
+    ) -> Result<u64, Status> {
+        if self.ring.shards().contains(&name) {
+            return Err(Status::invalid_argument(format!(
+                "Shard '{name}' is already on the ring"
+            )));
+        }
+        let next = self.ring.with_shard(name);
+        let target = self.clients.len();
+        let mut moved = 0;
+        for collection in collections {
+            for source in &mut self.clients {
+                let mut pull = source
+                    .sync_pull(collection.clone(), (0..SYNC_BUCKETS).collect())
+                    .await?;
+                let mut batch = Vec::new();
+                let mut ids = Vec::new();
+                while let Some(point) = pull.message().await? {
+                    if next.shard_for(point.id) != Some(target) {
+                        continue;
+                    }
+                    ids.push(point.id);
+                    batch.push((point.id, point.vector, point.metadata));
+                    if batch.len() == REBALANCE_BATCH {
+                        client
+                            .batch_insert(
+                                std::mem::take(&mut batch),
+                                Some(collection.clone()),
+                                DurabilityLevel::DefaultLevel,
+                            )
+                            .await?;
+                    }
+                }
+                if !batch.is_empty() {
+                    client
+                        .batch_insert(
+                            batch,
+                            Some(collection.clone()),
+                            DurabilityLevel::DefaultLevel,
+                        )
+                        .await?;
+                }
+                for &id in &ids {
+                    source.delete(id, Some(collection.clone())).await?;
+                }
+                moved += ids.len() as u64;
+            }
+        }
+        self.ring = next;
+        self.clients.push(client);
+        Ok(moved)
+    }
+}
+
+/// The best `top_k` hits of several distance-sorted lists. An id found on
+/// more than one shard (mid-rebalance) keeps its best distance.
+fn merge(lists: Vec<Vec<SearchResult>>, top_k: usize) -> Vec<SearchResult> {
+    let mut best: HashMap<u32, SearchResult> = HashMap::new();
+    for hit in lists.into_iter().flatten() {
+        match best.get(&hit.id) {
+            Some(kept) if kept.distance <= hit.distance => {}
+            _ => {
+                best.insert(hit.id, hit);
+            }
+        }
+    }
+    let mut hits: Vec<SearchResult> = best.into_values().collect();
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.id.cmp(&b.id)));
+    hits.truncate(top_k);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_a_shard_moves_only_its_share() {
+        let ring = HashRing::new(["a:50051", "b:50051", "c:50051"], DEFAULT_VNODES);
+        let mut counts = [0usize; 3];
+        for id in 0..30_000 {
+            counts[ring.shard_for(id).unwrap()] += 1;
+        }
+        assert!(
+            counts.iter().all(|&c| (7_000..13_000).contains(&c)),
+            "{counts:?}"
+        );
+
+        let next = ring.with_shard("d:50051");
+        let moves = ring.moves(&next, 0..30_000);
+        assert!(moves.iter().all(|m| m.to == 3));
+        assert!((5_000..10_000).contains(&moves.len()), "{}", moves.len());
+        assert_eq!(
+            HashRing::new(["a:50051", "b:50051", "c:50051"], DEFAULT_VNODES).shard_for(1234),
+            ring.shard_for(1234)
+        );
+        assert_eq!(HashRing::new(Vec::<String>::new(), 8).shard_for(1), None);
+    }
+
+    #[test]
+    fn merge_keeps_best_distance_per_id() {
+        let hit = |id, distance| SearchResult {
+            id,
+            distance,
+            ..Default::default()
+        };
+        let merged = merge(
+            vec![
+                vec![hit(1, 0.1), hit(2, 0.4)],
+                vec![hit(3, 0.2), hit(2, 0.3)],
+            ],
+            2,
+        );
+        let ids: Vec<(u32, f64)> = merged.iter().map(|h| (h.id, h.distance)).collect();
+        assert_eq!(ids, vec![(1, 0.1), (3, 0.2)]);
+        assert_eq!(
+            merge(vec![vec![hit(2, 0.4)], vec![hit(2, 0.3)]], 5)[0].distance,
+            0.3
+        );
+    }
+}
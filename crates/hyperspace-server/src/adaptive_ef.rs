@@ -0,0 +1,190 @@
+//! Load-adaptive `ef_search`.
+//!
+//! With `HS_ADAPTIVE_EF=true` each collection caps the `ef_search` of its
+//! searches by a value that follows load. Every `ADJUST_EVERY` the
+//! controller looks at how many searches are queued behind the search
+//! permits (`HS_SEARCH_CONCURRENCY`) and at the slowest search since the last
+//! look:
+//!
+//! - more than `HS_ADAPTIVE_EF_QUEUE` queued (default 0), or slower than
+//!   `HS_ADAPTIVE_EF_LATENCY_MS` (default 0, no latency ceiling): the cap
+//!   drops by a quarter, never below `HS_ADAPTIVE_EF_FLOOR` (default 32),
+//!   the smallest `ef_search` that still meets the recall you need;
+//! - nothing queued and under half the latency ceiling: the cap rises by an
+//!   eighth until it reaches `HS_HNSW_EF_SEARCH`, where searches run with the
+//!   `ef_search` they asked for again.
+//!
+//! Results of a search whose `ef_search` was lowered are not put in the
+//! search cache, so they are not served once the load is gone. The current
+//! cap is exported as `hyperspace_effective_ef_search`.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const ADJUST_EVERY: Duration = Duration::from_millis(100);
+const DEFAULT_FLOOR: usize = 32;
+
+pub struct AdaptiveEf {
+    floor: usize,
+    ceiling: usize,
+    queue_limit: usize,
+    latency_ceiling: Option<Duration>,
+    /// Searches allowed to run at once; the rest of `in_flight` is queued.
+    concurrency: usize,
+    current: AtomicUsize,
+    in_flight: AtomicUsize,
+    /// Slowest search since the last adjustment, in microseconds.
+    slowest_us: AtomicU64,
+    last_adjust: Mutex<Instant>,
+}
+
+/// One running search; records its latency when dropped.
+pub struct SearchGuard<'a> {
+    controller: &'a AdaptiveEf,
+    started: Instant,
+}
+
+impl Drop for SearchGuard<'_> {
+    fn drop(&mut self) {
+        self.controller.finished(self.started.elapsed());
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+impl AdaptiveEf {
+    /// Builds a controller from the `HS_ADAPTIVE_EF*` variables, or `None`
+    /// unless `HS_ADAPTIVE_EF=true`.
+    pub fn from_env(concurrency: usize) -> Option<Arc<Self>> {
+        if !std::env::var("HS_ADAPTIVE_EF").is_ok_and(|v| v.to_lowercase() == "true") {
+            return None;
+        }
+        let ceiling = env_or("HS_HNSW_EF_SEARCH", 100);
+        let latency_ms: u64 = env_or("HS_ADAPTIVE_EF_LATENCY_MS", 0);
+        Some(Arc::new(Self::new(
+            env_or("HS_ADAPTIVE_EF_FLOOR", DEFAULT_FLOOR).min(ceiling),
+            ceiling,
+            env_or("HS_ADAPTIVE_EF_QUEUE", 0),
+            (latency_ms > 0).then(|| Duration::from_millis(latency_ms)),
+            concurrency,
+        )))
+    }
+
+    pub fn new(
+        floor: usize,
+        ceiling: usize,
+        queue_limit: usize,
+        latency_ceiling: Option<Duration>,
+        concurrency: usize,
+    ) -> Self {
+        let floor = floor.max(1);
+        Self {
+            floor,
+            ceiling: ceiling.max(floor),
+            queue_limit,
+            latency_ceiling,
+            concurrency,
+            current: AtomicUsize::new(ceiling.max(floor)),
+            in_flight: AtomicUsize::new(0),
+            slowest_us: AtomicU64::new(0),
+            last_adjust: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Current cap on `ef_search`.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// `ef_search` to run a search that asked for `requested` with.
+    pub fn ef_search(&self, requested: usize) -> usize {
+        let current = self.current();
+        if current >= self.ceiling {
+            requested
+        } else {
+            requested.min(current)
+        }
+    }
+
+    /// Counts a search from now until the guard drops, queueing included.
+    pub fn begin(&self) -> SearchGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        SearchGuard {
+            controller: self,
+            started: Instant::now(),
+        }
+    }
+
+    fn finished(&self, latency: Duration) {
+        let queued = self
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed)
+            .saturating_sub(self.concurrency);
+        self.slowest_us
+            .fetch_max(latency.as_micros() as u64, Ordering::Relaxed);
+        let Some(mut last) = self.last_adjust.try_lock() else {
+            return;
+        };
+        if last.elapsed() < ADJUST_EVERY {
+            return;
+        }
+        *last = Instant::now();
+        let slowest = Duration::from_micros(self.slowest_us.swap(0, Ordering::Relaxed));
+        self.adjust(queued, slowest);
+    }
+
+    fn adjust(&self, queued: usize, slowest: Duration) {
+        let current = self.current();
+        let too_slow = self.latency_ceiling.is_some_and(|limit| slowest > limit);
+        let next = if queued > self.queue_limit || too_slow {
+            (current - current / 4).max(self.floor)
+        } else if queued == 0 && self.latency_ceiling.is_none_or(|limit| slowest < limit / 2) {
+            (current + (current / 8).max(1)).min(self.ceiling)
+        } else {
+            current
+        };
+        self.current.store(next, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowers_under_load_and_recovers_when_idle() {
+        let ef = AdaptiveEf::new(20, 100, 2, Some(Duration::from_millis(50)), 4);
+        assert_eq!(ef.ef_search(400), 400);
+
+        ef.adjust(3, Duration::from_millis(5));
+        assert_eq!(ef.current(), 75);
+        assert_eq!(ef.ef_search(400), 75);
+        assert_eq!(ef.ef_search(10), 10);
+
+        for _ in 0..20 {
+            ef.adjust(10, Duration::from_millis(5));
+        }
+        assert_eq!(ef.current(), 20);
+
+        ef.adjust(0, Duration::from_millis(10));
+        assert_eq!(ef.current(), 22);
+        // A short queue holds; an empty one over the latency ceiling backs off.
+        ef.adjust(1, Duration::from_millis(10));
+        assert_eq!(ef.current(), 22);
+        ef.adjust(0, Duration::from_millis(80));
+        assert_eq!(ef.current(), 20);
+
+        for _ in 0..30 {
+            ef.adjust(0, Duration::from_millis(1));
+        }
+        assert_eq!(ef.current(), 100);
+        assert_eq!(ef.ef_search(400), 400);
+    }
+}
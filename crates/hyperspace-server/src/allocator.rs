@@ -0,0 +1,83 @@
+//! jemalloc statistics and purging.
+//!
+//! Freed pages stay dirty in jemalloc's arenas and are handed back to the OS
+//! only as they decay, so a vacuum that drops a whole index barely moves the
+//! resident size for a while. [`purge`] releases them at once; [`stats`]
+//! shows how much the allocator holds beyond what is live.
+
+use tikv_jemalloc_ctl::{epoch, stats};
+
+/// `MALLCTL_ARENAS_ALL`: an arena index that addresses every arena.
+const ALL_ARENAS: &[u8] = b"arena.4096.purge\0";
+
+/// Allocator byte counts, refreshed on every call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AllocatorStats {
+    /// Bytes handed out to the application.
+    pub allocated: usize,
+    /// Bytes in pages with live allocations.
+    pub active: usize,
+    /// Bytes physically resident in allocator-owned pages, metadata included.
+    pub resident: usize,
+    /// Bytes unmapped from the OS but kept as reserved address space.
+    pub retained: usize,
+}
+
+impl AllocatorStats {
+    /// Share of active pages not covered by live allocations.
+    pub fn fragmentation(&self) -> f64 {
+        if self.active == 0 {
+            return 0.0;
+        }
+        self.active.saturating_sub(self.allocated) as f64 / self.active as f64
+    }
+}
+
+/// Current statistics, or `None` if jemalloc does not report them.
+pub fn stats() -> Option<AllocatorStats> {
+    epoch::advance().ok()?;
+    Some(AllocatorStats {
+        allocated: stats::allocated::read().ok()?,
+        active: stats::active::read().ok()?,
+        resident: stats::resident::read().ok()?,
+        retained: stats::retained::read().ok()?,
+    })
+}
+
+/// Returns the dirty pages of every arena to the OS.
+pub fn purge() -> Result<(), String> {
+    // `arena.<i>.purge` takes no value: jemalloc rejects any non-null
+    // old/new pointer, which is what the typed `raw` helpers pass.
+    // SAFETY: the name is NUL-terminated and all value pointers are null.
+    let code = unsafe {
+        tikv_jemalloc_sys::mallctl(
+            ALL_ARENAS.as_ptr().cast(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(format!("arena purge failed with code {code}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn purge_returns_dirty_pages() {
+        let before = stats().unwrap();
+        assert!(before.resident >= before.active);
+        assert!(before.active >= before.allocated);
+
+        drop(vec![1u8; 64 << 20]);
+        purge().unwrap();
+        let after = stats().unwrap();
+        assert!((0.0..1.0).contains(&after.fragmentation()));
+    }
+}
@@ -0,0 +1,228 @@
+//! Background anti-entropy repair for followers.
+//!
+//! The replication stream is best effort: a follower that was down longer
+//! than the leader's journal retains, or that missed an entry to a bug,
+//! drifts silently. Every `HS_ANTI_ENTROPY_SEC` this task compares the Merkle
+//! buckets of each resident collection with the leader (`SyncHandshake`),
+//! pulls the divergent buckets (`SyncPull`) and makes the local bucket match:
+//! leader points are upserted, local points the leader no longer has are
+//! deleted.
+//!
+//! Bucket hashes are only maintained with `HS_GOSSIP_ENABLED=true`, and only
+//! compare equal across nodes when vectors round-trip losslessly
+//! (`HS_QUANTIZATION_LEVEL=none`).
+
+use crate::manager::CollectionManager;
+use hyperspace_core::{Collection, Durability, HyperspaceResult};
+use hyperspace_proto::hyperspace::database_client::DatabaseClient;
+use hyperspace_proto::hyperspace::{SyncHandshakeRequest, SyncPullRequest, SyncVectorData};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Weak;
+use std::time::Duration;
+use tokio::sync::watch;
+use tonic::transport::Channel;
+use tonic::Request;
+
+static ROUNDS: AtomicU64 = AtomicU64::new(0);
+static BUCKETS_REPAIRED: AtomicU64 = AtomicU64::new(0);
+static POINTS_UPSERTED: AtomicU64 = AtomicU64::new(0);
+static POINTS_DELETED: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide repair counters since startup.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepairStats {
+    pub rounds: u64,
+    pub buckets_repaired: u64,
+    pub points_upserted: u64,
+    pub points_deleted: u64,
+}
+
+pub fn stats() -> RepairStats {
+    RepairStats {
+        rounds: ROUNDS.load(Ordering::Relaxed),
+        buckets_repaired: BUCKETS_REPAIRED.load(Ordering::Relaxed),
+        points_upserted: POINTS_UPSERTED.load(Ordering::Relaxed),
+        points_deleted: POINTS_DELETED.load(Ordering::Relaxed),
+    }
+}
+
+/// Repair interval from `HS_ANTI_ENTROPY_SEC` (default 300); `None` when `0`.
+pub fn interval_from_env() -> Option<Duration> {
+    let secs = std::env::var("HS_ANTI_ENTROPY_SEC")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(300);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Makes bucket `bucket` of `col` hold exactly `remote`.
+///
+/// `expected_hash` is the local bucket hash the diff was computed against. If
+/// replication touched the bucket since, the pulled points may be older than
+/// what was just applied, so the bucket is left for the next round and `None`
+/// is returned. Otherwise returns `(upserted, deleted)`.
+pub async fn repair_bucket(
+    col: &dyn Collection,
+    bucket: u32,
+    expected_hash: u64,
+    remote: Vec<SyncVectorData>,
+    clock: u64,
+) -> HyperspaceResult<Option<(usize, usize)>> {
+    if col.buckets().get(bucket as usize) != Some(&expected_hash) {
+        return Ok(None);
+    }
+
+    let remote_ids: HashSet<u32> = remote.iter().map(|p| p.id).collect();
+    let mut deleted = 0;
+    for (id, _, _) in col.peek_buckets(&[bucket]) {
+        if !remote_ids.contains(&id) {
+            col.delete(id, clock).await?;
+            deleted += 1;
+        }
+    }
+
+    let mut upserted = 0;
+    for point in remote {
+        col.insert(
+            &point.vector,
+            point.id,
+            point.metadata,
+            clock,
+            Durability::Batch,
+        )
+        .await?;
+        upserted += 1;
+    }
+    Ok(Some((upserted, deleted)))
+}
+
+/// Wraps `message` with the API key and tenant headers the peer expects.
+pub(crate) fn request<T>(message: T, api_key: &str, user_id: &str) -> Request<T> {
+    let mut req = Request::new(message);
+    if let Ok(key) = api_key.parse() {
+        req.metadata_mut().insert("x-api-key", key);
+    }
+    if let Ok(uid) = user_id.parse() {
+        req.metadata_mut().insert("x-hyperspace-user-id", uid);
+    }
+    req
+}
+
+/// Compares one collection with the leader and repairs divergent buckets.
+/// Returns the number of buckets repaired.
+async fn repair_collection(
+    client: &mut DatabaseClient<Channel>,
+    api_key: &str,
+    internal_name: &str,
+    col: &dyn Collection,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    // The leader resolves `{user}_{collection}`, so splitting the internal
+    // name at any underscore addresses the same collection.
+    let (user_id, name) = internal_name
+        .split_once('_')
+        .unwrap_or(("default_admin", internal_name));
+
+    let local_buckets = col.buckets();
+    let handshake = client
+        .sync_handshake(request(
+            SyncHandshakeRequest {
+                collection: name.to_string(),
+                client_buckets: local_buckets.clone(),
+                client_logical_clock: 0,
+                client_count: col.count() as u64,
+            },
+            api_key,
+            user_id,
+        ))
+        .await?
+        .into_inner();
+    if handshake.in_sync {
+        return Ok(0);
+    }
+
+    let buckets: Vec<u32> = handshake
+        .diff_buckets
+        .iter()
+        .map(|d| d.bucket_index)
+        .collect();
+    let mut stream = client
+        .sync_pull(request(
+            SyncPullRequest {
+                collection: name.to_string(),
+                bucket_indices: buckets.clone(),
+            },
+            api_key,
+            user_id,
+        ))
+        .await?
+        .into_inner();
+    let mut pulled: HashMap<u32, Vec<SyncVectorData>> = HashMap::new();
+    while let Some(point) = stream.message().await? {
+        pulled.entry(point.bucket_index).or_default().push(point);
+    }
+
+    let mut repaired = 0;
+    for bucket in buckets {
+        let expected = local_buckets[bucket as usize];
+        let remote = pulled.remove(&bucket).unwrap_or_default();
+        let outcome = repair_bucket(
+            col,
+            bucket,
+            expected,
+            remote,
+            handshake.server_logical_clock,
+        )
+        .await?;
+        if let Some((upserted, deleted)) = outcome {
+            repaired += 1;
+            POINTS_UPSERTED.fetch_add(upserted as u64, Ordering::Relaxed);
+            POINTS_DELETED.fetch_add(deleted as u64, Ordering::Relaxed);
+        }
+    }
+    BUCKETS_REPAIRED.fetch_add(repaired as u64, Ordering::Relaxed);
+    Ok(repaired)
+}
+
+/// Spawns the periodic repair loop against whichever leader `upstream`
+/// names; rounds are skipped while this node leads. Only collections that
+/// are resident in memory are checked, so repair never wakes idle ones.
+pub fn spawn(
+    manager: Weak<CollectionManager>,
+    upstream: watch::Receiver<Option<String>>,
+    api_key: String,
+    every: Duration,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let Some(mgr) = manager.upgrade() else {
+                break;
+            };
+            let Some(leader) = upstream.borrow().clone() else {
+                continue;
+            };
+            let mut client = match DatabaseClient::connect(leader.clone()).await {
+                Ok(client) => client,
+                Err(e) => {
+                    eprintln!("⚠️ Anti-entropy: cannot reach leader {leader}: {e}");
+                    continue;
+                }
+            };
+
+            for internal_name in mgr.list_all() {
+                let Some(col) = mgr.get_internal(&internal_name).await else {
+                    continue;
+                };
+                match repair_collection(&mut client, &api_key, &internal_name, col.as_ref()).await {
+                    Ok(0) => {}
+                    Ok(n) => println!("🩹 Anti-entropy: repaired {n} buckets in '{internal_name}'"),
+                    Err(e) => eprintln!("⚠️ Anti-entropy: '{internal_name}' failed: {e}"),
+                }
+            }
+            ROUNDS.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+}
@@ -0,0 +1,166 @@
+//! Read-only API keys and the scope each route needs.
+//!
+//! `HYPERSPACE_API_KEY` grants everything. `HYPERSPACE_READ_API_KEY`, if also
+//! set, is a second key limited to routes and gRPC methods that only read:
+//! listing, stats, search, graph navigation and sync pulls. Anything that
+//! changes data or server state needs the full key. Methods not listed here
+//! need the full key, so a new RPC is never readable by accident.
+
+use sha2::{Digest, Sha256};
+
+/// What a route or gRPC method needs from the caller's key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Reads data or state; the read-only key is enough.
+    Read,
+    /// Changes data or server state; needs `HYPERSPACE_API_KEY`.
+    Admin,
+}
+
+/// Path of the gRPC method being called, e.g. `/hyperspace.Database/Search`.
+/// Tonic interceptors do not see the URI, so a layer in front of the service
+/// puts it in the request extensions.
+#[derive(Debug, Clone)]
+pub struct GrpcPath(pub String);
+
+pub fn hash_key(key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Hash of `HYPERSPACE_READ_API_KEY`, if set.
+pub fn read_key_hash() -> Option<String> {
+    std::env::var("HYPERSPACE_READ_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(|key| hash_key(&key))
+}
+
+/// Records the method path for [`grpc_scope`].
+pub fn tag_grpc_path<B>(
+    mut request: tonic::codegen::http::Request<B>,
+) -> tonic::codegen::http::Request<B> {
+    let path = GrpcPath(request.uri().path().to_string());
+    request.extensions_mut().insert(path);
+    request
+}
+
+/// Scope of a gRPC method, by its path or bare name.
+pub fn grpc_scope(path: &str) -> Scope {
+    let method = path.rsplit('/').next().unwrap_or_default();
+    match method {
+        "ListCollections"
+        | "GetCollectionStats"
+        | "ListNamespace"
+        | "GetNamespaceStats"
+        | "Vectorize"
+        | "SearchText"
+        | "Search"
+        | "SearchBatch"
+        | "SearchMultiCollection"
+        | "MultiSearch"
+        | "RunQueryTemplate"
+        | "ListQueryTemplates"
+        | "GetExperiment"
+        | "ListCollectionSpecs"
+        | "GetNode"
+        | "GetNeighbors"
+        | "GetConceptParents"
+        | "Traverse"
+        | "FindSemanticClusters"
+        | "Monitor"
+        | "WatchIndexingProgress"
+        | "ListTrash"
+        | "GetDigest"
+        | "GetTopology"
+        | "SyncHandshake"
+        | "SyncPull" => Scope::Read,
+        _ => Scope::Admin,
+    }
+}
+
+/// Scope of an HTTP route. `GET` is a read outside `/api/admin`; a `POST`
+/// is a read only for the query-style routes that take a JSON body.
+pub fn http_scope(method: &axum::http::Method, path: &str) -> Scope {
+    use axum::http::Method;
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    if segments.starts_with(&["api", "admin"]) {
+        return Scope::Admin;
+    }
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Scope::Read;
+    }
+    if *method != Method::POST {
+        return Scope::Admin;
+    }
+    match segments.as_slice() {
+        ["api", "analyze", "geometry"]
+        | ["api", "collections", _, "search" | "query"]
+        | ["api", "collections", _, "queries", _, "run"]
+        | ["api", "collections", _, "graph", "traverse" | "clusters"]
+        | ["api", "collections", _, "sync", "handshake" | "pull"] => Scope::Read,
+        _ => Scope::Admin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Method;
+
+    #[test]
+    fn classifies_http_routes() {
+        let read = [
+            (Method::GET, "/api/collections"),
+            (Method::GET, "/api/collections/docs/graph/node"),
+            (Method::GET, "/api/collections/docs/graph/export"),
+            (Method::POST, "/api/collections/docs/graph/traverse"),
+            (Method::POST, "/api/collections/docs/graph/clusters"),
+            (Method::POST, "/api/collections/docs/search"),
+            (Method::POST, "/api/collections/docs/queries/top/run"),
+            (Method::POST, "/api/analyze/geometry"),
+            (Method::GET, "/metrics"),
+        ];
+        for (method, path) in read {
+            assert_eq!(http_scope(&method, path), Scope::Read, "{method} {path}");
+        }
+        let admin = [
+            (Method::POST, "/api/collections"),
+            (Method::POST, "/api/collections/docs/insert"),
+            (Method::POST, "/api/collections/search"),
+            (Method::DELETE, "/api/collections/docs"),
+            (Method::PUT, "/api/collections/docs/queries/top"),
+            (Method::POST, "/api/collections/docs/snapshot"),
+            (Method::POST, "/api/collections/docs/purge"),
+            (Method::GET, "/api/admin/usage"),
+            (Method::GET, "/api/admin/telemetry"),
+            (Method::PUT, "/api/specs"),
+        ];
+        for (method, path) in admin {
+            assert_eq!(http_scope(&method, path), Scope::Admin, "{method} {path}");
+        }
+    }
+
+    #[test]
+    fn classifies_grpc_methods() {
+        assert_eq!(grpc_scope("/hyperspace.Database/Search"), Scope::Read);
+        assert_eq!(grpc_scope("/hyperspace.Database/MultiSearch"), Scope::Read);
+        assert_eq!(grpc_scope("/hyperspace.Database/Traverse"), Scope::Read);
+        assert_eq!(grpc_scope("/hyperspace.Database/GetTopology"), Scope::Read);
+        assert_eq!(grpc_scope("/hyperspace.Database/Insert"), Scope::Admin);
+        assert_eq!(grpc_scope("/hyperspace.Database/Replicate"), Scope::Admin);
+        assert_eq!(grpc_scope("/hyperspace.Database/Purge"), Scope::Admin);
+        assert_eq!(grpc_scope("/hyperspace.Database/PullWal"), Scope::Admin);
+        assert_eq!(
+            grpc_scope("/hyperspace.Database/ImportCollection"),
+            Scope::Admin
+        );
+        assert_eq!(
+            grpc_scope("/hyperspace.Database/RollbackCollection"),
+            Scope::Admin
+        );
+        assert_eq!(grpc_scope("/hyperspace.Database/NewMethod"), Scope::Admin);
+        assert_eq!(grpc_scope(""), Scope::Admin);
+    }
+}
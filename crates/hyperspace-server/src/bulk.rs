@@ -0,0 +1,212 @@
+//! NDJSON bulk ingestion for `POST /api/collections/{name}/bulk`.
+//!
+//! Each request line is one `{"id", "vector", "metadata"}` object. Lines are
+//! parsed and validated as they arrive, grouped into `insert_batch` calls, and
+//! the outcome is streamed back as NDJSON events: one `error` per rejected
+//! line, one `progress` per committed batch and a final `done`.
+
+use hyperspace_core::{Collection, Durability};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+pub const MAX_BATCH_SIZE: usize = 10_000;
+
+#[derive(Deserialize)]
+pub struct BulkLine {
+    pub id: u32,
+    pub vector: Vec<f64>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkEvent {
+    /// A single line was malformed or failed validation.
+    Error { line: usize, error: String },
+    /// A whole batch was rejected by the collection (e.g. a resource limit).
+    BatchError {
+        first_line: usize,
+        last_line: usize,
+        error: String,
+    },
+    /// The request body could not be read to the end.
+    Aborted { error: String },
+    Progress {
+        lines: usize,
+        inserted: usize,
+        failed: usize,
+    },
+    Done {
+        lines: usize,
+        inserted: usize,
+        failed: usize,
+    },
+}
+
+impl BulkEvent {
+    pub fn to_ndjson(&self) -> String {
+        let mut s = serde_json::to_string(self).unwrap_or_default();
+        s.push('\n');
+        s
+    }
+}
+
+/// Splits a byte stream into lines and feeds them to the collection in batches.
+pub struct BulkIngest {
+    col: Arc<dyn Collection>,
+    clock: u64,
+    batch_size: usize,
+    // Bytes after the last newline of the previous chunk
+    partial: Vec<u8>,
+    batch: Vec<(Vec<f64>, u32, HashMap<String, String>)>,
+    batch_first_line: usize,
+    batch_last_line: usize,
+    lines: usize,
+    inserted: usize,
+    failed: usize,
+}
+
+impl BulkIngest {
+    pub fn new(col: Arc<dyn Collection>, clock: u64, batch_size: usize) -> Self {
+        let batch_size = batch_size.clamp(1, MAX_BATCH_SIZE);
+        Self {
+            col,
+            clock,
+            batch_size,
+            partial: Vec::new(),
+            batch: Vec::with_capacity(batch_size),
+            batch_first_line: 0,
+            batch_last_line: 0,
+            lines: 0,
+            inserted: 0,
+            failed: 0,
+        }
+    }
+
+    /// Consumes one body chunk; returns the events it produced.
+    pub async fn feed(&mut self, chunk: &[u8]) -> Vec<BulkEvent> {
+        let mut events = Vec::new();
+        self.partial.extend_from_slice(chunk);
+        let buf = std::mem::take(&mut self.partial);
+        let mut start = 0;
+        while let Some(pos) = buf[start..].iter().position(|&b| b == b'\n') {
+            self.line(&buf[start..start + pos], &mut events).await;
+            start += pos + 1;
+        }
+        self.partial = buf[start..].to_vec();
+        events
+    }
+
+    /// Adds one point parsed elsewhere (e.g. a CSV row) as the next line.
+    pub async fn push(&mut self, point: Result<BulkLine, String>) -> Vec<BulkEvent> {
+        let mut events = Vec::new();
+        self.lines += 1;
+        self.add(point, &mut events).await;
+        events
+    }
+
+    /// Drops an incomplete trailing line, e.g. after the body stream failed.
+    pub fn discard_partial(&mut self) {
+        self.partial.clear();
+    }
+
+    /// Processes any trailing line without a newline and commits the last batch.
+    pub async fn finish(mut self) -> Vec<BulkEvent> {
+        let mut events = Vec::new();
+        let rest = std::mem::take(&mut self.partial);
+        if !rest.is_empty() {
+            self.line(&rest, &mut events).await;
+        }
+        self.flush(&mut events).await;
+        events.push(BulkEvent::Done {
+            lines: self.lines,
+            inserted: self.inserted,
+            failed: self.failed,
+        });
+        events
+    }
+
+    async fn line(&mut self, raw: &[u8], events: &mut Vec<BulkEvent>) {
+        // Line numbers are 1-based and count blank lines, matching the client's file.
+        self.lines += 1;
+        let raw = raw.trim_ascii();
+        if raw.is_empty() {
+            return;
+        }
+
+        let parsed = serde_json::from_slice::<BulkLine>(raw).map_err(|e| e.to_string());
+        self.add(parsed, events).await;
+    }
+
+    /// Validates a point and queues it under the current line number.
+    async fn add(&mut self, point: Result<BulkLine, String>, events: &mut Vec<BulkEvent>) {
+        let line_no = self.lines;
+        let parsed = point
+            .and_then(|l| {
+                hyperspace_core::check_vector(
+                    &l.vector,
+                    self.col.dimension(),
+                    self.col.metric_name(),
+                )
+                .map(|()| l)
+                .map_err(|v| v.to_string())
+            })
+            .and_then(|mut l| match self.col.metadata_schema() {
+                Some(schema) => schema
+                    .check(&mut l.metadata)
+                    .map(|_| l)
+                    .map_err(|v| v.to_string()),
+                None => Ok(l),
+            });
+        match parsed {
+            Ok(l) => {
+                if self.batch.is_empty() {
+                    self.batch_first_line = line_no;
+                }
+                self.batch_last_line = line_no;
+                self.batch.push((l.vector, l.id, l.metadata));
+                if self.batch.len() >= self.batch_size {
+                    self.flush(events).await;
+                }
+            }
+            Err(error) => {
+                self.failed += 1;
+                events.push(BulkEvent::Error {
+                    line: line_no,
+                    error,
+                });
+            }
+        }
+    }
+
+    async fn flush(&mut self, events: &mut Vec<BulkEvent>) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+        let n = batch.len();
+        match self
+            .col
+            .insert_batch(batch, self.clock, Durability::Default)
+            .await
+        {
+            Ok(()) => self.inserted += n,
+            Err(e) => {
+                self.failed += n;
+                events.push(BulkEvent::BatchError {
+                    first_line: self.batch_first_line,
+                    last_line: self.batch_last_line,
+                    error: e.to_string(),
+                });
+            }
+        }
+        events.push(BulkEvent::Progress {
+            lines: self.lines,
+            inserted: self.inserted,
+            failed: self.failed,
+        });
+    }
+}
@@ -0,0 +1,254 @@
+//! Capacity planning (`GET /api/capacity`).
+//!
+//! Estimates the RAM and disk a collection of `count` vectors needs before it
+//! exists. Vector slots, the graph and the snapshot use the layouts the
+//! server allocates; metadata and id maps use per-point constants measured on
+//! the current structures. Figures are upper bounds for a freshly built
+//! collection: HNSW neighbor lists are assumed full, and the WAL is assumed
+//! to hold one insert record per point.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult, QuantizationMode};
+use hyperspace_store::wal::Wal;
+use hyperspace_store::VectorStore;
+
+/// Forward-store row per point with metadata: its `DashMap` slot plus the
+/// boxed field and value runs.
+const FORWARD_ROW_BYTES: u64 = 48;
+/// Per metadata key: a `(key id, value end)` field and one id in a roaring
+/// array container of the tag bitmap.
+const METADATA_KEY_BYTES: u64 = 8 + 2;
+/// Both `u32 <-> u32` id maps at hashbrown's typical load factor.
+const ID_MAP_BYTES: u64 = 2 * 12;
+
+#[derive(serde::Deserialize)]
+pub struct CapacityQuery {
+    pub dimension: usize,
+    pub count: usize,
+    /// `none`, `scalar` or `binary`; defaults to `HS_QUANTIZATION_LEVEL`.
+    pub quantization: Option<String>,
+    /// Defaults to `HS_METRIC`.
+    pub metric: Option<String>,
+    /// Defaults to `HS_HNSW_M`.
+    pub m: Option<usize>,
+    /// f32 storage for unquantized vectors; defaults to `HS_STORAGE_FLOAT32`.
+    pub float32: Option<bool>,
+    /// Average metadata keys per point.
+    #[serde(default)]
+    pub metadata_keys: usize,
+    /// Average bytes of metadata keys and values per point.
+    #[serde(default)]
+    pub metadata_bytes: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct CapacityEstimate {
+    pub dimension: usize,
+    pub count: usize,
+    pub quantization: &'static str,
+    pub metric: String,
+    pub m: usize,
+    pub float32: bool,
+    /// Bytes of one stored vector.
+    pub vector_bytes: usize,
+    pub ram: RamEstimate,
+    pub disk: DiskEstimate,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RamEstimate {
+    /// Vector segments; memory-mapped, so the page cache holds them when hot.
+    pub vectors: u64,
+    pub graph: u64,
+    pub metadata: u64,
+    pub id_maps: u64,
+    pub total: u64,
+    /// While a snapshot is serialized next to the live index.
+    pub peak_during_snapshot: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DiskEstimate {
+    /// Vector segments and their checksum files.
+    pub vectors: u64,
+    pub index_snapshot: u64,
+    pub wal: u64,
+    pub total: u64,
+}
+
+fn round_up(bytes: usize, align: usize) -> usize {
+    bytes.div_ceil(align) * align
+}
+
+/// Size of one stored vector, matching the `repr(C, align(64))` layouts in
+/// `hyperspace_core::vector`.
+fn vector_bytes(dimension: usize, mode: QuantizationMode, float32: bool) -> usize {
+    match mode {
+        QuantizationMode::ScalarI8 => round_up(round_up(dimension, 4) + 4, 64),
+        QuantizationMode::Binary => hyperspace_core::vector::BinaryHyperVector::<1>::SIZE,
+        QuantizationMode::None if float32 => round_up(4 * (dimension + 1), 64),
+        QuantizationMode::None => round_up(8 * (dimension + 1), 64),
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+pub fn estimate(query: &CapacityQuery) -> HyperspaceResult<CapacityEstimate> {
+    if query.dimension == 0 {
+        return Err(HyperspaceError::Validation(
+            "dimension must be positive".into(),
+        ));
+    }
+    let quantization = query
+        .quantization
+        .clone()
+        .unwrap_or_else(|| env_or("HS_QUANTIZATION_LEVEL", "scalar".to_string()));
+    let mode = match quantization.as_str() {
+        "none" => QuantizationMode::None,
+        "scalar" => QuantizationMode::ScalarI8,
+        "binary" => QuantizationMode::Binary,
+        other => {
+            return Err(HyperspaceError::Validation(format!(
+                "Unknown quantization '{other}' (expected none, scalar or binary)"
+            )))
+        }
+    };
+    let metric = query
+        .metric
+        .clone()
+        .unwrap_or_else(|| env_or("HS_METRIC", "l2".to_string()));
+    if !matches!(
+        metric.as_str(),
+        "poincare" | "l2" | "euclidean" | "cosine" | "lorentz"
+    ) {
+        return Err(HyperspaceError::Validation(format!(
+            "Unknown metric '{metric}'"
+        )));
+    }
+    let m = query.m.unwrap_or_else(|| env_or("HS_HNSW_M", 16));
+    if m < 2 {
+        return Err(HyperspaceError::Validation("m must be at least 2".into()));
+    }
+    let float32 = mode == QuantizationMode::None
+        && query.float32.unwrap_or_else(|| {
+            std::env::var("HS_STORAGE_FLOAT32")
+                .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        });
+
+    let count = query.count;
+    let points = count as u64;
+    let element = vector_bytes(query.dimension, mode, float32);
+    let has_metadata = query.metadata_keys > 0 || query.metadata_bytes > 0;
+
+    let vectors = VectorStore::segment_bytes_for(count, element);
+    let graph = points * hyperspace_index::graph_bytes_per_node(m) as u64;
+    let metadata = if has_metadata {
+        points
+            * (FORWARD_ROW_BYTES
+                + METADATA_KEY_BYTES * query.metadata_keys as u64
+                + query.metadata_bytes as u64)
+    } else {
+        0
+    };
+    let id_maps = points * ID_MAP_BYTES;
+    let ram_total = vectors + graph + metadata + id_maps;
+
+    let index_snapshot = points
+        * (hyperspace_index::snapshot_bytes_per_node(m) as u64
+            + METADATA_KEY_BYTES * query.metadata_keys as u64
+            + query.metadata_bytes as u64);
+    let disk_vectors = VectorStore::disk_bytes_for(count, element);
+    let wal = points
+        * Wal::insert_record_bytes(query.dimension, query.metadata_keys, query.metadata_bytes);
+
+    Ok(CapacityEstimate {
+        dimension: query.dimension,
+        count,
+        quantization: crate::collection_spec::quantization_name(mode),
+        metric,
+        m,
+        float32,
+        vector_bytes: element,
+        ram: RamEstimate {
+            vectors,
+            graph,
+            metadata,
+            id_maps,
+            total: ram_total,
+            peak_during_snapshot: ram_total + index_snapshot,
+        },
+        disk: DiskEstimate {
+            vectors: disk_vectors,
+            index_snapshot,
+            wal,
+            total: disk_vectors + index_snapshot + wal,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_core::vector::{HyperVector, HyperVectorF32, QuantizedHyperVector};
+
+    fn query(dimension: usize, count: usize, quantization: &str) -> CapacityQuery {
+        CapacityQuery {
+            dimension,
+            count,
+            quantization: Some(quantization.into()),
+            metric: Some("l2".into()),
+            m: Some(16),
+            float32: Some(false),
+            metadata_keys: 0,
+            metadata_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn vector_bytes_match_stored_layouts() {
+        use QuantizationMode::{Binary, None, ScalarI8};
+        assert_eq!(vector_bytes(7, None, false), HyperVector::<7>::SIZE);
+        assert_eq!(vector_bytes(768, None, false), HyperVector::<768>::SIZE);
+        assert_eq!(vector_bytes(9, None, true), HyperVectorF32::<9>::SIZE);
+        assert_eq!(vector_bytes(1536, None, true), HyperVectorF32::<1536>::SIZE);
+        assert_eq!(
+            vector_bytes(5, ScalarI8, false),
+            QuantizedHyperVector::<5>::SIZE
+        );
+        assert_eq!(
+            vector_bytes(61, ScalarI8, false),
+            QuantizedHyperVector::<61>::SIZE
+        );
+        assert_eq!(
+            vector_bytes(768, ScalarI8, false),
+            QuantizedHyperVector::<768>::SIZE
+        );
+        assert_eq!(
+            vector_bytes(1024, Binary, false),
+            hyperspace_core::vector::BinaryHyperVector::<1024>::SIZE
+        );
+    }
+
+    #[test]
+    fn estimate_scales_with_count_and_quantization() {
+        let small = estimate(&query(768, 1_000_000, "scalar")).unwrap();
+        let large = estimate(&query(768, 50_000_000, "scalar")).unwrap();
+        assert_eq!(small.vector_bytes, 832);
+        assert_eq!(large.ram.graph, 50 * small.ram.graph);
+        assert!(large.ram.total > 40 * 1_000_000_000);
+        assert!(large.ram.peak_during_snapshot > large.ram.total);
+        assert_eq!(
+            large.disk.total,
+            large.disk.vectors + large.disk.index_snapshot + large.disk.wal
+        );
+
+        let full = estimate(&query(768, 1_000_000, "none")).unwrap();
+        assert!(full.ram.vectors > 7 * small.ram.vectors);
+        assert!(estimate(&query(768, 10, "fp4")).is_err());
+        assert!(estimate(&query(0, 10, "scalar")).is_err());
+    }
+}
@@ -13,8 +13,8 @@
 //!   (Task 1.3).
 //!
 //! ## Thread Safety
-//! All operations are `Send + Sync`. Chunk loading happens inside `spawn_blocking`
-//! (or Rayon) to avoid blocking the async executor.
+//! All operations are `Send + Sync`. Chunks are loaded and searched on the
+//! search pool to avoid blocking the async executor.
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -94,6 +94,9 @@ pub fn search_chunk<const N: usize, M: Metric<N>>(
         use_wasserstein,
         bm25_options: None,
         fusion_method: None,
+        text_match: hyperspace_core::TextMatch::Any,
+        trace: None,
+        num_probes: 0,
     };
 
     let results = chunk_index.search(query, filters, complex_filters, &params);
@@ -101,7 +104,8 @@ pub fn search_chunk<const N: usize, M: Metric<N>>(
     Ok(results)
 }
 
-/// Searches multiple chunks in parallel using tokio (P1: Parallel chunk search).
+/// Searches multiple chunks in parallel (P1: Parallel chunk search) on the
+/// caller's rayon pool, i.e. the search pool of `crate::pools`.
 ///
 /// # Parameters
 /// - `chunk_dirs`: Paths to chunk directories to search.
@@ -117,7 +121,7 @@ pub fn search_chunk<const N: usize, M: Metric<N>>(
 /// Note: IDs are chunk-local and cannot be used for metadata lookups in the main index.
 /// The caller should use only the distances for ranking merge.
 #[allow(clippy::too_many_arguments)]
-pub async fn scatter_gather_search_async<const N: usize, M: Metric<N> + Send + Sync + 'static>(
+pub fn scatter_gather_search<const N: usize, M: Metric<N> + Send + Sync + 'static>(
     chunk_dirs: &[std::path::PathBuf],
     query: &[f64],
     k: usize,
@@ -128,83 +132,34 @@ pub async fn scatter_gather_search_async<const N: usize, M: Metric<N> + Send + S
     config: &Arc<GlobalConfig>,
     use_wasserstein: bool,
 ) -> Vec<(u32, f64, usize)> {
-    // P1: Parallel chunk search - use tokio to search chunks concurrently
-    // Chunk loading is mmap-based, so parallel loading is safe and efficient
-    let chunk_futures: Vec<_> = chunk_dirs
-        .iter()
-        .map(|dir| {
-            let dir = dir.clone();
-            let query = query.to_vec();
-            let filters = filters.clone();
-            let complex_filters = complex_filters.to_vec();
-            let config = config.clone();
+    use rayon::prelude::*;
 
-            tokio::task::spawn_blocking(move || {
-                search_chunk::<N, M>(
-                    &dir,
-                    &query,
-                    k,
-                    ef_search,
-                    &filters,
-                    &complex_filters,
-                    mode,
-                    &config,
-                    use_wasserstein,
-                )
-            })
+    let mut all_results: Vec<(u32, f64, usize)> = chunk_dirs
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(chunk_idx, dir)| {
+            let results = search_chunk::<N, M>(
+                dir,
+                query,
+                k,
+                ef_search,
+                filters,
+                complex_filters,
+                mode,
+                config,
+                use_wasserstein,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️ ChunkSearcher: Failed to search chunk: {e}");
+                Vec::new()
+            });
+            results
+                .into_iter()
+                .map(move |(local_id, dist)| (local_id, dist, chunk_idx))
         })
         .collect();
 
-    // Collect results from all parallel tasks
-    let mut all_results: Vec<(u32, f64, usize)> = Vec::new();
-
-    for (chunk_idx, future) in chunk_futures.into_iter().enumerate() {
-        match future.await {
-            Ok(Ok(results)) => {
-                for (local_id, dist) in results {
-                    all_results.push((local_id, dist, chunk_idx));
-                }
-            }
-            Ok(Err(e)) => {
-                eprintln!("⚠️ ChunkSearcher: Failed to search chunk: {e}");
-            }
-            Err(e) => {
-                eprintln!("⚠️ ChunkSearcher: Task panicked: {e}");
-            }
-        }
-    }
-
-    // Sort by distance ascending and truncate to k.
     all_results.sort_by(|a, b| a.1.total_cmp(&b.1));
     all_results.truncate(k);
     all_results
 }
-
-/// Legacy synchronous wrapper for backwards compatibility.
-/// Spawns an async task and blocks on it - use scatter_gather_search_async when possible.
-#[allow(clippy::too_many_arguments)]
-pub fn scatter_gather_search<const N: usize, M: Metric<N> + Send + Sync + 'static>(
-    chunk_dirs: &[std::path::PathBuf],
-    query: &[f64],
-    k: usize,
-    ef_search: usize,
-    filters: &HashMap<String, String>,
-    complex_filters: &[FilterExpr],
-    mode: QuantizationMode,
-    config: &Arc<GlobalConfig>,
-    use_wasserstein: bool,
-) -> Vec<(u32, f64, usize)> {
-    // Use tokio runtime to run async function
-    let rt = tokio::runtime::Handle::current();
-    rt.block_on(scatter_gather_search_async::<N, M>(
-        chunk_dirs,
-        query,
-        k,
-        ef_search,
-        filters,
-        complex_filters,
-        mode,
-        config,
-        use_wasserstein,
-    ))
-}
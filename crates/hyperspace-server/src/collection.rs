@@ -1,32 +1,81 @@
+use crate::adaptive_ef::AdaptiveEf;
 use crate::chunk_searcher;
+use crate::collection_spec::{HnswParams, IvfParams};
+use crate::drift::DriftMonitor;
+use crate::group_commit::{self, GroupCommit};
+use crate::index_watermark::IndexWatermark;
+use crate::late_interaction::{self, TokenStore};
+use crate::limits::{CollectionLimits, LimitGuard};
 use crate::meta_router::{CentroidAccumulator, ChunkMeta, MetaRouter};
+use crate::pools::{self, Pool};
+use crate::replication::ReplicationFeed;
+use crate::scrubber;
+use crate::search_cache::SearchCache;
+use crate::snapshot::{CollectionState, SnapshotPolicy, SnapshotWriter};
+use crate::storage_health::StorageHealth;
 use crate::sync::CollectionDigest;
+use crate::wal_replay::ReplayTracker;
 use arc_swap::ArcSwap;
 use dashmap::DashMap;
 use hyperspace_core::gpu::{rerank_topk_exact, GpuMetric};
 use hyperspace_core::{
-    Collection, FilterExpr, GlobalConfig, Metric, SearchParams, SearchResult, StorageMode,
-    VacuumFilterOp, VacuumFilterQuery,
+    Collection, FilterExpr, GlobalConfig, GraphLayer, HyperspaceError, HyperspaceResult,
+    IndexingProgress, MetadataKeyStats, MetadataSchema, Metric, NormalizationPolicy, PurgeOutcome,
+    ScrubReport, SearchParams, SearchResult, StorageMode, SynonymMap, TokenMatrix, VacuumFilterOp,
+    VacuumFilterQuery,
 };
 use hyperspace_index::HnswIndex;
-use hyperspace_proto::hyperspace::{replication_log, InsertOp, ReplicationLog};
-use hyperspace_store::{wal::Wal, VectorStore};
-use serde::{Deserialize, Serialize};
+use hyperspace_proto::hyperspace::{
+    replication_log, DeleteOp, InsertOp, PurgeOp, ReplicationLog, StorageAlertEvent,
+};
+use hyperspace_store::wal::{Wal, WalOp};
+use hyperspace_store::{ChecksumStatus, VectorStore};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, LazyLock};
-use tokio::sync::{broadcast, mpsc, Semaphore};
+use tokio::sync::{mpsc, Semaphore};
 use tokio::task::JoinHandle;
 
-#[derive(Serialize, Deserialize)]
-struct CollectionState {
-    id_map: HashMap<u32, u32>,
-    reverse_id_map: HashMap<u32, u32>,
-    buckets: Vec<u64>,
-    #[serde(default)]
-    last_persisted_clock: u64,
+/// BM25 scoring and tokenization settings from `HS_BM25_*`.
+pub(crate) fn bm25_params_from_env() -> hyperspace_core::bm25::Bm25Params {
+    let bm25_method = std::env::var("HS_BM25_METHOD")
+        .unwrap_or_else(|_| "bm25plus".to_string())
+        .to_lowercase();
+    let method = match bm25_method.as_str() {
+        "robertson" => hyperspace_core::bm25::Bm25Method::Robertson,
+        "lucene" => hyperspace_core::bm25::Bm25Method::Lucene,
+        "atire" => hyperspace_core::bm25::Bm25Method::Atire,
+        "bm25l" => hyperspace_core::bm25::Bm25Method::Bm25l,
+        _ => hyperspace_core::bm25::Bm25Method::Bm25Plus,
+    };
+    let k1 = std::env::var("HS_BM25_K1")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1.2);
+    let b = std::env::var("HS_BM25_B")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.75);
+    let delta = std::env::var("HS_BM25_DELTA")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.5);
+    let language = std::env::var("HS_BM25_LANGUAGE").unwrap_or_else(|_| "english".to_string());
+    let ngrams = std::env::var("HS_BM25_NGRAMS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1);
+
+    hyperspace_core::bm25::Bm25Params {
+        method,
+        k1,
+        b,
+        delta,
+        language,
+        ngrams,
+    }
 }
 
 pub struct CollectionImpl<const N: usize, M: Metric<N>> {
@@ -34,8 +83,10 @@ pub struct CollectionImpl<const N: usize, M: Metric<N>> {
     node_id: String,
     index_link: Arc<ArcSwap<HnswIndex<N, M>>>,
     wal_link: Arc<ArcSwap<tokio::sync::Mutex<Wal>>>,
-    index_tx: mpsc::UnboundedSender<(u32, HashMap<String, String>)>,
-    replication_tx: broadcast::Sender<ReplicationLog>,
+    index_tx: mpsc::UnboundedSender<(u32, HashMap<String, String>, u64)>,
+    // Highest logical clock with nothing left in the indexer queue
+    index_watermark: Arc<IndexWatermark>,
+    replication_tx: ReplicationFeed,
     config: Arc<GlobalConfig>,
     bg_tasks: Vec<JoinHandle<()>>,
     // Buckets for Merkle Tree synchronization
@@ -72,11 +123,50 @@ pub struct CollectionImpl<const N: usize, M: Metric<N>> {
     max_ram_bytes: u64,
     // List of rotated WAL segments waiting to be flushed into a chunk (Task 8.1)
     pending_wal_flushes: Arc<tokio::sync::Mutex<Vec<PathBuf>>>,
+    // Optional LRU of recent search results, invalidated on every write (HS_SEARCH_CACHE_SIZE)
+    search_cache: Option<Arc<SearchCache>>,
+    // Profiles recent inserts against a baseline window (HS_DRIFT_WINDOW)
+    drift: Option<Arc<DriftMonitor>>,
+    // Caps ef_search while searches queue up (HS_ADAPTIVE_EF)
+    adaptive_ef: Option<Arc<AdaptiveEf>>,
+    // Writes index.snap, metadata.snap and state.json; shared with the
+    // background snapshot task
+    snapshot_writer: Arc<SnapshotWriter<N, M>>,
+    // Point / storage caps from meta.json, checked before every write
+    limits: LimitGuard,
+    // Declared metadata types from meta.json, checked by the API layer
+    schema: Option<MetadataSchema>,
+    // What writes outside the metric's canonical form become, from meta.json
+    normalization: NormalizationPolicy,
+    // Token-level vectors for late-interaction reranking (tokens.log)
+    tokens: TokenStore,
+    // Coalesces Strict-mode fsyncs across concurrent writers (HS_WAL_GROUP_COMMIT_MS)
+    group_commit: Option<GroupCommit>,
+    // Consecutive storage write failures; read-only past HS_READ_ONLY_AFTER_IO_ERRORS
+    storage_health: StorageHealth,
+    // Findings of the latest integrity scrub
+    scrub_report: parking_lot::Mutex<Option<ScrubReport>>,
+    // Serializes delta updates of the same point (striped by user id)
+    delta_locks: [tokio::sync::Mutex<()>; DELTA_LOCK_STRIPES],
 }
 
+const DELTA_LOCK_STRIPES: usize = 64;
+
 static EMPTY_LEGACY_FILTERS: LazyLock<HashMap<String, String>> = LazyLock::new(HashMap::new);
 static EMPTY_COMPLEX_FILTERS: LazyLock<Vec<FilterExpr>> = LazyLock::new(Vec::new);
 
+/// Runs an index search on this thread and adds the nodes it scored to
+/// `params.trace`.
+fn traced_search<T>(params: &SearchParams, search: impl FnOnce() -> T) -> T {
+    let Some(trace) = &params.trace else {
+        return search();
+    };
+    let _ = hyperspace_index::take_visited_count();
+    let out = search();
+    trace.add_visited(hyperspace_index::take_visited_count());
+    out
+}
+
 struct BatchEntry<'a> {
     id: u32,
     vector: Cow<'a, [f64]>,
@@ -141,7 +231,7 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
         }
     }
 
-    /// Normalizes vector if metric is Cosine.
+    /// Normalizes a query if metric is Cosine, whatever the write policy.
     /// Returns Cow to avoid allocation if normalization is not needed.
     #[inline]
     fn normalize_if_cosine(vector: &[f64]) -> Cow<'_, [f64]> {
@@ -160,15 +250,91 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
         Cow::Owned(normalized)
     }
 
+    /// Settles the fsync a write owes while the WAL lock is held: inline
+    /// without group commit, otherwise returns the segment to hand to the
+    /// committer once the lock is released.
+    fn owed_sync(
+        &self,
+        wal: &mut Wal,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<Option<Arc<std::fs::File>>> {
+        if !wal.defers_sync() && durability != hyperspace_core::Durability::Strict {
+            return Ok(None);
+        }
+        if self.group_commit.is_some() {
+            Ok(Some(wal.sync_file()))
+        } else {
+            wal.sync()?;
+            Ok(None)
+        }
+    }
+
+    /// Counts a failed storage write, alerts event subscribers and hands the
+    /// error back.
+    fn storage_error(&self, error: HyperspaceError) -> HyperspaceError {
+        if let Some(failure) = self.storage_health.failed(&error) {
+            if failure.read_only {
+                eprintln!(
+                    "🛑 '{}' is read-only after {} storage failures: {error}",
+                    self.name, failure.consecutive
+                );
+            } else {
+                eprintln!("⚠️ Storage write failed in '{}': {error}", self.name);
+            }
+            self.replication_tx.alert(StorageAlertEvent {
+                collection: self.name.clone(),
+                error: error.to_string(),
+                consecutive_failures: failure.consecutive,
+                read_only: failure.read_only,
+            });
+        }
+        error
+    }
+
+    async fn commit_owed(&self, file: Option<Arc<std::fs::File>>) -> HyperspaceResult<()> {
+        match (file, &self.group_commit) {
+            (Some(file), Some(commit)) => commit.sync(file).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Widens a packed f32 vector into a fixed-size array, keeping the f32
+    /// wire path free of intermediate `Vec<f64>` allocations.
+    #[inline]
+    fn widen_f32(vector: &[f32]) -> HyperspaceResult<[f64; N]> {
+        if vector.len() != N {
+            return Err(HyperspaceError::Validation(format!(
+                "Vector dimension mismatch. Expected {}, got {}",
+                N,
+                vector.len()
+            )));
+        }
+        let mut widened = [0.0; N];
+        for (dst, &src) in widened.iter_mut().zip(vector) {
+            *dst = f64::from(src);
+        }
+        Ok(widened)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         name: String,
         node_id: String,
         data_dir: std::path::PathBuf,
         wal_path: std::path::PathBuf,
         mode: hyperspace_core::QuantizationMode,
-        replication_tx: broadcast::Sender<ReplicationLog>,
+        replication_tx: ReplicationFeed,
+        snapshot_policy: SnapshotPolicy,
+        limits: CollectionLimits,
+        schema: Option<MetadataSchema>,
+        wal_sync_mode: Option<hyperspace_store::wal::WalSyncMode>,
+        hnsw: HnswParams,
+        ivf: Option<IvfParams>,
+        normalization: Option<NormalizationPolicy>,
+        synonyms: SynonymMap,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let snap_path = data_dir.join("index.snap");
+        let metadata_path = data_dir.join("metadata.snap");
         let config = Arc::new(GlobalConfig::new());
 
         let gossip_env =
@@ -192,50 +358,59 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             .parse()
             .unwrap_or(16);
 
-        config.set_ef_construction(ef_cons_env);
+        config.set_ef_construction(hnsw.ef_construction.unwrap_or(ef_cons_env));
         config.set_ef_search(ef_search_env);
-        config.set_m(m_env);
-
-        let bm25_method = std::env::var("HS_BM25_METHOD")
-            .unwrap_or_else(|_| "bm25plus".to_string())
-            .to_lowercase();
-        let method = match bm25_method.as_str() {
-            "robertson" => hyperspace_core::bm25::Bm25Method::Robertson,
-            "lucene" => hyperspace_core::bm25::Bm25Method::Lucene,
-            "atire" => hyperspace_core::bm25::Bm25Method::Atire,
-            "bm25l" => hyperspace_core::bm25::Bm25Method::Bm25l,
-            _ => hyperspace_core::bm25::Bm25Method::Bm25Plus,
-        };
-        let k1 = std::env::var("HS_BM25_K1")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(1.2);
-        let b = std::env::var("HS_BM25_B")
+        config.set_m(hnsw.m.unwrap_or(m_env));
+        config.set_extend_candidates_enabled(
+            std::env::var("HS_HNSW_EXTEND_CANDIDATES").is_ok_and(|v| v.to_lowercase() == "true"),
+        );
+        config.set_keep_pruned_enabled(
+            std::env::var("HS_HNSW_KEEP_PRUNED").is_ok_and(|v| v.to_lowercase() == "true"),
+        );
+        if let Some(k) = std::env::var("HS_HNSW_ENTRY_POINTS")
             .ok()
             .and_then(|v| v.parse().ok())
-            .unwrap_or(0.75);
-        let delta = std::env::var("HS_BM25_DELTA")
+        {
+            config.set_entry_points(k);
+        }
+        if let Some(probes) = std::env::var("HS_HNSW_ENTRY_PROBES")
             .ok()
             .and_then(|v| v.parse().ok())
-            .unwrap_or(0.5);
-        let language = std::env::var("HS_BM25_LANGUAGE").unwrap_or_else(|_| "english".to_string());
-        let ngrams = std::env::var("HS_BM25_NGRAMS")
+        {
+            config.set_entry_probes(probes);
+        }
+        if let Some(threshold) = std::env::var("HS_FILTER_BRUTEFORCE_THRESHOLD")
             .ok()
             .and_then(|v| v.parse().ok())
-            .unwrap_or(1);
+        {
+            config.set_filter_bruteforce_threshold(threshold);
+        }
+
+        if let Some(ivf) = ivf {
+            let cells_env = std::env::var("HS_IVF_CELLS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(64);
+            config.set_ivf_cells(ivf.cells.unwrap_or(cells_env));
+            if let Some(probes) = ivf.probes.or_else(|| {
+                std::env::var("HS_IVF_PROBES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+            }) {
+                config.set_ivf_probes(probes);
+            }
+        }
+
         let fusion_method = std::env::var("HS_FUSION_METHOD")
             .unwrap_or_else(|_| "rrf".to_string())
             .to_lowercase();
         config.set_fusion_method(fusion_method);
 
-        config.set_bm25_params(hyperspace_core::bm25::Bm25Params {
-            method,
-            k1,
-            b,
-            delta,
-            language,
-            ngrams,
-        });
+        config.set_bm25_params(bm25_params_from_env());
+        if let Some(schema) = &schema {
+            config.set_unindexed_keys(schema.unindexed.iter().cloned().collect());
+        }
+        config.set_synonyms(synonyms);
 
         let storage_f32_requested = std::env::var("HS_STORAGE_FLOAT32")
             .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"));
@@ -265,7 +440,8 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             std::fs::create_dir_all(&data_dir)?;
         }
 
-        let (_store, index, _recovered_count) = if snap_path.exists() {
+        let mut graph_loaded = false;
+        let (_store, index, recovered_count) = if snap_path.exists() {
             let store = Arc::new(VectorStore::new(&data_dir, element_size));
             match HnswIndex::<N, M>::load_snapshot_with_storage_precision(
                 &snap_path,
@@ -274,7 +450,19 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
                 config.clone(),
                 storage_f32,
             ) {
-                Ok(idx) => {
+                Ok(mut idx) => {
+                    if metadata_path.exists() {
+                        match idx.apply_metadata_snapshot(&metadata_path) {
+                            Ok(true) => println!("📦 Applied metadata snapshot for {name}"),
+                            Ok(false) => eprintln!(
+                                "⚠️ Metadata snapshot for {name} does not match the graph; ignored"
+                            ),
+                            Err(e) => {
+                                eprintln!("⚠️ Failed to load metadata snapshot for {name}: {e}");
+                            }
+                        }
+                    }
+                    graph_loaded = true;
                     let count = idx.count_nodes();
                     (store, Arc::new(idx), count)
                 }
@@ -307,6 +495,9 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             )
         };
 
+        // Graph size as persisted, before WAL replay grows it
+        let snapshot_graph_nodes = index.graph_len();
+
         // Wrap index in ArcSwap for Lock-Free Hot Swap
         let index_link = Arc::new(ArcSwap::new(index.clone()));
 
@@ -316,6 +507,7 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
         let mut reverse_id_map_data = HashMap::new();
         let mut buckets_data = vec![0; crate::sync::SYNC_BUCKETS];
         let last_clock = Arc::new(AtomicU64::new(0));
+        let mut state_loaded = false;
 
         if state_path.exists() {
             if let Ok(s) = std::fs::read_to_string(&state_path) {
@@ -326,20 +518,27 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
                         buckets_data = state.buckets;
                     }
                     last_clock.store(state.last_persisted_clock, Ordering::Relaxed);
+                    state_loaded = true;
                 }
             }
         }
 
-        // WAL
-        let sync_mode_str = std::env::var("HYPERSPACE_WAL_SYNC_MODE")
-            .unwrap_or_else(|_| "async".to_string())
-            .to_lowercase();
+        // index.snap with points but no readable state.json: nothing maps
+        // user ids to the stored points, and replaying the WAL from clock 0
+        // would append every point a second time.
+        if graph_loaded && !state_loaded && recovered_count > 0 {
+            return Err(HyperspaceError::Corruption(format!(
+                "Collection '{name}' has {recovered_count} point(s) in index.snap but no \
+                 readable state.json. Restore state.json from a backup, or rebuild from the \
+                 WAL alone if it still holds every point: mv {} {}.orphaned",
+                snap_path.display(),
+                snap_path.display(),
+            ))
+            .into());
+        }
 
-        let sync_mode = match sync_mode_str.as_str() {
-            "strict" | "fsync" => hyperspace_store::wal::WalSyncMode::Strict,
-            "batch" => hyperspace_store::wal::WalSyncMode::Batch,
-            _ => hyperspace_store::wal::WalSyncMode::Async,
-        };
+        // WAL: meta.json override, else HYPERSPACE_WAL_SYNC_MODE
+        let sync_mode = wal_sync_mode.unwrap_or_else(hyperspace_store::wal::WalSyncMode::from_env);
 
         if sync_mode == hyperspace_store::wal::WalSyncMode::Strict {
             println!("🔒 WAL Durability: STRICT (fsync on every write)");
@@ -397,13 +596,21 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
         wal.set_size_limit(wal_segment_mb * 1024 * 1024);
         println!("📦 WAL Segment Size: {wal_segment_mb} MB");
 
+        let (group_commit, group_commit_task) = match group_commit::window_from_env() {
+            Some(window) => {
+                let (commit, task) = GroupCommit::spawn(window);
+                (Some(commit), Some(task))
+            }
+            None => (None, None),
+        };
+        wal.set_group_commit(group_commit.is_some());
+
         let wal_link = Arc::new(ArcSwap::new(Arc::new(tokio::sync::Mutex::new(wal))));
         let flushing_vector_count = Arc::new(AtomicUsize::new(0));
         let wal_pending_count = Arc::new(AtomicU64::new(0));
 
         // Replay
         let index_ref = index.clone();
-        let loaded_clock = last_clock.load(Ordering::Relaxed);
 
         // Find all frozen WAL segments that haven't been flushed yet
         let mut wal_segments = Vec::new();
@@ -437,26 +644,118 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
         let mut final_replay = replay_queue;
         final_replay.push(wal_path.clone());
 
+        // state.json without a usable index.snap: its points sit in storage
+        // slots the fresh graph does not know, and appends would overwrite
+        // them. Rebuild from the whole WAL if it still holds every point,
+        // otherwise refuse to open rather than lose them.
+        if !graph_loaded && !id_map_data.is_empty() {
+            let mut seen = HashSet::new();
+            for path in &final_replay {
+                Wal::replay(path, |entry| {
+                    seen.insert(match entry {
+                        hyperspace_store::wal::WalEntry::Insert { id, .. }
+                        | hyperspace_store::wal::WalEntry::Delete { id, .. } => id,
+                    });
+                })?;
+            }
+            let missing = id_map_data.keys().filter(|id| !seen.contains(id)).count();
+            if missing > 0 {
+                return Err(HyperspaceError::Corruption(format!(
+                    "Collection '{name}' has no usable index.snap, and {missing} of the {} \
+                     point(s) in state.json are no longer in the WAL. Restore index.snap from \
+                     a backup, or rebuild from the WAL alone without them: mv {} {}.orphaned",
+                    id_map_data.len(),
+                    state_path.display(),
+                    state_path.display(),
+                ))
+                .into());
+            }
+            println!(
+                "♻️  Rebuilding index of {name} from {} WAL segment(s): index.snap is missing",
+                final_replay.len()
+            );
+            id_map_data.clear();
+            reverse_id_map_data.clear();
+            buckets_data.fill(0);
+            last_clock.store(0, Ordering::Relaxed);
+        }
+        let loaded_clock = last_clock.load(Ordering::Relaxed);
+
+        // Indexer Concurrency Configuration
+        // Default: 1 (Serial) for maximum graph quality
+        // Set to 0 to use all CPU cores (faster but lower recall due to race conditions)
+        // WAL replay links recovered points with the same number of threads.
+        let num_cpus = std::thread::available_parallelism().map_or(8, std::num::NonZero::get);
+        let concurrency_env = std::env::var("HS_INDEXER_CONCURRENCY")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<usize>()
+            .unwrap_or(1);
+
+        let concurrency = if concurrency_env == 0 {
+            num_cpus
+        } else if concurrency_env > num_cpus {
+            println!(
+                 "⚠️  Clamping Indexer Concurrency from {concurrency_env} to {num_cpus} (CPU limit) to avoid thrashing."
+             );
+            num_cpus
+        } else {
+            concurrency_env
+        };
+
+        println!("⚙️  Indexer Concurrency: {concurrency} thread(s)");
+
         println!("⚡ Replaying {} WAL segment(s)...", final_replay.len());
 
+        // Points are written to storage in WAL order here and linked into the
+        // graph afterwards, in parallel like the online indexer.
+        let mut pending: BTreeMap<u32, HashMap<String, String>> = BTreeMap::new();
+
         for path in final_replay {
             Wal::replay(&path, |entry| {
-                let hyperspace_store::wal::WalEntry::Insert {
-                    id,
-                    vector,
-                    metadata,
-                    logical_clock,
-                } = entry;
+                let (id, vector, metadata, logical_clock) = match entry {
+                    hyperspace_store::wal::WalEntry::Insert {
+                        id,
+                        vector,
+                        metadata,
+                        logical_clock,
+                    } => (id, vector, metadata, logical_clock),
+                    hyperspace_store::wal::WalEntry::Delete { id, logical_clock } => {
+                        if logical_clock > loaded_clock {
+                            if let Some(internal_id) = id_map_data.remove(&id) {
+                                reverse_id_map_data.remove(&internal_id);
+                                if gossip_env && (internal_id as usize) < index_ref.count() {
+                                    let vector = index_ref.get_vector(internal_id);
+                                    let hash = CollectionDigest::hash_entry(id, &vector.coords);
+                                    let b_idx = CollectionDigest::get_bucket_index(id);
+                                    buckets_data[b_idx] ^= hash;
+                                }
+                                index_ref.delete(internal_id);
+                                pending.remove(&internal_id);
+                            }
+                            last_clock.fetch_max(logical_clock, Ordering::Relaxed);
+                            wal_pending_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                        return;
+                    }
+                };
 
                 // Only replay operations strictly newer than what's persisted in state.json
                 if logical_clock > loaded_clock {
                     // If ID exists, delete old version from index to prevent leaks (Upsert)
                     if let Some(&old_internal_id) = id_map_data.get(&id) {
+                        // Unhash the old version, as an online upsert does
+                        if gossip_env && (old_internal_id as usize) < index_ref.count() {
+                            let old = index_ref.get_vector(old_internal_id);
+                            let hash = CollectionDigest::hash_entry(id, &old.coords);
+                            buckets_data[CollectionDigest::get_bucket_index(id)] ^= hash;
+                        }
                         index_ref.delete(old_internal_id);
                         reverse_id_map_data.remove(&old_internal_id);
+                        pending.remove(&old_internal_id);
                     }
 
-                    if let Ok(internal_id) = index_ref.insert(&vector, metadata) {
+                    if let Ok(internal_id) = index_ref.insert_to_storage(&vector) {
+                        pending.insert(internal_id, metadata);
                         id_map_data.insert(id, internal_id);
                         reverse_id_map_data.insert(internal_id, id);
 
@@ -474,32 +773,33 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             })?;
         }
 
+        if !pending.is_empty() {
+            let tracker = ReplayTracker::start(&name, pending.len() as u64);
+            let pending: Vec<_> = pending.into_iter().collect();
+            let next = AtomicUsize::new(0);
+            std::thread::scope(|scope| {
+                for _ in 0..concurrency.min(pending.len()) {
+                    scope.spawn(|| {
+                        while let Some((id, meta)) =
+                            pending.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            if let Err(e) = index_ref.index_node(*id, meta.clone()) {
+                                eprintln!("❌ Replay indexing error on ID {id}: {e}");
+                            }
+                            tracker.advance();
+                        }
+                    });
+                }
+            });
+        }
+
         // Background Tasks
         let (index_tx, mut index_rx) = mpsc::unbounded_channel();
         let idx_link_worker = index_link.clone();
+        let index_watermark = Arc::new(IndexWatermark::new(last_clock.load(Ordering::Relaxed)));
+        let watermark_worker = index_watermark.clone();
         let cfg_worker = config.clone();
 
-        // Indexer Concurrency Configuration
-        // Default: 1 (Serial) for maximum graph quality
-        // Set to 0 to use all CPU cores (faster but lower recall due to race conditions)
-        let num_cpus = std::thread::available_parallelism().map_or(8, std::num::NonZero::get);
-        let concurrency_env = std::env::var("HS_INDEXER_CONCURRENCY")
-            .unwrap_or_else(|_| "1".to_string())
-            .parse::<usize>()
-            .unwrap_or(1);
-
-        let concurrency = if concurrency_env == 0 {
-            num_cpus
-        } else if concurrency_env > num_cpus {
-            println!(
-                 "⚠️  Clamping Indexer Concurrency from {concurrency_env} to {num_cpus} (CPU limit) to avoid thrashing."
-             );
-            num_cpus
-        } else {
-            concurrency_env
-        };
-
-        println!("⚙️  Indexer Concurrency: {concurrency} thread(s)");
         let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
 
         let search_concurrency_env = std::env::var("HS_SEARCH_CONCURRENCY")
@@ -526,26 +826,35 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             .unwrap_or(0.0)
             .max(0.0);
 
+        let search_cache = SearchCache::from_env();
+        let search_cache_worker = search_cache.clone();
+
         let indexer_task = tokio::spawn(async move {
             use std::sync::atomic::AtomicU64;
             let received = Arc::new(AtomicU64::new(0));
             let errors = Arc::new(AtomicU64::new(0));
 
-            while let Some((id, meta)) = index_rx.recv().await {
+            while let Some((id, meta, clock)) = index_rx.recv().await {
                 let permit = semaphore.clone().acquire_owned().await.unwrap();
                 let idx_link = idx_link_worker.clone();
                 let cfg = cfg_worker.clone();
                 let errors_ref = errors.clone();
+                let cache = search_cache_worker.clone();
+                let watermark = watermark_worker.clone();
                 cfg.inc_active();
 
                 tokio::spawn(async move {
                     let _permit = permit;
-                    let result = tokio::task::spawn_blocking(move || {
+                    let result = pools::spawn(Pool::Indexing, move || {
                         let idx = idx_link.load().clone();
                         let result = idx.index_node(id, meta);
                         (result, id)
                     })
                     .await;
+                    if let Some(cache) = &cache {
+                        cache.invalidate();
+                    }
+                    watermark.done(clock);
 
                     match result {
                         Ok((Ok(()), _processed_id)) => {
@@ -613,9 +922,6 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             }
         }
 
-        let idx_link_snap = index_link.clone();
-        let snap_path_clone = snap_path.clone();
-
         let buckets: Arc<Vec<AtomicU64>> =
             Arc::new(buckets_data.into_iter().map(AtomicU64::new).collect());
         let id_map = Arc::new(id_map_data.into_iter().collect::<DashMap<u32, u32>>());
@@ -638,52 +944,80 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
                 .collect::<DashMap<u32, u32>>(),
         );
 
-        let id_map_snap = id_map.clone();
-        let reverse_id_map_snap = reverse_id_map.clone();
-        let buckets_snap = buckets.clone();
-        let state_path_snap = data_dir.join("state.json");
-        let last_clock_snap = last_clock.clone();
-
-        let snap_interval = std::env::var("HYPERSPACE_SNAPSHOT_INTERVAL_SEC")
-            .unwrap_or("60".to_string())
-            .parse::<u64>()
-            .unwrap_or(60);
+        // Only a graph loaded from index.snap can take metadata snapshots
+        // before the first graph snapshot of this run.
+        let snapshot_graph = if graph_loaded {
+            (Arc::downgrade(&index), snapshot_graph_nodes)
+        } else {
+            (std::sync::Weak::new(), 0)
+        };
+        let snapshot_writer = Arc::new(SnapshotWriter {
+            index_link: index_link.clone(),
+            snap_path,
+            metadata_path,
+            state_path: data_dir.join("state.json"),
+            id_map: id_map.clone(),
+            reverse_id_map: reverse_id_map.clone(),
+            buckets: buckets.clone(),
+            last_clock: last_clock.clone(),
+            ops_since: AtomicU64::new(0),
+            write_lock: parking_lot::Mutex::new(()),
+            graph: parking_lot::Mutex::new(snapshot_graph),
+            history: snapshot_policy.history(),
+            // A restart alone doesn't add a generation
+            history_clock: parking_lot::Mutex::new(
+                (!crate::snapshot_history::generations(&data_dir).is_empty())
+                    .then(|| last_clock.load(Ordering::Relaxed)),
+            ),
+        });
 
+        let writer_bg = snapshot_writer.clone();
+        let wal_link_snap = wal_link.clone();
         let snapshot_handle = tokio::spawn(async move {
+            let interval = snapshot_policy.interval();
+            let metadata_interval = snapshot_policy.metadata_interval();
+            let mut last_snapshot = tokio::time::Instant::now();
+            let mut last_metadata_snapshot = last_snapshot;
             loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(snap_interval)).await;
-                let idx = idx_link_snap.load().clone();
-                if let Err(e) = idx.save_snapshot(&snap_path_clone) {
-                    eprintln!("Snapshot error: {e}");
-                }
-
-                // Save State (DashMap iteration)
-                let map_data: HashMap<u32, u32> = id_map_snap
-                    .iter()
-                    .map(|entry| (*entry.key(), *entry.value()))
-                    .collect();
-                let reverse_map_data: HashMap<u32, u32> = reverse_id_map_snap
-                    .iter()
-                    .map(|entry| (*entry.key(), *entry.value()))
-                    .collect();
-                let buckets_data: Vec<u64> = buckets_snap
-                    .iter()
-                    .map(|b| b.load(Ordering::Relaxed))
-                    .collect();
+                tokio::time::sleep(snapshot_policy.poll_interval()).await;
 
-                let state = CollectionState {
-                    id_map: map_data,
-                    reverse_id_map: reverse_map_data,
-                    buckets: buckets_data,
-                    last_persisted_clock: last_clock_snap.load(Ordering::Relaxed),
-                };
+                let mut due = last_snapshot.elapsed() >= interval;
+                if let Some(limit) = snapshot_policy.every_ops() {
+                    due |= writer_bg.ops_since.load(Ordering::Relaxed) >= limit;
+                }
+                if let Some(limit) = snapshot_policy.wal_bytes() {
+                    if !due {
+                        let wal_guard = wal_link_snap.load();
+                        due = wal_guard.lock().await.size() >= limit;
+                    }
+                }
+                if !due {
+                    if metadata_interval.is_some_and(|i| last_metadata_snapshot.elapsed() >= i) {
+                        let writer = writer_bg.clone();
+                        match pools::spawn(Pool::Indexing, move || writer.write_metadata()).await {
+                            Ok(Err(e)) => eprintln!("Metadata snapshot error: {e}"),
+                            Err(e) => eprintln!("Metadata snapshot task failed: {e}"),
+                            Ok(Ok(_)) => {}
+                        }
+                        last_metadata_snapshot = tokio::time::Instant::now();
+                    }
+                    continue;
+                }
 
-                if let Ok(s) = serde_json::to_string(&state) {
-                    let _ = std::fs::write(&state_path_snap, s);
+                let writer = writer_bg.clone();
+                match pools::spawn(Pool::Indexing, move || writer.write()).await {
+                    Ok(Err(e)) => eprintln!("Snapshot error: {e}"),
+                    Err(e) => eprintln!("Snapshot task failed: {e}"),
+                    Ok(Ok(())) => {}
                 }
+                last_snapshot = tokio::time::Instant::now();
+                last_metadata_snapshot = last_snapshot;
             }
         });
 
+        let mut bg_tasks = vec![indexer_task, snapshot_handle];
+        bg_tasks.extend(group_commit_task);
+
         let mut initial_root_hash = 0u64;
         for b in buckets.iter() {
             initial_root_hash ^= b.load(Ordering::Relaxed);
@@ -695,13 +1029,23 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             index_link,
             wal_link,
             index_tx,
+            index_watermark,
             replication_tx,
             config,
-            bg_tasks: vec![indexer_task, snapshot_handle],
+            bg_tasks,
             buckets,
             root_hash: AtomicU64::new(initial_root_hash),
             reverse_id_map,
             id_map,
+            limits: LimitGuard::new(limits, data_dir.clone()),
+            schema: schema.filter(|s| !s.is_empty()),
+            normalization: normalization
+                .unwrap_or_else(|| NormalizationPolicy::default_for(M::name())),
+            tokens: TokenStore::open(&data_dir)?,
+            group_commit,
+            storage_health: StorageHealth::from_env(),
+            scrub_report: parking_lot::Mutex::new(None),
+            delta_locks: std::array::from_fn(|_| tokio::sync::Mutex::new(())),
             data_dir,
             mode,
             last_clock,
@@ -715,99 +1059,692 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
             storage_mode,
             max_ram_bytes,
             pending_wal_flushes,
+            search_cache,
+            drift: DriftMonitor::from_env(N),
+            adaptive_ef: AdaptiveEf::from_env(search_concurrency),
+            snapshot_writer,
         })
     }
 
-    #[allow(clippy::too_many_arguments)] // Background worker requires all context
-    fn spawn_flush_worker(
-        frozen_wal_paths: Vec<PathBuf>,
-        config: Arc<GlobalConfig>,
-        mode: hyperspace_core::QuantizationMode,
-        data_dir: PathBuf,
-        flush_limiter: Arc<Semaphore>,
-        meta_router: Arc<MetaRouter<N>>,
-        index_link: Arc<ArcSwap<HnswIndex<N, M>>>,
-        _id_map: Arc<DashMap<u32, u32>>,
-        _reverse_id_map: Arc<DashMap<u32, u32>>,
-        flushing_vector_count: Arc<AtomicUsize>,
-    ) {
-        let storage_f32_requested = std::env::var("HS_STORAGE_FLOAT32")
-            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"));
-        let storage_f32 = storage_f32_requested && mode == hyperspace_core::QuantizationMode::None;
-        let element_size = match mode {
-            hyperspace_core::QuantizationMode::ScalarI8 => {
-                hyperspace_core::vector::QuantizedHyperVector::<N>::SIZE
-            }
-            hyperspace_core::QuantizationMode::Binary => {
-                hyperspace_core::vector::BinaryHyperVector::<N>::SIZE
-            }
-            hyperspace_core::QuantizationMode::None => {
-                if storage_f32 {
-                    hyperspace_core::vector::HyperVectorF32::<N>::SIZE
-                } else {
-                    hyperspace_core::vector::HyperVector::<N>::SIZE
-                }
-            }
-        };
-
-        tokio::spawn(async move {
-            let permit = flush_limiter.clone().acquire_owned().await;
-            let _ = tokio::task::spawn_blocking(move || {
-                let _permit = permit;
-
-                println!("🔄 Flush Worker: Starting conversion of {} WAL segment(s)...", frozen_wal_paths.len());
-                let _flush_start = std::time::Instant::now();
-
-                // Track the vectors entering purgatory across all files
-                let mut total_frozen_entries = 0;
-                for path in &frozen_wal_paths {
-                    total_frozen_entries += Wal::pending_entries_at_path(path);
-                }
-                flushing_vector_count.fetch_add(total_frozen_entries as usize, Ordering::SeqCst);
-                println!("🔄 Flush Worker: Total entries to process: {total_frozen_entries}");
-
-                let chunk_id = uuid::Uuid::new_v4().to_string();
-                let chunk_name = format!("chunk_{chunk_id}.hyp");
-                let chunk_dir = data_dir.join(&chunk_name);
+    #[inline]
+    fn invalidate_search_cache(&self) {
+        if let Some(cache) = &self.search_cache {
+            cache.invalidate();
+        }
+    }
 
-                if let Err(e) = std::fs::create_dir_all(&chunk_dir) {
-                    eprintln!("Failed to create chunk directory {chunk_name}: {e}");
-                    return;
-                }
+    /// Waits until nothing is queued for the indexer, which would otherwise
+    /// link and tag a point after it was purged.
+    async fn drain_indexer(&self) {
+        while self.index_watermark.get().1 > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
 
-                let temp_store = Arc::new(VectorStore::new(&chunk_dir, element_size));
-                let local_index = HnswIndex::<N, M>::new_with_storage_precision(
-                    temp_store.clone(),
-                    mode,
-                    config.clone(),
-                    storage_f32,
+    fn record_drift(&self, vector: &[f64]) {
+        let Some(drift) = &self.drift else {
+            return;
+        };
+        if let Some(score) = drift.record(vector) {
+            if score > drift.threshold() {
+                eprintln!(
+                    "⚠️ Drift: recent inserts into '{}' score {score:.2} against the baseline (threshold {:.2}); did the embedding model change?",
+                    self.name,
+                    drift.threshold()
                 );
+            }
+        }
+    }
 
-                let mut insert_count = 0u32;
-                let mut centroid_acc = CentroidAccumulator::new(N);
+    /// Runs a search past the cache. The flag is `false` when load-adaptive
+    /// ef lowered `ef_search`, so the results must not be cached.
+    async fn search_uncached(
+        &self,
+        query: &[f64],
+        filters: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<(Vec<SearchResult>, bool)> {
+        let Some(adaptive_ef) = &self.adaptive_ef else {
+            let results = self
+                .search_index(query, filters, complex_filters, params)
+                .await?;
+            return Ok((results, true));
+        };
+        let _search = adaptive_ef.begin();
+        let mut params = params.clone();
+        let requested = params.ef_search;
+        params.ef_search = adaptive_ef.ef_search(requested);
+        let results = self
+            .search_index(query, filters, complex_filters, &params)
+            .await?;
+        Ok((results, params.ef_search >= requested))
+    }
 
-                // Replay ALL accumulated segments into the same chunk
-                for (i, path) in frozen_wal_paths.iter().enumerate() {
-                    let replay_start = std::time::Instant::now();
-                    let replay_res = Wal::replay(path, |entry| {
-                        let hyperspace_store::wal::WalEntry::Insert { vector, metadata, .. } = entry;
-                        if vector.len() == N {
-                            centroid_acc.add(&vector);
-                            if let Ok(new_id) = local_index.insert_to_storage(&vector) {
-                                let _ = local_index.index_node(new_id, metadata);
-                                insert_count += 1;
-                            }
-                        }
-                    });
+    async fn search_index(
+        &self,
+        query: &[f64],
+        filters: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<Vec<SearchResult>> {
+        if query.len() != N {
+            return Err(HyperspaceError::Validation(format!(
+                "Query dimension mismatch. Expected {}, got {}",
+                N,
+                query.len()
+            )));
+        }
 
-                    let replay_elapsed = replay_start.elapsed();
-                    println!("🔄 Flush Worker: Replay segment {}/{} completed in {:.2}s ({} vectors)",
-                        i+1, frozen_wal_paths.len(), replay_elapsed.as_secs_f64(), insert_count);
+        // Quick Win #5: Zero-copy normalization - keep Cow until absolutely necessary
+        let processed_query_cow = Self::normalize_if_cosine(query);
 
-                    if replay_res.is_err() {
-                        eprintln!("⚠️ Failed to replay WAL segment {}/{} during flush. Some data may be lost or chunk will be partial.", i+1, frozen_wal_paths.len());
-                    }
-                }
+        let index_link = self.index_link.clone();
+        let reverse_id_map = self.reverse_id_map.clone();
+        let ids_are_identity = self.ids_are_identity.load(Ordering::Acquire);
+
+        // Move only the required fields to avoid cloning whole params struct.
+        let top_k = params.top_k;
+        let ef_search = params.ef_search;
+        let rerank_enabled = std::env::var("HS_RERANK_ENABLED")
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"));
+        let rerank_oversample = std::env::var("HS_RERANK_OVERSAMPLE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(4)
+            .max(1);
+        let use_wasserstein = params.use_wasserstein;
+        let filters_owned = (!filters.is_empty()).then(|| filters.clone());
+        let complex_filters_owned = (!complex_filters.is_empty()).then(|| complex_filters.to_vec());
+        let meta_router_ref = self.meta_router.clone();
+        let mode_for_search = self.mode;
+        let config_for_search = self.config.clone();
+        let permit = self
+            .search_limiter
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("Search limiter failed: {e}"))?;
+
+        // Quick Win: For small top_k, run search inline to avoid spawn_blocking overhead
+        let use_blocking = top_k > 50 || rerank_enabled;
+
+        if use_blocking {
+            // Convert to owned only when entering blocking task
+            let processed_query = processed_query_cow.into_owned();
+            let mut search_params_owned = params.clone();
+            pools::spawn(Pool::Search, move || {
+                let _permit = permit;
+                let index = index_link.load();
+                let include_metadata = index.has_nonempty_metadata();
+                let filters_ref = filters_owned.as_ref().unwrap_or(&EMPTY_LEGACY_FILTERS);
+                let complex_filters_ref = complex_filters_owned
+                    .as_ref()
+                    .map_or(EMPTY_COMPLEX_FILTERS.as_slice(), Vec::as_slice);
+                let search_k = if rerank_enabled {
+                    top_k.saturating_mul(rerank_oversample).max(top_k)
+                } else {
+                    top_k
+                };
+
+                search_params_owned.top_k = search_k;
+                let mem_results = traced_search(&search_params_owned, || {
+                    index.search(
+                        &processed_query,
+                        filters_ref,
+                        complex_filters_ref,
+                        &search_params_owned,
+                    )
+                });
+
+                // === 2. Search cold chunks via MetaRouter (disk mmap) ===
+                let probe_k = std::env::var("HS_CHUNK_PROBE_K")
+                    .ok()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(3);
+                let routed_chunks = meta_router_ref.route(&processed_query, probe_k);
+                let chunk_dirs: Vec<std::path::PathBuf> = routed_chunks
+                    .iter()
+                    .map(|(_, path, _)| path.clone())
+                    .collect();
+
+                let chunk_results = if chunk_dirs.is_empty() {
+                    Vec::new()
+                } else {
+                    chunk_searcher::scatter_gather_search::<N, M>(
+                        &chunk_dirs,
+                        &processed_query,
+                        search_k,
+                        ef_search,
+                        filters_ref,
+                        complex_filters_ref,
+                        mode_for_search,
+                        &config_for_search,
+                        use_wasserstein,
+                    )
+                };
+
+                // === 3. Merge MemTable + Chunk results by distance ===
+                // MemTable results carry real internal IDs.
+                // Chunk results carry chunk-local IDs (not usable for metadata).
+                // We merge by distance only, preferring MemTable entries for metadata.
+                let mut merged: Vec<(u32, f64, bool)> =
+                    Vec::with_capacity(mem_results.len() + chunk_results.len());
+
+                for (id, dist) in &mem_results {
+                    merged.push((*id, *dist, true)); // true = from MemTable
+                }
+                for (_, dist, _) in &chunk_results {
+                    // Chunk results don't have usable IDs for the collection's id_map.
+                    // We use u32::MAX as a sentinel — they'll be filtered in metadata step.
+                    merged.push((u32::MAX, *dist, false)); // false = from chunk
+                }
+
+                merged.sort_by(|a, b| a.1.total_cmp(&b.1));
+                merged.truncate(search_k);
+
+                // Include all results (RAM + Chunks).
+                // Note: Results from chunks will have internal IDs that need a segment mapping.
+                let results: Vec<(u32, f64)> =
+                    merged.into_iter().map(|(id, dist, _)| (id, dist)).collect();
+
+                let metric_tag = match M::name() {
+                    "cosine" => GpuMetric::Cosine,
+                    "poincare" => GpuMetric::Poincare,
+                    "lorentz" => GpuMetric::Lorentz,
+                    _ => GpuMetric::L2,
+                };
+
+                let reranked_internal: Vec<(u32, f64)> = if rerank_enabled && !results.is_empty() {
+                    let candidate_ids: Vec<u32> = results.iter().map(|(id, _)| *id).collect();
+                    let candidate_vectors: Vec<Vec<f64>> = candidate_ids
+                        .iter()
+                        .map(|id| index.get_vector(*id).coords.to_vec())
+                        .collect();
+                    let candidate_refs: Vec<&[f64]> =
+                        candidate_vectors.iter().map(Vec::as_slice).collect();
+                    rerank_topk_exact(
+                        metric_tag,
+                        &processed_query,
+                        &candidate_ids,
+                        &candidate_refs,
+                    )
+                } else {
+                    results
+                };
+
+                // Fetch metadata and convert IDs inside blocking worker.
+                reranked_internal
+                    .into_iter()
+                    .take(top_k)
+                    .map(|(internal_id, dist)| {
+                        let meta = if include_metadata {
+                            index.metadata.forward.get(internal_id).unwrap_or_default()
+                        } else {
+                            HashMap::new()
+                        };
+
+                        let user_id = if ids_are_identity {
+                            internal_id
+                        } else {
+                            reverse_id_map.get(&internal_id).map_or(internal_id, |v| *v)
+                        };
+
+                        (user_id, dist, meta)
+                    })
+                    .collect::<Vec<SearchResult>>()
+            })
+            .await
+            .map_err(|e| HyperspaceError::Internal(format!("Search task failed: {e}")))
+        } else {
+            // Quick Win: Inline search for small top_k - avoid spawn_blocking overhead
+            // Still need to convert Cow to owned for HNSW search
+            let processed_query = processed_query_cow.into_owned();
+            let _permit = permit;
+            let index = index_link.load();
+            let include_metadata = index.has_nonempty_metadata();
+            let filters_ref = filters_owned.as_ref().unwrap_or(&EMPTY_LEGACY_FILTERS);
+            let complex_filters_ref = complex_filters_owned
+                .as_ref()
+                .map_or(EMPTY_COMPLEX_FILTERS.as_slice(), Vec::as_slice);
+
+            // === 1. Search the hot MemTable (in-RAM HNSW) ===
+            let mem_results = traced_search(params, || {
+                index.search(&processed_query, filters_ref, complex_filters_ref, params)
+            });
+
+            // === 2. Search cold chunks (skip for small queries - assume hot data) ===
+            // Skip chunk search for small top_k to reduce latency
+
+            // === 3. Convert results ===
+            let results: Vec<SearchResult> = mem_results
+                .into_iter()
+                .take(top_k)
+                .map(|(internal_id, dist)| {
+                    let meta = if include_metadata {
+                        index.metadata.forward.get(internal_id).unwrap_or_default()
+                    } else {
+                        HashMap::new()
+                    };
+
+                    let user_id = if ids_are_identity {
+                        internal_id
+                    } else {
+                        reverse_id_map.get(&internal_id).map_or(internal_id, |v| *v)
+                    };
+
+                    (user_id, dist, meta)
+                })
+                .collect();
+
+            Ok(results)
+        }
+    }
+
+    /// Drops user `id` from the ID maps, the digest and the graph. Unknown
+    /// ids are a no-op: their slot may hold another point, and a replayed
+    /// delete must not unhash the bucket twice.
+    fn remove_point(&self, id: u32) {
+        let Some((_, internal_id)) = self.id_map.remove(&id) else {
+            return;
+        };
+        self.reverse_id_map.remove(&internal_id);
+
+        let idx = self.index_link.load();
+        if self.config.is_gossip_enabled() {
+            // Defensive check: only update if ID is within bounds of active index
+            if (internal_id as usize) < idx.count() {
+                let vector = idx.get_vector(internal_id);
+                let hash = CollectionDigest::hash_entry(id, &vector.coords);
+                let b_idx = CollectionDigest::get_bucket_index(id);
+
+                self.buckets[b_idx].fetch_xor(hash, Ordering::Relaxed);
+                self.root_hash.fetch_xor(hash, Ordering::Relaxed);
+            }
+        }
+
+        idx.delete(internal_id);
+        if let Err(e) = self.tokens.remove(id) {
+            eprintln!("⚠️ Dropping token vectors of {id} in '{}': {e}", self.name);
+        }
+    }
+
+    /// Removes `deletes`, then writes `vectors`. With deletes the WAL gets
+    /// one mixed record, so replay never sees part of the batch.
+    async fn write_batch(
+        &self,
+        deletes: &[u32],
+        vectors: Vec<(Vec<f64>, u32, HashMap<String, String>)>,
+        clock: u64,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<()> {
+        self.storage_health.check(&self.name)?;
+        // 1. Validation
+        for (vec, _, _) in &vectors {
+            if vec.len() != N {
+                return Err(HyperspaceError::Validation(format!(
+                    "Vector dimension mismatch. Expected {}, got {}",
+                    N,
+                    vec.len()
+                )));
+            }
+        }
+        for (vec, _, _) in &vectors {
+            self.record_drift(vec);
+        }
+
+        let new_points = vectors
+            .iter()
+            .filter(|(_, id, _)| !self.id_map.contains_key(id) || deletes.contains(id))
+            .count();
+        self.limits.check(self.count(), new_points)?;
+
+        for &id in deletes {
+            self.remove_point(id);
+        }
+
+        // Optimization: Use lifetime to hold reference to input vectors to avoid allocation.
+
+        let mut entries = Vec::with_capacity(vectors.len());
+
+        // 2. Process Logic (Zero-Copy Path)
+        // Note: Iterate by reference to preserve original data lifetimes.
+
+        // HOISTED LOCK: Load the index pointer to avoid taking the RwLock for every item.
+        // ArcSwap provides zero-contention access to the index.
+        let index_reader = self.index_link.load();
+
+        for (vector, id, metadata) in &vectors {
+            // Borrowed unless the policy rewrites it (No Allocation)
+            let processed_vector = self.normalization.apply(vector);
+
+            // Check existing
+            let existing_internal_id = self.id_map.get(id).map(|v| *v);
+
+            // Bucket updates (Read-only access to vector)
+            let mut reindex_needed = true;
+            if let Some(old_internal_id) = existing_internal_id {
+                // Defensive: Only attempt fast-upsert and gossip-undo if vector is in the active HNSW segment.
+                if (old_internal_id as usize) < index_reader.count() {
+                    let old_vector = index_reader.get_vector(old_internal_id);
+                    if self.config.is_gossip_enabled() {
+                        let old_id_hash = CollectionDigest::hash_entry(*id, &old_vector.coords);
+                        let bucket_idx = CollectionDigest::get_bucket_index(*id);
+                        self.buckets[bucket_idx].fetch_xor(old_id_hash, Ordering::Relaxed);
+                        self.root_hash.fetch_xor(old_id_hash, Ordering::Relaxed);
+                    }
+
+                    if self.fast_upsert_delta > 0.0 {
+                        let shift_sq = Self::shift_l2_sq(&old_vector.coords, &processed_vector);
+                        let old_meta = index_reader.metadata_by_id(old_internal_id);
+                        let metadata_changed = old_meta != *metadata;
+                        reindex_needed = metadata_changed
+                            || shift_sq > self.fast_upsert_delta * self.fast_upsert_delta;
+                    }
+                }
+            }
+
+            if self.config.is_gossip_enabled() {
+                let entry_hash = CollectionDigest::hash_entry(*id, &processed_vector);
+                let bucket_idx = CollectionDigest::get_bucket_index(*id);
+                self.buckets[bucket_idx].fetch_xor(entry_hash, Ordering::Relaxed);
+                self.root_hash.fetch_xor(entry_hash, Ordering::Relaxed);
+            }
+
+            // Storage
+            // insert_to_storage writes bytes to Mmap. It copies bytes, but doesn't heap allocate vector objects.
+            let internal_id = if let Some(old_id) = existing_internal_id {
+                if old_id != *id {
+                    self.ids_are_identity.store(false, Ordering::Release);
+                }
+                index_reader
+                    .update_storage(old_id, &processed_vector)
+                    .map_err(|e| self.storage_error(e))?;
+                old_id
+            } else {
+                let new_id = index_reader
+                    .insert_to_storage(&processed_vector)
+                    .map_err(|e| self.storage_error(e))?;
+
+                self.id_map.insert(*id, new_id);
+                self.reverse_id_map.insert(new_id, *id);
+                if new_id != *id {
+                    self.ids_are_identity.store(false, Ordering::Release);
+                }
+                new_id
+            };
+
+            entries.push(BatchEntry {
+                id: *id,
+                vector: processed_vector, // Moves the Cow (cheap pointer copy), not data
+                metadata,                 // Reference
+                internal_id,
+                reindex_needed,
+            });
+        }
+
+        // 3. WAL Batch
+        // Allocate here as WAL requires owned data.
+        // This is the first allocation of the vector in the Poincaré pipeline.
+        let wal_data: Vec<_> = entries
+            .iter()
+            .map(|e| (e.vector.to_vec(), e.id, e.metadata.clone()))
+            .collect();
+
+        let mut frozen_paths_opt = None;
+        let owed_sync;
+        {
+            let wal_guard = self.wal_link.load();
+            let mut wal = wal_guard.lock().await;
+            if deletes.is_empty() {
+                wal.append_batch(&wal_data, clock)
+                    .map_err(|e| self.storage_error(e.into()))?;
+            } else {
+                let ops: Vec<WalOp<'_>> = deletes
+                    .iter()
+                    .map(|&id| WalOp::Delete { id })
+                    .chain(wal_data.iter().map(|(vector, id, metadata)| WalOp::Insert {
+                        id: *id,
+                        vector,
+                        metadata,
+                    }))
+                    .collect();
+                wal.append_mixed(&ops, clock)
+                    .map_err(|e| self.storage_error(e.into()))?;
+            }
+
+            self.last_clock.fetch_max(clock, Ordering::Relaxed);
+            self.invalidate_search_cache();
+            self.snapshot_writer
+                .record_ops((deletes.len() + wal_data.len()) as u64);
+
+            owed_sync = self
+                .owed_sync(&mut wal, durability)
+                .map_err(|e| self.storage_error(e))?;
+
+            if wal.is_full() {
+                if let Ok(frozen_path) = wal.rotate() {
+                    // Reset WAL pending count as they move to next phase
+                    self.wal_pending_count.store(0, Ordering::SeqCst);
+
+                    let mut pending = self.pending_wal_flushes.lock().await;
+                    pending.push(frozen_path);
+
+                    let should_flush = match self.storage_mode {
+                        StorageMode::Tiered => {
+                            // LSM-style: Flush when MemTable exceeds memory budget
+                            let memtable_nodes = self.index_link.load().count_nodes();
+                            let memtable_budget = self.max_ram_bytes / 10;
+                            let est_memory = memtable_nodes * (N * 8 + 64);
+
+                            let should = est_memory as u64 > memtable_budget;
+
+                            // DEBUG: Log every rotation
+                            if should {
+                                println!(
+                                    "🔍 Flush Check (Tiered, batch): memtable={} vectors | est_memory={} MB | threshold={} MB | should_flush={}",
+                                    memtable_nodes,
+                                    est_memory / (1024 * 1024),
+                                    memtable_budget / (1024 * 1024),
+                                    should
+                                );
+                            }
+
+                            should
+                        }
+                        StorageMode::Performance => {
+                            // Performance Mode: NEVER flush to chunks
+                            // All data stays in RAM (MemTable) for maximum performance
+                            false
+                        }
+                    };
+
+                    if should_flush {
+                        frozen_paths_opt = Some(std::mem::take(&mut *pending));
+                    } else {
+                        println!(
+                            "📦 WAL Rotated (batch, {} pending segments), keeping MemTable HOT (Performance Mode)",
+                            pending.len()
+                        );
+                    }
+                }
+            } else {
+                self.wal_pending_count
+                    .fetch_add((deletes.len() + vectors.len()) as u64, Ordering::SeqCst);
+            }
+        }
+        self.commit_owed(owed_sync)
+            .await
+            .map_err(|e| self.storage_error(e))?;
+        self.storage_health.succeeded();
+
+        if let Some(frozen_paths) = frozen_paths_opt {
+            Self::spawn_flush_worker(
+                frozen_paths,
+                self.config.clone(),
+                self.mode,
+                self.data_dir.clone(),
+                self.flush_limiter.clone(),
+                self.meta_router.clone(),
+                self.index_link.clone(),
+                self.id_map.clone(),
+                self.reverse_id_map.clone(),
+                self.flushing_vector_count.clone(),
+                self.search_cache.clone(),
+            );
+        }
+
+        // 4. Index Queue
+        let queued = entries.iter().filter(|e| e.reindex_needed).count();
+        for _ in 0..queued {
+            self.config.inc_queue();
+        }
+        self.index_watermark.enqueue(clock, queued as u64);
+
+        // Queue for indexing (Send only lightweight metadata clone + internal_id)
+        for entry in &entries {
+            if entry.reindex_needed {
+                let _ = self
+                    .index_tx
+                    .send((entry.internal_id, entry.metadata.clone(), clock));
+            }
+        }
+
+        // 5. Replication
+        if self.replication_tx.is_active() {
+            for &id in deletes {
+                self.replication_tx.publish(ReplicationLog {
+                    logical_clock: clock,
+                    origin_node_id: self.node_id.clone(),
+                    collection: self.name.clone(),
+                    operation: Some(replication_log::Operation::Delete(DeleteOp { id })),
+                    ..Default::default()
+                });
+            }
+            for entry in entries {
+                let log = ReplicationLog {
+                    logical_clock: clock,
+                    origin_node_id: self.node_id.clone(),
+                    collection: self.name.clone(),
+                    operation: Some(replication_log::Operation::Insert(InsertOp {
+                        id: entry.id,
+                        // Convert Cow to Owned for channel transmission.
+                        vector: entry.vector.into_owned(),
+                        metadata: entry.metadata.clone(),
+                        typed_metadata: HashMap::new(),
+                    })),
+                    ..Default::default()
+                };
+                self.replication_tx.publish(log);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)] // Background worker requires all context
+    fn spawn_flush_worker(
+        frozen_wal_paths: Vec<PathBuf>,
+        config: Arc<GlobalConfig>,
+        mode: hyperspace_core::QuantizationMode,
+        data_dir: PathBuf,
+        flush_limiter: Arc<Semaphore>,
+        meta_router: Arc<MetaRouter<N>>,
+        index_link: Arc<ArcSwap<HnswIndex<N, M>>>,
+        _id_map: Arc<DashMap<u32, u32>>,
+        _reverse_id_map: Arc<DashMap<u32, u32>>,
+        flushing_vector_count: Arc<AtomicUsize>,
+        search_cache: Option<Arc<SearchCache>>,
+    ) {
+        let storage_f32_requested = std::env::var("HS_STORAGE_FLOAT32")
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"));
+        let storage_f32 = storage_f32_requested && mode == hyperspace_core::QuantizationMode::None;
+        let element_size = match mode {
+            hyperspace_core::QuantizationMode::ScalarI8 => {
+                hyperspace_core::vector::QuantizedHyperVector::<N>::SIZE
+            }
+            hyperspace_core::QuantizationMode::Binary => {
+                hyperspace_core::vector::BinaryHyperVector::<N>::SIZE
+            }
+            hyperspace_core::QuantizationMode::None => {
+                if storage_f32 {
+                    hyperspace_core::vector::HyperVectorF32::<N>::SIZE
+                } else {
+                    hyperspace_core::vector::HyperVector::<N>::SIZE
+                }
+            }
+        };
+
+        tokio::spawn(async move {
+            let permit = flush_limiter.clone().acquire_owned().await;
+            let _ = pools::spawn(Pool::Indexing, move || {
+                let _permit = permit;
+
+                println!("🔄 Flush Worker: Starting conversion of {} WAL segment(s)...", frozen_wal_paths.len());
+                let _flush_start = std::time::Instant::now();
+
+                // Track the vectors entering purgatory across all files
+                let mut total_frozen_entries = 0;
+                for path in &frozen_wal_paths {
+                    total_frozen_entries += Wal::pending_entries_at_path(path);
+                }
+                flushing_vector_count.fetch_add(total_frozen_entries as usize, Ordering::SeqCst);
+                println!("🔄 Flush Worker: Total entries to process: {total_frozen_entries}");
+
+                let chunk_id = uuid::Uuid::new_v4().to_string();
+                let chunk_name = format!("chunk_{chunk_id}.hyp");
+                let chunk_dir = data_dir.join(&chunk_name);
+
+                if let Err(e) = std::fs::create_dir_all(&chunk_dir) {
+                    eprintln!("Failed to create chunk directory {chunk_name}: {e}");
+                    return;
+                }
+
+                let temp_store = Arc::new(VectorStore::new(&chunk_dir, element_size));
+                let local_index = HnswIndex::<N, M>::new_with_storage_precision(
+                    temp_store.clone(),
+                    mode,
+                    config.clone(),
+                    storage_f32,
+                );
+
+                let mut insert_count = 0u32;
+                let mut centroid_acc = CentroidAccumulator::new(N);
+                // User ID -> chunk node, so later upserts and deletes in the
+                // segments hide earlier versions.
+                let mut chunk_ids: HashMap<u32, u32> = HashMap::new();
+
+                // Replay ALL accumulated segments into the same chunk
+                for (i, path) in frozen_wal_paths.iter().enumerate() {
+                    let replay_start = std::time::Instant::now();
+                    let replay_res = Wal::replay(path, |entry| match entry {
+                        hyperspace_store::wal::WalEntry::Insert { id, vector, metadata, .. } => {
+                            if vector.len() == N {
+                                centroid_acc.add(&vector);
+                                if let Ok(new_id) = local_index.insert_to_storage(&vector) {
+                                    let _ = local_index.index_node(new_id, metadata);
+                                    if let Some(old_id) = chunk_ids.insert(id, new_id) {
+                                        local_index.delete(old_id);
+                                    }
+                                    insert_count += 1;
+                                }
+                            }
+                        }
+                        hyperspace_store::wal::WalEntry::Delete { id, .. } => {
+                            if let Some(old_id) = chunk_ids.remove(&id) {
+                                local_index.delete(old_id);
+                            }
+                        }
+                    });
+
+                    let replay_elapsed = replay_start.elapsed();
+                    println!("🔄 Flush Worker: Replay segment {}/{} completed in {:.2}s ({} vectors)",
+                        i+1, frozen_wal_paths.len(), replay_elapsed.as_secs_f64(), insert_count);
+
+                    if replay_res.is_err() {
+                        eprintln!("⚠️ Failed to replay WAL segment {}/{} during flush. Some data may be lost or chunk will be partial.", i+1, frozen_wal_paths.len());
+                    }
+                }
 
                 if insert_count == 0 {
                     let _ = std::fs::remove_dir_all(&chunk_dir);
@@ -854,6 +1791,9 @@ impl<const N: usize, M: Metric<N>> CollectionImpl<N, M> {
                         storage_f32,
                     ));
                     index_link.store(fresh_index);
+                    if let Some(cache) = &search_cache {
+                        cache.invalidate();
+                    }
 
                     // Note: Clearing id_map was a mistake (Task 1.2 bug).
                     // We must keep all mappings for search and recall to work across segments.
@@ -898,308 +1838,96 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         metadata: HashMap<String, String>,
         clock: u64,
         durability: hyperspace_core::Durability,
-    ) -> Result<(), String> {
+    ) -> HyperspaceResult<()> {
+        self.storage_health.check(&self.name)?;
         if vector.len() != N {
-            return Err(format!(
+            return Err(HyperspaceError::Validation(format!(
                 "Vector dimension mismatch. Expected {}, got {}",
                 N,
                 vector.len()
-            ));
+            )));
         }
 
-        let processed_vector_cow = Self::normalize_if_cosine(vector);
+        self.record_drift(vector);
+        let processed_vector_cow = self.normalization.apply(vector);
         // We need a slice for ops, and maybe an owned vec for storage if new
         let processed_vector = &processed_vector_cow;
 
         // Check if this user ID already exists (for upsert)
         let existing_internal_id = self.id_map.get(&id).map(|v| *v);
+        self.limits
+            .check(self.count(), usize::from(existing_internal_id.is_none()))?;
 
         let mut reindex_needed = true;
         if let Some(old_internal_id) = existing_internal_id {
             let index = self.index_link.load();
             // Defensive: Only attempt fast-upsert and gossip-undo if vector is in the active HNSW segment.
             if (old_internal_id as usize) < index.count() {
-                let old_vector = index.get_vector(old_internal_id);
-                if self.config.is_gossip_enabled() {
-                    let old_id_hash = CollectionDigest::hash_entry(id, &old_vector.coords);
-                    let bucket_idx = CollectionDigest::get_bucket_index(id);
-                    self.buckets[bucket_idx].fetch_xor(old_id_hash, Ordering::Relaxed);
-                    self.root_hash.fetch_xor(old_id_hash, Ordering::Relaxed);
-                }
-
-                if self.fast_upsert_delta > 0.0 {
-                    let shift_sq = Self::shift_l2_sq(&old_vector.coords, processed_vector);
-                    let old_meta = index.metadata_by_id(old_internal_id);
-                    let metadata_changed = old_meta != metadata;
-                    reindex_needed = metadata_changed
-                        || shift_sq > self.fast_upsert_delta * self.fast_upsert_delta;
-                }
-            }
-        }
-
-        if self.config.is_gossip_enabled() {
-            let entry_hash = CollectionDigest::hash_entry(id, processed_vector);
-            let bucket_idx = CollectionDigest::get_bucket_index(id);
-            self.buckets[bucket_idx].fetch_xor(entry_hash, Ordering::Relaxed);
-            self.root_hash.fetch_xor(entry_hash, Ordering::Relaxed);
-        }
-
-        let internal_id = if let Some(old_id) = existing_internal_id {
-            if old_id != id {
-                self.ids_are_identity.store(false, Ordering::Release);
-            }
-            self.index_link
-                .load()
-                .update_storage(old_id, processed_vector)
-                .map_err(|e| e.clone())?;
-            old_id
-        } else {
-            let new_id = self
-                .index_link
-                .load()
-                .insert_to_storage(processed_vector)
-                .map_err(|e| e.clone())?;
-            self.id_map.insert(id, new_id);
-            self.reverse_id_map.insert(new_id, id);
-            if new_id != id {
-                self.ids_are_identity.store(false, Ordering::Release);
-            }
-            new_id
-        };
-
-        let mut frozen_paths_opt = None;
-        {
-            let wal_guard = self.wal_link.load();
-            let mut wal = wal_guard.lock().await;
-
-            // Use User ID for WAL to support replication/restore
-            wal.append(id, processed_vector, &metadata, clock)
-                .map_err(|e| format!("WAL Error: {e}"))?;
-
-            self.last_clock.fetch_max(clock, Ordering::Relaxed);
-
-            if durability == hyperspace_core::Durability::Strict {
-                wal.sync().map_err(|e| format!("WAL Sync Error: {e}"))?;
-            }
-
-            if wal.is_full() {
-                if let Ok(frozen_path) = wal.rotate() {
-                    // Reset WAL pending count as they move to next phase
-                    self.wal_pending_count.store(0, Ordering::SeqCst);
-
-                    let mut pending = self.pending_wal_flushes.lock().await;
-                    pending.push(frozen_path);
-
-                    let should_flush = match self.storage_mode {
-                        StorageMode::Tiered => {
-                            // LSM-style: Flush when MemTable exceeds memory budget
-                            let memtable_nodes = self.index_link.load().count_nodes();
-                            let memtable_budget = self.max_ram_bytes / 10;
-                            let est_memory = memtable_nodes * (N * 8 + 64);
-
-                            let should = est_memory as u64 > memtable_budget;
-
-                            // DEBUG: Log every rotation
-                            if should {
-                                println!(
-                                    "🔍 Flush Check (Tiered): memtable={} vectors | est_memory={} MB | threshold={} MB | should_flush={}",
-                                    memtable_nodes,
-                                    est_memory / (1024 * 1024),
-                                    memtable_budget / (1024 * 1024),
-                                    should
-                                );
-                            }
-
-                            should
-                        }
-                        StorageMode::Performance => {
-                            // Performance Mode: NEVER flush to chunks
-                            // All data stays in RAM (MemTable) for maximum performance
-                            // Persistence is handled by snapshots only
-                            false
-                        }
-                    };
-
-                    if should_flush {
-                        // Take all pending segments to flush into one chunk
-                        frozen_paths_opt = Some(std::mem::take(&mut *pending));
-                    } else {
-                        println!(
-                            "📦 WAL Rotated ({} pending segments), keeping MemTable HOT (Performance Mode)", 
-                            pending.len()
-                        );
-                    }
-                }
-            } else {
-                self.wal_pending_count.fetch_add(1, Ordering::SeqCst);
-            }
-        }
-
-        if let Some(frozen_paths) = frozen_paths_opt {
-            Self::spawn_flush_worker(
-                frozen_paths,
-                self.config.clone(),
-                self.mode,
-                self.data_dir.clone(),
-                self.flush_limiter.clone(),
-                self.meta_router.clone(),
-                self.index_link.clone(),
-                self.id_map.clone(),
-                self.reverse_id_map.clone(),
-                self.flushing_vector_count.clone(),
-            );
-        }
-
-        if reindex_needed {
-            self.config.inc_queue();
-            let queue_size = self.config.get_queue_size();
-
-            // Debug: Log queue buildup
-            if queue_size > 10_000 && queue_size.is_multiple_of(5_000) {
-                let active = self.config.active_indexing.load(Ordering::Relaxed);
-                println!("⚠️  Index queue building up: {queue_size} pending, {active} active");
-            }
-
-            let _ = self.index_tx.send((internal_id, metadata.clone()));
-        }
-
-        if self.replication_tx.receiver_count() > 0 {
-            // Need owned vector for replication
-            let vector_owned = processed_vector_cow.into_owned();
-            let log = ReplicationLog {
-                logical_clock: clock,
-                origin_node_id: self.node_id.clone(),
-                collection: self.name.clone(),
-                operation: Some(replication_log::Operation::Insert(InsertOp {
-                    id,
-                    vector: vector_owned,
-                    metadata,
-                    typed_metadata: HashMap::new(),
-                })),
-            };
-            let _ = self.replication_tx.send(log);
-        }
-
-        Ok(())
-    }
-
-    async fn insert_batch(
-        &self,
-        vectors: Vec<(Vec<f64>, u32, HashMap<String, String>)>,
-        clock: u64,
-        durability: hyperspace_core::Durability,
-    ) -> Result<(), String> {
-        // 1. Validation
-        for (vec, _, _) in &vectors {
-            if vec.len() != N {
-                return Err(format!(
-                    "Vector dimension mismatch. Expected {}, got {}",
-                    N,
-                    vec.len()
-                ));
-            }
-        }
-
-        // Optimization: Use lifetime to hold reference to input vectors to avoid allocation.
-
-        let mut entries = Vec::with_capacity(vectors.len());
-
-        // 2. Process Logic (Zero-Copy Path)
-        // Note: Iterate by reference to preserve original data lifetimes.
-
-        // HOISTED LOCK: Load the index pointer to avoid taking the RwLock for every item.
-        // ArcSwap provides zero-contention access to the index.
-        let index_reader = self.index_link.load();
-
-        for (vector, id, metadata) in &vectors {
-            // Returns Borrowed for Poincare (No Allocation)
-            let processed_vector = Self::normalize_if_cosine(vector);
-
-            // Check existing
-            let existing_internal_id = self.id_map.get(id).map(|v| *v);
-
-            // Bucket updates (Read-only access to vector)
-            let mut reindex_needed = true;
-            if let Some(old_internal_id) = existing_internal_id {
-                // Defensive: Only attempt fast-upsert and gossip-undo if vector is in the active HNSW segment.
-                if (old_internal_id as usize) < index_reader.count() {
-                    let old_vector = index_reader.get_vector(old_internal_id);
-                    if self.config.is_gossip_enabled() {
-                        let old_id_hash = CollectionDigest::hash_entry(*id, &old_vector.coords);
-                        let bucket_idx = CollectionDigest::get_bucket_index(*id);
-                        self.buckets[bucket_idx].fetch_xor(old_id_hash, Ordering::Relaxed);
-                        self.root_hash.fetch_xor(old_id_hash, Ordering::Relaxed);
-                    }
-
-                    if self.fast_upsert_delta > 0.0 {
-                        let shift_sq = Self::shift_l2_sq(&old_vector.coords, &processed_vector);
-                        let old_meta = index_reader.metadata_by_id(old_internal_id);
-                        let metadata_changed = old_meta != *metadata;
-                        reindex_needed = metadata_changed
-                            || shift_sq > self.fast_upsert_delta * self.fast_upsert_delta;
-                    }
-                }
-            }
-
-            if self.config.is_gossip_enabled() {
-                let entry_hash = CollectionDigest::hash_entry(*id, &processed_vector);
-                let bucket_idx = CollectionDigest::get_bucket_index(*id);
-                self.buckets[bucket_idx].fetch_xor(entry_hash, Ordering::Relaxed);
-                self.root_hash.fetch_xor(entry_hash, Ordering::Relaxed);
-            }
-
-            // Storage
-            // insert_to_storage writes bytes to Mmap. It copies bytes, but doesn't heap allocate vector objects.
-            let internal_id = if let Some(old_id) = existing_internal_id {
-                if old_id != *id {
-                    self.ids_are_identity.store(false, Ordering::Release);
+                let old_vector = index.get_vector(old_internal_id);
+                if self.config.is_gossip_enabled() {
+                    let old_id_hash = CollectionDigest::hash_entry(id, &old_vector.coords);
+                    let bucket_idx = CollectionDigest::get_bucket_index(id);
+                    self.buckets[bucket_idx].fetch_xor(old_id_hash, Ordering::Relaxed);
+                    self.root_hash.fetch_xor(old_id_hash, Ordering::Relaxed);
                 }
-                index_reader
-                    .update_storage(old_id, &processed_vector)
-                    .map_err(|e| e.clone())?;
-                old_id
-            } else {
-                let new_id = index_reader
-                    .insert_to_storage(&processed_vector)
-                    .map_err(|e| e.clone())?;
 
-                self.id_map.insert(*id, new_id);
-                self.reverse_id_map.insert(new_id, *id);
-                if new_id != *id {
-                    self.ids_are_identity.store(false, Ordering::Release);
+                if self.fast_upsert_delta > 0.0 {
+                    let shift_sq = Self::shift_l2_sq(&old_vector.coords, processed_vector);
+                    let old_meta = index.metadata_by_id(old_internal_id);
+                    let metadata_changed = old_meta != metadata;
+                    reindex_needed = metadata_changed
+                        || shift_sq > self.fast_upsert_delta * self.fast_upsert_delta;
                 }
-                new_id
-            };
+            }
+        }
 
-            entries.push(BatchEntry {
-                id: *id,
-                vector: processed_vector, // Moves the Cow (cheap pointer copy), not data
-                metadata,                 // Reference
-                internal_id,
-                reindex_needed,
-            });
+        if self.config.is_gossip_enabled() {
+            let entry_hash = CollectionDigest::hash_entry(id, processed_vector);
+            let bucket_idx = CollectionDigest::get_bucket_index(id);
+            self.buckets[bucket_idx].fetch_xor(entry_hash, Ordering::Relaxed);
+            self.root_hash.fetch_xor(entry_hash, Ordering::Relaxed);
         }
 
-        // 3. WAL Batch
-        // Allocate here as WAL requires owned data.
-        // This is the first allocation of the vector in the Poincaré pipeline.
-        let wal_data: Vec<_> = entries
-            .iter()
-            .map(|e| (e.vector.to_vec(), e.id, e.metadata.clone()))
-            .collect();
+        let internal_id = if let Some(old_id) = existing_internal_id {
+            if old_id != id {
+                self.ids_are_identity.store(false, Ordering::Release);
+            }
+            self.index_link
+                .load()
+                .update_storage(old_id, processed_vector)
+                .map_err(|e| self.storage_error(e))?;
+            old_id
+        } else {
+            let new_id = self
+                .index_link
+                .load()
+                .insert_to_storage(processed_vector)
+                .map_err(|e| self.storage_error(e))?;
+            self.id_map.insert(id, new_id);
+            self.reverse_id_map.insert(new_id, id);
+            if new_id != id {
+                self.ids_are_identity.store(false, Ordering::Release);
+            }
+            new_id
+        };
 
         let mut frozen_paths_opt = None;
+        let owed_sync;
         {
             let wal_guard = self.wal_link.load();
             let mut wal = wal_guard.lock().await;
-            wal.append_batch(&wal_data, clock)
-                .map_err(|e| e.to_string())?;
+
+            // Use User ID for WAL to support replication/restore
+            wal.append(id, processed_vector, &metadata, clock)
+                .map_err(|e| self.storage_error(e.into()))?;
 
             self.last_clock.fetch_max(clock, Ordering::Relaxed);
+            self.invalidate_search_cache();
+            self.snapshot_writer.record_ops(1);
 
-            if durability == hyperspace_core::Durability::Strict {
-                wal.sync().map_err(|e| e.to_string())?;
-            }
+            owed_sync = self
+                .owed_sync(&mut wal, durability)
+                .map_err(|e| self.storage_error(e))?;
 
             if wal.is_full() {
                 if let Ok(frozen_path) = wal.rotate() {
@@ -1221,7 +1949,7 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
                             // DEBUG: Log every rotation
                             if should {
                                 println!(
-                                    "🔍 Flush Check (Tiered, batch): memtable={} vectors | est_memory={} MB | threshold={} MB | should_flush={}",
+                                    "🔍 Flush Check (Tiered): memtable={} vectors | est_memory={} MB | threshold={} MB | should_flush={}",
                                     memtable_nodes,
                                     est_memory / (1024 * 1024),
                                     memtable_budget / (1024 * 1024),
@@ -1234,24 +1962,29 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
                         StorageMode::Performance => {
                             // Performance Mode: NEVER flush to chunks
                             // All data stays in RAM (MemTable) for maximum performance
+                            // Persistence is handled by snapshots only
                             false
                         }
                     };
 
                     if should_flush {
+                        // Take all pending segments to flush into one chunk
                         frozen_paths_opt = Some(std::mem::take(&mut *pending));
                     } else {
                         println!(
-                            "📦 WAL Rotated (batch, {} pending segments), keeping MemTable HOT (Performance Mode)",
+                            "📦 WAL Rotated ({} pending segments), keeping MemTable HOT (Performance Mode)", 
                             pending.len()
                         );
                     }
                 }
             } else {
-                self.wal_pending_count
-                    .fetch_add(vectors.len() as u64, Ordering::SeqCst);
+                self.wal_pending_count.fetch_add(1, Ordering::SeqCst);
             }
         }
+        self.commit_owed(owed_sync)
+            .await
+            .map_err(|e| self.storage_error(e))?;
+        self.storage_health.succeeded();
 
         if let Some(frozen_paths) = frozen_paths_opt {
             Self::spawn_flush_worker(
@@ -1265,302 +1998,236 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
                 self.id_map.clone(),
                 self.reverse_id_map.clone(),
                 self.flushing_vector_count.clone(),
+                self.search_cache.clone(),
             );
         }
 
-        // 4. Index Queue
-        for _ in 0..entries.iter().filter(|e| e.reindex_needed).count() {
+        if reindex_needed {
             self.config.inc_queue();
-        }
-
-        // Queue for indexing (Send only lightweight metadata clone + internal_id)
-        for entry in &entries {
-            if entry.reindex_needed {
-                let _ = self
-                    .index_tx
-                    .send((entry.internal_id, entry.metadata.clone()));
-            }
-        }
+            let queue_size = self.config.get_queue_size();
 
-        // 5. Replication
-        if self.replication_tx.receiver_count() > 0 {
-            for entry in entries {
-                let log = ReplicationLog {
-                    logical_clock: clock,
-                    origin_node_id: self.node_id.clone(),
-                    collection: self.name.clone(),
-                    operation: Some(replication_log::Operation::Insert(InsertOp {
-                        id: entry.id,
-                        // Convert Cow to Owned for channel transmission.
-                        vector: entry.vector.into_owned(),
-                        metadata: entry.metadata.clone(),
-                        typed_metadata: HashMap::new(),
-                    })),
-                };
-                let _ = self.replication_tx.send(log);
+            // Debug: Log queue buildup
+            if queue_size > 10_000 && queue_size.is_multiple_of(5_000) {
+                let active = self.config.active_indexing.load(Ordering::Relaxed);
+                println!("⚠️  Index queue building up: {queue_size} pending, {active} active");
             }
-        }
-
-        Ok(())
-    }
 
-    fn delete(&self, id: u32) -> Result<(), String> {
-        let internal_id = if let Some((_, internal_id)) = self.id_map.remove(&id) {
-            self.reverse_id_map.remove(&internal_id);
-            internal_id
+            self.index_watermark.enqueue(clock, 1);
+            let _ = self.index_tx.send((internal_id, metadata.clone(), clock));
         } else {
-            id
-        };
-
-        let idx = self.index_link.load();
-        if self.config.is_gossip_enabled() {
-            // Defensive check: only update if ID is within bounds of active index
-            if (internal_id as usize) < idx.count() {
-                let vector = idx.get_vector(internal_id);
-                let hash = CollectionDigest::hash_entry(id, &vector.coords);
-                let b_idx = CollectionDigest::get_bucket_index(id);
-
-                self.buckets[b_idx].fetch_xor(hash, Ordering::Relaxed);
-                self.root_hash.fetch_xor(hash, Ordering::Relaxed);
-            }
-        }
-
-        idx.delete(internal_id);
-        Ok(())
-    }
-
-    async fn search(
-        &self,
-        query: &[f64],
-        filters: &HashMap<String, String>,
-        complex_filters: &[FilterExpr],
-        params: &SearchParams,
-    ) -> Result<Vec<SearchResult>, String> {
-        if query.len() != N {
-            return Err(format!(
-                "Query dimension mismatch. Expected {}, got {}",
-                N,
-                query.len()
-            ));
+            self.index_watermark.applied(clock);
         }
 
-        // Quick Win #5: Zero-copy normalization - keep Cow until absolutely necessary
-        let processed_query_cow = Self::normalize_if_cosine(query);
-
-        let index_link = self.index_link.clone();
-        let reverse_id_map = self.reverse_id_map.clone();
-        let ids_are_identity = self.ids_are_identity.load(Ordering::Acquire);
-
-        // Move only the required fields to avoid cloning whole params struct.
-        let top_k = params.top_k;
-        let ef_search = params.ef_search;
-        let rerank_enabled = std::env::var("HS_RERANK_ENABLED")
-            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"));
-        let rerank_oversample = std::env::var("HS_RERANK_OVERSAMPLE")
-            .ok()
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(4)
-            .max(1);
-        let use_wasserstein = params.use_wasserstein;
-        let filters_owned = (!filters.is_empty()).then(|| filters.clone());
-        let complex_filters_owned = (!complex_filters.is_empty()).then(|| complex_filters.to_vec());
-        let meta_router_ref = self.meta_router.clone();
-        let mode_for_search = self.mode;
-        let config_for_search = self.config.clone();
-        let permit = self
-            .search_limiter
-            .clone()
-            .acquire_owned()
-            .await
-            .map_err(|e| format!("Search limiter failed: {e}"))?;
-
-        // Quick Win: For small top_k, run search inline to avoid spawn_blocking overhead
-        let use_blocking = top_k > 50 || rerank_enabled;
-
-        if use_blocking {
-            // Convert to owned only when entering blocking task
-            let processed_query = processed_query_cow.into_owned();
-            let mut search_params_owned = params.clone();
-            tokio::task::spawn_blocking(move || {
-                let _permit = permit;
-                let index = index_link.load();
-                let include_metadata = index.has_nonempty_metadata();
-                let filters_ref = filters_owned.as_ref().unwrap_or(&EMPTY_LEGACY_FILTERS);
-                let complex_filters_ref = complex_filters_owned
-                    .as_ref()
-                    .map_or(EMPTY_COMPLEX_FILTERS.as_slice(), Vec::as_slice);
-                let search_k = if rerank_enabled {
-                    top_k.saturating_mul(rerank_oversample).max(top_k)
-                } else {
-                    top_k
-                };
-
-                search_params_owned.top_k = search_k;
-                let mem_results = index.search(
-                    &processed_query,
-                    filters_ref,
-                    complex_filters_ref,
-                    &search_params_owned,
-                );
-
-                // === 2. Search cold chunks via MetaRouter (disk mmap) ===
-                let probe_k = std::env::var("HS_CHUNK_PROBE_K")
-                    .ok()
-                    .and_then(|v| v.parse::<usize>().ok())
-                    .unwrap_or(3);
-                let routed_chunks = meta_router_ref.route(&processed_query, probe_k);
-                let chunk_dirs: Vec<std::path::PathBuf> = routed_chunks
-                    .iter()
-                    .map(|(_, path, _)| path.clone())
-                    .collect();
-
-                let chunk_results = if chunk_dirs.is_empty() {
-                    Vec::new()
-                } else {
-                    chunk_searcher::scatter_gather_search::<N, M>(
-                        &chunk_dirs,
-                        &processed_query,
-                        search_k,
-                        ef_search,
-                        filters_ref,
-                        complex_filters_ref,
-                        mode_for_search,
-                        &config_for_search,
-                        use_wasserstein,
-                    )
-                };
-
-                // === 3. Merge MemTable + Chunk results by distance ===
-                // MemTable results carry real internal IDs.
-                // Chunk results carry chunk-local IDs (not usable for metadata).
-                // We merge by distance only, preferring MemTable entries for metadata.
-                let mut merged: Vec<(u32, f64, bool)> =
-                    Vec::with_capacity(mem_results.len() + chunk_results.len());
-
-                for (id, dist) in &mem_results {
-                    merged.push((*id, *dist, true)); // true = from MemTable
-                }
-                for (_, dist, _) in &chunk_results {
-                    // Chunk results don't have usable IDs for the collection's id_map.
-                    // We use u32::MAX as a sentinel — they'll be filtered in metadata step.
-                    merged.push((u32::MAX, *dist, false)); // false = from chunk
-                }
-
-                merged.sort_by(|a, b| a.1.total_cmp(&b.1));
-                merged.truncate(search_k);
-
-                // Include all results (RAM + Chunks).
-                // Note: Results from chunks will have internal IDs that need a segment mapping.
-                let results: Vec<(u32, f64)> =
-                    merged.into_iter().map(|(id, dist, _)| (id, dist)).collect();
-
-                let metric_tag = match M::name() {
-                    "cosine" => GpuMetric::Cosine,
-                    "poincare" => GpuMetric::Poincare,
-                    "lorentz" => GpuMetric::Lorentz,
-                    _ => GpuMetric::L2,
-                };
+        if self.replication_tx.is_active() {
+            // Need owned vector for replication
+            let vector_owned = processed_vector_cow.into_owned();
+            let log = ReplicationLog {
+                logical_clock: clock,
+                origin_node_id: self.node_id.clone(),
+                collection: self.name.clone(),
+                operation: Some(replication_log::Operation::Insert(InsertOp {
+                    id,
+                    vector: vector_owned,
+                    metadata,
+                    typed_metadata: HashMap::new(),
+                })),
+                ..Default::default()
+            };
+            self.replication_tx.publish(log);
+        }
 
-                let reranked_internal: Vec<(u32, f64)> = if rerank_enabled && !results.is_empty() {
-                    let candidate_ids: Vec<u32> = results.iter().map(|(id, _)| *id).collect();
-                    let candidate_vectors: Vec<Vec<f64>> = candidate_ids
-                        .iter()
-                        .map(|id| index.get_vector(*id).coords.to_vec())
-                        .collect();
-                    let candidate_refs: Vec<&[f64]> =
-                        candidate_vectors.iter().map(Vec::as_slice).collect();
-                    rerank_topk_exact(
-                        metric_tag,
-                        &processed_query,
-                        &candidate_ids,
-                        &candidate_refs,
-                    )
-                } else {
-                    results
-                };
+        Ok(())
+    }
 
-                // Fetch metadata and convert IDs inside blocking worker.
-                reranked_internal
-                    .into_iter()
-                    .take(top_k)
-                    .map(|(internal_id, dist)| {
-                        let meta = if include_metadata {
-                            index
-                                .metadata
-                                .forward
-                                .get(&internal_id)
-                                .map(|m| m.clone())
-                                .unwrap_or_default()
-                        } else {
-                            HashMap::new()
-                        };
+    async fn insert_batch(
+        &self,
+        vectors: Vec<(Vec<f64>, u32, HashMap<String, String>)>,
+        clock: u64,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<()> {
+        self.write_batch(&[], vectors, clock, durability).await
+    }
 
-                        let user_id = if ids_are_identity {
-                            internal_id
-                        } else {
-                            reverse_id_map.get(&internal_id).map_or(internal_id, |v| *v)
-                        };
+    async fn apply_batch(
+        &self,
+        deletes: Vec<u32>,
+        vectors: Vec<(Vec<f64>, u32, HashMap<String, String>)>,
+        clock: u64,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<()> {
+        self.write_batch(&deletes, vectors, clock, durability).await
+    }
 
-                        (user_id, dist, meta)
-                    })
-                    .collect::<Vec<SearchResult>>()
-            })
+    async fn delete(&self, id: u32, clock: u64) -> HyperspaceResult<()> {
+        self.storage_health.check(&self.name)?;
+        // Log the tombstone first so a crash before the next snapshot
+        // cannot resurrect the point on replay.
+        let owed_sync = {
+            let wal_guard = self.wal_link.load();
+            let mut wal = wal_guard.lock().await;
+            wal.append_delete(id, clock)
+                .map_err(|e| self.storage_error(e.into()))?;
+            self.last_clock.fetch_max(clock, Ordering::Relaxed);
+            self.wal_pending_count.fetch_add(1, Ordering::SeqCst);
+            self.owed_sync(&mut wal, hyperspace_core::Durability::Default)
+                .map_err(|e| self.storage_error(e))?
+        };
+        self.commit_owed(owed_sync)
             .await
-            .map_err(|e| format!("Search task failed: {e}"))
-        } else {
-            // Quick Win: Inline search for small top_k - avoid spawn_blocking overhead
-            // Still need to convert Cow to owned for HNSW search
-            let processed_query = processed_query_cow.into_owned();
-            let _permit = permit;
-            let index = index_link.load();
-            let include_metadata = index.has_nonempty_metadata();
-            let filters_ref = filters_owned.as_ref().unwrap_or(&EMPTY_LEGACY_FILTERS);
-            let complex_filters_ref = complex_filters_owned
-                .as_ref()
-                .map_or(EMPTY_COMPLEX_FILTERS.as_slice(), Vec::as_slice);
+            .map_err(|e| self.storage_error(e))?;
+        self.storage_health.succeeded();
 
-            // === 1. Search the hot MemTable (in-RAM HNSW) ===
-            let mem_results =
-                index.search(&processed_query, filters_ref, complex_filters_ref, params);
+        self.remove_point(id);
+        self.invalidate_search_cache();
+        self.index_watermark.applied(clock);
+        self.snapshot_writer.record_ops(1);
+        Ok(())
+    }
 
-            // === 2. Search cold chunks (skip for small queries - assume hot data) ===
-            // Skip chunk search for small top_k to reduce latency
+    async fn update_vector_delta(
+        &self,
+        id: u32,
+        indices: &[u32],
+        values: &[f64],
+        clock: u64,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<()> {
+        if indices.len() != values.len() {
+            return Err(HyperspaceError::Validation(format!(
+                "Delta has {} indices but {} values",
+                indices.len(),
+                values.len()
+            )));
+        }
+        // Only full-precision vectors read back exactly; a quantized or
+        // zonal copy would write its rounding into every other coordinate.
+        if self.mode != hyperspace_core::QuantizationMode::None || self.index_link.load().zonal {
+            return Err(HyperspaceError::Validation(
+                "Delta updates need full-precision storage (quantization 'none'); send a full insert"
+                    .into(),
+            ));
+        }
+        let _guard = self.delta_locks[id as usize % DELTA_LOCK_STRIPES]
+            .lock()
+            .await;
+        let Some(internal_id) = self.id_map.get(&id).map(|v| *v) else {
+            return Err(HyperspaceError::NotFound(format!("Point {id}")));
+        };
 
-            // === 3. Convert results ===
-            let results: Vec<SearchResult> = mem_results
-                .into_iter()
-                .take(top_k)
-                .map(|(internal_id, dist)| {
-                    let meta = if include_metadata {
-                        index
-                            .metadata
-                            .forward
-                            .get(&internal_id)
-                            .map(|m| m.clone())
-                            .unwrap_or_default()
-                    } else {
-                        HashMap::new()
-                    };
+        let (mut coords, metadata) = {
+            let index = self.index_link.load();
+            // Flushed chunks are read-only; only the active segment can be patched.
+            if internal_id as usize >= index.count() {
+                return Err(HyperspaceError::Validation(format!(
+                    "Point {id} is not in the active segment; send a full insert"
+                )));
+            }
+            (
+                index.get_vector(internal_id).coords,
+                index.metadata_by_id(internal_id),
+            )
+        };
+        for (&i, &value) in indices.iter().zip(values) {
+            let Some(slot) = coords.get_mut(i as usize) else {
+                return Err(HyperspaceError::Validation(format!(
+                    "Delta index {i} out of range for dimension {N}"
+                )));
+            };
+            *slot = value;
+        }
 
-                    let user_id = if ids_are_identity {
-                        internal_id
-                    } else {
-                        reverse_id_map.get(&internal_id).map_or(internal_id, |v| *v)
-                    };
+        // The regular upsert path writes the WAL, patches storage in place
+        // and relinks only when the shift exceeds HS_FAST_UPSERT_DELTA.
+        self.insert(&coords, id, metadata, clock, durability).await
+    }
 
-                    (user_id, dist, meta)
-                })
-                .collect();
+    async fn insert_f32(
+        &self,
+        vector: &[f32],
+        id: u32,
+        metadata: HashMap<String, String>,
+        clock: u64,
+        durability: hyperspace_core::Durability,
+    ) -> HyperspaceResult<()> {
+        let widened = Self::widen_f32(vector)?;
+        self.insert(&widened, id, metadata, clock, durability).await
+    }
 
-            Ok(results)
+    async fn search_f32(
+        &self,
+        query: &[f32],
+        filters: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<Vec<SearchResult>> {
+        let widened = Self::widen_f32(query)?;
+        self.search(&widened, filters, complex_filters, params)
+            .await
+    }
+
+    async fn search(
+        &self,
+        query: &[f64],
+        filters: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<Vec<SearchResult>> {
+        let Some(cache) = &self.search_cache else {
+            let (results, _) = self
+                .search_uncached(query, filters, complex_filters, params)
+                .await?;
+            return Ok(results);
+        };
+        let key = SearchCache::key(query, filters, complex_filters, params);
+        // A traced search (explain, slow-query sampling) has to run to fill
+        // its trace; its results still refresh the cache.
+        if params.trace.is_none() {
+            if let Some(hit) = cache.get(key) {
+                return Ok(hit);
+            }
         }
+        // Capture the epoch before searching so a concurrent write discards this fill.
+        let epoch = cache.epoch();
+        let (results, full_ef) = self
+            .search_uncached(query, filters, complex_filters, params)
+            .await?;
+        // Results of a load-lowered ef would outlive the load in the cache.
+        if full_ef {
+            cache.put(key, epoch, &results);
+        }
+        Ok(results)
     }
 
-    async fn optimize(&self) -> Result<(), String> {
+    async fn optimize(&self) -> HyperspaceResult<()> {
         self.optimize_with_filter(None).await
     }
 
-    async fn optimize_with_filter(&self, filter: Option<VacuumFilterQuery>) -> Result<(), String> {
+    async fn snapshot(&self) -> HyperspaceResult<()> {
+        let writer = self.snapshot_writer.clone();
+        pools::spawn(Pool::Indexing, move || writer.write())
+            .await
+            .map_err(|e| HyperspaceError::Internal(format!("Snapshot task failed: {e}")))??;
+        self.tokens.compact()?;
+        Ok(())
+    }
+
+    async fn set_wal_sync_mode(&self, mode: hyperspace_core::Durability) -> HyperspaceResult<()> {
+        use hyperspace_store::wal::WalSyncMode;
+        let mode = match mode {
+            hyperspace_core::Durability::Default => WalSyncMode::from_env(),
+            hyperspace_core::Durability::Async => WalSyncMode::Async,
+            hyperspace_core::Durability::Batch => WalSyncMode::Batch,
+            hyperspace_core::Durability::Strict => WalSyncMode::Strict,
+        };
+        let wal_guard = self.wal_link.load();
+        wal_guard.lock().await.set_mode(mode)?;
+        Ok(())
+    }
+
+    async fn optimize_with_filter(
+        &self,
+        filter: Option<VacuumFilterQuery>,
+    ) -> HyperspaceResult<()> {
         println!("🧹 Starting Hot Vacuum for '{}'...", self.name);
         let start = std::time::Instant::now();
         // Removed unused name
@@ -1571,7 +2238,7 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         let filter_for_vacuum = filter.clone();
 
         // Run heavy lifting in blocking thread
-        let (new_index_arc, temp_dir, new_snap_path) = tokio::task::spawn_blocking(move || {
+        let (new_index_arc, temp_dir, new_snap_path) = pools::spawn(Pool::Indexing, move || {
             use hyperspace_core::config::GlobalConfig;
             use hyperspace_store::VectorStore;
             use std::path::PathBuf;
@@ -1585,7 +2252,8 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
             let count = all_data.len();
 
             if count == 0 {
-                return Ok((None, PathBuf::new(), PathBuf::new())); // Nothing to do
+                return Ok::<_, HyperspaceError>((None, PathBuf::new(), PathBuf::new()));
+                // Nothing to do
             }
 
             // 2. Setup "Turbo Mode"
@@ -1596,14 +2264,19 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
             vacuum_config.set_m(vacuum_m);
             vacuum_config.set_ef_construction(vacuum_ef);
             vacuum_config.set_ef_search(original_config.get_ef_search());
+            vacuum_config
+                .set_extend_candidates_enabled(original_config.is_extend_candidates_enabled());
+            vacuum_config.set_keep_pruned_enabled(original_config.is_keep_pruned_enabled());
+            vacuum_config.set_entry_points(original_config.get_entry_points());
+            vacuum_config.set_entry_probes(original_config.get_entry_probes());
+            vacuum_config
+                .set_filter_bruteforce_threshold(original_config.get_filter_bruteforce_threshold());
 
             println!("   Building Shadow Index (M={vacuum_m}, EF={vacuum_ef})...");
 
             // 3. Create temp storage
             let temp_dir = data_dir.join(format!("idx_opt_{}", uuid::Uuid::new_v4()));
-            if let Err(e) = std::fs::create_dir_all(&temp_dir) {
-                return Err(e.to_string());
-            }
+            std::fs::create_dir_all(&temp_dir)?;
 
             let element_size = match mode {
                 hyperspace_core::QuantizationMode::ScalarI8 => {
@@ -1629,27 +2302,33 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
 
             // Save to disk
             let new_snap_path = data_dir.join("index.snap.new");
-            if let Err(e) = new_index.save_snapshot(&new_snap_path) {
-                return Err(e.clone());
-            }
+            new_index.save_snapshot(&new_snap_path)?;
 
             Ok((Some(Arc::new(new_index)), temp_dir, new_snap_path))
         })
         .await
-        .map_err(|e| e.to_string())??;
+        .map_err(|e| HyperspaceError::Internal(e.to_string()))??;
 
         if let Some(new_index) = new_index_arc {
             // 5. Hot Swap
             {
                 println!("🔄 Swapping indexes in memory...");
-                self.index_link.store(new_index);
+                let old_index = self.index_link.swap(new_index);
+                self.invalidate_search_cache();
+                // Searches still holding the old graph release it when they
+                // finish; its pages then decay back to the OS on their own
+                drop(old_index);
             }
 
             // 6. Finalize on disk
             let snap_path = self.data_dir.join("index.snap");
             // Rename overwrites
-            std::fs::rename(&new_snap_path, &snap_path).map_err(|e| e.to_string())?;
+            std::fs::rename(&new_snap_path, &snap_path)?;
+            self.snapshot_writer.discard_metadata()?;
             std::fs::remove_dir_all(&temp_dir).ok();
+            if let Err(e) = crate::allocator::purge() {
+                eprintln!("⚠️ Vacuum of '{}' could not purge memory: {e}", self.name);
+            }
 
             println!(
                 "✨ Vacuum Complete in {:?}. Recall upgraded.",
@@ -1697,7 +2376,16 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         self.config.get_queue_size()
     }
 
-    fn graph_neighbors(&self, id: u32, layer: usize, limit: usize) -> Result<Vec<u32>, String> {
+    fn indexing_progress(&self) -> IndexingProgress {
+        let (indexed_clock, queue_depth) = self.index_watermark.get();
+        IndexingProgress {
+            indexed_clock,
+            written_clock: self.last_clock.load(Ordering::Relaxed),
+            queue_depth,
+        }
+    }
+
+    fn graph_neighbors(&self, id: u32, layer: usize, limit: usize) -> HyperspaceResult<Vec<u32>> {
         let internal_id = self.to_internal_id(id);
         let neighbors = self
             .index_link
@@ -1710,7 +2398,7 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         &self,
         source_id: u32,
         neighbor_ids: &[u32],
-    ) -> Result<Vec<f64>, String> {
+    ) -> HyperspaceResult<Vec<f64>> {
         let idx = self.index_link.load();
         let source_internal_id = self.to_internal_id(source_id);
         let source = idx.get_vector(source_internal_id);
@@ -1731,7 +2419,7 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         layer: usize,
         max_depth: usize,
         max_nodes: usize,
-    ) -> Result<Vec<u32>, String> {
+    ) -> HyperspaceResult<Vec<u32>> {
         let internal_start = self.to_internal_id(start_id);
         let traversed =
             self.index_link
@@ -1740,13 +2428,38 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         Ok(traversed.into_iter().map(|n| self.to_user_id(n)).collect())
     }
 
+    fn graph_max_layer(&self) -> usize {
+        self.index_link.load().max_layer()
+    }
+
+    fn graph_layer(&self, layer: usize) -> HyperspaceResult<GraphLayer> {
+        let idx = self.index_link.load();
+        if layer > idx.max_layer() {
+            return Err(HyperspaceError::Validation(format!(
+                "Layer {layer} is above the top layer {}",
+                idx.max_layer()
+            )));
+        }
+        Ok(idx
+            .graph_layer(layer)
+            .into_iter()
+            .map(|(id, edges)| {
+                let edges = edges
+                    .into_iter()
+                    .map(|(n, d)| (self.to_user_id(n), d))
+                    .collect();
+                (self.to_user_id(id), edges)
+            })
+            .collect())
+    }
+
     fn graph_clusters(
         &self,
         layer: usize,
         min_cluster_size: usize,
         max_clusters: usize,
         max_nodes: usize,
-    ) -> Result<Vec<Vec<u32>>, String> {
+    ) -> HyperspaceResult<Vec<Vec<u32>>> {
         let clusters = self.index_link.load().graph_connected_components(
             layer,
             min_cluster_size,
@@ -1763,6 +2476,289 @@ impl<const N: usize, M: Metric<N>> Collection for CollectionImpl<N, M> {
         let internal_id = self.to_internal_id(id);
         self.index_link.load().metadata_by_id(internal_id)
     }
+
+    fn ids_with_metadata(&self, key: &str, value: &str) -> Vec<u32> {
+        let filter = HashMap::from([(key.to_string(), value.to_string())]);
+        let Some(matched) = self.index_link.load().matching_ids(&filter, &[]) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u32> = matched.iter().map(|id| self.to_user_id(id)).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn vector_by_id(&self, id: u32) -> Option<Vec<f64>> {
+        let internal_id = *self.id_map.get(&id)?;
+        let index = self.index_link.load();
+        // Flushed chunks only keep their vectors on disk.
+        ((internal_id as usize) < index.count())
+            .then(|| index.get_vector(internal_id).coords.to_vec())
+    }
+
+    fn contains(&self, id: u32) -> bool {
+        self.id_map.contains_key(&id)
+    }
+
+    fn drift_score(&self) -> Option<f64> {
+        self.drift.as_ref().and_then(|d| d.last_score())
+    }
+
+    fn effective_ef_search(&self) -> Option<usize> {
+        self.adaptive_ef.as_ref().map(|ef| ef.current())
+    }
+
+    async fn scrub(&self, repair: bool) -> HyperspaceResult<ScrubReport> {
+        let started = std::time::Instant::now();
+        let index = self.index_link.load_full();
+        let storage = index.get_storage();
+        let storage_count = storage.count();
+        // Zonal collections keep vectors outside the store.
+        let graph_nodes = if index.zonal {
+            storage_count
+        } else {
+            index.graph_len()
+        };
+        let pause = scrubber::batch_pause();
+        let mut report = ScrubReport {
+            storage_count: storage_count as u64,
+            graph_nodes: graph_nodes as u64,
+            ..ScrubReport::default()
+        };
+
+        let mut corrupt = Vec::new();
+        for start in (0..storage_count).step_by(scrubber::BATCH) {
+            for id in start as u32..(start + scrubber::BATCH).min(storage_count) as u32 {
+                match storage.verify(id) {
+                    ChecksumStatus::Match => report.checked += 1,
+                    ChecksumStatus::Mismatch => {
+                        report.checked += 1;
+                        if !index.is_deleted(id) {
+                            corrupt.push(id);
+                        }
+                    }
+                    ChecksumStatus::Missing => {
+                        storage.seal(id);
+                        report.sealed += 1;
+                    }
+                }
+            }
+            if !pause.is_zero() {
+                tokio::time::sleep(pause).await;
+            }
+        }
+        // Nodes past the end of storage would be scored against zeros.
+        corrupt
+            .extend((storage_count as u32..graph_nodes as u32).filter(|&id| !index.is_deleted(id)));
+        report.corrupt = corrupt.iter().map(|&id| self.to_user_id(id)).collect();
+
+        // A flush may have swapped the segment meanwhile; its ids mean
+        // nothing in the new one.
+        if repair && !corrupt.is_empty() && Arc::ptr_eq(&index, &self.index_link.load_full()) {
+            let clock = self.last_clock.load(Ordering::Relaxed);
+            for (&internal_id, &id) in corrupt.iter().zip(&report.corrupt) {
+                if self.to_internal_id(id) == internal_id {
+                    self.delete(id, clock).await?;
+                }
+            }
+            report.repaired = true;
+        }
+
+        report.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        report.finished_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        if !report.corrupt.is_empty() || storage_count != graph_nodes {
+            let error = format!(
+                "Scrub found {} corrupt vector(s){}; storage holds {storage_count}, graph {graph_nodes}",
+                report.corrupt.len(),
+                if report.repaired { " and deleted them" } else { "" },
+            );
+            eprintln!("🛑 '{}': {error}", self.name);
+            self.replication_tx.alert(StorageAlertEvent {
+                collection: self.name.clone(),
+                error,
+                consecutive_failures: 0,
+                read_only: self.storage_health.is_read_only(),
+            });
+        }
+        *self.scrub_report.lock() = Some(report.clone());
+        Ok(report)
+    }
+
+    fn scrub_report(&self) -> Option<ScrubReport> {
+        self.scrub_report.lock().clone()
+    }
+
+    async fn purge(
+        &self,
+        filter: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        clock: u64,
+    ) -> HyperspaceResult<PurgeOutcome> {
+        self.drain_indexer().await;
+        let Some(matched) = self.index_link.load().matching_ids(filter, complex_filters) else {
+            return Err(HyperspaceError::Validation(
+                "Purge needs a filter; delete the collection to erase all of it".into(),
+            ));
+        };
+        let ids: Vec<u32> = matched.iter().map(|id| self.to_user_id(id)).collect();
+        self.purge_ids(&ids, clock).await
+    }
+
+    async fn purge_ids(&self, ids: &[u32], clock: u64) -> HyperspaceResult<PurgeOutcome> {
+        self.storage_health.check(&self.name)?;
+        // Chunks are immutable snapshots with their own id space.
+        if self.meta_router.chunk_count() > 0
+            || self.flushing_vector_count.load(Ordering::SeqCst) > 0
+        {
+            return Err(HyperspaceError::Precondition(format!(
+                "'{}' has points flushed to immutable chunks, which purge cannot erase",
+                self.name
+            )));
+        }
+        self.drain_indexer().await;
+        let index = self.index_link.load_full();
+        let targets: Vec<(u32, u32)> = ids
+            .iter()
+            .filter(|&&id| self.contains(id))
+            .map(|&id| (id, self.to_internal_id(id)))
+            .filter(|&(_, internal_id)| (internal_id as usize) < index.graph_len())
+            .collect();
+        if targets.is_empty() {
+            return Ok(PurgeOutcome::default());
+        }
+        let purged: Vec<u32> = targets.iter().map(|&(id, _)| id).collect();
+
+        // Tombstones first, as for deletes, then close the segment so every
+        // segment holding the points can be redacted.
+        let frozen = {
+            let wal_guard = self.wal_link.load();
+            let mut wal = wal_guard.lock().await;
+            let ops: Vec<WalOp<'_>> = purged.iter().map(|&id| WalOp::Delete { id }).collect();
+            wal.append_mixed(&ops, clock)
+                .map_err(|e| self.storage_error(e.into()))?;
+            self.last_clock.fetch_max(clock, Ordering::Relaxed);
+            let frozen = wal.rotate().map_err(|e| self.storage_error(e.into()))?;
+            self.wal_pending_count.store(0, Ordering::SeqCst);
+            frozen
+        };
+        self.storage_health.succeeded();
+
+        for &(id, _) in &targets {
+            self.remove_point(id);
+        }
+        let internal_ids: Vec<u32> = targets
+            .iter()
+            .map(|&(_, internal_id)| internal_id)
+            .collect();
+        pools::spawn(Pool::Indexing, move || index.purge(&internal_ids))
+            .await
+            .map_err(|e| HyperspaceError::Internal(format!("Purge task failed: {e}")))??;
+        self.invalidate_search_cache();
+        self.index_watermark.applied(clock);
+
+        let (wal_records_removed, journal_entries_removed) = {
+            // Held so no flush worker takes a segment while it is rewritten
+            let mut pending = self.pending_wal_flushes.lock().await;
+            pending.push(frozen);
+            let segments = pending.clone();
+            let feed = self.replication_tx.clone();
+            let name = self.name.clone();
+            let set: HashSet<u32> = purged.iter().copied().collect();
+            pools::spawn(Pool::Indexing, move || {
+                let mut wal_records = 0;
+                for segment in &segments {
+                    wal_records += Wal::redact(segment, &set)?;
+                }
+                Ok::<_, std::io::Error>((wal_records, feed.redact(&name, &set)?))
+            })
+            .await
+            .map_err(|e| HyperspaceError::Internal(format!("Purge task failed: {e}")))?
+            .map_err(|e| self.storage_error(e.into()))?
+        };
+
+        if self.replication_tx.is_active() {
+            self.replication_tx.publish(ReplicationLog {
+                logical_clock: clock,
+                origin_node_id: self.node_id.clone(),
+                collection: self.name.clone(),
+                operation: Some(replication_log::Operation::Purge(PurgeOp {
+                    ids: purged.clone(),
+                })),
+                ..Default::default()
+            });
+        }
+
+        // Every generation predates the purge; the snapshot below starts a
+        // new history without the points.
+        let writer = self.snapshot_writer.clone();
+        let history_generations_removed =
+            pools::spawn(Pool::Indexing, move || writer.forget_history())
+                .await
+                .map_err(|e| HyperspaceError::Internal(format!("Purge task failed: {e}")))?
+                .map_err(|e| self.storage_error(e))?;
+
+        // The old index.snap still holds the points' links and metadata.
+        self.snapshot().await?;
+        println!(
+            "🧹 Purged {} point(s) from '{}' ({wal_records_removed} WAL record(s), {journal_entries_removed} journal entries)",
+            purged.len(),
+            self.name
+        );
+        Ok(PurgeOutcome {
+            purged,
+            wal_records_removed,
+            journal_entries_removed,
+            history_generations_removed,
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.storage_health.is_read_only()
+    }
+
+    fn set_writable(&self) {
+        self.storage_health.set_writable();
+    }
+
+    fn metadata_schema(&self) -> Option<&MetadataSchema> {
+        self.schema.as_ref()
+    }
+
+    fn normalization(&self) -> NormalizationPolicy {
+        self.normalization
+    }
+
+    fn metadata_key_stats(&self) -> Vec<MetadataKeyStats> {
+        self.index_link.load().metadata.key_stats()
+    }
+
+    fn metadata_distinct_values(&self) -> HashMap<String, u64> {
+        self.index_link.load().metadata.distinct_values()
+    }
+
+    fn set_synonyms(&self, synonyms: SynonymMap) {
+        self.index_link.load().set_synonyms(synonyms);
+        // Cached hybrid results were ranked with the old expansions.
+        self.invalidate_search_cache();
+    }
+
+    fn put_token_vectors(&self, id: u32, tokens: Option<TokenMatrix>) -> HyperspaceResult<()> {
+        match tokens {
+            Some(tokens) => self.tokens.put(id, tokens)?,
+            None => self.tokens.remove(id)?,
+        }
+        Ok(())
+    }
+
+    fn rerank_late_interaction(
+        &self,
+        hits: Vec<SearchResult>,
+        query: &TokenMatrix,
+        top_k: usize,
+    ) -> Vec<SearchResult> {
+        late_interaction::rerank(hits, query, |id| self.tokens.get(id), top_k)
+    }
 }
 
 impl<const N: usize, M: Metric<N>> Drop for CollectionImpl<N, M> {
@@ -0,0 +1,225 @@
+//! Declarative collection specs.
+//!
+//! A [`CollectionSpec`] is the desired state of one collection. Applying it
+//! creates the collection when it is missing and otherwise converges what can
+//! change in place: description, labels and aliases. Dimension, metric,
+//! quantization and the HNSW build parameters are baked into the stored
+//! index, so a spec that differs there is rejected and the collection has to
+//! be migrated instead. Applying the same spec twice changes nothing, which
+//! lets a Kubernetes operator or GitOps loop re-apply it on every sync.
+//!
+//! Aliases are extra names a collection answers to on the data plane. They
+//! are kept per server in `aliases.json` next to `moved.json`.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult, QuantizationMode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Build-time HNSW parameters; unset ones follow `HS_HNSW_M` and
+/// `HS_HNSW_EF_CONSTRUCT`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HnswParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef_construction: Option<usize>,
+}
+
+impl HnswParams {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Coarse IVF routing in front of the graph, picked with `index_type: ivf`
+/// at creation; unset fields follow `HS_IVF_CELLS` and `HS_IVF_PROBES`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IvfParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cells: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probes: Option<usize>,
+}
+
+impl IvfParams {
+    pub fn validate(&self) -> HyperspaceResult<()> {
+        if self.cells.is_some_and(|k| !(2..=65_536).contains(&k)) {
+            return Err(HyperspaceError::Validation(
+                "ivf_cells must be between 2 and 65536".into(),
+            ));
+        }
+        if self.probes == Some(0) {
+            return Err(HyperspaceError::Validation(
+                "ivf_probes must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionSpec {
+    pub name: String,
+    /// `0` for a text-only collection.
+    pub dimension: u32,
+    pub metric: String,
+    /// `scalar`, `binary` or `none`; empty takes `HS_QUANTIZATION_LEVEL` on
+    /// creation and matches anything afterwards.
+    #[serde(default)]
+    pub quantization: String,
+    #[serde(default, skip_serializing_if = "HnswParams::is_default")]
+    pub hnsw: HnswParams,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// What applying a spec did, or would do on a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ApplyOutcome {
+    pub created: bool,
+    /// One line per converged field; empty when already in the desired state.
+    pub changes: Vec<String>,
+}
+
+impl CollectionSpec {
+    /// Checks the fields the manager does not; sorts and dedups `aliases`.
+    pub fn normalize(&mut self) -> HyperspaceResult<()> {
+        if self.metric.is_empty() {
+            return Err(HyperspaceError::Validation(
+                "Spec metric is required".into(),
+            ));
+        }
+        self.metric = self.metric.to_lowercase();
+        self.quantization = self.quantization.to_lowercase();
+        if !matches!(
+            self.quantization.as_str(),
+            "" | "scalar" | "binary" | "none"
+        ) {
+            return Err(HyperspaceError::Validation(format!(
+                "Unknown quantization '{}' (expected scalar, binary or none)",
+                self.quantization
+            )));
+        }
+        if self.hnsw.m.is_some_and(|m| !(2..=128).contains(&m)) {
+            return Err(HyperspaceError::Validation(
+                "hnsw.m must be between 2 and 128".into(),
+            ));
+        }
+        if self.hnsw.ef_construction == Some(0) {
+            return Err(HyperspaceError::Validation(
+                "hnsw.ef_construction must be at least 1".into(),
+            ));
+        }
+        self.aliases.sort();
+        self.aliases.dedup();
+        Ok(())
+    }
+}
+
+pub fn quantization_mode(name: &str) -> QuantizationMode {
+    match name {
+        "binary" => QuantizationMode::Binary,
+        "none" => QuantizationMode::None,
+        _ => QuantizationMode::ScalarI8,
+    }
+}
+
+pub fn quantization_name(mode: QuantizationMode) -> &'static str {
+    match mode {
+        QuantizationMode::Binary => "binary",
+        QuantizationMode::None => "none",
+        QuantizationMode::ScalarI8 => "scalar",
+    }
+}
+
+/// `euclidean` and `l2` name the same metric.
+fn canonical_metric(metric: &str) -> &str {
+    if metric == "euclidean" {
+        "l2"
+    } else {
+        metric
+    }
+}
+
+/// Differences between a stored collection and `spec` that cannot be applied
+/// in place, as `field current -> desired` lines.
+pub fn fixed_field_drift(
+    spec: &CollectionSpec,
+    dimension: u32,
+    metric: &str,
+    quantization: QuantizationMode,
+    hnsw: HnswParams,
+) -> Vec<String> {
+    let mut drift = Vec::new();
+    if spec.dimension != dimension {
+        drift.push(format!("dimension {dimension} -> {}", spec.dimension));
+    }
+    if canonical_metric(&spec.metric) != canonical_metric(metric) {
+        drift.push(format!("metric {metric} -> {}", spec.metric));
+    }
+    if !spec.quantization.is_empty() && quantization_mode(&spec.quantization) != quantization {
+        drift.push(format!(
+            "quantization {} -> {}",
+            quantization_name(quantization),
+            spec.quantization
+        ));
+    }
+    let current =
+        |v: Option<usize>| v.map_or_else(|| "server default".to_string(), |v| v.to_string());
+    if let Some(m) = spec.hnsw.m.filter(|&m| hnsw.m != Some(m)) {
+        drift.push(format!("hnsw.m {} -> {m}", current(hnsw.m)));
+    }
+    if let Some(ef) = spec
+        .hnsw
+        .ef_construction
+        .filter(|&ef| hnsw.ef_construction != Some(ef))
+    {
+        drift.push(format!(
+            "hnsw.ef_construction {} -> {ef}",
+            current(hnsw.ef_construction)
+        ));
+    }
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_ignores_unset_and_equivalent_fields() {
+        let mut spec = CollectionSpec {
+            name: "docs".into(),
+            dimension: 8,
+            metric: "Euclidean".into(),
+            aliases: vec!["b".into(), "a".into(), "b".into()],
+            ..Default::default()
+        };
+        spec.normalize().unwrap();
+        assert_eq!(spec.aliases, vec!["a", "b"]);
+        let hnsw = HnswParams::default();
+        assert_eq!(
+            fixed_field_drift(&spec, 8, "l2", QuantizationMode::Binary, hnsw),
+            Vec::<String>::new()
+        );
+
+        spec.dimension = 16;
+        spec.quantization = "none".into();
+        spec.hnsw.m = Some(32);
+        assert_eq!(
+            fixed_field_drift(&spec, 8, "l2", QuantizationMode::ScalarI8, hnsw),
+            vec![
+                "dimension 8 -> 16",
+                "quantization scalar -> none",
+                "hnsw.m server default -> 32"
+            ]
+        );
+
+        spec.quantization = "pq".into();
+        assert!(spec.normalize().is_err());
+    }
+}
@@ -0,0 +1,554 @@
+//! Arrow IPC and Parquet export and import of whole collections.
+//!
+//! `ExportCollection` with the `ARROW_IPC` or `PARQUET` format writes every
+//! live point as one row of:
+//!
+//! - `id`: the point id, `uint32`
+//! - `vector`: a `fixed_size_list<double>` of the collection's dimension
+//! - `metadata`: a `map<utf8, utf8>`
+//! - `deleted`: a `bool`, false in exports
+//!
+//! The schema metadata holds `hyperspace.dimension`, `hyperspace.metric` and
+//! `hyperspace.collection`. Arrow IPC is the file format (Feather v2); Parquet
+//! gets one row group per record batch of [`BATCH_ROWS`].
+//!
+//! `ImportCollection` reads the same layout, so files written by pyarrow or
+//! polars work as well: `id` may be any integer type, `vector` a list of
+//! `float` or `double`, and `metadata` and `deleted` may be left out. Rows
+//! with `deleted` set delete their id instead of inserting it.
+//!
+//! Deleted points keep no id once removed, so an export can't list them; the
+//! column carries deletions from files made elsewhere. Points written while an
+//! export runs may be missing from it or appear twice. Both formats need a
+//! server built with the `arrow-export` feature.
+
+use hyperspace_core::HyperspaceResult;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Rows per record batch on export.
+pub const BATCH_ROWS: usize = 8192;
+
+#[cfg_attr(not(feature = "arrow-export"), allow(dead_code))]
+const DIMENSION_KEY: &str = "hyperspace.dimension";
+#[cfg_attr(not(feature = "arrow-export"), allow(dead_code))]
+const METRIC_KEY: &str = "hyperspace.metric";
+#[cfg_attr(not(feature = "arrow-export"), allow(dead_code))]
+const COLLECTION_KEY: &str = "hyperspace.collection";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnarFormat {
+    ArrowIpc,
+    Parquet,
+}
+
+/// A page of points as returned by `Collection::peek`.
+pub type Page = Vec<(u32, Vec<f64>, HashMap<String, String>)>;
+
+/// One row of an imported file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub id: u32,
+    /// Empty for a deleted row without a vector.
+    pub vector: Vec<f64>,
+    pub metadata: HashMap<String, String>,
+    pub deleted: bool,
+}
+
+/// The collection a file was exported from, when its schema says.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    pub dimension: Option<usize>,
+    pub metric: Option<String>,
+}
+
+/// Blocking: writes the pages `next_page` returns, until an empty one, to
+/// `out`. Returns the number of rows written.
+#[cfg(feature = "arrow-export")]
+pub fn write(
+    out: &Path,
+    format: ColumnarFormat,
+    collection: &str,
+    dimension: usize,
+    metric: &str,
+    mut next_page: impl FnMut() -> Page,
+) -> HyperspaceResult<u64> {
+    use arrow_ipc::writer::FileWriter;
+    use parquet::arrow::ArrowWriter;
+
+    let schema = arrow_impl::schema(dimension, collection, metric);
+    let file = std::fs::File::create(out)?;
+    let mut ipc = None;
+    let mut parquet = None;
+    match format {
+        ColumnarFormat::ArrowIpc => {
+            ipc = Some(FileWriter::try_new(file, &schema).map_err(arrow_impl::err)?);
+        }
+        ColumnarFormat::Parquet => {
+            parquet =
+                Some(ArrowWriter::try_new(file, schema.clone(), None).map_err(arrow_impl::err)?);
+        }
+    }
+
+    let mut rows = 0u64;
+    loop {
+        let page = next_page();
+        if page.is_empty() {
+            break;
+        }
+        rows += page.len() as u64;
+        let batch = arrow_impl::record_batch(&schema, dimension, &page)?;
+        if let Some(w) = &mut ipc {
+            w.write(&batch).map_err(arrow_impl::err)?;
+        }
+        if let Some(w) = &mut parquet {
+            w.write(&batch).map_err(arrow_impl::err)?;
+        }
+    }
+    if let Some(mut w) = ipc {
+        w.finish().map_err(arrow_impl::err)?;
+    }
+    if let Some(w) = parquet {
+        w.close().map_err(arrow_impl::err)?;
+    }
+    Ok(rows)
+}
+
+#[cfg(not(feature = "arrow-export"))]
+pub fn write(
+    _out: &Path,
+    _format: ColumnarFormat,
+    _collection: &str,
+    _dimension: usize,
+    _metric: &str,
+    _next_page: impl FnMut() -> Page,
+) -> HyperspaceResult<u64> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "arrow-export"))]
+fn unsupported() -> hyperspace_core::HyperspaceError {
+    hyperspace_core::HyperspaceError::Validation(
+        "Arrow IPC and Parquet exports need a server built with the arrow-export feature".into(),
+    )
+}
+
+/// Reads an exported file a record batch at a time.
+pub struct Reader {
+    header: Header,
+    #[cfg(feature = "arrow-export")]
+    batches:
+        Box<dyn Iterator<Item = Result<arrow_array::RecordBatch, arrow_schema::ArrowError>> + Send>,
+    /// Rows read so far, for error messages.
+    #[cfg_attr(not(feature = "arrow-export"), allow(dead_code))]
+    row: usize,
+}
+
+impl Reader {
+    /// Blocking: opens `path` and reads its schema.
+    #[cfg(feature = "arrow-export")]
+    pub fn open(path: &Path, format: ColumnarFormat) -> HyperspaceResult<Self> {
+        use arrow_ipc::reader::FileReader;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let file = std::fs::File::open(path)?;
+        let (schema, batches): (arrow_schema::SchemaRef, Box<dyn Iterator<Item = _> + Send>) =
+            match format {
+                ColumnarFormat::ArrowIpc => {
+                    let reader = FileReader::try_new(file, None).map_err(arrow_impl::invalid)?;
+                    (reader.schema(), Box::new(reader))
+                }
+                ColumnarFormat::Parquet => {
+                    let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+                        .map_err(arrow_impl::invalid)?;
+                    let schema = builder.schema().clone();
+                    let reader = builder
+                        .with_batch_size(BATCH_ROWS)
+                        .build()
+                        .map_err(arrow_impl::invalid)?;
+                    (schema, Box::new(reader))
+                }
+            };
+        arrow_impl::check_schema(&schema)?;
+        let meta = schema.metadata();
+        let header = Header {
+            dimension: meta.get(DIMENSION_KEY).and_then(|d| d.parse().ok()),
+            metric: meta.get(METRIC_KEY).cloned(),
+        };
+        Ok(Self {
+            header,
+            batches,
+            row: 0,
+        })
+    }
+
+    #[cfg(not(feature = "arrow-export"))]
+    pub fn open(_path: &Path, _format: ColumnarFormat) -> HyperspaceResult<Self> {
+        Err(unsupported())
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Blocking: the rows of the next record batch, `None` at the end.
+    #[cfg(feature = "arrow-export")]
+    pub fn next_rows(&mut self) -> HyperspaceResult<Option<Vec<Row>>> {
+        let Some(batch) = self.batches.next() else {
+            return Ok(None);
+        };
+        let batch = batch.map_err(arrow_impl::invalid)?;
+        let rows = arrow_impl::rows(&batch, self.row)?;
+        self.row += rows.len();
+        Ok(Some(rows))
+    }
+
+    #[cfg(not(feature = "arrow-export"))]
+    #[allow(clippy::unused_self)]
+    pub fn next_rows(&mut self) -> HyperspaceResult<Option<Vec<Row>>> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(feature = "arrow-export")]
+mod arrow_impl {
+    use super::{Row, COLLECTION_KEY, DIMENSION_KEY, METRIC_KEY};
+    use arrow_array::builder::{FixedSizeListBuilder, Float64Builder, MapBuilder, StringBuilder};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{
+        Float32Type, Float64Type, Int32Type, Int64Type, UInt32Type, UInt64Type,
+    };
+    use arrow_array::{Array, ArrayRef, BooleanArray, RecordBatch, UInt32Array};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use hyperspace_core::{HyperspaceError, HyperspaceResult};
+    use std::collections::HashMap;
+    use std::ops::Range;
+    use std::sync::Arc;
+
+    pub fn err(e: impl std::fmt::Display) -> HyperspaceError {
+        HyperspaceError::Internal(e.to_string())
+    }
+
+    pub fn invalid(e: impl std::fmt::Display) -> HyperspaceError {
+        HyperspaceError::Validation(format!("Unreadable file: {e}"))
+    }
+
+    fn map_type() -> DataType {
+        DataType::Map(
+            Arc::new(Field::new(
+                "entries",
+                DataType::Struct(
+                    vec![
+                        Field::new("keys", DataType::Utf8, false),
+                        Field::new("values", DataType::Utf8, true),
+                    ]
+                    .into(),
+                ),
+                false,
+            )),
+            false,
+        )
+    }
+
+    pub fn schema(dimension: usize, collection: &str, metric: &str) -> SchemaRef {
+        let item = Arc::new(Field::new("item", DataType::Float64, true));
+        let fields = vec![
+            Field::new("id", DataType::UInt32, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(item, i32::try_from(dimension).unwrap_or(i32::MAX)),
+                false,
+            ),
+            Field::new("metadata", map_type(), false),
+            Field::new("deleted", DataType::Boolean, false),
+        ];
+        let metadata = HashMap::from([
+            (DIMENSION_KEY.to_string(), dimension.to_string()),
+            (METRIC_KEY.to_string(), metric.to_string()),
+            (COLLECTION_KEY.to_string(), collection.to_string()),
+        ]);
+        Arc::new(Schema::new_with_metadata(fields, metadata))
+    }
+
+    pub fn record_batch(
+        schema: &SchemaRef,
+        dimension: usize,
+        page: &super::Page,
+    ) -> HyperspaceResult<RecordBatch> {
+        let ids = UInt32Array::from_iter_values(page.iter().map(|(id, _, _)| *id));
+        let mut vectors = FixedSizeListBuilder::with_capacity(
+            Float64Builder::with_capacity(page.len() * dimension),
+            i32::try_from(dimension).unwrap_or(i32::MAX),
+            page.len(),
+        );
+        let mut metadata = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        for (id, vector, meta) in page {
+            if vector.len() != dimension {
+                return Err(HyperspaceError::Internal(format!(
+                    "Point {id} has {} dimensions, expected {dimension}",
+                    vector.len()
+                )));
+            }
+            vectors.values().append_slice(vector);
+            vectors.append(true);
+            for (key, value) in meta {
+                metadata.keys().append_value(key);
+                metadata.values().append_value(value);
+            }
+            metadata.append(true).map_err(err)?;
+        }
+        let deleted = BooleanArray::from(vec![false; page.len()]);
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(ids),
+                Arc::new(vectors.finish()),
+                Arc::new(metadata.finish()),
+                Arc::new(deleted),
+            ],
+        )
+        .map_err(err)
+    }
+
+    /// Rejects a schema `rows` can't read before any row is imported.
+    pub fn check_schema(schema: &Schema) -> HyperspaceResult<()> {
+        let reject = |msg: String| Err(HyperspaceError::Validation(msg));
+        match schema.field_with_name("id").map(Field::data_type) {
+            Ok(DataType::UInt32 | DataType::UInt64 | DataType::Int32 | DataType::Int64) => {}
+            Ok(other) => return reject(format!("Column 'id' must be an integer, not {other}")),
+            Err(_) => return reject("The file has no 'id' column".into()),
+        }
+        match schema.field_with_name("vector").map(Field::data_type) {
+            Ok(
+                DataType::FixedSizeList(item, _) | DataType::List(item) | DataType::LargeList(item),
+            ) if matches!(item.data_type(), DataType::Float32 | DataType::Float64) => {}
+            Ok(other) => {
+                return reject(format!(
+                    "Column 'vector' must be a list of float or double, not {other}"
+                ))
+            }
+            Err(_) => return reject("The file has no 'vector' column".into()),
+        }
+        if let Ok(field) = schema.field_with_name("metadata") {
+            let DataType::Map(entries, _) = field.data_type() else {
+                return reject(format!(
+                    "Column 'metadata' must be a map<utf8, utf8>, not {}",
+                    field.data_type()
+                ));
+            };
+            let utf8 = match entries.data_type() {
+                DataType::Struct(kv) => kv.iter().all(|f| f.data_type() == &DataType::Utf8),
+                _ => false,
+            };
+            if !utf8 {
+                return reject("Column 'metadata' must be a map<utf8, utf8>".into());
+            }
+        }
+        if let Ok(field) = schema.field_with_name("deleted") {
+            if field.data_type() != &DataType::Boolean {
+                return reject(format!(
+                    "Column 'deleted' must be a bool, not {}",
+                    field.data_type()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn ids(column: &ArrayRef) -> Vec<Option<i128>> {
+        fn collect<T: arrow_array::ArrowPrimitiveType>(column: &ArrayRef) -> Vec<Option<i128>>
+        where
+            T::Native: Into<i128>,
+        {
+            column
+                .as_primitive::<T>()
+                .iter()
+                .map(|v| v.map(Into::into))
+                .collect()
+        }
+        match column.data_type() {
+            DataType::UInt32 => collect::<UInt32Type>(column),
+            DataType::UInt64 => collect::<UInt64Type>(column),
+            DataType::Int32 => collect::<Int32Type>(column),
+            _ => collect::<Int64Type>(column),
+        }
+    }
+
+    /// The value range of every list in `column`, `None` for a null one.
+    fn vector_ranges(column: &ArrayRef) -> (ArrayRef, Vec<Option<Range<usize>>>) {
+        let valid = |i: usize| column.is_valid(i);
+        match column.data_type() {
+            DataType::FixedSizeList(..) => {
+                let list = column.as_fixed_size_list();
+                let n = list.value_length() as usize;
+                let ranges = (0..list.len())
+                    .map(|i| {
+                        let start = list.value_offset(i) as usize;
+                        valid(i).then_some(start..start + n)
+                    })
+                    .collect();
+                (list.values().clone(), ranges)
+            }
+            DataType::List(_) => {
+                let list = column.as_list::<i32>();
+                let offsets = list.value_offsets();
+                let ranges = (0..list.len())
+                    .map(|i| valid(i).then(|| offsets[i] as usize..offsets[i + 1] as usize))
+                    .collect();
+                (list.values().clone(), ranges)
+            }
+            _ => {
+                let list = column.as_list::<i64>();
+                let offsets = list.value_offsets();
+                let ranges = (0..list.len())
+                    .map(|i| valid(i).then(|| offsets[i] as usize..offsets[i + 1] as usize))
+                    .collect();
+                (list.values().clone(), ranges)
+            }
+        }
+    }
+
+    fn vector(values: &ArrayRef, range: Range<usize>) -> Vec<f64> {
+        match values.data_type() {
+            DataType::Float32 => values.as_primitive::<Float32Type>().values()[range]
+                .iter()
+                .map(|&x| f64::from(x))
+                .collect(),
+            _ => values.as_primitive::<Float64Type>().values()[range].to_vec(),
+        }
+    }
+
+    fn metadata(column: &ArrayRef, i: usize) -> HashMap<String, String> {
+        let map = column.as_map();
+        if map.is_null(i) {
+            return HashMap::new();
+        }
+        let entries = map.value(i);
+        let keys = entries.column(0).as_string::<i32>();
+        let values = entries.column(1).as_string::<i32>();
+        (0..entries.len())
+            .filter(|&j| values.is_valid(j))
+            .map(|j| (keys.value(j).to_string(), values.value(j).to_string()))
+            .collect()
+    }
+
+    /// The rows of `batch`, numbered from `first_row + 1` in errors.
+    pub fn rows(batch: &RecordBatch, first_row: usize) -> HyperspaceResult<Vec<Row>> {
+        let column = |name: &str| batch.column_by_name(name);
+        let ids = ids(column("id").ok_or_else(|| invalid("no 'id' column"))?);
+        let (values, ranges) =
+            vector_ranges(column("vector").ok_or_else(|| invalid("no 'vector' column"))?);
+        let metadata_column = column("metadata");
+        let deleted = column("deleted").map(|c| c.as_boolean());
+
+        let mut rows = Vec::with_capacity(batch.num_rows());
+        for (i, (id, range)) in ids.into_iter().zip(ranges).enumerate() {
+            let line = first_row + i + 1;
+            let id = id.and_then(|id| u32::try_from(id).ok()).ok_or_else(|| {
+                HyperspaceError::Validation(format!("Row {line}: id is null or out of range"))
+            })?;
+            let deleted = deleted.is_some_and(|d| d.is_valid(i) && d.value(i));
+            let vector = match range {
+                Some(range) => vector(&values, range),
+                None if deleted => Vec::new(),
+                None => {
+                    return Err(HyperspaceError::Validation(format!(
+                        "Row {line}: point {id} has no vector"
+                    )))
+                }
+            };
+            rows.push(Row {
+                id,
+                vector,
+                metadata: metadata_column.map(|c| metadata(c, i)).unwrap_or_default(),
+                deleted,
+            });
+        }
+        Ok(rows)
+    }
+}
+
+#[cfg(all(test, feature = "arrow-export"))]
+mod tests {
+    use super::*;
+
+    fn page() -> Page {
+        vec![
+            (
+                7,
+                vec![0.5, 1.0],
+                HashMap::from([("lang".into(), "en".into())]),
+            ),
+            (3, vec![-1.0, 0.25], HashMap::new()),
+        ]
+    }
+
+    #[test]
+    fn exports_round_trip_in_both_formats() {
+        for format in [ColumnarFormat::ArrowIpc, ColumnarFormat::Parquet] {
+            let path = std::env::temp_dir().join(format!("hs_columnar_{}", uuid::Uuid::new_v4()));
+            let mut pages = vec![page()].into_iter();
+            let written = write(&path, format, "docs", 2, "l2", || {
+                pages.next().unwrap_or_default()
+            })
+            .unwrap();
+            assert_eq!(written, 2);
+
+            let mut reader = Reader::open(&path, format).unwrap();
+            assert_eq!(
+                reader.header(),
+                &Header {
+                    dimension: Some(2),
+                    metric: Some("l2".into()),
+                }
+            );
+            let rows = reader.next_rows().unwrap().unwrap();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[0].id, 7);
+            assert_eq!(rows[0].vector, vec![0.5, 1.0]);
+            assert_eq!(rows[0].metadata["lang"], "en");
+            assert!(rows[1].metadata.is_empty() && !rows[1].deleted);
+            assert!(reader.next_rows().unwrap().is_none());
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn reads_foreign_layouts_and_deletions() {
+        use arrow_array::builder::{Float32Builder, ListBuilder};
+        use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch};
+        use std::sync::Arc;
+
+        let mut vectors = ListBuilder::new(Float32Builder::new());
+        vectors.values().append_slice(&[1.0, 2.0]);
+        vectors.append(true);
+        vectors.append(false);
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            ("vector", Arc::new(vectors.finish()) as ArrayRef),
+            (
+                "deleted",
+                Arc::new(BooleanArray::from(vec![false, true])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let path = std::env::temp_dir().join(format!("hs_columnar_{}", uuid::Uuid::new_v4()));
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let mut reader = Reader::open(&path, ColumnarFormat::Parquet).unwrap();
+        assert_eq!(reader.header(), &Header::default());
+        let rows = reader.next_rows().unwrap().unwrap();
+        assert_eq!(rows[0].vector, vec![1.0, 2.0]);
+        assert!(rows[1].deleted && rows[1].vector.is_empty());
+        let _ = std::fs::remove_file(&path);
+
+        let bad =
+            RecordBatch::try_from_iter([("id", Arc::new(Int64Array::from(vec![1])) as ArrayRef)])
+                .unwrap();
+        assert!(arrow_impl::check_schema(&bad.schema()).is_err());
+    }
+}
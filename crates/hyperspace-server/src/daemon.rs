@@ -0,0 +1,183 @@
+//! Running under a service manager.
+//!
+//! - `--daemon` detaches from the terminal (Unix only): double fork, new
+//!   session, stdio redirected to `--log-file` or `/dev/null`. It runs before
+//!   the Tokio runtime starts, while the process still has a single thread.
+//! - `--pid-file` records the server's PID and is removed on clean exit. A
+//!   file naming a live process refuses the start; a stale one is replaced.
+//! - systemd `Type=notify`: when `NOTIFY_SOCKET` is set, the server reports
+//!   `READY=1` once startup loading is done and the gRPC port is bound. With
+//!   the default `HS_LAZY_LOAD=true` no collection is opened at boot, so the
+//!   first request to each one still pays for its WAL replay; set
+//!   `HS_LAZY_LOAD=false` to replay every WAL before `READY=1`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Detaches from the controlling terminal. Must be called before any other
+/// thread is started.
+#[cfg(unix)]
+pub fn detach(log_file: Option<&Path>) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let out = match log_file {
+        Some(path) => fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?,
+        None => fs::OpenOptions::new().write(true).open("/dev/null")?,
+    };
+    let null = fs::File::open("/dev/null")?;
+
+    // SAFETY: the process is still single-threaded, so the children start
+    // in a consistent state. The parents leave through `_exit`, so
+    // destructors only ever run in the daemon.
+    unsafe {
+        for step in 0..2 {
+            match libc::fork() {
+                -1 => return Err(io::Error::last_os_error()),
+                0 => {}
+                _ => libc::_exit(0),
+            }
+            if step == 0 && libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if libc::dup2(null.as_raw_fd(), 0) == -1
+            || libc::dup2(out.as_raw_fd(), 1) == -1
+            || libc::dup2(out.as_raw_fd(), 2) == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn detach(_log_file: Option<&Path>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "--daemon is only supported on Unix; register the server with the service manager instead",
+    ))
+}
+
+/// The PID file of the running server, removed on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(pid) = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| s.trim().parse::<u32>().ok())
+        {
+            if pid != std::process::id() && is_running(pid) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} names running process {pid}", path.display()),
+                ));
+            }
+        }
+        fs::write(path, format!("{}\n", std::process::id()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists.
+    let rc = unsafe { libc::kill(pid, 0) };
+    rc == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    false
+}
+
+/// Sends `state` (e.g. `READY=1`) to systemd. A no-op outside a
+/// `Type=notify` unit.
+pub fn notify(state: &str) {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    if let Err(e) = send_notify(&socket, state) {
+        eprintln!("⚠️ sd_notify({state}) failed: {e}");
+    }
+}
+
+#[cfg(unix)]
+fn send_notify(socket: &std::ffi::OsStr, state: &str) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let sock = UnixDatagram::unbound()?;
+    let bytes = socket.as_bytes();
+    if let Some(name) = bytes.strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            return sock.send_to_addr(state.as_bytes(), &addr).map(|_| ());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "abstract notify sockets need Linux",
+            ));
+        }
+    }
+    sock.send_to(state.as_bytes(), socket).map(|_| ())
+}
+
+#[cfg(not(unix))]
+fn send_notify(_socket: &std::ffi::OsStr, _state: &str) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_file_replaces_stale_and_cleans_up() {
+        let path = std::env::temp_dir().join(format!("hs_pid_{}.pid", uuid::Uuid::new_v4()));
+        // A PID no process can have counts as stale.
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+        let pid = PidFile::create(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        drop(pid);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notify_reaches_the_socket() {
+        let path = std::env::temp_dir().join(format!("hs_notify_{}.sock", uuid::Uuid::new_v4()));
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        send_notify(path.as_os_str(), "READY=1").unwrap();
+        let mut buf = [0u8; 64];
+        let n = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,186 @@
+//! Embedding drift monitoring.
+//!
+//! Switching embedding models mid-stream leaves a collection with two
+//! incompatible vector populations and quietly ruins recall. With
+//! `HS_DRIFT_WINDOW` above zero each collection profiles its inserts in
+//! windows of that many vectors: the mean vector and a histogram of norms.
+//! The first full window becomes the baseline. Each later window gets a drift
+//! score, the larger of
+//!
+//! - the centroid shift relative to the baseline's mean norm, and
+//! - the total variation distance between the two norm histograms (0..1).
+//!
+//! A score above `HS_DRIFT_THRESHOLD` (default 0.5) is logged and counted.
+//! Recording costs one pass over the vector per insert.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+static ALERTS: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide number of windows that exceeded the drift threshold.
+pub fn alerts() -> u64 {
+    ALERTS.load(Ordering::Relaxed)
+}
+
+/// Norm histogram bins, half an octave wide, covering norms 2^-4..2^4.
+const NORM_BINS: usize = 16;
+
+fn norm_bin(norm: f64) -> usize {
+    if norm <= 0.0 {
+        return 0;
+    }
+    ((norm.log2() + 4.0) * 2.0)
+        .floor()
+        .clamp(0.0, (NORM_BINS - 1) as f64) as usize
+}
+
+struct Profile {
+    sum: Vec<f64>,
+    norm_sum: f64,
+    norms: [u64; NORM_BINS],
+    count: usize,
+}
+
+impl Profile {
+    fn new(dimension: usize) -> Self {
+        Self {
+            sum: vec![0.0; dimension],
+            norm_sum: 0.0,
+            norms: [0; NORM_BINS],
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, vector: &[f64]) {
+        let mut sq = 0.0;
+        for (s, &x) in self.sum.iter_mut().zip(vector) {
+            *s += x;
+            sq += x * x;
+        }
+        let norm = sq.sqrt();
+        self.norm_sum += norm;
+        self.norms[norm_bin(norm)] += 1;
+        self.count += 1;
+    }
+
+    fn score_against(&self, baseline: &Self) -> f64 {
+        let (n, b) = (self.count as f64, baseline.count as f64);
+        let shift = self
+            .sum
+            .iter()
+            .zip(&baseline.sum)
+            .map(|(x, y)| (x / n - y / b).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let centroid = shift / (baseline.norm_sum / b).max(1e-12);
+        let histogram = 0.5
+            * self
+                .norms
+                .iter()
+                .zip(&baseline.norms)
+                .map(|(&x, &y)| (x as f64 / n - y as f64 / b).abs())
+                .sum::<f64>();
+        centroid.max(histogram)
+    }
+}
+
+struct State {
+    baseline: Option<Profile>,
+    current: Profile,
+}
+
+pub struct DriftMonitor {
+    window: usize,
+    threshold: f64,
+    state: Mutex<State>,
+    /// Score of the last completed window, as `f64` bits; NaN before one.
+    last_score: AtomicU64,
+}
+
+impl DriftMonitor {
+    /// Builds a monitor from `HS_DRIFT_WINDOW` and `HS_DRIFT_THRESHOLD`, or
+    /// `None` when the window is 0 (the default).
+    pub fn from_env(dimension: usize) -> Option<Arc<Self>> {
+        let window = std::env::var("HS_DRIFT_WINDOW")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let threshold = std::env::var("HS_DRIFT_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+        (window > 0).then(|| Arc::new(Self::new(dimension, window, threshold)))
+    }
+
+    pub fn new(dimension: usize, window: usize, threshold: f64) -> Self {
+        Self {
+            window,
+            threshold,
+            state: Mutex::new(State {
+                baseline: None,
+                current: Profile::new(dimension),
+            }),
+            last_score: AtomicU64::new(f64::NAN.to_bits()),
+        }
+    }
+
+    /// Adds an inserted vector. Returns the window's score when this vector
+    /// completes a window compared against the baseline.
+    pub fn record(&self, vector: &[f64]) -> Option<f64> {
+        let mut state = self.state.lock();
+        state.current.add(vector);
+        if state.current.count < self.window {
+            return None;
+        }
+        let finished = std::mem::replace(&mut state.current, Profile::new(vector.len()));
+        let Some(baseline) = &state.baseline else {
+            state.baseline = Some(finished);
+            return None;
+        };
+        let score = finished.score_against(baseline);
+        drop(state);
+
+        self.last_score.store(score.to_bits(), Ordering::Relaxed);
+        if score > self.threshold {
+            ALERTS.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(score)
+    }
+
+    /// Score of the last completed window, `None` until one exists.
+    pub fn last_score(&self) -> Option<f64> {
+        let score = f64::from_bits(self.last_score.load(Ordering::Relaxed));
+        (!score.is_nan()).then_some(score)
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_switch_raises_score() {
+        let monitor = DriftMonitor::new(4, 50, 0.5);
+        let model_a = |i: usize| vec![1.0, 0.5, (i % 7) as f64 * 0.01, 0.0];
+        for i in 0..100 {
+            if let Some(score) = monitor.record(&model_a(i)) {
+                assert!(score < 0.1, "{score}");
+            }
+        }
+        assert!(monitor.last_score().unwrap() < 0.1);
+
+        // A different model: other direction, much smaller norms.
+        let before = alerts();
+        for i in 0..50 {
+            monitor.record(&[0.0, 0.0, 0.1, f64::from(i % 5) * 0.01]);
+        }
+        assert!(monitor.last_score().unwrap() > 0.5);
+        assert!(alerts() > before);
+    }
+}
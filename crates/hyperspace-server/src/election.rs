@@ -0,0 +1,326 @@
+//! Lease-based leader election for the leader/follower setup.
+//!
+//! Not consensus: a single lease in a shared [`LeaseStore`] names the current
+//! leader and its advertised gRPC address. The leader renews it every third
+//! of `HS_ELECTION_LEASE_TTL_SEC`. When it stops renewing, the first follower
+//! to grab the expired lease promotes itself; the others see the new holder
+//! and re-point their replication stream. A leader that finds someone else
+//! holding the lease steps down and follows them.
+//!
+//! Expiry compares wall clocks, so nodes need roughly synchronized time.
+//! The lease term is the leadership epoch; see [`crate::fencing`] for how it
+//! keeps a deposed leader from taking writes. Writes the old leader accepted
+//! but had not yet replicated are lost on failover; anti-entropy repair
+//! converges the survivors.
+
+use crate::manager::{ClusterRole, CollectionManager};
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    pub holder: String,
+    /// gRPC URL followers connect to, e.g. `http://10.0.0.5:50051`.
+    pub address: String,
+    /// Incremented every time the lease changes hands.
+    pub term: u64,
+    pub expires_at_ms: u64,
+}
+
+impl Lease {
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at_ms <= now_ms
+    }
+}
+
+/// Shared storage for the leader lease; implement it over etcd, Consul or a
+/// database row to plug in an external lock service.
+#[async_trait::async_trait]
+pub trait LeaseStore: Send + Sync {
+    async fn read(&self) -> HyperspaceResult<Option<Lease>>;
+
+    /// Atomically installs `candidate` if there is no lease, it has expired
+    /// or `candidate.holder` already holds it. Returns the lease in force
+    /// afterwards, which is someone else's when the attempt lost.
+    async fn try_acquire(&self, candidate: &Lease) -> HyperspaceResult<Lease>;
+}
+
+/// Lease kept in a JSON file on storage every node can reach (e.g. NFS).
+/// Updates are serialized with an exclusive `.lock` file next to it.
+pub struct FileLeaseStore {
+    path: PathBuf,
+    /// A lock older than this is left over from a crashed holder.
+    stale_lock: Duration,
+}
+
+impl FileLeaseStore {
+    pub fn new(path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            path: path.into(),
+            stale_lock: ttl,
+        }
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.path.with_extension("lock")
+    }
+
+    fn read_sync(&self) -> HyperspaceResult<Option<Lease>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(s) if s.trim().is_empty() => Ok(None),
+            Ok(s) => serde_json::from_str(&s)
+                .map(Some)
+                .map_err(|e| HyperspaceError::Corruption(format!("Leader lease: {e}"))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn lock(&self) -> HyperspaceResult<()> {
+        let lock = self.lock_path();
+        match OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let stale = std::fs::metadata(&lock)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| t.elapsed().ok())
+                    .is_some_and(|age| age > self.stale_lock);
+                if stale {
+                    let _ = std::fs::remove_file(&lock);
+                }
+                Err(HyperspaceError::Capacity("Leader lease is busy".into()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn try_acquire_sync(&self, candidate: &Lease) -> HyperspaceResult<Lease> {
+        self.lock()?;
+        let result = (|| {
+            let current = self.read_sync()?;
+            let next = match current {
+                Some(cur) if cur.holder == candidate.holder => Lease {
+                    term: cur.term,
+                    ..candidate.clone()
+                },
+                Some(cur) if !cur.is_expired(now_ms()) => return Ok(cur),
+                Some(cur) => Lease {
+                    term: cur.term + 1,
+                    ..candidate.clone()
+                },
+                None => Lease {
+                    term: 1,
+                    ..candidate.clone()
+                },
+            };
+            let data =
+                serde_json::to_vec(&next).map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+            let tmp = self.path.with_extension("tmp");
+            let mut f = std::fs::File::create(&tmp)?;
+            f.write_all(&data)?;
+            f.sync_all()?;
+            std::fs::rename(&tmp, &self.path)?;
+            Ok(next)
+        })();
+        let _ = std::fs::remove_file(self.lock_path());
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl LeaseStore for FileLeaseStore {
+    async fn read(&self) -> HyperspaceResult<Option<Lease>> {
+        self.read_sync()
+    }
+
+    async fn try_acquire(&self, candidate: &Lease) -> HyperspaceResult<Lease> {
+        self.try_acquire_sync(candidate)
+    }
+}
+
+pub struct ElectionConfig {
+    pub node_id: String,
+    pub advertise_addr: String,
+    pub ttl: Duration,
+}
+
+impl ElectionConfig {
+    /// Builds the store and config from `HS_ELECTION_LEASE` (`file:<path>`),
+    /// `HS_ELECTION_LEASE_TTL_SEC` (default 10) and `HS_ADVERTISE_ADDR`.
+    /// Returns `Ok(None)` when election is not configured.
+    pub fn from_env(node_id: String) -> HyperspaceResult<Option<(Arc<dyn LeaseStore>, Self)>> {
+        let Ok(spec) = std::env::var("HS_ELECTION_LEASE") else {
+            return Ok(None);
+        };
+        let ttl = Duration::from_secs(
+            std::env::var("HS_ELECTION_LEASE_TTL_SEC")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(10)
+                .max(1),
+        );
+        let store: Arc<dyn LeaseStore> = match spec.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Arc::new(FileLeaseStore::new(path, ttl)),
+            _ => {
+                return Err(HyperspaceError::Validation(format!(
+                    "Unsupported HS_ELECTION_LEASE '{spec}' (expected file:<path>)"
+                )))
+            }
+        };
+        let advertise_addr = std::env::var("HS_ADVERTISE_ADDR").map_err(|_| {
+            HyperspaceError::Validation(
+                "HS_ADVERTISE_ADDR must be set when HS_ELECTION_LEASE is enabled".into(),
+            )
+        })?;
+        Ok(Some((
+            store,
+            Self {
+                node_id,
+                advertise_addr,
+                ttl,
+            },
+        )))
+    }
+}
+
+/// Switches this node to leader: writes are accepted until `lease` expires
+/// and the replication stream stops.
+async fn promote(
+    manager: &CollectionManager,
+    upstream: &watch::Sender<Option<String>>,
+    lease: &Lease,
+) {
+    manager.fencing.lead(lease.term, lease.expires_at_ms);
+    let mut state = manager.cluster_state.write().await;
+    if state.role != ClusterRole::Leader {
+        println!("👑 Election: promoted to leader (epoch {})", lease.term);
+    }
+    state.role = ClusterRole::Leader;
+    state.upstream_peer = None;
+    upstream.send_if_modified(|u| u.take().is_some());
+}
+
+/// Switches this node to follow the holder of `lease`, re-pointing
+/// replication if needed.
+async fn follow(
+    manager: &CollectionManager,
+    upstream: &watch::Sender<Option<String>>,
+    lease: &Lease,
+) {
+    manager.fencing.follow(lease.term);
+    let leader = lease.address.as_str();
+    let mut state = manager.cluster_state.write().await;
+    if state.role != ClusterRole::Follower || state.upstream_peer.as_deref() != Some(leader) {
+        println!("🧭 Election: following {leader}");
+    }
+    state.role = ClusterRole::Follower;
+    state.upstream_peer = Some(leader.to_string());
+    upstream.send_if_modified(|u| {
+        if u.as_deref() == Some(leader) {
+            return false;
+        }
+        *u = Some(leader.to_string());
+        true
+    });
+}
+
+/// Runs one election step: renew or claim the lease, or follow its holder.
+pub async fn step(
+    manager: &CollectionManager,
+    store: &dyn LeaseStore,
+    config: &ElectionConfig,
+    upstream: &watch::Sender<Option<String>>,
+) -> HyperspaceResult<()> {
+    let now = now_ms();
+    let is_leader = manager.cluster_state.read().await.role == ClusterRole::Leader;
+    let lease = match store.read().await? {
+        Some(l) if !is_leader && !l.is_expired(now) && l.holder != config.node_id => l,
+        _ => {
+            let candidate = Lease {
+                holder: config.node_id.clone(),
+                address: config.advertise_addr.clone(),
+                term: 0,
+                expires_at_ms: now + config.ttl.as_millis() as u64,
+            };
+            store.try_acquire(&candidate).await?
+        }
+    };
+
+    if lease.holder == config.node_id {
+        promote(manager, upstream, &lease).await;
+    } else {
+        follow(manager, upstream, &lease).await;
+    }
+    Ok(())
+}
+
+/// Spawns the election loop. `upstream` carries the leader address the
+/// replication stream should follow (`None` while this node leads).
+pub fn spawn(
+    manager: Weak<CollectionManager>,
+    store: Arc<dyn LeaseStore>,
+    config: ElectionConfig,
+    upstream: watch::Sender<Option<String>>,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.ttl / 3);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Some(mgr) = manager.upgrade() else {
+                break;
+            };
+            if let Err(e) = step(&mgr, store.as_ref(), &config, &upstream).await {
+                eprintln!("⚠️ Election: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(holder: &str, expires_at_ms: u64) -> Lease {
+        Lease {
+            holder: holder.into(),
+            address: format!("http://{holder}:50051"),
+            term: 0,
+            expires_at_ms,
+        }
+    }
+
+    #[test]
+    fn file_lease_changes_hands_only_after_expiry() {
+        let dir = std::env::temp_dir().join(format!("hs_lease_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = FileLeaseStore::new(dir.join("leader.lease"), Duration::from_secs(10));
+        let far = now_ms() + 60_000;
+
+        let a = store.try_acquire_sync(&lease("a", far)).unwrap();
+        assert_eq!((a.holder.as_str(), a.term), ("a", 1));
+        // A live lease held by someone else wins.
+        let b = store.try_acquire_sync(&lease("b", far)).unwrap();
+        assert_eq!(b.holder, "a");
+        // Renewal keeps the term.
+        assert_eq!(store.try_acquire_sync(&lease("a", 1)).unwrap().term, 1);
+        // Expired: the next candidate takes over with a new term.
+        let b = store.try_acquire_sync(&lease("b", far)).unwrap();
+        assert_eq!((b.holder.as_str(), b.term), ("b", 2));
+        assert!(!dir.join("leader.lock").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
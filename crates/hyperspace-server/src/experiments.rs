@@ -0,0 +1,242 @@
+//! Server-side A/B search experiments.
+//!
+//! An experiment pits two search configurations (arms) of one collection
+//! against each other. Searches that carry a `session_key` are assigned to
+//! arm B when a hash of the experiment name and the key falls below
+//! `arm_b_fraction`, otherwise to arm A, so a session sees the same arm on
+//! every query. Searches without a key keep their own settings.
+//!
+//! Each assigned search appends one NDJSON line to `experiment.log` in the
+//! collection directory (arm, session, latency, hit count and distances) for
+//! offline comparison; running totals per arm are kept in memory.
+//!
+//! The definition is stored in `experiment.json` next to `meta.json`.
+
+use crate::query_fusion::QueryFusion;
+use hyperspace_core::{HyperspaceError, HyperspaceResult, SearchResult};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const EXPERIMENT_FILE: &str = "experiment.json";
+pub const EXPERIMENT_LOG_FILE: &str = "experiment.log";
+
+/// Search settings one arm overrides; unset fields keep the request's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchArm {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef_search: Option<usize>,
+    /// Candidates reranked by late interaction, for searches with query tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank_candidates: Option<usize>,
+    /// Fusion of multi-query searches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_fusion: Option<QueryFusion>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    pub name: String,
+    pub arm_a: SearchArm,
+    pub arm_b: SearchArm,
+    /// Share of sessions routed to arm B, in `[0, 1]`.
+    pub arm_b_fraction: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arm {
+    A,
+    B,
+}
+
+impl Arm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::A => "a",
+            Self::B => "b",
+        }
+    }
+}
+
+impl Experiment {
+    pub fn validate(&self) -> HyperspaceResult<()> {
+        if self.name.is_empty() {
+            return Err(HyperspaceError::Validation(
+                "Experiment name cannot be empty".into(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.arm_b_fraction) {
+            return Err(HyperspaceError::Validation(format!(
+                "arm_b_fraction must be between 0 and 1, got {}",
+                self.arm_b_fraction
+            )));
+        }
+        for arm in [&self.arm_a, &self.arm_b] {
+            if arm.ef_search == Some(0) || arm.rerank_candidates == Some(0) {
+                return Err(HyperspaceError::Validation(
+                    "ef_search and rerank_candidates must be at least 1".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The arm of `session_key`; stable for a given experiment name.
+    pub fn assign(&self, session_key: &str) -> Arm {
+        let digest = Sha256::new()
+            .chain_update(self.name.as_bytes())
+            .chain_update([0])
+            .chain_update(session_key.as_bytes())
+            .finalize();
+        let mut head = [0u8; 8];
+        head.copy_from_slice(&digest[..8]);
+        // 53 bits map exactly onto [0, 1).
+        let point = (u64::from_be_bytes(head) >> 11) as f64 / (1u64 << 53) as f64;
+        if point < self.arm_b_fraction {
+            Arm::B
+        } else {
+            Arm::A
+        }
+    }
+
+    pub fn arm(&self, arm: Arm) -> &SearchArm {
+        match arm {
+            Arm::A => &self.arm_a,
+            Arm::B => &self.arm_b,
+        }
+    }
+}
+
+/// Running totals of one arm since the experiment was (re)defined.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ArmStats {
+    pub queries: u64,
+    pub empty_results: u64,
+    pub total_latency: Duration,
+    /// Sum of the best distance over searches that returned hits.
+    pub total_top_distance: f64,
+}
+
+#[derive(Serialize)]
+struct LogLine<'a> {
+    ts_ms: u128,
+    experiment: &'a str,
+    arm: &'a str,
+    session: &'a str,
+    latency_us: u128,
+    hits: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_distance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mean_distance: Option<f64>,
+}
+
+/// A defined experiment plus its log and counters.
+pub struct RunningExperiment {
+    pub experiment: Experiment,
+    state: Mutex<(LineWriter<File>, [ArmStats; 2])>,
+}
+
+impl RunningExperiment {
+    pub fn open(dir: &Path, experiment: Experiment) -> HyperspaceResult<Self> {
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(EXPERIMENT_LOG_FILE))?;
+        Ok(Self {
+            experiment,
+            state: Mutex::new((LineWriter::new(log), [ArmStats::default(); 2])),
+        })
+    }
+
+    /// Counts one search and appends it to the log.
+    pub fn record(&self, arm: Arm, session_key: &str, latency: Duration, hits: &[SearchResult]) {
+        let top_distance = hits.iter().map(|h| h.1).reduce(f64::min);
+        let line = LogLine {
+            ts_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            experiment: &self.experiment.name,
+            arm: arm.as_str(),
+            session: session_key,
+            latency_us: latency.as_micros(),
+            hits: hits.len(),
+            top_distance,
+            mean_distance: top_distance
+                .map(|_| hits.iter().map(|h| h.1).sum::<f64>() / hits.len() as f64),
+        };
+        let (log, arms) = &mut *self.state.lock();
+        let stats = &mut arms[arm as usize];
+        stats.queries += 1;
+        stats.total_latency += latency;
+        match top_distance {
+            Some(d) => stats.total_top_distance += d,
+            None => stats.empty_results += 1,
+        }
+        if let Ok(json) = serde_json::to_string(&line) {
+            if let Err(e) = writeln!(log, "{json}") {
+                eprintln!("⚠️ Experiment '{}' log write failed: {e}", line.experiment);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> [ArmStats; 2] {
+        self.state.lock().1
+    }
+}
+
+pub fn load(dir: &Path) -> HyperspaceResult<Option<Experiment>> {
+    match std::fs::read_to_string(dir.join(EXPERIMENT_FILE)) {
+        Ok(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| HyperspaceError::Corruption(format!("{EXPERIMENT_FILE}: {e}"))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save(dir: &Path, experiment: &Experiment) -> HyperspaceResult<()> {
+    let s = serde_json::to_string_pretty(experiment)
+        .map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+    let tmp = dir.join(format!("{EXPERIMENT_FILE}.tmp"));
+    std::fs::write(&tmp, s)?;
+    std::fs::rename(tmp, dir.join(EXPERIMENT_FILE))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assignment_is_sticky_and_follows_the_fraction() {
+        let experiment = Experiment {
+            name: "ef".into(),
+            arm_b_fraction: 0.25,
+            ..Default::default()
+        };
+        experiment.validate().unwrap();
+        let arms: Vec<Arm> = (0..4000)
+            .map(|i| experiment.assign(&format!("session-{i}")))
+            .collect();
+        let b = arms.iter().filter(|&&a| a == Arm::B).count();
+        assert!((800..1200).contains(&b), "{b} of 4000 sessions in arm B");
+        assert_eq!(experiment.assign("session-7"), arms[7]);
+
+        let all_a = Experiment {
+            arm_b_fraction: 0.0,
+            ..experiment.clone()
+        };
+        assert!((0..100).all(|i| all_a.assign(&i.to_string()) == Arm::A));
+        let invalid = Experiment {
+            arm_b_fraction: 1.5,
+            ..experiment
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Staleness gate for reads served by a follower.
+//!
+//! The leader reports its logical clock in a heartbeat at the start of every
+//! replication stream and every `HEARTBEAT_INTERVAL` after. A follower's lag
+//! is that clock minus the newest one it has applied. With
+//! `HS_FOLLOWER_MAX_STALENESS` set, a follower lagging by more than that many
+//! ticks, or not connected to its leader, is stale: reads fail with
+//! `UNAVAILABLE` (`HS_STALE_READS=reject`, the default) or are answered with
+//! an `x-hyperspace-stale: true` header (`HS_STALE_READS=flag`).
+//! `GET /api/ready` answers 503 while stale so load balancers can steer
+//! traffic away. Leaders are never stale.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How often an idle replication stream reports the leader's clock.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Response header marking a read served past the staleness bound.
+pub const STALE_HEADER: &str = "x-hyperspace-stale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StaleReads {
+    /// Refuse reads with `UNAVAILABLE` / 503.
+    Reject,
+    /// Serve reads, marked with [`STALE_HEADER`].
+    Flag,
+}
+
+pub struct FollowerReads {
+    // Ticks of lag tolerated; None never gates reads
+    max_staleness: Option<u64>,
+    mode: StaleReads,
+    following: AtomicBool,
+    connected: AtomicBool,
+    leader_clock: AtomicU64,
+    applied_clock: AtomicU64,
+}
+
+/// Replication lag as reported by `/api/cluster/status` and `/api/ready`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowerReadStatus {
+    pub following: bool,
+    pub connected: bool,
+    pub leader_clock: u64,
+    pub applied_clock: u64,
+    pub lag: u64,
+    pub max_staleness: Option<u64>,
+    pub stale: bool,
+    pub mode: StaleReads,
+}
+
+impl FollowerReads {
+    pub fn from_env() -> Self {
+        let max_staleness = std::env::var("HS_FOLLOWER_MAX_STALENESS")
+            .ok()
+            .and_then(|v| v.parse().ok());
+        let mode = match std::env::var("HS_STALE_READS").as_deref() {
+            Ok("flag") => StaleReads::Flag,
+            _ => StaleReads::Reject,
+        };
+        Self::new(max_staleness, mode)
+    }
+
+    pub fn new(max_staleness: Option<u64>, mode: StaleReads) -> Self {
+        Self {
+            max_staleness,
+            mode,
+            following: AtomicBool::new(false),
+            connected: AtomicBool::new(false),
+            leader_clock: AtomicU64::new(0),
+            applied_clock: AtomicU64::new(0),
+        }
+    }
+
+    pub fn mode(&self) -> StaleReads {
+        self.mode
+    }
+
+    /// Starts or stops following; a new leader's clock is unknown until its
+    /// first heartbeat.
+    pub fn set_following(&self, following: bool) {
+        self.following.store(following, Ordering::Release);
+        self.connected.store(false, Ordering::Release);
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Release);
+    }
+
+    /// Records the leader's clock from a heartbeat.
+    pub fn leader_at(&self, clock: u64) {
+        self.leader_clock.fetch_max(clock, Ordering::AcqRel);
+    }
+
+    /// Records an applied entry; the leader is at least that far along.
+    pub fn applied(&self, clock: u64) {
+        self.applied_clock.fetch_max(clock, Ordering::AcqRel);
+        self.leader_clock.fetch_max(clock, Ordering::AcqRel);
+    }
+
+    pub fn lag(&self) -> u64 {
+        self.leader_clock
+            .load(Ordering::Acquire)
+            .saturating_sub(self.applied_clock.load(Ordering::Acquire))
+    }
+
+    /// Whether reads should be refused or flagged right now.
+    pub fn is_stale(&self) -> bool {
+        let Some(max) = self.max_staleness else {
+            return false;
+        };
+        if !self.following.load(Ordering::Acquire) {
+            return false;
+        }
+        !self.connected.load(Ordering::Acquire) || self.lag() > max
+    }
+
+    pub fn status(&self) -> FollowerReadStatus {
+        FollowerReadStatus {
+            following: self.following.load(Ordering::Acquire),
+            connected: self.connected.load(Ordering::Acquire),
+            leader_clock: self.leader_clock.load(Ordering::Acquire),
+            applied_clock: self.applied_clock.load(Ordering::Acquire),
+            lag: self.lag(),
+            max_staleness: self.max_staleness,
+            stale: self.is_stale(),
+            mode: self.mode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gates_reads_past_max_staleness() {
+        let reads = FollowerReads::new(Some(10), StaleReads::Reject);
+        // Leaders serve reads regardless
+        assert!(!reads.is_stale());
+
+        reads.set_following(true);
+        assert!(reads.is_stale(), "not connected yet");
+        reads.set_connected(true);
+        reads.leader_at(100);
+        reads.applied(85);
+        assert_eq!(reads.lag(), 15);
+        assert!(reads.is_stale());
+        reads.applied(90);
+        assert!(!reads.is_stale());
+
+        // Entries newer than the last heartbeat raise the leader's clock too
+        reads.applied(120);
+        assert_eq!(reads.lag(), 0);
+
+        reads.set_connected(false);
+        assert!(reads.is_stale());
+        reads.set_following(false);
+        assert!(!reads.is_stale());
+    }
+
+    #[test]
+    fn never_stale_without_a_bound() {
+        let reads = FollowerReads::new(None, StaleReads::Flag);
+        reads.set_following(true);
+        reads.leader_at(1_000);
+        assert!(!reads.is_stale());
+        assert_eq!(reads.status().lag, 1_000);
+    }
+}
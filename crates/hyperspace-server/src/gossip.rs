@@ -1,25 +1,35 @@
 //! # Gossip Protocol — Swarm Discovery & Health (Task 3.4)
 //!
-//! Lightweight UDP-based gossip for peer-to-peer node discovery.
+//! Lightweight UDP-based gossip for peer-to-peer node discovery and cluster
+//! membership.
 //!
 //! ## Protocol
-//! Each node broadcasts a `GossipMessage::Heartbeat` every `HEARTBEAT_INTERVAL`.
-//! Peers that haven't been seen for `PEER_TTL` are evicted from the peer table.
+//! Each node broadcasts a `GossipMessage::Heartbeat` every `HEARTBEAT_INTERVAL`
+//! carrying its live role, leader, gRPC address and collections, plus the
+//! gossip addresses of the peers it knows. Heartbeats go to the seeds, to every
+//! known peer and to the peers those peers know, so a node only needs one seed
+//! to find the whole cluster. Peers that haven't been seen for `PEER_TTL` are
+//! evicted from the peer table.
+//!
+//! [`topology`] turns the peer table into the membership list served by
+//! `GetTopology` and `GET /api/cluster/topology`, which clients use to find
+//! leaders and followers from any node.
 //!
 //! ## How to enable
 //! Set `HS_GOSSIP_PORT` (default: 7946) and `HS_GOSSIP_PEERS` (comma-separated
 //! list of known seed peers, e.g. `192.168.1.10:7946,192.168.1.11:7946`).
+//! Peers reach this node's gRPC API at `HS_ADVERTISE_ADDR` when set, otherwise
+//! at the address its heartbeats come from.
 //!
 //! ## Zero-dependency design
 //! Uses raw `tokio::net::UdpSocket` — no libp2p required.
 //! libp2p / Kademlia DHT is planned for Sprint 6.
 
-use crate::manager::ClusterRole;
-use crate::sync::CollectionDigest;
+use crate::manager::CollectionManager;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::net::UdpSocket;
 use tokio::sync::RwLock;
@@ -29,7 +39,8 @@ use tokio::sync::RwLock;
 pub const DEFAULT_GOSSIP_PORT: u16 = 7946;
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const PEER_TTL: Duration = Duration::from_secs(30);
-const MAX_UDP_PAYLOAD: usize = 4096;
+// Largest UDP datagram; heartbeats list every collection and known peer
+const MAX_UDP_PAYLOAD: usize = 65_507;
 
 // ─── Data Structures ────────────────────────────────────────────────────────
 
@@ -48,6 +59,18 @@ pub enum GossipMessage {
         /// Lightweight collection digests (name + state_hash only, not full bucket list).
         digests: Vec<GossipCollectionSummary>,
         timestamp_secs: u64,
+        /// The gRPC port, reached at the heartbeat's source IP.
+        #[serde(default)]
+        grpc_port: u16,
+        /// `HS_ADVERTISE_ADDR`, overriding the derived gRPC address.
+        #[serde(default)]
+        advertise_addr: Option<String>,
+        /// gRPC address of the leader a follower replicates from.
+        #[serde(default)]
+        leader: Option<String>,
+        /// Gossip addresses of the peers this node knows.
+        #[serde(default)]
+        members: Vec<String>,
     },
     /// Request full sync handshake from a specific collection.
     SyncRequest {
@@ -81,6 +104,15 @@ pub struct PeerInfo {
     pub last_seen_secs: u64,
     /// Derived: whether peer appears healthy (last_seen < TTL).
     pub healthy: bool,
+    /// Peer's gRPC API URL, e.g. `http://10.0.0.5:50051`.
+    #[serde(default)]
+    pub grpc_addr: String,
+    /// gRPC address of the peer's leader, when it follows one.
+    #[serde(default)]
+    pub leader: Option<String>,
+    /// Gossip addresses the peer knows, contacted to discover more peers.
+    #[serde(default)]
+    pub members: Vec<String>,
 }
 
 impl PeerInfo {
@@ -95,17 +127,64 @@ pub type PeerRegistry = Arc<RwLock<HashMap<String, PeerInfo>>>;
 
 // ─── Gossip Engine ──────────────────────────────────────────────────────────
 
+/// A cluster member as listed by `GetTopology`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterNode {
+    pub node_id: String,
+    pub role: String,
+    /// Empty for the answering node without `HS_ADVERTISE_ADDR`: the caller
+    /// already knows how to reach it.
+    pub grpc_addr: String,
+    pub http_addr: String,
+    pub leader: Option<String>,
+    pub logical_clock: u64,
+    pub collections: Vec<String>,
+    pub healthy: bool,
+    /// The node that answered.
+    pub local: bool,
+}
+
+/// This node followed by every live peer.
+pub async fn topology(manager: &CollectionManager) -> Vec<ClusterNode> {
+    let state = manager.cluster_state.read().await.clone();
+    let collections = manager.list_all();
+    let mut nodes = vec![ClusterNode {
+        node_id: state.node_id,
+        role: format!("{:?}", state.role),
+        grpc_addr: std::env::var("HS_ADVERTISE_ADDR").unwrap_or_default(),
+        http_addr: String::new(),
+        leader: state.upstream_peer,
+        logical_clock: state.logical_clock,
+        collections,
+        healthy: true,
+        local: true,
+    }];
+    let peers = manager.peers.read().await;
+    let mut live: Vec<&PeerInfo> = peers.values().filter(|p| !p.is_stale()).collect();
+    live.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    nodes.extend(live.into_iter().map(|peer| ClusterNode {
+        node_id: peer.node_id.clone(),
+        role: peer.role.clone(),
+        grpc_addr: peer.grpc_addr.clone(),
+        http_addr: peer.addr.clone(),
+        leader: peer.leader.clone(),
+        logical_clock: peer.logical_clock,
+        collections: peer.collections.iter().map(|c| c.name.clone()).collect(),
+        healthy: peer.healthy,
+        local: false,
+    }));
+    nodes
+}
+
 /// Starts the gossip engine as two concurrent tasks:
 /// - **Broadcaster**: Sends heartbeats to all known seed peers + discovered peers.
 /// - **Listener**: Receives heartbeats and updates the peer registry.
 ///
-/// Returns the shared `PeerRegistry` that the HTTP layer reads for /api/swarm/peers.
+/// Returns the manager's `PeerRegistry` that the HTTP layer reads for /api/swarm/peers.
 pub async fn start_gossip(
-    node_id: String,
-    role: ClusterRole,
+    manager: &Arc<CollectionManager>,
     http_port: u16,
-    logical_clock_ref: Arc<tokio::sync::RwLock<u64>>,
-    digests_ref: Arc<RwLock<Vec<CollectionDigest>>>,
+    grpc_port: u16,
 ) -> PeerRegistry {
     let gossip_port = std::env::var("HS_GOSSIP_PORT")
         .ok()
@@ -119,7 +198,8 @@ pub async fn start_gossip(
         .map(String::from)
         .collect();
 
-    let registry: PeerRegistry = Arc::new(RwLock::new(HashMap::new()));
+    let registry = manager.peers.clone();
+    let node_id = manager.cluster_state.read().await.node_id.clone();
 
     // Spawn listener
     let registry_l = Arc::clone(&registry);
@@ -130,20 +210,16 @@ pub async fn start_gossip(
     });
 
     // Spawn broadcaster
-    let registry_b = Arc::clone(&registry);
-    let role_str = format!("{role:?}");
+    let identity = Identity {
+        node_id,
+        http_port,
+        gossip_port,
+        grpc_port,
+        advertise_addr: std::env::var("HS_ADVERTISE_ADDR").ok(),
+    };
+    let manager = Arc::downgrade(manager);
     tokio::spawn(async move {
-        run_broadcaster(
-            node_id,
-            role_str,
-            http_port,
-            gossip_port,
-            seed_peers,
-            logical_clock_ref,
-            digests_ref,
-            registry_b,
-        )
-        .await;
+        run_broadcaster(identity, seed_peers, manager).await;
     });
 
     println!(
@@ -198,6 +274,10 @@ async fn handle_incoming(
         logical_clock,
         digests,
         timestamp_secs,
+        grpc_port,
+        advertise_addr,
+        leader,
+        members,
     } = msg
     {
         // Ignore our own broadcasts
@@ -220,6 +300,14 @@ async fn handle_incoming(
                 collections: digests,
                 last_seen_secs: timestamp_secs,
                 healthy,
+                // Nodes predating GetTopology don't send a gRPC port
+                grpc_addr: match (advertise_addr, grpc_port) {
+                    (Some(addr), _) => addr,
+                    (None, 0) => String::new(),
+                    (None, port) => format!("http://{peer_ip}:{port}"),
+                },
+                leader,
+                members,
             },
         );
     }
@@ -227,16 +315,19 @@ async fn handle_incoming(
 
 // ─── Broadcaster Task ───────────────────────────────────────────────────────
 
-#[allow(clippy::too_many_arguments)]
-async fn run_broadcaster(
+/// What a node says about itself that never changes while it runs.
+struct Identity {
     node_id: String,
-    role: String,
     http_port: u16,
     gossip_port: u16,
+    grpc_port: u16,
+    advertise_addr: Option<String>,
+}
+
+async fn run_broadcaster(
+    identity: Identity,
     seed_peers: Vec<String>,
-    logical_clock_ref: Arc<tokio::sync::RwLock<u64>>,
-    digests_ref: Arc<RwLock<Vec<CollectionDigest>>>,
-    registry: PeerRegistry,
+    manager: Weak<CollectionManager>,
 ) {
     // Bind to any source port for sending
     let Ok(sock) = UdpSocket::bind("0.0.0.0:0").await else {
@@ -247,40 +338,66 @@ async fn run_broadcaster(
     let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
     loop {
         interval.tick().await;
-
-        let logical_clock = *logical_clock_ref.read().await;
-        let digests = digests_ref.read().await;
-        let summaries: Vec<GossipCollectionSummary> = digests
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        let registry = manager.peers.clone();
+
+        // Role and leader change at runtime with election
+        let (role, leader, logical_clock) = {
+            let state = manager.cluster_state.read().await;
+            (
+                format!("{:?}", state.role),
+                state.upstream_peer.clone(),
+                state.logical_clock,
+            )
+        };
+        let summaries: Vec<GossipCollectionSummary> = manager
+            .resident_collections()
             .iter()
-            .map(|d| GossipCollectionSummary {
-                name: d.collection_name.clone(),
-                state_hash: d.state_hash,
-                vector_count: d.vector_count,
-                logical_clock: d.logical_clock,
+            .map(|col| GossipCollectionSummary {
+                name: col.name().to_string(),
+                state_hash: col.state_hash(),
+                vector_count: col.count(),
+                logical_clock,
             })
             .collect();
-        drop(digests);
+        drop(manager);
+
+        // Seeds, known peers, and the peers they know
+        let (members, targets) = {
+            let reg = registry.read().await;
+            let members: Vec<String> = reg
+                .values()
+                .filter(|p| p.healthy)
+                .map(|p| p.gossip_addr.clone())
+                .collect();
+            let targets: HashSet<String> = seed_peers
+                .iter()
+                .cloned()
+                .chain(reg.values().map(|p| p.gossip_addr.clone()))
+                .chain(reg.values().flat_map(|p| p.members.iter().cloned()))
+                .collect();
+            (members, targets)
+        };
 
         let msg = GossipMessage::Heartbeat {
-            node_id: node_id.clone(),
-            role: role.clone(),
-            http_port,
-            gossip_port,
+            node_id: identity.node_id.clone(),
+            role,
+            http_port: identity.http_port,
+            gossip_port: identity.gossip_port,
             logical_clock,
             digests: summaries,
             timestamp_secs: now_secs(),
+            grpc_port: identity.grpc_port,
+            advertise_addr: identity.advertise_addr.clone(),
+            leader,
+            members,
         };
 
         if let Ok(payload) = serde_json::to_vec(&msg) {
-            // Send to seed peers
-            for peer in &seed_peers {
-                let _ = sock.send_to(&payload, peer).await;
-            }
-
-            // Send to all discovered peers — use their gossip_addr (not HTTP)
-            let reg = registry.read().await;
-            for peer in reg.values() {
-                let _ = sock.send_to(&payload, &peer.gossip_addr).await;
+            for target in &targets {
+                let _ = sock.send_to(&payload, target).await;
             }
         }
 
@@ -322,6 +439,9 @@ mod tests {
             collections: vec![],
             last_seen_secs: 0, // Very old
             healthy: false,
+            grpc_addr: "http://127.0.0.1:50051".to_string(),
+            leader: None,
+            members: vec![],
         };
         assert!(peer.is_stale());
 
@@ -344,6 +464,10 @@ mod tests {
                 logical_clock: 42,
             }],
             timestamp_secs: now_secs(),
+            grpc_port: 50051,
+            advertise_addr: None,
+            leader: None,
+            members: vec!["10.0.0.7:7946".to_string()],
         };
         let bytes = serde_json::to_vec(&msg).unwrap();
         assert!(!bytes.is_empty());
@@ -356,6 +480,59 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_heartbeat_records_membership() {
+        let registry = PeerRegistry::default();
+        let from: SocketAddr = "10.0.0.5:40123".parse().unwrap();
+        let heartbeat = |node_id: &str, advertise_addr: Option<&str>| GossipMessage::Heartbeat {
+            node_id: node_id.to_string(),
+            role: "Follower".to_string(),
+            http_port: 50050,
+            gossip_port: 7946,
+            logical_clock: 7,
+            digests: vec![],
+            timestamp_secs: now_secs(),
+            grpc_port: 50051,
+            advertise_addr: advertise_addr.map(String::from),
+            leader: Some("http://10.0.0.1:50051".to_string()),
+            members: vec!["10.0.0.9:7946".to_string()],
+        };
+
+        for (node_id, advertise) in [("a", None), ("b", Some("http://db-b:50051")), ("me", None)] {
+            let bytes = serde_json::to_vec(&heartbeat(node_id, advertise)).unwrap();
+            handle_incoming(&bytes, from, "me", &registry).await;
+        }
+
+        let reg = registry.read().await;
+        assert_eq!(reg.len(), 2, "own heartbeats are ignored");
+        assert_eq!(reg["a"].grpc_addr, "http://10.0.0.5:50051");
+        assert_eq!(reg["a"].gossip_addr, "10.0.0.5:7946");
+        assert_eq!(reg["a"].leader.as_deref(), Some("http://10.0.0.1:50051"));
+        assert_eq!(reg["a"].members, vec!["10.0.0.9:7946"]);
+        assert_eq!(reg["b"].grpc_addr, "http://db-b:50051");
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_from_older_node() {
+        let registry = PeerRegistry::default();
+        let from: SocketAddr = "10.0.0.5:40123".parse().unwrap();
+        let old = serde_json::json!({
+            "type": "Heartbeat",
+            "node_id": "old",
+            "role": "Leader",
+            "http_port": 50050,
+            "gossip_port": 7946,
+            "logical_clock": 3,
+            "digests": [],
+            "timestamp_secs": now_secs(),
+        });
+        handle_incoming(&serde_json::to_vec(&old).unwrap(), from, "me", &registry).await;
+
+        let reg = registry.read().await;
+        assert_eq!(reg["old"].grpc_addr, "");
+        assert!(reg["old"].members.is_empty());
+    }
+
     #[test]
     fn test_collection_summary_state_hash() {
         let summary = GossipCollectionSummary {
@@ -0,0 +1,260 @@
+//! HNSW graph export for offline analysis.
+//!
+//! Dumps the index topology, one layer or all of them, with the distance of
+//! every link:
+//!
+//! - `graphml`: one directed graph. Nodes carry `top_layer`, edges carry
+//!   `layer` and `distance`. Loads with `networkx.read_graphml` and in Gephi;
+//!   a pair linked on several layers gives parallel edges.
+//! - `csv`: an edge list with a `layer,source,target,distance` header.
+//! - `parquet`: the same columns, one row group per layer. Only with the
+//!   `graph-parquet` feature.
+//!
+//! Links are directed: HNSW keeps separate neighbour lists on both ends, so
+//! `a -> b` does not imply `b -> a`. Deleted points are left out.
+
+use hyperspace_core::{Collection, GraphLayer, HyperspaceError, HyperspaceResult};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    GraphMl,
+    Csv,
+    Parquet,
+}
+
+impl GraphFormat {
+    pub fn parse(s: &str) -> HyperspaceResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "graphml" => Ok(Self::GraphMl),
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(HyperspaceError::Validation(format!(
+                "Unknown graph export format '{other}', expected graphml, csv or parquet"
+            ))),
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::GraphMl => "application/graphml+xml",
+            Self::Csv => "text/csv",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::GraphMl => "graphml",
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// Writes the graph of `col` to `out`: only `layer` when given, otherwise
+/// every layer from 0 up.
+pub fn export(
+    col: &dyn Collection,
+    layer: Option<usize>,
+    format: GraphFormat,
+    out: &Path,
+) -> HyperspaceResult<()> {
+    let layers: Vec<(usize, GraphLayer)> = match layer {
+        Some(layer) => vec![(layer, col.graph_layer(layer)?)],
+        None => (0..=col.graph_max_layer())
+            .map(|l| Ok((l, col.graph_layer(l)?)))
+            .collect::<HyperspaceResult<_>>()?,
+    };
+    let file = File::create(out)?;
+    match format {
+        GraphFormat::GraphMl => write_graphml(BufWriter::new(file), &layers)?,
+        GraphFormat::Csv => write_csv(BufWriter::new(file), &layers)?,
+        GraphFormat::Parquet => write_parquet(file, &layers)?,
+    }
+    Ok(())
+}
+
+fn write_graphml<W: Write>(mut w: W, layers: &[(usize, GraphLayer)]) -> std::io::Result<()> {
+    // Layers are nested, so a node's top layer is the last one it shows up on.
+    let mut top_layer = BTreeMap::new();
+    for (layer, nodes) in layers {
+        for (id, _) in nodes {
+            top_layer.insert(*id, *layer);
+        }
+    }
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        w,
+        r#"  <key id="top_layer" for="node" attr.name="top_layer" attr.type="int"/>"#
+    )?;
+    writeln!(
+        w,
+        r#"  <key id="layer" for="edge" attr.name="layer" attr.type="int"/>"#
+    )?;
+    writeln!(
+        w,
+        r#"  <key id="distance" for="edge" attr.name="distance" attr.type="double"/>"#
+    )?;
+    writeln!(w, r#"  <graph id="hnsw" edgedefault="directed">"#)?;
+    for (id, layer) in &top_layer {
+        writeln!(
+            w,
+            r#"    <node id="n{id}"><data key="top_layer">{layer}</data></node>"#
+        )?;
+    }
+    for (layer, nodes) in layers {
+        for (source, edges) in nodes {
+            for (target, distance) in edges {
+                writeln!(
+                    w,
+                    r#"    <edge source="n{source}" target="n{target}"><data key="layer">{layer}</data><data key="distance">{distance}</data></edge>"#
+                )?;
+            }
+        }
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")?;
+    w.flush()
+}
+
+fn write_csv<W: Write>(mut w: W, layers: &[(usize, GraphLayer)]) -> std::io::Result<()> {
+    writeln!(w, "layer,source,target,distance")?;
+    for (layer, nodes) in layers {
+        for (source, edges) in nodes {
+            for (target, distance) in edges {
+                writeln!(w, "{layer},{source},{target},{distance}")?;
+            }
+        }
+    }
+    w.flush()
+}
+
+#[cfg(feature = "graph-parquet")]
+fn write_parquet(file: File, layers: &[(usize, GraphLayer)]) -> HyperspaceResult<()> {
+    use parquet::data_type::{DoubleType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    let err = |e: parquet::errors::ParquetError| HyperspaceError::Internal(e.to_string());
+    let schema = parse_message_type(
+        "message hnsw_edges {
+            REQUIRED INT32 layer;
+            REQUIRED INT64 source;
+            REQUIRED INT64 target;
+            REQUIRED DOUBLE distance;
+        }",
+    )
+    .map_err(err)?;
+    let props = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), props).map_err(err)?;
+    for (layer, nodes) in layers {
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        let mut distances = Vec::new();
+        for (source, edges) in nodes {
+            for (target, distance) in edges {
+                sources.push(i64::from(*source));
+                targets.push(i64::from(*target));
+                distances.push(*distance);
+            }
+        }
+        let layer_col = vec![i32::try_from(*layer).unwrap_or(i32::MAX); sources.len()];
+
+        let mut group = writer.next_row_group().map_err(err)?;
+        if let Some(mut col) = group.next_column().map_err(err)? {
+            col.typed::<Int32Type>()
+                .write_batch(&layer_col, None, None)
+                .map_err(err)?;
+            col.close().map_err(err)?;
+        }
+        for values in [&sources, &targets] {
+            if let Some(mut col) = group.next_column().map_err(err)? {
+                col.typed::<Int64Type>()
+                    .write_batch(values, None, None)
+                    .map_err(err)?;
+                col.close().map_err(err)?;
+            }
+        }
+        if let Some(mut col) = group.next_column().map_err(err)? {
+            col.typed::<DoubleType>()
+                .write_batch(&distances, None, None)
+                .map_err(err)?;
+            col.close().map_err(err)?;
+        }
+        group.close().map_err(err)?;
+    }
+    writer.close().map_err(err)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "graph-parquet"))]
+fn write_parquet(_file: File, _layers: &[(usize, GraphLayer)]) -> HyperspaceResult<()> {
+    Err(HyperspaceError::Validation(
+        "Parquet export needs a server built with the graph-parquet feature".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<(usize, GraphLayer)> {
+        vec![
+            (
+                0,
+                vec![
+                    (1, vec![(2, 0.5)]),
+                    (2, vec![(1, 0.5), (3, 1.25)]),
+                    (3, Vec::new()),
+                ],
+            ),
+            (1, vec![(2, Vec::new())]),
+        ]
+    }
+
+    #[test]
+    fn csv_and_graphml_list_every_edge() {
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &sample()).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "layer,source,target,distance\n0,1,2,0.5\n0,2,1,0.5\n0,2,3,1.25\n"
+        );
+
+        let mut xml = Vec::new();
+        write_graphml(&mut xml, &sample()).unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 3);
+        assert!(xml.contains(r#"<node id="n2"><data key="top_layer">1</data></node>"#));
+        assert!(xml.contains(
+            r#"<edge source="n2" target="n3"><data key="layer">0</data><data key="distance">1.25</data></edge>"#
+        ));
+    }
+
+    #[cfg(feature = "graph-parquet")]
+    #[test]
+    fn parquet_has_one_row_group_per_layer() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let path = std::env::temp_dir().join(format!("hs_graph_{}.parquet", uuid::Uuid::new_v4()));
+        write_parquet(File::create(&path).unwrap(), &sample()).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let meta = reader.metadata();
+        assert_eq!(meta.num_row_groups(), 2);
+        assert_eq!(meta.row_group(0).num_rows(), 3);
+        assert_eq!(meta.row_group(1).num_rows(), 0);
+        assert_eq!(meta.file_metadata().schema_descr().num_columns(), 4);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,130 @@
+//! Group commit for `Strict` WAL writes.
+//!
+//! Fsyncing every insert caps durable throughput at the disk's sync rate.
+//! With `HS_WAL_GROUP_COMMIT_MS` above zero, Strict writers append and flush
+//! under the WAL lock, release it, and then wait here. The committer waits up
+//! to that many milliseconds for more writers, issues one fsync for the whole
+//! batch and wakes everyone. A write is still acknowledged only after it is
+//! on disk.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
+use std::fs::File;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+static FSYNCS: AtomicU64 = AtomicU64::new(0);
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide (fsyncs issued, writes committed) by group commit.
+pub fn stats() -> (u64, u64) {
+    (
+        FSYNCS.load(Ordering::Relaxed),
+        WRITES.load(Ordering::Relaxed),
+    )
+}
+
+/// Batching window from `HS_WAL_GROUP_COMMIT_MS` (default 2); `None` when
+/// `0`, which keeps one fsync per Strict write.
+pub fn window_from_env() -> Option<Duration> {
+    let ms = std::env::var("HS_WAL_GROUP_COMMIT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(2);
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+struct Waiter {
+    file: Arc<File>,
+    done: oneshot::Sender<Result<(), String>>,
+}
+
+/// Per-collection commit batcher.
+pub struct GroupCommit {
+    tx: mpsc::UnboundedSender<Waiter>,
+}
+
+impl GroupCommit {
+    /// Starts the committer task; abort the handle to stop it.
+    pub fn spawn(window: Duration) -> (Self, JoinHandle<()>) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Waiter>();
+        let task = tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                tokio::time::sleep(window).await;
+                let mut batch = vec![first];
+                while let Ok(w) = rx.try_recv() {
+                    batch.push(w);
+                }
+
+                // Usually one segment; two if the WAL rotated mid-batch.
+                let mut files: Vec<Arc<File>> = Vec::new();
+                for w in &batch {
+                    if !files.iter().any(|f| Arc::ptr_eq(f, &w.file)) {
+                        files.push(w.file.clone());
+                    }
+                }
+                let synced = files.len() as u64;
+                let result = tokio::task::spawn_blocking(move || {
+                    files.iter().try_for_each(|f| f.sync_all())
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()));
+
+                FSYNCS.fetch_add(synced, Ordering::Relaxed);
+                WRITES.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                for w in batch {
+                    let _ = w.done.send(result.clone());
+                }
+            }
+        });
+        (Self { tx }, task)
+    }
+
+    /// Waits until `file` has been fsynced by a batch that started after
+    /// this call.
+    pub async fn sync(&self, file: Arc<File>) -> HyperspaceResult<()> {
+        let (done, rx) = oneshot::channel();
+        self.tx
+            .send(Waiter { file, done })
+            .map_err(|_| HyperspaceError::Internal("WAL committer stopped".into()))?;
+        rx.await
+            .map_err(|_| HyperspaceError::Internal("WAL committer stopped".into()))?
+            .map_err(|e| HyperspaceError::Io(std::io::Error::other(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_writers_share_one_fsync() {
+        let dir = std::env::temp_dir().join(format!("hs_group_commit_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = Arc::new(File::create(dir.join("wal.log")).unwrap());
+        let (commit, task) = GroupCommit::spawn(Duration::from_millis(20));
+        let commit = Arc::new(commit);
+
+        let (fsyncs_before, writes_before) = stats();
+        let waiters: Vec<_> = (0..16)
+            .map(|_| {
+                let commit = commit.clone();
+                let file = file.clone();
+                tokio::spawn(async move { commit.sync(file).await })
+            })
+            .collect();
+        for w in waiters {
+            w.await.unwrap().unwrap();
+        }
+        let (fsyncs, writes) = stats();
+
+        // Other tests may commit concurrently, so only bound the counts.
+        assert!(writes - writes_before >= 16);
+        assert!(fsyncs - fsyncs_before < 16, "{fsyncs_before} -> {fsyncs}");
+        task.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,20 @@
+use crate::auth::{self, Scope};
+use crate::bulk::{BulkEvent, BulkIngest};
+use crate::capacity;
+use crate::collection_spec::{CollectionSpec, IvfParams};
+use crate::follower_reads::{self, FollowerReadStatus, StaleReads};
 use crate::gossip::PeerRegistry;
+use crate::graph_export::{self, GraphFormat};
+use crate::ingest::{self, IngestRequest, TextEmbedder};
+use crate::limits::CollectionLimits;
 use crate::manager::CollectionManager;
+use crate::manager::{CollectionInfo, CollectionOptions};
+use crate::query_templates::{self, QueryTemplate};
+use crate::snapshot::SnapshotPolicy;
+use crate::snapshot_history::SnapshotRef;
+use crate::telemetry;
 use axum::{
+    body::Body,
     extract::{Extension, Path, Query, Request, State},
     http::{StatusCode, Uri},
     middleware::{self, Next},
@@ -8,15 +22,16 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use hyperspace_core::SearchParams;
+use futures::StreamExt;
+use hyperspace_core::{SearchParams, SynonymMap};
 use rust_embed::RustEmbed;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::sync::Arc;
 use std::sync::OnceLock;
 use std::time::Instant;
 use sysinfo::Pid;
-use tikv_jemalloc_ctl::epoch;
 use tower_http::cors::CorsLayer;
 
 const TYPED_META_PREFIX: &str = "__hs_typed__";
@@ -30,16 +45,19 @@ struct FrontendAssets;
 pub struct RequestContext {
     pub user_id: String,
     pub is_admin: bool,
+    /// Authenticated with `HYPERSPACE_READ_API_KEY`.
+    pub read_only: bool,
 }
 
 async fn validate_api_key(
-    State(expected_hash): State<Option<String>>,
+    State((expected_hash, read_hash)): State<(Option<String>, Option<String>)>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let mut ctx = RequestContext {
         user_id: "anonymous".to_string(),
         is_admin: false,
+        read_only: false,
     };
 
     // 1. Extract User Identity (for Multi-tenancy)
@@ -67,18 +85,28 @@ async fn validate_api_key(
                     if ctx.user_id == "anonymous" {
                         ctx.user_id = "default_admin".to_string();
                     }
+                } else if read_hash.as_deref() == Some(hash.as_str()) {
+                    // Same data as the admin key, read-only routes only
+                    ctx.read_only = true;
+                    if ctx.user_id == "anonymous" {
+                        ctx.user_id = "default_admin".to_string();
+                    }
                 }
             }
         }
 
         // 3. Enforce Auth for API endpoints
         let path = request.uri().path();
-        if path.starts_with("/api/") || path == "/metrics" {
+        // Readiness probes come from load balancers without a key
+        if (path.starts_with("/api/") || path == "/metrics") && path != "/api/ready" {
             // If neither valid API key nor valid x-hyperspace-user-id was provided
             if !ctx.is_admin && ctx.user_id == "anonymous" {
                 return Err(StatusCode::UNAUTHORIZED);
             }
         }
+        if ctx.read_only && auth::http_scope(request.method(), path) == Scope::Admin {
+            return Err(StatusCode::FORBIDDEN);
+        }
     } else {
         // No Auth configured in environment (Dev mode)
         ctx.is_admin = true;
@@ -92,6 +120,79 @@ async fn validate_api_key(
     Ok(next.run(request).await)
 }
 
+/// Refuses or flags collection reads while this follower is stale; see
+/// [`crate::follower_reads`].
+async fn gate_stale_reads(
+    State(manager): State<Arc<CollectionManager>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let gated = path.starts_with("/api/collections")
+        && auth::http_scope(request.method(), path) == Scope::Read;
+    let reads = &manager.follower_reads;
+    if !gated || !reads.is_stale() {
+        return next.run(request).await;
+    }
+    match reads.mode() {
+        StaleReads::Reject => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!(
+                "Follower is {} ticks behind its leader; read from the leader",
+                reads.lag()
+            ),
+        )
+            .into_response(),
+        StaleReads::Flag => {
+            let mut response = next.run(request).await;
+            response.headers_mut().insert(
+                follower_reads::STALE_HEADER,
+                axum::http::HeaderValue::from_static("true"),
+            );
+            response
+        }
+    }
+}
+
+/// Whether `method` on `path` writes collection data, which only a current
+/// leader may accept.
+pub(crate) fn is_data_write(method: &axum::http::Method, path: &str) -> bool {
+    use axum::http::Method;
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["api", "collections"] => *method == Method::POST,
+        ["api", "collections", _] => *method == Method::DELETE,
+        ["api", "collections", _, action] => match *action {
+            "insert" | "bulk" | "ingest" | "purge" | "snapshot" | "scrub" | "rebuild" => {
+                *method == Method::POST
+            }
+            "synonyms" => *method == Method::PUT,
+            _ => false,
+        },
+        ["api", "specs"] => *method == Method::PUT,
+        _ => false,
+    }
+}
+
+/// Refuses data writes on followers and on a leader fenced off by a newer
+/// epoch or an expired lease, as `check_writable` does for gRPC.
+async fn fence_writes(
+    State(manager): State<Arc<CollectionManager>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_data_write(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+    if manager.cluster_state.read().await.role == crate::manager::ClusterRole::Follower {
+        return (StatusCode::FORBIDDEN, "Followers are read-only").into_response();
+    }
+    if let Err(e) = manager.fencing.check_write() {
+        return (StatusCode::CONFLICT, e).into_response();
+    }
+    next.run(request).await
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct ModelStatus {
     pub enabled: bool,
@@ -111,6 +212,7 @@ pub async fn start_http_server(
     port: u16,
     embedding_info: Option<EmbeddingInfo>,
     peer_registry: Option<PeerRegistry>,
+    embedder: TextEmbedder,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Get API key hash if set
     let api_key_hash = std::env::var("HYPERSPACE_API_KEY").ok().map(|key| {
@@ -118,6 +220,7 @@ pub async fn start_http_server(
         hasher.update(key.as_bytes());
         hex::encode(hasher.finalize())
     });
+    let read_key_hash = auth::read_key_hash();
 
     let start_time = Arc::new(Instant::now());
     let embedding_state = Arc::new(embedding_info);
@@ -132,10 +235,31 @@ pub async fn start_http_server(
             get(get_collection_digest).delete(delete_collection),
         )
         .route("/api/collections/{name}/insert", post(insert_vector))
+        .route("/api/collections/{name}/bulk", post(bulk_insert))
+        .route("/api/collections/{name}/ingest", post(start_ingest))
+        .route("/api/ingest", get(list_ingest_jobs))
+        .route("/api/ingest/{job}", get(get_ingest_job))
         .route("/api/collections/{name}/stats", get(get_stats))
         .route("/api/collections/{name}/digest", get(get_collection_digest))
         .route("/api/collections/{name}/peek", get(peek_collection))
+        .route("/api/collections/{name}/snapshots", get(list_snapshots))
         .route("/api/collections/{name}/search", post(search_collection))
+        .route("/api/collections/{name}/query", post(query_collection))
+        .route("/api/collections/{name}/queries", get(list_query_templates))
+        .route(
+            "/api/collections/{name}/queries/{query}",
+            get(get_query_template)
+                .put(put_query_template)
+                .delete(delete_query_template),
+        )
+        .route(
+            "/api/collections/{name}/queries/{query}/run",
+            post(run_query_template),
+        )
+        .route(
+            "/api/collections/{name}/synonyms",
+            get(get_synonyms).put(put_synonyms),
+        )
         .route("/api/analyze/geometry", post(analyze_raw_geometry))
         .route(
             "/api/collections/{name}/analyze/geometry",
@@ -158,8 +282,12 @@ pub async fn start_http_server(
             "/api/collections/{name}/graph/clusters",
             post(graph_clusters),
         )
+        .route("/api/collections/{name}/graph/export", get(graph_export))
         .route("/api/status", get(get_status))
+        .route("/api/capacity", get(get_capacity_estimate))
         .route("/api/cluster/status", get(get_cluster_status))
+        .route("/api/cluster/topology", get(get_cluster_topology))
+        .route("/api/ready", get(get_readiness))
         .route("/api/metrics", get(get_metrics))
         .route("/metrics", get(get_prometheus_metrics))
         .route("/api/logs", get(get_logs))
@@ -167,8 +295,31 @@ pub async fn start_http_server(
             "/api/collections/{name}/rebuild",
             post(rebuild_collection_http),
         )
+        .route(
+            "/api/collections/{name}/scrub",
+            get(get_scrub_report_http).post(scrub_collection_http),
+        )
+        .route(
+            "/api/collections/{name}/metadata/stats",
+            get(get_metadata_stats_http),
+        )
+        .route(
+            "/api/collections/{name}/writable",
+            post(set_collection_writable_http),
+        )
+        .route("/api/collections/{name}/purge", post(purge_collection_http))
+        .route(
+            "/api/collections/{name}/snapshot",
+            get(download_snapshot).post(upload_snapshot),
+        )
         .route("/api/admin/vacuum", post(trigger_vacuum_http))
         .route("/api/admin/usage", get(get_usage_report_http))
+        .route("/api/admin/slow-queries", get(get_slow_queries_http))
+        .route("/api/admin/telemetry", get(get_telemetry_preview_http))
+        .route(
+            "/api/specs",
+            get(list_collection_specs_http).put(apply_collection_spec_http),
+        )
         // Delta Sync HTTP API (Task 2.1 — for WASM and REST clients)
         .route(
             "/api/collections/{name}/sync/handshake",
@@ -178,7 +329,15 @@ pub async fn start_http_server(
         // P2P Swarm API (Task 3.4) — Gossip peer registry
         .route("/api/swarm/peers", get(get_swarm_peers))
         .layer(middleware::from_fn_with_state(
-            api_key_hash.clone(),
+            manager.clone(),
+            gate_stale_reads,
+        ))
+        .layer(middleware::from_fn_with_state(
+            manager.clone(),
+            fence_writes,
+        ))
+        .layer(middleware::from_fn_with_state(
+            (api_key_hash.clone(), read_key_hash.clone()),
             validate_api_key,
         ))
         .fallback(static_handler)
@@ -186,12 +345,16 @@ pub async fn start_http_server(
         // Pass PeerRegistry as an Extension so all handlers can opt-in without
         // changing the 3-tuple State type.
         .layer(axum::Extension(Arc::new(peer_registry)))
+        .layer(axum::Extension(embedder))
         .with_state((manager, start_time, embedding_state));
 
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
     println!("HTTP Dashboard listening on http://{addr}");
     if api_key_hash.is_some() {
         println!("🔒 Dashboard API Key Auth Enabled");
+        if read_key_hash.is_some() {
+            println!("🔒 Read-only API key accepted");
+        }
     } else {
         println!("⚠️  Dashboard API Key Auth Disabled");
     }
@@ -207,6 +370,23 @@ pub async fn start_http_server(
     Ok(())
 }
 
+/// Maps engine errors to HTTP status codes so clients can tell bad input from
+/// transient failures.
+fn error_response(e: &hyperspace_core::HyperspaceError) -> Response {
+    use hyperspace_core::HyperspaceError;
+    let status = match e {
+        HyperspaceError::Validation(_) => StatusCode::BAD_REQUEST,
+        HyperspaceError::NotFound(_) => StatusCode::NOT_FOUND,
+        HyperspaceError::Precondition(_) => StatusCode::CONFLICT,
+        HyperspaceError::Capacity(_) => StatusCode::INSUFFICIENT_STORAGE,
+        HyperspaceError::Io(_) => StatusCode::SERVICE_UNAVAILABLE,
+        HyperspaceError::Corruption(_) | HyperspaceError::Internal(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string()).into_response()
+}
+
 async fn static_handler(uri: Uri) -> impl IntoResponse {
     let path = uri.path().trim_start_matches('/');
 
@@ -254,6 +434,18 @@ struct CollectionSummary {
     dimension: usize,
     metric: String,
     indexing_queue: u64,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    labels: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(serde::Serialize)]
+struct ClusterStatus {
+    #[serde(flatten)]
+    state: crate::manager::ClusterState,
+    follower_reads: FollowerReadStatus,
+    fencing: crate::fencing::FencingStatus,
 }
 
 async fn get_cluster_status(
@@ -262,9 +454,55 @@ async fn get_cluster_status(
         Arc<Instant>,
         Arc<Option<EmbeddingInfo>>,
     )>,
-) -> Json<crate::manager::ClusterState> {
-    let state = manager.cluster_state.read().await;
-    Json(state.clone())
+) -> Json<ClusterStatus> {
+    let state = manager.cluster_state.read().await.clone();
+    Json(ClusterStatus {
+        state,
+        follower_reads: manager.follower_reads.status(),
+        fencing: manager.fencing.status(),
+    })
+}
+
+/// GET /api/cluster/topology
+///
+/// This node and every live gossip peer, with roles, leaders and collections.
+async fn get_cluster_topology(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "nodes": crate::gossip::topology(&manager).await,
+    }))
+}
+
+/// GET /api/ready
+///
+/// 503 while this follower is stale, for load balancer health checks.
+async fn get_readiness(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+) -> Response {
+    let status = manager.follower_reads.status();
+    let code = if status.stale {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (code, Json(status)).into_response()
+}
+
+#[derive(serde::Deserialize)]
+struct ListCollectionsParams {
+    /// Only collections under this namespace, e.g. `team/project`.
+    namespace: Option<String>,
+    /// Label selector, `key:value` pairs separated by commas; all must match.
+    labels: Option<String>,
 }
 
 async fn list_collections(
@@ -274,17 +512,32 @@ async fn list_collections(
         Arc<Option<EmbeddingInfo>>,
     )>,
     Extension(ctx): Extension<RequestContext>,
+    Query(params): Query<ListCollectionsParams>,
 ) -> Json<Vec<CollectionSummary>> {
-    let names = manager.list(&ctx.user_id);
+    let names = manager.list_namespace(&ctx.user_id, params.namespace.as_deref().unwrap_or(""));
+    let selector: HashMap<String, String> = params
+        .labels
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|pair| pair.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
     let mut summaries = Vec::new();
     for name in names {
-        if let Some(col) = manager.get(&ctx.user_id, &name).await {
+        let info = manager.collection_info(&ctx.user_id, &name);
+        if !info.matches(&selector) {
+            continue;
+        }
+        if let Some(overview) = manager.collection_overview(&ctx.user_id, &name) {
             summaries.push(CollectionSummary {
                 name: name.clone(),
-                count: col.count(),
-                dimension: col.dimension(),
-                metric: col.metric_name().to_string(),
-                indexing_queue: col.queue_size(),
+                count: overview.count,
+                dimension: overview.dimension,
+                metric: overview.metric,
+                indexing_queue: overview.indexing_queue,
+                description: info.description,
+                labels: info.labels,
             });
         }
     }
@@ -295,6 +548,26 @@ struct CreateCollectionRequest {
     name: String,
     dimension: u32,
     metric: String,
+    #[serde(default)]
+    snapshot: SnapshotPolicy,
+    #[serde(default)]
+    limits: CollectionLimits,
+    #[serde(default)]
+    schema: Option<hyperspace_core::MetadataSchema>,
+    #[serde(default, flatten)]
+    info: CollectionInfo,
+    /// `hnsw` (default) or `ivf`.
+    #[serde(default)]
+    index_type: Option<String>,
+    /// IVF cells and probes; only read with `index_type: ivf`.
+    #[serde(default)]
+    ivf: IvfParams,
+    /// Unset follows the metric: `normalize` for cosine, `reject` otherwise.
+    #[serde(default)]
+    normalization: Option<hyperspace_core::NormalizationPolicy>,
+    /// Collection holding the parent documents of this one's chunks.
+    #[serde(default)]
+    parent_collection: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -312,13 +585,50 @@ async fn create_collection(
     )>,
     Extension(ctx): Extension<RequestContext>,
     Json(payload): Json<CreateCollectionRequest>,
-) -> impl IntoResponse {
+) -> Response {
+    let ivf = match payload
+        .index_type
+        .as_deref()
+        .map(str::to_lowercase)
+        .as_deref()
+    {
+        None | Some("hnsw") => None,
+        Some("ivf") => {
+            if let Err(e) = payload.ivf.validate() {
+                return error_response(&e);
+            }
+            Some(payload.ivf)
+        }
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown index_type '{other}' (expected hnsw or ivf)"),
+            )
+                .into_response()
+        }
+    };
+    if let Some(Err(e)) = payload
+        .normalization
+        .map(|policy| policy.validate_for(&payload.metric.to_lowercase()))
+    {
+        return (StatusCode::BAD_REQUEST, e).into_response();
+    }
     match manager
-        .create_collection(
+        .create_collection_with_options(
             &ctx.user_id,
             &payload.name,
             payload.dimension,
             &payload.metric,
+            CollectionOptions {
+                snapshot: payload.snapshot,
+                limits: payload.limits,
+                info: payload.info,
+                schema: payload.schema,
+                ivf,
+                normalization: payload.normalization,
+                parent: payload.parent_collection,
+                ..Default::default()
+            },
         )
         .await
     {
@@ -327,6 +637,105 @@ async fn create_collection(
     }
 }
 
+/// Streams a snapshot bundle (tar) of the collection for migration.
+async fn download_snapshot(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> Response {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let tmp = std::env::temp_dir().join(format!("hs_export_{}.tar", uuid::Uuid::new_v4()));
+    if let Err(e) = manager.export_collection(&ctx.user_id, &name, &tmp).await {
+        let _ = std::fs::remove_file(&tmp);
+        return error_response(&e);
+    }
+    let filename = format!("{}.tar", name.replace('/', "~"));
+    stream_temp_file(&tmp, "application/x-tar", &filename).await
+}
+
+/// Streams the temp file at `path` as an attachment, removing it once open.
+async fn stream_temp_file(path: &std::path::Path, content_type: &str, filename: &str) -> Response {
+    use tokio::io::AsyncReadExt;
+
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    // The open handle keeps the data readable after the name is gone.
+    let _ = tokio::fs::remove_file(path).await;
+
+    let chunks = futures::stream::unfold(Some(file), |file| async move {
+        let mut file = file?;
+        let mut buf = vec![0u8; 64 * 1024];
+        match file.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(axum::body::Bytes::from(buf)), Some(file)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, content_type.to_string()),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response()
+}
+
+/// Creates collection `name` from an uploaded snapshot bundle.
+async fn upload_snapshot(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    body: Body,
+) -> Response {
+    use tokio::io::AsyncWriteExt;
+
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let tmp = std::env::temp_dir().join(format!("hs_import_{}.tar", uuid::Uuid::new_v4()));
+    let received: std::io::Result<()> = async {
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        let mut chunks = body.into_data_stream();
+        while let Some(chunk) = chunks.next().await {
+            file.write_all(&chunk.map_err(std::io::Error::other)?)
+                .await?;
+        }
+        file.flush().await
+    }
+    .await;
+    let result = match received {
+        Ok(()) => manager
+            .import_collection(&ctx.user_id, &name, &tmp)
+            .await
+            .map_or_else(
+                |e| error_response(&e),
+                |()| StatusCode::CREATED.into_response(),
+            ),
+        Err(e) => (StatusCode::BAD_REQUEST, format!("Upload failed: {e}")).into_response(),
+    };
+    let _ = tokio::fs::remove_file(&tmp).await;
+    result
+}
+
 async fn insert_vector(
     Path(name): Path<String>,
     State((manager, _, _)): State<(
@@ -338,8 +747,17 @@ async fn insert_vector(
     Json(payload): Json<InsertPayload>,
 ) -> impl IntoResponse {
     if let Some(col) = manager.get(&ctx.user_id, &name).await {
+        if let Err(v) =
+            col.normalization()
+                .check(&payload.vector, col.dimension(), col.metric_name())
+        {
+            return (StatusCode::BAD_REQUEST, v.to_string()).into_response();
+        }
         let clock = manager.cluster_state.read().await.logical_clock;
-        let meta = payload.metadata.unwrap_or_default();
+        let mut meta = payload.metadata.unwrap_or_default();
+        if let Some(Err(v)) = col.metadata_schema().map(|schema| schema.check(&mut meta)) {
+            return (StatusCode::BAD_REQUEST, v.to_string()).into_response();
+        }
 
         match col
             .insert(
@@ -351,14 +769,154 @@ async fn insert_vector(
             )
             .await
         {
-            Ok(()) => StatusCode::OK.into_response(),
-            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+            Ok(()) => {
+                manager.meter.record_vectors_written(&ctx.user_id, 1);
+                StatusCode::OK.into_response()
+            }
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
     }
 }
 
+#[derive(serde::Deserialize)]
+struct BulkQuery {
+    #[serde(default = "default_bulk_batch_size")]
+    batch_size: usize,
+}
+
+fn default_bulk_batch_size() -> usize {
+    crate::bulk::DEFAULT_BATCH_SIZE
+}
+
+/// POST /api/collections/{name}/bulk
+///
+/// Streams NDJSON points in and NDJSON progress/error events out; see
+/// [`crate::bulk`] for both formats.
+async fn bulk_insert(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(query): Query<BulkQuery>,
+    body: Body,
+) -> impl IntoResponse {
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    let clock = manager.cluster_state.read().await.logical_clock;
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::convert::Infallible>>(64);
+    tokio::spawn(async move {
+        let mut ingest = BulkIngest::new(col, clock, query.batch_size);
+        let mut chunks = body.into_data_stream();
+        while let Some(chunk) = chunks.next().await {
+            let events = match chunk {
+                Ok(bytes) => ingest.feed(&bytes).await,
+                Err(e) => {
+                    ingest.discard_partial();
+                    vec![BulkEvent::Aborted {
+                        error: e.to_string(),
+                    }]
+                }
+            };
+            let aborted = matches!(events.last(), Some(BulkEvent::Aborted { .. }));
+            for event in events {
+                let _ = tx.send(Ok(event.to_ndjson())).await;
+            }
+            if aborted {
+                break;
+            }
+        }
+        for event in ingest.finish().await {
+            if let BulkEvent::Done { inserted, .. } = event {
+                manager
+                    .meter
+                    .record_vectors_written(&ctx.user_id, inserted as u64);
+            }
+            let _ = tx.send(Ok(event.to_ndjson())).await;
+        }
+    });
+
+    (
+        [(axum::http::header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// POST /api/collections/{name}/ingest
+///
+/// Starts importing a CSV or Parquet file from `HS_INGEST_DIR` and answers
+/// with the job; see [`crate::ingest`].
+async fn start_ingest(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Extension(embedder): Extension<TextEmbedder>,
+    Json(request): Json<IngestRequest>,
+) -> impl IntoResponse {
+    // Reads files from the server's disk
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    let clock = manager.cluster_state.read().await.logical_clock;
+    match ingest::start(
+        &manager.ingest_jobs,
+        manager.meter.clone(),
+        &ctx.user_id,
+        &name,
+        col,
+        clock,
+        request,
+        embedder,
+    )
+    .await
+    {
+        Ok(job) => (StatusCode::ACCEPTED, Json(job)).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// GET /api/ingest
+async fn list_ingest_jobs(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    Json(manager.ingest_jobs.list(&ctx.user_id))
+}
+
+/// GET /api/ingest/{job}
+async fn get_ingest_job(
+    Path(job): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.ingest_jobs.get(&ctx.user_id, &job) {
+        Some(status) => Json(status).into_response(),
+        None => (StatusCode::NOT_FOUND, "Ingest job not found").into_response(),
+    }
+}
+
 async fn delete_collection(
     Path(name): Path<String>,
     State((manager, _, _)): State<(
@@ -390,6 +948,9 @@ async fn get_stats(
             "metric": col.metric_name(),
             "quantization": format!("{:?}", col.quantization_mode()),
             "indexing_queue": col.queue_size(),
+            "read_only": col.is_read_only(),
+            "normalization": col.normalization(),
+            "distinct_values": col.metadata_distinct_values(),
         }))
         .into_response()
     } else {
@@ -446,7 +1007,8 @@ async fn get_status(
             "mode": std::env::var("HS_MODE").unwrap_or("performance".to_string()),
             "max_ram_gb": std::env::var("HS_MAX_RAM_GB").unwrap_or("0".to_string()),
         },
-        "embedding": embedding.as_ref()
+        "embedding": embedding.as_ref(),
+        "wal_replay": crate::wal_replay::active(),
     }))
 }
 
@@ -475,6 +1037,22 @@ async fn get_metrics(
         };
 
         let (active_count, idle_count) = manager.get_collection_counts();
+        let (cache_hits, cache_misses) = crate::search_cache::stats();
+        let cache_lookups = cache_hits + cache_misses;
+        let cache_hit_rate = if cache_lookups == 0 {
+            0.0
+        } else {
+            cache_hits as f64 / cache_lookups as f64
+        };
+        let allocator = crate::allocator::stats().map(|a| {
+            serde_json::json!({
+                "allocated_bytes": a.allocated,
+                "active_bytes": a.active,
+                "resident_bytes": a.resident,
+                "retained_bytes": a.retained,
+                "fragmentation": a.fragmentation(),
+            })
+        });
 
         return Json(serde_json::json!({
             "total_vectors": total_vecs,
@@ -484,6 +1062,8 @@ async fn get_metrics(
             "ram_usage_mb": ram_usage_mb,
             "cpu_usage_percent": cpu_usage_percent,
             "disk_usage_mb": disk_usage_mb,
+            "search_cache_hit_rate": cache_hit_rate,
+            "allocator": allocator,
             "is_admin": true
         }))
         .into_response();
@@ -533,8 +1113,18 @@ async fn get_prometheus_metrics(
     };
 
     let disk_mb = calculate_dir_size("./data").unwrap_or(0) / 1_048_576;
-
-    let body = format!(
+    let (cache_hits, cache_misses) = crate::search_cache::stats();
+    let crate::anti_entropy::RepairStats {
+        rounds,
+        buckets_repaired,
+        points_upserted,
+        points_deleted,
+    } = crate::anti_entropy::stats();
+    let (group_fsyncs, group_writes) = crate::group_commit::stats();
+    let drift_alerts = crate::drift::alerts();
+    let trash_purged = crate::trash::purged();
+
+    let mut body = format!(
         "# HELP hyperspace_active_collections Number of collections in memory\n\
          # TYPE hyperspace_active_collections gauge\n\
          hyperspace_active_collections {active}\n\
@@ -552,8 +1142,86 @@ async fn get_prometheus_metrics(
          hyperspace_disk_usage_mb {disk_mb}\n\
          # HELP hyperspace_cpu_usage_percent CPU usage percent\n\
          # TYPE hyperspace_cpu_usage_percent gauge\n\
-         hyperspace_cpu_usage_percent {cpu_percent}\n"
+         hyperspace_cpu_usage_percent {cpu_percent}\n\
+         # HELP hyperspace_search_cache_hits_total Search requests served from the result cache\n\
+         # TYPE hyperspace_search_cache_hits_total counter\n\
+         hyperspace_search_cache_hits_total {cache_hits}\n\
+         # HELP hyperspace_search_cache_misses_total Search requests that missed the result cache\n\
+         # TYPE hyperspace_search_cache_misses_total counter\n\
+         hyperspace_search_cache_misses_total {cache_misses}\n\
+         # HELP hyperspace_anti_entropy_rounds_total Completed follower anti-entropy rounds\n\
+         # TYPE hyperspace_anti_entropy_rounds_total counter\n\
+         hyperspace_anti_entropy_rounds_total {rounds}\n\
+         # HELP hyperspace_anti_entropy_buckets_repaired_total Divergent sync buckets repaired from the leader\n\
+         # TYPE hyperspace_anti_entropy_buckets_repaired_total counter\n\
+         hyperspace_anti_entropy_buckets_repaired_total {buckets_repaired}\n\
+         # HELP hyperspace_anti_entropy_points_upserted_total Points re-applied from the leader during repair\n\
+         # TYPE hyperspace_anti_entropy_points_upserted_total counter\n\
+         hyperspace_anti_entropy_points_upserted_total {points_upserted}\n\
+         # HELP hyperspace_anti_entropy_points_deleted_total Local points removed because the leader no longer has them\n\
+         # TYPE hyperspace_anti_entropy_points_deleted_total counter\n\
+         hyperspace_anti_entropy_points_deleted_total {points_deleted}\n\
+         # HELP hyperspace_wal_group_commit_fsyncs_total Fsyncs issued by WAL group commit\n\
+         # TYPE hyperspace_wal_group_commit_fsyncs_total counter\n\
+         hyperspace_wal_group_commit_fsyncs_total {group_fsyncs}\n\
+         # HELP hyperspace_wal_group_commit_writes_total Strict writes acknowledged through WAL group commit\n\
+         # TYPE hyperspace_wal_group_commit_writes_total counter\n\
+         hyperspace_wal_group_commit_writes_total {group_writes}\n\
+         # HELP hyperspace_embedding_drift_alerts_total Insert windows whose drift score exceeded HS_DRIFT_THRESHOLD\n\
+         # TYPE hyperspace_embedding_drift_alerts_total counter\n\
+         hyperspace_embedding_drift_alerts_total {drift_alerts}\n\
+         # HELP hyperspace_trash_purged_total Deleted collections purged after HS_TRASH_RETENTION_SEC\n\
+         # TYPE hyperspace_trash_purged_total counter\n\
+         hyperspace_trash_purged_total {trash_purged}\n"
     );
+    if let Some(alloc) = crate::allocator::stats() {
+        let fragmentation = alloc.fragmentation();
+        let _ = write!(
+            body,
+            "# HELP hyperspace_allocator_allocated_bytes Bytes allocated by the application through jemalloc\n\
+             # TYPE hyperspace_allocator_allocated_bytes gauge\n\
+             hyperspace_allocator_allocated_bytes {}\n\
+             # HELP hyperspace_allocator_active_bytes Bytes in jemalloc pages with live allocations\n\
+             # TYPE hyperspace_allocator_active_bytes gauge\n\
+             hyperspace_allocator_active_bytes {}\n\
+             # HELP hyperspace_allocator_resident_bytes Bytes of jemalloc pages resident in memory\n\
+             # TYPE hyperspace_allocator_resident_bytes gauge\n\
+             hyperspace_allocator_resident_bytes {}\n\
+             # HELP hyperspace_allocator_retained_bytes Bytes jemalloc returned to the OS but keeps mapped\n\
+             # TYPE hyperspace_allocator_retained_bytes gauge\n\
+             hyperspace_allocator_retained_bytes {}\n\
+             # HELP hyperspace_allocator_fragmentation Share of active bytes not allocated\n\
+             # TYPE hyperspace_allocator_fragmentation gauge\n\
+             hyperspace_allocator_fragmentation {fragmentation}\n",
+            alloc.allocated, alloc.active, alloc.resident, alloc.retained
+        );
+    }
+    let scores = manager.drift_scores();
+    if !scores.is_empty() {
+        body.push_str(
+            "# HELP hyperspace_embedding_drift_score Drift of the latest insert window against the collection baseline\n\
+             # TYPE hyperspace_embedding_drift_score gauge\n",
+        );
+        for (collection, score) in scores {
+            let _ = writeln!(
+                body,
+                "hyperspace_embedding_drift_score{{collection=\"{collection}\"}} {score}"
+            );
+        }
+    }
+    let effective_ef = manager.effective_ef_search();
+    if !effective_ef.is_empty() {
+        body.push_str(
+            "# HELP hyperspace_effective_ef_search ef_search cap of the load-adaptive controller\n\
+             # TYPE hyperspace_effective_ef_search gauge\n",
+        );
+        for (collection, ef) in effective_ef {
+            let _ = writeln!(
+                body,
+                "hyperspace_effective_ef_search{{collection=\"{collection}\"}} {ef}"
+            );
+        }
+    }
 
     (
         [(
@@ -586,6 +1254,9 @@ fn calculate_dir_size(path: &str) -> std::io::Result<u64> {
 struct PeekParams {
     limit: Option<usize>,
     offset: Option<usize>,
+    /// Read a snapshot generation instead of the live collection.
+    snapshot_id: Option<u64>,
+    as_of: Option<u64>,
 }
 
 async fn peek_collection(
@@ -600,7 +1271,14 @@ async fn peek_collection(
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(50).min(250);
     let offset = params.offset.unwrap_or(0);
-    if let Some(col) = manager.get(&ctx.user_id, &name).await {
+    let col = match SnapshotRef::from_request(params.snapshot_id, params.as_of) {
+        Some(snapshot) => match manager.historical(&ctx.user_id, &name, snapshot).await {
+            Ok(col) => Some(col),
+            Err(e) => return error_response(&e),
+        },
+        None => manager.get(&ctx.user_id, &name).await,
+    };
+    if let Some(col) = col {
         let items = col.peek(limit, offset);
         Json(items).into_response()
     } else {
@@ -819,6 +1497,12 @@ fn graph_match_filters(
                     }
                 }
             }
+            hyperspace_core::FilterExpr::PhraseMatch { phrase } => {
+                let params = crate::collection::bm25_params_from_env();
+                if !hyperspace_index::MetadataIndex::contains_phrase(metadata, phrase, &params) {
+                    return false;
+                }
+            }
             hyperspace_core::FilterExpr::InCone { .. }
             | hyperspace_core::FilterExpr::InBox { .. }
             | hyperspace_core::FilterExpr::InBall { .. } => {
@@ -864,6 +1548,9 @@ async fn search_collection(
             use_wasserstein: payload.use_wasserstein.unwrap_or(false),
             bm25_options: None,
             fusion_method: None,
+            text_match: hyperspace_core::TextMatch::Any,
+            trace: None,
+            num_probes: 0,
         };
         match col
             .search(
@@ -887,9 +1574,10 @@ async fn search_collection(
                         })
                     })
                     .collect();
+                manager.meter.record_searches(&ctx.user_id, 1);
                 Json(mapped).into_response()
             }
-            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
@@ -897,30 +1585,300 @@ async fn search_collection(
 }
 
 #[derive(serde::Deserialize)]
-struct GraphNodeQuery {
-    id: u32,
-    layer: Option<usize>,
-}
-
-#[derive(serde::Deserialize)]
-struct GraphNeighborsQuery {
-    id: u32,
-    layer: Option<usize>,
-    limit: Option<usize>,
-    offset: Option<usize>,
-}
-
-#[derive(serde::Deserialize)]
-struct GraphTraverseReq {
-    start_id: u32,
-    layer: Option<usize>,
-    max_depth: Option<usize>,
-    max_nodes: Option<usize>,
-    filter: Option<HashMap<String, String>>,
-    filters: Option<Vec<HttpFilter>>,
+struct QueryReq {
+    vector: Vec<f64>,
+    top_k: Option<usize>,
+    /// Filter string, e.g. `genre = "jazz" AND year >= 1990`.
+    #[serde(rename = "where", default)]
+    where_clause: String,
 }
 
-#[derive(serde::Deserialize)]
+/// POST /api/collections/{name}/query
+///
+/// Query-console variant of `search` that takes a textual filter (parsed by
+/// [`hyperspace_core::parse_filter`]) and echoes the metadata fields it matched on.
+async fn query_collection(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<QueryReq>,
+) -> impl IntoResponse {
+    let filters = match hyperspace_core::parse_filter(&payload.where_clause) {
+        Ok(f) => f,
+        Err(e) => return error_response(&e.into()),
+    };
+    let filter_keys: Vec<&str> = filters
+        .iter()
+        .filter_map(|f| match f {
+            hyperspace_core::FilterExpr::Match { key, .. }
+            | hyperspace_core::FilterExpr::Range { key, .. } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    let params = SearchParams {
+        top_k: payload.top_k.unwrap_or(10),
+        ef_search: default_ef_search(),
+        hybrid_query: None,
+        hybrid_alpha: None,
+        use_wasserstein: false,
+        bm25_options: None,
+        fusion_method: None,
+        text_match: hyperspace_core::TextMatch::Any,
+        trace: None,
+        num_probes: 0,
+    };
+    match col
+        .search(&payload.vector, &HashMap::new(), &filters, &params)
+        .await
+    {
+        Ok(res) => {
+            let mapped: Vec<serde_json::Value> = res
+                .iter()
+                .map(|(id, dist, meta)| {
+                    let (metadata, typed_metadata) = parse_typed_metadata(meta);
+                    let matched: HashMap<&str, serde_json::Value> = filter_keys
+                        .iter()
+                        .filter_map(|k| {
+                            typed_metadata
+                                .get(*k)
+                                .cloned()
+                                .or_else(|| metadata.get(*k).map(|v| v.clone().into()))
+                                .map(|v| (*k, v))
+                        })
+                        .collect();
+                    serde_json::json!({
+                        "id": id,
+                        "distance": dist,
+                        "metadata": metadata,
+                        "typed_metadata": typed_metadata,
+                        "matched": matched
+                    })
+                })
+                .collect();
+            manager.meter.record_searches(&ctx.user_id, 1);
+            Json(mapped).into_response()
+        }
+        Err(e) => error_response(&e),
+    }
+}
+
+/// GET /api/collections/{name}/queries
+async fn list_query_templates(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.list_query_templates(&ctx.user_id, &name) {
+        Ok(templates) => Json(templates).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// GET /api/collections/{name}/queries/{query}
+async fn get_query_template(
+    Path((name, query)): Path<(String, String)>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.get_query_template(&ctx.user_id, &name, &query) {
+        Ok(template) => Json(template).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// PUT /api/collections/{name}/queries/{query}
+///
+/// Registers or replaces a template; the response carries its new version.
+async fn put_query_template(
+    Path((name, query)): Path<(String, String)>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(template): Json<QueryTemplate>,
+) -> impl IntoResponse {
+    match manager.put_query_template(&ctx.user_id, &name, &query, template) {
+        Ok(version) => {
+            Json(serde_json::json!({ "name": query, "version": version })).into_response()
+        }
+        Err(e) => error_response(&e),
+    }
+}
+
+/// DELETE /api/collections/{name}/queries/{query}
+async fn delete_query_template(
+    Path((name, query)): Path<(String, String)>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.delete_query_template(&ctx.user_id, &name, &query) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "Query not found").into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// GET /api/collections/{name}/snapshots
+///
+/// Ids of the snapshot generations `snapshot_id` can name, oldest first.
+async fn list_snapshots(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.snapshot_generations(&ctx.user_id, &name) {
+        Ok(ids) => Json(serde_json::json!({ "snapshots": ids })).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// GET /api/collections/{name}/synonyms
+async fn get_synonyms(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.synonyms(&ctx.user_id, &name) {
+        Ok(synonyms) => Json(synonyms).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// PUT /api/collections/{name}/synonyms
+///
+/// Replaces the synonym groups; an empty `groups` list removes them.
+async fn put_synonyms(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(synonyms): Json<SynonymMap>,
+) -> impl IntoResponse {
+    match manager.set_synonyms(&ctx.user_id, &name, synonyms).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RunQueryReq {
+    vector: Vec<f64>,
+    #[serde(default)]
+    params: HashMap<String, String>,
+    top_k: Option<usize>,
+}
+
+/// POST /api/collections/{name}/queries/{query}/run
+async fn run_query_template(
+    Path((name, query)): Path<(String, String)>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<RunQueryReq>,
+) -> impl IntoResponse {
+    let template = match manager.get_query_template(&ctx.user_id, &name, &query) {
+        Ok(t) => t,
+        Err(e) => return error_response(&e),
+    };
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    match query_templates::execute(
+        &*col,
+        &template,
+        &payload.vector,
+        &payload.params,
+        payload.top_k,
+        default_ef_search(),
+    )
+    .await
+    {
+        Ok(res) => {
+            let mapped: Vec<serde_json::Value> = res
+                .iter()
+                .map(|(id, dist, meta)| {
+                    let (metadata, typed_metadata) = parse_typed_metadata(meta);
+                    serde_json::json!({
+                        "id": id,
+                        "distance": dist,
+                        "metadata": metadata,
+                        "typed_metadata": typed_metadata
+                    })
+                })
+                .collect();
+            manager.meter.record_searches(&ctx.user_id, 1);
+            Json(serde_json::json!({
+                "query": query,
+                "version": template.version,
+                "results": mapped
+            }))
+            .into_response()
+        }
+        Err(e) => error_response(&e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GraphNodeQuery {
+    id: u32,
+    layer: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct GraphNeighborsQuery {
+    id: u32,
+    layer: Option<usize>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct GraphTraverseReq {
+    start_id: u32,
+    layer: Option<usize>,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+    filter: Option<HashMap<String, String>>,
+    filters: Option<Vec<HttpFilter>>,
+}
+
+#[derive(serde::Deserialize)]
 struct GraphClustersReq {
     layer: Option<usize>,
     min_cluster_size: Option<usize>,
@@ -973,7 +1931,7 @@ async fn graph_get_neighbors(
                     .collect();
                 Json(nodes).into_response()
             }
-            Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
@@ -1004,7 +1962,7 @@ async fn graph_get_parents(
                     .collect();
                 Json(nodes).into_response()
             }
-            Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
@@ -1045,7 +2003,7 @@ async fn graph_traverse(
                     .collect();
                 Json(nodes).into_response()
             }
-            Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
@@ -1069,13 +2027,61 @@ async fn graph_clusters(
     if let Some(col) = manager.get(&ctx.user_id, &name).await {
         match col.graph_clusters(layer, min_cluster_size, max_clusters, max_nodes) {
             Ok(clusters) => Json(clusters).into_response(),
-            Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Err(e) => error_response(&e),
         }
     } else {
         (StatusCode::NOT_FOUND, "Collection not found").into_response()
     }
 }
 
+#[derive(serde::Deserialize)]
+struct GraphExportQuery {
+    format: Option<String>,
+    layer: Option<usize>,
+}
+
+/// Downloads the HNSW graph as GraphML, CSV or Parquet; see `graph_export`.
+async fn graph_export(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(q): Query<GraphExportQuery>,
+) -> Response {
+    let format = match GraphFormat::parse(q.format.as_deref().unwrap_or("graphml")) {
+        Ok(format) => format,
+        Err(e) => return error_response(&e),
+    };
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    let tmp = std::env::temp_dir().join(format!("hs_graph_{}", uuid::Uuid::new_v4()));
+    let out = tmp.clone();
+    let written =
+        tokio::task::spawn_blocking(move || graph_export::export(&*col, q.layer, format, &out))
+            .await
+            .unwrap_or_else(|e| Err(hyperspace_core::HyperspaceError::Internal(e.to_string())));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return error_response(&e);
+    }
+    let layer = q.layer.map_or_else(String::new, |l| format!("_layer{l}"));
+    let filename = format!("{}{layer}.{}", name.replace('/', "~"), format.extension());
+    stream_temp_file(&tmp, format.content_type(), &filename).await
+}
+
+/// GET /api/capacity?dimension=768&count=50000000 — estimated RAM and disk
+/// for a collection of that shape; see `capacity::CapacityQuery`.
+async fn get_capacity_estimate(Query(query): Query<capacity::CapacityQuery>) -> Response {
+    match capacity::estimate(&query) {
+        Ok(estimate) => Json(estimate).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
 async fn get_logs() -> Json<Vec<String>> {
     Json(vec![
         "[SYSTEM] Hyperspace DB Online".into(),
@@ -1084,6 +2090,140 @@ async fn get_logs() -> Json<Vec<String>> {
     ])
 }
 
+/// GET /api/collections/{name}/scrub — findings of the latest scrub, or
+/// `null` before the first one.
+async fn get_scrub_report_http(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    match manager.get(&ctx.user_id, &name).await {
+        Some(col) => Json(col.scrub_report()).into_response(),
+        None => (StatusCode::NOT_FOUND, "Collection not found").into_response(),
+    }
+}
+
+/// GET /api/collections/{name}/metadata/stats — metadata index size per
+/// key, largest first.
+async fn get_metadata_stats_http(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    match manager.get(&ctx.user_id, &name).await {
+        Some(col) => Json(serde_json::json!({
+            "count": col.count(),
+            "keys": col.metadata_key_stats(),
+        }))
+        .into_response(),
+        None => (StatusCode::NOT_FOUND, "Collection not found").into_response(),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ScrubParams {
+    repair: Option<bool>,
+}
+
+/// POST /api/collections/{name}/scrub?repair=true — scrub now and wait for
+/// the report.
+async fn scrub_collection_http(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(params): Query<ScrubParams>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    match col.scrub(params.repair.unwrap_or(false)).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// POST /api/collections/{name}/writable — accept writes again after the
+/// collection went read-only on storage failures.
+async fn set_collection_writable_http(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    match manager.get(&ctx.user_id, &name).await {
+        Some(col) => {
+            col.set_writable();
+            Json(serde_json::json!({ "read_only": false })).into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "Collection not found").into_response(),
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+struct PurgeReq {
+    filter: Option<HashMap<String, String>>,
+    filters: Option<Vec<HttpFilter>>,
+    /// Filter string, e.g. `user_id = "42"`.
+    #[serde(rename = "where")]
+    where_clause: Option<String>,
+}
+
+/// POST /api/collections/{name}/purge — GDPR erasure; answers with the
+/// signed report (see [`crate::purge`]).
+async fn purge_collection_http(
+    Path(name): Path<String>,
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<PurgeReq>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let mut complex_filters = convert_filters(payload.filters.as_deref().unwrap_or_default());
+    if let Some(expr) = payload.where_clause.as_deref() {
+        match hyperspace_core::parse_filter(expr) {
+            Ok(parsed) => complex_filters.extend(parsed),
+            Err(e) => return error_response(&e.into()),
+        }
+    }
+    let Some(col) = manager.get(&ctx.user_id, &name).await else {
+        return (StatusCode::NOT_FOUND, "Collection not found").into_response();
+    };
+    let filter = payload.filter.unwrap_or_default();
+    match crate::purge::run(&manager, &name, col.as_ref(), &filter, &complex_filters).await {
+        Ok(evidence) => Json(evidence).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
 async fn rebuild_collection_http(
     Path(name): Path<String>,
     State((manager, _, _)): State<(
@@ -1144,15 +2284,7 @@ async fn trigger_vacuum_http(
         return (StatusCode::FORBIDDEN, "Admin access required").into_response();
     }
 
-    // 1. Refresh jemalloc statistics
-    if let Err(e) = epoch::advance() {
-        eprintln!("Failed to advance jemalloc epoch: {e}");
-    }
-
-    // 2. Perform global purge via mallctl
-    // In jemalloc 5.x, "arena.4096.purge" purges all arenas.
-    // SAFETY: Calling jemalloc purge is safe here as it only triggers memory return to OS.
-    if let Err(e) = unsafe { tikv_jemalloc_ctl::raw::update(b"arena.4096.purge\0", ()) } {
+    if let Err(e) = crate::allocator::purge() {
         eprintln!("Failed to purge jemalloc arenas: {e}");
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -1179,10 +2311,94 @@ async fn get_usage_report_http(
     if !ctx.is_admin {
         return (StatusCode::FORBIDDEN, "Admin access required").into_response();
     }
-    let report = manager.get_usage_report();
+    let report = manager.meter.report(manager.get_usage_report());
     Json(report).into_response()
 }
 
+/// GET /api/admin/telemetry — the report telemetry would send, and whether
+/// it is sent at all; see [`crate::telemetry`].
+async fn get_telemetry_preview_http(
+    State((manager, start_time, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    Json(telemetry::preview(&manager, *start_time).await).into_response()
+}
+
+#[derive(serde::Deserialize)]
+struct SlowQueryParams {
+    user: Option<String>,
+    limit: Option<usize>,
+}
+
+async fn get_slow_queries_http(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(params): Query<SlowQueryParams>,
+) -> impl IntoResponse {
+    if !ctx.is_admin {
+        return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+    }
+    let entries = manager
+        .slow_queries
+        .entries(params.user.as_deref(), params.limit.unwrap_or(100));
+    Json(entries).into_response()
+}
+
+#[derive(serde::Deserialize)]
+struct ListSpecsParams {
+    namespace: Option<String>,
+}
+
+/// GET /api/specs?namespace=team
+async fn list_collection_specs_http(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(params): Query<ListSpecsParams>,
+) -> impl IntoResponse {
+    let namespace = params.namespace.unwrap_or_default();
+    Json(manager.collection_specs(&ctx.user_id, &namespace)).into_response()
+}
+
+#[derive(serde::Deserialize)]
+struct ApplySpecParams {
+    dry_run: Option<bool>,
+}
+
+/// PUT /api/specs?dry_run=true
+async fn apply_collection_spec_http(
+    State((manager, _, _)): State<(
+        Arc<CollectionManager>,
+        Arc<Instant>,
+        Arc<Option<EmbeddingInfo>>,
+    )>,
+    Extension(ctx): Extension<RequestContext>,
+    Query(params): Query<ApplySpecParams>,
+    Json(spec): Json<CollectionSpec>,
+) -> impl IntoResponse {
+    match manager
+        .apply_collection_spec(&ctx.user_id, spec, params.dry_run.unwrap_or(false))
+        .await
+    {
+        Ok(outcome) => Json(outcome).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
 // ─── Delta Sync HTTP Handlers (Task 2.1) ──────────────────────────────────
 
 // The `client_` prefix on all fields mirrors the JSON API schema where all peer
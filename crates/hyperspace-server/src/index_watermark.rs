@@ -0,0 +1,107 @@
+//! Applied-clock watermark of the asynchronous indexer.
+//!
+//! Inserts are acknowledged once they reach the WAL; the indexer task links
+//! them into the graph later, out of order when `HS_INDEXER_CONCURRENCY` is
+//! above 1. The watermark is the highest logical clock with nothing at or
+//! below it still queued: one below the oldest queued clock, or the highest
+//! accepted clock when the queue is empty.
+//!
+//! A write is registered before it is acknowledged, so once the watermark
+//! reaches the clock of an acknowledged write, that write is searchable. The
+//! watermark can step back when a write that ticked its clock earlier is
+//! registered after a later one has already been indexed.
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+struct State {
+    /// Queued points per logical clock.
+    pending: BTreeMap<u64, u64>,
+    depth: u64,
+    accepted: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct IndexWatermark {
+    state: Mutex<State>,
+}
+
+impl IndexWatermark {
+    /// Starts with everything up to `clock` indexed, e.g. after WAL replay.
+    pub fn new(clock: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                accepted: clock,
+                ..State::default()
+            }),
+        }
+    }
+
+    /// Registers `points` handed to the indexer under `clock`.
+    pub fn enqueue(&self, clock: u64, points: u64) {
+        if points == 0 {
+            self.applied(clock);
+            return;
+        }
+        let mut state = self.state.lock();
+        *state.pending.entry(clock).or_default() += points;
+        state.depth += points;
+        state.accepted = state.accepted.max(clock);
+    }
+
+    /// Records a write at `clock` that was applied without queueing.
+    pub fn applied(&self, clock: u64) {
+        let mut state = self.state.lock();
+        state.accepted = state.accepted.max(clock);
+    }
+
+    /// Marks one queued point of `clock` as indexed (or failed).
+    pub fn done(&self, clock: u64) {
+        let mut state = self.state.lock();
+        if let Some(left) = state.pending.get_mut(&clock) {
+            *left -= 1;
+            if *left == 0 {
+                state.pending.remove(&clock);
+            }
+            state.depth -= 1;
+        }
+    }
+
+    /// The watermark and the number of queued points.
+    pub fn get(&self) -> (u64, u64) {
+        let state = self.state.lock();
+        let clock = state
+            .pending
+            .keys()
+            .next()
+            .map_or(state.accepted, |oldest| oldest.saturating_sub(1));
+        (clock, state.depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watermark_waits_for_the_oldest_queued_clock() {
+        let mark = IndexWatermark::new(4);
+        assert_eq!(mark.get(), (4, 0));
+
+        mark.enqueue(5, 2);
+        mark.enqueue(7, 1);
+        mark.applied(8);
+        assert_eq!(mark.get(), (4, 3));
+
+        // Clock 7 finishes first: 5 still holds the watermark back.
+        mark.done(7);
+        mark.done(5);
+        assert_eq!(mark.get(), (4, 1));
+        mark.done(5);
+        assert_eq!(mark.get(), (8, 0));
+
+        mark.enqueue(9, 0);
+        assert_eq!(mark.get(), (9, 0));
+    }
+}
@@ -0,0 +1,1011 @@
+//! File ingestion for `POST /api/collections/{name}/ingest`.
+//!
+//! Imports a CSV or Parquet file that already sits on the server, under
+//! `HS_INGEST_DIR` (default `import`). The request maps columns onto points:
+//! `id` holds the point id, and the vector comes either from a `vector` column
+//! (a Parquet list, or a CSV cell such as `[0.1, 0.2]` or `0.1 0.2`) or from a
+//! `text` column embedded with the collection's model. `metadata` names the
+//! columns stored as metadata; without it every other column is.
+//!
+//! The import runs as a background job. Rows are read on the indexing pool
+//! and validated and inserted in batches by [`BulkIngest`], so the same rules
+//! and error reporting as the NDJSON bulk endpoint apply, with data rows
+//! numbered from 1 after the CSV header. `GET /api/ingest/{job}` reports
+//! progress and the first rejected rows. Parquet needs a server built with
+//! the `ingest-parquet` feature.
+//!
+//! PDF, HTML, Markdown and text files are imported as documents: the text is
+//! extracted with `hyperspace_embed::extract`, split into chunks (the
+//! collection metric's `HS_EMBED_<METRIC>_CHUNK_SIZE`, else 256 words), and
+//! each chunk becomes a row with `id` (counting up from the request's
+//! `first_id`), `text`, `source`, `title`, `section`, `page` and `chunk`
+//! columns. Without `columns`, `text` is embedded and the rest is metadata.
+
+use crate::bulk::{BulkEvent, BulkIngest, BulkLine};
+use crate::metering::{approx_tokens, Meter};
+use crate::pools::{self, Pool};
+use dashmap::DashMap;
+use hyperspace_core::{Collection, HyperspaceError, HyperspaceResult};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Rejected rows kept in a job's status; later ones are only counted.
+const MAX_REPORTED_ERRORS: usize = 100;
+/// Finished jobs kept for status queries.
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Deserialize)]
+pub struct IngestRequest {
+    /// File path relative to `HS_INGEST_DIR`.
+    pub path: String,
+    /// `csv`, `parquet`, or a document: `pdf`, `html`, `md` or `txt`; taken
+    /// from the file extension when omitted.
+    pub format: Option<String>,
+    /// Required for CSV and Parquet; documents default to embedding `text`.
+    pub columns: Option<ColumnMapping>,
+    /// Id of a document's first chunk; the others follow consecutively.
+    pub first_id: Option<u32>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// CSV field separator.
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+}
+
+fn default_batch_size() -> usize {
+    crate::bulk::DEFAULT_BATCH_SIZE
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+#[derive(Deserialize)]
+pub struct ColumnMapping {
+    pub id: String,
+    /// Column holding the vector; exclusive with `text`.
+    pub vector: Option<String>,
+    /// Column embedded into the vector; exclusive with `vector`.
+    pub text: Option<String>,
+    /// Columns stored as metadata; every column but `id` and `vector` when
+    /// omitted.
+    pub metadata: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestStatus {
+    pub id: String,
+    pub collection: String,
+    pub path: String,
+    pub state: JobState,
+    pub rows: usize,
+    pub inserted: usize,
+    pub failed: usize,
+    /// The first rejected rows and batches.
+    pub errors: Vec<BulkEvent>,
+    /// Why a failed job stopped early.
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+pub struct IngestJob {
+    user_id: String,
+    status: Mutex<IngestStatus>,
+}
+
+impl IngestJob {
+    pub fn status(&self) -> IngestStatus {
+        self.status.lock().clone()
+    }
+
+    fn record(&self, events: Vec<BulkEvent>) {
+        let mut status = self.status.lock();
+        for event in events {
+            match event {
+                BulkEvent::Progress {
+                    lines,
+                    inserted,
+                    failed,
+                }
+                | BulkEvent::Done {
+                    lines,
+                    inserted,
+                    failed,
+                } => {
+                    status.rows = lines;
+                    status.inserted = inserted;
+                    status.failed = failed;
+                }
+                BulkEvent::Aborted { error } => status.error = Some(error),
+                error => {
+                    if status.errors.len() < MAX_REPORTED_ERRORS {
+                        status.errors.push(error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(&self, error: Option<String>) {
+        let mut status = self.status.lock();
+        status.state = if error.is_some() {
+            JobState::Failed
+        } else {
+            JobState::Done
+        };
+        status.error = error;
+        status.finished_at = Some(unix_now());
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Running and recently finished imports.
+#[derive(Default)]
+pub struct IngestJobs {
+    jobs: DashMap<String, Arc<IngestJob>>,
+}
+
+impl IngestJobs {
+    fn register(&self, user_id: &str, collection: &str, path: &str) -> Arc<IngestJob> {
+        let finished: Vec<(u64, String)> = self
+            .jobs
+            .iter()
+            .filter_map(|job| {
+                let status = job.status.lock();
+                status.finished_at.map(|at| (at, status.id.clone()))
+            })
+            .collect();
+        if finished.len() >= MAX_FINISHED_JOBS {
+            let mut finished = finished;
+            finished.sort_unstable();
+            for (_, id) in &finished[..=finished.len() - MAX_FINISHED_JOBS] {
+                self.jobs.remove(id);
+            }
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let job = Arc::new(IngestJob {
+            user_id: user_id.to_string(),
+            status: Mutex::new(IngestStatus {
+                id: id.clone(),
+                collection: collection.to_string(),
+                path: path.to_string(),
+                state: JobState::Running,
+                rows: 0,
+                inserted: 0,
+                failed: 0,
+                errors: Vec::new(),
+                error: None,
+                started_at: unix_now(),
+                finished_at: None,
+            }),
+        });
+        self.jobs.insert(id, job.clone());
+        job
+    }
+
+    pub fn get(&self, user_id: &str, id: &str) -> Option<IngestStatus> {
+        self.jobs
+            .get(id)
+            .filter(|job| job.user_id == user_id)
+            .map(|job| job.status())
+    }
+
+    /// Jobs of `user_id`, newest first.
+    pub fn list(&self, user_id: &str) -> Vec<IngestStatus> {
+        let mut jobs: Vec<IngestStatus> = self
+            .jobs
+            .iter()
+            .filter(|job| job.user_id == user_id)
+            .map(|job| job.status())
+            .collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.started_at));
+        jobs
+    }
+}
+
+/// Embeds `text` columns with the server's embedding models, if any.
+#[derive(Clone, Default)]
+pub struct TextEmbedder {
+    #[cfg(feature = "embed")]
+    pub vectorizer: Option<Arc<hyperspace_embed::MultiVectorizer>>,
+}
+
+impl TextEmbedder {
+    #[cfg_attr(not(feature = "embed"), allow(clippy::unused_self))]
+    fn enabled(&self) -> bool {
+        #[cfg(feature = "embed")]
+        return self.vectorizer.is_some();
+        #[cfg(not(feature = "embed"))]
+        false
+    }
+
+    #[cfg_attr(
+        not(feature = "embed"),
+        allow(unused_variables, clippy::unused_async_trait_impl)
+    )]
+    async fn embed(&self, texts: Vec<String>, metric: &str) -> Result<Vec<Vec<f64>>, String> {
+        #[cfg(feature = "embed")]
+        if let Some(multi) = &self.vectorizer {
+            return multi
+                .vectorize_for(texts, metric)
+                .await
+                .map_err(|e| format!("Embedding failed: {e}"));
+        }
+        Err("Embedding engine disabled".into())
+    }
+}
+
+/// One cell of a row, as read from the file.
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Null,
+    Text(String),
+    /// A numeric Parquet list.
+    #[cfg_attr(not(feature = "ingest-parquet"), allow(dead_code))]
+    List(Vec<f64>),
+}
+
+/// Reads rows in batches; runs on the indexing pool.
+trait RowReader: Send {
+    fn columns(&self) -> &[String];
+
+    /// Up to `n` rows, empty at the end of the file. A row that cannot be
+    /// read is an `Err` of its own; an `Err` overall ends the import.
+    fn next_rows(&mut self, n: usize) -> Result<Vec<Result<Vec<Cell>, String>>, String>;
+}
+
+struct CsvReader {
+    input: Box<dyn BufRead + Send>,
+    delimiter: char,
+    columns: Vec<String>,
+}
+
+impl CsvReader {
+    fn new(input: Box<dyn BufRead + Send>, delimiter: char) -> Result<Self, String> {
+        let mut reader = Self {
+            input,
+            delimiter,
+            columns: Vec::new(),
+        };
+        let header = reader
+            .record()
+            .map_err(|e| e.to_string())?
+            .ok_or("CSV file is empty")?;
+        reader.columns = header
+            .into_iter()
+            .map(|c| c.trim_start_matches('\u{feff}').trim().to_string())
+            .collect();
+        Ok(reader)
+    }
+
+    /// Next record, RFC 4180 style: quoted fields may hold separators,
+    /// newlines and `""` for a quote. Blank lines are skipped.
+    fn record(&mut self) -> io::Result<Option<Vec<String>>> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                // End of file, possibly inside an unterminated quote
+                if !in_quotes {
+                    return Ok(None);
+                }
+                fields.push(field);
+                return Ok(Some(fields));
+            }
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    if c != '"' {
+                        field.push(c);
+                    } else if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        in_quotes = false;
+                    }
+                } else if c == '"' {
+                    in_quotes = true;
+                } else if c == self.delimiter {
+                    fields.push(std::mem::take(&mut field));
+                } else if c != '\n' && c != '\r' {
+                    field.push(c);
+                }
+            }
+            if in_quotes {
+                continue;
+            }
+            if fields.is_empty() && field.trim().is_empty() {
+                continue;
+            }
+            fields.push(field);
+            return Ok(Some(fields));
+        }
+    }
+}
+
+impl RowReader for CsvReader {
+    fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn next_rows(&mut self, n: usize) -> Result<Vec<Result<Vec<Cell>, String>>, String> {
+        let mut rows = Vec::with_capacity(n);
+        while rows.len() < n {
+            let Some(record) = self.record().map_err(|e| e.to_string())? else {
+                break;
+            };
+            rows.push(if record.len() == self.columns.len() {
+                Ok(record
+                    .into_iter()
+                    .map(|v| {
+                        if v.is_empty() {
+                            Cell::Null
+                        } else {
+                            Cell::Text(v)
+                        }
+                    })
+                    .collect())
+            } else {
+                Err(format!(
+                    "expected {} fields, found {}",
+                    self.columns.len(),
+                    record.len()
+                ))
+            });
+        }
+        Ok(rows)
+    }
+}
+
+#[cfg(feature = "ingest-parquet")]
+struct ParquetReader {
+    columns: Vec<String>,
+    rows: parquet::record::reader::RowIter<'static>,
+}
+
+#[cfg(feature = "ingest-parquet")]
+impl ParquetReader {
+    fn open(file: File) -> Result<Self, String> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
+        let columns = reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .root_schema()
+            .get_fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect();
+        Ok(Self {
+            columns,
+            rows: reader.into_iter(),
+        })
+    }
+
+    fn number(field: &parquet::record::Field) -> Option<f64> {
+        use parquet::record::Field;
+        Some(match *field {
+            Field::Float(v) => f64::from(v),
+            Field::Double(v) => v,
+            Field::Byte(v) => f64::from(v),
+            Field::Short(v) => f64::from(v),
+            Field::Int(v) => f64::from(v),
+            Field::Long(v) => v as f64,
+            _ => return None,
+        })
+    }
+
+    fn cell(field: &parquet::record::Field) -> Cell {
+        use parquet::record::Field;
+        match field {
+            Field::Null => Cell::Null,
+            Field::Str(s) => Cell::Text(s.clone()),
+            Field::ListInternal(list) => {
+                let numbers: Option<Vec<f64>> = list.elements().iter().map(Self::number).collect();
+                numbers.map_or_else(|| Cell::Text(field.to_string()), Cell::List)
+            }
+            other => Cell::Text(other.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ingest-parquet")]
+impl RowReader for ParquetReader {
+    fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn next_rows(&mut self, n: usize) -> Result<Vec<Result<Vec<Cell>, String>>, String> {
+        let mut rows = Vec::with_capacity(n);
+        for row in self.rows.by_ref().take(n) {
+            let row = row.map_err(|e| e.to_string())?;
+            rows.push(Ok(row
+                .get_column_iter()
+                .map(|(_, field)| Self::cell(field))
+                .collect()));
+        }
+        Ok(rows)
+    }
+}
+
+/// A PDF, HTML, Markdown or text file as rows, one per chunk.
+#[cfg(feature = "embed")]
+struct DocumentReader {
+    columns: Vec<String>,
+    chunks: std::vec::IntoIter<hyperspace_embed::extract::DocumentChunk>,
+    next_id: u64,
+    source: String,
+}
+
+#[cfg(feature = "embed")]
+impl RowReader for DocumentReader {
+    fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    fn next_rows(&mut self, n: usize) -> Result<Vec<Result<Vec<Cell>, String>>, String> {
+        let mut rows = Vec::with_capacity(n);
+        for mut chunk in self.chunks.by_ref().take(n) {
+            let id = self.next_id;
+            self.next_id += 1;
+            rows.push(Ok(self
+                .columns
+                .iter()
+                .map(|column| match column.as_str() {
+                    "id" => Cell::Text(id.to_string()),
+                    "text" => Cell::Text(std::mem::take(&mut chunk.text)),
+                    "source" => Cell::Text(self.source.clone()),
+                    key => chunk.metadata.remove(key).map_or(Cell::Null, Cell::Text),
+                })
+                .collect()));
+        }
+        Ok(rows)
+    }
+}
+
+const DOCUMENT_FORMATS: &[&str] = &["pdf", "html", "htm", "md", "markdown", "txt"];
+/// Columns of a document's rows.
+#[cfg(feature = "embed")]
+const DOCUMENT_COLUMNS: [&str; 7] = ["id", "text", "source", "title", "section", "page", "chunk"];
+/// Words per document chunk when the metric has no chunking configured.
+#[cfg(feature = "embed")]
+const DOCUMENT_CHUNK_WORDS: usize = 256;
+
+fn open_file(path: &Path) -> HyperspaceResult<File> {
+    File::open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => HyperspaceError::NotFound(format!("{}", path.display())),
+        _ => HyperspaceError::Io(e),
+    })
+}
+
+#[cfg(feature = "embed")]
+fn open_document(
+    path: &Path,
+    format: &str,
+    first_id: u32,
+    metric: &str,
+    source: String,
+) -> HyperspaceResult<Box<dyn RowReader>> {
+    use hyperspace_embed::extract::{self, DocumentFormat};
+    use hyperspace_embed::ChunkingConfig;
+    use std::io::Read;
+
+    let mut bytes = Vec::new();
+    open_file(path)?.read_to_end(&mut bytes)?;
+    let format = DocumentFormat::from_name(format).unwrap_or(DocumentFormat::Text);
+    let document = extract::extract(&bytes, format)
+        .map_err(|e| HyperspaceError::Validation(format!("{}: {e}", path.display())))?;
+    let chunking = ChunkingConfig::from_env(metric).unwrap_or(ChunkingConfig {
+        chunk_size: DOCUMENT_CHUNK_WORDS,
+        overlap: 0.1,
+    });
+    Ok(Box::new(DocumentReader {
+        columns: DOCUMENT_COLUMNS.map(String::from).to_vec(),
+        chunks: document.chunks(Some(&chunking)).into_iter(),
+        next_id: u64::from(first_id),
+        source,
+    }))
+}
+
+#[cfg(not(feature = "embed"))]
+fn open_document(
+    _path: &Path,
+    _format: &str,
+    _first_id: u32,
+    _metric: &str,
+    _source: String,
+) -> HyperspaceResult<Box<dyn RowReader>> {
+    Err(HyperspaceError::Validation(
+        "Document ingestion needs a server built with the embed feature".into(),
+    ))
+}
+
+fn open_reader(path: &Path, format: &str, delimiter: char) -> HyperspaceResult<Box<dyn RowReader>> {
+    let file = open_file(path)?;
+    let invalid = |e: String| HyperspaceError::Validation(format!("{}: {e}", path.display()));
+    match format {
+        "csv" => Ok(Box::new(
+            CsvReader::new(Box::new(BufReader::new(file)), delimiter).map_err(invalid)?,
+        )),
+        #[cfg(feature = "ingest-parquet")]
+        "parquet" => Ok(Box::new(ParquetReader::open(file).map_err(invalid)?)),
+        #[cfg(not(feature = "ingest-parquet"))]
+        "parquet" => Err(HyperspaceError::Validation(
+            "Parquet ingestion needs a server built with the ingest-parquet feature".into(),
+        )),
+        other => Err(HyperspaceError::Validation(format!(
+            "Unknown ingest format '{other}', expected csv, parquet, pdf, html, md or txt"
+        ))),
+    }
+}
+
+/// `path` inside `HS_INGEST_DIR`; absolute paths and `..` are refused.
+fn resolve_path(path: &str) -> HyperspaceResult<PathBuf> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(HyperspaceError::Validation(format!(
+            "Ingest path '{path}' must be relative to HS_INGEST_DIR without '..'"
+        )));
+    }
+    let base = std::env::var("HS_INGEST_DIR").unwrap_or_else(|_| "import".to_string());
+    Ok(Path::new(&base).join(relative))
+}
+
+enum Source {
+    Vector(usize),
+    Text(usize),
+}
+
+/// Column positions of a [`ColumnMapping`].
+struct Mapping {
+    id: usize,
+    source: Source,
+    metadata: Vec<(String, usize)>,
+}
+
+impl ColumnMapping {
+    fn resolve(&self, columns: &[String]) -> HyperspaceResult<Mapping> {
+        let find = |name: &str| {
+            columns.iter().position(|c| c == name).ok_or_else(|| {
+                HyperspaceError::Validation(format!(
+                    "Column '{name}' not found; the file has {columns:?}"
+                ))
+            })
+        };
+        let (source, source_name) = match (&self.vector, &self.text) {
+            (Some(vector), None) => (Source::Vector(find(vector)?), vector),
+            (None, Some(text)) => (Source::Text(find(text)?), text),
+            _ => {
+                return Err(HyperspaceError::Validation(
+                    "Map exactly one of the 'vector' and 'text' columns".into(),
+                ))
+            }
+        };
+        let metadata = match &self.metadata {
+            Some(names) => names
+                .iter()
+                .map(|name| Ok((name.clone(), find(name)?)))
+                .collect::<HyperspaceResult<_>>()?,
+            None => columns
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c != self.id && *c != source_name)
+                .map(|(i, c)| (c.clone(), i))
+                .collect(),
+        };
+        Ok(Mapping {
+            id: find(&self.id)?,
+            source,
+            metadata,
+        })
+    }
+}
+
+/// Parses `[0.1, 0.2]`, `0.1,0.2`, `0.1;0.2` or `0.1 0.2`.
+fn parse_vector(text: &str) -> Result<Vec<f64>, String> {
+    text.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse().map_err(|_| format!("'{v}' is not a number")))
+        .collect()
+}
+
+impl Mapping {
+    /// A point without its vector, plus the vector or the text to embed.
+    fn point(&self, row: &[Cell]) -> Result<(BulkLine, Option<String>), String> {
+        let id = match &row[self.id] {
+            Cell::Text(v) => v
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("id '{v}' is not an unsigned 32-bit integer"))?,
+            _ => return Err("id is empty".into()),
+        };
+        let mut metadata = HashMap::with_capacity(self.metadata.len());
+        for (name, i) in &self.metadata {
+            match &row[*i] {
+                Cell::Null => {}
+                Cell::Text(v) => {
+                    metadata.insert(name.clone(), v.clone());
+                }
+                Cell::List(v) => {
+                    metadata.insert(name.clone(), serde_json::to_string(v).unwrap_or_default());
+                }
+            }
+        }
+        let mut point = BulkLine {
+            id,
+            vector: Vec::new(),
+            metadata,
+        };
+        match self.source {
+            Source::Vector(i) => {
+                point.vector = match &row[i] {
+                    Cell::List(v) => v.clone(),
+                    Cell::Text(v) => parse_vector(v)?,
+                    Cell::Null => return Err("vector is empty".into()),
+                };
+                Ok((point, None))
+            }
+            Source::Text(i) => match &row[i] {
+                Cell::Text(v) => Ok((point, Some(v.clone()))),
+                _ => Err("text is empty".into()),
+            },
+        }
+    }
+}
+
+/// Validates the request, opens the file and starts the import in the
+/// background; returns the new job's initial status.
+#[allow(clippy::too_many_arguments)]
+pub async fn start(
+    jobs: &IngestJobs,
+    meter: Arc<Meter>,
+    user_id: &str,
+    collection: &str,
+    col: Arc<dyn Collection>,
+    clock: u64,
+    request: IngestRequest,
+    embedder: TextEmbedder,
+) -> HyperspaceResult<IngestStatus> {
+    let path = resolve_path(&request.path)?;
+    let format = match &request.format {
+        Some(format) => format.to_lowercase(),
+        None => path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .ok_or_else(|| {
+                HyperspaceError::Validation("Set 'format': the path has no extension".into())
+            })?,
+    };
+    let document = DOCUMENT_FORMATS.contains(&format.as_str());
+    let columns = match request.columns {
+        Some(columns) => columns,
+        None if document => ColumnMapping {
+            id: "id".into(),
+            vector: None,
+            text: Some("text".into()),
+            metadata: None,
+        },
+        None => {
+            return Err(HyperspaceError::Validation(
+                "Set 'columns' to map the file's columns onto points".into(),
+            ))
+        }
+    };
+    if columns.text.is_some() && !embedder.enabled() {
+        return Err(HyperspaceError::Validation(
+            "A 'text' column needs an embedding model (HYPERSPACE_EMBED=true)".into(),
+        ));
+    }
+    let first_id = match (document, request.first_id) {
+        (true, None) => {
+            return Err(HyperspaceError::Validation(
+                "Set 'first_id': document chunks take consecutive ids from it".into(),
+            ))
+        }
+        (_, first_id) => first_id.unwrap_or_default(),
+    };
+    let delimiter = request.delimiter;
+    let metric = col.metric_name();
+    let source = request.path.clone();
+    let reader = pools::spawn(Pool::Indexing, move || {
+        if document {
+            open_document(&path, &format, first_id, metric, source)
+        } else {
+            open_reader(&path, &format, delimiter)
+        }
+    })
+    .await
+    .map_err(|e| HyperspaceError::Internal(e.to_string()))??;
+    let mapping = columns.resolve(reader.columns())?;
+
+    let job = jobs.register(user_id, collection, &request.path);
+    let status = job.status();
+    let batch_size = request.batch_size.clamp(1, crate::bulk::MAX_BATCH_SIZE);
+    let user_id = user_id.to_string();
+    tokio::spawn(async move {
+        let error = run(
+            &job, reader, &mapping, col, clock, batch_size, &embedder, &meter, &user_id,
+        )
+        .await
+        .err();
+        job.finish(error);
+    });
+    Ok(status)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run(
+    job: &IngestJob,
+    mut reader: Box<dyn RowReader>,
+    mapping: &Mapping,
+    col: Arc<dyn Collection>,
+    clock: u64,
+    batch_size: usize,
+    embedder: &TextEmbedder,
+    meter: &Meter,
+    user_id: &str,
+) -> Result<(), String> {
+    let metric = col.metric_name().to_string();
+    let mut ingest = BulkIngest::new(col, clock, batch_size);
+    loop {
+        let (back, rows) = pools::spawn(Pool::Indexing, move || {
+            let rows = reader.next_rows(batch_size);
+            (reader, rows)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        reader = back;
+        let rows = rows?;
+        if rows.is_empty() {
+            break;
+        }
+
+        let mut points: Vec<Result<(BulkLine, Option<String>), String>> = rows
+            .into_iter()
+            .map(|row| row.and_then(|row| mapping.point(&row)))
+            .collect();
+        let texts: Vec<String> = points
+            .iter()
+            .filter_map(|p| p.as_ref().ok().and_then(|(_, text)| text.clone()))
+            .collect();
+        if !texts.is_empty() {
+            let tokens = texts.iter().map(|t| approx_tokens(t)).sum();
+            let result = embedder.embed(texts, &metric).await;
+            if result.is_ok() {
+                meter.record_embedded_tokens(user_id, tokens);
+            }
+            let mut vectors = result.map(Vec::into_iter);
+            for point in &mut points {
+                let Ok((line, Some(_))) = point else {
+                    continue;
+                };
+                match &mut vectors {
+                    Ok(vectors) => match vectors.next() {
+                        Some(vector) => line.vector = vector,
+                        None => *point = Err("Empty vector result".into()),
+                    },
+                    Err(e) => *point = Err(e.clone()),
+                }
+            }
+        }
+
+        for point in points {
+            job.record(ingest.push(point.map(|(line, _)| line)).await);
+        }
+    }
+    let events = ingest.finish().await;
+    if let Some(BulkEvent::Done { inserted, .. }) = events.last() {
+        meter.record_vectors_written(user_id, *inserted as u64);
+    }
+    job.record(events);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(text: &str) -> CsvReader {
+        CsvReader::new(Box::new(io::Cursor::new(text.to_string())), ',').unwrap()
+    }
+
+    #[test]
+    fn reads_quoted_csv_fields() {
+        let mut reader = csv("\u{feff}id,vector,title\n\
+             1,\"[0.5, 1]\",\"Hello, \"\"world\"\"\"\n\
+             \n\
+             2,0.25 0.75,\"two\r\nlines\"\r\n\
+             3,1\n");
+        assert_eq!(reader.columns(), ["id", "vector", "title"]);
+        let rows = reader.next_rows(10).unwrap();
+        assert_eq!(rows.len(), 3);
+        let text = |s: &str| Cell::Text(s.to_string());
+        assert_eq!(
+            rows[0],
+            Ok(vec![text("1"), text("[0.5, 1]"), text("Hello, \"world\"")])
+        );
+        assert_eq!(
+            rows[1],
+            Ok(vec![text("2"), text("0.25 0.75"), text("two\r\nlines")])
+        );
+        assert_eq!(rows[2], Err("expected 3 fields, found 2".into()));
+        assert_eq!(reader.next_rows(10).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn maps_columns_onto_points() {
+        let columns = ["key", "emb", "color", "size"].map(String::from);
+        let mapping = ColumnMapping {
+            id: "key".into(),
+            vector: Some("emb".into()),
+            text: None,
+            metadata: None,
+        }
+        .resolve(&columns)
+        .unwrap();
+        let row = [
+            Cell::Text("7".into()),
+            Cell::Text("[1, 2.5]".into()),
+            Cell::Text("red".into()),
+            Cell::Null,
+        ];
+        let (point, text) = mapping.point(&row).unwrap();
+        assert_eq!((point.id, point.vector), (7, vec![1.0, 2.5]));
+        assert_eq!(
+            point.metadata,
+            HashMap::from([("color".into(), "red".into())])
+        );
+        assert!(text.is_none());
+
+        let bad = [
+            Cell::Text("x".into()),
+            Cell::List(vec![1.0]),
+            Cell::Null,
+            Cell::Null,
+        ];
+        assert!(mapping.point(&bad).is_err());
+
+        let missing = ColumnMapping {
+            id: "key".into(),
+            vector: None,
+            text: Some("title".into()),
+            metadata: None,
+        };
+        assert!(missing.resolve(&columns).is_err());
+        assert!(resolve_path("../etc/passwd").is_err());
+        assert!(resolve_path("/etc/passwd").is_err());
+    }
+
+    #[cfg(feature = "embed")]
+    #[test]
+    fn reads_document_chunks_as_rows() {
+        let path = std::env::temp_dir().join(format!("hs_ingest_{}.md", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "# Guide\n\nInstall it.\n\n## Usage\n\nRun it.\n").unwrap();
+        let mut reader = open_document(&path, "md", 10, "cosine", "docs/guide.md".into()).unwrap();
+        assert_eq!(reader.columns(), DOCUMENT_COLUMNS);
+        let text = |s: &str| Cell::Text(s.to_string());
+        assert_eq!(
+            reader.next_rows(10).unwrap(),
+            [
+                Ok(vec![
+                    text("10"),
+                    text("Install it."),
+                    text("docs/guide.md"),
+                    text("Guide"),
+                    text("Guide"),
+                    Cell::Null,
+                    text("0"),
+                ]),
+                Ok(vec![
+                    text("11"),
+                    text("Run it."),
+                    text("docs/guide.md"),
+                    text("Guide"),
+                    text("Usage"),
+                    Cell::Null,
+                    text("1"),
+                ]),
+            ]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "ingest-parquet")]
+    #[test]
+    fn reads_parquet_list_vectors() {
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let schema = parse_message_type(
+            "message points {
+                REQUIRED INT32 id;
+                REQUIRED GROUP vector (LIST) {
+                    REPEATED GROUP list { REQUIRED DOUBLE element; }
+                }
+                OPTIONAL BYTE_ARRAY color (UTF8);
+            }",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("hs_ingest_{}.parquet", uuid::Uuid::new_v4()));
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer =
+            SerializedFileWriter::new(File::create(&path).unwrap(), Arc::new(schema), props)
+                .unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut col = group.next_column().unwrap().unwrap();
+        col.typed::<Int32Type>()
+            .write_batch(&[1, 2], None, None)
+            .unwrap();
+        col.close().unwrap();
+        let mut col = group.next_column().unwrap().unwrap();
+        col.typed::<DoubleType>()
+            .write_batch(
+                &[0.5, 1.5, 2.5, 3.5],
+                Some(&[1, 1, 1, 1]),
+                Some(&[0, 1, 0, 1]),
+            )
+            .unwrap();
+        col.close().unwrap();
+        let mut col = group.next_column().unwrap().unwrap();
+        col.typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("red")], Some(&[1, 0]), None)
+            .unwrap();
+        col.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let mut reader = open_reader(&path, "parquet", ',').unwrap();
+        assert_eq!(reader.columns(), ["id", "vector", "color"]);
+        let rows = reader.next_rows(10).unwrap();
+        assert_eq!(
+            rows,
+            [
+                Ok(vec![
+                    Cell::Text("1".into()),
+                    Cell::List(vec![0.5, 1.5]),
+                    Cell::Text("red".into())
+                ]),
+                Ok(vec![
+                    Cell::Text("2".into()),
+                    Cell::List(vec![2.5, 3.5]),
+                    Cell::Null
+                ]),
+            ]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,268 @@
+//! Late-interaction (ColBERT-style) scoring.
+//!
+//! A document keeps its pooled vector in the graph as usual and, next to it,
+//! a variable number of token-level vectors. A search that carries query
+//! tokens retrieves candidates by the pooled vector and reranks them by
+//! MaxSim: each query token is matched with its most similar document token
+//! (dot product) and the maxima are summed. The reported distance is
+//! `Σ (1 - max)`, which is 0 for a perfect match of normalized vectors and
+//! keeps smaller-is-better.
+//!
+//! Token vectors live in `tokens.log` in the collection directory, an
+//! append-only log of `(id, rows, dimension, f32 values)` records where
+//! `rows = 0` removes the id. It is rewritten on snapshot and on open when
+//! most of it is dead. Writes reach the OS cache, not the disk, before the
+//! insert is acknowledged.
+
+use dashmap::DashMap;
+use hyperspace_core::{SearchResult, TokenMatrix};
+use parking_lot::Mutex;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub const TOKENS_FILE: &str = "tokens.log";
+
+/// Candidates fetched per requested result when the request doesn't say.
+pub const DEFAULT_OVERFETCH: usize = 4;
+
+pub struct TokenStore {
+    path: PathBuf,
+    docs: DashMap<u32, Arc<TokenMatrix>>,
+    log: Mutex<BufWriter<File>>,
+    /// Records in the log that a later record overrides.
+    dead: AtomicUsize,
+}
+
+impl TokenStore {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let path = dir.join(TOKENS_FILE);
+        let docs = DashMap::new();
+        let mut dead = 0;
+        if let Ok(file) = File::open(&path) {
+            let len = file.metadata()?.len();
+            let mut reader = BufReader::new(file);
+            let mut valid = 0;
+            while let Some((id, matrix)) = read_record(&mut reader)? {
+                valid = reader.stream_position()?;
+                let replaced = if let Some(m) = matrix {
+                    docs.insert(id, Arc::new(m)).is_some()
+                } else {
+                    dead += 1;
+                    docs.remove(&id).is_some()
+                };
+                dead += usize::from(replaced);
+            }
+            // A torn record at the tail is from a crash mid-write; cut it
+            // off so new records are not appended behind it.
+            if valid < len {
+                OpenOptions::new().write(true).open(&path)?.set_len(valid)?;
+            }
+        }
+        let log = OpenOptions::new().create(true).append(true).open(&path)?;
+        let store = Self {
+            path,
+            docs,
+            log: Mutex::new(BufWriter::new(log)),
+            dead: AtomicUsize::new(dead),
+        };
+        if dead > store.docs.len() {
+            store.compact()?;
+        }
+        Ok(store)
+    }
+
+    pub fn get(&self, id: u32) -> Option<Arc<TokenMatrix>> {
+        self.docs.get(&id).map(|m| m.clone())
+    }
+
+    /// Stores `matrix` as the token vectors of `id`.
+    pub fn put(&self, id: u32, matrix: TokenMatrix) -> io::Result<()> {
+        let mut log = self.log.lock();
+        write_record(&mut *log, id, Some(&matrix))?;
+        log.flush()?;
+        if self.docs.insert(id, Arc::new(matrix)).is_some() {
+            self.dead.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Drops the token vectors of `id`, if it has any.
+    pub fn remove(&self, id: u32) -> io::Result<()> {
+        if !self.docs.contains_key(&id) {
+            return Ok(());
+        }
+        let mut log = self.log.lock();
+        write_record(&mut *log, id, None)?;
+        log.flush()?;
+        self.docs.remove(&id);
+        self.dead.fetch_add(2, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Rewrites the log with only the live records.
+    pub fn compact(&self) -> io::Result<()> {
+        let mut log = self.log.lock();
+        if self.dead.load(Ordering::Relaxed) == 0 {
+            return log.get_ref().sync_data();
+        }
+        let tmp = self.path.with_extension("log.tmp");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            for entry in &self.docs {
+                write_record(&mut out, *entry.key(), Some(entry.value()))?;
+            }
+            out.into_inner()?.sync_all()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        *log = BufWriter::new(OpenOptions::new().append(true).open(&self.path)?);
+        self.dead.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+fn write_record(out: &mut impl Write, id: u32, matrix: Option<&TokenMatrix>) -> io::Result<()> {
+    let (rows, dimension) = matrix.map_or((0, 0), |m| (m.rows(), m.dimension()));
+    out.write_all(&id.to_le_bytes())?;
+    out.write_all(&(rows as u32).to_le_bytes())?;
+    out.write_all(&(dimension as u32).to_le_bytes())?;
+    for v in matrix.map_or(&[][..], TokenMatrix::values) {
+        out.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// The next record, `None` at the end of the log or at a torn tail.
+#[allow(clippy::type_complexity)]
+fn read_record(input: &mut impl Read) -> io::Result<Option<(u32, Option<TokenMatrix>)>> {
+    let mut header = [0u8; 12];
+    match input.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let word =
+        |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+    let (id, rows, dimension) = (word(0), word(4) as usize, word(8) as usize);
+    if rows == 0 {
+        return Ok(Some((id, None)));
+    }
+    let mut bytes = vec![0u8; rows * dimension * 4];
+    match input.read_exact(&mut bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let values = bytes
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .map(|b| f32::from_le_bytes(*b))
+        .collect();
+    Ok(TokenMatrix::new(dimension, values)
+        .ok()
+        .map(|m| (id, Some(m))))
+}
+
+/// Reorders `hits` by MaxSim against `query` and keeps `top_k`. Hits without
+/// token vectors of the query's dimension go last, in their original order.
+pub fn rerank(
+    hits: Vec<SearchResult>,
+    query: &TokenMatrix,
+    tokens: impl Fn(u32) -> Option<Arc<TokenMatrix>>,
+    top_k: usize,
+) -> Vec<SearchResult> {
+    let mut scored: Vec<(Option<f64>, usize, SearchResult)> = hits
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (id, distance, meta))| {
+            let late = tokens(id).and_then(|doc| doc.maxsim_distance(query));
+            (late, rank, (id, late.unwrap_or(distance), meta))
+        })
+        .collect();
+    scored.sort_by(|a, b| match (a.0, b.0) {
+        (Some(x), Some(y)) => x.total_cmp(&y).then(a.1.cmp(&b.1)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.1.cmp(&b.1),
+    });
+    scored
+        .into_iter()
+        .take(top_k)
+        .map(|(_, _, hit)| hit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn matrix(rows: &[[f32; 2]]) -> TokenMatrix {
+        TokenMatrix::new(2, rows.iter().flatten().copied().collect()).unwrap()
+    }
+
+    #[test]
+    fn store_survives_reopen_and_compacts() {
+        let dir = std::env::temp_dir().join(format!("hs_tokens_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        {
+            let store = TokenStore::open(&dir).unwrap();
+            store.put(1, matrix(&[[1.0, 0.0]])).unwrap();
+            store.put(2, matrix(&[[0.0, 1.0], [1.0, 0.0]])).unwrap();
+            store.put(1, matrix(&[[0.6, 0.8]])).unwrap();
+            store.remove(2).unwrap();
+        }
+        // Three dead records against one live one: rewritten on open.
+        let store = TokenStore::open(&dir).unwrap();
+        assert_eq!(store.docs.len(), 1);
+        assert_eq!(store.get(1).unwrap().values(), &[0.6, 0.8]);
+        assert!(store.get(2).is_none());
+        assert_eq!(fs::metadata(dir.join(TOKENS_FILE)).unwrap().len(), 12 + 8);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn torn_tail_is_cut_off() {
+        let dir = std::env::temp_dir().join(format!("hs_tokens_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        TokenStore::open(&dir)
+            .unwrap()
+            .put(1, matrix(&[[1.0, 0.0]]))
+            .unwrap();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(dir.join(TOKENS_FILE))
+            .unwrap();
+        log.write_all(&[2, 0, 0, 0, 1]).unwrap();
+        drop(log);
+
+        let store = TokenStore::open(&dir).unwrap();
+        store.put(3, matrix(&[[0.0, 1.0]])).unwrap();
+        drop(store);
+        let store = TokenStore::open(&dir).unwrap();
+        assert_eq!(store.docs.len(), 2);
+        assert!(store.get(3).is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rerank_orders_by_maxsim() {
+        let docs: HashMap<u32, Arc<TokenMatrix>> = HashMap::from([
+            (1, Arc::new(matrix(&[[1.0, 0.0]]))),
+            (2, Arc::new(matrix(&[[1.0, 0.0], [0.0, 1.0]]))),
+        ]);
+        let query = matrix(&[[1.0, 0.0], [0.0, 1.0]]);
+        let hits = vec![
+            (3, 0.1, HashMap::new()),
+            (1, 0.2, HashMap::new()),
+            (2, 0.3, HashMap::new()),
+        ];
+        let out = rerank(hits, &query, |id| docs.get(&id).cloned(), 3);
+        assert_eq!(out.iter().map(|h| h.0).collect::<Vec<_>>(), vec![2, 1, 3]);
+        assert!(out[0].1.abs() < 1e-9);
+        assert!((out[1].1 - 1.0).abs() < 1e-9);
+    }
+}
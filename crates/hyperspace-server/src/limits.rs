@@ -0,0 +1,147 @@
+//! Per-collection resource caps.
+//!
+//! Limits are chosen at creation time and stored in the collection's
+//! `meta.json`. They bound how many points a collection may hold and how many
+//! bytes its data directory may occupy, so one runaway ingest job can't fill a
+//! disk shared with other tenants. Unset or `0` means unlimited.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Walking the data directory on every write would dominate small inserts.
+const STORAGE_REFRESH: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionLimits {
+    /// Maximum number of stored points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_points: Option<u64>,
+    /// Maximum size of the collection directory (index, WAL, chunks) in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_storage_bytes: Option<u64>,
+}
+
+impl CollectionLimits {
+    pub fn max_points(&self) -> Option<u64> {
+        self.max_points.filter(|&n| n > 0)
+    }
+
+    pub fn max_storage_bytes(&self) -> Option<u64> {
+        self.max_storage_bytes.filter(|&n| n > 0)
+    }
+}
+
+/// Enforces [`CollectionLimits`] on the write path.
+pub(crate) struct LimitGuard {
+    limits: CollectionLimits,
+    data_dir: PathBuf,
+    // (measured at, bytes) — refreshed at most every STORAGE_REFRESH
+    storage: Mutex<Option<(Instant, u64)>>,
+}
+
+impl LimitGuard {
+    pub fn new(limits: CollectionLimits, data_dir: PathBuf) -> Self {
+        Self {
+            limits,
+            data_dir,
+            storage: Mutex::new(None),
+        }
+    }
+
+    /// Rejects a write that would add `new_points` to a collection currently
+    /// holding `current_points`, or any write once storage is over budget.
+    pub fn check(&self, current_points: usize, new_points: usize) -> HyperspaceResult<()> {
+        if let Some(max) = self.limits.max_points() {
+            let after = (current_points + new_points) as u64;
+            if new_points > 0 && after > max {
+                return Err(HyperspaceError::Capacity(format!(
+                    "Collection point limit reached: {current_points} stored, \
+                     {new_points} new, max_points={max}"
+                )));
+            }
+        }
+
+        if let Some(max) = self.limits.max_storage_bytes() {
+            let used = self.storage_bytes();
+            if used >= max {
+                return Err(HyperspaceError::Capacity(format!(
+                    "Collection storage limit reached: {used} bytes used, max_storage_bytes={max}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn storage_bytes(&self) -> u64 {
+        let mut cached = self.storage.lock();
+        if let Some((at, bytes)) = *cached {
+            if at.elapsed() < STORAGE_REFRESH {
+                return bytes;
+            }
+        }
+        let bytes = dir_size(&self.data_dir).unwrap_or(0);
+        *cached = Some((Instant::now(), bytes));
+        bytes
+    }
+}
+
+/// Recursive size of all files under `path`.
+pub(crate) fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total_size = 0u64;
+
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                total_size += metadata.len();
+            } else if metadata.is_dir() {
+                total_size += dir_size(&entry.path())?;
+            }
+        }
+    }
+    Ok(total_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_and_storage_caps() {
+        let dir = std::env::temp_dir().join(format!("hyperspace_limits_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("wal.log"), vec![0u8; 64]).unwrap();
+
+        let guard = LimitGuard::new(
+            CollectionLimits {
+                max_points: Some(10),
+                max_storage_bytes: None,
+            },
+            dir.clone(),
+        );
+        assert!(guard.check(9, 1).is_ok());
+        assert!(matches!(
+            guard.check(10, 1),
+            Err(HyperspaceError::Capacity(_))
+        ));
+        // Upserts don't add points.
+        assert!(guard.check(10, 0).is_ok());
+
+        let guard = LimitGuard::new(
+            CollectionLimits {
+                max_points: Some(0),
+                max_storage_bytes: Some(64),
+            },
+            dir.clone(),
+        );
+        assert!(matches!(
+            guard.check(0, 1),
+            Err(HyperspaceError::Capacity(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
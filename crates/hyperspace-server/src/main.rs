@@ -19,36 +19,97 @@ use clap::Parser;
 // Access index via CollectionManager.
 // use hyperspace_index::HnswIndex;
 
+mod adaptive_ef;
+mod allocator;
+mod anti_entropy;
+mod auth;
+mod bulk;
+mod bundle;
+mod capacity;
 mod chunk_backend;
 mod chunk_searcher;
 mod collection;
+mod collection_spec;
+mod columnar;
+mod daemon;
+mod drift;
+mod election;
+mod experiments;
+mod fencing;
+mod follower_reads;
 mod gossip;
+mod graph_export;
+mod group_commit;
 mod http_server;
+mod index_watermark;
+mod ingest;
+mod late_interaction;
+mod limits;
 mod manager;
 mod meta_router;
+mod metadata_join;
+mod metering;
+mod migration;
+mod parents;
+mod pools;
+mod purge;
+mod query_fusion;
+mod query_templates;
+mod replication;
+mod scrubber;
+mod search_cache;
+#[cfg(test)]
+mod simulation;
+mod slow_queries;
+mod snapshot;
+mod snapshot_history;
+mod storage_health;
 mod sync;
+mod telemetry;
 #[cfg(test)]
 mod tests;
-use manager::CollectionManager;
+mod text_collection;
+mod trash;
+mod wal_replay;
+mod wal_shipping;
+use manager::{ClusterRole, CollectionManager};
+use query_templates::QueryTemplate;
+use replication::{ReplicationFeed, ReplicationJournal};
 
 #[cfg(feature = "embed")]
-use hyperspace_embed::{ApiProvider, Metric, MultiVectorizer, OnnxVectorizer, RemoteVectorizer};
+use hyperspace_embed::{
+    ApiProvider, AudioVectorizer, ImageVectorizer, Metric, MultiVectorizer, OnnxVectorizer,
+    RemoteVectorizer,
+};
 use hyperspace_proto::hyperspace::database_server::{Database, DatabaseServer};
 use hyperspace_proto::hyperspace::{
-    metadata_value, BatchInsertRequest, BatchSearchRequest, BatchSearchResponse,
-    CollectionStatsRequest, CollectionStatsResponse, ConfigUpdate, CreateCollectionRequest,
-    DeleteCollectionRequest, DeleteRequest, DeleteResponse, DiffBucket, DigestRequest,
-    DigestResponse, EventMessage, EventSubscriptionRequest, EventType, Filter,
+    metadata_value, ApplyCollectionSpecRequest, ApplyCollectionSpecResponse, BatchInsertRequest,
+    BatchSearchRequest, BatchSearchResponse, ClusterNode, CollectionStatsRequest,
+    CollectionStatsResponse, ConfigUpdate, CreateCollectionRequest, DeleteCollectionRequest,
+    DeleteExperimentRequest, DeleteNamespaceResponse, DeleteQueryTemplateRequest, DeleteRequest,
+    DeleteResponse, DiffBucket, DigestRequest, DigestResponse, EventMessage,
+    EventSubscriptionRequest, EventType, ExperimentArmStats, Filter, FilterSyntaxError,
     FindSemanticClustersRequest, FindSemanticClustersResponse, GetConceptParentsRequest,
-    GetConceptParentsResponse, GetNeighborsRequest, GetNeighborsResponse, GetNodeRequest,
-    GraphCluster, GraphNode, InsertRequest, InsertResponse, InsertTextRequest,
-    ListCollectionsResponse, MetadataValue, MonitorRequest, SearchMultiCollectionRequest,
+    GetConceptParentsResponse, GetExperimentRequest, GetExperimentResponse, GetNeighborsRequest,
+    GetNeighborsResponse, GetNodeRequest, GraphCluster, GraphNode, IndexType, IndexingProgress,
+    InsertAudioRequest, InsertErrorCode, InsertErrorDetail, InsertImageRequest, InsertRequest,
+    InsertResponse, InsertStreamAck, InsertTextRequest, ListCollectionSpecsRequest,
+    ListCollectionSpecsResponse, ListCollectionsRequest, ListCollectionsResponse,
+    ListQueryTemplatesRequest, ListQueryTemplatesResponse, MetadataFieldType, MetadataJoin,
+    MetadataValue, MonitorRequest, MultiSearchHit, MultiSearchRequest, MultiSearchResponse,
+    NamespaceRequest, NamespaceStatsResponse, ParentHit, PurgeReport, PurgeRequest,
+    PutExperimentRequest, PutQueryTemplateRequest, PutQueryTemplateResponse,
+    QueryFusion as ProtoQueryFusion, QueryVector, RunQueryTemplateRequest,
+    SchemaMode as ProtoSchemaMode, SearchExplain, SearchMultiCollectionRequest,
     SearchMultiCollectionResponse, SearchRequest, SearchResponse, SearchResult, SearchTextRequest,
-    SyncHandshakeRequest, SyncHandshakeResponse, SyncPullRequest, SyncPushResponse, SyncVectorData,
-    SystemStats, TraverseRequest, TraverseResponse, VectorDeletedEvent, VectorInsertedEvent,
-    VectorizeRequest, VectorizeResponse,
+    StorageAlertEvent, SyncHandshakeRequest, SyncHandshakeResponse, SyncPullRequest,
+    SyncPushResponse, SyncVectorData, SystemStats, TextMatch as ProtoTextMatch, TokenVectors,
+    TopologyRequest, TopologyResponse, TraverseRequest, TraverseResponse, UpdateVectorDeltaRequest,
+    VectorDeletedEvent, VectorInsertedEvent, VectorizeRequest, VectorizeResponse,
+    WatchIndexingProgressRequest, WriteMode,
 };
-use hyperspace_proto::hyperspace::{replication_log, Empty, ReplicationLog};
+use hyperspace_proto::hyperspace::{replication_log, ReplicationLog};
+use hyperspace_store::wal::WalSyncMode;
 use tonic::Streaming;
 
 use sha2::{Digest, Sha256};
@@ -81,10 +142,14 @@ struct Args {
     #[arg(long)]
     leader: Option<String>,
 
-    /// User ID for multi-tenant replication (if follower)
+    /// User ID for multi-tenant replication (if follower or standby)
     #[arg(long)]
     user_id: Option<String>,
 
+    /// Primary address: run as a warm standby pulling its WAL
+    #[arg(long, env = "HS_STANDBY_OF")]
+    standby_of: Option<String>,
+
     /// Unique Node ID for this instance
     #[arg(long)]
     node_id: Option<String>,
@@ -92,11 +157,25 @@ struct Args {
     /// Allow outgoing replication streams?
     #[arg(long, default_value = "false", env = "HS_REPLICATION_ALLOWED")]
     replication_allowed: bool,
+
+    /// Detach from the terminal and run in the background (Unix only)
+    #[arg(long, default_value = "false")]
+    daemon: bool,
+
+    /// Write the server PID to this file; removed on clean shutdown
+    #[arg(long, env = "HS_PID_FILE")]
+    pid_file: Option<std::path::PathBuf>,
+
+    /// With --daemon: append stdout and stderr here instead of discarding them
+    #[arg(long, env = "HS_LOG_FILE")]
+    log_file: Option<std::path::PathBuf>,
 }
 
 #[derive(Clone)]
 struct AuthInterceptor {
     expected_hash: Option<String>,
+    /// `HYPERSPACE_READ_API_KEY`, limited to `Scope::Read` methods.
+    read_hash: Option<String>,
 }
 
 impl Interceptor for AuthInterceptor {
@@ -115,6 +194,20 @@ impl Interceptor for AuthInterceptor {
                         if constant_time_eq(request_hash.as_bytes(), expected.as_bytes()) {
                             return Ok(request);
                         }
+                        if let Some(read) = &self.read_hash {
+                            if constant_time_eq(request_hash.as_bytes(), read.as_bytes()) {
+                                let path = request
+                                    .extensions()
+                                    .get::<auth::GrpcPath>()
+                                    .map_or("", |p| p.0.as_str());
+                                if auth::grpc_scope(path) == auth::Scope::Read {
+                                    return Ok(request);
+                                }
+                                return Err(Status::permission_denied(format!(
+                                    "Read-only API key cannot call {path}"
+                                )));
+                            }
+                        }
                     }
                     Err(Status::unauthenticated("Invalid API Key"))
                 }
@@ -167,6 +260,18 @@ fn search_batch_inner_concurrency() -> usize {
     })
 }
 
+/// Indexing queue depth above which `InsertStream` holds its next ack.
+fn insert_stream_max_queue() -> u64 {
+    static MAX_QUEUE: OnceLock<u64> = OnceLock::new();
+    *MAX_QUEUE.get_or_init(|| {
+        std::env::var("HS_INSERT_STREAM_MAX_QUEUE")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(50_000)
+    })
+}
+
 fn range_bounds_f64(r: &hyperspace_proto::hyperspace::Range) -> (Option<f64>, Option<f64>) {
     let gte = r.gte_f64.or(r.gte.map(|v| v as f64));
     let lte = r.lte_f64.or(r.lte.map(|v| v as f64));
@@ -204,24 +309,342 @@ fn parse_bm25_options(
     params
 }
 
-fn build_filters(
-    req: SearchRequest,
-) -> (
-    String,
-    Vec<f64>,
-    std::collections::HashMap<String, String>,
-    Vec<hyperspace_core::FilterExpr>,
-    hyperspace_core::SearchParams,
-) {
-    let col_name = if req.collection.is_empty() {
-        "default".to_string()
-    } else {
-        req.collection
-    };
+/// Vector payload as received on the wire: classic `f64` or packed `f32`.
+/// The f32 variant is passed through to `Collection::*_f32` without widening
+/// into a heap-allocated `Vec<f64>`.
+#[derive(Clone)]
+enum WireVector {
+    F64(Vec<f64>),
+    F32(Vec<f32>),
+}
 
-    let exact_filter = req.filter.into_iter().collect();
+impl WireVector {
+    fn new(vector: Vec<f64>, vector_f32: Vec<f32>) -> Self {
+        if vector.is_empty() && !vector_f32.is_empty() {
+            Self::F32(vector_f32)
+        } else {
+            Self::F64(vector)
+        }
+    }
+
+    fn check(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+    ) -> Result<(), hyperspace_core::VectorViolation> {
+        let policy = col.normalization();
+        match self {
+            Self::F64(v) => policy.check(v, col.dimension(), col.metric_name()),
+            Self::F32(v) => policy.check(v, col.dimension(), col.metric_name()),
+        }
+    }
+
+    fn into_f64(self) -> Vec<f64> {
+        match self {
+            Self::F64(v) => v,
+            Self::F32(v) => v.into_iter().map(f64::from).collect(),
+        }
+    }
+
+    async fn search(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[hyperspace_core::FilterExpr],
+        params: &hyperspace_core::SearchParams,
+    ) -> hyperspace_core::HyperspaceResult<Vec<hyperspace_core::SearchResult>> {
+        match self {
+            Self::F64(v) => col.search(v, filter, complex_filters, params).await,
+            Self::F32(v) => col.search_f32(v, filter, complex_filters, params).await,
+        }
+    }
+}
+
+/// The query side of a `SearchRequest`: one vector, or several whose results
+/// are fused (`queries` / `query_fusion`).
+enum QueryVectors {
+    Single(WireVector),
+    Fused(Vec<Vec<f64>>, query_fusion::QueryFusion),
+}
+
+impl QueryVectors {
+    fn new(vector: WireVector, queries: Vec<QueryVector>, fusion: i32) -> Self {
+        if queries.is_empty() {
+            return Self::Single(vector);
+        }
+        let first = vector.into_f64();
+        let vectors = (!first.is_empty())
+            .then_some(first)
+            .into_iter()
+            .chain(queries.into_iter().map(|q| q.values))
+            .collect();
+        Self::Fused(vectors, query_fusion_from_proto(fusion))
+    }
+
+    async fn search(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[hyperspace_core::FilterExpr],
+        params: &hyperspace_core::SearchParams,
+    ) -> hyperspace_core::HyperspaceResult<Vec<hyperspace_core::SearchResult>> {
+        match self {
+            Self::Single(vector) => vector.search(col, filter, complex_filters, params).await,
+            Self::Fused(queries, fusion) => {
+                query_fusion::search_fused(col, queries, filter, complex_filters, params, *fusion)
+                    .await
+            }
+        }
+    }
+}
+
+/// Validated token vectors; `None` when absent or empty.
+fn token_matrix(
+    tokens: Option<TokenVectors>,
+) -> hyperspace_core::HyperspaceResult<Option<hyperspace_core::TokenMatrix>> {
+    match tokens {
+        Some(t) if !t.values.is_empty() => {
+            hyperspace_core::TokenMatrix::new(t.dimension as usize, t.values).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The vectors of a search and, with `query_tokens`, the late-interaction
+/// rerank applied to what they retrieve.
+struct SearchQuery {
+    vectors: QueryVectors,
+    /// Query tokens and the number of hits to keep after reranking.
+    rerank: Option<(hyperspace_core::TokenMatrix, usize)>,
+}
+
+impl SearchQuery {
+    /// Like [`Self::search`], and records the search in the slow query log
+    /// when it takes longer than the threshold. Returns the diagnostics of
+    /// searches whose `params` carry a trace (`explain`).
+    #[allow(clippy::too_many_arguments)]
+    async fn search_logged(
+        &self,
+        slow_queries: &slow_queries::SlowQueryLog,
+        user_id: &str,
+        collection: &str,
+        filter_expr: Option<&str>,
+        col: &dyn hyperspace_core::Collection,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[hyperspace_core::FilterExpr],
+        params: &hyperspace_core::SearchParams,
+    ) -> hyperspace_core::HyperspaceResult<(
+        Vec<hyperspace_core::SearchResult>,
+        Option<SearchExplain>,
+    )> {
+        let traced = slow_queries.traced(params);
+        let explain = params.trace.clone();
+        let params = traced.as_ref().unwrap_or(params);
+        let started = std::time::Instant::now();
+        let hits = self.search(col, filter, complex_filters, params).await?;
+        let latency = started.elapsed();
+        let shape = slow_queries::SearchShape {
+            user_id,
+            collection,
+            filter_clauses: filter.len() + complex_filters.len(),
+            filter_expr,
+            queries: match &self.vectors {
+                QueryVectors::Single(_) => 1,
+                QueryVectors::Fused(queries, _) => queries.len(),
+            },
+        };
+        slow_queries.observe(&shape, params, latency, hits.len());
+        let explain = explain.map(|trace| search_explain(&trace, latency));
+        Ok((hits, explain))
+    }
+
+    /// Overrides the request's settings with those of an experiment arm.
+    fn apply_arm(
+        &mut self,
+        arm: &experiments::SearchArm,
+        params: &mut hyperspace_core::SearchParams,
+    ) {
+        if let (Some(candidates), Some((_, top_k))) = (arm.rerank_candidates, &self.rerank) {
+            params.top_k = candidates.max(*top_k);
+        }
+        if let Some(ef_search) = arm.ef_search {
+            params.ef_search = ef_search.max(params.top_k);
+        }
+        if let (Some(fusion), QueryVectors::Fused(_, current)) =
+            (arm.query_fusion, &mut self.vectors)
+        {
+            *current = fusion;
+        }
+    }
+
+    async fn search(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        filter: &std::collections::HashMap<String, String>,
+        complex_filters: &[hyperspace_core::FilterExpr],
+        params: &hyperspace_core::SearchParams,
+    ) -> hyperspace_core::HyperspaceResult<Vec<hyperspace_core::SearchResult>> {
+        let hits = self
+            .vectors
+            .search(col, filter, complex_filters, params)
+            .await?;
+        let Some((tokens, top_k)) = &self.rerank else {
+            return Ok(hits);
+        };
+        let started = std::time::Instant::now();
+        let hits = col.rerank_late_interaction(hits, tokens, *top_k);
+        if let Some(trace) = &params.trace {
+            trace.record(|stats| stats.rerank_time += started.elapsed());
+        }
+        Ok(hits)
+    }
+}
+
+fn search_explain(
+    trace: &hyperspace_core::SearchTrace,
+    total: std::time::Duration,
+) -> SearchExplain {
+    let stats = trace.stats();
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    SearchExplain {
+        visited_nodes: trace.visited(),
+        layers_traversed: stats.layers,
+        total_ms: ms(total),
+        filter_ms: ms(stats.filter_time),
+        routing_ms: ms(stats.routing_time),
+        layer0_ms: ms(stats.layer0_time),
+        rerank_ms: ms(stats.rerank_time),
+        filter_matches: stats.filter_matches.unwrap_or(0),
+        filter_selectivity: stats.filter_selectivity(),
+        filter_bruteforce: stats.filter_bruteforce,
+        filter_short_circuit: stats.filter_short_circuit,
+    }
+}
+
+/// Rejects a malformed `filter_expr` with `INVALID_ARGUMENT` and a structured
+/// `FilterSyntaxError` carrying the byte position of the problem.
+fn filter_syntax_status(expression: &str, e: &hyperspace_core::FilterParseError) -> Status {
+    FilterSyntaxError {
+        position: e.position as u32,
+        message: e.message.clone(),
+        expression: expression.to_string(),
+    }
+    .into_status()
+}
+
+fn query_template_from_proto(t: hyperspace_proto::hyperspace::QueryTemplate) -> QueryTemplate {
+    QueryTemplate {
+        filter_expr: t.filter_expr,
+        top_k: t.top_k.map(|k| k as usize),
+        ef_search: t.ef_search.map(|ef| ef as usize),
+        hybrid_query: t.hybrid_query,
+        hybrid_alpha: t.hybrid_alpha,
+        group_by: t.group_by,
+        group_size: t.group_size.map(|n| n as usize),
+        defaults: t.defaults,
+        version: t.version,
+    }
+}
+
+fn query_template_to_proto(t: QueryTemplate) -> hyperspace_proto::hyperspace::QueryTemplate {
+    hyperspace_proto::hyperspace::QueryTemplate {
+        filter_expr: t.filter_expr,
+        top_k: t.top_k.map(|k| k as u32),
+        ef_search: t.ef_search.map(|ef| ef as u32),
+        hybrid_query: t.hybrid_query,
+        hybrid_alpha: t.hybrid_alpha,
+        group_by: t.group_by,
+        group_size: t.group_size.map(|n| n as u32),
+        defaults: t.defaults,
+        version: t.version,
+    }
+}
+
+fn query_fusion_from_proto(fusion: i32) -> query_fusion::QueryFusion {
+    use query_fusion::QueryFusion as Fusion;
+    match ProtoQueryFusion::try_from(fusion).unwrap_or_default() {
+        ProtoQueryFusion::MaxSim => Fusion::MaxSim,
+        ProtoQueryFusion::Average => Fusion::Average,
+        ProtoQueryFusion::Rrf => Fusion::Rrf,
+    }
+}
+
+fn text_match_from_proto(mode: i32) -> hyperspace_core::TextMatch {
+    use hyperspace_core::TextMatch;
+    match ProtoTextMatch::try_from(mode).unwrap_or_default() {
+        ProtoTextMatch::MatchAny => TextMatch::Any,
+        ProtoTextMatch::MatchAll => TextMatch::All,
+        ProtoTextMatch::MatchPhrase => TextMatch::Phrase,
+    }
+}
+
+fn collection_spec_from_proto(
+    spec: hyperspace_proto::hyperspace::CollectionSpec,
+) -> collection_spec::CollectionSpec {
+    let hnsw = spec.hnsw.unwrap_or_default();
+    collection_spec::CollectionSpec {
+        name: spec.name,
+        dimension: spec.dimension,
+        metric: spec.metric,
+        quantization: spec.quantization,
+        hnsw: collection_spec::HnswParams {
+            m: hnsw.m.map(|m| m as usize),
+            ef_construction: hnsw.ef_construction.map(|ef| ef as usize),
+        },
+        aliases: spec.aliases,
+        description: spec.description,
+        labels: spec.labels.into_iter().collect(),
+    }
+}
+
+fn collection_spec_to_proto(
+    spec: collection_spec::CollectionSpec,
+) -> hyperspace_proto::hyperspace::CollectionSpec {
+    hyperspace_proto::hyperspace::CollectionSpec {
+        name: spec.name,
+        dimension: spec.dimension,
+        metric: spec.metric,
+        quantization: spec.quantization,
+        hnsw: (!spec.hnsw.is_default()).then(|| hyperspace_proto::hyperspace::HnswParams {
+            m: spec.hnsw.m.map(|m| m as u32),
+            ef_construction: spec.hnsw.ef_construction.map(|ef| ef as u32),
+        }),
+        aliases: spec.aliases,
+        description: spec.description,
+        labels: spec.labels.into_iter().collect(),
+    }
+}
+
+fn search_arm_from_proto(arm: &hyperspace_proto::hyperspace::SearchArm) -> experiments::SearchArm {
+    experiments::SearchArm {
+        ef_search: arm.ef_search.map(|ef| ef as usize),
+        rerank_candidates: arm.rerank_candidates.map(|n| n as usize),
+        query_fusion: arm.query_fusion.map(query_fusion_from_proto),
+    }
+}
+
+fn search_arm_to_proto(arm: &experiments::SearchArm) -> hyperspace_proto::hyperspace::SearchArm {
+    use query_fusion::QueryFusion as Fusion;
+    hyperspace_proto::hyperspace::SearchArm {
+        ef_search: arm.ef_search.map(|ef| ef as u32),
+        rerank_candidates: arm.rerank_candidates.map(|n| n as u32),
+        query_fusion: arm.query_fusion.map(|f| {
+            (match f {
+                Fusion::MaxSim => ProtoQueryFusion::MaxSim,
+                Fusion::Average => ProtoQueryFusion::Average,
+                Fusion::Rrf => ProtoQueryFusion::Rrf,
+            }) as i32
+        }),
+    }
+}
+
+/// Converts proto filters, plus an optional textual `filter_expr`, to `FilterExpr`s.
+#[allow(clippy::result_large_err)]
+fn complex_filters(
+    filters: Vec<Filter>,
+    filter_expr: Option<&str>,
+) -> Result<Vec<hyperspace_core::FilterExpr>, Status> {
     let mut complex_filters = Vec::new();
-    for f in req.filters {
+    for f in filters {
         if let Some(cond) = f.condition {
             match cond {
                 hyperspace_proto::hyperspace::filter::Condition::Match(m) => {
@@ -257,21 +680,79 @@ fn build_filters(
                         radius: b.radius,
                     });
                 }
+                hyperspace_proto::hyperspace::filter::Condition::PhraseMatch(p) => {
+                    complex_filters
+                        .push(hyperspace_core::FilterExpr::PhraseMatch { phrase: p.phrase });
+                }
             }
         }
     }
 
+    if let Some(expr) = filter_expr {
+        let parsed =
+            hyperspace_core::parse_filter(expr).map_err(|e| filter_syntax_status(expr, &e))?;
+        complex_filters.extend(parsed);
+    }
+    Ok(complex_filters)
+}
+
+/// Cap on `SearchRequest.num_probes`: each probe is a full layer-0 search.
+const MAX_NUM_PROBES: u32 = 16;
+
+#[allow(clippy::result_large_err)]
+fn build_filters(
+    req: SearchRequest,
+) -> Result<
+    (
+        String,
+        SearchQuery,
+        std::collections::HashMap<String, String>,
+        Vec<hyperspace_core::FilterExpr>,
+        hyperspace_core::SearchParams,
+    ),
+    Status,
+> {
+    let col_name = if req.collection.is_empty() {
+        "default".to_string()
+    } else {
+        req.collection
+    };
+
+    let exact_filter = req.filter.into_iter().collect();
+    let complex_filters = complex_filters(req.filters, req.filter_expr.as_deref())?;
+
+    let rerank = token_matrix(req.query_tokens)
+        .map_err(error_status)?
+        .map(|t| (t, req.top_k as usize));
+    let top_k = match (&rerank, req.rerank_candidates) {
+        (None, _) => req.top_k as usize,
+        (Some(_), 0) => req.top_k as usize * late_interaction::DEFAULT_OVERFETCH,
+        (Some(_), n) => (n as usize).max(req.top_k as usize),
+    };
     let params = hyperspace_core::SearchParams {
-        top_k: req.top_k as usize,
+        top_k,
         ef_search: default_ef_search(),
         hybrid_query: req.hybrid_query,
         hybrid_alpha: req.hybrid_alpha,
         use_wasserstein: req.use_wasserstein,
         bm25_options: req.bm25_options.as_ref().map(parse_bm25_options),
         fusion_method: req.bm25_options.and_then(|opts| opts.fusion_method),
+        text_match: text_match_from_proto(req.text_match),
+        trace: req
+            .explain
+            .then(|| Arc::new(hyperspace_core::SearchTrace::default())),
+        num_probes: req.num_probes.min(MAX_NUM_PROBES) as usize,
     };
 
-    (col_name, req.vector, exact_filter, complex_filters, params)
+    let vector = SearchQuery {
+        vectors: QueryVectors::new(
+            WireVector::new(req.vector, req.vector_f32),
+            req.queries,
+            req.query_fusion,
+        ),
+        rerank,
+    };
+    Ok((col_name, vector, exact_filter, complex_filters, params))
 }
 
 const TYPED_META_PREFIX: &str = "__hs_typed__";
@@ -369,6 +850,103 @@ fn extract_typed_metadata(
     typed
 }
 
+/// Which hits go into the response and which of their fields, from
+/// `score_threshold`, `with_vector`, `with_payload` and `joins` of the
+/// request.
+#[derive(Clone, Default)]
+struct ResultProjection {
+    with_vector: bool,
+    /// `None` returns every key.
+    payload: Option<HashSet<String>>,
+    max_distance: Option<f64>,
+    /// Set by [`HyperspaceService::resolve_joins`]; joined fields pass
+    /// `payload`.
+    joins: Vec<metadata_join::ResolvedJoin>,
+}
+
+impl ResultProjection {
+    fn new(req: &SearchRequest) -> Self {
+        Self {
+            with_vector: req.with_vector,
+            payload: (!req.with_payload.is_empty())
+                .then(|| req.with_payload.iter().cloned().collect()),
+            max_distance: req.score_threshold,
+            joins: Vec::new(),
+        }
+    }
+
+    /// Drops hits past `score_threshold` and selects the fields of the rest.
+    fn results(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        hits: Vec<hyperspace_core::SearchResult>,
+    ) -> Vec<SearchResult> {
+        let mut hits: Vec<_> = hits
+            .into_iter()
+            .filter(|(_, distance, _)| self.max_distance.is_none_or(|max| *distance <= max))
+            .collect();
+        for join in &self.joins {
+            join.enrich(&mut hits);
+        }
+        hits.into_iter().map(|hit| self.apply(col, hit)).collect()
+    }
+
+    /// Groups chunk hits by the parent they point at, see [`parents`].
+    /// Parents missing from `parent_col`, or whose chunks are all past
+    /// `score_threshold`, are dropped.
+    fn parents(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        parent_col: &dyn hyperspace_core::Collection,
+        hits: Vec<hyperspace_core::SearchResult>,
+        top_k: usize,
+        chunks_per_parent: usize,
+    ) -> Vec<ParentHit> {
+        let hits = hits
+            .into_iter()
+            .filter(|(_, distance, _)| self.max_distance.is_none_or(|max| *distance <= max))
+            .collect();
+        parents::group(hits, top_k, chunks_per_parent)
+            .into_iter()
+            .filter(|(id, _)| parent_col.contains(*id))
+            .map(|(id, chunks)| {
+                let meta = parent_col.metadata_by_id(id);
+                ParentHit {
+                    id,
+                    distance: chunks[0].1,
+                    typed_metadata: extract_typed_metadata(&meta),
+                    metadata: strip_internal_metadata(&meta),
+                    chunks: self.results(col, chunks),
+                }
+            })
+            .collect()
+    }
+
+    fn apply(
+        &self,
+        col: &dyn hyperspace_core::Collection,
+        (id, distance, mut meta): hyperspace_core::SearchResult,
+    ) -> SearchResult {
+        if let Some(keys) = &self.payload {
+            meta.retain(|k, _| {
+                let key = k.strip_prefix(TYPED_META_PREFIX).unwrap_or(k);
+                keys.contains(key) || self.joins.iter().any(|j| key.starts_with(&j.prefix))
+            });
+        }
+        SearchResult {
+            id,
+            distance,
+            typed_metadata: extract_typed_metadata(&meta),
+            metadata: strip_internal_metadata(&meta),
+            vector: if self.with_vector {
+                col.vector_by_id(id).unwrap_or_default()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
 fn build_graph_node(
     col: &Arc<dyn hyperspace_core::Collection>,
     id: u32,
@@ -432,6 +1010,12 @@ fn matches_filter_exprs(
                     }
                 }
             }
+            hyperspace_core::FilterExpr::PhraseMatch { phrase } => {
+                let params = collection::bm25_params_from_env();
+                if !hyperspace_index::MetadataIndex::contains_phrase(metadata, phrase, &params) {
+                    return false;
+                }
+            }
             hyperspace_core::FilterExpr::InCone { .. }
             | hyperspace_core::FilterExpr::InBox { .. }
             | hyperspace_core::FilterExpr::InBall { .. } => {
@@ -487,6 +1071,10 @@ fn parse_graph_filters(
                         radius: b.radius,
                     });
                 }
+                hyperspace_proto::hyperspace::filter::Condition::PhraseMatch(p) => {
+                    complex_filters
+                        .push(hyperspace_core::FilterExpr::PhraseMatch { phrase: p.phrase });
+                }
             }
         }
     }
@@ -549,110 +1137,446 @@ fn get_user_id<T>(req: &Request<T>) -> String {
         )
 }
 
-pub struct HyperspaceService {
-    manager: Arc<CollectionManager>,
-    replication_tx: broadcast::Sender<ReplicationLog>,
-    role: String,
-    replication_allowed: bool,
-    #[cfg(feature = "embed")]
-    vectorizer: Option<Arc<MultiVectorizer>>,
+/// The columnar format an `ExportFormat` asks for, `None` for a bundle.
+fn columnar_format(
+    format: i32,
+) -> hyperspace_core::HyperspaceResult<Option<columnar::ColumnarFormat>> {
+    use hyperspace_proto::hyperspace::ExportFormat;
+    match ExportFormat::try_from(format) {
+        Ok(ExportFormat::ExportBundle) => Ok(None),
+        Ok(ExportFormat::ExportArrowIpc) => Ok(Some(columnar::ColumnarFormat::ArrowIpc)),
+        Ok(ExportFormat::ExportParquet) => Ok(Some(columnar::ColumnarFormat::Parquet)),
+        Err(_) => Err(hyperspace_core::HyperspaceError::Validation(format!(
+            "Unknown export format {format}"
+        ))),
+    }
 }
 
-#[tonic::async_trait]
-impl Database for HyperspaceService {
-    // --- Collection Management ---
-
-    async fn create_collection(
-        &self,
-        request: Request<CreateCollectionRequest>,
-    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
-        let user_id = get_user_id(&request);
-        let req = request.into_inner();
-        if req.name.is_empty() {
-            return Err(Status::invalid_argument("Collection name cannot be empty"));
+/// Writes an upload of `CollectionBundleChunk`s to `out`. Returns the
+/// collection and format named by the first chunk.
+#[allow(clippy::result_large_err)]
+async fn receive_chunks(
+    mut stream: Streaming<hyperspace_proto::hyperspace::CollectionBundleChunk>,
+    out: &std::path::Path,
+) -> Result<(String, i32), Status> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::File::create(out)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+    let mut first = None;
+    while let Some(chunk) = stream.message().await? {
+        if first.is_none() {
+            first = Some((chunk.collection, chunk.format));
         }
-
-        // Map string metric to internal
-        // Manager accepts string metric.
-        match self
-            .manager
-            .create_collection(&user_id, &req.name, req.dimension, &req.metric)
+        file.write_all(&chunk.data)
             .await
-        {
-            Ok(()) => Ok(Response::new(
-                hyperspace_proto::hyperspace::StatusResponse {
-                    status: format!("Collection '{}' created.", req.name),
-                },
-            )),
-            Err(e) => Err(Status::already_exists(e)),
-        }
+            .map_err(|e| Status::internal(e.to_string()))?;
     }
-
-    async fn delete_collection(
-        &self,
-        request: Request<DeleteCollectionRequest>,
-    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
-        let user_id = get_user_id(&request);
-        let req = request.into_inner();
-        match self.manager.delete_collection(&user_id, &req.name).await {
-            Ok(()) => Ok(Response::new(
-                hyperspace_proto::hyperspace::StatusResponse {
-                    status: format!("Collection '{}' deleted.", req.name),
-                },
-            )),
-            Err(e) => Err(Status::not_found(e)),
-        }
+    file.flush()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+    match first {
+        Some((name, format)) if !name.is_empty() => Ok((name, format)),
+        _ => Err(Status::invalid_argument(
+            "First chunk must name the collection",
+        )),
     }
+}
 
-    async fn list_collections(
-        &self,
-        _request: Request<Empty>,
-    ) -> Result<Response<ListCollectionsResponse>, Status> {
-        let user_id = get_user_id(&_request);
-        let collections = self.manager.list_detailed(&user_id).await;
-        Ok(Response::new(ListCollectionsResponse { collections }))
+/// Maps engine errors onto gRPC status codes so clients can decide whether
+/// to retry, fix their input or give up.
+fn error_status(e: hyperspace_core::HyperspaceError) -> Status {
+    use hyperspace_core::HyperspaceError;
+    match e {
+        HyperspaceError::Validation(msg) => Status::invalid_argument(msg),
+        HyperspaceError::NotFound(msg) => Status::not_found(msg),
+        HyperspaceError::Precondition(msg) => Status::failed_precondition(msg),
+        HyperspaceError::Capacity(msg) => Status::resource_exhausted(msg),
+        HyperspaceError::Corruption(msg) => Status::data_loss(msg),
+        HyperspaceError::Io(err) => Status::unavailable(err.to_string()),
+        HyperspaceError::Internal(msg) => Status::internal(msg),
     }
+}
 
-    async fn get_collection_stats(
-        &self,
-        request: Request<CollectionStatsRequest>,
-    ) -> Result<Response<CollectionStatsResponse>, Status> {
-        let user_id = get_user_id(&request);
-        let req = request.into_inner();
-        if let Some(col) = self.manager.get(&user_id, &req.name).await {
-            Ok(Response::new(CollectionStatsResponse {
-                count: col.count() as u64,
-                dimension: col.dimension() as u32,
-                metric: col.metric_name().to_string(),
-                indexing_queue: col.queue_size(),
-            }))
-        } else {
-            Err(Status::not_found("Collection not found"))
+/// Maps a rejected vector to an `INVALID_ARGUMENT` status with a structured
+/// `InsertErrorDetail` so SDKs can surface typed errors instead of strings.
+fn vector_violation_status(
+    id: u32,
+    field: &str,
+    violation: &hyperspace_core::VectorViolation,
+) -> Status {
+    use hyperspace_core::VectorViolation;
+    let mut detail = InsertErrorDetail {
+        id,
+        field: field.to_string(),
+        message: violation.to_string(),
+        ..Default::default()
+    };
+    match violation {
+        VectorViolation::DimensionMismatch { expected, actual } => {
+            detail.set_code(InsertErrorCode::DimensionMismatch);
+            detail.expected_dimension = *expected as u32;
+            detail.actual_dimension = *actual as u32;
+        }
+        VectorViolation::NonFinite { index } => {
+            detail.set_code(InsertErrorCode::NanValues);
+            detail.field = format!("{field}[{index}]");
+        }
+        VectorViolation::OutOfBall { norm_sq } => {
+            detail.set_code(InsertErrorCode::OutOfBall);
+            detail.norm = norm_sq.sqrt();
+        }
+        VectorViolation::OffHyperboloid { minkowski_norm } => {
+            detail.set_code(InsertErrorCode::OutOfBall);
+            detail.norm = *minkowski_norm;
+        }
+        VectorViolation::NotNormalized { norm } => {
+            detail.set_code(InsertErrorCode::NotNormalized);
+            detail.norm = *norm;
         }
     }
+    detail.into_status()
+}
 
-    // --- Data Plane ---
+fn metadata_schema_from_proto(
+    req: &CreateCollectionRequest,
+) -> Option<hyperspace_core::MetadataSchema> {
+    use hyperspace_core::{MetadataType, SchemaMode};
+    if req.metadata_schema.is_empty() && req.unindexed_keys.is_empty() {
+        return None;
+    }
+    let fields = req
+        .metadata_schema
+        .iter()
+        .map(|(key, &ty)| {
+            let ty = match MetadataFieldType::try_from(ty).unwrap_or_default() {
+                MetadataFieldType::MetadataString => MetadataType::String,
+                MetadataFieldType::MetadataInt => MetadataType::Int,
+                MetadataFieldType::MetadataFloat => MetadataType::Float,
+                MetadataFieldType::MetadataBool => MetadataType::Bool,
+            };
+            (key.clone(), ty)
+        })
+        .collect();
+    let mode = match ProtoSchemaMode::try_from(req.schema_mode).unwrap_or_default() {
+        ProtoSchemaMode::SchemaStrict => SchemaMode::Strict,
+        ProtoSchemaMode::SchemaLenient => SchemaMode::Lenient,
+    };
+    Some(hyperspace_core::MetadataSchema {
+        fields,
+        mode,
+        unindexed: req.unindexed_keys.iter().cloned().collect(),
+    })
+}
 
-    async fn insert(
-        &self,
-        request: Request<InsertRequest>,
-    ) -> Result<Response<InsertResponse>, Status> {
-        if self.role == "follower" {
-            return Err(Status::permission_denied("Followers are read-only"));
+/// Normalization policy of a create request; `None` follows the metric.
+#[allow(clippy::result_large_err)]
+fn normalization_from_proto(
+    req: &CreateCollectionRequest,
+) -> Result<Option<hyperspace_core::NormalizationPolicy>, Status> {
+    use hyperspace_core::normalization::{NormalizationPolicy, DEFAULT_PROJECTION_EPSILON};
+    let policy = match req.normalization.to_lowercase().as_str() {
+        "" => return Ok(None),
+        "normalize" => NormalizationPolicy::Normalize,
+        "reject" => NormalizationPolicy::Reject,
+        "project" => NormalizationPolicy::Project {
+            epsilon: req.projection_epsilon.unwrap_or(DEFAULT_PROJECTION_EPSILON),
+        },
+        other => {
+            return Err(Status::invalid_argument(format!(
+                "Unknown normalization '{other}' (expected normalize, reject or project)"
+            )))
         }
-        let user_id = get_user_id(&request);
-        let req = request.into_inner();
+    };
+    policy
+        .validate_for(&req.metric.to_lowercase())
+        .map_err(Status::invalid_argument)?;
+    Ok(Some(policy))
+}
 
+/// IVF parameters of a create request; `None` for a plain HNSW index.
+#[allow(clippy::result_large_err)]
+fn ivf_params_from_proto(
+    req: &CreateCollectionRequest,
+) -> Result<Option<collection_spec::IvfParams>, Status> {
+    match IndexType::try_from(req.index_type) {
+        Ok(IndexType::IndexHnsw) => Ok(None),
+        Ok(IndexType::IndexIvf) => {
+            let params = collection_spec::IvfParams {
+                cells: req.ivf_cells.map(|k| k as usize),
+                probes: req.ivf_probes.map(|p| p as usize),
+            };
+            params
+                .validate()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            Ok(Some(params))
+        }
+        Err(_) => Err(Status::invalid_argument(format!(
+            "Unknown index_type {}",
+            req.index_type
+        ))),
+    }
+}
+
+/// Checks one point's metadata against the collection's schema. Strict
+/// schemas reject a mistyped value with a `METADATA_TYPE_MISMATCH` detail;
+/// lenient ones drop it from `metadata`.
+#[allow(clippy::result_large_err)]
+fn check_metadata_schema(
+    col: &dyn hyperspace_core::Collection,
+    id: u32,
+    field: &str,
+    metadata: &mut std::collections::HashMap<String, String>,
+) -> Result<(), Status> {
+    let Some(schema) = col.metadata_schema() else {
+        return Ok(());
+    };
+    schema.check(metadata).map(drop).map_err(|violation| {
+        let mut detail = InsertErrorDetail {
+            id,
+            field: format!("{field}.{}", violation.key),
+            message: violation.to_string(),
+            expected_type: violation.expected.as_str().to_string(),
+            ..Default::default()
+        };
+        detail.set_code(InsertErrorCode::MetadataTypeMismatch);
+        detail.into_status()
+    })
+}
+
+/// Applies a request's `WriteMode` to one point: `INSERT_ONLY` rejects an
+/// existing id with an `ID_EXISTS` detail, `UPSERT_MERGE` layers `metadata`
+/// over the stored keys. Returns the metadata to write.
+///
+/// Merges read the indexed metadata, so a write to the same id that is
+/// still in the indexing queue may not be seen yet.
+#[allow(clippy::result_large_err)]
+fn apply_write_mode(
+    col: &dyn hyperspace_core::Collection,
+    mode: i32,
+    id: u32,
+    field: &str,
+    metadata: std::collections::HashMap<String, String>,
+) -> Result<std::collections::HashMap<String, String>, Status> {
+    match WriteMode::try_from(mode).unwrap_or_default() {
+        WriteMode::UpsertReplace => Ok(metadata),
+        WriteMode::InsertOnly => {
+            if !col.contains(id) {
+                return Ok(metadata);
+            }
+            let mut detail = InsertErrorDetail {
+                id,
+                field: field.to_string(),
+                message: format!("Id {id} already exists"),
+                ..Default::default()
+            };
+            detail.set_code(InsertErrorCode::IdExists);
+            Err(detail.into_status())
+        }
+        WriteMode::UpsertMerge => {
+            let mut merged = col.metadata_by_id(id);
+            merged.extend(metadata);
+            Ok(merged)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HyperspaceService {
+    manager: Arc<CollectionManager>,
+    replication_tx: ReplicationFeed,
+    replication_allowed: bool,
+    #[cfg(feature = "embed")]
+    vectorizer: Option<Arc<MultiVectorizer>>,
+}
+
+impl HyperspaceService {
+    /// `NOT_FOUND` for a missing collection. When it was migrated away, the
+    /// message and the `x-hyperspace-moved-to` header name the new server.
+    fn collection_not_found(&self, user_id: &str, name: &str) -> Status {
+        let Some(addr) = self.manager.moved_to(user_id, name) else {
+            return Status::not_found(format!("Collection '{name}' not found"));
+        };
+        let mut status = Status::not_found(format!("Collection '{name}' moved to {addr}"));
+        if let Ok(value) = addr.parse() {
+            status.metadata_mut().insert("x-hyperspace-moved-to", value);
+        }
+        status
+    }
+
+    /// Looks up the collections `joins` read, for [`ResultProjection`].
+    #[allow(clippy::result_large_err)]
+    async fn resolve_joins(
+        &self,
+        user_id: &str,
+        joins: &[MetadataJoin],
+    ) -> Result<Vec<metadata_join::ResolvedJoin>, Status> {
+        if joins.len() > metadata_join::MAX_JOINS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} joins per search, got {}",
+                metadata_join::MAX_JOINS,
+                joins.len()
+            )));
+        }
+        let mut resolved = Vec::with_capacity(joins.len());
+        for join in joins {
+            if join.key.is_empty() {
+                return Err(Status::invalid_argument(format!(
+                    "Join with '{}' needs a key",
+                    join.collection
+                )));
+            }
+            let target = self
+                .manager
+                .get(user_id, &join.collection)
+                .await
+                .ok_or_else(|| self.collection_not_found(user_id, &join.collection))?;
+            resolved.push(metadata_join::ResolvedJoin::new(join.clone(), target));
+        }
+        Ok(resolved)
+    }
+
+    /// Embeds a query with the text model of collections of `metric`.
+    #[allow(clippy::result_large_err)]
+    async fn embed_query(
+        &self,
+        user_id: &str,
+        text: &str,
+        metric: &str,
+    ) -> Result<Vec<f64>, Status> {
+        #[cfg(feature = "embed")]
+        {
+            let multi = self
+                .vectorizer
+                .as_ref()
+                .ok_or_else(|| Status::failed_precondition("Embedding engine disabled"))?;
+            let vectors = multi
+                .vectorize_for(vec![text.to_string()], metric)
+                .await
+                .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+            self.manager
+                .meter
+                .record_embedded_tokens(user_id, metering::approx_tokens(text));
+            vectors
+                .into_iter()
+                .next()
+                .ok_or_else(|| Status::internal("Empty vector result"))
+        }
+        #[cfg(not(feature = "embed"))]
+        {
+            let _ = (user_id, text, metric);
+            Err(Status::unimplemented(
+                "Embedding feature not compiled; search with a vector",
+            ))
+        }
+    }
+
+    /// The collection holding the parent documents of `name`'s chunks.
+    #[allow(clippy::result_large_err)]
+    async fn parent_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Arc<dyn hyperspace_core::Collection>, Status> {
+        let parent = self
+            .manager
+            .parent_collection(user_id, name)
+            .map_err(error_status)?
+            .ok_or_else(|| {
+                Status::failed_precondition(format!("Collection '{name}' has no parent collection"))
+            })?;
+        self.manager
+            .get(user_id, &parent)
+            .await
+            .ok_or_else(|| self.collection_not_found(user_id, &parent))
+    }
+
+    /// Current role; it can change at runtime when election is enabled.
+    async fn is_follower(&self) -> bool {
+        self.manager.cluster_state.read().await.role == ClusterRole::Follower
+    }
+
+    /// Validates and writes one `BatchInsert`. Returns the collection written
+    /// to and the clock of the write.
+    #[allow(clippy::result_large_err)]
+    async fn write_batch(
+        &self,
+        user_id: &str,
+        mut req: BatchInsertRequest,
+    ) -> Result<(Arc<dyn hyperspace_core::Collection>, u64), Status> {
         let col_name = if req.collection.is_empty() {
             "default".to_string()
         } else {
             req.collection
         };
-        if let Some(col) = self.manager.get(&user_id, &col_name).await {
-            let meta = merge_metadata(
-                req.metadata.into_iter().collect(),
-                req.typed_metadata.into_iter().collect(),
-            );
+
+        if let Some(col) = self.manager.get(user_id, &col_name).await {
+            let mut seen_ids = HashSet::with_capacity(req.vectors.len());
+            for (i, v) in req.vectors.iter().enumerate() {
+                let check = if v.vector.is_empty() && !v.vector_f32.is_empty() {
+                    col.normalization()
+                        .check(&v.vector_f32, col.dimension(), col.metric_name())
+                } else {
+                    col.normalization()
+                        .check(&v.vector, col.dimension(), col.metric_name())
+                };
+                if let Err(violation) = check {
+                    return Err(vector_violation_status(
+                        v.id,
+                        &format!("vectors[{i}].vector"),
+                        &violation,
+                    ));
+                }
+                if !seen_ids.insert(v.id) {
+                    let mut detail = InsertErrorDetail {
+                        id: v.id,
+                        field: format!("vectors[{i}].id"),
+                        message: format!("Duplicate id {} within batch", v.id),
+                        ..Default::default()
+                    };
+                    detail.set_code(InsertErrorCode::DuplicateId);
+                    return Err(detail.into_status());
+                }
+            }
+
+            // Token vectors are validated up front and written after the batch.
+            let tokens = req
+                .vectors
+                .iter_mut()
+                .map(|v| Ok((v.id, token_matrix(v.token_vectors.take())?)))
+                .collect::<hyperspace_core::HyperspaceResult<Vec<_>>>()
+                .map_err(error_status)?;
+
+            // Convert protos to internal types
+            let vectors: Vec<(Vec<f64>, u32, std::collections::HashMap<String, String>)> = req
+                .vectors
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let mut meta =
+                        merge_metadata(v.metadata.into_iter().collect(), v.typed_metadata);
+                    check_metadata_schema(
+                        col.as_ref(),
+                        v.id,
+                        &format!("vectors[{i}].metadata"),
+                        &mut meta,
+                    )?;
+                    let meta = apply_write_mode(
+                        col.as_ref(),
+                        req.write_mode,
+                        v.id,
+                        &format!("vectors[{i}].id"),
+                        meta,
+                    )?;
+                    Ok((
+                        WireVector::new(v.vector, v.vector_f32).into_f64(),
+                        v.id,
+                        meta,
+                    ))
+                })
+                .collect::<Result<_, Status>>()?;
+
             // Tick clock
             let clock = self.manager.tick_cluster_clock().await;
 
@@ -674,28 +1598,225 @@ impl Database for HyperspaceService {
                 _ => hyperspace_core::Durability::Default,
             };
 
-            // id is u32 in proto.
-            if let Err(e) = col
-                .insert(&req.vector, req.id, meta, clock, durability)
-                .await
-            {
-                return Err(Status::internal(e));
+            let count = vectors.len() as u64;
+            let written = if req.delete_ids.is_empty() {
+                col.insert_batch(vectors, clock, durability).await
+            } else {
+                col.apply_batch(req.delete_ids, vectors, clock, durability)
+                    .await
+            };
+            if let Err(e) = written.and_then(|()| {
+                tokens
+                    .into_iter()
+                    .try_for_each(|(id, t)| col.put_token_vectors(id, t))
+            }) {
+                return Err(error_status(e));
             }
-            Ok(Response::new(InsertResponse { success: true }))
+            self.manager.meter.record_vectors_written(user_id, count);
+            Ok((col, clock))
         } else {
-            Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )))
+            Err(self.collection_not_found(user_id, &col_name))
         }
     }
 
-    async fn batch_insert(
-        &self,
-        request: Request<BatchInsertRequest>,
-    ) -> Result<Response<InsertResponse>, Status> {
-        if self.role == "follower" {
+    /// Refuses writes on followers and on a leader fenced off by a newer
+    /// epoch or an expired lease.
+    #[allow(clippy::result_large_err)]
+    async fn check_writable(&self) -> Result<(), Status> {
+        if self.is_follower().await {
             return Err(Status::permission_denied("Followers are read-only"));
         }
+        self.manager
+            .fencing
+            .check_write()
+            .map_err(Status::failed_precondition)
+    }
+}
+
+#[tonic::async_trait]
+impl Database for HyperspaceService {
+    // --- Collection Management ---
+
+    async fn create_collection(
+        &self,
+        request: Request<CreateCollectionRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        if req.name.is_empty() {
+            return Err(Status::invalid_argument("Collection name cannot be empty"));
+        }
+        manager::validate_collection_name(&req.name).map_err(Status::invalid_argument)?;
+        let ivf = ivf_params_from_proto(&req)?;
+        let normalization = normalization_from_proto(&req)?;
+        let schema = metadata_schema_from_proto(&req);
+
+        // Map string metric to internal
+        // Manager accepts string metric.
+        match self
+            .manager
+            .create_collection_with_options(
+                &user_id,
+                &req.name,
+                req.dimension,
+                &req.metric,
+                manager::CollectionOptions {
+                    snapshot: snapshot::SnapshotPolicy {
+                        interval_sec: req.snapshot_interval_sec,
+                        every_ops: req.snapshot_every_ops,
+                        wal_bytes: req.snapshot_wal_bytes,
+                        metadata_interval_sec: req.snapshot_metadata_interval_sec,
+                        history: req.snapshot_history,
+                    },
+                    limits: limits::CollectionLimits {
+                        max_points: req.max_points,
+                        max_storage_bytes: req.max_storage_bytes,
+                    },
+                    info: manager::CollectionInfo {
+                        description: req.description.clone(),
+                        labels: req.labels.into_iter().collect(),
+                    },
+                    schema,
+                    ivf,
+                    normalization,
+                    parent: (!req.parent_collection.is_empty())
+                        .then(|| req.parent_collection.clone()),
+                    ..Default::default()
+                },
+            )
+            .await
+        {
+            Ok(()) => Ok(Response::new(
+                hyperspace_proto::hyperspace::StatusResponse {
+                    status: format!("Collection '{}' created.", req.name),
+                },
+            )),
+            Err(e) => Err(Status::already_exists(e)),
+        }
+    }
+
+    async fn delete_collection(
+        &self,
+        request: Request<DeleteCollectionRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        match self.manager.delete_collection(&user_id, &req.name).await {
+            Ok(()) => Ok(Response::new(
+                hyperspace_proto::hyperspace::StatusResponse {
+                    status: format!("Collection '{}' deleted.", req.name),
+                },
+            )),
+            Err(e) => Err(Status::not_found(e)),
+        }
+    }
+
+    async fn list_collections(
+        &self,
+        request: Request<ListCollectionsRequest>,
+    ) -> Result<Response<ListCollectionsResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let collections = self.manager.list_detailed_in(&user_id, "", &req.labels);
+        Ok(Response::new(ListCollectionsResponse { collections }))
+    }
+
+    async fn get_collection_stats(
+        &self,
+        request: Request<CollectionStatsRequest>,
+    ) -> Result<Response<CollectionStatsResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        if let Some(col) = self.manager.get(&user_id, &req.name).await {
+            let info = self.manager.collection_info(&user_id, &req.name);
+            Ok(Response::new(CollectionStatsResponse {
+                count: col.count() as u64,
+                dimension: col.dimension() as u32,
+                metric: col.metric_name().to_string(),
+                indexing_queue: col.queue_size(),
+                labels: info.labels_map(),
+                description: info.description,
+                read_only: col.is_read_only(),
+                normalization: col.normalization().as_str().to_string(),
+                projection_epsilon: col.normalization().epsilon(),
+                distinct_values: col.metadata_distinct_values(),
+            }))
+        } else {
+            Err(Status::not_found("Collection not found"))
+        }
+    }
+
+    async fn list_namespace(
+        &self,
+        request: Request<NamespaceRequest>,
+    ) -> Result<Response<ListCollectionsResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let collections = self.manager.list_detailed_in(
+            &user_id,
+            &req.namespace,
+            &std::collections::HashMap::new(),
+        );
+        Ok(Response::new(ListCollectionsResponse { collections }))
+    }
+
+    async fn delete_namespace(
+        &self,
+        request: Request<NamespaceRequest>,
+    ) -> Result<Response<DeleteNamespaceResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let deleted = self
+            .manager
+            .delete_namespace(&user_id, &req.namespace)
+            .await
+            .map_err(error_status)?;
+        Ok(Response::new(DeleteNamespaceResponse { deleted }))
+    }
+
+    async fn get_namespace_stats(
+        &self,
+        request: Request<NamespaceRequest>,
+    ) -> Result<Response<NamespaceStatsResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let namespace = req.namespace.trim_end_matches('/');
+        let usage = self.manager.get_namespace_usage(&user_id, namespace);
+
+        let mut children = std::collections::BTreeSet::new();
+        for name in self.manager.list_namespace(&user_id, namespace) {
+            let rest = if namespace.is_empty() {
+                name.as_str()
+            } else {
+                &name[namespace.len() + 1..]
+            };
+            if let Some((child, _)) = rest.split_once('/') {
+                children.insert(if namespace.is_empty() {
+                    child.to_string()
+                } else {
+                    format!("{namespace}/{child}")
+                });
+            }
+        }
+
+        Ok(Response::new(NamespaceStatsResponse {
+            collections: usage.collection_count as u32,
+            count: usage.vector_count as u64,
+            disk_usage_bytes: usage.disk_usage_bytes,
+            children: children.into_iter().collect(),
+        }))
+    }
+
+    // --- Data Plane ---
+
+    async fn insert(
+        &self,
+        request: Request<InsertRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        self.check_writable().await?;
         let user_id = get_user_id(&request);
         let req = request.into_inner();
 
@@ -704,21 +1825,17 @@ impl Database for HyperspaceService {
         } else {
             req.collection
         };
-
         if let Some(col) = self.manager.get(&user_id, &col_name).await {
-            // Convert protos to internal types
-            let vectors: Vec<(Vec<f64>, u32, std::collections::HashMap<String, String>)> = req
-                .vectors
-                .into_iter()
-                .map(|v| {
-                    (
-                        v.vector,
-                        v.id,
-                        merge_metadata(v.metadata.into_iter().collect(), v.typed_metadata),
-                    )
-                })
-                .collect();
-
+            let vector = WireVector::new(req.vector, req.vector_f32);
+            if let Err(v) = vector.check(col.as_ref()) {
+                return Err(vector_violation_status(req.id, "vector", &v));
+            }
+            let mut meta = merge_metadata(
+                req.metadata.into_iter().collect(),
+                req.typed_metadata.into_iter().collect(),
+            );
+            check_metadata_schema(col.as_ref(), req.id, "metadata", &mut meta)?;
+            let meta = apply_write_mode(col.as_ref(), req.write_mode, req.id, "id", meta)?;
             // Tick clock
             let clock = self.manager.tick_cluster_clock().await;
 
@@ -740,17 +1857,99 @@ impl Database for HyperspaceService {
                 _ => hyperspace_core::Durability::Default,
             };
 
-            if let Err(e) = col.insert_batch(vectors, clock, durability).await {
-                return Err(Status::internal(e));
+            let tokens = token_matrix(req.token_vectors).map_err(error_status)?;
+            // id is u32 in proto.
+            let res = match &vector {
+                WireVector::F64(v) => col.insert(v, req.id, meta, clock, durability).await,
+                WireVector::F32(v) => col.insert_f32(v, req.id, meta, clock, durability).await,
+            };
+            if let Err(e) = res.and_then(|()| col.put_token_vectors(req.id, tokens)) {
+                return Err(error_status(e));
             }
-            Ok(Response::new(InsertResponse { success: true }))
+            self.manager.meter.record_vectors_written(&user_id, 1);
+            Ok(Response::new(InsertResponse {
+                success: true,
+                logical_clock: clock,
+            }))
         } else {
-            Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )))
+            Err(self.collection_not_found(&user_id, &col_name))
         }
     }
 
+    async fn batch_insert(
+        &self,
+        request: Request<BatchInsertRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let (_, clock) = self.write_batch(&user_id, request.into_inner()).await?;
+        Ok(Response::new(InsertResponse {
+            success: true,
+            logical_clock: clock,
+        }))
+    }
+
+    type InsertStreamStream = ReceiverStream<Result<InsertStreamAck, Status>>;
+
+    async fn insert_stream(
+        &self,
+        request: Request<Streaming<BatchInsertRequest>>,
+    ) -> Result<Response<Self::InsertStreamStream>, Status> {
+        let user_id = get_user_id(&request);
+        let mut chunks = request.into_inner();
+        let (tx, rx) = mpsc::channel(4);
+        let service = self.clone();
+        let max_queue = insert_stream_max_queue();
+
+        // The next message is read only after the previous one is acked, so
+        // a slow indexer holds the client back through transport flow control.
+        tokio::spawn(async move {
+            let mut sequence = 0;
+            loop {
+                let req = match chunks.message().await {
+                    Ok(Some(req)) => req,
+                    Ok(None) => break,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        break;
+                    }
+                };
+                sequence += 1;
+                let written = req.vectors.len() as u32;
+                let result = match service.check_writable().await {
+                    Ok(()) => service.write_batch(&user_id, req).await,
+                    Err(status) => Err(status),
+                };
+                let ack = match result {
+                    Ok((col, clock)) => {
+                        while col.queue_size() > max_queue && !tx.is_closed() {
+                            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+                        }
+                        Ok(InsertStreamAck {
+                            sequence,
+                            written,
+                            logical_clock: clock,
+                            queue_depth: col.queue_size(),
+                        })
+                    }
+                    // An ack queued just before an error may not reach the
+                    // client, so the error names the message that failed
+                    Err(mut status) => {
+                        status
+                            .metadata_mut()
+                            .insert("x-hyperspace-sequence", sequence.into());
+                        Err(status)
+                    }
+                };
+                let failed = ack.is_err();
+                if tx.send(ack).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
     #[allow(unused_variables)]
     async fn insert_text(
         &self,
@@ -758,9 +1957,7 @@ impl Database for HyperspaceService {
     ) -> Result<Response<InsertResponse>, Status> {
         #[cfg(feature = "embed")]
         {
-            if self.role == "follower" {
-                return Err(Status::permission_denied("Followers are read-only"));
-            }
+            self.check_writable().await?;
             let user_id = get_user_id(&request);
             let req = request.into_inner();
 
@@ -778,10 +1975,12 @@ impl Database for HyperspaceService {
                     "l2".to_string()
                 };
 
+                let tokens = metering::approx_tokens(&req.text);
                 let vectors = multi
                     .vectorize_for(vec![req.text], &metric)
                     .await
                     .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+                self.manager.meter.record_embedded_tokens(&user_id, tokens);
 
                 if vectors.is_empty() {
                     return Err(Status::internal("Empty vector result"));
@@ -795,8 +1994,16 @@ impl Database for HyperspaceService {
                 };
 
                 if let Some(col) = self.manager.get(&user_id, &col_name).await {
-                    let meta: std::collections::HashMap<String, String> =
+                    // The embedding model may not match the collection's dimension.
+                    if let Err(v) =
+                        col.normalization()
+                            .check(&vector, col.dimension(), col.metric_name())
+                    {
+                        return Err(vector_violation_status(req.id, "vector", &v));
+                    }
+                    let mut meta: std::collections::HashMap<String, String> =
                         req.metadata.into_iter().collect();
+                    check_metadata_schema(col.as_ref(), req.id, "metadata", &mut meta)?;
                     let clock = self.manager.tick_cluster_clock().await;
 
                     // Durability mapping
@@ -817,23 +2024,183 @@ impl Database for HyperspaceService {
                         _ => hyperspace_core::Durability::Default,
                     };
 
-                    if let Err(e) = col.insert(&vector, req.id, meta, clock, durability).await {
-                        return Err(Status::internal(e));
-                    }
-                    return Ok(Response::new(InsertResponse { success: true }));
+                    if let Err(e) = col.insert(&vector, req.id, meta, clock, durability).await {
+                        return Err(error_status(e));
+                    }
+                    self.manager.meter.record_vectors_written(&user_id, 1);
+                    return Ok(Response::new(InsertResponse {
+                        success: true,
+                        logical_clock: clock,
+                    }));
+                }
+
+                return Err(self.collection_not_found(&user_id, &col_name));
+            }
+
+            return Err(Status::unimplemented(
+                "Server configured without embedding model",
+            ));
+        }
+        #[cfg(not(feature = "embed"))]
+        return Err(Status::unimplemented("Embedding feature not compiled"));
+    }
+
+    async fn insert_image(
+        &self,
+        request: Request<InsertImageRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        #[cfg(feature = "embed")]
+        {
+            self.check_writable().await?;
+            let user_id = get_user_id(&request);
+            let req = request.into_inner();
+
+            let Some(multi) = self.vectorizer.as_ref().filter(|m| !m.images.is_empty()) else {
+                return Err(Status::unimplemented(
+                    "Server configured without an image embedding model",
+                ));
+            };
+            let col_name = if req.collection.is_empty() {
+                "default".to_string()
+            } else {
+                req.collection
+            };
+            let Some(col) = self.manager.get(&user_id, &col_name).await else {
+                return Err(self.collection_not_found(&user_id, &col_name));
+            };
+
+            let image = hyperspace_embed::image::decode(&req.image)
+                .map_err(|e| Status::invalid_argument(format!("Invalid image: {e}")))?;
+            let vectors = multi
+                .vectorize_images_for(vec![image], col.metric_name())
+                .await
+                .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+            let Some(vector) = vectors.into_iter().next() else {
+                return Err(Status::internal("Empty vector result"));
+            };
+
+            // The image model may not match the collection's dimension.
+            if let Err(v) = col
+                .normalization()
+                .check(&vector, col.dimension(), col.metric_name())
+            {
+                return Err(vector_violation_status(req.id, "vector", &v));
+            }
+            let mut meta: std::collections::HashMap<String, String> =
+                req.metadata.into_iter().collect();
+            check_metadata_schema(col.as_ref(), req.id, "metadata", &mut meta)?;
+            let clock = self.manager.tick_cluster_clock().await;
+
+            let durability = match hyperspace_proto::hyperspace::DurabilityLevel::try_from(
+                req.durability,
+            )
+            .ok()
+            {
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Strict) => {
+                    hyperspace_core::Durability::Strict
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Async) => {
+                    hyperspace_core::Durability::Async
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Batch) => {
+                    hyperspace_core::Durability::Batch
+                }
+                _ => hyperspace_core::Durability::Default,
+            };
+
+            if let Err(e) = col.insert(&vector, req.id, meta, clock, durability).await {
+                return Err(error_status(e));
+            }
+            self.manager.meter.record_vectors_written(&user_id, 1);
+            Ok(Response::new(InsertResponse {
+                success: true,
+                logical_clock: clock,
+            }))
+        }
+        #[cfg(not(feature = "embed"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented("Embedding feature not compiled"))
+        }
+    }
+
+    async fn insert_audio(
+        &self,
+        request: Request<InsertAudioRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        #[cfg(feature = "embed")]
+        {
+            self.check_writable().await?;
+            let user_id = get_user_id(&request);
+            let req = request.into_inner();
+
+            let Some(multi) = self.vectorizer.as_ref().filter(|m| !m.audio.is_empty()) else {
+                return Err(Status::unimplemented(
+                    "Server configured without an audio embedding model",
+                ));
+            };
+            let col_name = if req.collection.is_empty() {
+                "default".to_string()
+            } else {
+                req.collection
+            };
+            let Some(col) = self.manager.get(&user_id, &col_name).await else {
+                return Err(self.collection_not_found(&user_id, &col_name));
+            };
+
+            let clip = hyperspace_embed::audio::decode(&req.audio)
+                .map_err(|e| Status::invalid_argument(format!("Invalid audio: {e}")))?;
+            let vectors = multi
+                .vectorize_audio_for(vec![clip], col.metric_name())
+                .await
+                .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+            let Some(vector) = vectors.into_iter().next() else {
+                return Err(Status::internal("Empty vector result"));
+            };
+
+            // The audio model may not match the collection's dimension.
+            if let Err(v) = col
+                .normalization()
+                .check(&vector, col.dimension(), col.metric_name())
+            {
+                return Err(vector_violation_status(req.id, "vector", &v));
+            }
+            let mut meta: std::collections::HashMap<String, String> =
+                req.metadata.into_iter().collect();
+            check_metadata_schema(col.as_ref(), req.id, "metadata", &mut meta)?;
+            let clock = self.manager.tick_cluster_clock().await;
+
+            let durability = match hyperspace_proto::hyperspace::DurabilityLevel::try_from(
+                req.durability,
+            )
+            .ok()
+            {
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Strict) => {
+                    hyperspace_core::Durability::Strict
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Async) => {
+                    hyperspace_core::Durability::Async
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Batch) => {
+                    hyperspace_core::Durability::Batch
                 }
+                _ => hyperspace_core::Durability::Default,
+            };
 
-                return Err(Status::not_found(format!(
-                    "Collection '{col_name}' not found"
-                )));
+            if let Err(e) = col.insert(&vector, req.id, meta, clock, durability).await {
+                return Err(error_status(e));
             }
-
-            return Err(Status::unimplemented(
-                "Server configured without embedding model",
-            ));
+            self.manager.meter.record_vectors_written(&user_id, 1);
+            Ok(Response::new(InsertResponse {
+                success: true,
+                logical_clock: clock,
+            }))
         }
         #[cfg(not(feature = "embed"))]
-        return Err(Status::unimplemented("Embedding feature not compiled"));
+        {
+            let _ = request;
+            Err(Status::unimplemented("Embedding feature not compiled"))
+        }
     }
 
     async fn vectorize(
@@ -842,12 +2209,15 @@ impl Database for HyperspaceService {
     ) -> Result<Response<VectorizeResponse>, Status> {
         #[cfg(feature = "embed")]
         {
+            let user_id = get_user_id(&request);
             let req = request.into_inner();
             if let Some(multi) = &self.vectorizer {
+                let tokens = metering::approx_tokens(&req.text);
                 let vectors = multi
                     .vectorize_for(vec![req.text], &req.metric)
                     .await
                     .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+                self.manager.meter.record_embedded_tokens(&user_id, tokens);
                 if vectors.is_empty() {
                     return Err(Status::internal("Empty vector result"));
                 }
@@ -888,10 +2258,12 @@ impl Database for HyperspaceService {
                     "l2".to_string()
                 };
 
+                let tokens = metering::approx_tokens(&req.text);
                 let vectors = multi
                     .vectorize_for(vec![req.text], &metric)
                     .await
                     .map_err(|e| Status::internal(format!("Embedding failed: {e}")))?;
+                self.manager.meter.record_embedded_tokens(&user_id, tokens);
 
                 if vectors.is_empty() {
                     return Err(Status::internal("Empty vector result"));
@@ -937,6 +2309,11 @@ impl Database for HyperspaceService {
                                     radius: b.radius,
                                 });
                             }
+                            hyperspace_proto::hyperspace::filter::Condition::PhraseMatch(p) => {
+                                complex_filters.push(hyperspace_core::FilterExpr::PhraseMatch {
+                                    phrase: p.phrase,
+                                });
+                            }
                         }
                     }
                 }
@@ -949,6 +2326,9 @@ impl Database for HyperspaceService {
                     use_wasserstein: false,
                     bm25_options: req.bm25_options.as_ref().map(parse_bm25_options),
                     fusion_method: req.bm25_options.and_then(|opts| opts.fusion_method),
+                    text_match: hyperspace_core::TextMatch::Any,
+                    trace: None,
+                    num_probes: 0,
                 };
 
                 if let Some(col) = self.manager.get(&user_id, &col_name).await {
@@ -967,17 +2347,22 @@ impl Database for HyperspaceService {
                                         distance: dist,
                                         metadata,
                                         typed_metadata,
+                                        vector: Vec::new(),
                                     }
                                 })
                                 .collect();
-                            Ok(Response::new(SearchResponse { results: output }))
+                            self.manager.meter.record_searches(&user_id, 1);
+                            Ok(Response::new(SearchResponse {
+                                results: output,
+                                experiment_arm: String::new(),
+                                explain: None,
+                                parents: Vec::new(),
+                            }))
                         }
-                        Err(e) => Err(Status::internal(e)),
+                        Err(e) => Err(error_status(e)),
                     }
                 } else {
-                    Err(Status::not_found(format!(
-                        "Collection '{col_name}' not found"
-                    )))
+                    Err(self.collection_not_found(&user_id, &col_name))
                 }
             } else {
                 Err(Status::failed_precondition("Embedding engine disabled"))
@@ -994,6 +2379,7 @@ impl Database for HyperspaceService {
         &self,
         request: Request<DeleteRequest>,
     ) -> Result<Response<DeleteResponse>, Status> {
+        self.check_writable().await?;
         let user_id = get_user_id(&request);
         let req = request.into_inner();
         let col_name = if req.collection.is_empty() {
@@ -1003,26 +2389,91 @@ impl Database for HyperspaceService {
         };
 
         if let Some(col) = self.manager.get(&user_id, &col_name).await {
-            if let Err(e) = col.delete(req.id) {
-                return Err(Status::internal(e));
+            let clock = self.manager.tick_cluster_clock().await;
+            if let Err(e) = col.delete(req.id, clock).await {
+                return Err(error_status(e));
             }
-            if self.replication_tx.receiver_count() > 0 {
-                let clock = self.manager.tick_cluster_clock().await;
+            if self.replication_tx.is_active() {
                 let log = ReplicationLog {
                     logical_clock: clock,
                     origin_node_id: self.manager.cluster_state.read().await.node_id.clone(),
-                    collection: col_name.clone(),
+                    // Followers resolve the internal name, as for inserts
+                    collection: col.name().to_string(),
                     operation: Some(replication_log::Operation::Delete(
                         hyperspace_proto::hyperspace::DeleteOp { id: req.id },
                     )),
+                    ..Default::default()
                 };
-                let _ = self.replication_tx.send(log);
+                self.replication_tx.publish(log);
             }
             Ok(Response::new(DeleteResponse { success: true }))
         } else {
-            Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )))
+            Err(self.collection_not_found(&user_id, &col_name))
+        }
+    }
+
+    async fn update_vector_delta(
+        &self,
+        request: Request<UpdateVectorDeltaRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let col_name = if req.collection.is_empty() {
+            "default".to_string()
+        } else {
+            req.collection
+        };
+
+        if let Some(col) = self.manager.get(&user_id, &col_name).await {
+            let dimension = col.dimension();
+            if let Some(i) = req.indices.iter().position(|&d| d as usize >= dimension) {
+                let mut detail = InsertErrorDetail {
+                    id: req.id,
+                    field: format!("indices[{i}]"),
+                    message: format!(
+                        "Index {} out of range for dimension {dimension}",
+                        req.indices[i]
+                    ),
+                    expected_dimension: dimension as u32,
+                    ..Default::default()
+                };
+                detail.set_code(InsertErrorCode::DimensionMismatch);
+                return Err(detail.into_status());
+            }
+            let clock = self.manager.tick_cluster_clock().await;
+
+            // Durability mapping
+            let durability = match hyperspace_proto::hyperspace::DurabilityLevel::try_from(
+                req.durability,
+            )
+            .ok()
+            {
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Strict) => {
+                    hyperspace_core::Durability::Strict
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Async) => {
+                    hyperspace_core::Durability::Async
+                }
+                Some(hyperspace_proto::hyperspace::DurabilityLevel::Batch) => {
+                    hyperspace_core::Durability::Batch
+                }
+                _ => hyperspace_core::Durability::Default,
+            };
+
+            if let Err(e) = col
+                .update_vector_delta(req.id, &req.indices, &req.values, clock, durability)
+                .await
+            {
+                return Err(error_status(e));
+            }
+            self.manager.meter.record_vectors_written(&user_id, 1);
+            Ok(Response::new(InsertResponse {
+                success: true,
+                logical_clock: clock,
+            }))
+        } else {
+            Err(self.collection_not_found(&user_id, &col_name))
         }
     }
 
@@ -1031,36 +2482,102 @@ impl Database for HyperspaceService {
         request: Request<SearchRequest>,
     ) -> Result<Response<SearchResponse>, Status> {
         let user_id = get_user_id(&request);
-        let (col_name, vector, exact_filter, complex_filters, params) =
-            build_filters(request.into_inner());
-
-        if let Some(col) = self.manager.get(&user_id, &col_name).await {
-            match col
-                .search(&vector, &exact_filter, &complex_filters, &params)
+        let mut req = request.into_inner();
+        let mut projection = ResultProjection::new(&req);
+        projection.joins = self.resolve_joins(&user_id, &req.joins).await?;
+        let session_key = std::mem::take(&mut req.session_key);
+        let filter_expr = req.filter_expr.clone();
+        let parent_search = req.parents.take();
+        let snapshot = snapshot_history::SnapshotRef::from_request(req.snapshot_id, req.as_of);
+        let top_k = req.top_k as usize;
+        let (col_name, mut vector, exact_filter, complex_filters, mut params) = build_filters(req)?;
+
+        let col = match snapshot {
+            Some(snapshot) => Some(
+                self.manager
+                    .historical(&user_id, &col_name, snapshot)
+                    .await
+                    .map_err(error_status)?,
+            ),
+            None => self.manager.get(&user_id, &col_name).await,
+        };
+        if let Some(col) = col {
+            let parent = match parent_search {
+                Some(search) => {
+                    let chunks_per_parent = match search.chunks_per_parent {
+                        0 => parents::DEFAULT_CHUNKS_PER_PARENT,
+                        n => n as usize,
+                    };
+                    let parent_col = self.parent_collection(&user_id, &col_name).await?;
+                    let fetch = parents::fetch_size(top_k, chunks_per_parent);
+                    params.top_k = params.top_k.max(fetch);
+                    params.ef_search = params.ef_search.max(params.top_k);
+                    if let Some((_, keep)) = &mut vector.rerank {
+                        *keep = (*keep).max(fetch);
+                    }
+                    Some((parent_col, chunks_per_parent))
+                }
+                None => None,
+            };
+            let experiment = if session_key.is_empty() {
+                None
+            } else {
+                self.manager
+                    .experiment(&user_id, &col_name)
+                    .map_err(error_status)?
+                    .map(|running| {
+                        let arm = running.experiment.assign(&session_key);
+                        vector.apply_arm(running.experiment.arm(arm), &mut params);
+                        (running, arm)
+                    })
+            };
+            let started = std::time::Instant::now();
+            match vector
+                .search_logged(
+                    &self.manager.slow_queries,
+                    &user_id,
+                    &col_name,
+                    filter_expr.as_deref(),
+                    &*col,
+                    &exact_filter,
+                    &complex_filters,
+                    &params,
+                )
                 .await
             {
-                Ok(res) => {
-                    let output = res
-                        .into_iter()
-                        .map(|(id, dist, meta)| {
-                            let typed_metadata = extract_typed_metadata(&meta);
-                            let metadata = strip_internal_metadata(&meta);
-                            SearchResult {
-                                id,
-                                distance: dist,
-                                metadata,
-                                typed_metadata,
-                            }
-                        })
-                        .collect();
-                    Ok(Response::new(SearchResponse { results: output }))
+                Ok((res, explain)) => {
+                    let experiment_arm = match experiment {
+                        Some((running, arm)) => {
+                            running.record(arm, &session_key, started.elapsed(), &res);
+                            arm.as_str().to_string()
+                        }
+                        None => String::new(),
+                    };
+                    let (output, parents) = match &parent {
+                        Some((parent_col, chunks_per_parent)) => (
+                            Vec::new(),
+                            projection.parents(
+                                &*col,
+                                &**parent_col,
+                                res,
+                                top_k,
+                                *chunks_per_parent,
+                            ),
+                        ),
+                        None => (projection.results(&*col, res), Vec::new()),
+                    };
+                    self.manager.meter.record_searches(&user_id, 1);
+                    Ok(Response::new(SearchResponse {
+                        results: output,
+                        experiment_arm,
+                        explain,
+                        parents,
+                    }))
                 }
-                Err(e) => Err(Status::internal(e)),
+                Err(e) => Err(error_status(e)),
             }
         } else {
-            Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )))
+            Err(self.collection_not_found(&user_id, &col_name))
         }
     }
 
@@ -1075,30 +2592,40 @@ impl Database for HyperspaceService {
         if inner_concurrency <= 1 {
             let mut responses = Vec::with_capacity(req.searches.len());
             for search_req in req.searches {
+                let mut projection = ResultProjection::new(&search_req);
+                projection.joins = self.resolve_joins(&user_id, &search_req.joins).await?;
+                let filter_expr = search_req.filter_expr.clone();
                 let (col_name, vector, exact_filter, complex_filters, params) =
-                    build_filters(search_req);
-                let col = self.manager.get(&user_id, &col_name).await.ok_or_else(|| {
-                    Status::not_found(format!("Collection '{col_name}' not found"))
-                })?;
-                let res = col
-                    .search(&vector, &exact_filter, &complex_filters, &params)
+                    build_filters(search_req)?;
+                let col = self
+                    .manager
+                    .get(&user_id, &col_name)
                     .await
-                    .map_err(Status::internal)?;
-                let results = res
-                    .into_iter()
-                    .map(|(id, dist, meta)| {
-                        let typed_metadata = extract_typed_metadata(&meta);
-                        let metadata = strip_internal_metadata(&meta);
-                        SearchResult {
-                            id,
-                            distance: dist,
-                            metadata,
-                            typed_metadata,
-                        }
-                    })
-                    .collect();
-                responses.push(SearchResponse { results });
+                    .ok_or_else(|| self.collection_not_found(&user_id, &col_name))?;
+                let (res, explain) = vector
+                    .search_logged(
+                        &self.manager.slow_queries,
+                        &user_id,
+                        &col_name,
+                        filter_expr.as_deref(),
+                        &*col,
+                        &exact_filter,
+                        &complex_filters,
+                        &params,
+                    )
+                    .await
+                    .map_err(error_status)?;
+                let results = projection.results(&*col, res);
+                responses.push(SearchResponse {
+                    results,
+                    experiment_arm: String::new(),
+                    explain,
+                    parents: Vec::new(),
+                });
             }
+            self.manager
+                .meter
+                .record_searches(&user_id, responses.len() as u64);
             return Ok(Response::new(BatchSearchResponse { responses }));
         }
 
@@ -1106,38 +2633,48 @@ impl Database for HyperspaceService {
         let semaphore = Arc::new(tokio::sync::Semaphore::new(inner_concurrency));
         let mut tasks = tokio::task::JoinSet::new();
         for (idx, search_req) in req.searches.into_iter().enumerate() {
+            let mut projection = ResultProjection::new(&search_req);
+            projection.joins = self.resolve_joins(&user_id, &search_req.joins).await?;
+            let filter_expr = search_req.filter_expr.clone();
             let (col_name, vector, exact_filter, complex_filters, params) =
-                build_filters(search_req);
-            let col =
-                self.manager.get(&user_id, &col_name).await.ok_or_else(|| {
-                    Status::not_found(format!("Collection '{col_name}' not found"))
-                })?;
+                build_filters(search_req)?;
+            let col = self
+                .manager
+                .get(&user_id, &col_name)
+                .await
+                .ok_or_else(|| self.collection_not_found(&user_id, &col_name))?;
             let permit = semaphore
                 .clone()
                 .acquire_owned()
                 .await
                 .map_err(|e| Status::internal(format!("search_batch semaphore error: {e}")))?;
+            let (slow_queries, user_id) = (self.manager.slow_queries.clone(), user_id.clone());
             tasks.spawn(async move {
                 let _permit = permit;
-                let res = col
-                    .search(&vector, &exact_filter, &complex_filters, &params)
+                let (res, explain) = vector
+                    .search_logged(
+                        &slow_queries,
+                        &user_id,
+                        &col_name,
+                        filter_expr.as_deref(),
+                        &*col,
+                        &exact_filter,
+                        &complex_filters,
+                        &params,
+                    )
                     .await
-                    .map_err(Status::internal)?;
-
-                let results = res
-                    .into_iter()
-                    .map(|(id, dist, meta)| {
-                        let typed_metadata = extract_typed_metadata(&meta);
-                        let metadata = strip_internal_metadata(&meta);
-                        SearchResult {
-                            id,
-                            distance: dist,
-                            metadata,
-                            typed_metadata,
-                        }
-                    })
-                    .collect();
-                Ok::<(usize, SearchResponse), Status>((idx, SearchResponse { results }))
+                    .map_err(error_status)?;
+
+                let results = projection.results(&*col, res);
+                Ok::<(usize, SearchResponse), Status>((
+                    idx,
+                    SearchResponse {
+                        results,
+                        experiment_arm: String::new(),
+                        explain,
+                        parents: Vec::new(),
+                    },
+                ))
             });
         }
 
@@ -1156,6 +2693,9 @@ impl Database for HyperspaceService {
             responses.push(response);
         }
 
+        self.manager
+            .meter
+            .record_searches(&user_id, responses.len() as u64);
         Ok(Response::new(BatchSearchResponse { responses }))
     }
 
@@ -1165,15 +2705,18 @@ impl Database for HyperspaceService {
     ) -> Result<Response<SearchMultiCollectionResponse>, Status> {
         let user_id = get_user_id(&request);
         let req = request.into_inner();
+        let vector = WireVector::new(req.vector, req.vector_f32);
         let inner_concurrency = search_batch_inner_concurrency();
 
         let mut responses = std::collections::HashMap::new();
 
         if inner_concurrency <= 1 {
             for col_name in req.collections {
-                let col = self.manager.get(&user_id, &col_name).await.ok_or_else(|| {
-                    Status::not_found(format!("Collection '{col_name}' not found"))
-                })?;
+                let col = self
+                    .manager
+                    .get(&user_id, &col_name)
+                    .await
+                    .ok_or_else(|| self.collection_not_found(&user_id, &col_name))?;
                 let params = hyperspace_core::SearchParams {
                     top_k: req.top_k as usize,
                     ef_search: default_ef_search(),
@@ -1182,13 +2725,16 @@ impl Database for HyperspaceService {
                     use_wasserstein: false,
                     bm25_options: None,
                     fusion_method: None,
+                    text_match: hyperspace_core::TextMatch::Any,
+                    trace: None,
+                    num_probes: 0,
                 };
                 let exact_filter = std::collections::HashMap::new();
                 let complex_filters = Vec::new();
-                let res = col
-                    .search(&req.vector, &exact_filter, &complex_filters, &params)
+                let res = vector
+                    .search(col.as_ref(), &exact_filter, &complex_filters, &params)
                     .await
-                    .map_err(Status::internal)?;
+                    .map_err(error_status)?;
                 let results = res
                     .into_iter()
                     .map(|(id, dist, meta)| {
@@ -1199,71 +2745,433 @@ impl Database for HyperspaceService {
                             distance: dist,
                             metadata,
                             typed_metadata,
+                            vector: Vec::new(),
                         }
                     })
                     .collect();
-                responses.insert(col_name, SearchResponse { results });
+                responses.insert(
+                    col_name,
+                    SearchResponse {
+                        results,
+                        experiment_arm: String::new(),
+                        explain: None,
+                        parents: Vec::new(),
+                    },
+                );
             }
+            self.manager
+                .meter
+                .record_searches(&user_id, responses.len() as u64);
             return Ok(Response::new(SearchMultiCollectionResponse { responses }));
         }
 
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(inner_concurrency));
-        let mut tasks = tokio::task::JoinSet::new();
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(inner_concurrency));
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for col_name in req.collections {
+            let col = self
+                .manager
+                .get(&user_id, &col_name)
+                .await
+                .ok_or_else(|| self.collection_not_found(&user_id, &col_name))?;
+            let vector = vector.clone();
+            let top_k = req.top_k;
+            let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
+                Status::internal(format!("search_multi_collection semaphore error: {e}"))
+            })?;
+
+            tasks.spawn(async move {
+                let _permit = permit;
+                let params = hyperspace_core::SearchParams {
+                    top_k: top_k as usize,
+                    ef_search: default_ef_search(),
+                    hybrid_query: None,
+                    hybrid_alpha: None,
+                    use_wasserstein: false,
+                    bm25_options: None,
+                    fusion_method: None,
+                    text_match: hyperspace_core::TextMatch::Any,
+                    trace: None,
+                    num_probes: 0,
+                };
+                let exact_filter = std::collections::HashMap::new();
+                let complex_filters = Vec::new();
+                let res = vector
+                    .search(col.as_ref(), &exact_filter, &complex_filters, &params)
+                    .await
+                    .map_err(error_status)?;
+                let results = res
+                    .into_iter()
+                    .map(|(id, dist, meta)| {
+                        let typed_metadata = extract_typed_metadata(&meta);
+                        let metadata = strip_internal_metadata(&meta);
+                        SearchResult {
+                            id,
+                            distance: dist,
+                            metadata,
+                            typed_metadata,
+                            vector: Vec::new(),
+                        }
+                    })
+                    .collect();
+                Ok::<_, Status>((
+                    col_name,
+                    SearchResponse {
+                        results,
+                        experiment_arm: String::new(),
+                        explain: None,
+                        parents: Vec::new(),
+                    },
+                ))
+            });
+        }
+
+        while let Some(join_res) = tasks.join_next().await {
+            let res = join_res.map_err(|e| {
+                Status::internal(format!("search_multi_collection join error: {e}"))
+            })?;
+            let (col_name, response) = res?;
+            responses.insert(col_name, response);
+        }
+
+        self.manager
+            .meter
+            .record_searches(&user_id, responses.len() as u64);
+        Ok(Response::new(SearchMultiCollectionResponse { responses }))
+    }
+
+    async fn multi_search(
+        &self,
+        request: Request<MultiSearchRequest>,
+    ) -> Result<Response<MultiSearchResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        if req.collections.is_empty() {
+            return Err(Status::invalid_argument("No collections to search"));
+        }
+        if req.collections.len() > query_fusion::MAX_COLLECTIONS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} collections per search, got {}",
+                query_fusion::MAX_COLLECTIONS,
+                req.collections.len()
+            )));
+        }
+        // Fused ranking runs every collection on one f64 query, so an f32
+        // one is widened once here
+        let query = WireVector::new(req.vector, req.vector_f32).into_f64();
+        if query.is_empty() && req.text.is_empty() {
+            return Err(Status::invalid_argument(
+                "Either vector or text is required",
+            ));
+        }
+
+        let mut collections = Vec::with_capacity(req.collections.len());
+        for name in &req.collections {
+            let col = self
+                .manager
+                .get(&user_id, name)
+                .await
+                .ok_or_else(|| self.collection_not_found(&user_id, name))?;
+            collections.push(col);
+        }
+
+        // Collections of one metric share an embedding model, so embed once
+        // per metric.
+        let text = (!req.text.is_empty()).then_some(req.text);
+        let mut embedded: std::collections::HashMap<&'static str, Vec<f64>> =
+            std::collections::HashMap::new();
+        if query.is_empty() {
+            for col in collections.iter().filter(|col| col.dimension() > 0) {
+                let metric = col.metric_name();
+                if !embedded.contains_key(metric) {
+                    let vector = self
+                        .embed_query(&user_id, text.as_deref().unwrap_or_default(), metric)
+                        .await?;
+                    embedded.insert(metric, vector);
+                }
+            }
+        }
+
+        let exact_filter = req.filter.into_iter().collect();
+        let complex_filters = complex_filters(req.filters, None)?;
+        let searches = collections.iter().map(|col| {
+            let text_only = col.dimension() == 0;
+            let vector: &[f64] = if text_only {
+                &[]
+            } else if query.is_empty() {
+                &embedded[col.metric_name()]
+            } else {
+                &query
+            };
+            let params = hyperspace_core::SearchParams {
+                top_k: req.top_k as usize,
+                ef_search: default_ef_search(),
+                hybrid_query: (text_only || req.hybrid_alpha.is_some())
+                    .then(|| text.clone())
+                    .flatten(),
+                hybrid_alpha: req.hybrid_alpha,
+                use_wasserstein: false,
+                bm25_options: None,
+                fusion_method: None,
+                text_match: hyperspace_core::TextMatch::Any,
+                trace: None,
+                num_probes: 0,
+            };
+            let (exact_filter, complex_filters) = (&exact_filter, &complex_filters);
+            async move {
+                col.search(vector, exact_filter, complex_filters, &params)
+                    .await
+            }
+        });
+        let lists = futures::future::try_join_all(searches)
+            .await
+            .map_err(error_status)?;
+        self.manager
+            .meter
+            .record_searches(&user_id, lists.len() as u64);
+
+        let dedupe_key = (!req.dedupe_key.is_empty()).then_some(req.dedupe_key.as_str());
+        let results = query_fusion::fuse_collections(
+            req.collections.into_iter().zip(lists).collect(),
+            dedupe_key,
+            req.top_k as usize,
+        )
+        .into_iter()
+        .map(|(collection, (id, distance, meta))| MultiSearchHit {
+            collection,
+            result: Some(SearchResult {
+                id,
+                distance,
+                typed_metadata: extract_typed_metadata(&meta),
+                metadata: strip_internal_metadata(&meta),
+                vector: Vec::new(),
+            }),
+        })
+        .collect();
+        Ok(Response::new(MultiSearchResponse { results }))
+    }
+
+    async fn put_query_template(
+        &self,
+        request: Request<PutQueryTemplateRequest>,
+    ) -> Result<Response<PutQueryTemplateResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let template = req
+            .template
+            .map(query_template_from_proto)
+            .ok_or_else(|| Status::invalid_argument("Missing template"))?;
+        let version = self
+            .manager
+            .put_query_template(&user_id, &req.collection, &req.name, template)
+            .map_err(error_status)?;
+        Ok(Response::new(PutQueryTemplateResponse { version }))
+    }
+
+    async fn run_query_template(
+        &self,
+        request: Request<RunQueryTemplateRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let template = self
+            .manager
+            .get_query_template(&user_id, &req.collection, &req.name)
+            .map_err(error_status)?;
+        let col = self
+            .manager
+            .get(&user_id, &req.collection)
+            .await
+            .ok_or_else(|| {
+                Status::not_found(format!("Collection '{}' not found", req.collection))
+            })?;
+        let top_k = (req.top_k > 0).then_some(req.top_k as usize);
+        let vector = WireVector::new(req.vector, req.vector_f32).into_f64();
+        let res = query_templates::execute(
+            &*col,
+            &template,
+            &vector,
+            &req.params,
+            top_k,
+            default_ef_search(),
+        )
+        .await
+        .map_err(error_status)?;
+        let results = res
+            .into_iter()
+            .map(|(id, dist, meta)| SearchResult {
+                id,
+                distance: dist,
+                typed_metadata: extract_typed_metadata(&meta),
+                metadata: strip_internal_metadata(&meta),
+                vector: Vec::new(),
+            })
+            .collect();
+        self.manager.meter.record_searches(&user_id, 1);
+        Ok(Response::new(SearchResponse {
+            results,
+            experiment_arm: String::new(),
+            explain: None,
+            parents: Vec::new(),
+        }))
+    }
+
+    async fn list_query_templates(
+        &self,
+        request: Request<ListQueryTemplatesRequest>,
+    ) -> Result<Response<ListQueryTemplatesResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let templates = self
+            .manager
+            .list_query_templates(&user_id, &req.collection)
+            .map_err(error_status)?
+            .into_iter()
+            .map(|(name, t)| (name, query_template_to_proto(t)))
+            .collect();
+        Ok(Response::new(ListQueryTemplatesResponse { templates }))
+    }
+
+    async fn delete_query_template(
+        &self,
+        request: Request<DeleteQueryTemplateRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let existed = self
+            .manager
+            .delete_query_template(&user_id, &req.collection, &req.name)
+            .map_err(error_status)?;
+        if !existed {
+            return Err(Status::not_found(format!("Query '{}' not found", req.name)));
+        }
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: format!("Query '{}' deleted", req.name),
+            },
+        ))
+    }
+
+    async fn put_experiment(
+        &self,
+        request: Request<PutExperimentRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let experiment = req
+            .experiment
+            .ok_or_else(|| Status::invalid_argument("Missing experiment"))?;
+        let name = experiment.name.clone();
+        let experiment = experiments::Experiment {
+            name: experiment.name,
+            arm_a: search_arm_from_proto(&experiment.arm_a.unwrap_or_default()),
+            arm_b: search_arm_from_proto(&experiment.arm_b.unwrap_or_default()),
+            arm_b_fraction: experiment.arm_b_fraction,
+        };
+        self.manager
+            .put_experiment(&user_id, &req.collection, experiment)
+            .map_err(error_status)?;
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: format!("Experiment '{name}' running"),
+            },
+        ))
+    }
 
-        for col_name in req.collections {
-            let col =
-                self.manager.get(&user_id, &col_name).await.ok_or_else(|| {
-                    Status::not_found(format!("Collection '{col_name}' not found"))
-                })?;
-            let vector = req.vector.clone();
-            let top_k = req.top_k;
-            let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-                Status::internal(format!("search_multi_collection semaphore error: {e}"))
-            })?;
+    async fn get_experiment(
+        &self,
+        request: Request<GetExperimentRequest>,
+    ) -> Result<Response<GetExperimentResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let running = self
+            .manager
+            .experiment(&user_id, &req.collection)
+            .map_err(error_status)?
+            .ok_or_else(|| Status::not_found(format!("No experiment on '{}'", req.collection)))?;
+        let stats = [experiments::Arm::A, experiments::Arm::B]
+            .into_iter()
+            .zip(running.stats())
+            .map(|(arm, s)| {
+                let per_query = |total: f64, n: u64| if n == 0 { 0.0 } else { total / n as f64 };
+                ExperimentArmStats {
+                    arm: arm.as_str().to_string(),
+                    queries: s.queries,
+                    empty_results: s.empty_results,
+                    mean_latency_ms: per_query(s.total_latency.as_secs_f64() * 1000.0, s.queries),
+                    mean_top_distance: per_query(s.total_top_distance, s.queries - s.empty_results),
+                }
+            })
+            .collect();
+        let e = &running.experiment;
+        Ok(Response::new(GetExperimentResponse {
+            experiment: Some(hyperspace_proto::hyperspace::Experiment {
+                name: e.name.clone(),
+                arm_a: Some(search_arm_to_proto(&e.arm_a)),
+                arm_b: Some(search_arm_to_proto(&e.arm_b)),
+                arm_b_fraction: e.arm_b_fraction,
+            }),
+            stats,
+        }))
+    }
 
-            tasks.spawn(async move {
-                let _permit = permit;
-                let params = hyperspace_core::SearchParams {
-                    top_k: top_k as usize,
-                    ef_search: default_ef_search(),
-                    hybrid_query: None,
-                    hybrid_alpha: None,
-                    use_wasserstein: false,
-                    bm25_options: None,
-                    fusion_method: None,
-                };
-                let exact_filter = std::collections::HashMap::new();
-                let complex_filters = Vec::new();
-                let res = col
-                    .search(&vector, &exact_filter, &complex_filters, &params)
-                    .await
-                    .map_err(Status::internal)?;
-                let results = res
-                    .into_iter()
-                    .map(|(id, dist, meta)| {
-                        let typed_metadata = extract_typed_metadata(&meta);
-                        let metadata = strip_internal_metadata(&meta);
-                        SearchResult {
-                            id,
-                            distance: dist,
-                            metadata,
-                            typed_metadata,
-                        }
-                    })
-                    .collect();
-                Ok::<_, Status>((col_name, SearchResponse { results }))
-            });
+    async fn delete_experiment(
+        &self,
+        request: Request<DeleteExperimentRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let existed = self
+            .manager
+            .delete_experiment(&user_id, &req.collection)
+            .map_err(error_status)?;
+        if !existed {
+            return Err(Status::not_found(format!(
+                "No experiment on '{}'",
+                req.collection
+            )));
         }
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: format!("Experiment on '{}' stopped", req.collection),
+            },
+        ))
+    }
 
-        while let Some(join_res) = tasks.join_next().await {
-            let res = join_res.map_err(|e| {
-                Status::internal(format!("search_multi_collection join error: {e}"))
-            })?;
-            let (col_name, response) = res?;
-            responses.insert(col_name, response);
-        }
+    async fn apply_collection_spec(
+        &self,
+        request: Request<ApplyCollectionSpecRequest>,
+    ) -> Result<Response<ApplyCollectionSpecResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let spec = req
+            .spec
+            .ok_or_else(|| Status::invalid_argument("spec is required"))?;
+        let outcome = self
+            .manager
+            .apply_collection_spec(&user_id, collection_spec_from_proto(spec), req.dry_run)
+            .await
+            .map_err(error_status)?;
+        Ok(Response::new(ApplyCollectionSpecResponse {
+            created: outcome.created,
+            changes: outcome.changes,
+        }))
+    }
 
-        Ok(Response::new(SearchMultiCollectionResponse { responses }))
+    async fn list_collection_specs(
+        &self,
+        request: Request<ListCollectionSpecsRequest>,
+    ) -> Result<Response<ListCollectionSpecsResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let specs = self
+            .manager
+            .collection_specs(&user_id, &req.namespace)
+            .into_iter()
+            .map(collection_spec_to_proto)
+            .collect();
+        Ok(Response::new(ListCollectionSpecsResponse { specs }))
     }
 
     async fn get_node(
@@ -1279,9 +3187,7 @@ impl Database for HyperspaceService {
         };
         let layer = req.layer as usize;
         let Some(col) = self.manager.get(&user_id, &col_name).await else {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         };
         let node = build_graph_node(&col, req.id, layer);
         Ok(Response::new(node))
@@ -1306,14 +3212,12 @@ impl Database for HyperspaceService {
         };
         let offset = req.offset as usize;
         let Some(col) = self.manager.get(&user_id, &col_name).await else {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         };
         let fetch_limit = limit.saturating_add(offset);
         let mut ids = col
             .graph_neighbors(req.id, layer, fetch_limit)
-            .map_err(Status::invalid_argument)?;
+            .map_err(error_status)?;
         if offset > 0 {
             ids = ids.into_iter().skip(offset).collect();
         }
@@ -1322,7 +3226,7 @@ impl Database for HyperspaceService {
         }
         let edge_weights = col
             .graph_neighbor_distances(req.id, &ids)
-            .map_err(Status::internal)?;
+            .map_err(error_status)?;
         let neighbors = ids
             .into_iter()
             .map(|id| build_graph_node(&col, id, layer))
@@ -1351,16 +3255,14 @@ impl Database for HyperspaceService {
             req.limit as usize
         };
         let Some(col) = self.manager.get(&user_id, &col_name).await else {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         };
         let upper_layer = layer.saturating_add(1);
         let (ids, resolved_layer) = match col.graph_neighbors(req.id, upper_layer, limit) {
             Ok(ids) => (ids, upper_layer),
             Err(_) => (
                 col.graph_neighbors(req.id, layer, limit)
-                    .map_err(Status::invalid_argument)?,
+                    .map_err(error_status)?,
                 layer,
             ),
         };
@@ -1396,13 +3298,11 @@ impl Database for HyperspaceService {
         let (exact_filter, complex_filters) =
             parse_graph_filters(req.filter.into_iter().collect(), req.filters);
         let Some(col) = self.manager.get(&user_id, &col_name).await else {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         };
         let mut ids = col
             .graph_traverse(req.start_id, layer, max_depth, max_nodes)
-            .map_err(Status::invalid_argument)?;
+            .map_err(error_status)?;
         if !exact_filter.is_empty() || !complex_filters.is_empty() {
             ids.retain(|id| {
                 let meta = col.metadata_by_id(*id);
@@ -1444,13 +3344,11 @@ impl Database for HyperspaceService {
             req.max_nodes as usize
         };
         let Some(col) = self.manager.get(&user_id, &col_name).await else {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         };
         let clusters = col
             .graph_clusters(layer, min_cluster_size, max_clusters, max_nodes)
-            .map_err(Status::internal)?
+            .map_err(error_status)?
             .into_iter()
             .map(|node_ids| GraphCluster { node_ids })
             .collect();
@@ -1469,20 +3367,11 @@ impl Database for HyperspaceService {
 
         tokio::spawn(async move {
             loop {
-                // Aggregate stats
-                // Use list_all for global stats
-                let collections = manager.list_all();
-                let mut total_count = 0;
-
-                for name in &collections {
-                    if let Some(c) = manager.get_internal(name).await {
-                        total_count += c.count();
-                    }
-                }
-
+                // Collections are counted on disk; vectors only for resident
+                // collections so idle ones are not woken every second.
                 let stats = SystemStats {
-                    total_collections: collections.len() as u64,
-                    total_vectors: total_count as u64,
+                    total_collections: manager.list_all().len() as u64,
+                    total_vectors: manager.total_vector_count() as u64,
                     total_memory_mb: 0.0, // TODO
                     qps: 0.0,             // TODO
                 };
@@ -1496,6 +3385,52 @@ impl Database for HyperspaceService {
         Ok(Response::new(ReceiverStream::new(rx)))
     }
 
+    type WatchIndexingProgressStream = ReceiverStream<Result<IndexingProgress, Status>>;
+
+    async fn watch_indexing_progress(
+        &self,
+        request: Request<WatchIndexingProgressRequest>,
+    ) -> Result<Response<Self::WatchIndexingProgressStream>, Status> {
+        let user_id = get_user_id(&request);
+        let name = request.into_inner().collection;
+        if self.manager.get(&user_id, &name).await.is_none() {
+            return Err(self.collection_not_found(&user_id, &name));
+        }
+        let (tx, rx) = mpsc::channel(4);
+        let manager = self.manager.clone();
+
+        // Sampled server-side and sent only on change. The collection is
+        // looked up on every tick so the stream does not keep it alive.
+        tokio::spawn(async move {
+            let mut last = None;
+            while !tx.is_closed() {
+                let Some(col) = manager.get(&user_id, &name).await else {
+                    let _ = tx
+                        .send(Err(Status::not_found(format!(
+                            "Collection '{name}' was removed"
+                        ))))
+                        .await;
+                    break;
+                };
+                let progress = col.indexing_progress();
+                drop(col);
+                if last != Some(progress) {
+                    last = Some(progress);
+                    let msg = IndexingProgress {
+                        indexed_clock: progress.indexed_clock,
+                        written_clock: progress.written_clock,
+                        queue_depth: progress.queue_depth,
+                    };
+                    if tx.send(Ok(msg)).await.is_err() {
+                        break;
+                    }
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
     type ReplicateStream = ReceiverStream<Result<ReplicationLog, Status>>;
     type SubscribeToEventsStream = ReceiverStream<Result<EventMessage, Status>>;
     type SyncPullStream = ReceiverStream<Result<SyncVectorData, Status>>;
@@ -1525,6 +3460,28 @@ impl Database for HyperspaceService {
         }
     }
 
+    async fn get_topology(
+        &self,
+        _request: Request<TopologyRequest>,
+    ) -> Result<Response<TopologyResponse>, Status> {
+        let nodes = gossip::topology(&self.manager)
+            .await
+            .into_iter()
+            .map(|node| ClusterNode {
+                node_id: node.node_id,
+                role: node.role,
+                grpc_addr: node.grpc_addr,
+                http_addr: node.http_addr,
+                leader: node.leader.unwrap_or_default(),
+                logical_clock: node.logical_clock,
+                collections: node.collections,
+                healthy: node.healthy,
+                local: node.local,
+            })
+            .collect();
+        Ok(Response::new(TopologyResponse { nodes }))
+    }
+
     async fn replicate(
         &self,
         request: Request<hyperspace_proto::hyperspace::ReplicationRequest>,
@@ -1533,7 +3490,7 @@ impl Database for HyperspaceService {
             return Err(Status::permission_denied("Replication export is disabled on this node. Set HS_REPLICATION_ALLOWED=true to enable."));
         }
 
-        if self.role == "follower" {
+        if self.is_follower().await {
             return Err(Status::failed_precondition(
                 "I am a follower, cannot replicate from me",
             ));
@@ -1545,60 +3502,243 @@ impl Database for HyperspaceService {
             .map_or_else(|| "unknown".to_string(), |addr| addr.to_string());
 
         let req = request.into_inner();
-        println!(
-            "📡 Follower connected: {peer_addr} (Last clock: {})",
-            req.last_logical_clock
-        );
+        let from = req.last_logical_clock;
+        let to = req.to_logical_clock;
+        let epoch = self.manager.fencing.epoch();
+        self.manager.fencing.observe(req.epoch);
+        if req.epoch > epoch {
+            return Err(Status::failed_precondition(format!(
+                "Leadership epoch {epoch} was superseded by epoch {}; follow the new leader",
+                req.epoch
+            )));
+        }
+        println!("📡 Follower connected: {peer_addr} (Last clock: {from})");
+
+        let journal = self.replication_tx.journal().cloned();
+        match &journal {
+            Some(journal) => {
+                if let Some(oldest) = journal.oldest_clock() {
+                    if from > 0 && from < oldest {
+                        return Err(Status::out_of_range(format!(
+                            "Clock {from} is no longer retained (oldest is {oldest}); \
+                             resync the follower with SyncPull"
+                        )));
+                    }
+                }
+            }
+            None if to.is_some() => {
+                return Err(Status::failed_precondition(
+                    "Replication journal is disabled on this node",
+                ));
+            }
+            None => {}
+        }
 
-        // Register follower
-        {
+        // A bounded range is a one-off catch-up, not a live follower.
+        let live = to.is_none();
+        if live {
             let mut state = self.manager.cluster_state.write().await;
             if !state.downstream_peers.contains(&peer_addr) {
                 state.downstream_peers.push(peer_addr.clone());
             }
         }
 
+        // Subscribe before replaying so nothing published meanwhile is missed;
+        // the overlap is re-sent, which followers apply idempotently.
         let mut rx = self.replication_tx.subscribe();
         let (tx, out_rx) = mpsc::channel(100);
         let manager = self.manager.clone();
         let peer_addr_clone = peer_addr.clone();
 
         tokio::spawn(async move {
-            loop {
-                match rx.recv().await {
-                    Ok(log) => {
-                        if tx.send(Ok(log)).await.is_err() {
-                            break;
+            let mut cursor = from;
+            // Tell the follower how far behind it starts
+            let mut open = tx.send(Ok(heartbeat(&manager, epoch).await)).await.is_ok();
+            if let (true, Some(journal)) = (open, &journal) {
+                match replication::replay_into(journal.clone(), from, to, epoch, tx.clone()).await {
+                    Some(newest) => cursor = newest,
+                    None => open = false,
+                }
+            }
+
+            if live && open {
+                let mut beat = tokio::time::interval(follower_reads::HEARTBEAT_INTERVAL);
+                beat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    let received = tokio::select! {
+                        received = rx.recv() => received,
+                        _ = beat.tick() => {
+                            // Leadership changed hands: this stream speaks for an old epoch
+                            if manager.fencing.epoch() != epoch
+                                || tx.send(Ok(heartbeat(&manager, epoch).await)).await.is_err()
+                            {
+                                break;
+                            }
+                            continue;
                         }
+                    };
+                    match received {
+                        Ok(mut log) => {
+                            cursor = cursor.max(log.logical_clock);
+                            log.epoch = epoch;
+                            if tx.send(Ok(log)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            let Some(journal) = &journal else {
+                                eprintln!(
+                                    "⚠️ Replication stream lagged, skipped {skipped} messages"
+                                );
+                                continue;
+                            };
+                            eprintln!(
+                                "⚠️ Replication stream lagged by {skipped}, catching up from journal at clock {cursor}"
+                            );
+                            match replication::replay_into(
+                                journal.clone(),
+                                cursor,
+                                None,
+                                epoch,
+                                tx.clone(),
+                            )
+                            .await
+                            {
+                                Some(newest) => cursor = newest,
+                                None => break,
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                     }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
-                        eprintln!("⚠️ Replication stream lagged, skipped {skipped} messages");
-                    }
-                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                 }
             }
-            // Unregister on disconnect
-            let mut state = manager.cluster_state.write().await;
-            state.downstream_peers.retain(|p| p != &peer_addr_clone);
-            println!("📡 Follower disconnected: {peer_addr_clone}");
+
+            if live {
+                // Unregister on disconnect
+                let mut state = manager.cluster_state.write().await;
+                state.downstream_peers.retain(|p| p != &peer_addr_clone);
+                println!("📡 Follower disconnected: {peer_addr_clone}");
+            }
         });
 
         Ok(Response::new(ReceiverStream::new(out_rx)))
     }
 
+    async fn pull_wal(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::PullWalRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::PullWalResponse>, Status> {
+        if !self.replication_allowed {
+            return Err(Status::permission_denied("Replication export is disabled on this node. Set HS_REPLICATION_ALLOWED=true to enable."));
+        }
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let max_bytes = match req.max_bytes as usize {
+            0 => wal_shipping::DEFAULT_MAX_BYTES,
+            n => n.min(wal_shipping::MAX_BYTES),
+        };
+        let range = self
+            .manager
+            .wal_range(
+                &user_id,
+                &req.collection,
+                req.after_segment,
+                req.offset,
+                max_bytes,
+            )
+            .await
+            .map_err(error_status)?
+            .ok_or_else(|| {
+                Status::out_of_range(format!(
+                    "WAL position {}+{} of '{}' is no longer retained; reseed the standby",
+                    req.after_segment, req.offset, req.collection
+                ))
+            })?;
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::PullWalResponse {
+                segment: range.segment,
+                offset: range.offset,
+                data: range.data,
+                sealed: range.sealed,
+            },
+        ))
+    }
+
+    async fn promote_standby(
+        &self,
+        _request: Request<hyperspace_proto::hyperspace::Empty>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let primary = wal_shipping::promote(&self.manager)
+            .await
+            .ok_or_else(|| Status::failed_precondition("This node is not a standby"))?;
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: format!(
+                    "Promoted to leader after applying {} bytes of WAL from {primary}.",
+                    self.manager.standby.applied_bytes()
+                ),
+            },
+        ))
+    }
+
     async fn subscribe_to_events(
         &self,
         request: Request<EventSubscriptionRequest>,
     ) -> Result<Response<Self::SubscribeToEventsStream>, Status> {
+        let user_id = get_user_id(&request);
         let req = request.into_inner();
         let wanted: HashSet<i32> = req.types.into_iter().collect();
         let filter_collection = req.collection.unwrap_or_default();
+        // Inserts are logged under the internal `{user}_{collection}` name,
+        // deletes under the name the client used.
+        let internal_collection =
+            CollectionManager::get_internal_name(&user_id, &filter_collection);
+        let include_vectors = req.include_vectors;
         let mut rx = self.replication_tx.subscribe();
+        let mut alerts = self.replication_tx.subscribe_alerts();
+        let alert_prefix = format!("{user_id}_");
         let (tx, out_rx) = mpsc::channel(100);
 
         tokio::spawn(async move {
             loop {
-                let log = match rx.recv().await {
+                let received = tokio::select! {
+                    received = rx.recv() => received,
+                    alert = alerts.recv() => {
+                        let alert = match alert {
+                            Ok(alert) => alert,
+                            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                        };
+                        let ty = EventType::StorageAlert as i32;
+                        // Alerts carry internal names; only the owner sees them.
+                        if !alert.collection.starts_with(&alert_prefix)
+                            || (!filter_collection.is_empty()
+                                && alert.collection != internal_collection)
+                            || (!wanted.is_empty() && !wanted.contains(&ty))
+                        {
+                            continue;
+                        }
+                        let event = EventMessage {
+                            r#type: ty,
+                            payload: Some(
+                                hyperspace_proto::hyperspace::event_message::Payload::StorageAlert(
+                                    StorageAlertEvent {
+                                        collection: CollectionManager::display_name(
+                                            &user_id,
+                                            &alert.collection,
+                                        ),
+                                        ..alert
+                                    },
+                                ),
+                            ),
+                        };
+                        if tx.send(Ok(event)).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+                let log = match received {
                     Ok(log) => log,
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
                         eprintln!("⚠️ Event stream lagged, skipped {skipped} messages");
@@ -1607,7 +3747,10 @@ impl Database for HyperspaceService {
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                 };
 
-                if !filter_collection.is_empty() && filter_collection != log.collection {
+                if !filter_collection.is_empty()
+                    && filter_collection != log.collection
+                    && internal_collection != log.collection
+                {
                     continue;
                 }
 
@@ -1633,6 +3776,11 @@ impl Database for HyperspaceService {
                                     origin_node_id: log.origin_node_id.clone(),
                                     metadata,
                                     typed_metadata,
+                                    vector: if include_vectors {
+                                        op.vector
+                                    } else {
+                                        Vec::new()
+                                    },
                                 },
                             )),
                         }
@@ -1663,56 +3811,346 @@ impl Database for HyperspaceService {
                     break;
                 }
             }
-        });
+        });
+
+        Ok(Response::new(ReceiverStream::new(out_rx)))
+    }
+
+    async fn trigger_snapshot(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::SnapshotRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let name = (!req.collection.is_empty()).then_some(req.collection.as_str());
+        match self.manager.snapshot_collections(&user_id, name).await {
+            Ok(count) => Ok(Response::new(
+                hyperspace_proto::hyperspace::StatusResponse {
+                    status: format!("Snapshot written for {count} collection(s)"),
+                },
+            )),
+            Err(e) => Err(error_status(e)),
+        }
+    }
+
+    async fn trigger_vacuum(
+        &self,
+        _request: Request<hyperspace_proto::hyperspace::Empty>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        println!("🧹 Manual Vacuum Triggered: Memory cleanup initiated.");
+        allocator::purge().map_err(Status::internal)?;
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: "System memory purged and returned to OS".to_string(),
+            },
+        ))
+    }
+
+    async fn trigger_reconsolidation(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::ReconsolidationRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        let req = request.into_inner();
+        let col_name = if req.collection.is_empty() {
+            "default".to_string()
+        } else {
+            req.collection
+        };
+
+        let lr = req.learning_rate;
+        println!("🧠 Memory Reconsolidation Triggered for {col_name} (lr={lr})");
+
+        // Future: spawn background Tokio task using hyperspace_core::optim::MemoryReconsolidator
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse {
+                status: "Memory reconsolidation background task scheduled".to_string(),
+            },
+        ))
+    }
+
+    async fn list_trash(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::Empty>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::ListTrashResponse>, Status> {
+        let user_id = get_user_id(&request);
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::ListTrashResponse {
+                collections: self.manager.list_trash(&user_id),
+            },
+        ))
+    }
+
+    async fn restore_collection(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::RestoreCollectionRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        match self.manager.restore_collection(&user_id, &req.name).await {
+            Ok(()) => Ok(Response::new(
+                hyperspace_proto::hyperspace::StatusResponse {
+                    status: format!("Collection '{}' restored.", req.name),
+                },
+            )),
+            // The only precondition is a collection taking the name back.
+            Err(hyperspace_core::HyperspaceError::Precondition(msg)) => {
+                Err(Status::already_exists(msg))
+            }
+            Err(e) => Err(error_status(e)),
+        }
+    }
 
-        Ok(Response::new(ReceiverStream::new(out_rx)))
+    async fn list_collection_versions(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::ListCollectionVersionsRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::ListCollectionVersionsResponse>, Status>
+    {
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let versions = self
+            .manager
+            .snapshot_generations(&user_id, &req.name)
+            .map_err(error_status)?
+            .into_iter()
+            .map(|version| hyperspace_proto::hyperspace::CollectionVersion {
+                version,
+                created_at: version / 1000,
+            })
+            .collect();
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::ListCollectionVersionsResponse { versions },
+        ))
     }
 
-    async fn trigger_snapshot(
+    async fn rollback_collection(
         &self,
-        _request: Request<hyperspace_proto::hyperspace::Empty>,
+        request: Request<hyperspace_proto::hyperspace::RollbackCollectionRequest>,
     ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
-        // Individual collections manage snapshots via background tasks.
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        self.manager
+            .rollback_collection(&user_id, &req.name, req.version)
+            .await
+            .map_err(error_status)?;
         Ok(Response::new(
             hyperspace_proto::hyperspace::StatusResponse {
-                status: "Snapshots are handled automatically by background tasks.".into(),
+                status: format!(
+                    "Collection '{}' rolled back to version {}.",
+                    req.name, req.version
+                ),
             },
         ))
     }
 
-    async fn trigger_vacuum(
+    async fn migrate_collection(
         &self,
-        _request: Request<hyperspace_proto::hyperspace::Empty>,
+        request: Request<hyperspace_proto::hyperspace::MigrateCollectionRequest>,
+    ) -> Result<Response<hyperspace_proto::hyperspace::MigrateCollectionResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        if req.target_addr.is_empty() {
+            return Err(Status::invalid_argument("target_addr is required"));
+        }
+        if self.manager.get(&user_id, &req.collection).await.is_none() {
+            return Err(self.collection_not_found(&user_id, &req.collection));
+        }
+        let target_name = if req.target_collection.is_empty() {
+            req.collection.clone()
+        } else {
+            req.target_collection
+        };
+        let api_key = std::env::var("HYPERSPACE_API_KEY").unwrap_or_default();
+        println!(
+            "🚚 Migrating '{}' to {} as '{target_name}'",
+            req.collection, req.target_addr
+        );
+        let report = migration::migrate(
+            &self.manager,
+            &user_id,
+            &req.collection,
+            &req.target_addr,
+            &target_name,
+            &api_key,
+        )
+        .await
+        .map_err(|e| Status::aborted(format!("Migration of '{}' failed: {e}", req.collection)))?;
+        println!(
+            "🚚 Migrated '{}': {} bundle bytes, {} replayed writes, writes paused {:?}",
+            req.collection, report.bundle_bytes, report.replayed_ops, report.write_pause
+        );
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::MigrateCollectionResponse {
+                bundle_bytes: report.bundle_bytes,
+                replayed_ops: report.replayed_ops,
+                write_pause_ms: report.write_pause.as_millis() as u64,
+            },
+        ))
+    }
+
+    async fn receive_collection(
+        &self,
+        request: Request<Streaming<hyperspace_proto::hyperspace::CollectionBundleChunk>>,
     ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
-        // Trigger manual GC/Vacuum
-        println!("🧹 Manual Vacuum Triggered: Memory cleanup initiated.");
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let tmp = std::env::temp_dir().join(format!("hs_receive_{}.tar", uuid::Uuid::new_v4()));
+        let received = receive_chunks(request.into_inner(), &tmp).await;
+        let result = match received {
+            Ok((name, _)) => self
+                .manager
+                .import_collection(&user_id, &name, &tmp)
+                .await
+                .map(|()| name)
+                .map_err(error_status),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&tmp).await;
+        let name = result?;
         Ok(Response::new(
             hyperspace_proto::hyperspace::StatusResponse {
-                status: "Memory cleanup triggered".to_string(),
+                status: format!("Collection '{name}' received."),
             },
         ))
     }
 
-    async fn trigger_reconsolidation(
+    async fn import_collection(
         &self,
-        request: Request<hyperspace_proto::hyperspace::ReconsolidationRequest>,
+        request: Request<Streaming<hyperspace_proto::hyperspace::CollectionBundleChunk>>,
     ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
+        let tmp = std::env::temp_dir().join(format!("hs_import_{}", uuid::Uuid::new_v4()));
+        let received = receive_chunks(request.into_inner(), &tmp).await;
+        let result = match received {
+            Ok((name, format)) => match columnar_format(format) {
+                Ok(None) => self
+                    .manager
+                    .import_collection(&user_id, &name, &tmp)
+                    .await
+                    .map(|()| format!("Collection '{name}' imported.")),
+                Ok(Some(format)) => self
+                    .manager
+                    .import_columnar(&user_id, &name, format, &tmp)
+                    .await
+                    .map(|rows| format!("Imported {rows} rows into '{name}'.")),
+                Err(e) => Err(e),
+            }
+            .map_err(error_status),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&tmp).await;
+        Ok(Response::new(
+            hyperspace_proto::hyperspace::StatusResponse { status: result? },
+        ))
+    }
+
+    type ExportCollectionStream =
+        ReceiverStream<Result<hyperspace_proto::hyperspace::CollectionBundleChunk, Status>>;
+
+    async fn export_collection(
+        &self,
+        request: Request<hyperspace_proto::hyperspace::ExportCollectionRequest>,
+    ) -> Result<Response<Self::ExportCollectionStream>, Status> {
+        use tokio::io::AsyncReadExt;
+
+        let user_id = get_user_id(&request);
+        let req = request.into_inner();
+        let (name, wire_format) = (req.collection, req.format);
+        let format = columnar_format(wire_format).map_err(error_status)?;
+        let tmp = std::env::temp_dir().join(format!("hs_export_{}", uuid::Uuid::new_v4()));
+        let written = match format {
+            None => self.manager.export_collection(&user_id, &name, &tmp).await,
+            Some(format) => self
+                .manager
+                .export_columnar(&user_id, &name, format, &tmp)
+                .await
+                .map(|_| ()),
+        };
+        let opened = match written {
+            Ok(()) => tokio::fs::File::open(&tmp)
+                .await
+                .map_err(|e| Status::internal(e.to_string())),
+            Err(e) => Err(error_status(e)),
+        };
+        let mut file = match opened {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                return Err(e);
+            }
+        };
+        let total_bytes = file.metadata().await.map_or(0, |m| m.len());
+        let (tx, rx) = mpsc::channel(4);
+
+        tokio::spawn(async move {
+            let mut first = true;
+            loop {
+                let mut data = vec![0u8; migration::BUNDLE_CHUNK];
+                let chunk = match file.read(&mut data).await {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        data.truncate(n);
+                        Ok(hyperspace_proto::hyperspace::CollectionBundleChunk {
+                            collection: if first { name.clone() } else { String::new() },
+                            data,
+                            total_bytes: if first { total_bytes } else { 0 },
+                            format: if first { wire_format } else { 0 },
+                        })
+                    }
+                    Err(e) => Err(Status::internal(e.to_string())),
+                };
+                first = false;
+                let failed = chunk.is_err();
+                if tx.send(chunk).await.is_err() || failed {
+                    break;
+                }
+            }
+            drop(file);
+            let _ = tokio::fs::remove_file(&tmp).await;
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn purge(&self, request: Request<PurgeRequest>) -> Result<Response<PurgeReport>, Status> {
+        self.check_writable().await?;
+        let user_id = get_user_id(&request);
         let req = request.into_inner();
         let col_name = if req.collection.is_empty() {
             "default".to_string()
         } else {
             req.collection
         };
+        let exact_filter = req.filter.into_iter().collect();
+        let complex_filters = complex_filters(req.filters, req.filter_expr.as_deref())?;
 
-        let lr = req.learning_rate;
-        println!("🧠 Memory Reconsolidation Triggered for {col_name} (lr={lr})");
-
-        // Future: spawn background Tokio task using hyperspace_core::optim::MemoryReconsolidator
-        Ok(Response::new(
-            hyperspace_proto::hyperspace::StatusResponse {
-                status: "Memory reconsolidation background task scheduled".to_string(),
-            },
-        ))
+        let Some(col) = self.manager.get(&user_id, &col_name).await else {
+            return Err(self.collection_not_found(&user_id, &col_name));
+        };
+        let evidence = purge::run(
+            &self.manager,
+            &col_name,
+            col.as_ref(),
+            &exact_filter,
+            &complex_filters,
+        )
+        .await
+        .map_err(error_status)?;
+        Ok(Response::new(PurgeReport {
+            collection: evidence.collection,
+            purged_ids: evidence.purged_ids,
+            wal_records_removed: evidence.wal_records_removed,
+            journal_entries_removed: evidence.journal_entries_removed,
+            history_generations_removed: evidence.history_generations_removed,
+            logical_clock: evidence.logical_clock,
+            purged_at: evidence.purged_at,
+            node_id: evidence.node_id,
+            filter: evidence.filter,
+            digest: evidence.digest,
+            signature: evidence.signature,
+        }))
     }
 
     async fn rebuild_index(
@@ -1746,8 +4184,6 @@ impl Database for HyperspaceService {
         &self,
         request: Request<ConfigUpdate>,
     ) -> Result<Response<hyperspace_proto::hyperspace::StatusResponse>, Status> {
-        // TODO: Update config for specific collection
-        // ConfigUpdate now has `collection` field.
         let user_id = get_user_id(&request);
         let req = request.into_inner();
         let col_name = if req.collection.is_empty() {
@@ -1757,14 +4193,33 @@ impl Database for HyperspaceService {
         };
 
         if self.manager.get(&user_id, &col_name).await.is_none() {
-            return Err(Status::not_found(format!(
-                "Collection '{col_name}' not found"
-            )));
+            return Err(self.collection_not_found(&user_id, &col_name));
         }
-        // Not implemented on trait yet.
+
+        let Some(mode) = req.wal_sync_mode else {
+            // Not implemented on trait yet.
+            return Ok(Response::new(
+                hyperspace_proto::hyperspace::StatusResponse {
+                    status: "Dynamic config not yet implemented for collections".into(),
+                },
+            ));
+        };
+        let parsed = match mode.to_lowercase().as_str() {
+            "default" => None,
+            other => Some(WalSyncMode::parse(other).ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "Unknown wal_sync_mode '{mode}' (expected strict, batch, async or default)"
+                ))
+            })?),
+        };
+        self.manager
+            .set_wal_sync_mode(&user_id, &col_name, parsed)
+            .await
+            .map_err(error_status)?;
+        let applied = parsed.map_or("default", WalSyncMode::as_str);
         Ok(Response::new(
             hyperspace_proto::hyperspace::StatusResponse {
-                status: "Dynamic config not yet implemented for collections".into(),
+                status: format!("WAL sync mode for '{col_name}' set to {applied}"),
             },
         ))
     }
@@ -1787,7 +4242,7 @@ impl Database for HyperspaceService {
             .manager
             .get(&user_id, col_name)
             .await
-            .ok_or_else(|| Status::not_found(format!("Collection '{col_name}' not found")))?;
+            .ok_or_else(|| self.collection_not_found(&user_id, col_name))?;
 
         let server_buckets = col.buckets();
         let server_clock = self.manager.cluster_state.read().await.logical_clock;
@@ -1851,7 +4306,7 @@ impl Database for HyperspaceService {
             .manager
             .get(&user_id, &col_name)
             .await
-            .ok_or_else(|| Status::not_found(format!("Collection '{col_name}' not found")))?;
+            .ok_or_else(|| self.collection_not_found(&user_id, &col_name))?;
 
         let bucket_indices: Vec<u32> = req.bucket_indices;
         if bucket_indices.is_empty() {
@@ -1955,8 +4410,223 @@ impl Database for HyperspaceService {
     }
 }
 
+/// Replication entry reporting this leader's current clock.
+async fn heartbeat(manager: &CollectionManager, epoch: u64) -> ReplicationLog {
+    let leader_clock = manager.cluster_state.read().await.logical_clock;
+    ReplicationLog {
+        logical_clock: 0,
+        origin_node_id: String::new(),
+        collection: String::new(),
+        operation: Some(replication_log::Operation::Heartbeat(
+            hyperspace_proto::hyperspace::HeartbeatOp { leader_clock },
+        )),
+        epoch,
+    }
+}
+
+/// Applies one entry from the leader's replication stream.
+async fn apply_replication_log(mgr: &CollectionManager, log: ReplicationLog) {
+    let col_name = if log.collection.is_empty() {
+        "default"
+    } else {
+        &log.collection
+    };
+
+    // Merge clock
+    mgr.merge_cluster_clock(log.logical_clock).await;
+
+    match log.operation {
+        Some(replication_log::Operation::Insert(op)) => {
+            // Use get_internal for replication
+            if let Some(col) = mgr.get_internal(col_name).await {
+                let merged_meta =
+                    merge_metadata(op.metadata.into_iter().collect(), op.typed_metadata);
+                if let Err(e) = col
+                    .insert(
+                        &op.vector,
+                        op.id,
+                        merged_meta,
+                        log.logical_clock,
+                        hyperspace_core::Durability::Default,
+                    )
+                    .await
+                {
+                    eprintln!("Rep Error: {e}");
+                }
+            } else {
+                eprintln!("Unknown collection for insert: {col_name}");
+            }
+        }
+        Some(replication_log::Operation::CreateCollection(op)) => {
+            println!("Rep: Creating collection {col_name}");
+            if let Err(e) = mgr
+                .create_collection_from_replication(
+                    col_name,
+                    op.dimension,
+                    &op.metric,
+                    manager::CollectionInfo {
+                        description: op.description,
+                        labels: op.labels.into_iter().collect(),
+                    },
+                )
+                .await
+            {
+                eprintln!("Rep Error (Create): {e}");
+            }
+        }
+        Some(replication_log::Operation::DeleteCollection(_)) => {
+            println!("Rep: Deleting collection {col_name}");
+            if let Err(e) = mgr.delete_collection_from_replication(col_name).await {
+                eprintln!("Rep Error (Delete): {e}");
+            }
+        }
+        Some(replication_log::Operation::Delete(op)) => {
+            if let Some(col) = mgr.get_internal(col_name).await {
+                let _ = col.delete(op.id, log.logical_clock).await;
+            }
+        }
+        Some(replication_log::Operation::Purge(op)) => {
+            if let Some(col) = mgr.get_internal(col_name).await {
+                if let Err(e) = col.purge_ids(&op.ids, log.logical_clock).await {
+                    eprintln!("Rep Error (Purge): {e}");
+                }
+                mgr.close_generations(col_name);
+            }
+        }
+        Some(replication_log::Operation::RestoreCollection(_)) => {
+            println!("Rep: Restoring collection {col_name}");
+            if let Err(e) = mgr.restore_collection_from_replication(col_name).await {
+                eprintln!("Rep Error (Restore): {e}");
+            }
+        }
+        // Lag bookkeeping only, see `spawn_follower`
+        Some(replication_log::Operation::Heartbeat(_)) | None => {}
+    }
+}
+
+/// Streams replication from whichever leader `upstream` names, reconnecting
+/// on failure and re-pointing as soon as the upstream changes.
+fn spawn_follower(
+    manager: std::sync::Weak<CollectionManager>,
+    upstream: tokio::sync::watch::Sender<Option<String>>,
+    api_key: Option<String>,
+    user_id: Option<String>,
+) {
+    tokio::spawn(async move {
+        use hyperspace_proto::hyperspace::database_client::DatabaseClient;
+        use tonic::transport::Channel;
+
+        // Holding the sender keeps `changed()` pending when nothing else can
+        // re-point us (election disabled).
+        let mut rx = upstream.subscribe();
+
+        // Newest leader clock applied; the leader replays from here on reconnect.
+        // Our own clock can't be used: merging advances it past the leader's.
+        let mut applied_clock = 0u64;
+        loop {
+            let upstream = rx.borrow_and_update().clone();
+            if let Some(mgr) = manager.upgrade() {
+                mgr.follower_reads.set_following(upstream.is_some());
+            }
+            let Some(leader) = upstream else {
+                // No upstream (leading): wait until the election says otherwise.
+                if rx.changed().await.is_err() {
+                    break;
+                }
+                continue;
+            };
+
+            println!("Connecting to leader {leader}...");
+            let endpoint = match Channel::from_shared(leader.clone()) {
+                Ok(endpoint) => endpoint,
+                Err(e) => {
+                    eprintln!("Invalid leader URL {leader}: {e}");
+                    if rx.changed().await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            match endpoint.connect().await {
+                Ok(channel) => {
+                    let interceptor = ClientAuthInterceptor {
+                        api_key: api_key.clone().unwrap_or_default(),
+                        user_id: user_id.clone(),
+                    };
+                    let mut client = DatabaseClient::with_interceptor(channel, interceptor);
+
+                    println!("Connected! Requesting replication stream...");
+                    let req = hyperspace_proto::hyperspace::ReplicationRequest {
+                        last_logical_clock: applied_clock,
+                        to_logical_clock: None,
+                        epoch: manager.upgrade().map_or(0, |mgr| mgr.fencing.newest()),
+                    };
+
+                    match client.replicate(req).await {
+                        Ok(resp) => {
+                            let mut stream = resp.into_inner();
+                            if let Some(mgr) = manager.upgrade() {
+                                mgr.follower_reads.set_connected(true);
+                            }
+                            loop {
+                                let log = tokio::select! {
+                                    msg = stream.message() => match msg {
+                                        Ok(Some(log)) => log,
+                                        _ => break,
+                                    },
+                                    // Re-point (or stop following) right away.
+                                    _ = rx.changed() => break,
+                                };
+                                let Some(mgr) = manager.upgrade() else {
+                                    return;
+                                };
+                                if !mgr.fencing.observe(log.epoch) {
+                                    eprintln!(
+                                        "⚠️ Dropping stream from {leader}: epoch {} is older than {}",
+                                        log.epoch,
+                                        mgr.fencing.newest()
+                                    );
+                                    break;
+                                }
+                                if let Some(replication_log::Operation::Heartbeat(beat)) =
+                                    &log.operation
+                                {
+                                    mgr.follower_reads.leader_at(beat.leader_clock);
+                                    continue;
+                                }
+                                applied_clock = applied_clock.max(log.logical_clock);
+                                apply_replication_log(&mgr, log).await;
+                                mgr.follower_reads.applied(applied_clock);
+                            }
+                            if let Some(mgr) = manager.upgrade() {
+                                mgr.follower_reads.set_connected(false);
+                            }
+                        }
+                        Err(e) if e.code() == tonic::Code::OutOfRange => {
+                            // The gap can't be replayed; take what is retained.
+                            eprintln!("⚠️ {}", e.message());
+                            applied_clock = 0;
+                        }
+                        Err(e) => eprintln!("Failed: {e}"),
+                    }
+                }
+                Err(e) => eprintln!("Conn failed: {e}"),
+            }
+            if manager.strong_count() == 0 {
+                break;
+            }
+            if !rx.has_changed().unwrap_or(false) {
+                tokio::select! {
+                    () = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {}
+                    _ = rx.changed() => {}
+                }
+            }
+        }
+    });
+}
+
 async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let addr = format!("0.0.0.0:{}", args.port).parse()?;
+    let addr: std::net::SocketAddr = format!("0.0.0.0:{}", args.port).parse()?;
 
     // Setup Manager
     let data_dir = std::path::PathBuf::from(
@@ -1970,11 +4640,35 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
     println!("⚙️ Event Stream Buffer: {event_buffer}");
     let (replication_tx, _) = broadcast::channel(event_buffer);
 
+    // Leaders that export replication journal every entry so followers can
+    // resume from their last clock instead of losing what they missed. With
+    // election any node may become leader, so every node keeps one.
+    let election_requested = std::env::var("HS_ELECTION_LEASE").is_ok();
+    let journal_enabled = args.replication_allowed
+        && (args.role != "follower" || election_requested)
+        && !std::env::var("HS_REPLICATION_LOG")
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"));
+    let replication_tx = if journal_enabled {
+        let journal = ReplicationJournal::open(&data_dir)?;
+        println!(
+            "📜 Replication journal: [ENABLED] (oldest clock: {})",
+            journal
+                .oldest_clock()
+                .map_or_else(|| "-".to_string(), |c| c.to_string())
+        );
+        ReplicationFeed::with_journal(replication_tx, journal)
+    } else {
+        ReplicationFeed::from(replication_tx)
+    };
+
     let manager = Arc::new(CollectionManager::new(data_dir, replication_tx.clone()));
 
     // Load existing
     println!("Loading collections...");
     manager.load_existing().await?;
+    metering::spawn_flusher(&manager);
+    scrubber::spawn(&manager);
+    telemetry::spawn(&manager);
 
     // Use env vars for default
     let dim_str = std::env::var("HS_DIMENSION").unwrap_or("1024".to_string());
@@ -1996,136 +4690,69 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
             .await?;
     }
 
-    // Follower Logic
-    if args.role == "follower" {
-        if let Some(leader) = args.leader.clone() {
-            println!("🚀 Starting as FOLLOWER of: {leader}");
-            let manager_weak = Arc::downgrade(&manager);
-            let api_key_for_client = std::env::var("HYPERSPACE_API_KEY").ok();
-
-            tokio::spawn(async move {
-                use hyperspace_proto::hyperspace::database_client::DatabaseClient;
-                use tonic::transport::Channel;
-
-                loop {
-                    println!("Connecting to leader {leader}...");
-                    match Channel::from_shared(leader.clone())
-                        .expect("Invalid leader URL")
-                        .connect()
-                        .await
-                    {
-                        Ok(channel) => {
-                            let interceptor = ClientAuthInterceptor {
-                                api_key: api_key_for_client.clone().unwrap_or_default(),
-                                user_id: args.user_id.clone(),
-                            };
-                            let mut client = DatabaseClient::with_interceptor(channel, interceptor);
-
-                            println!("Connected! Requesting replication stream...");
-                            let current_clock = manager_weak.upgrade().map_or(0, |m| {
-                                futures::executor::block_on(async {
-                                    m.cluster_state.read().await.logical_clock
-                                })
-                            });
-
-                            let req = hyperspace_proto::hyperspace::ReplicationRequest {
-                                last_logical_clock: current_clock,
-                            };
+    let node_id = manager.cluster_state.read().await.node_id.clone();
+    let election = election::ElectionConfig::from_env(node_id)?;
 
-                            match client.replicate(req).await {
-                                Ok(resp) => {
-                                    let mut stream = resp.into_inner();
-                                    while let Ok(Some(log)) = stream.message().await {
-                                        if let Some(mgr) = manager_weak.upgrade() {
-                                            let col_name = if log.collection.is_empty() {
-                                                "default"
-                                            } else {
-                                                &log.collection
-                                            };
-
-                                            // Merge clock
-                                            mgr.merge_cluster_clock(log.logical_clock).await;
-
-                                            match log.operation {
-                                                Some(replication_log::Operation::Insert(op)) => {
-                                                    // Use get_internal for replication
-                                                    if let Some(col) =
-                                                        mgr.get_internal(col_name).await
-                                                    {
-                                                        let merged_meta = merge_metadata(
-                                                            op.metadata.into_iter().collect(),
-                                                            op.typed_metadata,
-                                                        );
-                                                        if let Err(e) = col.insert(
-                                                            &op.vector,
-                                                            op.id,
-                                                            merged_meta,
-                                                            log.logical_clock,
-                                                            hyperspace_core::Durability::Default,
-                                                        ).await {
-                                                            eprintln!("Rep Error: {e}");
-                                                        }
-                                                    } else {
-                                                        eprintln!("Unknown collection for insert: {col_name}");
-                                                    }
-                                                }
-                                                Some(
-                                                    replication_log::Operation::CreateCollection(
-                                                        op,
-                                                    ),
-                                                ) => {
-                                                    println!("Rep: Creating collection {col_name}");
-                                                    if let Err(e) = mgr
-                                                        .create_collection_from_replication(
-                                                            col_name,
-                                                            op.dimension,
-                                                            &op.metric,
-                                                        )
-                                                        .await
-                                                    {
-                                                        eprintln!("Rep Error (Create): {e}");
-                                                    }
-                                                }
-                                                Some(
-                                                    replication_log::Operation::DeleteCollection(_),
-                                                ) => {
-                                                    println!("Rep: Deleting collection {col_name}");
-                                                    if let Err(e) = mgr
-                                                        .delete_collection_from_replication(
-                                                            col_name,
-                                                        )
-                                                        .await
-                                                    {
-                                                        eprintln!("Rep Error (Delete): {e}");
-                                                    }
-                                                }
-                                                Some(replication_log::Operation::Delete(op)) => {
-                                                    if let Some(col) =
-                                                        mgr.get_internal(col_name).await
-                                                    {
-                                                        let _ = col.delete(op.id);
-                                                    }
-                                                }
-                                                None => {}
-                                            }
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                }
-                                Err(e) => eprintln!("Failed: {e}"),
-                            }
-                        }
-                        Err(e) => eprintln!("Conn failed: {e}"),
-                    }
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                }
-            });
-        }
+    // Follower Logic. The upstream is fixed by `--leader` unless election is
+    // enabled, in which case the lease holder decides who leads.
+    let initial_upstream = if args.role == "follower" {
+        args.leader.clone()
     } else {
+        None
+    };
+    {
+        let mut state = manager.cluster_state.write().await;
+        // A standby refuses writes until it is promoted
+        state.role = if args.role == "follower" || args.standby_of.is_some() {
+            ClusterRole::Follower
+        } else {
+            ClusterRole::Leader
+        };
+        state.upstream_peer.clone_from(&initial_upstream);
+    }
+    if let Some(leader) = &initial_upstream {
+        println!("🚀 Starting as FOLLOWER of: {leader}");
+    } else if let Some(primary) = &args.standby_of {
+        println!("🚀 Starting as WARM STANDBY of: {primary}");
+    } else if args.role != "follower" {
         println!("🚀 Starting as LEADER");
     }
 
+    let (upstream_tx, upstream_rx) = tokio::sync::watch::channel(initial_upstream);
+    let api_key_for_client = std::env::var("HYPERSPACE_API_KEY").ok();
+    spawn_follower(
+        Arc::downgrade(&manager),
+        upstream_tx.clone(),
+        api_key_for_client.clone(),
+        args.user_id.clone(),
+    );
+    if let Some(primary) = args.standby_of.clone() {
+        wal_shipping::spawn(
+            Arc::downgrade(&manager),
+            primary,
+            api_key_for_client.clone().unwrap_or_default(),
+            args.user_id
+                .clone()
+                .unwrap_or_else(|| "default_admin".to_string()),
+            wal_shipping::poll_interval_from_env(),
+        );
+    }
+    if let Some(every) = anti_entropy::interval_from_env() {
+        anti_entropy::spawn(
+            Arc::downgrade(&manager),
+            upstream_rx,
+            api_key_for_client.unwrap_or_default(),
+            every,
+        );
+    }
+    if let Some((store, config)) = election {
+        println!(
+            "🗳️ Leader election: [ENABLED] (advertising {}, lease TTL {:?})",
+            config.advertise_addr, config.ttl
+        );
+        election::spawn(Arc::downgrade(&manager), store, config, upstream_tx);
+    }
+
     // 1. Initialize Vectorizer (Moved before HTTP Server)
     //
     // Per-Metric Embedding Architecture:
@@ -2232,8 +4859,82 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
                         Arc::new(RemoteVectorizer::new(provider, api_key, model, base_url)),
                     );
                 }
+
+                // Optional CLIP vision tower embedding images into this metric's space
+                let image_path =
+                    std::env::var(format!("HS_EMBED_{metric_upper}_IMAGE_MODEL_PATH")).ok();
+                let image_hf_id =
+                    std::env::var(format!("HS_EMBED_{metric_upper}_IMAGE_HF_MODEL_ID")).ok();
+                if image_path.is_some() || image_hf_id.is_some() {
+                    let dim: usize = std::env::var(format!("HS_EMBED_{metric_upper}_DIM"))
+                        .or_else(|_| std::env::var("HYPERSPACE_EMBED_DIM"))
+                        .unwrap_or_else(|_| "128".to_string())
+                        .parse()
+                        .unwrap_or(128);
+                    let loaded = if let Some(path) = image_path {
+                        println!(
+                            "🖼️  [{metric_upper}] Loading local image model: {path} (dim={dim})"
+                        );
+                        ImageVectorizer::new(&path, dim, metric)
+                    } else {
+                        let model_id = image_hf_id.unwrap_or_default();
+                        println!("🖼️  [{metric_upper}] Downloading HF image model: {model_id} (dim={dim})");
+                        let hf_token = std::env::var("HF_TOKEN")
+                            .or_else(|_| std::env::var("HUGGING_FACE_HUB_TOKEN"))
+                            .ok();
+                        ImageVectorizer::new_from_hf(
+                            &model_id,
+                            hf_token.as_deref(),
+                            dim,
+                            metric,
+                            std::env::var(format!("HS_EMBED_{metric_upper}_IMAGE_HF_FILENAME"))
+                                .ok(),
+                        )
+                    };
+                    match loaded {
+                        Ok(v) => multi.add_image(metric_name, Arc::new(v)),
+                        Err(e) => eprintln!("❌ [{metric_upper}] Image model failed to load: {e}"),
+                    }
+                }
+
+                // Optional CLAP audio tower embedding sounds into this metric's space
+                let audio_path =
+                    std::env::var(format!("HS_EMBED_{metric_upper}_AUDIO_MODEL_PATH")).ok();
+                let audio_hf_id =
+                    std::env::var(format!("HS_EMBED_{metric_upper}_AUDIO_HF_MODEL_ID")).ok();
+                if audio_path.is_some() || audio_hf_id.is_some() {
+                    let dim: usize = std::env::var(format!("HS_EMBED_{metric_upper}_DIM"))
+                        .or_else(|_| std::env::var("HYPERSPACE_EMBED_DIM"))
+                        .unwrap_or_else(|_| "128".to_string())
+                        .parse()
+                        .unwrap_or(128);
+                    let loaded = if let Some(path) = audio_path {
+                        println!(
+                            "🔊 [{metric_upper}] Loading local audio model: {path} (dim={dim})"
+                        );
+                        AudioVectorizer::new(&path, dim, metric)
+                    } else {
+                        let model_id = audio_hf_id.unwrap_or_default();
+                        println!("🔊 [{metric_upper}] Downloading HF audio model: {model_id} (dim={dim})");
+                        let hf_token = std::env::var("HF_TOKEN")
+                            .or_else(|_| std::env::var("HUGGING_FACE_HUB_TOKEN"))
+                            .ok();
+                        AudioVectorizer::new_from_hf(
+                            &model_id,
+                            hf_token.as_deref(),
+                            dim,
+                            metric,
+                            std::env::var(format!("HS_EMBED_{metric_upper}_AUDIO_HF_FILENAME"))
+                                .ok(),
+                        )
+                    };
+                    match loaded {
+                        Ok(v) => multi.add_audio(metric_name, Arc::new(v)),
+                        Err(e) => eprintln!("❌ [{metric_upper}] Audio model failed to load: {e}"),
+                    }
+                }
             }
-            let count = multi.models.len();
+            let count = multi.models.len() + multi.images.len() + multi.audio.len();
             if count == 0 {
                 println!("⚠️  All configured models failed to load - Embedding Pipeline DISABLED");
                 None
@@ -2309,19 +5010,7 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
         std::env::var("HS_GOSSIP_ENABLED").is_ok_and(|v| v.to_lowercase() == "true");
 
     let peer_registry: Option<gossip::PeerRegistry> = if gossip_enabled {
-        let (node_id, role, logical_clock) = {
-            let state = manager.cluster_state.read().await;
-            (
-                state.node_id.clone(),
-                state.role.clone(),
-                state.logical_clock,
-            )
-        };
-        // Digests ref: empty initially, populated as insert/search ops update bucket hashes
-        let digests_ref = Arc::new(tokio::sync::RwLock::new(Vec::new()));
-        // Logical clock ref: mirrors the cluster Lamport clock
-        let clock_ref = Arc::new(tokio::sync::RwLock::new(logical_clock));
-        let registry = gossip::start_gossip(node_id, role, http_port, clock_ref, digests_ref).await;
+        let registry = gossip::start_gossip(&manager, http_port, args.port).await;
         Some(registry)
     } else {
         println!("ℹ️  Gossip disabled — set HS_GOSSIP_PEERS=<ip:port,...> to enable swarm mode");
@@ -2330,9 +5019,19 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
 
     // 4. Start HTTP Dashboard
     let http_mgr = manager.clone();
+    let embedder = ingest::TextEmbedder {
+        #[cfg(feature = "embed")]
+        vectorizer: vectorizer.clone(),
+    };
     tokio::spawn(async move {
-        if let Err(e) =
-            http_server::start_http_server(http_mgr, http_port, embedding_info, peer_registry).await
+        if let Err(e) = http_server::start_http_server(
+            http_mgr,
+            http_port,
+            embedding_info,
+            peer_registry,
+            embedder,
+        )
+        .await
         {
             eprintln!("HTTP Server panicked: {e}");
         }
@@ -2350,10 +5049,10 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
         println!("🔒 Replication: [DISABLED] (Outgoing streams blocked)");
     }
 
+    let shutdown_mgr = manager.clone();
     let service = HyperspaceService {
         manager,
         replication_tx,
-        role: args.role,
         replication_allowed: args.replication_allowed,
         #[cfg(feature = "embed")]
         vectorizer,
@@ -2368,13 +5067,19 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
         let mut hasher = Sha256::new();
         hasher.update(key.as_bytes());
         let hash = hex::encode(hasher.finalize());
+        let read_hash = auth::read_key_hash();
+        if read_hash.is_some() {
+            println!("🔒 Read-only API key accepted");
+        }
         AuthInterceptor {
             expected_hash: Some(hash),
+            read_hash,
         }
     } else {
         println!("⚠️ API Auth Disabled");
         AuthInterceptor {
             expected_hash: None,
+            read_hash: None,
         }
     };
 
@@ -2385,25 +5090,72 @@ async fn start_server(args: Args) -> Result<(), Box<dyn std::error::Error + Send
         .max_decoding_message_size(max_msg_size)
         .max_encoding_message_size(max_msg_size);
 
+    // Followers past HS_FOLLOWER_MAX_STALENESS refuse reads (after auth)...
+    let reads_manager = shutdown_mgr.clone();
+    #[allow(clippy::result_large_err)]
+    let stale_gate = move |request: Request<()>| {
+        let reads = &reads_manager.follower_reads;
+        if reads.mode() == follower_reads::StaleReads::Reject && reads.is_stale() {
+            let path = request
+                .extensions()
+                .get::<auth::GrpcPath>()
+                .map_or("", |p| p.0.as_str());
+            // Topology stays answerable so clients can find the leader
+            if auth::grpc_scope(path) == auth::Scope::Read && !path.ends_with("/GetTopology") {
+                return Err(Status::unavailable(format!(
+                    "Follower is {} ticks behind its leader; read from the leader",
+                    reads.lag()
+                )));
+            }
+        }
+        Ok(request)
+    };
+    let gated_service =
+        tonic::service::interceptor::InterceptedService::new(db_service, stale_gate);
     let service_with_auth =
-        tonic::service::interceptor::InterceptedService::new(db_service, interceptor);
+        tonic::service::interceptor::InterceptedService::new(gated_service, interceptor);
+    // ...or flag them
+    let flag_manager = shutdown_mgr.clone();
+    let flag_stale = move |mut response: tonic::codegen::http::Response<tonic::body::BoxBody>| {
+        let reads = &flag_manager.follower_reads;
+        if reads.mode() == follower_reads::StaleReads::Flag && reads.is_stale() {
+            response.headers_mut().insert(
+                follower_reads::STALE_HEADER,
+                tonic::codegen::http::HeaderValue::from_static("true"),
+            );
+        }
+        response
+    };
+
+    // Bind before reporting readiness so systemd never routes to a closed port.
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let incoming = tonic::transport::server::TcpIncoming::from_listener(listener, true, None)?;
+    daemon::notify("READY=1\nSTATUS=Serving");
 
     Server::builder()
+        .layer(tower::util::MapRequestLayer::new(auth::tag_grpc_path))
+        .layer(tower::util::MapResponseLayer::new(flag_stale))
         .add_service(service_with_auth)
-        .serve_with_shutdown(addr, async {
+        .serve_with_incoming_shutdown(incoming, async {
             tokio::signal::ctrl_c().await.ok();
             println!("\n🛑 Received Ctrl+C. Initiating graceful shutdown...");
+            daemon::notify("STOPPING=1");
         })
         .await?;
 
+    // Deliver units metered since the last periodic flush
+    if shutdown_mgr.meter.has_sinks() {
+        let storage = shutdown_mgr.get_usage_report();
+        let _ = shutdown_mgr.meter.flush(&storage).await;
+    }
+
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let version = env!("CARGO_PKG_VERSION");
     println!("\x1b[36m");
-    println!(r"█▀▀  █║  █║  ▀▀█  [H] HyperspaceDB v{version}");");
+    println!(r"█▀▀  █║  █║  ▀▀█  [H] HyperspaceDB v{version}");
     println!(r"█    █║  █║    █  🦺 Dashboard: http://localhost:50050");
     println!(r"█    █████║    █  🚀 SaaS: https://yar.ink");
     println!(r"█▄▄  █║  █║  ▄▄█  ⭐ Star: https://github.com/YARlabs/hyperspace-db");
@@ -2412,5 +5164,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     dotenv::dotenv().ok();
     let args = Args::parse();
-    start_server(args).await
+    // Forking is only safe before the runtime starts its worker threads.
+    if args.daemon {
+        daemon::detach(args.log_file.as_deref())?;
+    }
+    let _pid_file = args
+        .pid_file
+        .as_deref()
+        .map(daemon::PidFile::create)
+        .transpose()?;
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(start_server(args))
 }
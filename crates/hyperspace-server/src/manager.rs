@@ -1,19 +1,42 @@
+use crate::bundle;
 use crate::collection::CollectionImpl;
+use crate::collection_spec::{self, ApplyOutcome, CollectionSpec, HnswParams, IvfParams};
+use crate::columnar::{self, ColumnarFormat};
+use crate::experiments::{self, Experiment, RunningExperiment};
+use crate::fencing::Fencing;
+use crate::follower_reads::FollowerReads;
+use crate::gossip::PeerRegistry;
+use crate::ingest::IngestJobs;
+use crate::limits::{dir_size, CollectionLimits};
+use crate::metering::Meter;
+use crate::query_templates::{self, QueryTemplate};
+use crate::replication::ReplicationFeed;
+use crate::slow_queries::SlowQueryLog;
+use crate::snapshot::{CollectionState, SnapshotPolicy};
+use crate::snapshot_history::{self, SnapshotRef};
+use crate::text_collection::TextCollection;
+use crate::trash;
+use crate::wal_shipping::{WalRange, WalStandby};
 use dashmap::DashMap;
-use hyperspace_core::VacuumFilterQuery;
 use hyperspace_core::{Collection, CosineMetric, EuclideanMetric, LorentzMetric, PoincareMetric};
+use hyperspace_core::{
+    Durability, HyperspaceError, HyperspaceResult, MetadataSchema, NormalizationPolicy, SynonymMap,
+    VacuumFilterQuery,
+};
 use hyperspace_proto::hyperspace::{
-    replication_log, CreateCollectionOp, DeleteCollectionOp, ReplicationLog,
+    replication_log, CreateCollectionOp, DeleteCollectionOp, ReplicationLog, RestoreCollectionOp,
+    TrashedCollection,
 };
+use hyperspace_store::wal::WalSyncMode;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use sysinfo::System;
-use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -31,11 +54,86 @@ pub enum ClusterRole {
     Standalone,
 }
 
+/// Per-collection settings chosen at creation and persisted in `meta.json`.
+#[derive(Debug, Clone, Default)]
+pub struct CollectionOptions {
+    pub snapshot: SnapshotPolicy,
+    pub limits: CollectionLimits,
+    pub info: CollectionInfo,
+    pub schema: Option<MetadataSchema>,
+    /// `scalar`, `binary` or `none`; unset follows `HS_QUANTIZATION_LEVEL`.
+    pub quantization: Option<String>,
+    pub hnsw: HnswParams,
+    /// IVF routing; `None` searches the graph alone.
+    pub ivf: Option<IvfParams>,
+    /// Unset follows the metric: `normalize` for cosine, `reject` otherwise.
+    pub normalization: Option<NormalizationPolicy>,
+    /// Collection holding the parent documents of this collection's chunks,
+    /// linked by their `parent_id` metadata.
+    pub parent: Option<String>,
+}
+
+/// Description and labels attached to a collection for governance and
+/// dashboard grouping. Not interpreted by the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionInfo {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl CollectionInfo {
+    /// Whether every label in `selector` is present with the same value.
+    pub fn matches(&self, selector: &HashMap<String, String>) -> bool {
+        selector
+            .iter()
+            .all(|(k, v)| self.labels.get(k).is_some_and(|l| l == v))
+    }
+
+    pub fn labels_map(&self) -> HashMap<String, String> {
+        self.labels
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+/// Size and shape of a collection as shown in listings.
+pub struct CollectionOverview {
+    pub count: usize,
+    pub dimension: usize,
+    pub metric: String,
+    pub indexing_queue: u64,
+}
+
 pub struct CollectionEntry {
     pub collection: Arc<dyn Collection>,
     pub last_accessed: AtomicU64,
 }
 
+/// Writes a final snapshot and drops `name` from the resident map.
+///
+/// Skipped while a caller still holds the collection or its indexing queue is
+/// non-empty, so a later wake never opens the same files twice.
+async fn evict_collection(collections: &DashMap<String, CollectionEntry>, name: &str) -> bool {
+    let Some(col) = collections.get(name).map(|e| e.collection.clone()) else {
+        return false;
+    };
+    if col.queue_size() > 0 {
+        return false;
+    }
+    if let Err(e) = col.snapshot().await {
+        eprintln!("Final snapshot of '{name}' failed, keeping it resident: {e}");
+        return false;
+    }
+    drop(col);
+
+    collections
+        .remove_if(name, |_, entry| Arc::strong_count(&entry.collection) == 1)
+        .is_some()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClusterState {
     pub node_id: String,
@@ -73,9 +171,41 @@ pub struct CollectionManager {
     base_path: PathBuf,
     // Stores entries with metadata (e.g., access time).
     collections: Arc<DashMap<String, CollectionEntry>>,
-    replication_tx: broadcast::Sender<ReplicationLog>,
+    replication_tx: ReplicationFeed,
     pub cluster_state: Arc<RwLock<ClusterState>>,
     pub system: Arc<Mutex<System>>,
+    // Billable units per tenant, recorded by the API handlers
+    pub meter: Arc<Meter>,
+    // Searches over HS_SLOW_QUERY_MS, for the admin API
+    pub slow_queries: Arc<SlowQueryLog>,
+    // CSV/Parquet imports started over HTTP, for GET /api/ingest
+    pub ingest_jobs: IngestJobs,
+    // Replication lag while following, gates reads past HS_FOLLOWER_MAX_STALENESS
+    pub follower_reads: FollowerReads,
+    // Leadership epoch stamped on replication streams; fences deposed leaders' writes
+    pub fencing: Fencing,
+    // Primary this node pulls WAL from as a warm standby (--standby-of)
+    pub standby: WalStandby,
+    // Cluster members seen over gossip (HS_GOSSIP_ENABLED), for GetTopology
+    pub peers: PeerRegistry,
+    // Cap on resident collections; LRU ones are closed past it (HS_MAX_RESIDENT_COLLECTIONS, 0 = unlimited)
+    max_resident: usize,
+    // Open collections on first access instead of at boot (HS_LAZY_LOAD)
+    lazy_load: bool,
+    // Serializes cold opens so concurrent requests never open the same files twice
+    load_lock: tokio::sync::Mutex<()>,
+    // Serializes read-modify-write of per-collection `queries.json`
+    templates_lock: Mutex<()>,
+    // Internal name -> running A/B experiment, `None` once a lookup found none
+    experiments: DashMap<String, Option<Arc<RunningExperiment>>>,
+    // Deleted collections are kept this long before purging (HS_TRASH_RETENTION_SEC, None = delete at once)
+    trash_retention: Option<Duration>,
+    // Internal name -> gRPC address of collections migrated away, persisted in `moved.json`
+    moved: DashMap<String, String>,
+    // Internal alias name -> internal collection name, persisted in `aliases.json`
+    aliases: DashMap<String, String>,
+    // "<internal name>@<id>" -> snapshot generation opened by a time-travel query
+    generations: DashMap<String, CollectionEntry>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -85,15 +215,52 @@ pub struct UserUsage {
     pub disk_usage_bytes: u64,
 }
 
+const ALIASES_FILE: &str = "aliases.json";
+
+/// Stands in for `/` in namespaced names on disk (`team/docs` is stored as
+/// `{user}_team~docs`), so every collection stays one flat directory.
+const NAMESPACE_SEP_ON_DISK: char = '~';
+
+/// Whether `name` lives under `namespace` (`team/project` covers
+/// `team/project/docs` and `team/project/a/b`). An empty namespace covers all.
+pub fn in_namespace(name: &str, namespace: &str) -> bool {
+    let namespace = namespace.trim_end_matches('/');
+    namespace.is_empty()
+        || name
+            .strip_prefix(namespace)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Checks a collection name, which may be a namespace path such as
+/// `team/project/docs`.
+pub fn validate_collection_name(name: &str) -> Result<(), String> {
+    if name.contains(NAMESPACE_SEP_ON_DISK) || name.contains('\\') {
+        return Err(format!(
+            "Collection name '{name}' must not contain '{NAMESPACE_SEP_ON_DISK}' or '\\'"
+        ));
+    }
+    if name
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(format!(
+            "Collection name '{name}' has an empty or relative path segment"
+        ));
+    }
+    Ok(())
+}
+
 impl CollectionManager {
-    fn get_internal_name(user_id: &str, collection_name: &str) -> String {
-        format!("{user_id}_{collection_name}")
+    pub(crate) fn get_internal_name(user_id: &str, collection_name: &str) -> String {
+        let on_disk = collection_name.replace('/', &NAMESPACE_SEP_ON_DISK.to_string());
+        format!("{user_id}_{on_disk}")
     }
 
-    pub fn new(base_path: PathBuf, replication_tx: broadcast::Sender<ReplicationLog>) -> Self {
+    pub fn new(base_path: PathBuf, replication_tx: impl Into<ReplicationFeed>) -> Self {
+        let replication_tx = replication_tx.into();
         // Try load cluster state
         let state_path = base_path.join("cluster.json");
-        let state = if state_path.exists() {
+        let mut state = if state_path.exists() {
             let data = fs::read_to_string(&state_path).unwrap_or_default();
             serde_json::from_str(&data).unwrap_or_else(|_| ClusterState::new())
         } else {
@@ -105,6 +272,11 @@ impl CollectionManager {
             }
             s
         };
+        // cluster.json keeps the clock it was created with; resume past what
+        // was already published so clocks never go back across a restart.
+        if let Some(newest) = replication_tx.journal().and_then(|j| j.newest_clock()) {
+            state.logical_clock = state.logical_clock.max(newest);
+        }
 
         let collections = Arc::new(DashMap::<String, CollectionEntry>::new());
         let mgr_map = collections.clone();
@@ -139,7 +311,7 @@ impl CollectionManager {
                 }
 
                 for key in to_remove {
-                    if mgr_map.remove(&key).is_some() {
+                    if evict_collection(&mgr_map, &key).await {
                         println!("💤 Idling collection '{key}' unloaded from memory");
                     }
                 }
@@ -160,12 +332,54 @@ impl CollectionManager {
             }
         });
 
+        let max_resident = std::env::var("HS_MAX_RESIDENT_COLLECTIONS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        let lazy_load = !std::env::var("HS_LAZY_LOAD")
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"));
+        // A redirect whose collection is still here was left by a migration
+        // that stopped before dropping it: the local copy stays authoritative.
+        let moved = fs::read_to_string(base_path.join("moved.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str::<BTreeMap<String, String>>(&s).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| !base_path.join(name).join("meta.json").exists())
+            .collect();
+        let aliases = fs::read_to_string(base_path.join(ALIASES_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str::<BTreeMap<String, String>>(&s).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let trash_retention = trash::retention_from_env();
+        if let Some(retention) = trash_retention {
+            trash::spawn_purger(base_path.clone(), retention);
+        }
+
         Self {
             base_path,
             collections,
             replication_tx,
             cluster_state: Arc::new(RwLock::new(state)),
             system,
+            meter: Arc::new(Meter::from_env()),
+            slow_queries: Arc::new(SlowQueryLog::from_env()),
+            ingest_jobs: IngestJobs::default(),
+            follower_reads: FollowerReads::from_env(),
+            fencing: Fencing::default(),
+            standby: WalStandby::default(),
+            peers: PeerRegistry::default(),
+            max_resident,
+            lazy_load,
+            load_lock: tokio::sync::Mutex::new(()),
+            templates_lock: Mutex::new(()),
+            experiments: DashMap::new(),
+            trash_retention,
+            moved,
+            aliases,
+            generations: DashMap::new(),
         }
     }
 
@@ -174,31 +388,112 @@ impl CollectionManager {
             fs::create_dir_all(&self.base_path)?;
         }
 
+        let mut found = 0usize;
         for entry in fs::read_dir(&self.base_path)? {
             let entry = entry?;
             let path = entry.path();
-            if path.is_dir() {
+            if path.is_dir() && entry.file_name() != trash::TRASH_DIR {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     // Load metadata to determine dimension and metric
 
                     if let Ok(meta) = CollectionMetadata::load(&path) {
+                        found += 1;
+                        if self.lazy_load {
+                            continue;
+                        }
                         self.instantiate_collection(name, meta).await?;
                         println!("Loaded collection: {name}");
+                        self.enforce_resident_limit(name).await;
                     } else {
                         eprintln!("Skipping unknown directory (no meta.json): {name}");
                     }
                 }
             }
         }
+        if self.lazy_load {
+            println!("Found {found} collection(s) on disk; opening on first access");
+        }
         Ok(())
     }
 
+    /// Opens a collection from disk unless it is already resident.
+    async fn wake(&self, internal_name: &str) -> Option<Arc<dyn Collection>> {
+        let _guard = self.load_lock.lock().await;
+        if let Some(entry) = self.collections.get(internal_name) {
+            return Some(entry.collection.clone());
+        }
+
+        let col_dir = self.base_path.join(internal_name);
+        let meta = CollectionMetadata::load(&col_dir).ok()?;
+        println!("🧊 Waking up cold collection: '{internal_name}'");
+        if let Err(e) = self.instantiate_collection(internal_name, meta).await {
+            eprintln!("Failed to revive cold collection '{internal_name}': {e}");
+            return None;
+        }
+        self.enforce_resident_limit(internal_name).await;
+
+        self.collections
+            .get(internal_name)
+            .map(|entry| entry.collection.clone())
+    }
+
+    /// Closes least-recently-used collections until at most `max_resident`
+    /// remain. `keep` (the one just opened) is never chosen.
+    async fn enforce_resident_limit(&self, keep: &str) {
+        if self.max_resident == 0 {
+            return;
+        }
+        let mut skipped = std::collections::HashSet::new();
+        while self.collections.len() > self.max_resident {
+            let victim = self
+                .collections
+                .iter()
+                .filter(|e| e.key() != keep && !skipped.contains(e.key()))
+                .min_by_key(|e| e.value().last_accessed.load(Ordering::Relaxed))
+                .map(|e| e.key().clone());
+            let Some(victim) = victim else {
+                break;
+            };
+            if evict_collection(&self.collections, &victim).await {
+                println!(
+                    "📤 Collection '{victim}' closed (resident limit {})",
+                    self.max_resident
+                );
+            } else {
+                skipped.insert(victim);
+            }
+        }
+    }
+
     async fn instantiate_collection(
         &self,
         name: &str,
         meta: CollectionMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let col_dir = self.base_path.join(name);
+        let collection = self
+            .open_collection(name, self.base_path.join(name), meta)
+            .await?;
+        // Writes without a journal leave their clock only in the collection
+        let written = collection.indexing_progress().written_clock;
+        {
+            let mut state = self.cluster_state.write().await;
+            state.logical_clock = state.logical_clock.max(written);
+        }
+        let entry = CollectionEntry {
+            collection,
+            last_accessed: AtomicU64::new(current_time_secs()),
+        };
+        self.collections.insert(name.to_string(), entry);
+        Ok(())
+    }
+
+    /// Opens the collection files in `col_dir` as described by `meta`.
+    async fn open_collection(
+        &self,
+        name: &str,
+        col_dir: PathBuf,
+        meta: CollectionMetadata,
+    ) -> Result<Arc<dyn Collection>, Box<dyn std::error::Error + Send + Sync>> {
         let wal_path = col_dir.join("wal.log");
         let quant_mode = meta.quantization_mode();
         let node_id = self.cluster_state.read().await.node_id.clone();
@@ -214,6 +509,14 @@ impl CollectionManager {
                         wal_path.clone(),
                         quant_mode,
                         self.replication_tx.clone(),
+                        meta.snapshot,
+                        meta.limits,
+                        meta.schema.clone(),
+                        meta.wal_sync_mode(),
+                        meta.hnsw,
+                        meta.ivf,
+                        meta.normalization,
+                        meta.synonyms.clone(),
                     )
                     .await?,
                 )
@@ -221,6 +524,19 @@ impl CollectionManager {
         }
 
         let collection: Arc<dyn Collection> = match (meta.dimension, meta.metric.as_str()) {
+            // Text-only: metadata and BM25 without vectors
+            (0, _) => Arc::new(TextCollection::open(
+                name.to_string(),
+                node_id.clone(),
+                col_dir.clone(),
+                self.replication_tx.clone(),
+                meta.snapshot,
+                meta.limits,
+                meta.schema.clone(),
+                meta.wal_sync_mode(),
+                meta.synonyms.clone(),
+            )?),
+
             // Hyperbolic (Poincaré)
             (4, "poincare") => inst!(4, PoincareMetric),
             (8, "poincare") => inst!(8, PoincareMetric),
@@ -280,15 +596,129 @@ impl CollectionManager {
                 .into());
             }
         };
+        Ok(collection)
+    }
 
-        let entry = CollectionEntry {
-            collection,
-            last_accessed: AtomicU64::new(current_time_secs()),
-        };
-        self.collections.insert(name.to_string(), entry);
+    /// Ids of a collection's snapshot generations, oldest first.
+    pub fn snapshot_generations(&self, user_id: &str, name: &str) -> HyperspaceResult<Vec<u64>> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        Ok(snapshot_history::generations(&dir))
+    }
+
+    /// Makes snapshot generation `version` the live state of a collection.
+    /// The current state is snapshotted first, which keeps it as the newest
+    /// generation to roll forward to. Not replicated.
+    pub async fn rollback_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+        version: u64,
+    ) -> HyperspaceResult<()> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        let internal_name = self.resolve_alias(Self::get_internal_name(user_id, name));
+        if CollectionMetadata::load(&dir)?.snapshot.history() == 0 {
+            return Err(HyperspaceError::Validation(format!(
+                "Collection '{name}' keeps no snapshot history"
+            )));
+        }
+        snapshot_history::resolve(&dir, SnapshotRef::Id(version))?;
+
+        let _guard = self.load_lock.lock().await;
+        let resident = self
+            .collections
+            .get(&internal_name)
+            .map(|entry| entry.collection.clone());
+        if let Some(col) = resident {
+            col.snapshot().await?;
+            drop(col);
+            if self
+                .collections
+                .remove_if(&internal_name, |_, entry| {
+                    Arc::strong_count(&entry.collection) == 1
+                })
+                .is_none()
+            {
+                return Err(HyperspaceError::Precondition(format!(
+                    "Collection '{name}' is in use; retry the rollback"
+                )));
+            }
+        }
+
+        let restore_dir = dir.clone();
+        tokio::task::spawn_blocking(move || snapshot_history::restore(&restore_dir, version))
+            .await
+            .map_err(|e| HyperspaceError::Internal(e.to_string()))??;
+        println!("⏪ Collection '{internal_name}' rolled back to snapshot {version}");
+        let meta = CollectionMetadata::load(&dir)?;
+        self.instantiate_collection(&internal_name, meta)
+            .await
+            .map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+        self.enforce_resident_limit(&internal_name).await;
         Ok(())
     }
 
+    /// Closes the open snapshot generations of `internal_name`, so none is
+    /// served from memory after its files are gone.
+    pub fn close_generations(&self, internal_name: &str) {
+        let prefix = format!("{internal_name}@");
+        self.generations.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    /// A collection as of one of its snapshot generations, opened on first
+    /// use. Only its read paths are exposed.
+    pub async fn historical(
+        &self,
+        user_id: &str,
+        name: &str,
+        snapshot: SnapshotRef,
+    ) -> HyperspaceResult<Arc<dyn Collection>> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        let id = snapshot_history::resolve(&dir, snapshot)?;
+        let key = format!(
+            "{}@{id}",
+            self.resolve_alias(Self::get_internal_name(user_id, name))
+        );
+        if let Some(entry) = self.generations.get(&key) {
+            entry
+                .last_accessed
+                .store(current_time_secs(), Ordering::Relaxed);
+            return Ok(entry.collection.clone());
+        }
+
+        let _guard = self.load_lock.lock().await;
+        if let Some(entry) = self.generations.get(&key) {
+            return Ok(entry.collection.clone());
+        }
+        let mut meta = CollectionMetadata::load(&dir)?;
+        // A generation never records generations of its own
+        meta.snapshot.history = Some(0);
+        println!("🕰️ Opening snapshot generation '{key}'");
+        let collection = self
+            .open_collection(&key, snapshot_history::generation_dir(&dir, id), meta)
+            .await
+            .map_err(|e| HyperspaceError::Internal(format!("Failed to open snapshot {id}: {e}")))?;
+        self.generations.insert(
+            key.clone(),
+            CollectionEntry {
+                collection: collection.clone(),
+                last_accessed: AtomicU64::new(current_time_secs()),
+            },
+        );
+        while self.generations.len() > snapshot_history::MAX_OPEN {
+            let victim = self
+                .generations
+                .iter()
+                .filter(|e| e.key() != &key)
+                .min_by_key(|e| e.value().last_accessed.load(Ordering::Relaxed))
+                .map(|e| e.key().clone());
+            let Some(victim) = victim else {
+                break;
+            };
+            self.generations.remove(&victim);
+        }
+        Ok(collection)
+    }
+
     pub async fn create_collection(
         &self,
         user_id: &str,
@@ -296,8 +726,36 @@ impl CollectionManager {
         dimension: u32,
         metric: &str,
     ) -> Result<(), String> {
+        self.create_collection_with_options(
+            user_id,
+            name,
+            dimension,
+            metric,
+            CollectionOptions::default(),
+        )
+        .await
+    }
+
+    /// Like [`Self::create_collection`], but persists per-collection snapshot
+    /// cadence, resource limits and metadata schema.
+    pub async fn create_collection_with_options(
+        &self,
+        user_id: &str,
+        name: &str,
+        dimension: u32,
+        metric: &str,
+        options: CollectionOptions,
+    ) -> Result<(), String> {
+        validate_collection_name(name)?;
+        if let Some(parent) = &options.parent {
+            if parent == name {
+                return Err("A collection can't be its own parent".to_string());
+            }
+            self.existing_collection_dir(user_id, parent)
+                .map_err(|_| format!("Parent collection '{parent}' not found"))?;
+        }
         let internal_name = Self::get_internal_name(user_id, name);
-        self.create_collection_internal(&internal_name, dimension, metric, true)
+        self.create_collection_internal(&internal_name, dimension, metric, options, true)
             .await
     }
 
@@ -306,9 +764,19 @@ impl CollectionManager {
         name: &str,
         dimension: u32,
         metric: &str,
+        info: CollectionInfo,
     ) -> Result<(), String> {
-        self.create_collection_internal(name, dimension, metric, false)
-            .await
+        self.create_collection_internal(
+            name,
+            dimension,
+            metric,
+            CollectionOptions {
+                info,
+                ..CollectionOptions::default()
+            },
+            false,
+        )
+        .await
     }
 
     pub async fn rebuild_collection(&self, user_id: &str, name: &str) -> Result<(), String> {
@@ -322,12 +790,9 @@ impl CollectionManager {
         name: &str,
         filter: Option<VacuumFilterQuery>,
     ) -> Result<(), String> {
-        let internal_name = Self::get_internal_name(user_id, name);
         // Trigger optimization (Hot Vacuum)
-        if let Some(entry) = self.collections.get(&internal_name) {
-            entry
-                .collection
-                .optimize_with_filter(filter)
+        if let Some(col) = self.get(user_id, name).await {
+            col.optimize_with_filter(filter)
                 .await
                 .map_err(|e| format!("Optimization failed: {e}"))?;
             Ok(())
@@ -336,6 +801,47 @@ impl CollectionManager {
         }
     }
 
+    /// Directory holding every collection.
+    pub fn data_dir(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Collections currently open, of every tenant.
+    pub fn resident_collections(&self) -> Vec<Arc<dyn Collection>> {
+        self.collections
+            .iter()
+            .map(|entry| entry.value().collection.clone())
+            .collect()
+    }
+
+    /// Forces an immediate snapshot of one collection, or of every resident
+    /// collection of `user_id` when `name` is `None`. Returns how many were
+    /// written.
+    pub async fn snapshot_collections(
+        &self,
+        user_id: &str,
+        name: Option<&str>,
+    ) -> HyperspaceResult<usize> {
+        let targets: Vec<Arc<dyn Collection>> = if let Some(name) = name {
+            let col = self
+                .get(user_id, name)
+                .await
+                .ok_or_else(|| HyperspaceError::NotFound(format!("Collection '{name}'")))?;
+            vec![col]
+        } else {
+            let prefix = format!("{user_id}_");
+            self.collections
+                .iter()
+                .filter(|entry| entry.key().starts_with(&prefix))
+                .map(|entry| entry.value().collection.clone())
+                .collect()
+        };
+        for col in &targets {
+            col.snapshot().await?;
+        }
+        Ok(targets.len())
+    }
+
     pub fn get_collection_counts(&self) -> (usize, usize) {
         // Active: currently in DashMap (RAM)
         let active = self.collections.len();
@@ -343,7 +849,8 @@ impl CollectionManager {
         // Total: count directories in data folder
         let total = match std::fs::read_dir(&self.base_path) {
             Ok(entries) => entries
-                .filter(|e| e.is_ok() && e.as_ref().unwrap().path().is_dir())
+                .flatten()
+                .filter(|e| e.path().is_dir() && e.file_name() != trash::TRASH_DIR)
                 .count(),
             Err(_) => 0,
         };
@@ -358,32 +865,56 @@ impl CollectionManager {
         name: &str,
         dimension: u32,
         metric: &str,
+        options: CollectionOptions,
         replicate: bool,
     ) -> Result<(), String> {
-        if self.collections.contains_key(name) {
+        let col_dir = self.base_path.join(name);
+        if self.collections.contains_key(name) || col_dir.join("meta.json").exists() {
             return Err(format!("Collection '{name}' already exists"));
         }
+        if self.aliases.contains_key(name) {
+            return Err(format!(
+                "'{name}' is already an alias of another collection"
+            ));
+        }
+        if let Some(policy) = options.normalization {
+            policy.validate_for(metric)?;
+        }
 
-        let col_dir = self.base_path.join(name);
         if !col_dir.exists() {
             fs::create_dir_all(&col_dir).map_err(|e| e.to_string())?;
         }
 
-        let quantization = std::env::var("HS_QUANTIZATION_LEVEL")
-            .unwrap_or("scalar".to_string())
-            .to_lowercase();
+        let quantization = options.quantization.unwrap_or_else(|| {
+            std::env::var("HS_QUANTIZATION_LEVEL")
+                .unwrap_or("scalar".to_string())
+                .to_lowercase()
+        });
 
         let meta = CollectionMetadata {
             dimension,
             metric: metric.to_string(),
             quantization,
+            snapshot: options.snapshot,
+            limits: options.limits,
+            wal_sync_mode: None,
+            schema: options.schema,
+            info: options.info.clone(),
+            hnsw: options.hnsw,
+            ivf: options.ivf,
+            normalization: options.normalization,
+            synonyms: SynonymMap::default(),
+            parent: options.parent,
         };
 
         meta.save(&col_dir).map_err(|e| e.to_string())?;
+        self.set_moved_internal(name, None)
+            .map_err(|e| e.to_string())?;
 
         self.instantiate_collection(name, meta)
             .await
             .map_err(|e| e.to_string())?;
+        self.enforce_resident_limit(name).await;
 
         if replicate {
             // Broadcast replication event
@@ -396,23 +927,35 @@ impl CollectionManager {
                     CreateCollectionOp {
                         dimension,
                         metric: metric.to_string(),
+                        description: options.info.description,
+                        labels: options.info.labels.into_iter().collect(),
                     },
                 )),
+                ..Default::default()
             };
-            let _ = self.replication_tx.send(log);
+            self.replication_tx.publish(log);
         }
 
         Ok(())
     }
 
     pub async fn get_internal(&self, internal_name: &str) -> Option<Arc<dyn Collection>> {
-        self.collections
-            .get(internal_name)
-            .map(|c| c.value().collection.clone())
+        if let Some(entry) = self.collections.get(internal_name) {
+            entry
+                .last_accessed
+                .store(current_time_secs(), Ordering::Relaxed);
+            return Some(entry.collection.clone());
+        }
+        self.wake(internal_name).await
     }
 
     pub async fn get(&self, user_id: &str, name: &str) -> Option<Arc<dyn Collection>> {
-        let internal_name = Self::get_internal_name(user_id, name);
+        let internal_name = self.resolve_alias(Self::get_internal_name(user_id, name));
+        // Mid-migration: requests follow the redirect while the switch-over
+        // replays the last writes.
+        if self.moved.contains_key(&internal_name) {
+            return None;
+        }
 
         // 1. Fast path: Check memory
         if let Some(entry) = self.collections.get(&internal_name) {
@@ -424,23 +967,7 @@ impl CollectionManager {
         }
 
         // 2. Slow path: Check disk (Lazy Loading) - Wake up cold collection
-        let col_dir = self.base_path.join(&internal_name);
-        if col_dir.exists() && col_dir.join("meta.json").exists() {
-            // Try to load metadata and revive collection
-            if let Ok(meta) = CollectionMetadata::load(&col_dir) {
-                println!("🧊 Waking up cold collection: '{internal_name}'");
-                if let Ok(()) = self.instantiate_collection(&internal_name, meta).await {
-                    // Check map again after loading
-                    if let Some(entry) = self.collections.get(&internal_name) {
-                        return Some(entry.collection.clone());
-                    }
-                } else {
-                    eprintln!("Failed to revive cold collection '{internal_name}'");
-                }
-            }
-        }
-
-        None
+        self.wake(&internal_name).await
     }
 
     pub fn list(&self, user_id: &str) -> Vec<String> {
@@ -454,7 +981,7 @@ impl CollectionManager {
                     .key()
                     .strip_prefix(&prefix)
                     .unwrap_or(entry.key())
-                    .to_string()
+                    .replace(NAMESPACE_SEP_ON_DISK, "/")
             })
             .collect();
 
@@ -464,7 +991,7 @@ impl CollectionManager {
                 if let Ok(name) = entry.file_name().into_string() {
                     if name.starts_with(&prefix) && entry.path().is_dir() {
                         if let Some(stripped) = name.strip_prefix(&prefix) {
-                            collections.insert(stripped.to_string());
+                            collections.insert(stripped.replace(NAMESPACE_SEP_ON_DISK, "/"));
                         }
                     }
                 }
@@ -476,29 +1003,125 @@ impl CollectionManager {
         list
     }
 
-    pub async fn list_detailed(
+    /// Collections of `user_id` under `namespace`, sorted.
+    pub fn list_namespace(&self, user_id: &str, namespace: &str) -> Vec<String> {
+        let mut names = self.list(user_id);
+        names.retain(|name| in_namespace(name, namespace));
+        names
+    }
+
+    /// Summaries of the collections under `namespace` whose labels match
+    /// `labels`.
+    pub fn list_detailed_in(
         &self,
         user_id: &str,
+        namespace: &str,
+        labels: &HashMap<String, String>,
     ) -> Vec<hyperspace_proto::hyperspace::CollectionSummary> {
-        let names = self.list(user_id);
+        let names = self.list_namespace(user_id, namespace);
         let mut summaries = Vec::new();
         for name in names {
-            if let Some(col) = self.get(user_id, &name).await {
+            let info = self.collection_info(user_id, &name);
+            if !info.matches(labels) {
+                continue;
+            }
+            if let Some(overview) = self.collection_overview(user_id, &name) {
                 summaries.push(hyperspace_proto::hyperspace::CollectionSummary {
                     name: name.clone(),
-                    count: col.count() as u64,
-                    dimension: col.dimension() as u32,
-                    metric: col.metric_name().to_string(),
+                    count: overview.count as u64,
+                    dimension: overview.dimension as u32,
+                    metric: overview.metric,
+                    labels: info.labels_map(),
+                    description: info.description,
                 });
             }
         }
         summaries
     }
 
+    /// Live count, dimension and metric of a resident collection. A cold one
+    /// is not woken: its shape comes from `meta.json` and its count from the
+    /// last snapshot's `state.json`, so writes since then are not included.
+    pub fn collection_overview(&self, user_id: &str, name: &str) -> Option<CollectionOverview> {
+        let internal_name = Self::get_internal_name(user_id, name);
+        if self.moved.contains_key(&internal_name) {
+            return None;
+        }
+        if let Some(entry) = self.collections.get(&internal_name) {
+            let col = &entry.collection;
+            return Some(CollectionOverview {
+                count: col.count(),
+                dimension: col.dimension(),
+                metric: col.metric_name().to_string(),
+                indexing_queue: col.queue_size(),
+            });
+        }
+
+        let dir = self.base_path.join(&internal_name);
+        let meta = CollectionMetadata::load(&dir).ok()?;
+        let count = fs::read_to_string(dir.join("state.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str::<CollectionState>(&s).ok())
+            .map_or(0, |state| state.id_map.len());
+        let metric = match (meta.dimension, meta.metric.as_str()) {
+            (0, _) => "bm25".to_string(),
+            (_, "euclidean") => "l2".to_string(),
+            (_, metric) => metric.to_string(),
+        };
+        Some(CollectionOverview {
+            count,
+            dimension: meta.dimension as usize,
+            metric,
+            indexing_queue: 0,
+        })
+    }
+
+    /// Description and labels from the collection's `meta.json`; empty if
+    /// the collection does not exist or predates them.
+    pub fn collection_info(&self, user_id: &str, name: &str) -> CollectionInfo {
+        let dir = self.base_path.join(Self::get_internal_name(user_id, name));
+        CollectionMetadata::load(&dir).map_or_else(|_| CollectionInfo::default(), |meta| meta.info)
+    }
+
+    /// Internal names of every collection of every tenant, resident or cold.
     pub fn list_all(&self) -> Vec<String> {
-        self.collections
+        let mut names: std::collections::BTreeSet<String> = self
+            .collections
             .iter()
             .map(|entry| entry.key().clone())
+            .collect();
+        if let Ok(entries) = std::fs::read_dir(&self.base_path) {
+            for entry in entries.flatten() {
+                if entry.file_name() == trash::TRASH_DIR || !entry.path().join("meta.json").exists()
+                {
+                    continue;
+                }
+                if let Ok(name) = entry.file_name().into_string() {
+                    names.insert(name);
+                }
+            }
+        }
+        names.into_iter().collect()
+    }
+
+    /// Latest drift score of every resident collection that has one.
+    pub fn drift_scores(&self) -> Vec<(String, f64)> {
+        self.collections
+            .iter()
+            .filter_map(|entry| {
+                let score = entry.value().collection.drift_score()?;
+                Some((entry.key().clone(), score))
+            })
+            .collect()
+    }
+
+    pub fn effective_ef_search(&self) -> Vec<(String, usize)> {
+        self.collections
+            .iter()
+            .filter_map(|entry| {
+                let ef = entry.value().collection.effective_ef_search()?;
+                Some((entry.key().clone(), ef))
+            })
             .collect()
     }
 
@@ -524,6 +1147,27 @@ impl CollectionManager {
         self.delete_collection_internal(&internal_name, true).await
     }
 
+    /// Deletes every collection under `namespace` and returns their names.
+    /// The empty namespace is refused so a typo cannot wipe a tenant.
+    pub async fn delete_namespace(
+        &self,
+        user_id: &str,
+        namespace: &str,
+    ) -> HyperspaceResult<Vec<String>> {
+        if namespace.trim_end_matches('/').is_empty() {
+            return Err(HyperspaceError::Validation(
+                "Namespace cannot be empty".to_string(),
+            ));
+        }
+        let names = self.list_namespace(user_id, namespace);
+        for name in &names {
+            self.delete_collection(user_id, name)
+                .await
+                .map_err(HyperspaceError::Internal)?;
+        }
+        Ok(names)
+    }
+
     pub async fn delete_collection_from_replication(&self, name: &str) -> Result<(), String> {
         self.delete_collection_internal(name, false).await
     }
@@ -535,11 +1179,20 @@ impl CollectionManager {
         if let Some((_, _col)) = self.collections.remove(name) {
             found = true;
         }
+        self.experiments.remove(name);
+        let prefix = format!("{name}@");
+        self.generations.retain(|key, _| !key.starts_with(&prefix));
+        self.set_aliases_internal(name, &[])
+            .map_err(|e| e.to_string())?;
 
         // 2. Cleanup files (handles cold storage too)
         let col_dir = self.base_path.join(name);
         if col_dir.exists() {
-            fs::remove_dir_all(col_dir).map_err(|e| e.to_string())?;
+            if self.trash_retention.is_some() {
+                trash::move_to_trash(&self.base_path, name).map_err(|e| e.to_string())?;
+            } else {
+                fs::remove_dir_all(col_dir).map_err(|e| e.to_string())?;
+            }
             found = true;
         }
 
@@ -553,14 +1206,744 @@ impl CollectionManager {
                 operation: Some(replication_log::Operation::DeleteCollection(
                     DeleteCollectionOp {},
                 )),
+                ..Default::default()
             };
-            let _ = self.replication_tx.send(log);
+            self.replication_tx.publish(log);
         }
 
         // Idempotent: return success even if not found
         Ok(())
     }
 
+    /// Collections of `user_id` waiting in the trash, oldest first.
+    pub fn list_trash(&self, user_id: &str) -> Vec<TrashedCollection> {
+        let prefix = format!("{user_id}_");
+        let retention = self.trash_retention.unwrap_or_default().as_secs();
+        trash::list(&self.base_path)
+            .into_iter()
+            .filter_map(|entry| {
+                let name = entry.internal_name.strip_prefix(&prefix)?;
+                Some(TrashedCollection {
+                    name: name.replace(NAMESPACE_SEP_ON_DISK, "/"),
+                    deleted_at: entry.deleted_at,
+                    purge_at: entry.deleted_at + retention,
+                })
+            })
+            .collect()
+    }
+
+    /// Brings back the most recently deleted copy of `name` from the trash.
+    pub async fn restore_collection(&self, user_id: &str, name: &str) -> HyperspaceResult<()> {
+        let internal_name = Self::get_internal_name(user_id, name);
+        self.restore_collection_internal(&internal_name, true).await
+    }
+
+    pub async fn restore_collection_from_replication(&self, name: &str) -> HyperspaceResult<()> {
+        self.restore_collection_internal(name, false).await
+    }
+
+    async fn restore_collection_internal(
+        &self,
+        name: &str,
+        replicate: bool,
+    ) -> HyperspaceResult<()> {
+        {
+            // Keeps a concurrent create or wake from racing the rename.
+            let _guard = self.load_lock.lock().await;
+            if self.collections.contains_key(name)
+                || self.base_path.join(name).join("meta.json").exists()
+            {
+                return Err(HyperspaceError::Precondition(format!(
+                    "Collection '{name}' already exists"
+                )));
+            }
+            if !trash::restore(&self.base_path, name)? {
+                return Err(HyperspaceError::NotFound(format!(
+                    "Collection '{name}' is not in the trash"
+                )));
+            }
+        }
+        self.set_moved_internal(name, None)?;
+
+        if replicate {
+            let clock = self.tick_cluster_clock().await;
+            let log = ReplicationLog {
+                logical_clock: clock,
+                origin_node_id: self.cluster_state.read().await.node_id.clone(),
+                collection: name.to_string(),
+                operation: Some(replication_log::Operation::RestoreCollection(
+                    RestoreCollectionOp {},
+                )),
+                ..Default::default()
+            };
+            self.replication_tx.publish(log);
+        }
+        Ok(())
+    }
+
+    /// Live feed of replication entries, e.g. to catch writes during a
+    /// migration.
+    pub fn subscribe_replication(&self) -> tokio::sync::broadcast::Receiver<ReplicationLog> {
+        self.replication_tx.subscribe()
+    }
+
+    /// Address `name` was migrated to, if it was.
+    pub fn moved_to(&self, user_id: &str, name: &str) -> Option<String> {
+        self.moved
+            .get(&Self::get_internal_name(user_id, name))
+            .map(|addr| addr.clone())
+    }
+
+    /// Records that `name` now lives at `addr` (`None` clears it).
+    pub fn set_moved(&self, user_id: &str, name: &str, addr: Option<&str>) -> std::io::Result<()> {
+        self.set_moved_internal(&Self::get_internal_name(user_id, name), addr)
+    }
+
+    fn set_moved_internal(&self, internal_name: &str, addr: Option<&str>) -> std::io::Result<()> {
+        let changed = match addr {
+            Some(addr) => {
+                self.moved
+                    .insert(internal_name.to_string(), addr.to_string())
+                    .as_deref()
+                    != Some(addr)
+            }
+            None => self.moved.remove(internal_name).is_some(),
+        };
+        if !changed {
+            return Ok(());
+        }
+        let all: BTreeMap<String, String> = self
+            .moved
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        fs::write(
+            self.base_path.join("moved.json"),
+            serde_json::to_string_pretty(&all)?,
+        )
+    }
+
+    /// The collection `internal_name` is an alias of, or the name itself.
+    fn resolve_alias(&self, internal_name: String) -> String {
+        match self.aliases.get(&internal_name) {
+            Some(target) => target.clone(),
+            None => internal_name,
+        }
+    }
+
+    /// Internal names of the aliases pointing at `target`, sorted.
+    fn aliases_of(&self, target: &str) -> Vec<String> {
+        let mut aliases: Vec<String> = self
+            .aliases
+            .iter()
+            .filter(|e| e.value() == target)
+            .map(|e| e.key().clone())
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    /// Makes `aliases` exactly the aliases of `target`.
+    fn set_aliases_internal(&self, target: &str, aliases: &[String]) -> std::io::Result<()> {
+        let current = self.aliases_of(target);
+        if current == aliases {
+            return Ok(());
+        }
+        for alias in current {
+            self.aliases.remove(&alias);
+        }
+        for alias in aliases {
+            self.aliases.insert(alias.clone(), target.to_string());
+        }
+        let all: BTreeMap<String, String> = self
+            .aliases
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        let tmp = self.base_path.join(format!("{ALIASES_FILE}.tmp"));
+        fs::write(&tmp, serde_json::to_string_pretty(&all)?)?;
+        fs::rename(tmp, self.base_path.join(ALIASES_FILE))
+    }
+
+    /// Converges `spec.name` to `spec`: creates it when missing, otherwise
+    /// updates description, labels and aliases. With `dry_run` only reports
+    /// what would change.
+    pub async fn apply_collection_spec(
+        &self,
+        user_id: &str,
+        mut spec: CollectionSpec,
+        dry_run: bool,
+    ) -> HyperspaceResult<ApplyOutcome> {
+        validate_collection_name(&spec.name).map_err(HyperspaceError::Validation)?;
+        spec.normalize()?;
+        let internal_name = Self::get_internal_name(user_id, &spec.name);
+        if self.aliases.contains_key(&internal_name) {
+            return Err(HyperspaceError::Validation(format!(
+                "'{}' is an alias, not a collection",
+                spec.name
+            )));
+        }
+        let mut aliases = Vec::with_capacity(spec.aliases.len());
+        for alias in &spec.aliases {
+            validate_collection_name(alias).map_err(HyperspaceError::Validation)?;
+            let internal_alias = Self::get_internal_name(user_id, alias);
+            if internal_alias == internal_name
+                || self
+                    .base_path
+                    .join(&internal_alias)
+                    .join("meta.json")
+                    .exists()
+            {
+                return Err(HyperspaceError::Validation(format!(
+                    "Alias '{alias}' is the name of a collection"
+                )));
+            }
+            if self
+                .aliases
+                .get(&internal_alias)
+                .is_some_and(|target| *target != internal_name)
+            {
+                return Err(HyperspaceError::Validation(format!(
+                    "Alias '{alias}' already points to another collection"
+                )));
+            }
+            aliases.push(internal_alias);
+        }
+        aliases.sort();
+
+        let dir = self.base_path.join(&internal_name);
+        let info = CollectionInfo {
+            description: spec.description.clone(),
+            labels: spec.labels.clone(),
+        };
+        let mut outcome = ApplyOutcome::default();
+        if !dir.join("meta.json").exists() {
+            outcome.created = true;
+            outcome.changes.push(format!(
+                "create {} ({} dims, {})",
+                spec.name, spec.dimension, spec.metric
+            ));
+            if !spec.aliases.is_empty() {
+                outcome
+                    .changes
+                    .push(format!("aliases: {}", spec.aliases.join(", ")));
+            }
+            if dry_run {
+                return Ok(outcome);
+            }
+            let options = CollectionOptions {
+                info,
+                quantization: (!spec.quantization.is_empty()).then(|| spec.quantization.clone()),
+                hnsw: spec.hnsw,
+                ..CollectionOptions::default()
+            };
+            self.create_collection_internal(
+                &internal_name,
+                spec.dimension,
+                &spec.metric,
+                options,
+                true,
+            )
+            .await
+            .map_err(HyperspaceError::Validation)?;
+            self.set_aliases_internal(&internal_name, &aliases)?;
+            return Ok(outcome);
+        }
+
+        let _guard = self.load_lock.lock().await;
+        let mut meta = CollectionMetadata::load(&dir)?;
+        let drift = collection_spec::fixed_field_drift(
+            &spec,
+            meta.dimension,
+            &meta.metric,
+            meta.quantization_mode(),
+            meta.hnsw,
+        );
+        if !drift.is_empty() {
+            return Err(HyperspaceError::Validation(format!(
+                "Collection '{}' can't change {} in place; migrate it to a new collection",
+                spec.name,
+                drift.join(", ")
+            )));
+        }
+        if meta.info.description != info.description {
+            outcome.changes.push("description".to_string());
+        }
+        if meta.info.labels != info.labels {
+            outcome.changes.push("labels".to_string());
+        }
+        let current = self.aliases_of(&internal_name);
+        if current != aliases {
+            let shown = |names: &[String]| {
+                names
+                    .iter()
+                    .map(|n| Self::display_name(user_id, n))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            outcome.changes.push(format!(
+                "aliases: [{}] -> [{}]",
+                shown(&current),
+                shown(&aliases)
+            ));
+        }
+        if dry_run || outcome.changes.is_empty() {
+            return Ok(outcome);
+        }
+        if meta.info != info {
+            meta.info = info;
+            meta.save(&dir)?;
+        }
+        self.set_aliases_internal(&internal_name, &aliases)?;
+        Ok(outcome)
+    }
+
+    /// The current state of the collections of `user_id` under `namespace`
+    /// (empty for all) as specs.
+    pub fn collection_specs(&self, user_id: &str, namespace: &str) -> Vec<CollectionSpec> {
+        self.list(user_id)
+            .into_iter()
+            .filter(|name| in_namespace(name, namespace))
+            .filter_map(|name| {
+                let internal_name = Self::get_internal_name(user_id, &name);
+                let meta = CollectionMetadata::load(&self.base_path.join(&internal_name)).ok()?;
+                Some(CollectionSpec {
+                    dimension: meta.dimension,
+                    quantization: collection_spec::quantization_name(meta.quantization_mode())
+                        .to_string(),
+                    hnsw: meta.hnsw,
+                    aliases: self
+                        .aliases_of(&internal_name)
+                        .iter()
+                        .map(|a| Self::display_name(user_id, a))
+                        .collect(),
+                    description: meta.info.description,
+                    labels: meta.info.labels,
+                    metric: meta.metric,
+                    name,
+                })
+            })
+            .collect()
+    }
+
+    /// The name `user_id` knows `internal_name` by.
+    pub(crate) fn display_name(user_id: &str, internal_name: &str) -> String {
+        internal_name
+            .strip_prefix(&format!("{user_id}_"))
+            .unwrap_or(internal_name)
+            .replace(NAMESPACE_SEP_ON_DISK, "/")
+    }
+
+    /// Snapshots `name` and writes its directory as a bundle to `out`.
+    /// Writes landing while the bundle is written may or may not be in it.
+    pub async fn export_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+        out: &Path,
+    ) -> HyperspaceResult<()> {
+        let col = self
+            .get(user_id, name)
+            .await
+            .ok_or_else(|| HyperspaceError::NotFound(format!("Collection '{name}' not found")))?;
+        col.snapshot().await?;
+        let dir = self.base_path.join(Self::get_internal_name(user_id, name));
+        let out = out.to_path_buf();
+        tokio::task::spawn_blocking(move || bundle::pack(&dir, &out))
+            .await
+            .map_err(|e| HyperspaceError::Internal(e.to_string()))?
+    }
+
+    /// Creates collection `name` from a bundle written by
+    /// [`Self::export_collection`]. The import is local to this node and is
+    /// not replicated.
+    pub async fn import_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+        archive: &Path,
+    ) -> HyperspaceResult<()> {
+        validate_collection_name(name).map_err(HyperspaceError::Validation)?;
+        let internal_name = Self::get_internal_name(user_id, name);
+        let target = self.base_path.join(&internal_name);
+        if self.collections.contains_key(&internal_name) || target.exists() {
+            return Err(HyperspaceError::Validation(format!(
+                "Collection '{name}' already exists"
+            )));
+        }
+
+        self.experiments.remove(&internal_name);
+
+        // Unpack beside the target so a half-written bundle is never opened.
+        let staging = self.base_path.join(format!(".import-{}", Uuid::new_v4()));
+        let (archive, staged) = (archive.to_path_buf(), staging.clone());
+        let unpacked = tokio::task::spawn_blocking(move || {
+            bundle::unpack(&archive, &staged)?;
+            CollectionMetadata::load(&staged)
+                .map(|_| ())
+                .map_err(|e| HyperspaceError::Validation(format!("Bundle meta.json: {e}")))
+        })
+        .await
+        .map_err(|e| HyperspaceError::Internal(e.to_string()));
+        if let Err(e) = unpacked.and_then(|r| r) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+
+        {
+            let _guard = self.load_lock.lock().await;
+            if target.exists() {
+                let _ = fs::remove_dir_all(&staging);
+                return Err(HyperspaceError::Validation(format!(
+                    "Collection '{name}' already exists"
+                )));
+            }
+            fs::rename(&staging, &target)?;
+        }
+        self.set_moved_internal(&internal_name, None)?;
+        if self.get(user_id, name).await.is_none() {
+            let _ = fs::remove_dir_all(&target);
+            return Err(HyperspaceError::Validation(format!(
+                "Bundle for '{name}' could not be opened"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes the live points of `name` to `out` as an Arrow IPC or Parquet
+    /// file. Returns the number of points written.
+    pub async fn export_columnar(
+        &self,
+        user_id: &str,
+        name: &str,
+        format: ColumnarFormat,
+        out: &Path,
+    ) -> HyperspaceResult<u64> {
+        let col = self
+            .get(user_id, name)
+            .await
+            .ok_or_else(|| HyperspaceError::NotFound(format!("Collection '{name}' not found")))?;
+        let (name, out) = (name.to_string(), out.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            let mut offset = 0;
+            columnar::write(
+                &out,
+                format,
+                &name,
+                col.dimension(),
+                col.metric_name(),
+                || {
+                    let page = col.peek(columnar::BATCH_ROWS, offset);
+                    offset += page.len();
+                    page
+                },
+            )
+        })
+        .await
+        .map_err(|e| HyperspaceError::Internal(e.to_string()))?
+    }
+
+    /// Upserts the rows of an Arrow IPC or Parquet file into `name`, and
+    /// deletes the ids of rows marked deleted. A missing collection is created
+    /// with the dimension and metric in the file's schema metadata. Returns
+    /// the number of rows applied; on a malformed row the import stops, and
+    /// the batches before it stay applied.
+    pub async fn import_columnar(
+        &self,
+        user_id: &str,
+        name: &str,
+        format: ColumnarFormat,
+        path: &Path,
+    ) -> HyperspaceResult<u64> {
+        let path = path.to_path_buf();
+        let mut reader = tokio::task::spawn_blocking(move || columnar::Reader::open(&path, format))
+            .await
+            .map_err(|e| HyperspaceError::Internal(e.to_string()))??;
+        let header = reader.header().clone();
+        let col = if let Some(col) = self.get(user_id, name).await {
+            col
+        } else {
+            let (Some(dimension), Some(metric)) = (header.dimension, &header.metric) else {
+                return Err(HyperspaceError::NotFound(format!(
+                    "Collection '{name}' not found, and the file doesn't name a dimension and metric to create it with"
+                )));
+            };
+            self.create_collection(user_id, name, dimension as u32, metric)
+                .await
+                .map_err(HyperspaceError::Validation)?;
+            self.get(user_id, name).await.ok_or_else(|| {
+                HyperspaceError::Internal(format!("Collection '{name}' could not be opened"))
+            })?
+        };
+        if let Some(dimension) = header.dimension.filter(|&d| d != col.dimension()) {
+            return Err(HyperspaceError::Validation(format!(
+                "The file holds {dimension}-dimensional vectors, collection '{name}' has {}",
+                col.dimension()
+            )));
+        }
+
+        let mut applied = 0u64;
+        loop {
+            let (back, rows) = tokio::task::spawn_blocking(move || {
+                let rows = reader.next_rows();
+                (reader, rows)
+            })
+            .await
+            .map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+            reader = back;
+            let Some(rows) = rows? else {
+                break;
+            };
+            applied += rows.len() as u64;
+            let (tombstones, live): (Vec<_>, Vec<_>) = rows.into_iter().partition(|r| r.deleted);
+            let deletes = tombstones
+                .into_iter()
+                .map(|r| r.id)
+                .filter(|&id| col.contains(id))
+                .collect();
+            let vectors = live
+                .into_iter()
+                .map(|r| (r.vector, r.id, r.metadata))
+                .collect();
+            let clock = self.tick_cluster_clock().await;
+            col.apply_batch(deletes, vectors, clock, Durability::Default)
+                .await?;
+        }
+        Ok(applied)
+    }
+
+    /// Directory of the collection stored under `internal_name`.
+    pub fn internal_dir(&self, internal_name: &str) -> PathBuf {
+        self.base_path.join(internal_name)
+    }
+
+    /// Whole WAL records of a collection from a standby's position on;
+    /// `None` when the position is no longer in the log.
+    pub async fn wal_range(
+        &self,
+        user_id: &str,
+        name: &str,
+        after_segment: u64,
+        offset: u64,
+        max_bytes: usize,
+    ) -> HyperspaceResult<Option<WalRange>> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        tokio::task::spawn_blocking(move || {
+            crate::wal_shipping::read_range(&dir, after_segment, offset, max_bytes)
+        })
+        .await
+        .map_err(|e| HyperspaceError::Internal(e.to_string()))?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => HyperspaceError::Corruption(e.to_string()),
+            _ => HyperspaceError::Io(e),
+        })
+    }
+
+    /// Data directory of an existing collection, resident or not.
+    fn existing_collection_dir(&self, user_id: &str, name: &str) -> HyperspaceResult<PathBuf> {
+        let dir = self
+            .base_path
+            .join(self.resolve_alias(Self::get_internal_name(user_id, name)));
+        if dir.join("meta.json").exists() {
+            Ok(dir)
+        } else {
+            Err(HyperspaceError::NotFound(format!(
+                "Collection '{name}' not found"
+            )))
+        }
+    }
+
+    /// Sets how a collection's WAL syncs to disk and records it in
+    /// `meta.json`; `None` goes back to `HYPERSPACE_WAL_SYNC_MODE`. A resident
+    /// collection switches immediately, a cold one on its next open.
+    pub async fn set_wal_sync_mode(
+        &self,
+        user_id: &str,
+        name: &str,
+        mode: Option<WalSyncMode>,
+    ) -> HyperspaceResult<()> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        // Keeps a concurrent wake from opening with the old mode.
+        let _guard = self.load_lock.lock().await;
+        let mut meta = CollectionMetadata::load(&dir)?;
+        meta.wal_sync_mode = mode.map(|m| m.as_str().to_string());
+        meta.save(&dir)?;
+
+        let internal_name = Self::get_internal_name(user_id, name);
+        if let Some(entry) = self.collections.get(&internal_name) {
+            let col = entry.collection.clone();
+            drop(entry);
+            let durability = match mode {
+                None => Durability::Default,
+                Some(WalSyncMode::Async) => Durability::Async,
+                Some(WalSyncMode::Batch) => Durability::Batch,
+                Some(WalSyncMode::Strict) => Durability::Strict,
+            };
+            col.set_wal_sync_mode(durability).await?;
+        }
+        Ok(())
+    }
+
+    /// Synonym groups of a collection's lexical search, from `meta.json`.
+    pub fn synonyms(&self, user_id: &str, name: &str) -> HyperspaceResult<SynonymMap> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        Ok(CollectionMetadata::load(&dir)?.synonyms)
+    }
+
+    /// Collection holding the parent documents of `name`'s chunks, if one
+    /// was set on creation.
+    pub fn parent_collection(&self, user_id: &str, name: &str) -> HyperspaceResult<Option<String>> {
+        let dir = self.existing_collection_dir(user_id, name)?;
+        Ok(CollectionMetadata::load(&dir)?.parent)
+    }
+
+    /// Replaces a collection's synonym groups and records them in
+    /// `meta.json`. A resident collection switches immediately, a cold one
+    /// on its next open.
+    pub async fn set_synonyms(
+        &self,
+        user_id: &str,
+        name: &str,
+        synonyms: SynonymMap,
+    ) -> HyperspaceResult<()> {
+        synonyms.validate()?;
+        let dir = self.existing_collection_dir(user_id, name)?;
+        // Keeps a concurrent wake from opening with the old groups.
+        let _guard = self.load_lock.lock().await;
+        let mut meta = CollectionMetadata::load(&dir)?;
+        meta.synonyms = synonyms.clone();
+        meta.save(&dir)?;
+
+        let internal_name = Self::get_internal_name(user_id, name);
+        if let Some(entry) = self.collections.get(&internal_name) {
+            let col = entry.collection.clone();
+            drop(entry);
+            // Expanding at index time re-derives every document's postings.
+            tokio::task::spawn_blocking(move || col.set_synonyms(synonyms))
+                .await
+                .map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Registers or replaces a named query template; returns its new version.
+    pub fn put_query_template(
+        &self,
+        user_id: &str,
+        collection: &str,
+        name: &str,
+        mut template: QueryTemplate,
+    ) -> HyperspaceResult<u64> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(HyperspaceError::Validation(format!(
+                "Invalid query name '{name}'"
+            )));
+        }
+        template.validate()?;
+        let dir = self.existing_collection_dir(user_id, collection)?;
+
+        let _guard = self.templates_lock.lock();
+        let mut templates = query_templates::load(&dir)?;
+        template.version = templates.get(name).map_or(1, |t| t.version + 1);
+        let version = template.version;
+        templates.insert(name.to_string(), template);
+        query_templates::save(&dir, &templates)?;
+        Ok(version)
+    }
+
+    pub fn get_query_template(
+        &self,
+        user_id: &str,
+        collection: &str,
+        name: &str,
+    ) -> HyperspaceResult<QueryTemplate> {
+        let dir = self.existing_collection_dir(user_id, collection)?;
+        query_templates::load(&dir)?
+            .remove(name)
+            .ok_or_else(|| HyperspaceError::NotFound(format!("Query '{name}' not found")))
+    }
+
+    pub fn list_query_templates(
+        &self,
+        user_id: &str,
+        collection: &str,
+    ) -> HyperspaceResult<BTreeMap<String, QueryTemplate>> {
+        let dir = self.existing_collection_dir(user_id, collection)?;
+        query_templates::load(&dir)
+    }
+
+    /// Removes a named query template. Returns whether it existed.
+    pub fn delete_query_template(
+        &self,
+        user_id: &str,
+        collection: &str,
+        name: &str,
+    ) -> HyperspaceResult<bool> {
+        let dir = self.existing_collection_dir(user_id, collection)?;
+
+        let _guard = self.templates_lock.lock();
+        let mut templates = query_templates::load(&dir)?;
+        let existed = templates.remove(name).is_some();
+        if existed {
+            query_templates::save(&dir, &templates)?;
+        }
+        Ok(existed)
+    }
+
+    /// Defines the collection's A/B experiment, replacing any running one
+    /// and resetting its counters.
+    pub fn put_experiment(
+        &self,
+        user_id: &str,
+        collection: &str,
+        experiment: Experiment,
+    ) -> HyperspaceResult<()> {
+        experiment.validate()?;
+        let dir = self.existing_collection_dir(user_id, collection)?;
+        experiments::save(&dir, &experiment)?;
+        let running = RunningExperiment::open(&dir, experiment)?;
+        self.experiments.insert(
+            Self::get_internal_name(user_id, collection),
+            Some(Arc::new(running)),
+        );
+        Ok(())
+    }
+
+    /// The collection's running experiment, loaded from disk on first use.
+    pub fn experiment(
+        &self,
+        user_id: &str,
+        collection: &str,
+    ) -> HyperspaceResult<Option<Arc<RunningExperiment>>> {
+        let internal_name = Self::get_internal_name(user_id, collection);
+        if let Some(cached) = self.experiments.get(&internal_name) {
+            return Ok(cached.clone());
+        }
+        let dir = self.existing_collection_dir(user_id, collection)?;
+        let running = experiments::load(&dir)?
+            .map(|e| RunningExperiment::open(&dir, e).map(Arc::new))
+            .transpose()?;
+        self.experiments.insert(internal_name, running.clone());
+        Ok(running)
+    }
+
+    /// Stops the collection's experiment. Its log is kept. Returns whether
+    /// one was defined.
+    pub fn delete_experiment(&self, user_id: &str, collection: &str) -> HyperspaceResult<bool> {
+        let dir = self.existing_collection_dir(user_id, collection)?;
+        self.experiments
+            .insert(Self::get_internal_name(user_id, collection), None);
+        match fs::remove_file(dir.join(experiments::EXPERIMENT_FILE)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn get_user_usage(&self, user_id: &str) -> UserUsage {
         let prefix = format!("{user_id}_");
         let mut usage = UserUsage::default();
@@ -580,7 +1963,7 @@ impl CollectionManager {
                 if path.is_dir() {
                     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                         if name.starts_with(&prefix) {
-                            usage.disk_usage_bytes += calculate_dir_size(&path).unwrap_or(0);
+                            usage.disk_usage_bytes += dir_size(&path).unwrap_or(0);
 
                             // If this collection wasn't found in memory during step 1,
                             // it means it's an idle collection on disk.
@@ -595,6 +1978,22 @@ impl CollectionManager {
         usage
     }
 
+    /// Rolls usage up over the collections under `namespace`. Like
+    /// [`Self::get_user_usage`], vectors are only counted for resident
+    /// collections so idle ones are not woken.
+    pub fn get_namespace_usage(&self, user_id: &str, namespace: &str) -> UserUsage {
+        let mut usage = UserUsage::default();
+        for name in self.list_namespace(user_id, namespace) {
+            let internal_name = Self::get_internal_name(user_id, &name);
+            if let Some(entry) = self.collections.get(&internal_name) {
+                usage.vector_count += entry.value().collection.count();
+            }
+            usage.collection_count += 1;
+            usage.disk_usage_bytes += dir_size(&self.base_path.join(&internal_name)).unwrap_or(0);
+        }
+        usage
+    }
+
     pub fn get_usage_report(&self) -> std::collections::HashMap<String, UserUsage> {
         let mut report = std::collections::HashMap::new();
 
@@ -602,7 +2001,7 @@ impl CollectionManager {
         if let Ok(entries) = std::fs::read_dir(&self.base_path) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.is_dir() {
+                if path.is_dir() && entry.file_name() != trash::TRASH_DIR {
                     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                         // Parse {user_id}_{collection_name}
                         // We assume the first part before '_' is user_id.
@@ -615,7 +2014,7 @@ impl CollectionManager {
                             "unknown"
                         };
 
-                        let size = calculate_dir_size(&path).unwrap_or(0);
+                        let size = dir_size(&path).unwrap_or(0);
                         let usage = report
                             .entry(user_id.to_string())
                             .or_insert(UserUsage::default());
@@ -632,22 +2031,23 @@ impl CollectionManager {
         }
         report
     }
-}
-
-fn calculate_dir_size(path: &Path) -> std::io::Result<u64> {
-    let mut total_size = 0u64;
 
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let metadata = entry.metadata()?;
-            if metadata.is_file() {
-                total_size += metadata.len();
-            } else if metadata.is_dir() {
-                total_size += calculate_dir_size(&entry.path())?;
-            }
-        }
+    /// Dimension, metric and quantization of every collection on disk, read
+    /// from `meta.json` so cold collections stay closed.
+    pub fn collection_shapes(&self) -> Vec<(u32, String, String)> {
+        let Ok(entries) = std::fs::read_dir(&self.base_path) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|entry| entry.file_name() != trash::TRASH_DIR)
+            .filter_map(|entry| CollectionMetadata::load(&entry.path()).ok())
+            .map(|meta| {
+                let quantization = collection_spec::quantization_name(meta.quantization_mode());
+                (meta.dimension, meta.metric, quantization.to_string())
+            })
+            .collect()
     }
-    Ok(total_size)
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -655,6 +2055,28 @@ struct CollectionMetadata {
     dimension: u32,
     metric: String,
     quantization: String,
+    #[serde(default)]
+    snapshot: SnapshotPolicy,
+    #[serde(default)]
+    limits: CollectionLimits,
+    /// `strict`, `batch` or `async`; unset follows `HYPERSPACE_WAL_SYNC_MODE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wal_sync_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema: Option<MetadataSchema>,
+    #[serde(flatten)]
+    info: CollectionInfo,
+    #[serde(default, skip_serializing_if = "HnswParams::is_default")]
+    hnsw: HnswParams,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ivf: Option<IvfParams>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    normalization: Option<NormalizationPolicy>,
+    #[serde(default, skip_serializing_if = "SynonymMap::is_empty")]
+    synonyms: SynonymMap,
+    /// Name of the parent document collection, without the user prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
 }
 
 impl CollectionMetadata {
@@ -669,18 +2091,19 @@ impl CollectionMetadata {
         Ok(meta)
     }
 
+    fn wal_sync_mode(&self) -> Option<WalSyncMode> {
+        self.wal_sync_mode.as_deref().and_then(WalSyncMode::parse)
+    }
+
     fn quantization_mode(&self) -> hyperspace_core::QuantizationMode {
-        match self.quantization.as_str() {
-            "binary" => hyperspace_core::QuantizationMode::Binary,
-            "none" => hyperspace_core::QuantizationMode::None,
-            _ => hyperspace_core::QuantizationMode::ScalarI8,
-        }
+        collection_spec::quantization_mode(&self.quantization)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::sync::broadcast;
 
     #[test]
     fn test_logical_clock() {
@@ -700,4 +2123,58 @@ mod tests {
         state.merge(10);
         assert_eq!(state.logical_clock, 11); // max(2, 10) + 1 = 11
     }
+
+    #[tokio::test]
+    async fn test_resident_limit_evicts_lru_and_wakes_on_access() {
+        let tmp_dir = std::env::temp_dir().join(format!("hyperspace_lru_{}", Uuid::new_v4()));
+        let (tx, _rx) = broadcast::channel(16);
+        let mut manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager.max_resident = 1;
+
+        manager.create_collection("u", "a", 8, "l2").await.unwrap();
+        let a = manager.get("u", "a").await.unwrap();
+        a.insert(
+            &[0.1; 8],
+            7,
+            std::collections::HashMap::new(),
+            0,
+            hyperspace_core::Durability::Default,
+        )
+        .await
+        .unwrap();
+        while a.queue_size() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        drop(a);
+
+        let resident = |manager: &CollectionManager| {
+            manager
+                .collections
+                .iter()
+                .map(|entry| entry.key().clone())
+                .collect::<Vec<_>>()
+        };
+        manager.create_collection("u", "b", 8, "l2").await.unwrap();
+        assert_eq!(resident(&manager), vec!["u_b".to_string()]);
+        assert_eq!(
+            manager.list_all(),
+            vec!["u_a".to_string(), "u_b".to_string()]
+        );
+        assert!(tmp_dir.join("u_a").join("index.snap").exists());
+
+        // Listing reads the cold collection from disk instead of waking it.
+        let listed = manager.list_detailed_in("u", "", &HashMap::new());
+        let shapes: Vec<_> = listed
+            .iter()
+            .map(|c| (c.name.as_str(), c.count, c.dimension, c.metric.as_str()))
+            .collect();
+        assert_eq!(shapes, vec![("a", 1, 8, "l2"), ("b", 0, 8, "l2")]);
+        assert_eq!(resident(&manager), vec!["u_b".to_string()]);
+
+        let a = manager.get("u", "a").await.expect("cold collection wakes");
+        assert_eq!(a.count(), 1);
+        assert_eq!(resident(&manager), vec!["u_a".to_string()]);
+
+        let _ = fs::remove_dir_all(&tmp_dir);
+    }
 }
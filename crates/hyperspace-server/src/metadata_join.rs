@@ -0,0 +1,89 @@
+//! Cross-collection joins: search hits enriched with the metadata of a
+//! related point in another collection, e.g. chunks with the fields of their
+//! parent document, without a lookup round trip per hit.
+//!
+//! A hit whose `key` is `v` gets the metadata of the lowest-id point of the
+//! joined collection whose `foreign_key` is `v`, renamed with the join's
+//! prefix. Typed values stay typed. Hits without the key, or without a
+//! match, are returned as they are.
+
+use crate::TYPED_META_PREFIX;
+use hyperspace_core::{Collection, SearchResult};
+use hyperspace_proto::hyperspace::MetadataJoin;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// More joins per search than this are rejected.
+pub const MAX_JOINS: usize = 4;
+
+/// A join of a search request with the collection it reads.
+#[derive(Clone)]
+pub struct ResolvedJoin {
+    pub join: MetadataJoin,
+    pub target: Arc<dyn Collection>,
+    /// Prepended to every copied key.
+    pub prefix: String,
+}
+
+impl ResolvedJoin {
+    pub fn new(join: MetadataJoin, target: Arc<dyn Collection>) -> Self {
+        let prefix = if join.prefix.is_empty() {
+            format!("{}.", join.collection)
+        } else {
+            join.prefix.clone()
+        };
+        Self {
+            join,
+            target,
+            prefix,
+        }
+    }
+
+    /// Adds the joined fields to the metadata of every hit.
+    pub fn enrich(&self, hits: &mut [SearchResult]) {
+        // Hits often share a parent, so each value is looked up once.
+        let mut rows: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (_, _, meta) in hits.iter_mut() {
+            let Some(value) = meta.get(&self.join.key).cloned() else {
+                continue;
+            };
+            let row = rows
+                .entry(value)
+                .or_insert_with_key(|value| self.lookup(value));
+            meta.extend(row.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    /// Selected fields of the point joined to `value`, renamed.
+    fn lookup(&self, value: &str) -> HashMap<String, String> {
+        let foreign_key = if self.join.foreign_key.is_empty() {
+            &self.join.key
+        } else {
+            &self.join.foreign_key
+        };
+        let Some(&id) = self.target.ids_with_metadata(foreign_key, value).first() else {
+            return HashMap::new();
+        };
+        self.target
+            .metadata_by_id(id)
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let (typed, field) = match key.strip_prefix(TYPED_META_PREFIX) {
+                    Some(field) => (true, field),
+                    None => (false, key.as_str()),
+                };
+                if field.starts_with("__hs_")
+                    || !(self.join.fields.is_empty() || self.join.fields.iter().any(|f| f == field))
+                {
+                    return None;
+                }
+                let renamed = if typed {
+                    format!("{TYPED_META_PREFIX}{}{field}", self.prefix)
+                } else {
+                    format!("{}{field}", self.prefix)
+                };
+                Some((renamed, value))
+            })
+            .collect()
+    }
+}
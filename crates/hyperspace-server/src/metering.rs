@@ -0,0 +1,531 @@
+//! Usage metering for SaaS billing.
+//!
+//! Request handlers record billable units per tenant (the user id resolved
+//! from the API key) into in-memory counters: searches, vectors written and
+//! embedded tokens. A background task periodically turns the counters into
+//! [`UsageRecord`]s, adds storage gauges from the manager, and ships them to
+//! every configured [`UsageSink`].
+//!
+//! Each sink tracks what it has already received, so a sink that is down for a
+//! few periods gets the accumulated delta on its next successful write rather
+//! than losing units.
+//!
+//! Sinks are configured with `HS_METERING_SINKS`, a comma-separated list of
+//! `file:<path>`, `webhook:<url>` (feature `metering-webhook`) and
+//! `postgres:<connection string>` (feature `metering-postgres`).
+
+use crate::manager::{CollectionManager, UserUsage};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Rough token count used for embedding usage: whitespace-delimited words.
+#[cfg_attr(not(feature = "embed"), allow(dead_code))]
+pub fn approx_tokens(text: &str) -> u64 {
+    text.split_whitespace().count() as u64
+}
+
+#[derive(Default)]
+struct Counters {
+    searches: AtomicU64,
+    vectors_written: AtomicU64,
+    embedded_tokens: AtomicU64,
+}
+
+/// Cumulative billable units for one tenant since the process started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeteredTotals {
+    pub searches: u64,
+    pub vectors_written: u64,
+    pub embedded_tokens: u64,
+}
+
+impl MeteredTotals {
+    fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            searches: self.searches.saturating_sub(other.searches),
+            vectors_written: self.vectors_written.saturating_sub(other.vectors_written),
+            embedded_tokens: self.embedded_tokens.saturating_sub(other.embedded_tokens),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// One tenant's usage over `[period_start, period_end)`, as delivered to sinks.
+///
+/// Counters are deltas for the period; `vectors_stored` and `storage_bytes`
+/// are gauges sampled at `period_end`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub tenant: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub searches: u64,
+    pub vectors_written: u64,
+    pub embedded_tokens: u64,
+    pub vectors_stored: u64,
+    pub storage_bytes: u64,
+}
+
+/// Live per-tenant view served by `GET /api/admin/usage`: storage gauges plus
+/// cumulative metered units since `metered_since`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TenantUsage {
+    #[serde(flatten)]
+    pub storage: UserUsage,
+    #[serde(flatten)]
+    pub metered: MeteredTotals,
+    pub metered_since: u64,
+}
+
+/// Destination for flushed usage records.
+#[async_trait::async_trait]
+pub trait UsageSink: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn write(&self, records: &[UsageRecord]) -> Result<(), String>;
+}
+
+/// Appends records as NDJSON to a local file.
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl UsageSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn write(&self, records: &[UsageRecord]) -> Result<(), String> {
+        let mut buf = Vec::new();
+        for r in records {
+            serde_json::to_writer(&mut buf, r).map_err(|e| e.to_string())?;
+            buf.push(b'\n');
+        }
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut f = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            f.write_all(&buf)?;
+            f.sync_data()
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// POSTs each batch of records as a JSON array.
+#[cfg(feature = "metering-webhook")]
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "metering-webhook")]
+impl WebhookSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(feature = "metering-webhook")]
+#[async_trait::async_trait]
+impl UsageSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn write(&self, records: &[UsageRecord]) -> Result<(), String> {
+        self.client
+            .post(&self.url)
+            .json(records)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Inserts records into a `hyperspace_usage` table, created on first use.
+#[cfg(feature = "metering-postgres")]
+pub struct PostgresSink {
+    config: String,
+    client: tokio::sync::Mutex<Option<tokio_postgres::Client>>,
+}
+
+#[cfg(feature = "metering-postgres")]
+impl PostgresSink {
+    pub fn new(config: impl Into<String>) -> Self {
+        Self {
+            config: config.into(),
+            client: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    async fn connect(&self) -> Result<tokio_postgres::Client, tokio_postgres::Error> {
+        let (client, connection) =
+            tokio_postgres::connect(&self.config, tokio_postgres::NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("Metering Postgres connection closed: {e}");
+            }
+        });
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS hyperspace_usage (
+                    tenant TEXT NOT NULL,
+                    period_start BIGINT NOT NULL,
+                    period_end BIGINT NOT NULL,
+                    searches BIGINT NOT NULL,
+                    vectors_written BIGINT NOT NULL,
+                    embedded_tokens BIGINT NOT NULL,
+                    vectors_stored BIGINT NOT NULL,
+                    storage_bytes BIGINT NOT NULL
+                )",
+            )
+            .await?;
+        Ok(client)
+    }
+}
+
+#[cfg(feature = "metering-postgres")]
+#[async_trait::async_trait]
+impl UsageSink for PostgresSink {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn write(&self, records: &[UsageRecord]) -> Result<(), String> {
+        let mut guard = self.client.lock().await;
+        if guard.as_ref().is_none_or(tokio_postgres::Client::is_closed) {
+            *guard = Some(self.connect().await.map_err(|e| e.to_string())?);
+        }
+        let client = guard.as_mut().expect("connected above");
+        let tx = client.transaction().await.map_err(|e| e.to_string())?;
+        for r in records {
+            #[allow(clippy::cast_possible_wrap)]
+            tx.execute(
+                "INSERT INTO hyperspace_usage VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &r.tenant,
+                    &(r.period_start as i64),
+                    &(r.period_end as i64),
+                    &(r.searches as i64),
+                    &(r.vectors_written as i64),
+                    &(r.embedded_tokens as i64),
+                    &(r.vectors_stored as i64),
+                    &(r.storage_bytes as i64),
+                ],
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())
+    }
+}
+
+/// Builds one sink from a `kind:target` spec.
+fn parse_sink(spec: &str) -> Result<Box<dyn UsageSink>, String> {
+    let (kind, target) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("expected kind:target, got '{spec}'"))?;
+    match kind.trim() {
+        "file" => Ok(Box::new(FileSink::new(target.trim()))),
+        #[cfg(feature = "metering-webhook")]
+        "webhook" => Ok(Box::new(WebhookSink::new(target.trim()))),
+        #[cfg(feature = "metering-postgres")]
+        "postgres" => Ok(Box::new(PostgresSink::new(target.trim()))),
+        other => Err(format!("unsupported sink '{other}' (not compiled in?)")),
+    }
+}
+
+struct SinkState {
+    sink: Box<dyn UsageSink>,
+    // Per-tenant totals this sink has acknowledged
+    delivered: tokio::sync::Mutex<HashMap<String, MeteredTotals>>,
+    last_flush: AtomicU64,
+}
+
+pub struct Meter {
+    counters: DashMap<String, Counters>,
+    sinks: Vec<SinkState>,
+    started: u64,
+}
+
+impl Meter {
+    pub fn new(sinks: Vec<Box<dyn UsageSink>>) -> Self {
+        let started = now_secs();
+        Self {
+            counters: DashMap::new(),
+            sinks: sinks
+                .into_iter()
+                .map(|sink| SinkState {
+                    sink,
+                    delivered: tokio::sync::Mutex::new(HashMap::new()),
+                    last_flush: AtomicU64::new(started),
+                })
+                .collect(),
+            started,
+        }
+    }
+
+    /// Reads `HS_METERING_SINKS`; invalid entries are reported and skipped.
+    pub fn from_env() -> Self {
+        let sinks = std::env::var("HS_METERING_SINKS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .filter_map(|spec| match parse_sink(spec) {
+                Ok(sink) => Some(sink),
+                Err(e) => {
+                    eprintln!("⚠️ Ignoring metering sink: {e}");
+                    None
+                }
+            })
+            .collect();
+        Self::new(sinks)
+    }
+
+    pub fn has_sinks(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    fn add(&self, tenant: &str, n: u64, field: impl Fn(&Counters) -> &AtomicU64) {
+        if n == 0 {
+            return;
+        }
+        if let Some(c) = self.counters.get(tenant) {
+            field(&c).fetch_add(n, Ordering::Relaxed);
+            return;
+        }
+        let c = self.counters.entry(tenant.to_string()).or_default();
+        field(&c).fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn record_searches(&self, tenant: &str, n: u64) {
+        self.add(tenant, n, |c| &c.searches);
+    }
+
+    pub fn record_vectors_written(&self, tenant: &str, n: u64) {
+        self.add(tenant, n, |c| &c.vectors_written);
+    }
+
+    #[cfg_attr(not(feature = "embed"), allow(dead_code))]
+    pub fn record_embedded_tokens(&self, tenant: &str, n: u64) {
+        self.add(tenant, n, |c| &c.embedded_tokens);
+    }
+
+    /// Cumulative units per tenant since startup.
+    pub fn totals(&self) -> HashMap<String, MeteredTotals> {
+        self.counters
+            .iter()
+            .map(|e| {
+                let c = e.value();
+                (
+                    e.key().clone(),
+                    MeteredTotals {
+                        searches: c.searches.load(Ordering::Relaxed),
+                        vectors_written: c.vectors_written.load(Ordering::Relaxed),
+                        embedded_tokens: c.embedded_tokens.load(Ordering::Relaxed),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Merges storage gauges with the metered totals for every known tenant.
+    pub fn report(&self, storage: HashMap<String, UserUsage>) -> HashMap<String, TenantUsage> {
+        let mut report: HashMap<String, TenantUsage> = storage
+            .into_iter()
+            .map(|(tenant, storage)| {
+                (
+                    tenant,
+                    TenantUsage {
+                        storage,
+                        metered_since: self.started,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        for (tenant, metered) in self.totals() {
+            let entry = report.entry(tenant).or_insert_with(|| TenantUsage {
+                metered_since: self.started,
+                ..Default::default()
+            });
+            entry.metered = metered;
+        }
+        report
+    }
+
+    /// Delivers everything not yet acknowledged to each sink. `storage`
+    /// supplies the per-tenant gauges. Returns the first sink error, after
+    /// trying all sinks.
+    pub async fn flush(&self, storage: &HashMap<String, UserUsage>) -> Result<(), String> {
+        let totals = self.totals();
+        let now = now_secs();
+        let mut first_err = None;
+
+        for state in &self.sinks {
+            let mut delivered = state.delivered.lock().await;
+            let period_start = state.last_flush.load(Ordering::Relaxed);
+            let mut tenants: Vec<&String> = totals.keys().chain(storage.keys()).collect();
+            tenants.sort_unstable();
+            tenants.dedup();
+
+            let records: Vec<UsageRecord> = tenants
+                .into_iter()
+                .filter_map(|tenant| {
+                    let total = totals.get(tenant).copied().unwrap_or_default();
+                    let delta =
+                        total.saturating_sub(delivered.get(tenant).copied().unwrap_or_default());
+                    let gauges = storage.get(tenant).cloned().unwrap_or_default();
+                    if delta.is_zero() && gauges.vector_count == 0 && gauges.disk_usage_bytes == 0 {
+                        return None;
+                    }
+                    Some(UsageRecord {
+                        tenant: tenant.clone(),
+                        period_start,
+                        period_end: now,
+                        searches: delta.searches,
+                        vectors_written: delta.vectors_written,
+                        embedded_tokens: delta.embedded_tokens,
+                        vectors_stored: gauges.vector_count as u64,
+                        storage_bytes: gauges.disk_usage_bytes,
+                    })
+                })
+                .collect();
+            if records.is_empty() {
+                continue;
+            }
+
+            match state.sink.write(&records).await {
+                Ok(()) => {
+                    for r in &records {
+                        if let Some(total) = totals.get(&r.tenant) {
+                            delivered.insert(r.tenant.clone(), *total);
+                        }
+                    }
+                    state.last_flush.store(now, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    let msg = format!("metering sink '{}' failed: {e}", state.sink.name());
+                    eprintln!("⚠️ {msg}");
+                    first_err.get_or_insert(msg);
+                }
+            }
+        }
+        first_err.map_or(Ok(()), Err)
+    }
+}
+
+/// Flushes the manager's meter every `HS_METERING_FLUSH_SEC` (default 60).
+pub fn spawn_flusher(manager: &Arc<CollectionManager>) {
+    if !manager.meter.has_sinks() {
+        return;
+    }
+    let interval = std::env::var("HS_METERING_FLUSH_SEC")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60u64)
+        .max(1);
+    let manager = Arc::downgrade(manager);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            let Some(manager) = manager.upgrade() else {
+                break;
+            };
+            let storage = manager.get_usage_report();
+            let _ = manager.meter.flush(&storage).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    struct FlakySink {
+        fail: std::sync::atomic::AtomicBool,
+        received: Mutex<Vec<UsageRecord>>,
+    }
+
+    #[async_trait::async_trait]
+    impl UsageSink for Arc<FlakySink> {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn write(&self, records: &[UsageRecord]) -> Result<(), String> {
+            if self.fail.load(Ordering::Relaxed) {
+                return Err("down".into());
+            }
+            self.received.lock().extend_from_slice(records);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_flush_is_redelivered_as_accumulated_delta() {
+        let sink = Arc::new(FlakySink {
+            fail: std::sync::atomic::AtomicBool::new(true),
+            received: Mutex::new(Vec::new()),
+        });
+        let meter = Meter::new(vec![Box::new(sink.clone())]);
+        meter.record_searches("acme", 3);
+        meter.record_vectors_written("acme", 10);
+        assert!(meter.flush(&HashMap::new()).await.is_err());
+
+        meter.record_searches("acme", 2);
+        meter.record_embedded_tokens("acme", approx_tokens("four words right here"));
+        sink.fail.store(false, Ordering::Relaxed);
+        meter.flush(&HashMap::new()).await.unwrap();
+        // Nothing new: no record for an idle tenant without stored data.
+        meter.flush(&HashMap::new()).await.unwrap();
+
+        let received = sink.received.lock();
+        assert_eq!(received.len(), 1);
+        let r = &received[0];
+        assert_eq!(
+            (r.searches, r.vectors_written, r.embedded_tokens),
+            (5, 10, 4)
+        );
+        assert_eq!(meter.totals()["acme"].searches, 5);
+    }
+}
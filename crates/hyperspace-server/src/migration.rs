@@ -0,0 +1,309 @@
+//! Live migration of a collection to another server.
+//!
+//! `MigrateCollection` runs on the source. It subscribes to the local
+//! replication feed, then snapshots the collection and streams the bundle to
+//! the target's `ReceiveCollection`, which imports it. Writes that landed
+//! after the subscription are replayed on the target, round after round,
+//! until a round finds fewer than [`SETTLE_OPS`]. The switch-over then records
+//! a redirect for the name, so new requests fail fast with `NOT_FOUND` plus
+//! `x-hyperspace-moved-to`, waits for writes already in flight and replays
+//! them. Only once that last replay succeeded is the local copy dropped;
+//! if it fails, or writes are still in flight after `IN_FLIGHT_GRACE`, the
+//! redirect is cleared and the source keeps serving.
+//! Requests are only refused during the switch-over.
+//!
+//! Replaying entries the bundle already holds is harmless: upserts and
+//! deletes are idempotent. The replication feed must be able to buffer the
+//! writes made during the transfer; if it lags, the migration aborts and the
+//! source keeps serving.
+
+use crate::anti_entropy::request;
+use crate::manager::CollectionManager;
+use hyperspace_proto::hyperspace::database_client::DatabaseClient;
+use hyperspace_proto::hyperspace::{
+    replication_log, BatchInsertRequest, CollectionBundleChunk, DeleteRequest, ExportFormat,
+    ReplicationLog, VectorData,
+};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tonic::transport::Channel;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// A catch-up round replaying fewer writes than this triggers the switch-over.
+pub const SETTLE_OPS: usize = 64;
+
+/// Catch-up rounds before switching over regardless.
+const MAX_ROUNDS: usize = 20;
+
+pub const BUNDLE_CHUNK: usize = 1 << 20;
+const REPLAY_BATCH: usize = 1000;
+
+/// How long the switch-over waits for writes already holding the collection.
+const IN_FLIGHT_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MigrationReport {
+    pub bundle_bytes: u64,
+    pub replayed_ops: u64,
+    pub write_pause: Duration,
+}
+
+/// A write to replay on the target, in feed order.
+#[derive(Debug, PartialEq)]
+enum Op {
+    Upsert(VectorData),
+    Delete(u32),
+}
+
+/// Takes every entry for the collection off `feed` without waiting.
+fn drain(
+    feed: &mut broadcast::Receiver<ReplicationLog>,
+    internal_name: &str,
+) -> Result<Vec<Op>, BoxError> {
+    let mut ops = Vec::new();
+    loop {
+        let log = match feed.try_recv() {
+            Ok(log) => log,
+            Err(TryRecvError::Empty | TryRecvError::Closed) => return Ok(ops),
+            Err(TryRecvError::Lagged(n)) => {
+                return Err(format!("replication feed dropped {n} entries mid-migration").into())
+            }
+        };
+        if log.collection != internal_name {
+            continue;
+        }
+        match log.operation {
+            Some(replication_log::Operation::Insert(op)) => ops.push(Op::Upsert(VectorData {
+                vector: op.vector,
+                id: op.id,
+                metadata: op.metadata,
+                typed_metadata: op.typed_metadata,
+                vector_f32: Vec::new(),
+                token_vectors: None,
+            })),
+            Some(replication_log::Operation::Delete(op)) => ops.push(Op::Delete(op.id)),
+            // The target only soft-deletes; purge it again there to erase.
+            Some(replication_log::Operation::Purge(op)) => {
+                ops.extend(op.ids.into_iter().map(Op::Delete));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Applies `ops` to `collection` on the target in order; returns how many.
+async fn replay(
+    client: &mut DatabaseClient<Channel>,
+    api_key: &str,
+    user_id: &str,
+    collection: &str,
+    ops: Vec<Op>,
+) -> Result<u64, BoxError> {
+    let total = ops.len() as u64;
+    let mut batch = Vec::new();
+    for op in ops {
+        match op {
+            Op::Upsert(v) => batch.push(v),
+            Op::Delete(id) => {
+                flush(client, api_key, user_id, collection, &mut batch).await?;
+                client
+                    .delete(request(
+                        DeleteRequest {
+                            collection: collection.to_string(),
+                            id,
+                        },
+                        api_key,
+                        user_id,
+                    ))
+                    .await?;
+            }
+        }
+        if batch.len() >= REPLAY_BATCH {
+            flush(client, api_key, user_id, collection, &mut batch).await?;
+        }
+    }
+    flush(client, api_key, user_id, collection, &mut batch).await?;
+    Ok(total)
+}
+
+async fn flush(
+    client: &mut DatabaseClient<Channel>,
+    api_key: &str,
+    user_id: &str,
+    collection: &str,
+    batch: &mut Vec<VectorData>,
+) -> Result<(), BoxError> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    client
+        .batch_insert(request(
+            BatchInsertRequest {
+                collection: collection.to_string(),
+                vectors: std::mem::take(batch),
+                ..Default::default()
+            },
+            api_key,
+            user_id,
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Streams the bundle at `path` to the target; returns its size.
+async fn send_bundle(
+    client: &mut DatabaseClient<Channel>,
+    api_key: &str,
+    user_id: &str,
+    collection: &str,
+    path: &std::path::Path,
+) -> Result<u64, BoxError> {
+    let file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+    let first = Some(collection.to_string());
+    let chunks = futures::stream::unfold((file, first), move |(mut file, first)| async move {
+        let mut data = vec![0u8; BUNDLE_CHUNK];
+        match file.read(&mut data).await {
+            Ok(0) | Err(_) => None,
+            Ok(n) => {
+                data.truncate(n);
+                let chunk = CollectionBundleChunk {
+                    total_bytes: if first.is_some() { size } else { 0 },
+                    collection: first.unwrap_or_default(),
+                    data,
+                    format: ExportFormat::ExportBundle as i32,
+                };
+                Some((chunk, (file, None)))
+            }
+        }
+    });
+    client
+        .receive_collection(request(chunks, api_key, user_id))
+        .await?;
+    Ok(size)
+}
+
+/// Moves `name` of `user_id` to `target_addr`, where it is called
+/// `target_name`.
+pub async fn migrate(
+    manager: &CollectionManager,
+    user_id: &str,
+    name: &str,
+    target_addr: &str,
+    target_name: &str,
+    api_key: &str,
+) -> Result<MigrationReport, BoxError> {
+    let internal_name = CollectionManager::get_internal_name(user_id, name);
+    let col = manager
+        .get(user_id, name)
+        .await
+        .ok_or_else(|| format!("Collection '{name}' not found"))?;
+    let mut client = DatabaseClient::connect(target_addr.to_string()).await?;
+    let mut report = MigrationReport::default();
+
+    // Subscribe first: anything written after this point is replayed.
+    let mut feed = manager.subscribe_replication();
+    let bundle = std::env::temp_dir().join(format!("hs_migrate_{}.tar", uuid::Uuid::new_v4()));
+    let sent = match manager.export_collection(user_id, name, &bundle).await {
+        Ok(()) => send_bundle(&mut client, api_key, user_id, target_name, &bundle).await,
+        Err(e) => Err(e.into()),
+    };
+    let _ = tokio::fs::remove_file(&bundle).await;
+    report.bundle_bytes = sent?;
+
+    for _ in 0..MAX_ROUNDS {
+        let ops = drain(&mut feed, &internal_name)?;
+        let settled = ops.len() < SETTLE_OPS;
+        report.replayed_ops += replay(&mut client, api_key, user_id, target_name, ops).await?;
+        if settled {
+            break;
+        }
+    }
+
+    // Switch over: new requests see the redirect, in-flight ones finish here.
+    let paused = Instant::now();
+    manager.set_moved(user_id, name, Some(target_addr))?;
+    // The manager's entry and `col` are the only holders once writes drain.
+    while Arc::strong_count(&col) > 2 {
+        if paused.elapsed() >= IN_FLIGHT_GRACE {
+            // Their writes could publish after the final drain and be lost.
+            manager.set_moved(user_id, name, None)?;
+            return Err(format!(
+                "writes still in flight on '{name}' after {IN_FLIGHT_GRACE:?}; migration aborted"
+            )
+            .into());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    drop(col);
+    let last = match drain(&mut feed, &internal_name) {
+        Ok(ops) => replay(&mut client, api_key, user_id, target_name, ops).await,
+        Err(e) => Err(e),
+    };
+    let replayed = match last {
+        Ok(replayed) => replayed,
+        Err(e) => {
+            manager.set_moved(user_id, name, None)?;
+            return Err(e);
+        }
+    };
+    report.replayed_ops += replayed;
+    if let Err(e) = manager.delete_collection(user_id, name).await {
+        manager.set_moved(user_id, name, None)?;
+        return Err(e.into());
+    }
+    report.write_pause = paused.elapsed();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_proto::hyperspace::{DeleteOp, InsertOp};
+
+    #[test]
+    fn drain_keeps_order_and_only_this_collection() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let log = |collection: &str, operation| ReplicationLog {
+            collection: collection.to_string(),
+            operation: Some(operation),
+            ..Default::default()
+        };
+        let insert = |id| {
+            replication_log::Operation::Insert(InsertOp {
+                id,
+                vector: vec![0.5],
+                ..Default::default()
+            })
+        };
+        tx.send(log("u_docs", insert(1))).unwrap();
+        tx.send(log("u_other", insert(2))).unwrap();
+        tx.send(log(
+            "u_docs",
+            replication_log::Operation::Delete(DeleteOp { id: 1 }),
+        ))
+        .unwrap();
+        // Another tenant's internal name that equals the bare name.
+        tx.send(log(
+            "docs",
+            replication_log::Operation::Delete(DeleteOp { id: 3 }),
+        ))
+        .unwrap();
+        tx.send(log("u_docs", insert(3))).unwrap();
+
+        let ops = drain(&mut rx, "u_docs").unwrap();
+        let ids: Vec<(bool, u32)> = ops
+            .iter()
+            .map(|op| match op {
+                Op::Upsert(v) => (true, v.id),
+                Op::Delete(id) => (false, *id),
+            })
+            .collect();
+        assert_eq!(ids, vec![(true, 1), (false, 1), (true, 3)]);
+        assert_eq!(drain(&mut rx, "u_docs").unwrap(), Vec::new());
+    }
+}
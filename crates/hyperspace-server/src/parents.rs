@@ -0,0 +1,90 @@
+//! Parent/child documents: a collection of chunks (with vectors) whose
+//! `parent_id` metadata points at a point of its parent collection, which
+//! holds the documents themselves, often payload only.
+//!
+//! A parent search retrieves chunks and returns each parent once, ranked by
+//! its best chunk, together with its best-matching chunks. Chunks without a
+//! numeric `parent_id` don't belong to any parent and are dropped.
+
+use hyperspace_core::SearchResult;
+use std::collections::HashMap;
+
+/// Metadata key of a chunk holding the id of its parent.
+pub const PARENT_KEY: &str = "parent_id";
+
+/// Chunks returned per parent when the request leaves it at 0.
+pub const DEFAULT_CHUNKS_PER_PARENT: usize = 3;
+
+/// Chunks fetched per requested parent, since several chunks of the same
+/// document tend to match together.
+const OVERFETCH: usize = 4;
+
+/// Chunks to retrieve so that `top_k` distinct parents are usually found.
+pub fn fetch_size(top_k: usize, chunks_per_parent: usize) -> usize {
+    top_k
+        .saturating_mul(chunks_per_parent.max(OVERFETCH))
+        .max(top_k)
+}
+
+/// Groups hits (best first) by parent: at most `top_k` parents ordered by
+/// their best chunk, each with up to `chunks_per_parent` chunks.
+pub fn group(
+    hits: Vec<SearchResult>,
+    top_k: usize,
+    chunks_per_parent: usize,
+) -> Vec<(u32, Vec<SearchResult>)> {
+    let mut parents: Vec<(u32, Vec<SearchResult>)> = Vec::new();
+    let mut slots: HashMap<u32, usize> = HashMap::new();
+    for hit in hits {
+        let Some(parent) = hit.2.get(PARENT_KEY).and_then(|v| v.parse::<u32>().ok()) else {
+            continue;
+        };
+        let slot = match slots.get(&parent) {
+            Some(&slot) => slot,
+            None if parents.len() < top_k => {
+                slots.insert(parent, parents.len());
+                parents.push((parent, Vec::new()));
+                parents.len() - 1
+            }
+            None => continue,
+        };
+        let chunks = &mut parents[slot].1;
+        if chunks.len() < chunks_per_parent {
+            chunks.push(hit);
+        }
+    }
+    parents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: u32, distance: f64, parent: Option<&str>) -> SearchResult {
+        let meta = parent.map_or_else(HashMap::new, |p| {
+            HashMap::from([(PARENT_KEY.to_string(), p.to_string())])
+        });
+        (id, distance, meta)
+    }
+
+    #[test]
+    fn groups_chunks_by_best_parent() {
+        let hits = vec![
+            chunk(10, 0.1, Some("2")),
+            chunk(11, 0.2, None),
+            chunk(12, 0.3, Some("1")),
+            chunk(13, 0.4, Some("2")),
+            chunk(14, 0.5, Some("2")),
+            chunk(15, 0.6, Some("3")),
+            chunk(16, 0.7, Some("1")),
+        ];
+        let grouped = group(hits, 2, 2);
+        let ids: Vec<(u32, Vec<u32>)> = grouped
+            .iter()
+            .map(|(parent, chunks)| (*parent, chunks.iter().map(|c| c.0).collect()))
+            .collect();
+        assert_eq!(ids, vec![(2, vec![10, 13]), (1, vec![12, 16])]);
+        assert_eq!(fetch_size(5, 3), 20);
+        assert_eq!(fetch_size(5, 10), 50);
+    }
+}
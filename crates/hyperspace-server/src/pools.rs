@@ -0,0 +1,195 @@
+//! Dedicated thread pools for query serving and index maintenance.
+//!
+//! Searches and indexing used to share Tokio's blocking pool, so a vacuum or
+//! a chunk flush could take its threads and leave searches waiting. Each side
+//! now runs on its own rayon pool, and rayon work nested inside a job (chunk
+//! fan-out, parallel rebuilds) stays on that job's pool.
+//!
+//! `HS_SEARCH_THREADS` and `HS_INDEX_THREADS` size the pools (default: one
+//! thread per CPU each). `HS_SEARCH_CPUS` and `HS_INDEX_CPUS` pin a pool's
+//! threads to a CPU list such as `0-11` and `12-15`, so a rebuild cannot take
+//! cores from searches at all. Pinning is only supported on Linux.
+
+use std::any::Any;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pool {
+    /// Searches and chunk scans.
+    Search,
+    /// Graph linking, snapshots, vacuum and chunk flushes.
+    Indexing,
+}
+
+/// A job panicked; carries the panic message.
+#[derive(Debug)]
+pub struct TaskPanicked(String);
+
+impl fmt::Display for TaskPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task panicked: {}", self.0)
+    }
+}
+
+impl std::error::Error for TaskPanicked {}
+
+impl TaskPanicked {
+    fn new(payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Self(message)
+    }
+}
+
+/// Parses a CPU list such as `0-3,8,10-11`.
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((lo, hi)) => {
+                let (lo, hi): (usize, usize) = (lo.trim().parse().ok()?, hi.trim().parse().ok()?);
+                if lo > hi {
+                    return None;
+                }
+                cpus.extend(lo..=hi);
+            }
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    (!cpus.is_empty()).then_some(cpus)
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpus: &[usize]) {
+    // SAFETY: `set` is a plain bitmask owned by this frame; CPU_SET bounds-checks
+    // against CPU_SETSIZE and sched_setaffinity only reads it.
+    let result = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &raw const set)
+    };
+    if result != 0 {
+        eprintln!(
+            "⚠️ Could not pin thread to CPUs {cpus:?}: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_cpus: &[usize]) {}
+
+fn build(name: &'static str, threads_var: &str, cpus_var: &str) -> rayon::ThreadPool {
+    let cpus = std::env::var(cpus_var).ok().and_then(|list| {
+        let cpus = parse_cpu_list(&list);
+        if cpus.is_none() {
+            eprintln!("⚠️ Ignoring {cpus_var}={list}: expected a CPU list like 0-3,8");
+        } else if cfg!(not(target_os = "linux")) {
+            eprintln!("⚠️ Ignoring {cpus_var}: CPU pinning is only supported on Linux");
+            return None;
+        }
+        cpus
+    });
+    let threads = std::env::var(threads_var)
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .or_else(|| cpus.as_ref().map(Vec::len))
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(8, std::num::NonZero::get));
+    println!(
+        "⚙️  {name} pool: {threads} thread(s){}",
+        cpus.as_ref()
+            .map_or(String::new(), |c| format!(" pinned to CPUs {c:?}"))
+    );
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(move |i| format!("hs-{}-{i}", name.to_lowercase()))
+        .start_handler(move |_| {
+            if let Some(cpus) = &cpus {
+                pin_current_thread(cpus);
+            }
+        })
+        .build()
+        .expect("failed to start thread pool")
+}
+
+fn pool(pool: Pool) -> &'static rayon::ThreadPool {
+    static SEARCH: OnceLock<rayon::ThreadPool> = OnceLock::new();
+    static INDEXING: OnceLock<rayon::ThreadPool> = OnceLock::new();
+    match pool {
+        Pool::Search => {
+            SEARCH.get_or_init(|| build("Search", "HS_SEARCH_THREADS", "HS_SEARCH_CPUS"))
+        }
+        Pool::Indexing => {
+            INDEXING.get_or_init(|| build("Indexing", "HS_INDEX_THREADS", "HS_INDEX_CPUS"))
+        }
+    }
+}
+
+/// Runs `f` on `pool`, like `tokio::task::spawn_blocking`: the job starts
+/// right away and the returned future only waits for its result. Inside the
+/// job the caller's Tokio runtime is current, as it is on the blocking pool.
+pub fn spawn<R: Send + 'static>(
+    target: Pool,
+    f: impl FnOnce() -> R + Send + 'static,
+) -> impl std::future::Future<Output = Result<R, TaskPanicked>> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    pool(target).spawn(move || {
+        let _runtime = runtime.as_ref().map(tokio::runtime::Handle::enter);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+        let _ = tx.send(result.map_err(|payload| TaskPanicked::new(&*payload)));
+    });
+    async move {
+        rx.await
+            .unwrap_or_else(|_| Err(TaskPanicked("job dropped".into())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cpu_lists() {
+        assert_eq!(
+            parse_cpu_list("0-3,8, 10-11"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list("3-1"), None);
+        assert_eq!(parse_cpu_list("a"), None);
+        assert_eq!(parse_cpu_list(""), None);
+    }
+
+    #[tokio::test]
+    async fn runs_jobs_on_their_own_pool() {
+        let name = spawn(Pool::Search, || {
+            std::thread::current().name().map(str::to_string)
+        })
+        .await
+        .unwrap();
+        assert!(name.unwrap().starts_with("hs-search-"));
+
+        // The runtime stays reachable from inside a job.
+        let answer = spawn(Pool::Indexing, || {
+            tokio::runtime::Handle::current().block_on(async { 42 })
+        })
+        .await
+        .unwrap();
+        assert_eq!(answer, 42);
+
+        let err = spawn(Pool::Indexing, || -> u32 { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "task panicked: boom");
+    }
+}
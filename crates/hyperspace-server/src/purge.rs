@@ -0,0 +1,177 @@
+//! Signed evidence of a GDPR erasure (`Purge`).
+//!
+//! A purge hard-deletes the points matching a filter (see
+//! `Collection::purge`) and answers with a report of the erased IDs. The
+//! report's `digest` is the SHA-256 of its canonical form: one `key=value`
+//! line per field, in the order `collection`, `node_id`, `logical_clock`,
+//! `purged_at`, `filter`, `wal_records_removed`, `journal_entries_removed`,
+//! `history_generations_removed`, `purged_ids` (ascending, comma-separated).
+//! With `HS_PURGE_SIGNING_KEY` set, `signature` is the HMAC-SHA256 of the
+//! digest under that key, so an auditor holding the key can check that a
+//! stored report came from the cluster unaltered.
+
+use crate::manager::CollectionManager;
+use hmac::{Hmac, Mac};
+use hyperspace_core::{Collection, FilterExpr, HyperspaceResult};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PurgeEvidence {
+    pub collection: String,
+    pub node_id: String,
+    pub logical_clock: u64,
+    /// Unix seconds.
+    pub purged_at: u64,
+    /// The filter as given, keys sorted.
+    pub filter: String,
+    pub wal_records_removed: u64,
+    pub journal_entries_removed: u64,
+    /// Snapshot generations deleted along with the points.
+    pub history_generations_removed: u64,
+    pub purged_ids: Vec<u32>,
+    /// Hex SHA-256 of the canonical form.
+    pub digest: String,
+    /// Hex HMAC-SHA256 of `digest`; empty without a signing key.
+    pub signature: String,
+}
+
+impl PurgeEvidence {
+    /// Sorts the IDs and fills in `digest` and, with `key`, `signature`.
+    pub fn seal(mut self, key: Option<&[u8]>) -> Self {
+        self.purged_ids.sort_unstable();
+        self.digest = hex::encode(Sha256::digest(self.canonical().as_bytes()));
+        self.signature = key.map_or_else(String::new, |key| {
+            hex::encode(hmac_sha256(key, self.digest.as_bytes()))
+        });
+        self
+    }
+
+    /// Seals with the key from `HS_PURGE_SIGNING_KEY`, if set.
+    pub fn seal_from_env(self) -> Self {
+        let key = std::env::var("HS_PURGE_SIGNING_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+        self.seal(key.as_deref().map(str::as_bytes))
+    }
+
+    fn canonical(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "collection={}", self.collection);
+        let _ = writeln!(out, "node_id={}", self.node_id);
+        let _ = writeln!(out, "logical_clock={}", self.logical_clock);
+        let _ = writeln!(out, "purged_at={}", self.purged_at);
+        let _ = writeln!(out, "filter={}", self.filter);
+        let _ = writeln!(out, "wal_records_removed={}", self.wal_records_removed);
+        let _ = writeln!(
+            out,
+            "journal_entries_removed={}",
+            self.journal_entries_removed
+        );
+        let _ = writeln!(
+            out,
+            "history_generations_removed={}",
+            self.history_generations_removed
+        );
+        let ids: Vec<String> = self.purged_ids.iter().map(u32::to_string).collect();
+        let _ = writeln!(out, "purged_ids={}", ids.join(","));
+        out
+    }
+}
+
+/// Purges the points of collection `name` matching the filters and seals
+/// the report.
+pub async fn run(
+    manager: &CollectionManager,
+    name: &str,
+    col: &dyn Collection,
+    filter: &HashMap<String, String>,
+    complex_filters: &[FilterExpr],
+) -> HyperspaceResult<PurgeEvidence> {
+    let clock = manager.tick_cluster_clock().await;
+    let outcome = col.purge(filter, complex_filters, clock).await?;
+    manager.close_generations(col.name());
+    let sorted: BTreeMap<_, _> = filter.iter().collect();
+    let mut described: Vec<String> = sorted.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    described.extend(complex_filters.iter().map(|f| format!("{f:?}")));
+    Ok(PurgeEvidence {
+        collection: name.to_string(),
+        node_id: manager.cluster_state.read().await.node_id.clone(),
+        logical_clock: clock,
+        purged_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        filter: described.join(" AND "),
+        wal_records_removed: outcome.wal_records_removed,
+        journal_entries_removed: outcome.journal_entries_removed,
+        history_generations_removed: outcome.history_generations_removed,
+        purged_ids: outcome.purged,
+        ..PurgeEvidence::default()
+    }
+    .seal_from_env())
+}
+
+/// HMAC-SHA256 (RFC 2104).
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        // Test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: key longer than the block
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn seal_covers_every_field() {
+        let report = PurgeEvidence {
+            collection: "users".into(),
+            node_id: "n1".into(),
+            logical_clock: 42,
+            purged_at: 1_700_000_000,
+            filter: "tenant=acme".into(),
+            purged_ids: vec![9, 3],
+            ..PurgeEvidence::default()
+        };
+        let sealed = report.clone().seal(Some(b"secret"));
+        assert_eq!(sealed.purged_ids, vec![3, 9]);
+        assert_eq!(sealed.digest.len(), 64);
+        assert_eq!(sealed.signature.len(), 64);
+        assert!(report.clone().seal(None).signature.is_empty());
+
+        let tampered = PurgeEvidence {
+            purged_ids: vec![3],
+            ..report.clone()
+        }
+        .seal(Some(b"secret"));
+        assert_ne!(tampered.digest, sealed.digest);
+        assert_ne!(tampered.signature, sealed.signature);
+
+        let history = PurgeEvidence {
+            history_generations_removed: 2,
+            ..report
+        }
+        .seal(Some(b"secret"));
+        assert_ne!(history.digest, sealed.digest);
+    }
+}
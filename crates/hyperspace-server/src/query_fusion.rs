@@ -0,0 +1,232 @@
+//! Multi-query search: several query vectors in one request, fused into one
+//! ranking on the server.
+//!
+//! Query-expansion pipelines (HyDE, paraphrases, multi-vector queries) search
+//! each vector with the same filters and settings, then merge the lists:
+//!
+//! - `MaxSim`: a hit's distance is its best distance to any query.
+//! - `Average`: the mean over all queries. A hit one query didn't return is
+//!   counted at that query's worst returned distance, a lower bound of the
+//!   real one.
+//! - `Rrf`: reciprocal rank fusion; the distance is `1 - Σ 1/(60 + rank)`, so
+//!   smaller still means better.
+//!
+//! Federated search fuses the results of one query in several collections
+//! the same way with RRF; see [`fuse_collections`].
+
+use hyperspace_core::{
+    Collection, FilterExpr, HyperspaceError, HyperspaceResult, SearchParams, SearchResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// More queries than this are rejected; each one is a full search.
+pub const MAX_QUERIES: usize = 32;
+
+/// More collections than this are rejected by a federated search.
+pub const MAX_COLLECTIONS: usize = 32;
+
+/// How many candidates per requested result each query fetches.
+const OVERFETCH: usize = 2;
+
+const RRF_K: f64 = 60.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryFusion {
+    #[default]
+    MaxSim,
+    Average,
+    Rrf,
+}
+
+/// Searches every vector in `queries` and fuses the results.
+pub async fn search_fused(
+    col: &dyn Collection,
+    queries: &[Vec<f64>],
+    filter: &HashMap<String, String>,
+    complex_filters: &[FilterExpr],
+    params: &SearchParams,
+    fusion: QueryFusion,
+) -> HyperspaceResult<Vec<SearchResult>> {
+    if queries.len() > MAX_QUERIES {
+        return Err(HyperspaceError::Validation(format!(
+            "At most {MAX_QUERIES} query vectors per search, got {}",
+            queries.len()
+        )));
+    }
+    let mut inner = params.clone();
+    inner.top_k = params.top_k.saturating_mul(OVERFETCH);
+    let searches = queries
+        .iter()
+        .map(|q| col.search(q, filter, complex_filters, &inner));
+    let lists = futures::future::try_join_all(searches).await?;
+    Ok(fuse(lists, fusion, params.top_k))
+}
+
+/// Merges per-query result lists (each sorted by distance) into the best
+/// `top_k` hits.
+pub fn fuse(lists: Vec<Vec<SearchResult>>, fusion: QueryFusion, top_k: usize) -> Vec<SearchResult> {
+    let n = lists.len() as f64;
+    // id -> (fused value, metadata)
+    let mut fused: HashMap<u32, (f64, HashMap<String, String>)> = HashMap::new();
+    match fusion {
+        QueryFusion::MaxSim => {
+            for (id, distance, meta) in lists.into_iter().flatten() {
+                let entry = fused.entry(id).or_insert((distance, meta));
+                entry.0 = entry.0.min(distance);
+            }
+        }
+        QueryFusion::Average => {
+            let worst: Vec<f64> = lists
+                .iter()
+                .map(|l| l.iter().map(|h| h.1).fold(0.0, f64::max))
+                .collect();
+            let total_worst: f64 = worst.iter().sum();
+            // Start every hit at the sum of worst distances and swap in the
+            // real distance for each query that returned it.
+            for (q, list) in lists.into_iter().enumerate() {
+                for (id, distance, meta) in list {
+                    let entry = fused.entry(id).or_insert((total_worst, meta));
+                    entry.0 += distance - worst[q];
+                }
+            }
+            for entry in fused.values_mut() {
+                entry.0 /= n;
+            }
+        }
+        QueryFusion::Rrf => {
+            for list in lists {
+                for (rank, (id, _, meta)) in list.into_iter().enumerate() {
+                    let entry = fused.entry(id).or_insert((1.0, meta));
+                    entry.0 -= 1.0 / (RRF_K + rank as f64 + 1.0);
+                }
+            }
+        }
+    }
+    let mut hits: Vec<SearchResult> = fused
+        .into_iter()
+        .map(|(id, (distance, meta))| (id, distance, meta))
+        .collect();
+    hits.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    hits.truncate(top_k);
+    hits
+}
+
+/// Fuses per-collection result lists (each sorted by distance) with RRF into
+/// the best `top_k` hits, each with the collection it ranked best in. Ids
+/// are per collection; hits whose `dedupe_key` metadata agree are one
+/// document. Ties keep the order of `lists`.
+pub fn fuse_collections(
+    lists: Vec<(String, Vec<SearchResult>)>,
+    dedupe_key: Option<&str>,
+    top_k: usize,
+) -> Vec<(String, SearchResult)> {
+    #[derive(PartialEq, Eq, Hash)]
+    enum Doc {
+        Shared(String),
+        Hit(usize, u32),
+    }
+    // document -> (fused distance, best rank, list index, hit)
+    let mut fused: HashMap<Doc, (f64, usize, usize, SearchResult)> = HashMap::new();
+    let mut names = Vec::with_capacity(lists.len());
+    for (list_idx, (name, list)) in lists.into_iter().enumerate() {
+        names.push(name);
+        for (rank, hit) in list.into_iter().enumerate() {
+            let key = match dedupe_key.and_then(|key| hit.2.get(key)) {
+                Some(value) => Doc::Shared(value.clone()),
+                None => Doc::Hit(list_idx, hit.0),
+            };
+            let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match fused.entry(key) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    let entry = entry.get_mut();
+                    entry.0 -= score;
+                    if rank < entry.1 {
+                        (entry.1, entry.2, entry.3) = (rank, list_idx, hit);
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert((1.0 - score, rank, list_idx, hit));
+                }
+            }
+        }
+    }
+    let mut hits: Vec<_> = fused.into_values().collect();
+    hits.sort_by(|a, b| {
+        a.0.total_cmp(&b.0)
+            .then(a.2.cmp(&b.2))
+            .then(a.3 .0.cmp(&b.3 .0))
+    });
+    hits.truncate(top_k);
+    hits.into_iter()
+        .map(|(distance, _, list_idx, (id, _, meta))| {
+            (names[list_idx].clone(), (id, distance, meta))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(hits: &[(u32, f64)]) -> Vec<SearchResult> {
+        hits.iter()
+            .map(|&(id, d)| (id, d, HashMap::new()))
+            .collect()
+    }
+
+    fn ids(hits: &[SearchResult]) -> Vec<u32> {
+        hits.iter().map(|h| h.0).collect()
+    }
+
+    #[test]
+    fn strategies_rank_differently() {
+        let lists = || {
+            vec![
+                list(&[(1, 0.1), (2, 0.5), (3, 0.6)]),
+                list(&[(2, 0.2), (3, 0.3), (4, 0.9)]),
+            ]
+        };
+
+        let max = fuse(lists(), QueryFusion::MaxSim, 3);
+        assert_eq!(ids(&max), vec![1, 2, 3]);
+        assert!((max[1].1 - 0.2).abs() < 1e-12);
+
+        // 2: (0.5 + 0.2) / 2; 3: (0.6 + 0.3) / 2; 1: (0.1 + 0.9) / 2.
+        let avg = fuse(lists(), QueryFusion::Average, 3);
+        assert_eq!(ids(&avg), vec![2, 3, 1]);
+        assert!((avg[0].1 - 0.35).abs() < 1e-12);
+        assert!((avg[2].1 - 0.5).abs() < 1e-12);
+
+        // 2 is ranked 2nd and 1st, 3 is 3rd and 2nd, 1 is only 1st once.
+        let rrf = fuse(lists(), QueryFusion::Rrf, 2);
+        assert_eq!(ids(&rrf), vec![2, 3]);
+        assert!(rrf[0].1 < rrf[1].1);
+    }
+
+    #[test]
+    fn collections_fuse_by_rank_and_dedupe_key() {
+        let doc = |id: u32, sku: &str| (id, 0.5, HashMap::from([("sku".into(), sku.into())]));
+        let lists = || {
+            vec![
+                ("products".to_string(), vec![doc(1, "a"), doc(2, "b")]),
+                ("docs".to_string(), vec![doc(1, "c"), doc(7, "b")]),
+            ]
+        };
+
+        // Same ids in two collections are different documents.
+        let hits = fuse_collections(lists(), None, 4);
+        let tagged: Vec<(&str, u32)> = hits.iter().map(|(c, h)| (c.as_str(), h.0)).collect();
+        assert_eq!(
+            tagged,
+            vec![("products", 1), ("docs", 1), ("products", 2), ("docs", 7)]
+        );
+
+        // "b" is 2nd in both and outranks the first hits of each.
+        let hits = fuse_collections(lists(), Some("sku"), 4);
+        assert_eq!(hits.len(), 3);
+        assert_eq!((hits[0].0.as_str(), hits[0].1 .0), ("products", 2));
+        assert!((hits[0].1 .1 - (1.0 - 2.0 / 62.0)).abs() < 1e-12);
+    }
+}
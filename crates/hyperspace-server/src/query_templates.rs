@@ -0,0 +1,268 @@
+//! Named, server-side search templates.
+//!
+//! A template captures everything about a retrieval except the query vector:
+//! a filter expression (see [`hyperspace_core::parse_filter`]), `ef_search`,
+//! hybrid settings and an optional `group_by`. Clients invoke it by name and
+//! fill `$placeholders` from a parameter map, so complex configurations live
+//! on the server and change without redeploying callers.
+//!
+//! Templates are stored per collection in `queries.json` next to `meta.json`.
+//! Every overwrite bumps the template's `version`.
+
+use hyperspace_core::{
+    Collection, FilterExpr, HyperspaceError, HyperspaceResult, SearchParams, SearchResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+pub const TEMPLATES_FILE: &str = "queries.json";
+
+/// How many candidates per requested result are fetched before grouping.
+const GROUP_OVERFETCH: usize = 4;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryTemplate {
+    /// Filter string with `$name` placeholders, e.g. `genre = $genre AND year >= $from`.
+    #[serde(default)]
+    pub filter_expr: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef_search: Option<usize>,
+    /// Keyword query for hybrid search; may contain placeholders such as `$text`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hybrid_query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hybrid_alpha: Option<f32>,
+    /// Metadata key whose values partition results; each value keeps at most
+    /// `group_size` hits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_size: Option<usize>,
+    /// Values used for placeholders the caller does not supply.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: HashMap<String, String>,
+    /// Assigned by the server; incremented on every overwrite.
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Replaces `$name` placeholders in `input`. `quote` wraps non-numeric values
+/// as filter string literals so `genre = $genre` works for any value.
+fn substitute(
+    input: &str,
+    params: &HashMap<String, String>,
+    defaults: &HashMap<String, String>,
+    quote: bool,
+) -> HyperspaceResult<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        if len == 0 {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let name = &after[..len];
+        let value = params
+            .get(name)
+            .or_else(|| defaults.get(name))
+            .ok_or_else(|| {
+                HyperspaceError::Validation(format!("Missing template parameter '${name}'"))
+            })?;
+        if quote && value.parse::<f64>().is_err() {
+            out.push('"');
+            out.push_str(&value.replace('\\', "\\\\").replace('"', "\\\""));
+            out.push('"');
+        } else {
+            out.push_str(value);
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+impl QueryTemplate {
+    /// Checks that the template's filter parses once placeholders are filled
+    /// with a neutral value, so broken templates are rejected at registration.
+    pub fn validate(&self) -> HyperspaceResult<()> {
+        let probe = substitute_all_with(&self.filter_expr, "0");
+        hyperspace_core::parse_filter(&probe)?;
+        if self.group_size == Some(0) {
+            return Err(HyperspaceError::Validation(
+                "group_size must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fills placeholders and builds the filters and search parameters.
+    /// `top_k` overrides the template's own value when set.
+    pub fn render(
+        &self,
+        params: &HashMap<String, String>,
+        top_k: Option<usize>,
+        default_ef_search: usize,
+    ) -> HyperspaceResult<(Vec<FilterExpr>, SearchParams)> {
+        let filter = substitute(&self.filter_expr, params, &self.defaults, true)?;
+        let filters = hyperspace_core::parse_filter(&filter)?;
+        let hybrid_query = self
+            .hybrid_query
+            .as_deref()
+            .map(|q| substitute(q, params, &self.defaults, false))
+            .transpose()?;
+
+        let top_k = top_k.filter(|&k| k > 0).or(self.top_k).unwrap_or(10);
+        let fetch = if self.group_by.is_some() {
+            top_k * GROUP_OVERFETCH
+        } else {
+            top_k
+        };
+        let search = SearchParams {
+            top_k: fetch,
+            ef_search: self.ef_search.unwrap_or(default_ef_search).max(fetch),
+            hybrid_query,
+            hybrid_alpha: self.hybrid_alpha,
+            use_wasserstein: false,
+            bm25_options: None,
+            fusion_method: None,
+            text_match: hyperspace_core::TextMatch::Any,
+            trace: None,
+            num_probes: 0,
+        };
+        Ok((filters, search))
+    }
+
+    /// Applies `group_by` and truncates to `top_k`. Results without the
+    /// grouping key form their own group.
+    pub fn group(&self, results: Vec<SearchResult>, top_k: usize) -> Vec<SearchResult> {
+        let Some(key) = self.group_by.as_deref() else {
+            return results.into_iter().take(top_k).collect();
+        };
+        let per_group = self.group_size.unwrap_or(1).max(1);
+        let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+        results
+            .into_iter()
+            .filter(|(_, _, meta)| {
+                let n = counts.entry(meta.get(key).cloned()).or_default();
+                *n += 1;
+                *n <= per_group
+            })
+            .take(top_k)
+            .collect()
+    }
+}
+
+fn substitute_all_with(input: &str, value: &str) -> String {
+    let names: HashSet<String> = input
+        .split('$')
+        .skip(1)
+        .map(|s| {
+            s.chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect()
+        })
+        .filter(|s: &String| !s.is_empty())
+        .collect();
+    let params = names.into_iter().map(|n| (n, value.to_string())).collect();
+    substitute(input, &params, &HashMap::new(), false).unwrap_or_default()
+}
+
+/// Runs `template` against `col` with the given query vector and parameters.
+pub async fn execute(
+    col: &dyn Collection,
+    template: &QueryTemplate,
+    vector: &[f64],
+    params: &HashMap<String, String>,
+    top_k: Option<usize>,
+    default_ef_search: usize,
+) -> HyperspaceResult<Vec<SearchResult>> {
+    let (filters, search) = template.render(params, top_k, default_ef_search)?;
+    let k = top_k.filter(|&k| k > 0).or(template.top_k).unwrap_or(10);
+    let results = col
+        .search(vector, &HashMap::new(), &filters, &search)
+        .await?;
+    Ok(template.group(results, k))
+}
+
+pub fn load(dir: &Path) -> HyperspaceResult<BTreeMap<String, QueryTemplate>> {
+    match std::fs::read_to_string(dir.join(TEMPLATES_FILE)) {
+        Ok(s) => serde_json::from_str(&s)
+            .map_err(|e| HyperspaceError::Corruption(format!("{TEMPLATES_FILE}: {e}"))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save(dir: &Path, templates: &BTreeMap<String, QueryTemplate>) -> HyperspaceResult<()> {
+    let s = serde_json::to_string_pretty(templates)
+        .map_err(|e| HyperspaceError::Internal(e.to_string()))?;
+    let tmp = dir.join(format!("{TEMPLATES_FILE}.tmp"));
+    std::fs::write(&tmp, s)?;
+    std::fs::rename(tmp, dir.join(TEMPLATES_FILE))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_and_quotes_placeholders() {
+        let tpl = QueryTemplate {
+            filter_expr: "genre = $genre AND year >= $from".into(),
+            hybrid_query: Some("$text".into()),
+            defaults: params(&[("from", "1990")]),
+            ..Default::default()
+        };
+        tpl.validate().unwrap();
+        let (filters, search) = tpl
+            .render(
+                &params(&[("genre", "free \"jazz\""), ("text", "blue")]),
+                None,
+                100,
+            )
+            .unwrap();
+        assert!(matches!(
+            &filters[0],
+            FilterExpr::Match { key, value } if key == "genre" && value == "free \"jazz\""
+        ));
+        let FilterExpr::Range { gte, .. } = &filters[1] else {
+            panic!("expected range, got {:?}", filters[1]);
+        };
+        assert_eq!(*gte, Some(1990.0));
+        assert_eq!(search.hybrid_query.as_deref(), Some("blue"));
+        assert_eq!(search.top_k, 10);
+
+        let err = tpl.render(&HashMap::new(), None, 100).unwrap_err();
+        assert!(err.to_string().contains("$genre"));
+    }
+
+    #[test]
+    fn group_by_keeps_best_hits_per_value() {
+        let tpl = QueryTemplate {
+            group_by: Some("artist".into()),
+            group_size: Some(1),
+            ..Default::default()
+        };
+        let hit = |id: u32, artist: &str| (id, f64::from(id), params(&[("artist", artist)]));
+        let grouped = tpl.group(vec![hit(1, "a"), hit(2, "a"), hit(3, "b"), hit(4, "c")], 2);
+        let ids: Vec<u32> = grouped.iter().map(|r| r.0).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+}
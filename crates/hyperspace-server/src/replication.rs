@@ -0,0 +1,541 @@
+//! Durable replication feed.
+//!
+//! Every operation a leader exports goes through [`ReplicationFeed::publish`].
+//! When a journal is attached the entry is first appended to
+//! `replication.log` in the data directory, then broadcast to live
+//! subscribers. A follower that reconnects, or that falls behind the broadcast
+//! channel, is replayed from the journal starting at its last applied clock
+//! instead of silently missing operations.
+//!
+//! The journal is two segments: the active `replication.log` and the previous
+//! `replication.log.1`. The active segment rotates once it exceeds
+//! `HS_REPLICATION_LOG_SEGMENT_BYTES`, so retention is bounded to roughly two
+//! segments. The most recent `HS_REPLICATION_BUFFER` entries are also kept in
+//! memory so short catch-ups never touch the disk.
+//!
+//! Frame format: `[len: u32 LE][crc32: u32 LE][ReplicationLog protobuf]`.
+
+use hyperspace_proto::hyperspace::{replication_log, ReplicationLog, StorageAlertEvent};
+use parking_lot::Mutex;
+use prost::Message;
+use std::collections::{HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+pub const JOURNAL_FILE: &str = "replication.log";
+const DEFAULT_SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_BUFFER: usize = 10_000;
+const ALERT_BUFFER: usize = 64;
+const FRAME_HEADER: usize = 8;
+
+fn previous_segment(active: &Path) -> PathBuf {
+    active.with_extension("log.1")
+}
+
+/// Reads frames from `reader` until EOF or the first torn/corrupt frame.
+/// Returns the byte length of the valid prefix.
+fn read_frames(
+    reader: &mut impl Read,
+    mut f: impl FnMut(ReplicationLog) -> bool,
+) -> io::Result<u64> {
+    let mut valid = 0u64;
+    let mut header = [0u8; FRAME_HEADER];
+    let mut payload = Vec::new();
+    loop {
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(valid),
+            Err(e) => return Err(e),
+        }
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        payload.resize(len, 0);
+        match reader.read_exact(&mut payload) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(valid),
+            Err(e) => return Err(e),
+        }
+        if crc32fast::hash(&payload) != crc {
+            return Ok(valid);
+        }
+        let Ok(log) = ReplicationLog::decode(payload.as_slice()) else {
+            return Ok(valid);
+        };
+        valid += (FRAME_HEADER + len) as u64;
+        if !f(log) {
+            return Ok(valid);
+        }
+    }
+}
+
+/// Writes one frame; returns its size in bytes.
+fn write_frame(writer: &mut impl Write, log: &ReplicationLog) -> io::Result<u64> {
+    let payload = log.encode_to_vec();
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&crc32fast::hash(&payload).to_le_bytes())?;
+    writer.write_all(&payload)?;
+    Ok((FRAME_HEADER + payload.len()) as u64)
+}
+
+fn read_segment(path: &Path, f: impl FnMut(ReplicationLog) -> bool) -> io::Result<u64> {
+    match File::open(path) {
+        Ok(file) => read_frames(&mut BufReader::new(file), f),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+struct JournalInner {
+    writer: BufWriter<File>,
+    segment_bytes: u64,
+    recent: VecDeque<ReplicationLog>,
+    // First clock of each segment, for the retention check
+    previous_first: Option<u64>,
+    active_first: Option<u64>,
+    newest: Option<u64>,
+}
+
+/// Append-only on-disk log of exported replication entries.
+pub struct ReplicationJournal {
+    path: PathBuf,
+    max_segment_bytes: u64,
+    buffer_capacity: usize,
+    inner: Mutex<JournalInner>,
+}
+
+impl ReplicationJournal {
+    /// Opens (or creates) the journal in `dir`, truncating a torn tail left
+    /// by a crash. Sizes come from `HS_REPLICATION_LOG_SEGMENT_BYTES` and
+    /// `HS_REPLICATION_BUFFER`.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let max_segment_bytes = std::env::var("HS_REPLICATION_LOG_SEGMENT_BYTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_SEGMENT_BYTES)
+            .max(4096);
+        let buffer_capacity = std::env::var("HS_REPLICATION_BUFFER")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_BUFFER);
+        Self::open_with(dir, max_segment_bytes, buffer_capacity)
+    }
+
+    pub fn open_with(
+        dir: &Path,
+        max_segment_bytes: u64,
+        buffer_capacity: usize,
+    ) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(JOURNAL_FILE);
+        let mut recent = VecDeque::with_capacity(buffer_capacity.min(DEFAULT_BUFFER));
+        let mut newest = None;
+        let mut push = |log: ReplicationLog| {
+            newest = newest.max(Some(log.logical_clock));
+            if buffer_capacity > 0 {
+                if recent.len() == buffer_capacity {
+                    recent.pop_front();
+                }
+                recent.push_back(log);
+            }
+        };
+
+        let mut previous_first = None;
+        read_segment(&previous_segment(&path), |log| {
+            previous_first.get_or_insert(log.logical_clock);
+            push(log);
+            true
+        })?;
+        let mut active_first = None;
+        let valid = read_segment(&path, |log| {
+            active_first.get_or_insert(log.logical_clock);
+            push(log);
+            true
+        })?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)?;
+        if file.metadata()?.len() > valid {
+            eprintln!("⚠️ Replication journal: truncating torn tail at byte {valid}");
+            file.set_len(valid)?;
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            path,
+            max_segment_bytes,
+            buffer_capacity,
+            inner: Mutex::new(JournalInner {
+                writer: BufWriter::new(file),
+                segment_bytes: valid,
+                recent,
+                previous_first,
+                active_first,
+                newest,
+            }),
+        })
+    }
+
+    pub fn append(&self, log: &ReplicationLog) -> io::Result<()> {
+        let mut inner = self.inner.lock();
+        let written = write_frame(&mut inner.writer, log)?;
+        // Readers open the file independently, so every frame must reach the OS.
+        inner.writer.flush()?;
+        inner.segment_bytes += written;
+        inner.active_first.get_or_insert(log.logical_clock);
+        inner.newest = inner.newest.max(Some(log.logical_clock));
+
+        if self.buffer_capacity > 0 {
+            if inner.recent.len() == self.buffer_capacity {
+                inner.recent.pop_front();
+            }
+            inner.recent.push_back(log.clone());
+        }
+
+        if inner.segment_bytes >= self.max_segment_bytes {
+            inner.writer.get_ref().sync_data()?;
+            std::fs::rename(&self.path, previous_segment(&self.path))?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            inner.writer = BufWriter::new(file);
+            inner.segment_bytes = 0;
+            inner.previous_first = inner.active_first.take();
+        }
+        Ok(())
+    }
+
+    /// Drops the inserts of user IDs `ids` into `collection` from both
+    /// segments and the in-memory buffer. Returns how many were dropped.
+    pub fn redact(&self, collection: &str, ids: &HashSet<u32>) -> io::Result<u64> {
+        let redacted = |log: &ReplicationLog| {
+            log.collection == collection
+                && matches!(
+                    &log.operation,
+                    Some(replication_log::Operation::Insert(op)) if ids.contains(&op.id)
+                )
+        };
+        let mut inner = self.inner.lock();
+        inner.writer.flush()?;
+        inner.recent.retain(|log| !redacted(log));
+
+        let mut dropped = 0;
+        for segment in [previous_segment(&self.path), self.path.clone()] {
+            let mut kept = Vec::new();
+            let mut removed = 0;
+            read_segment(&segment, |log| {
+                if redacted(&log) {
+                    removed += 1;
+                } else {
+                    kept.push(log);
+                }
+                true
+            })?;
+            if removed == 0 {
+                continue;
+            }
+            dropped += removed;
+
+            let mut tmp = segment.clone().into_os_string();
+            tmp.push(".redact");
+            let tmp = PathBuf::from(tmp);
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            let mut bytes = 0;
+            for log in &kept {
+                bytes += write_frame(&mut writer, log)?;
+            }
+            writer
+                .into_inner()
+                .map_err(io::IntoInnerError::into_error)?
+                .sync_all()?;
+            std::fs::rename(&tmp, &segment)?;
+            if segment == self.path {
+                let file = OpenOptions::new().append(true).open(&self.path)?;
+                inner.writer = BufWriter::new(file);
+                inner.segment_bytes = bytes;
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// Oldest clock still retained, or `None` if the journal is empty.
+    pub fn oldest_clock(&self) -> Option<u64> {
+        let inner = self.inner.lock();
+        inner.previous_first.or(inner.active_first)
+    }
+
+    /// Newest clock appended, or `None` if the journal is empty.
+    pub fn newest_clock(&self) -> Option<u64> {
+        self.inner.lock().newest
+    }
+
+    /// Calls `f` for every retained entry with `from <= clock <= to`, in append
+    /// order, until it returns `false`. Blocking: call from a blocking thread.
+    ///
+    /// Served from the in-memory buffer when it reaches back far enough.
+    pub fn replay(
+        &self,
+        from: u64,
+        to: Option<u64>,
+        mut f: impl FnMut(ReplicationLog) -> bool,
+    ) -> io::Result<()> {
+        let in_range = |log: &ReplicationLog| {
+            log.logical_clock >= from && to.is_none_or(|to| log.logical_clock <= to)
+        };
+        let buffered: Option<Vec<ReplicationLog>> = {
+            let inner = self.inner.lock();
+            // Entries are appended in clock order, so anything after a buffered
+            // entry older than `from` is complete.
+            let covered = inner
+                .recent
+                .front()
+                .is_some_and(|first| first.logical_clock < from);
+            covered.then(|| {
+                inner
+                    .recent
+                    .iter()
+                    .filter(|l| in_range(l))
+                    .cloned()
+                    .collect()
+            })
+        };
+        if let Some(entries) = buffered {
+            for log in entries {
+                if !f(log) {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut stopped = false;
+        for segment in [previous_segment(&self.path), self.path.clone()] {
+            read_segment(&segment, |log| {
+                if in_range(&log) && !f(log) {
+                    stopped = true;
+                }
+                !stopped
+            })?;
+            if stopped {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sender side of replication: optional journal plus live broadcast.
+/// Storage alerts ride alongside for `SubscribeToEvents`; they are local to
+/// this node and never journaled or replicated.
+#[derive(Clone)]
+pub struct ReplicationFeed {
+    tx: broadcast::Sender<ReplicationLog>,
+    journal: Option<Arc<ReplicationJournal>>,
+    alerts: broadcast::Sender<StorageAlertEvent>,
+}
+
+impl From<broadcast::Sender<ReplicationLog>> for ReplicationFeed {
+    fn from(tx: broadcast::Sender<ReplicationLog>) -> Self {
+        Self {
+            tx,
+            journal: None,
+            alerts: broadcast::channel(ALERT_BUFFER).0,
+        }
+    }
+}
+
+impl ReplicationFeed {
+    pub fn with_journal(
+        tx: broadcast::Sender<ReplicationLog>,
+        journal: ReplicationJournal,
+    ) -> Self {
+        Self {
+            journal: Some(Arc::new(journal)),
+            ..Self::from(tx)
+        }
+    }
+
+    /// Whether anyone consumes entries; producers skip building them otherwise.
+    pub fn is_active(&self) -> bool {
+        self.journal.is_some() || self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, log: ReplicationLog) {
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.append(&log) {
+                eprintln!("⚠️ Replication journal append failed: {e}");
+            }
+        }
+        let _ = self.tx.send(log);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ReplicationLog> {
+        self.tx.subscribe()
+    }
+
+    /// Drops the inserts of `ids` into `collection` from the journal, if
+    /// one is attached. Entries already sent to followers are not recalled.
+    pub fn redact(&self, collection: &str, ids: &HashSet<u32>) -> io::Result<u64> {
+        match &self.journal {
+            Some(journal) => journal.redact(collection, ids),
+            None => Ok(0),
+        }
+    }
+
+    pub fn journal(&self) -> Option<&Arc<ReplicationJournal>> {
+        self.journal.as_ref()
+    }
+
+    pub fn alert(&self, alert: StorageAlertEvent) {
+        let _ = self.alerts.send(alert);
+    }
+
+    pub fn subscribe_alerts(&self) -> broadcast::Receiver<StorageAlertEvent> {
+        self.alerts.subscribe()
+    }
+}
+
+/// Streams journaled entries in `[from, to]` into a `Replicate` response,
+/// stamped with the streaming leader's `epoch`. Returns the newest clock
+/// sent, or `None` once the stream is closed or the journal could not be read.
+pub async fn replay_into(
+    journal: Arc<ReplicationJournal>,
+    from: u64,
+    to: Option<u64>,
+    epoch: u64,
+    tx: mpsc::Sender<Result<ReplicationLog, tonic::Status>>,
+) -> Option<u64> {
+    tokio::task::spawn_blocking(move || {
+        let mut newest = from;
+        let mut open = true;
+        let res = journal.replay(from, to, |mut log| {
+            newest = newest.max(log.logical_clock);
+            log.epoch = epoch;
+            open = tx.blocking_send(Ok(log)).is_ok();
+            open
+        });
+        if let Err(e) = res {
+            let _ = tx.blocking_send(Err(tonic::Status::internal(format!(
+                "Replication journal read failed: {e}"
+            ))));
+            return None;
+        }
+        open.then_some(newest)
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_proto::hyperspace::{DeleteOp, InsertOp};
+
+    fn entry(clock: u64) -> ReplicationLog {
+        ReplicationLog {
+            logical_clock: clock,
+            origin_node_id: "n1".into(),
+            collection: "c".into(),
+            operation: Some(replication_log::Operation::Delete(DeleteOp {
+                id: clock as u32,
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn clocks(journal: &ReplicationJournal, from: u64, to: Option<u64>) -> Vec<u64> {
+        let mut out = Vec::new();
+        journal
+            .replay(from, to, |l| {
+                out.push(l.logical_clock);
+                true
+            })
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn replays_ranges_across_rotation_and_restart() {
+        let dir = std::env::temp_dir().join(format!("hs_repl_journal_{}", uuid::Uuid::new_v4()));
+        {
+            // Tiny segments and buffer force rotation and disk reads.
+            let journal = ReplicationJournal::open_with(&dir, 4096, 4).unwrap();
+            for clock in 1..=600 {
+                journal.append(&entry(clock)).unwrap();
+            }
+            let oldest = journal.oldest_clock().unwrap();
+            assert!(oldest > 1, "old segments are dropped");
+            assert_eq!(clocks(&journal, 598, None), vec![598, 599, 600]);
+            assert_eq!(clocks(&journal, oldest, Some(oldest + 2)).len(), 3);
+        }
+
+        // A torn frame at the tail is discarded on reopen.
+        let mut f = OpenOptions::new()
+            .append(true)
+            .open(dir.join(JOURNAL_FILE))
+            .unwrap();
+        f.write_all(&[9, 0, 0, 0, 1, 2]).unwrap();
+        drop(f);
+
+        let journal = ReplicationJournal::open_with(&dir, 4096, 4).unwrap();
+        journal.append(&entry(601)).unwrap();
+        assert_eq!(clocks(&journal, 599, None), vec![599, 600, 601]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn redacts_inserts_of_purged_ids() {
+        let dir = std::env::temp_dir().join(format!("hs_repl_redact_{}", uuid::Uuid::new_v4()));
+        let insert = |clock: u64, collection: &str, id: u32| ReplicationLog {
+            logical_clock: clock,
+            collection: collection.into(),
+            operation: Some(replication_log::Operation::Insert(InsertOp {
+                id,
+                vector: vec![0.5; 4],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let journal = ReplicationJournal::open_with(&dir, 4096, 2).unwrap();
+        for clock in 1..=100 {
+            journal
+                .append(&insert(clock, "c", clock as u32 % 10))
+                .unwrap();
+        }
+        journal.append(&insert(101, "other", 7)).unwrap();
+        journal.append(&entry(102)).unwrap();
+
+        let retained = clocks(&journal, 0, None).len() as u64;
+        let dropped = journal.redact("c", &HashSet::from([7])).unwrap();
+        assert!(dropped > 0);
+        assert_eq!(clocks(&journal, 0, None).len() as u64, retained - dropped);
+        let mut left = Vec::new();
+        journal
+            .replay(0, None, |l| {
+                if let Some(replication_log::Operation::Insert(op)) = &l.operation {
+                    left.push((l.collection.clone(), op.id));
+                }
+                true
+            })
+            .unwrap();
+        assert!(!left.contains(&("c".to_string(), 7)));
+        assert!(left.contains(&("other".to_string(), 7)));
+
+        // Appends continue after the rewritten tail and survive a reopen.
+        journal.append(&entry(103)).unwrap();
+        drop(journal);
+        let journal = ReplicationJournal::open_with(&dir, 4096, 2).unwrap();
+        assert_eq!(clocks(&journal, 102, None), vec![102, 103]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,62 @@
+//! Background integrity scrubber.
+//!
+//! Every `HS_SCRUB_INTERVAL_SEC` (default one day, `0` disables) each
+//! resident collection's in-memory segment is walked slot by slot and
+//! compared with the CRC32 recorded when the vector was written. Slots written
+//! before checksums existed are sealed on the way. The graph's node count is
+//! cross-checked against the storage count. Findings are logged, kept for
+//! `GET /api/collections/{name}/scrub` and sent as `STORAGE_ALERT` events.
+//!
+//! With `HS_SCRUB_REPAIR=true` corrupt points are deleted so searches stop
+//! returning garbage; re-insert them from the source of truth. It is off by
+//! default: after a crash a data page and its checksum page may have reached
+//! the disk at different times, and deleting is not undoable.
+//!
+//! The walk is paced at `HS_SCRUB_RATE` vectors per second (default 200k,
+//! `0` unthrottled) so it stays out of the way of searches and writes.
+
+use crate::manager::CollectionManager;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Slots verified between pauses.
+pub(crate) const BATCH: usize = 1024;
+const DEFAULT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_RATE: u64 = 200_000;
+
+/// How long to pause after each batch to stay under `HS_SCRUB_RATE`.
+pub(crate) fn batch_pause() -> Duration {
+    let rate = std::env::var("HS_SCRUB_RATE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RATE);
+    if rate == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(BATCH as f64 / rate as f64)
+}
+
+pub fn spawn(manager: &Arc<CollectionManager>) {
+    let interval = std::env::var("HS_SCRUB_INTERVAL_SEC")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    if interval == 0 {
+        return;
+    }
+    let repair = std::env::var("HS_SCRUB_REPAIR").is_ok_and(|v| v.to_lowercase() == "true");
+    let manager = Arc::downgrade(manager);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            let Some(collections) = manager.upgrade().map(|m| m.resident_collections()) else {
+                break;
+            };
+            for col in collections {
+                if let Err(e) = col.scrub(repair).await {
+                    eprintln!("⚠️ Scrub of '{}' failed: {e}", col.name());
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,128 @@
+//! Per-collection LRU cache of search results.
+//!
+//! Dashboards and agents often resend the exact same query embedding. When
+//! `HS_SEARCH_CACHE_SIZE` is non-zero each collection keeps that many recent
+//! result sets keyed by a hash of (query, filters, k, ef, probes, hybrid
+//! options).
+//! Every write and synonym change advances the cache epoch, so entries
+//! computed before it are never served again.
+
+use hyperspace_core::{FilterExpr, SearchParams, SearchResult};
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide (hits, misses) across all collection caches.
+pub fn stats() -> (u64, u64) {
+    (HITS.load(Ordering::Relaxed), MISSES.load(Ordering::Relaxed))
+}
+
+fn capacity() -> usize {
+    std::env::var("HS_SEARCH_CACHE_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0)
+}
+
+struct Entry {
+    epoch: u64,
+    results: Vec<SearchResult>,
+}
+
+pub struct SearchCache {
+    epoch: AtomicU64,
+    entries: Mutex<LruCache<u64, Entry>>,
+}
+
+impl SearchCache {
+    /// Builds a cache sized from `HS_SEARCH_CACHE_SIZE`, or `None` when disabled.
+    pub fn from_env() -> Option<Arc<Self>> {
+        NonZeroUsize::new(capacity()).map(|cap| Arc::new(Self::new(cap)))
+    }
+
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            epoch: AtomicU64::new(0),
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Advances the write epoch; every cached result becomes stale.
+    pub fn invalidate(&self) {
+        self.epoch.fetch_add(1, Ordering::Release);
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Acquire)
+    }
+
+    pub fn key(
+        query: &[f64],
+        filters: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> u64 {
+        let mut h = std::collections::hash_map::DefaultHasher::new();
+        for v in query {
+            v.to_bits().hash(&mut h);
+        }
+        let mut legacy: Vec<_> = filters.iter().collect();
+        legacy.sort_unstable();
+        legacy.hash(&mut h);
+        // FilterExpr carries f64 bounds, so hash its canonical debug form.
+        format!("{complex_filters:?}").hash(&mut h);
+        params.top_k.hash(&mut h);
+        params.ef_search.hash(&mut h);
+        params.hybrid_query.hash(&mut h);
+        params.hybrid_alpha.map(f32::to_bits).hash(&mut h);
+        params.use_wasserstein.hash(&mut h);
+        format!("{:?}", params.bm25_options).hash(&mut h);
+        params.fusion_method.hash(&mut h);
+        params.text_match.hash(&mut h);
+        // 0 and 1 both run a single search.
+        params.num_probes.max(1).hash(&mut h);
+        h.finish()
+    }
+
+    pub fn get(&self, key: u64) -> Option<Vec<SearchResult>> {
+        let epoch = self.epoch();
+        let mut entries = self.entries.lock();
+        let hit = match entries.get(&key) {
+            Some(entry) if entry.epoch == epoch => Some(entry.results.clone()),
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        };
+        drop(entries);
+        if let Some(results) = hit {
+            HITS.fetch_add(1, Ordering::Relaxed);
+            Some(results)
+        } else {
+            MISSES.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /// Stores results computed at `epoch`; dropped if a write landed meanwhile.
+    pub fn put(&self, key: u64, epoch: u64, results: &[SearchResult]) {
+        if epoch != self.epoch() {
+            return;
+        }
+        self.entries.lock().put(
+            key,
+            Entry {
+                epoch,
+                results: results.to_vec(),
+            },
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! Deterministic simulation of a replicated cluster, for tests.
+//!
+//! A [`Cluster`] runs one leader and several followers in one process, each
+//! a real [`CollectionManager`] over its own data directory. The leader's
+//! replication feed is journaled as in production. Instead of gRPC streams,
+//! a simulated link per follower carries what the leader publishes, and a
+//! seeded RNG on a virtual tick clock decides when entries arrive, when a
+//! stream breaks and when a node restarts. The same seed replays the same
+//! schedule, so a failure can be reproduced from its seed.
+//!
+//! The code paths are the production ones. Followers apply entries with
+//! [`apply_replication_log`], which merges their cluster clock. A broken
+//! stream resumes from the follower's last applied clock out of the journal,
+//! the way `Replicate` serves it, and falls back to what is retained when
+//! that clock is gone. [`Cluster::repair`] diffs Merkle buckets and repairs
+//! them with [`repair_bucket`], as the anti-entropy task does with
+//! `SyncHandshake` and `SyncPull`.
+//!
+//! Bucket hashes need `HS_GOSSIP_ENABLED=true` and lossless vectors
+//! (`HS_QUANTIZATION_LEVEL=none`); [`Cluster::start`] sets both.
+
+use crate::anti_entropy::repair_bucket;
+use crate::apply_replication_log;
+use crate::manager::{ClusterRole, CollectionManager};
+use crate::replication::{ReplicationFeed, ReplicationJournal};
+use crate::sync::CollectionDigest;
+use hyperspace_core::{Collection, Durability};
+use hyperspace_proto::hyperspace::{replication_log, DeleteOp, ReplicationLog, SyncVectorData};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+const USER: &str = "default_admin";
+/// Writes pick ids from this range, so they overwrite and delete each other.
+const ID_SPACE: u32 = 64;
+/// The smallest segment the journal allows: long outages outrun retention.
+const JOURNAL_SEGMENT_BYTES: u64 = 4096;
+const JOURNAL_BUFFER: usize = 16;
+
+/// What can go wrong, and how often. Rates are chances per follower per tick.
+#[derive(Debug, Clone)]
+pub struct Faults {
+    /// Ticks an entry spends in flight. A stream stays ordered, so a slow
+    /// entry holds back the ones behind it.
+    pub delay: RangeInclusive<u64>,
+    /// A follower's stream breaks; whatever was in flight is lost.
+    pub disconnect_rate: f64,
+    /// Ticks a broken stream stays down before the follower reconnects.
+    pub downtime: RangeInclusive<u64>,
+    /// A follower restarts from its data directory, forgetting its position.
+    pub restart_rate: f64,
+    /// Chance per tick that the leader restarts, breaking every stream.
+    pub leader_restart_rate: f64,
+}
+
+impl Faults {
+    /// A healthy network: entries arrive on the next tick.
+    pub fn none() -> Self {
+        Self {
+            delay: 1..=1,
+            disconnect_rate: 0.0,
+            downtime: 1..=1,
+            restart_rate: 0.0,
+            leader_restart_rate: 0.0,
+        }
+    }
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            delay: 1..=5,
+            disconnect_rate: 0.02,
+            downtime: 5..=60,
+            restart_rate: 0.005,
+            leader_restart_rate: 0.002,
+        }
+    }
+}
+
+struct Link {
+    /// Entries on the wire with the tick they arrive at, in stream order.
+    in_flight: VecDeque<(u64, ReplicationLog)>,
+    /// `None` while connected, else the tick the follower reconnects at.
+    down_until: Option<u64>,
+}
+
+struct Follower {
+    dir: PathBuf,
+    manager: Arc<CollectionManager>,
+    /// Newest leader clock applied; in memory only, as in `spawn_follower`.
+    applied_clock: u64,
+    link: Link,
+}
+
+impl Follower {
+    async fn open(dir: PathBuf) -> Self {
+        let (tx, _) = broadcast::channel(16);
+        let manager = Arc::new(CollectionManager::new(dir.clone(), tx));
+        manager.cluster_state.write().await.role = ClusterRole::Follower;
+        Self {
+            dir,
+            manager,
+            applied_clock: 0,
+            link: Link {
+                in_flight: VecDeque::new(),
+                down_until: Some(0),
+            },
+        }
+    }
+}
+
+/// One leader and its followers; see the module docs.
+pub struct Cluster {
+    rng: StdRng,
+    now: u64,
+    faults: Faults,
+    root: PathBuf,
+    leader: Arc<CollectionManager>,
+    feed: ReplicationFeed,
+    published: broadcast::Receiver<ReplicationLog>,
+    /// Newest clock the leader has published; clocks must never go back.
+    last_published: u64,
+    followers: Vec<Follower>,
+    dimensions: BTreeMap<String, u32>,
+    /// What happened when, for reproducing a failure.
+    pub trace: Vec<String>,
+}
+
+impl Cluster {
+    /// Starts a leader and `followers` followers under a fresh temporary
+    /// directory. Every random choice derives from `seed`.
+    pub async fn start(seed: u64, followers: usize, faults: Faults) -> Self {
+        std::env::set_var("HS_GOSSIP_ENABLED", "true");
+        std::env::set_var("HS_QUANTIZATION_LEVEL", "none");
+        let root = std::env::temp_dir().join(format!("hyperspace_sim_{}", uuid::Uuid::new_v4()));
+        let (feed, published) = Self::open_feed(&root.join("leader"));
+        let leader = Arc::new(CollectionManager::new(root.join("leader"), feed.clone()));
+        let mut nodes = Vec::with_capacity(followers);
+        for i in 0..followers {
+            nodes.push(Follower::open(root.join(format!("follower{i}"))).await);
+        }
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            now: 0,
+            faults,
+            root,
+            leader,
+            feed,
+            published,
+            last_published: 0,
+            followers: nodes,
+            dimensions: BTreeMap::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    fn open_feed(dir: &std::path::Path) -> (ReplicationFeed, broadcast::Receiver<ReplicationLog>) {
+        let journal = ReplicationJournal::open_with(dir, JOURNAL_SEGMENT_BYTES, JOURNAL_BUFFER)
+            .expect("open replication journal");
+        let (tx, rx) = broadcast::channel(4096);
+        (ReplicationFeed::with_journal(tx, journal), rx)
+    }
+
+    pub fn leader(&self) -> &CollectionManager {
+        &self.leader
+    }
+
+    /// Creates a collection on the leader; followers learn of it through
+    /// replication like any other write.
+    pub async fn create_collection(&mut self, name: &str, dimension: u32) {
+        self.leader
+            .create_collection(USER, name, dimension, "l2")
+            .await
+            .expect("create collection on leader");
+        self.dimensions.insert(name.to_string(), dimension);
+        self.route();
+    }
+
+    /// Writes to `collection` on the leader as the API handlers do: a random
+    /// upsert or delete of an id in a small range, under a fresh clock.
+    pub async fn write(&mut self, collection: &str) {
+        let col = self
+            .leader
+            .get(USER, collection)
+            .await
+            .expect("collection on leader");
+        let id = self.rng.gen_range(0..ID_SPACE);
+        let clock = self.leader.tick_cluster_clock().await;
+        if self.rng.gen_bool(0.2) {
+            col.delete(id, clock).await.expect("delete on leader");
+            // Collections leave publishing a delete to the `Delete` handler
+            self.feed.publish(ReplicationLog {
+                logical_clock: clock,
+                origin_node_id: self.leader.cluster_state.read().await.node_id.clone(),
+                collection: col.name().to_string(),
+                operation: Some(replication_log::Operation::Delete(DeleteOp { id })),
+                ..Default::default()
+            });
+        } else {
+            let vector: Vec<f64> = (0..self.dimensions[collection])
+                .map(|_| self.rng.gen_range(-1.0..1.0))
+                .collect();
+            col.insert(&vector, id, HashMap::new(), clock, Durability::Default)
+                .await
+                .expect("insert on leader");
+        }
+        self.route();
+    }
+
+    /// Puts what the leader published since the last call on every
+    /// connected follower's wire.
+    fn route(&mut self) {
+        loop {
+            let log = match self.published.try_recv() {
+                Ok(log) => log,
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                    // As `Replicate` does: streams that lagged catch up from the journal
+                    self.trace
+                        .push(format!("t{}: feed lagged by {skipped}", self.now));
+                    for i in 0..self.followers.len() {
+                        self.disconnect(i, 0);
+                    }
+                    continue;
+                }
+                Err(_) => break,
+            };
+            assert!(
+                log.logical_clock >= self.last_published,
+                "leader published clock {} after {}",
+                log.logical_clock,
+                self.last_published
+            );
+            self.last_published = log.logical_clock;
+            for i in 0..self.followers.len() {
+                if self.followers[i].link.down_until.is_none() {
+                    self.send(i, log.clone());
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, follower: usize, log: ReplicationLog) {
+        let delay = self.rng.gen_range(self.faults.delay.clone());
+        let link = &mut self.followers[follower].link;
+        let after = link.in_flight.back().map_or(0, |(at, _)| *at);
+        link.in_flight
+            .push_back(((self.now + delay).max(after), log));
+    }
+
+    /// Breaks `follower`'s stream for `downtime` ticks.
+    fn disconnect(&mut self, follower: usize, downtime: u64) {
+        let link = &mut self.followers[follower].link;
+        link.in_flight.clear();
+        link.down_until = Some(self.now + downtime);
+    }
+
+    /// Reconnects `follower` the way `spawn_follower` and `Replicate` do:
+    /// replay the journal from its last applied clock, or from what is
+    /// retained when that is gone, then stream live.
+    fn reconnect(&mut self, follower: usize) {
+        let journal = self.feed.journal().expect("leader journals").clone();
+        let mut from = self.followers[follower].applied_clock;
+        if let Some(oldest) = journal.oldest_clock() {
+            if from > 0 && from < oldest {
+                self.trace.push(format!(
+                    "t{}: follower{follower} clock {from} no longer retained (oldest {oldest})",
+                    self.now
+                ));
+                from = 0;
+                self.followers[follower].applied_clock = 0;
+            }
+        }
+        self.trace.push(format!(
+            "t{}: follower{follower} resumes at {from}",
+            self.now
+        ));
+        self.followers[follower].link.down_until = None;
+        let mut backlog = Vec::new();
+        journal
+            .replay(from, None, |log| {
+                backlog.push(log);
+                true
+            })
+            .expect("replay journal");
+        for log in backlog {
+            self.send(follower, log);
+        }
+    }
+
+    async fn restart_follower(&mut self, follower: usize) {
+        self.trace
+            .push(format!("t{}: follower{follower} restarts", self.now));
+        let dir = self.followers[follower].dir.clone();
+        let mut reopened = Follower::open(dir).await;
+        reopened.link.down_until = Some(self.now + 1);
+        self.followers[follower] = reopened;
+    }
+
+    fn restart_leader(&mut self) {
+        self.trace.push(format!("t{}: leader restarts", self.now));
+        self.route();
+        let dir = self.root.join("leader");
+        let (feed, published) = Self::open_feed(&dir);
+        self.leader = Arc::new(CollectionManager::new(dir, feed.clone()));
+        self.feed = feed;
+        self.published = published;
+        for i in 0..self.followers.len() {
+            self.disconnect(i, 1);
+        }
+    }
+
+    /// Advances one tick: injects faults, reconnects streams that are due
+    /// and delivers what arrived.
+    pub async fn tick(&mut self) {
+        self.now += 1;
+        if self.rng.gen_bool(self.faults.leader_restart_rate) {
+            self.restart_leader();
+        }
+        for i in 0..self.followers.len() {
+            if self.rng.gen_bool(self.faults.restart_rate) {
+                self.restart_follower(i).await;
+            } else if self.followers[i].link.down_until.is_none()
+                && self.rng.gen_bool(self.faults.disconnect_rate)
+            {
+                let downtime = self.rng.gen_range(self.faults.downtime.clone());
+                self.trace.push(format!(
+                    "t{}: follower{i} disconnected for {downtime}",
+                    self.now
+                ));
+                self.disconnect(i, downtime);
+            }
+            if self.followers[i]
+                .link
+                .down_until
+                .is_some_and(|until| until <= self.now)
+            {
+                self.reconnect(i);
+            }
+            self.deliver(i).await;
+        }
+    }
+
+    async fn deliver(&mut self, follower: usize) {
+        let node = &mut self.followers[follower];
+        while node
+            .link
+            .in_flight
+            .front()
+            .is_some_and(|(at, _)| *at <= self.now)
+        {
+            let (_, log) = node.link.in_flight.pop_front().expect("checked front");
+            let clock = log.logical_clock;
+            node.applied_clock = node.applied_clock.max(clock);
+            apply_replication_log(&node.manager, log).await;
+            let local = node.manager.cluster_state.read().await.logical_clock;
+            assert!(
+                local > clock,
+                "follower{follower} clock {local} did not advance past applied {clock}"
+            );
+        }
+    }
+
+    /// Pauses fault injection and runs until every follower has reconnected
+    /// and received everything in flight.
+    pub async fn heal(&mut self) {
+        let faults = std::mem::replace(&mut self.faults, Faults::none());
+        for i in 0..self.followers.len() {
+            if self.followers[i].link.down_until.is_some() {
+                self.followers[i].link.down_until = Some(self.now);
+            }
+        }
+        loop {
+            self.tick().await;
+            if self
+                .followers
+                .iter()
+                .all(|f| f.link.down_until.is_none() && f.link.in_flight.is_empty())
+            {
+                break;
+            }
+        }
+        self.faults = faults;
+    }
+
+    /// Waits until every node has indexed what it was sent: a point is not
+    /// visible to bucket reads until its indexing job has run.
+    async fn settle(&self) {
+        let nodes = std::iter::once(&self.leader).chain(self.followers.iter().map(|f| &f.manager));
+        for manager in nodes {
+            for name in self.dimensions.keys() {
+                let Some(col) = manager.get(USER, name).await else {
+                    continue;
+                };
+                let start = std::time::Instant::now();
+                while col.indexing_progress().queue_depth > 0 {
+                    assert!(
+                        start.elapsed() < std::time::Duration::from_secs(30),
+                        "indexing of {name} did not drain"
+                    );
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                }
+            }
+        }
+    }
+
+    /// One anti-entropy round against the leader for every follower: diff
+    /// the buckets of each collection and make the divergent ones match.
+    /// Returns the number of buckets repaired.
+    pub async fn repair(&mut self) -> usize {
+        self.settle().await;
+        let clock = self.leader.cluster_state.read().await.logical_clock;
+        let mut total = 0;
+        for i in 0..self.followers.len() {
+            let mut repaired = 0;
+            for name in self.dimensions.keys() {
+                let (Some(theirs), Some(ours)) = (
+                    self.leader.get(USER, name).await,
+                    self.followers[i].manager.get(USER, name).await,
+                ) else {
+                    continue;
+                };
+                let local = ours.buckets();
+                let remote = theirs.buckets();
+                let diff: Vec<u32> = (0..local.len() as u32)
+                    .filter(|&b| local[b as usize] != remote[b as usize])
+                    .collect();
+                let mut pulled: HashMap<u32, Vec<SyncVectorData>> = HashMap::new();
+                for (id, vector, metadata) in theirs.peek_buckets(&diff) {
+                    let bucket_index = CollectionDigest::get_bucket_index(id) as u32;
+                    pulled
+                        .entry(bucket_index)
+                        .or_default()
+                        .push(SyncVectorData {
+                            collection: name.clone(),
+                            id,
+                            vector,
+                            metadata,
+                            bucket_index,
+                        });
+                }
+                for bucket in diff {
+                    let remote = pulled.remove(&bucket).unwrap_or_default();
+                    if repair_bucket(ours.as_ref(), bucket, local[bucket as usize], remote, clock)
+                        .await
+                        .expect("repair bucket")
+                        .is_some()
+                    {
+                        repaired += 1;
+                    }
+                }
+            }
+            if repaired > 0 {
+                self.trace.push(format!(
+                    "t{}: follower{i} repaired {repaired} buckets",
+                    self.now
+                ));
+            }
+            total += repaired;
+        }
+        total
+    }
+
+    /// Where followers differ from the leader: one line per collection that
+    /// is missing, or whose live points or bucket hashes differ. Empty once
+    /// the cluster has converged.
+    pub async fn divergence(&self) -> Vec<String> {
+        self.settle().await;
+        let mut out = Vec::new();
+        for (i, follower) in self.followers.iter().enumerate() {
+            for name in self.dimensions.keys() {
+                let Some(theirs) = self.leader.get(USER, name).await else {
+                    continue;
+                };
+                let Some(ours) = follower.manager.get(USER, name).await else {
+                    out.push(format!("follower{i} has no collection {name}"));
+                    continue;
+                };
+                let (expected, actual) = (live_points(theirs.as_ref()), live_points(ours.as_ref()));
+                let differing = expected
+                    .iter()
+                    .filter(|&(id, vector)| actual.get(id) != Some(vector))
+                    .count()
+                    + actual
+                        .keys()
+                        .filter(|id| !expected.contains_key(id))
+                        .count();
+                let buckets = ours
+                    .buckets()
+                    .iter()
+                    .zip(theirs.buckets())
+                    .filter(|(a, b)| **a != *b)
+                    .count();
+                if differing > 0 || buckets > 0 {
+                    out.push(format!(
+                        "follower{i} {name}: {differing} of {} points differ; {buckets} bucket hashes differ",
+                        expected.len()
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Every live point of `col` by id, with its vector's bit patterns.
+fn live_points(col: &dyn Collection) -> BTreeMap<u32, Vec<u64>> {
+    let all: Vec<u32> = (0..256).collect();
+    col.peek_buckets(&all)
+        .into_iter()
+        .map(|(id, vector, _)| (id, vector.iter().map(|x| x.to_bits()).collect()))
+        .collect()
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,171 @@
+//! Slow query log.
+//!
+//! Searches slower than `HS_SLOW_QUERY_MS` (default 250, 0 disables) are kept
+//! in a ring buffer of the last `HS_SLOW_QUERY_LOG_SIZE` (default 256)
+//! entries with the parameters that usually explain the latency: `top_k`,
+//! `ef_search`, how many filter clauses ran and how many graph nodes the
+//! in-memory index scored. Admins read it at `GET /api/admin/slow-queries`.
+
+use hyperspace_core::{SearchParams, SearchTrace};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlowQuery {
+    pub ts_ms: u64,
+    pub user_id: String,
+    pub collection: String,
+    pub latency_ms: f64,
+    pub top_k: usize,
+    pub ef_search: usize,
+    /// Metadata and geometric filter clauses.
+    pub filter_clauses: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_expr: Option<String>,
+    pub hybrid: bool,
+    /// Query vectors searched (more than one for fused searches).
+    pub queries: usize,
+    pub visited: u64,
+    pub results: usize,
+}
+
+pub struct SlowQueryLog {
+    threshold: Option<Duration>,
+    capacity: usize,
+    entries: Mutex<VecDeque<SlowQuery>>,
+}
+
+/// What a search handler knows about the request before it runs.
+pub struct SearchShape<'a> {
+    pub user_id: &'a str,
+    pub collection: &'a str,
+    pub filter_clauses: usize,
+    pub filter_expr: Option<&'a str>,
+    pub queries: usize,
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold: Option<Duration>, capacity: usize) -> Self {
+        Self {
+            threshold,
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let threshold = env("HS_SLOW_QUERY_MS", 250);
+        Self::new(
+            (threshold > 0).then(|| Duration::from_millis(threshold)),
+            env("HS_SLOW_QUERY_LOG_SIZE", 256) as usize,
+        )
+    }
+
+    /// `params` with a fresh trace attached, or `None` when the log is off or
+    /// `params` already carry a trace.
+    pub fn traced(&self, params: &SearchParams) -> Option<SearchParams> {
+        if self.threshold.is_none() || params.trace.is_some() {
+            return None;
+        }
+        Some(SearchParams {
+            trace: Some(Arc::new(SearchTrace::default())),
+            ..params.clone()
+        })
+    }
+
+    /// Keeps the search if it took longer than the threshold.
+    pub fn observe(
+        &self,
+        shape: &SearchShape<'_>,
+        params: &SearchParams,
+        latency: Duration,
+        results: usize,
+    ) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+        if latency < threshold {
+            return;
+        }
+        let entry = SlowQuery {
+            ts_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            user_id: shape.user_id.to_string(),
+            collection: shape.collection.to_string(),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            top_k: params.top_k,
+            ef_search: params.ef_search,
+            filter_clauses: shape.filter_clauses,
+            filter_expr: shape.filter_expr.map(str::to_string),
+            hybrid: params.hybrid_query.is_some(),
+            queries: shape.queries,
+            visited: params.trace.as_ref().map_or(0, |t| t.visited()),
+            results,
+        };
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Logged searches, newest first, optionally only those of `user_id`.
+    pub fn entries(&self, user_id: Option<&str>, limit: usize) -> Vec<SlowQuery> {
+        self.entries
+            .lock()
+            .iter()
+            .rev()
+            .filter(|e| user_id.is_none_or(|u| e.user_id == u))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(user_id: &str) -> SearchShape<'_> {
+        SearchShape {
+            user_id,
+            collection: "docs",
+            filter_clauses: 2,
+            filter_expr: Some("year >= 1990"),
+            queries: 1,
+        }
+    }
+
+    #[test]
+    fn keeps_newest_slow_searches() {
+        let log = SlowQueryLog::new(Some(Duration::from_millis(10)), 2);
+        let params = log.traced(&SearchParams::default()).unwrap();
+        params.trace.as_ref().unwrap().add_visited(42);
+
+        log.observe(&shape("a"), &params, Duration::from_millis(5), 1);
+        log.observe(&shape("a"), &params, Duration::from_millis(20), 1);
+        log.observe(&shape("b"), &params, Duration::from_millis(30), 0);
+        log.observe(&shape("a"), &params, Duration::from_millis(40), 3);
+
+        let all = log.entries(None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!((all[0].user_id.as_str(), all[0].results), ("a", 3));
+        assert_eq!(all[1].user_id, "b");
+        assert_eq!(all[0].visited, 42);
+        assert_eq!(log.entries(Some("b"), 10).len(), 1);
+
+        let off = SlowQueryLog::new(None, 2);
+        assert!(off.traced(&params).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Per-collection snapshot cadence.
+//!
+//! A collection snapshots its HNSW graph and id/bucket state when any enabled
+//! trigger fires: elapsed time, number of write operations, or active WAL size.
+//! The policy lives in the collection's `meta.json`; unset fields fall back to
+//! `HYPERSPACE_SNAPSHOT_INTERVAL_SEC`, `HS_SNAPSHOT_EVERY_OPS` and
+//! `HS_SNAPSHOT_WAL_BYTES`.
+//!
+//! Filters, payloads and tombstones can also be saved on their own cadence
+//! (`metadata_interval_sec` / `HS_METADATA_SNAPSHOT_INTERVAL_SEC`) to
+//! `metadata.snap`, so delete-heavy workloads replay less WAL after a restart
+//! without rewriting the graph. A metadata snapshot is only taken while the
+//! graph still matches `index.snap`; once points are inserted it waits for
+//! the next graph snapshot.
+//!
+//! With `history` (`HS_SNAPSHOT_HISTORY`), graph snapshots that follow
+//! writes are also kept as generations for time-travel queries, see
+//! [`crate::snapshot_history`].
+
+use crate::snapshot_history;
+use arc_swap::ArcSwap;
+use dashmap::DashMap;
+use hyperspace_core::{HyperspaceResult, Metric};
+use hyperspace_index::HnswIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+fn env_u64(key: &str, default: u64) -> u64 {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPolicy {
+    /// Snapshot at least this often (seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_sec: Option<u64>,
+    /// Snapshot after this many writes; `0` disables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_ops: Option<u64>,
+    /// Snapshot once the active WAL grows past this many bytes; `0` disables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wal_bytes: Option<u64>,
+    /// Snapshot metadata alone this often (seconds); `0` disables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_interval_sec: Option<u64>,
+    /// Historical generations kept queryable; `0` keeps none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<u64>,
+}
+
+impl SnapshotPolicy {
+    pub fn interval(&self) -> Duration {
+        let secs = self
+            .interval_sec
+            .unwrap_or_else(|| env_u64("HYPERSPACE_SNAPSHOT_INTERVAL_SEC", 60));
+        Duration::from_secs(secs.max(1))
+    }
+
+    pub fn every_ops(&self) -> Option<u64> {
+        Some(
+            self.every_ops
+                .unwrap_or_else(|| env_u64("HS_SNAPSHOT_EVERY_OPS", 0)),
+        )
+        .filter(|&n| n > 0)
+    }
+
+    pub fn wal_bytes(&self) -> Option<u64> {
+        Some(
+            self.wal_bytes
+                .unwrap_or_else(|| env_u64("HS_SNAPSHOT_WAL_BYTES", 0)),
+        )
+        .filter(|&n| n > 0)
+    }
+
+    pub fn metadata_interval(&self) -> Option<Duration> {
+        Some(
+            self.metadata_interval_sec
+                .unwrap_or_else(|| env_u64("HS_METADATA_SNAPSHOT_INTERVAL_SEC", 0)),
+        )
+        .filter(|&n| n > 0)
+        .map(Duration::from_secs)
+    }
+
+    pub fn history(&self) -> usize {
+        self.history
+            .unwrap_or_else(|| env_u64("HS_SNAPSHOT_HISTORY", 0)) as usize
+    }
+
+    /// How often the background task re-checks the triggers.
+    pub fn poll_interval(&self) -> Duration {
+        let interval = if self.every_ops().is_some() || self.wal_bytes().is_some() {
+            self.interval().min(Duration::from_secs(1))
+        } else {
+            self.interval()
+        };
+        self.metadata_interval()
+            .map_or(interval, |metadata| interval.min(metadata))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CollectionState {
+    pub id_map: HashMap<u32, u32>,
+    pub reverse_id_map: HashMap<u32, u32>,
+    pub buckets: Vec<u64>,
+    #[serde(default)]
+    pub last_persisted_clock: u64,
+}
+
+/// Everything needed to persist one collection's index and state files.
+pub(crate) struct SnapshotWriter<const N: usize, M: Metric<N>> {
+    pub index_link: Arc<ArcSwap<HnswIndex<N, M>>>,
+    pub snap_path: PathBuf,
+    pub metadata_path: PathBuf,
+    pub state_path: PathBuf,
+    pub id_map: Arc<DashMap<u32, u32>>,
+    pub reverse_id_map: Arc<DashMap<u32, u32>>,
+    pub buckets: Arc<Vec<AtomicU64>>,
+    pub last_clock: Arc<AtomicU64>,
+    /// Writes since the last snapshot (drives the `every_ops` trigger).
+    pub ops_since: AtomicU64,
+    /// Serializes background and forced snapshots.
+    pub write_lock: parking_lot::Mutex<()>,
+    /// The index `index.snap` was taken from and its node count then;
+    /// metadata snapshots are only valid against that graph.
+    pub graph: parking_lot::Mutex<(Weak<HnswIndex<N, M>>, usize)>,
+    /// Generations kept under `history/`; 0 keeps none.
+    pub history: usize,
+    /// Clock of the newest generation, so snapshots without writes in
+    /// between don't add one.
+    pub history_clock: parking_lot::Mutex<Option<u64>>,
+}
+
+impl<const N: usize, M: Metric<N>> SnapshotWriter<N, M> {
+    #[inline]
+    pub fn record_ops(&self, n: u64) {
+        self.ops_since.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Blocking: writes `index.snap` and `state.json`.
+    pub fn write(&self) -> HyperspaceResult<()> {
+        let _guard = self.write_lock.lock();
+        self.write_graph()
+    }
+
+    /// Blocking: writes `metadata.snap` and `state.json`. Returns `false`
+    /// without writing when the graph has changed since `index.snap`.
+    pub fn write_metadata(&self) -> HyperspaceResult<bool> {
+        let _guard = self.write_lock.lock();
+        let idx = self.index_link.load().clone();
+        let (graph, nodes) = self.graph.lock().clone();
+        if !graph.upgrade().is_some_and(|g| Arc::ptr_eq(&g, &idx)) || idx.graph_len() != nodes {
+            return Ok(false);
+        }
+        // Read before the metadata so no delete is both missing from the
+        // file and skipped by WAL replay
+        let clock = self.last_clock.load(Ordering::Relaxed);
+        if !idx.save_metadata_snapshot(&self.metadata_path, nodes)? {
+            // An insert landed while capturing; the graph has to go too
+            self.write_graph()?;
+            return Ok(true);
+        }
+        self.write_state(clock)?;
+        Ok(true)
+    }
+
+    /// Drops `metadata.snap` after `index.snap` was replaced by other means.
+    pub fn discard_metadata(&self) -> HyperspaceResult<()> {
+        match std::fs::remove_file(&self.metadata_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_graph(&self) -> HyperspaceResult<()> {
+        self.ops_since.store(0, Ordering::Relaxed);
+
+        let idx = self.index_link.load().clone();
+        let nodes = idx.graph_len();
+        idx.save_snapshot(&self.snap_path)?;
+        // The graph snapshot carries its own metadata; an older metadata.snap
+        // would override it on load.
+        self.discard_metadata()?;
+        *self.graph.lock() = (Arc::downgrade(&idx), nodes);
+        let clock = self.last_clock.load(Ordering::Relaxed);
+        self.write_state(clock)?;
+        self.record_history(&idx, clock)
+    }
+
+    /// Blocking: deletes every generation, so the next graph snapshot starts
+    /// a new history. Returns how many were removed.
+    pub fn forget_history(&self) -> HyperspaceResult<u64> {
+        let _guard = self.write_lock.lock();
+        let Some(dir) = self.snap_path.parent() else {
+            return Ok(0);
+        };
+        let removed = snapshot_history::remove_all(dir)?;
+        *self.history_clock.lock() = None;
+        Ok(removed)
+    }
+
+    /// Keeps the files just written as a new generation, unless nothing was
+    /// written since the last one.
+    fn record_history(&self, idx: &HnswIndex<N, M>, clock: u64) -> HyperspaceResult<()> {
+        if self.history == 0 {
+            return Ok(());
+        }
+        let mut last = self.history_clock.lock();
+        if *last == Some(clock) {
+            return Ok(());
+        }
+        let Some(dir) = self.snap_path.parent() else {
+            return Ok(());
+        };
+        snapshot_history::record(
+            dir,
+            self.history,
+            &self.snap_path,
+            &self.state_path,
+            &idx.get_storage(),
+        )?;
+        *last = Some(clock);
+        Ok(())
+    }
+
+    fn write_state(&self, clock: u64) -> HyperspaceResult<()> {
+        // Save State (DashMap iteration)
+        let state = CollectionState {
+            id_map: self
+                .id_map
+                .iter()
+                .map(|entry| (*entry.key(), *entry.value()))
+                .collect(),
+            reverse_id_map: self
+                .reverse_id_map
+                .iter()
+                .map(|entry| (*entry.key(), *entry.value()))
+                .collect(),
+            buckets: self
+                .buckets
+                .iter()
+                .map(|b| b.load(Ordering::Relaxed))
+                .collect(),
+            last_persisted_clock: clock,
+        };
+        let s = serde_json::to_string(&state).map_err(|e| e.to_string())?;
+        std::fs::write(&self.state_path, s)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_round_trips_and_disables_zero_triggers() {
+        let policy = SnapshotPolicy {
+            interval_sec: Some(5),
+            every_ops: Some(0),
+            wal_bytes: Some(1 << 20),
+            metadata_interval_sec: Some(0),
+            history: Some(2),
+        };
+        assert_eq!(policy.interval(), Duration::from_secs(5));
+        assert_eq!(policy.history(), 2);
+        assert_eq!(policy.every_ops(), None);
+        assert_eq!(policy.wal_bytes(), Some(1 << 20));
+        assert_eq!(policy.metadata_interval(), None);
+        assert_eq!(policy.poll_interval(), Duration::from_secs(1));
+        let metadata_only = SnapshotPolicy {
+            interval_sec: Some(600),
+            metadata_interval_sec: Some(30),
+            ..SnapshotPolicy::default()
+        };
+        assert_eq!(metadata_only.poll_interval(), Duration::from_secs(30));
+
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SnapshotPolicy>(&json).unwrap(),
+            policy
+        );
+        assert_eq!(
+            serde_json::from_str::<SnapshotPolicy>("{}").unwrap(),
+            SnapshotPolicy::default()
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Historical snapshot generations for time-travel queries.
+//!
+//! With a snapshot `history` of N, every graph snapshot taken after a write
+//! is also kept under `history/<id>/`, the id being the capture time in Unix
+//! milliseconds. A generation holds `index.snap`, `state.json` and a copy of
+//! the stored vectors, so later in-place updates don't reach it. Past N
+//! generations the oldest is removed.
+//!
+//! A search or scroll with `snapshot_id` or `as_of` opens the generation as
+//! a collection of its own, read only and cached by the manager. Immutable
+//! chunks flushed from the WAL in tiered mode are not part of a generation.
+//!
+//! A rollback makes a generation the live state again. The WAL of the state
+//! it replaces is moved to the newest generation's `wal/<time>/`, so it
+//! doesn't replay on top of the older graph and stays available.
+//!
+//! A purge deletes every generation, archived WALs included: they were all
+//! captured before it and may still hold the erased points.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
+use hyperspace_store::VectorStore;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory of a collection holding its generations.
+pub const HISTORY_DIR: &str = "history";
+
+/// Generations kept open across all collections; the least recently used
+/// one is closed past this.
+pub const MAX_OPEN: usize = 4;
+
+/// Which generation a query reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotRef {
+    /// A generation id, as listed by [`generations`].
+    Id(u64),
+    /// The newest generation captured at or before this Unix time (seconds).
+    AsOf(u64),
+}
+
+impl SnapshotRef {
+    /// `snapshot_id` wins over `as_of`; `None` reads the live collection.
+    pub fn from_request(snapshot_id: Option<u64>, as_of: Option<u64>) -> Option<Self> {
+        snapshot_id.map(Self::Id).or(as_of.map(Self::AsOf))
+    }
+}
+
+pub fn generation_dir(col_dir: &Path, id: u64) -> PathBuf {
+    col_dir.join(HISTORY_DIR).join(id.to_string())
+}
+
+/// Ids of a collection's generations, oldest first.
+pub fn generations(col_dir: &Path) -> Vec<u64> {
+    let mut ids: Vec<u64> = std::fs::read_dir(col_dir.join(HISTORY_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("index.snap").exists())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// The id of the generation `snapshot` refers to.
+pub fn resolve(col_dir: &Path, snapshot: SnapshotRef) -> HyperspaceResult<u64> {
+    let ids = generations(col_dir);
+    let found = match snapshot {
+        SnapshotRef::Id(id) => ids.contains(&id).then_some(id),
+        SnapshotRef::AsOf(secs) => ids.iter().rev().find(|&&id| id / 1000 <= secs).copied(),
+    };
+    found.ok_or_else(|| {
+        HyperspaceError::NotFound(match snapshot {
+            SnapshotRef::Id(id) => format!("Snapshot {id} not found"),
+            SnapshotRef::AsOf(secs) => format!("No snapshot taken at or before {secs}"),
+        })
+    })
+}
+
+/// Blocking: copies a snapshot just written into a new generation and drops
+/// the oldest ones past `keep`. Returns the new generation's id.
+pub fn record(
+    col_dir: &Path,
+    keep: usize,
+    snap_path: &Path,
+    state_path: &Path,
+    storage: &VectorStore,
+) -> HyperspaceResult<u64> {
+    let existing = generations(col_dir);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    // Two snapshots within a millisecond still get distinct ids
+    let id = existing.last().map_or(now, |&last| now.max(last + 1));
+
+    let dir = generation_dir(col_dir, id);
+    let partial = dir.with_extension("partial");
+    let _ = std::fs::remove_dir_all(&partial);
+    std::fs::create_dir_all(&partial)?;
+    std::fs::copy(state_path, partial.join("state.json"))?;
+    if storage.element_size() > 0 {
+        VectorStore::from_bytes(&partial, storage.element_size(), &storage.export());
+    }
+    // index.snap marks a complete generation, so it goes last
+    std::fs::copy(snap_path, partial.join("index.snap"))?;
+    std::fs::rename(&partial, &dir)?;
+
+    let excess = (existing.len() + 1).saturating_sub(keep);
+    for old in existing.into_iter().take(excess) {
+        let _ = std::fs::remove_dir_all(generation_dir(col_dir, old));
+    }
+    Ok(id)
+}
+
+/// Blocking: deletes every generation of a collection, complete or not.
+/// Returns how many complete ones were removed.
+pub fn remove_all(col_dir: &Path) -> std::io::Result<u64> {
+    let removed = generations(col_dir).len() as u64;
+    match std::fs::remove_dir_all(col_dir.join(HISTORY_DIR)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(removed),
+    }
+}
+
+/// Whether `name` is a segment of the collection's vector store.
+fn is_store_file(name: &str) -> bool {
+    name.strip_prefix("chunk_")
+        .and_then(|rest| {
+            rest.strip_suffix(".hyp")
+                .or_else(|| rest.strip_suffix(".crc"))
+        })
+        .is_some_and(|n| n.parse::<u32>().is_ok())
+}
+
+/// Blocking: replaces the graph, state and vectors of the closed collection
+/// in `col_dir` with those of generation `id`.
+pub fn restore(col_dir: &Path, id: u64) -> HyperspaceResult<()> {
+    let source = generation_dir(col_dir, id);
+    if !source.join("index.snap").exists() {
+        return Err(HyperspaceError::NotFound(format!(
+            "Snapshot {id} not found"
+        )));
+    }
+    let newest = generations(col_dir).last().copied().unwrap_or(id);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let archive = generation_dir(col_dir, newest)
+        .join("wal")
+        .join(now.to_string());
+    std::fs::create_dir_all(&archive)?;
+
+    for entry in std::fs::read_dir(col_dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        if name.starts_with("wal") {
+            std::fs::rename(entry.path(), archive.join(&name))?;
+        } else if is_store_file(&name) || name == "metadata.snap" {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    for entry in std::fs::read_dir(&source)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == "state.json" || is_store_file(&name) {
+            std::fs::copy(entry.path(), col_dir.join(&name))?;
+        }
+    }
+    // Through a temporary file, like every snapshot write
+    let snap_tmp = col_dir.join("index.snap.tmp");
+    std::fs::copy(source.join("index.snap"), &snap_tmp)?;
+    std::fs::rename(snap_tmp, col_dir.join("index.snap"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_prunes_and_resolves_generations() {
+        let dir = std::env::temp_dir().join(format!("hs_history_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (snap, state) = (dir.join("index.snap"), dir.join("state.json"));
+        std::fs::write(&snap, b"graph").unwrap();
+        std::fs::write(&state, b"{}").unwrap();
+        let storage = VectorStore::new(&dir.join("store"), 8);
+        storage.append(&[1u8; 8]).unwrap();
+
+        let ids: Vec<u64> = (0..3)
+            .map(|_| record(&dir, 2, &snap, &state, &storage).unwrap())
+            .collect();
+        assert_eq!(generations(&dir), ids[1..]);
+        assert_eq!(resolve(&dir, SnapshotRef::Id(ids[2])).unwrap(), ids[2]);
+        assert!(resolve(&dir, SnapshotRef::Id(ids[0])).is_err());
+        assert_eq!(
+            resolve(&dir, SnapshotRef::AsOf(ids[2] / 1000 + 60)).unwrap(),
+            ids[2]
+        );
+        assert!(resolve(&dir, SnapshotRef::AsOf(ids[1] / 1000 - 60)).is_err());
+        let copy = VectorStore::new(&generation_dir(&dir, ids[2]), 8);
+        assert_eq!(copy.get(0), &[1u8; 8]);
+
+        std::fs::write(&snap, b"newer graph").unwrap();
+        std::fs::write(dir.join("wal.log"), b"entries").unwrap();
+        restore(&dir, ids[1]).unwrap();
+        assert_eq!(std::fs::read(&snap).unwrap(), b"graph");
+        assert!(!dir.join("wal.log").exists());
+        let archived = std::fs::read_dir(generation_dir(&dir, ids[2]).join("wal"))
+            .unwrap()
+            .count();
+        assert_eq!(archived, 1);
+        assert!(is_store_file("chunk_12.crc"));
+        assert!(!is_store_file("chunk_0b5e.hyp"));
+
+        assert_eq!(remove_all(&dir).unwrap(), 2);
+        assert_eq!(generations(&dir), Vec::<u64>::new());
+        assert_eq!(remove_all(&dir).unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
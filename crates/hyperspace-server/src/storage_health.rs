@@ -0,0 +1,119 @@
+//! Write-path storage health.
+//!
+//! A failed append to the vector store or the WAL (disk full, I/O error)
+//! is counted per collection. After `HS_READ_ONLY_AFTER_IO_ERRORS`
+//! consecutive failures (default 3, `0` never) the collection turns
+//! read-only: writes are refused up front instead of half-applying, while
+//! searches keep working. It stays read-only until an admin re-enables writes
+//! (`POST /api/collections/{name}/writable`) or the server restarts.
+
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+const DEFAULT_READ_ONLY_AFTER: u32 = 3;
+
+pub(crate) struct StorageHealth {
+    failures: AtomicU32,
+    read_only: AtomicBool,
+    // Consecutive failures that switch to read-only; 0 never does
+    read_only_after: u32,
+}
+
+/// A storage failure worth alerting on.
+pub(crate) struct StorageFailure {
+    pub consecutive: u32,
+    pub read_only: bool,
+}
+
+impl StorageHealth {
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("HS_READ_ONLY_AFTER_IO_ERRORS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_READ_ONLY_AFTER),
+        )
+    }
+
+    pub fn new(read_only_after: u32) -> Self {
+        Self {
+            failures: AtomicU32::new(0),
+            read_only: AtomicBool::new(false),
+            read_only_after,
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Acquire)
+    }
+
+    pub fn set_writable(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        self.read_only.store(false, Ordering::Release);
+    }
+
+    /// Rejects a write to `collection` while it is read-only.
+    pub fn check(&self, collection: &str) -> HyperspaceResult<()> {
+        if self.is_read_only() {
+            return Err(HyperspaceError::Capacity(format!(
+                "Collection '{collection}' is read-only after repeated storage failures; \
+                 free disk space, then re-enable writes"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn succeeded(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Counts `error` if it came from storage. Validation and other caller
+    /// errors say nothing about the disk and return `None`.
+    pub fn failed(&self, error: &HyperspaceError) -> Option<StorageFailure> {
+        if !matches!(error, HyperspaceError::Io(_) | HyperspaceError::Capacity(_)) {
+            return None;
+        }
+        let consecutive = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.read_only_after > 0 && consecutive >= self.read_only_after {
+            self.read_only.store(true, Ordering::Release);
+        }
+        Some(StorageFailure {
+            consecutive,
+            read_only: self.is_read_only(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_read_only_after_consecutive_failures() {
+        let health = StorageHealth::new(2);
+        let disk_full = || HyperspaceError::Capacity("disk full".into());
+
+        assert!(health
+            .failed(&HyperspaceError::Validation("bad".into()))
+            .is_none());
+        assert!(!health.failed(&disk_full()).unwrap().read_only);
+        health.succeeded();
+        assert!(!health.failed(&disk_full()).unwrap().read_only);
+        let failure = health.failed(&disk_full()).unwrap();
+        assert_eq!(failure.consecutive, 2);
+        assert!(failure.read_only);
+        assert!(matches!(
+            health.check("docs"),
+            Err(HyperspaceError::Capacity(_))
+        ));
+
+        health.set_writable();
+        assert!(health.check("docs").is_ok());
+        assert!(
+            !StorageHealth::new(0)
+                .failed(&disk_full())
+                .unwrap()
+                .read_only
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Opt-in anonymous telemetry.
+//!
+//! Off unless `HS_TELEMETRY=true`. When enabled, a report of aggregate stats
+//! is POSTed as JSON to `HS_TELEMETRY_ENDPOINT` ten minutes after start and
+//! then every `HS_TELEMETRY_INTERVAL_SEC` (default one day). Sending needs
+//! the `telemetry` feature; nothing is sent without an endpoint.
+//!
+//! A report holds the server version, OS and architecture, uptime, cluster
+//! role, how many collections exist, how many use each dimension, metric and
+//! quantization, the resident vector count rounded down to a power of ten,
+//! the compiled-in cargo features and which optional subsystems are switched
+//! on. It never holds collection or tenant names, labels, metadata, vectors,
+//! keys, hostnames or addresses. `instance_id` is a random UUID kept in
+//! `<data>/telemetry_id` so repeated reports from one install can be told
+//! apart; delete the file to get a new one.
+//!
+//! `GET /api/admin/telemetry` shows exactly what would be sent, whether or
+//! not telemetry is enabled.
+
+use crate::manager::CollectionManager;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SCHEMA: u32 = 1;
+const ID_FILE: &str = "telemetry_id";
+#[cfg_attr(not(feature = "telemetry"), allow(dead_code))]
+const FIRST_REPORT_DELAY: Duration = Duration::from_mins(10);
+const DEFAULT_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryReport {
+    pub schema: u32,
+    pub instance_id: String,
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub uptime_hours: u64,
+    pub role: String,
+    pub collections: usize,
+    pub resident_collections: usize,
+    /// Vectors in resident collections, rounded down to a power of ten.
+    pub vectors_magnitude: u64,
+    /// Collections per dimension, metric and quantization.
+    pub dimensions: BTreeMap<u32, usize>,
+    pub metrics: BTreeMap<String, usize>,
+    pub quantization: BTreeMap<String, usize>,
+    pub compiled_features: Vec<&'static str>,
+    pub enabled_features: Vec<&'static str>,
+}
+
+/// What `GET /api/admin/telemetry` answers.
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryPreview {
+    pub enabled: bool,
+    pub endpoint: Option<String>,
+    pub interval_sec: u64,
+    /// Whether reports actually go out: enabled, an endpoint and the
+    /// `telemetry` feature.
+    pub sending: bool,
+    pub report: TelemetryReport,
+}
+
+struct Config {
+    enabled: bool,
+    endpoint: Option<String>,
+    interval_sec: u64,
+}
+
+impl Config {
+    fn from_env() -> Self {
+        Self {
+            enabled: env_flag("HS_TELEMETRY"),
+            endpoint: std::env::var("HS_TELEMETRY_ENDPOINT")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
+            interval_sec: std::env::var("HS_TELEMETRY_INTERVAL_SEC")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_INTERVAL_SECS)
+                .max(60),
+        }
+    }
+
+    fn sending(&self) -> bool {
+        self.enabled && self.endpoint.is_some() && cfg!(feature = "telemetry")
+    }
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+}
+
+fn env_positive(name: &str) -> bool {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .is_some_and(|n| n > 0)
+}
+
+/// `n` rounded down to a power of ten; 0 stays 0.
+fn magnitude(n: u64) -> u64 {
+    if n == 0 {
+        0
+    } else {
+        10u64.pow(n.ilog10())
+    }
+}
+
+/// The install's random id; without `create` a missing one is reported as
+/// `unassigned` instead of being written.
+fn instance_id(data_dir: &Path, create: bool) -> String {
+    let path = data_dir.join(ID_FILE);
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        let id = id.trim();
+        if uuid::Uuid::parse_str(id).is_ok() {
+            return id.to_string();
+        }
+    }
+    if !create {
+        return "unassigned".to_string();
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = std::fs::write(&path, &id) {
+        eprintln!("⚠️ Telemetry: cannot persist instance id: {e}");
+    }
+    id
+}
+
+fn compiled_features() -> Vec<&'static str> {
+    [
+        ("embed", cfg!(feature = "embed")),
+        ("nightly-simd", cfg!(feature = "nightly-simd")),
+        ("s3-tiering", cfg!(feature = "s3-tiering")),
+        ("metering-webhook", cfg!(feature = "metering-webhook")),
+        ("metering-postgres", cfg!(feature = "metering-postgres")),
+        ("graph-parquet", cfg!(feature = "graph-parquet")),
+        ("ingest-parquet", cfg!(feature = "ingest-parquet")),
+        ("io-uring", cfg!(feature = "io-uring")),
+        ("telemetry", cfg!(feature = "telemetry")),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect()
+}
+
+fn enabled_features(manager: &CollectionManager) -> Vec<&'static str> {
+    [
+        ("auth", std::env::var("HYPERSPACE_API_KEY").is_ok()),
+        (
+            "read_keys",
+            std::env::var("HYPERSPACE_READ_API_KEY").is_ok(),
+        ),
+        ("embedding", env_flag("HYPERSPACE_EMBED")),
+        ("gossip", env_flag("HS_GOSSIP_ENABLED")),
+        ("election", std::env::var("HS_ELECTION_LEASE").is_ok()),
+        ("metering", manager.meter.has_sinks()),
+        ("trash", env_positive("HS_TRASH_RETENTION_SEC")),
+        ("search_cache", env_positive("HS_SEARCH_CACHE_SIZE")),
+        ("rerank", env_flag("HS_RERANK_ENABLED")),
+        (
+            "purge_signing",
+            std::env::var("HS_PURGE_SIGNING_KEY").is_ok(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect()
+}
+
+/// Builds the report; `assign_id` creates the instance id if there is none.
+pub async fn report(
+    manager: &CollectionManager,
+    started: Instant,
+    assign_id: bool,
+) -> TelemetryReport {
+    let shapes = manager.collection_shapes();
+    let mut dimensions = BTreeMap::new();
+    let mut metrics = BTreeMap::new();
+    let mut quantization = BTreeMap::new();
+    for (dimension, metric, quant) in &shapes {
+        *dimensions.entry(*dimension).or_default() += 1;
+        *metrics.entry(metric.to_lowercase()).or_default() += 1;
+        *quantization.entry(quant.to_lowercase()).or_default() += 1;
+    }
+    let resident = manager.resident_collections();
+    let vectors: usize = resident.iter().map(|col| col.count()).sum();
+    let role = format!("{:?}", manager.cluster_state.read().await.role).to_lowercase();
+    TelemetryReport {
+        schema: SCHEMA,
+        instance_id: instance_id(manager.data_dir(), assign_id),
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        uptime_hours: started.elapsed().as_secs() / 3600,
+        role,
+        collections: shapes.len(),
+        resident_collections: resident.len(),
+        vectors_magnitude: magnitude(vectors as u64),
+        dimensions,
+        metrics,
+        quantization,
+        compiled_features: compiled_features(),
+        enabled_features: enabled_features(manager),
+    }
+}
+
+pub async fn preview(manager: &CollectionManager, started: Instant) -> TelemetryPreview {
+    let config = Config::from_env();
+    TelemetryPreview {
+        enabled: config.enabled,
+        sending: config.sending(),
+        endpoint: config.endpoint,
+        interval_sec: config.interval_sec,
+        report: report(manager, started, false).await,
+    }
+}
+
+#[cfg(feature = "telemetry")]
+async fn send(client: &reqwest::Client, url: &str, report: &TelemetryReport) -> Result<(), String> {
+    client
+        .post(url)
+        .json(report)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Starts reporting if `HS_TELEMETRY` is on; does nothing otherwise.
+pub fn spawn(manager: &Arc<CollectionManager>) {
+    let config = Config::from_env();
+    if !config.enabled {
+        return;
+    }
+    let Some(url) = config.endpoint.clone() else {
+        eprintln!(
+            "⚠️ Telemetry: HS_TELEMETRY is set but HS_TELEMETRY_ENDPOINT is not; nothing is sent"
+        );
+        return;
+    };
+    #[cfg(not(feature = "telemetry"))]
+    {
+        let _ = (manager, url);
+        eprintln!("⚠️ Telemetry: built without the `telemetry` feature; nothing is sent");
+    }
+    #[cfg(feature = "telemetry")]
+    {
+        println!(
+            "📊 Telemetry: [ENABLED] -> {url} every {}s (preview: GET /api/admin/telemetry)",
+            config.interval_sec
+        );
+        let started = Instant::now();
+        let manager = Arc::downgrade(manager);
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        tokio::spawn(async move {
+            tokio::time::sleep(FIRST_REPORT_DELAY).await;
+            loop {
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                let report = report(&manager, started, true).await;
+                drop(manager);
+                if let Err(e) = send(&client, &url, &report).await {
+                    eprintln!("⚠️ Telemetry report failed: {e}");
+                }
+                tokio::time::sleep(Duration::from_secs(config.interval_sec)).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast;
+
+    #[test]
+    fn rounds_counts_to_magnitudes() {
+        assert_eq!(magnitude(0), 0);
+        assert_eq!(magnitude(7), 1);
+        assert_eq!(magnitude(10), 10);
+        assert_eq!(magnitude(48_123), 10_000);
+    }
+
+    #[tokio::test]
+    async fn report_is_anonymous() {
+        let dir = std::env::temp_dir().join(format!("hs_telemetry_{}", uuid::Uuid::new_v4()));
+        let (tx, _rx) = broadcast::channel(16);
+        let manager = CollectionManager::new(dir.clone(), tx);
+        manager
+            .create_collection("acme", "patients", 8, "cosine")
+            .await
+            .unwrap();
+        manager
+            .create_collection("acme", "invoices", 16, "l2")
+            .await
+            .unwrap();
+
+        let preview = report(&manager, Instant::now(), false).await;
+        assert_eq!(preview.instance_id, "unassigned");
+        assert!(!dir.join(ID_FILE).exists(), "previews write nothing");
+        assert_eq!(preview.collections, 2);
+        assert_eq!(preview.dimensions, BTreeMap::from([(8, 1), (16, 1)]));
+        assert_eq!(preview.metrics.get("cosine"), Some(&1));
+
+        let json = serde_json::to_string(&preview).unwrap();
+        for secret in ["acme", "patients", "invoices", dir.to_str().unwrap()] {
+            assert!(!json.contains(secret), "{secret} leaked: {json}");
+        }
+
+        let sent = report(&manager, Instant::now(), true).await;
+        assert!(uuid::Uuid::parse_str(&sent.instance_id).is_ok());
+        let again = report(&manager, Instant::now(), true).await;
+        assert_eq!(sent.instance_id, again.instance_id);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
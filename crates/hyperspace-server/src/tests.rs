@@ -1,4 +1,5 @@
 use super::manager::CollectionManager;
+use super::simulation::{Cluster, Faults};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
@@ -8,6 +9,21 @@ use uuid::Uuid;
 
 use hyperspace_core::Durability;
 
+/// gRPC service over `manager` that answers no replication streams and has
+/// no embedder.
+fn test_service(
+    manager: std::sync::Arc<CollectionManager>,
+    replication_tx: impl Into<super::ReplicationFeed>,
+) -> super::HyperspaceService {
+    super::HyperspaceService {
+        manager,
+        replication_tx: replication_tx.into(),
+        replication_allowed: false,
+        #[cfg(feature = "embed")]
+        vectorizer: None,
+    }
+}
+
 #[tokio::test]
 async fn test_rebuild_and_queue() {
     // Setup temporary directory
@@ -104,6 +120,36 @@ async fn test_vacuum() {
     let _ = fs::remove_dir_all(&tmp_dir);
 }
 
+#[tokio::test]
+async fn test_snapshot_all_is_scoped_to_the_caller() {
+    use hyperspace_core::HyperspaceError;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_snap_scope_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    for (user, name) in [("alice", "a1"), ("alice", "a2"), ("bob", "b1")] {
+        manager
+            .create_collection(user, name, 8, "l2")
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        manager.snapshot_collections("alice", None).await.unwrap(),
+        2
+    );
+    assert_eq!(manager.snapshot_collections("bob", None).await.unwrap(), 1);
+    assert!(matches!(
+        manager.snapshot_collections("bob", Some("a1")).await,
+        Err(HyperspaceError::NotFound(_))
+    ));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
 /// Task 2.1: Delta Sync test — simulates Network Partition and recovery.
 /// Two "nodes" (CollectionManager instances) insert different vectors,
 /// then use the digest-based diff protocol to synchronize.
@@ -285,3 +331,3518 @@ async fn test_delta_sync() {
     let _ = fs::remove_dir_all(&dir_a);
     let _ = fs::remove_dir_all(&dir_b);
 }
+
+#[tokio::test]
+async fn test_f32_insert_and_search() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_f32_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "f32_col", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "f32_col").await.unwrap();
+
+    for i in 0u32..20 {
+        let v = vec![i as f32 * 0.1; 8];
+        col.insert_f32(&v, i, HashMap::new(), 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    assert!(col
+        .insert_f32(&[0.1; 4], 99, HashMap::new(), 0, Durability::Default)
+        .await
+        .is_err());
+
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let params = hyperspace_core::SearchParams {
+        top_k: 1,
+        ef_search: 64,
+        ..Default::default()
+    };
+    let res = col
+        .search_f32(&[0.5; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert_eq!(res[0].0, 5);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_f32_queries_across_collections() {
+    use super::ReplicationFeed;
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{MultiSearchRequest, SearchMultiCollectionRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_f32_multi_{uuid}"));
+    let (tx, _rx) = broadcast::channel(100);
+    let feed = ReplicationFeed::from(tx.clone());
+    let manager = Arc::new(CollectionManager::new(tmp_dir.clone(), tx));
+    let collections = vec!["a".to_string(), "b".to_string()];
+    for (offset, name) in (0u32..).step_by(100).zip(&collections) {
+        manager
+            .create_collection("default_admin", name, 8, "l2")
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", name).await.unwrap();
+        for i in 0u32..10 {
+            col.insert_f32(
+                &[i as f32 * 0.1; 8],
+                offset + i,
+                HashMap::new(),
+                0,
+                Durability::Default,
+            )
+            .await
+            .unwrap();
+        }
+        while col.queue_size() > 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+    let service = test_service(manager, feed);
+
+    let responses = service
+        .search_multi_collection(tonic::Request::new(SearchMultiCollectionRequest {
+            collections: collections.clone(),
+            top_k: 1,
+            vector_f32: vec![0.3; 8],
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .responses;
+    assert_eq!(responses["a"].results[0].id, 3);
+    assert_eq!(responses["b"].results[0].id, 103);
+
+    let hits = service
+        .multi_search(tonic::Request::new(MultiSearchRequest {
+            collections,
+            top_k: 2,
+            vector_f32: vec![0.7; 8],
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    let mut ids: Vec<u32> = hits.iter().map(|h| h.result.as_ref().unwrap().id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, [7, 107]);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_max_points_limit_rejects_new_ids() {
+    use super::limits::CollectionLimits;
+    use super::manager::CollectionOptions;
+    use hyperspace_core::HyperspaceError;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_limits_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let options = CollectionOptions {
+        limits: CollectionLimits {
+            max_points: Some(2),
+            max_storage_bytes: None,
+        },
+        ..Default::default()
+    };
+    manager
+        .create_collection_with_options("default_admin", "capped", 8, "l2", options)
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "capped").await.unwrap();
+
+    for i in 0u32..2 {
+        col.insert(&[0.1; 8], i, HashMap::new(), 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    let err = col
+        .insert(&[0.1; 8], 2, HashMap::new(), 0, Durability::Default)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, HyperspaceError::Capacity(_)));
+    let batch = vec![(vec![0.2; 8], 3, HashMap::new())];
+    assert!(col
+        .insert_batch(batch, 0, Durability::Default)
+        .await
+        .is_err());
+
+    // Upserting an existing id stays within the cap.
+    col.insert(&[0.3; 8], 1, HashMap::new(), 0, Durability::Default)
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_bulk_ndjson_batches_and_reports_bad_lines() {
+    use super::bulk::{BulkEvent, BulkIngest};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_bulk_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "bulk", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "bulk").await.unwrap();
+
+    let body = "{\"id\":1,\"vector\":[0.1,0.1,0.1,0.1,0.1,0.1,0.1,0.1]}\n\
+                {\"id\":2,\"vector\":[0.2,0.2,0.2,0.2,0.2,0.2,0.2,0.2],\"metadata\":{\"k\":\"v\"}}\n\
+                \n\
+                not json\n\
+                {\"id\":3,\"vector\":[0.3]}\n\
+                {\"id\":4,\"vector\":[0.4,0.4,0.4,0.4,0.4,0.4,0.4,0.4]}";
+    let mut ingest = BulkIngest::new(col.clone(), 0, 2);
+    let mut events = Vec::new();
+    // Split mid-line to exercise chunk reassembly.
+    let (a, b) = body.as_bytes().split_at(30);
+    events.extend(ingest.feed(a).await);
+    events.extend(ingest.feed(b).await);
+    events.extend(ingest.finish().await);
+
+    let errors: Vec<usize> = events
+        .iter()
+        .filter_map(|e| match e {
+            BulkEvent::Error { line, .. } => Some(*line),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(errors, vec![4, 5]);
+    assert_eq!(
+        events.last(),
+        Some(&BulkEvent::Done {
+            lines: 6,
+            inserted: 3,
+            failed: 2,
+        })
+    );
+
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(col.count(), 3);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_query_template_versioned_and_runs_with_params() {
+    use super::query_templates::{self, QueryTemplate};
+    use hyperspace_core::HyperspaceError;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_queries_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "songs", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "songs").await.unwrap();
+    for (id, genre, artist) in [
+        (1, "jazz", "a"),
+        (2, "jazz", "a"),
+        (3, "jazz", "b"),
+        (4, "rock", "c"),
+    ] {
+        let meta = HashMap::from([
+            ("genre".to_string(), genre.to_string()),
+            ("artist".to_string(), artist.to_string()),
+        ]);
+        col.insert(&[0.1 * f64::from(id); 8], id, meta, 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let template = QueryTemplate {
+        filter_expr: "genre = $genre".into(),
+        group_by: Some("artist".into()),
+        top_k: Some(5),
+        ..Default::default()
+    };
+    let v1 = manager
+        .put_query_template("default_admin", "songs", "by_genre", template.clone())
+        .unwrap();
+    let v2 = manager
+        .put_query_template("default_admin", "songs", "by_genre", template)
+        .unwrap();
+    assert_eq!((v1, v2), (1, 2));
+    assert!(matches!(
+        manager.put_query_template(
+            "default_admin",
+            "songs",
+            "broken",
+            QueryTemplate {
+                filter_expr: "genre = $genre OR x = 1".into(),
+                ..Default::default()
+            }
+        ),
+        Err(HyperspaceError::Validation(_))
+    ));
+
+    let stored = manager
+        .get_query_template("default_admin", "songs", "by_genre")
+        .unwrap();
+    let params = HashMap::from([("genre".to_string(), "jazz".to_string())]);
+    let res = query_templates::execute(&*col, &stored, &[0.1; 8], &params, None, 100)
+        .await
+        .unwrap();
+    // One hit per artist among the jazz songs.
+    let mut ids: Vec<u32> = res.iter().map(|r| r.0).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 3]);
+
+    assert!(manager
+        .delete_query_template("default_admin", "songs", "by_genre")
+        .unwrap());
+    assert!(manager
+        .list_query_templates("default_admin", "songs")
+        .unwrap()
+        .is_empty());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[test]
+fn test_grpc_filter_expr_parsed_and_errors_carry_position() {
+    use hyperspace_proto::hyperspace::{FilterSyntaxError, SearchRequest};
+
+    let req = SearchRequest {
+        vector: vec![0.0; 8],
+        top_k: 5,
+        filter_expr: Some("genre = \"jazz\" AND year >= 1990".to_string()),
+        ..Default::default()
+    };
+    let (_, _, _, complex_filters, _) = super::build_filters(req).unwrap();
+    assert_eq!(complex_filters.len(), 2);
+
+    let req = SearchRequest {
+        filter_expr: Some("year >= 1990 OR genre = \"jazz\"".to_string()),
+        ..Default::default()
+    };
+    let Err(status) = super::build_filters(req) else {
+        panic!("OR should be rejected");
+    };
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let detail = FilterSyntaxError::from_status(&status).unwrap();
+    assert_eq!(detail.position, 13);
+}
+
+#[test]
+fn test_search_cache_invalidated_by_writes() {
+    use super::search_cache::SearchCache;
+    use hyperspace_core::SearchParams;
+
+    let cache = SearchCache::new(std::num::NonZeroUsize::new(4).unwrap());
+    let params = SearchParams {
+        top_k: 5,
+        ef_search: 64,
+        hybrid_query: None,
+        hybrid_alpha: None,
+        use_wasserstein: false,
+        bm25_options: None,
+        fusion_method: None,
+        text_match: hyperspace_core::TextMatch::Any,
+        trace: None,
+        num_probes: 0,
+    };
+    let key = SearchCache::key(&[0.1, 0.2], &HashMap::new(), &[], &params);
+    let other = SearchCache::key(&[0.1, 0.3], &HashMap::new(), &[], &params);
+    assert_ne!(key, other);
+
+    let results = vec![(7, 0.5, HashMap::new())];
+    cache.put(key, cache.epoch(), &results);
+    assert_eq!(cache.get(key).map(|r| r[0].0), Some(7));
+
+    // A write between search start and fill must not populate the cache.
+    let stale_epoch = cache.epoch();
+    cache.invalidate();
+    assert!(cache.get(key).is_none());
+    cache.put(key, stale_epoch, &results);
+    assert!(cache.get(key).is_none());
+}
+
+#[test]
+fn test_search_cache_keys_on_num_probes() {
+    use super::search_cache::SearchCache;
+    use hyperspace_core::SearchParams;
+
+    let cache = SearchCache::new(std::num::NonZeroUsize::new(4).unwrap());
+    let key = |num_probes| {
+        let params = SearchParams {
+            top_k: 5,
+            ef_search: 64,
+            num_probes,
+            ..Default::default()
+        };
+        SearchCache::key(&[0.1, 0.2], &HashMap::new(), &[], &params)
+    };
+    cache.put(key(1), cache.epoch(), &[(7, 0.5, HashMap::new())]);
+    assert_eq!(cache.get(key(0)).map(|r| r[0].0), Some(7));
+    // A wider search must not be answered from the single-probe result.
+    assert!(cache.get(key(8)).is_none());
+}
+
+#[tokio::test]
+async fn test_search_cache_invalidated_by_synonyms() {
+    use hyperspace_core::{SearchParams, SynonymMap, TextMatch};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_cache_synonyms_{uuid}"));
+    env::set_var("HS_QUANTIZATION_LEVEL", "none");
+    env::set_var("HS_SEARCH_CACHE_SIZE", "16");
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "docs").await.unwrap();
+    env::remove_var("HS_SEARCH_CACHE_SIZE");
+    for (id, body) in [(1, "a reliable automobile"), (2, "a bicycle")] {
+        let meta = HashMap::from([("body".to_string(), body.to_string())]);
+        col.insert(&[f64::from(id) * 0.1; 8], id, meta, 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    while col.queue_size() > 0 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let params = SearchParams {
+        top_k: 5,
+        ef_search: 64,
+        hybrid_query: Some("car".to_string()),
+        text_match: TextMatch::All,
+        ..Default::default()
+    };
+    let search = || async {
+        col.search(&[0.1; 8], &HashMap::new(), &[], &params)
+            .await
+            .unwrap()
+            .iter()
+            .map(|(id, _, _)| *id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(search().await, Vec::<u32>::new());
+
+    manager
+        .set_synonyms(
+            "default_admin",
+            "docs",
+            SynonymMap {
+                groups: vec![vec!["car".into(), "automobile".into()]],
+                expand_at_index: true,
+            },
+        )
+        .await
+        .unwrap();
+    // The empty result cached before the update must not be served.
+    assert_eq!(search().await, vec![1]);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_anti_entropy_repairs_divergent_bucket() {
+    use super::anti_entropy::repair_bucket;
+    use hyperspace_proto::hyperspace::SyncVectorData;
+
+    let uuid = Uuid::new_v4();
+    let dir_leader = env::temp_dir().join(format!("hyperspace_ae_leader_{uuid}"));
+    let dir_follower = env::temp_dir().join(format!("hyperspace_ae_follower_{uuid}"));
+    env::set_var("HS_QUANTIZATION_LEVEL", "none");
+    env::set_var("HS_GOSSIP_ENABLED", "true");
+
+    let (tx_l, _) = broadcast::channel(100);
+    let (tx_f, _) = broadcast::channel(100);
+    let leader = CollectionManager::new(dir_leader.clone(), tx_l);
+    let follower = CollectionManager::new(dir_follower.clone(), tx_f);
+    for m in [&leader, &follower] {
+        m.create_collection("default_admin", "ae", 8, "l2")
+            .await
+            .unwrap();
+    }
+    let col_l = leader.get("default_admin", "ae").await.unwrap();
+    let col_f = follower.get("default_admin", "ae").await.unwrap();
+
+    // Bucket 1 diverges: the follower has a stale vector for id 1, an id the
+    // leader deleted (257) and misses one the leader has (513).
+    for (id, x) in [(1u32, 0.1), (2, 0.2), (513, 0.3)] {
+        col_l
+            .insert(&[x; 8], id, HashMap::new(), 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    for (id, x) in [(1u32, 0.9), (2, 0.2), (257, 0.4)] {
+        col_f
+            .insert(&[x; 8], id, HashMap::new(), 0, Durability::Default)
+            .await
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_ne!(col_l.buckets()[1], col_f.buckets()[1]);
+    assert_eq!(col_l.buckets()[2], col_f.buckets()[2]);
+
+    let remote: Vec<SyncVectorData> = col_l
+        .peek_buckets(&[1])
+        .into_iter()
+        .map(|(id, vector, metadata)| SyncVectorData {
+            collection: "ae".into(),
+            id,
+            vector,
+            metadata,
+            bucket_index: 1,
+        })
+        .collect();
+
+    // A bucket that changed since the handshake is left alone.
+    let stale = repair_bucket(col_f.as_ref(), 1, 0, remote.clone(), 5)
+        .await
+        .unwrap();
+    assert_eq!(stale, None);
+
+    let expected = col_f.buckets()[1];
+    let outcome = repair_bucket(col_f.as_ref(), 1, expected, remote, 5)
+        .await
+        .unwrap();
+    assert_eq!(outcome, Some((2, 1)));
+    assert_eq!(col_l.buckets(), col_f.buckets());
+
+    let _ = fs::remove_dir_all(&dir_leader);
+    let _ = fs::remove_dir_all(&dir_follower);
+}
+
+#[tokio::test]
+async fn test_delete_survives_restart_without_snapshot() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_wal_del_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+    let params = hyperspace_core::SearchParams {
+        top_k: 5,
+        ef_search: 64,
+        ..Default::default()
+    };
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .create_collection("default_admin", "wal_del", 8, "l2")
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", "wal_del").await.unwrap();
+        for i in 0u32..5 {
+            let v = vec![f64::from(i) * 0.1; 8];
+            col.insert(&v, i, HashMap::new(), u64::from(i) + 1, Durability::Default)
+                .await
+                .unwrap();
+        }
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        col.snapshot().await.unwrap();
+
+        // Deleted after the last snapshot: only the WAL knows about it.
+        col.delete(2, 10).await.unwrap();
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let col = manager.get("default_admin", "wal_del").await.unwrap();
+    let res = col
+        .search(&[0.2; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 4, "{res:?}");
+    assert!(res.iter().all(|(id, _, _)| *id != 2), "{res:?}");
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_metadata_snapshot_restores_deletes_without_wal() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_meta_snap_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+    let col_dir = tmp_dir.join("default_admin_meta_snap");
+    let params = hyperspace_core::SearchParams {
+        top_k: 5,
+        ef_search: 64,
+        ..Default::default()
+    };
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        let options = crate::manager::CollectionOptions {
+            snapshot: crate::snapshot::SnapshotPolicy {
+                interval_sec: Some(3600),
+                metadata_interval_sec: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        manager
+            .create_collection_with_options("default_admin", "meta_snap", 8, "l2", options)
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", "meta_snap").await.unwrap();
+        for i in 0u32..5 {
+            let v = vec![f64::from(i) * 0.1; 8];
+            col.insert(&v, i, HashMap::new(), u64::from(i) + 1, Durability::Default)
+                .await
+                .unwrap();
+        }
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        col.snapshot().await.unwrap();
+        assert!(!col_dir.join("metadata.snap").exists());
+        let graph = fs::read(col_dir.join("index.snap")).unwrap();
+
+        col.delete(2, 10).await.unwrap();
+        let start = std::time::Instant::now();
+        while !col_dir.join("metadata.snap").exists() && start.elapsed() < Duration::from_secs(5) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(col_dir.join("metadata.snap").exists());
+        // The graph was left alone.
+        assert_eq!(fs::read(col_dir.join("index.snap")).unwrap(), graph);
+    }
+
+    // Without the WAL, only metadata.snap knows about the delete.
+    for entry in fs::read_dir(&col_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("wal")
+        {
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let col = manager.get("default_admin", "meta_snap").await.unwrap();
+    let res = col
+        .search(&[0.2; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 4, "{res:?}");
+    assert!(res.iter().all(|(id, _, _)| *id != 2), "{res:?}");
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_missing_index_snapshot_rebuilds_from_wal_or_refuses() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_lost_snap_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+    let params = hyperspace_core::SearchParams {
+        top_k: 10,
+        ef_search: 64,
+        ..Default::default()
+    };
+    let wait_indexed = |col: std::sync::Arc<dyn hyperspace_core::Collection>| async move {
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+
+    for name in ["lost", "lost_wal", "lost_state"] {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .create_collection("default_admin", name, 8, "l2")
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", name).await.unwrap();
+        for i in 0u32..5 {
+            let v = vec![f64::from(i) * 0.1; 8];
+            col.insert(&v, i, HashMap::new(), u64::from(i) + 1, Durability::Default)
+                .await
+                .unwrap();
+        }
+        wait_indexed(col.clone()).await;
+        col.snapshot().await.unwrap();
+        col.insert(&[0.9; 8], 5, HashMap::new(), 6, Durability::Default)
+            .await
+            .unwrap();
+        wait_indexed(col.clone()).await;
+    }
+    let lost = tmp_dir.join("default_admin_lost");
+    fs::remove_file(lost.join("index.snap")).unwrap();
+    // Without the WAL nothing can account for the snapshotted points.
+    let lost_wal = tmp_dir.join("default_admin_lost_wal");
+    fs::remove_file(lost_wal.join("index.snap")).unwrap();
+    fs::remove_file(lost_wal.join("wal.log")).unwrap();
+    // Nothing maps user ids to the snapshotted graph's points.
+    let lost_state = tmp_dir.join("default_admin_lost_state");
+    fs::remove_file(lost_state.join("state.json")).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    assert!(manager.get("default_admin", "lost_wal").await.is_none());
+    assert!(lost_wal.join("state.json").exists());
+    assert!(manager.get("default_admin", "lost_state").await.is_none());
+    assert!(lost_state.join("index.snap").exists());
+
+    let col = manager.get("default_admin", "lost").await.unwrap();
+    wait_indexed(col.clone()).await;
+    // A new point takes a fresh slot instead of overwriting a recovered one.
+    col.insert(&[0.5; 8], 6, HashMap::new(), 7, Durability::Default)
+        .await
+        .unwrap();
+    wait_indexed(col.clone()).await;
+    let res = col
+        .search(&[0.0; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    let mut ids: Vec<u32> = res.iter().map(|(id, _, _)| *id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        col.search(&[0.3; 8], &HashMap::new(), &[], &params)
+            .await
+            .unwrap()[0]
+            .0,
+        3
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_wal_sync_mode_persists_in_meta() {
+    use hyperspace_store::wal::WalSyncMode;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_wal_mode_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "critical", 8, "l2")
+        .await
+        .unwrap();
+    manager
+        .set_wal_sync_mode("default_admin", "critical", Some(WalSyncMode::Strict))
+        .await
+        .unwrap();
+
+    let meta_path = tmp_dir.join("default_admin_critical").join("meta.json");
+    let meta = fs::read_to_string(&meta_path).unwrap();
+    assert!(meta.contains("\"wal_sync_mode\": \"strict\""), "{meta}");
+
+    // Writes keep working under the new mode.
+    let col = manager.get("default_admin", "critical").await.unwrap();
+    col.insert(&[0.1; 8], 1, HashMap::new(), 1, Durability::Default)
+        .await
+        .unwrap();
+
+    manager
+        .set_wal_sync_mode("default_admin", "critical", None)
+        .await
+        .unwrap();
+    let meta = fs::read_to_string(&meta_path).unwrap();
+    assert!(!meta.contains("wal_sync_mode"), "{meta}");
+
+    assert!(manager
+        .set_wal_sync_mode("default_admin", "missing", None)
+        .await
+        .is_err());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_write_mode_insert_only_and_merge() {
+    use hyperspace_proto::hyperspace::{InsertErrorCode, InsertErrorDetail, WriteMode};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_write_mode_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "modes", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "modes").await.unwrap();
+    let stored = HashMap::from([
+        ("genre".to_string(), "jazz".to_string()),
+        ("year".to_string(), "1959".to_string()),
+    ]);
+    col.insert(&[0.1; 8], 7, stored, 1, Durability::Default)
+        .await
+        .unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let update = HashMap::from([("year".to_string(), "1960".to_string())]);
+    let status = super::apply_write_mode(
+        col.as_ref(),
+        WriteMode::InsertOnly as i32,
+        7,
+        "id",
+        update.clone(),
+    )
+    .unwrap_err();
+    let detail = InsertErrorDetail::from_status(&status).unwrap();
+    assert_eq!(detail.code(), InsertErrorCode::IdExists);
+    assert!(super::apply_write_mode(
+        col.as_ref(),
+        WriteMode::InsertOnly as i32,
+        8,
+        "id",
+        update.clone()
+    )
+    .is_ok());
+
+    let merged = super::apply_write_mode(
+        col.as_ref(),
+        WriteMode::UpsertMerge as i32,
+        7,
+        "id",
+        update.clone(),
+    )
+    .unwrap();
+    assert_eq!(merged.get("genre").map(String::as_str), Some("jazz"));
+    assert_eq!(merged.get("year").map(String::as_str), Some("1960"));
+
+    let replaced = super::apply_write_mode(
+        col.as_ref(),
+        WriteMode::UpsertReplace as i32,
+        7,
+        "id",
+        update,
+    )
+    .unwrap();
+    assert_eq!(replaced.len(), 1);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_update_vector_delta_patches_coordinates() {
+    use super::manager::CollectionOptions;
+    use hyperspace_core::HyperspaceError;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_delta_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let quantized = |quantization: &str| CollectionOptions {
+        quantization: Some(quantization.to_string()),
+        ..Default::default()
+    };
+    manager
+        .create_collection_with_options("default_admin", "stream", 8, "l2", quantized("none"))
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "stream").await.unwrap();
+    let meta = HashMap::from([("model".to_string(), "v1".to_string())]);
+    col.insert(&[0.1; 8], 3, meta, 1, Durability::Default)
+        .await
+        .unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    col.update_vector_delta(3, &[0, 5], &[0.5, -0.25], 2, Durability::Default)
+        .await
+        .unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let (id, vector, meta) = col.peek(1, 0).pop().unwrap();
+    assert_eq!(id, 3);
+    assert_eq!(vector, vec![0.5, 0.1, 0.1, 0.1, 0.1, -0.25, 0.1, 0.1]);
+    assert_eq!(meta.get("model").map(String::as_str), Some("v1"));
+    assert_eq!(col.count(), 1);
+
+    assert!(matches!(
+        col.update_vector_delta(3, &[8], &[1.0], 3, Durability::Default)
+            .await,
+        Err(HyperspaceError::Validation(_))
+    ));
+    assert!(matches!(
+        col.update_vector_delta(3, &[0, 1], &[1.0], 3, Durability::Default)
+            .await,
+        Err(HyperspaceError::Validation(_))
+    ));
+    assert!(matches!(
+        col.update_vector_delta(99, &[0], &[1.0], 3, Durability::Default)
+            .await,
+        Err(HyperspaceError::NotFound(_))
+    ));
+
+    // Concurrent deltas on one point both land.
+    let patches = (0..8u32).map(|i| {
+        let col = col.clone();
+        tokio::spawn(async move {
+            col.update_vector_delta(3, &[i], &[-0.5], 3 + u64::from(i), Durability::Default)
+                .await
+        })
+    });
+    for patch in futures::future::join_all(patches).await {
+        patch.unwrap().unwrap();
+    }
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(col.peek(1, 0).pop().unwrap().1, vec![-0.5; 8]);
+
+    // Quantized storage would write its rounding back into the vector.
+    manager
+        .create_collection_with_options("default_admin", "scalar", 8, "l2", quantized("scalar"))
+        .await
+        .unwrap();
+    let scalar = manager.get("default_admin", "scalar").await.unwrap();
+    scalar
+        .insert(&[0.1; 8], 3, HashMap::new(), 1, Durability::Default)
+        .await
+        .unwrap();
+    assert!(matches!(
+        scalar
+            .update_vector_delta(3, &[0], &[0.5], 2, Durability::Default)
+            .await,
+        Err(HyperspaceError::Validation(_))
+    ));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_namespaced_collections_list_and_delete() {
+    use super::manager::in_namespace;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_ns_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        for name in ["team/a/docs", "team/a/raw/x", "team/ab/y", "solo"] {
+            manager
+                .create_collection("default_admin", name, 8, "l2")
+                .await
+                .unwrap();
+        }
+        for bad in ["team//docs", "team/../x", "/lead", "trail/", "a~b"] {
+            assert!(
+                manager
+                    .create_collection("default_admin", bad, 8, "l2")
+                    .await
+                    .is_err(),
+                "{bad}"
+            );
+        }
+        let col = manager.get("default_admin", "team/a/docs").await.unwrap();
+        col.insert(&[0.1; 8], 1, HashMap::new(), 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+
+    // Names survive a restart: the directories are flat on disk.
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    assert_eq!(
+        manager.list_namespace("default_admin", "team/a"),
+        vec!["team/a/docs".to_string(), "team/a/raw/x".to_string()]
+    );
+    assert!(!in_namespace("team/ab/y", "team/a"));
+    assert_eq!(manager.list_namespace("default_admin", "").len(), 4);
+
+    let usage = manager.get_namespace_usage("default_admin", "team/a/");
+    assert_eq!(usage.collection_count, 2);
+    assert!(usage.disk_usage_bytes > 0);
+
+    assert!(manager.delete_namespace("default_admin", "").await.is_err());
+    let deleted = manager
+        .delete_namespace("default_admin", "team/a")
+        .await
+        .unwrap();
+    assert_eq!(deleted.len(), 2);
+    assert_eq!(
+        manager.list("default_admin"),
+        vec!["solo".to_string(), "team/ab/y".to_string()]
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_collection_labels_persist_and_filter() {
+    use super::manager::{CollectionInfo, CollectionOptions};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_labels_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let labelled = |team: &str| CollectionOptions {
+        info: CollectionInfo {
+            description: format!("{team} embeddings"),
+            labels: [("team".to_string(), team.to_string())].into(),
+        },
+        ..Default::default()
+    };
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        for (name, team) in [("a", "search"), ("b", "ads"), ("c", "search")] {
+            manager
+                .create_collection_with_options("default_admin", name, 8, "l2", labelled(team))
+                .await
+                .unwrap();
+        }
+        manager
+            .create_collection("default_admin", "plain", 8, "l2")
+            .await
+            .unwrap();
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let info = manager.collection_info("default_admin", "b");
+    assert_eq!(info.description, "ads embeddings");
+    assert_eq!(info.labels["team"], "ads");
+    assert_eq!(
+        manager.collection_info("default_admin", "plain"),
+        CollectionInfo::default()
+    );
+
+    let selector = HashMap::from([("team".to_string(), "search".to_string())]);
+    let found = manager.list_detailed_in("default_admin", "", &selector);
+    let names: Vec<&str> = found.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "c"]);
+    assert_eq!(found[0].labels["team"], "search");
+    let all = manager.list_detailed_in("default_admin", "", &HashMap::new());
+    assert_eq!(all.len(), 4);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_deleted_collection_restores_from_trash() {
+    env::set_var("HS_TRASH_RETENTION_SEC", "3600");
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_trash_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "team/docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "team/docs").await.unwrap();
+    col.insert(&[0.1; 8], 7, HashMap::new(), 1, Durability::Default)
+        .await
+        .unwrap();
+    col.snapshot().await.unwrap();
+    drop(col);
+
+    manager
+        .delete_collection("default_admin", "team/docs")
+        .await
+        .unwrap();
+    assert_eq!(manager.list("default_admin"), Vec::<String>::new());
+    assert!(manager.get("default_admin", "team/docs").await.is_none());
+    let trashed = manager.list_trash("default_admin");
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].name, "team/docs");
+    assert_eq!(trashed[0].purge_at, trashed[0].deleted_at + 3600);
+    assert_eq!(manager.list_trash("someone_else"), Vec::new());
+    // The trash directory is not mistaken for a collection.
+    assert_eq!(manager.get_collection_counts(), (0, 0));
+
+    manager
+        .restore_collection("default_admin", "team/docs")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "team/docs").await.unwrap();
+    assert_eq!(col.count(), 1);
+    assert_eq!(manager.list_trash("default_admin"), Vec::new());
+    assert!(manager
+        .restore_collection("default_admin", "team/docs")
+        .await
+        .is_err());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_snapshot_bundle_migrates_collection() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_bundle_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+    let bundle = tmp_dir.join("docs.tar");
+
+    let (tx, _rx) = broadcast::channel(100);
+    let source = CollectionManager::new(tmp_dir.join("source"), tx);
+    source
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = source.get("default_admin", "docs").await.unwrap();
+    for i in 0u32..3 {
+        let meta = HashMap::from([("n".to_string(), i.to_string())]);
+        col.insert(&[0.1 * f64::from(i); 8], i, meta, 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+    source
+        .export_collection("default_admin", "docs", &bundle)
+        .await
+        .unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let target = CollectionManager::new(tmp_dir.join("target"), tx);
+    target
+        .import_collection("tenant", "team/docs", &bundle)
+        .await
+        .unwrap();
+    let imported = target.get("tenant", "team/docs").await.unwrap();
+    assert_eq!(imported.count(), 3);
+    assert_eq!(imported.dimension(), 8);
+
+    // Importing over an existing collection is refused.
+    assert!(matches!(
+        target
+            .import_collection("tenant", "team/docs", &bundle)
+            .await,
+        Err(hyperspace_core::HyperspaceError::Validation(_))
+    ));
+    assert!(matches!(
+        target.export_collection("tenant", "missing", &bundle).await,
+        Err(hyperspace_core::HyperspaceError::NotFound(_))
+    ));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_migrated_collection_redirect_persists_until_name_reused() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_moved_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .set_moved("default_admin", "docs", Some("http://10.0.0.7:50051"))
+            .unwrap();
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    assert_eq!(
+        manager.moved_to("default_admin", "docs").as_deref(),
+        Some("http://10.0.0.7:50051")
+    );
+    assert_eq!(manager.moved_to("tenant", "docs"), None);
+
+    manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    assert_eq!(manager.moved_to("default_admin", "docs"), None);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_interrupted_switch_over_keeps_the_local_copy() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_moved_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .create_collection("default_admin", "docs", 8, "l2")
+            .await
+            .unwrap();
+        manager
+            .set_moved("default_admin", "docs", Some("http://10.0.0.7:50051"))
+            .unwrap();
+        // Switching over: requests follow the redirect.
+        assert!(manager.get("default_admin", "docs").await.is_none());
+    }
+
+    // Stopped before the local copy was dropped.
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    assert_eq!(manager.moved_to("default_admin", "docs"), None);
+    assert!(manager.get("default_admin", "docs").await.is_some());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_migrate_collection_to_another_server() {
+    use super::ReplicationFeed;
+    use hyperspace_proto::hyperspace::database_server::{Database, DatabaseServer};
+    use hyperspace_proto::hyperspace::MigrateCollectionRequest;
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_migrate_{uuid}"));
+    let service = |dir: &str| {
+        let (tx, _rx) = broadcast::channel(1024);
+        let feed = ReplicationFeed::from(tx.clone());
+        let manager = Arc::new(CollectionManager::new(tmp_dir.join(dir), tx));
+        test_service(manager, feed)
+    };
+
+    let target = service("target");
+    let target_manager = target.manager.clone();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming =
+        tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(DatabaseServer::new(target))
+            .serve_with_incoming(incoming),
+    );
+
+    let source = service("source");
+    source
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = source.manager.get("default_admin", "docs").await.unwrap();
+    for i in 0u32..3 {
+        col.insert(&[0.1; 8], i, HashMap::new(), 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+    drop(col);
+
+    let report = source
+        .migrate_collection(tonic::Request::new(MigrateCollectionRequest {
+            collection: "docs".into(),
+            target_addr: format!("http://{addr}"),
+            target_collection: "team/docs".into(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(report.bundle_bytes > 0);
+
+    let moved = target_manager
+        .get("default_admin", "team/docs")
+        .await
+        .unwrap();
+    assert_eq!(moved.count(), 3);
+    assert!(source.manager.get("default_admin", "docs").await.is_none());
+    let status = source.collection_not_found("default_admin", "docs");
+    assert_eq!(
+        status.metadata().get("x-hyperspace-moved-to").unwrap(),
+        format!("http://{addr}").as_str()
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_insert_stream_acks_each_message_and_stops_at_an_error() {
+    use super::ReplicationFeed;
+    use hyperspace_proto::hyperspace::database_client::DatabaseClient;
+    use hyperspace_proto::hyperspace::database_server::DatabaseServer;
+    use hyperspace_proto::hyperspace::{BatchInsertRequest, VectorData};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_insert_stream_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let feed = ReplicationFeed::from(tx.clone());
+    let manager = Arc::new(CollectionManager::new(tmp_dir.clone(), tx));
+    manager
+        .create_collection("default_admin", "bulk", 8, "l2")
+        .await
+        .unwrap();
+    let service = test_service(manager.clone(), feed);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming =
+        tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(DatabaseServer::new(service))
+            .serve_with_incoming(incoming),
+    );
+
+    let chunk = |ids: std::ops::Range<u32>, dim: usize| BatchInsertRequest {
+        collection: "bulk".into(),
+        vectors: ids
+            .map(|id| VectorData {
+                id,
+                vector: vec![0.1; dim],
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let mut client = DatabaseClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let mut acks = client
+        .insert_stream(tokio_stream::iter(vec![chunk(0..10, 8), chunk(10..15, 8)]))
+        .await
+        .unwrap()
+        .into_inner();
+    for (sequence, written) in [(1, 10), (2, 5)] {
+        let ack = acks.message().await.unwrap().unwrap();
+        assert_eq!((ack.sequence, ack.written), (sequence, written));
+        assert!(ack.logical_clock > 0);
+    }
+    assert!(acks.message().await.unwrap().is_none());
+
+    // The third message is never read: the second one fails validation
+    let chunks = vec![chunk(15..20, 8), chunk(20..25, 3), chunk(25..30, 8)];
+    let mut acks = client
+        .insert_stream(tokio_stream::iter(chunks))
+        .await
+        .unwrap()
+        .into_inner();
+    let err = loop {
+        match acks.message().await {
+            Ok(Some(ack)) => assert_eq!(ack.sequence, 1),
+            Ok(None) => panic!("stream ended without an error"),
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    assert_eq!(err.metadata().get("x-hyperspace-sequence").unwrap(), "2");
+
+    let col = manager.get("default_admin", "bulk").await.unwrap();
+    assert_eq!(col.count(), 20);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_indexing_progress_reaches_written_clock() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_progress_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "progress", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "progress").await.unwrap();
+
+    for i in 0u32..50 {
+        let v = vec![f64::from(i) * 0.01; 8];
+        col.insert(&v, i, HashMap::new(), u64::from(i) + 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+    let batch = (50u32..60)
+        .map(|i| (vec![f64::from(i) * 0.01; 8], i, HashMap::new()))
+        .collect();
+    col.insert_batch(batch, 51, Durability::Default)
+        .await
+        .unwrap();
+    assert_eq!(col.indexing_progress().written_clock, 51);
+
+    let start = std::time::Instant::now();
+    let progress = loop {
+        let progress = col.indexing_progress();
+        if progress.indexed_clock >= 51 {
+            break progress;
+        }
+        assert!(
+            start.elapsed() <= Duration::from_secs(10),
+            "Indexing timeout: {progress:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(progress.queue_depth, 0);
+    assert_eq!(col.count(), 60);
+
+    // Deletes apply synchronously and move the watermark right away.
+    col.delete(3, 52).await.unwrap();
+    let progress = col.indexing_progress();
+    assert_eq!((progress.indexed_clock, progress.written_clock), (52, 52));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_export_collection_streams_importable_bundle() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::ExportCollectionRequest;
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_export_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.join("data"), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    for i in 0u32..3 {
+        col.insert(&[0.1; 8], i, HashMap::new(), 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+    drop(col);
+
+    let missing = service
+        .export_collection(tonic::Request::new(ExportCollectionRequest {
+            collection: "nope".into(),
+            format: 0,
+        }))
+        .await;
+    assert_eq!(missing.err().unwrap().code(), tonic::Code::NotFound);
+
+    let mut stream = service
+        .export_collection(tonic::Request::new(ExportCollectionRequest {
+            collection: "docs".into(),
+            format: 0,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .into_inner();
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.recv().await {
+        chunks.push(chunk.unwrap());
+    }
+    assert_eq!(chunks[0].collection, "docs");
+    let bundle: Vec<u8> = chunks.iter().flat_map(|c| c.data.clone()).collect();
+    assert_eq!(chunks[0].total_bytes, bundle.len() as u64);
+
+    let path = tmp_dir.join("docs.tar");
+    fs::write(&path, &bundle).unwrap();
+    service
+        .manager
+        .import_collection("default_admin", "restored", &path)
+        .await
+        .unwrap();
+    let restored = service
+        .manager
+        .get("default_admin", "restored")
+        .await
+        .unwrap();
+    assert_eq!(restored.count(), 3);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[cfg(feature = "arrow-export")]
+#[tokio::test]
+async fn test_columnar_export_imports_into_new_and_existing_collections() {
+    use crate::columnar::ColumnarFormat;
+    use hyperspace_core::HyperspaceError;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_columnar_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let manager = CollectionManager::new(tmp_dir.join("data"), tx);
+    manager
+        .create_collection("tenant", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("tenant", "docs").await.unwrap();
+    for i in 0u32..5 {
+        let meta = HashMap::from([("n".to_string(), i.to_string())]);
+        col.insert(&[f64::from(i) * 0.1; 8], i, meta, 1, Durability::Default)
+            .await
+            .unwrap();
+    }
+
+    for (format, name) in [
+        (ColumnarFormat::ArrowIpc, "from_ipc"),
+        (ColumnarFormat::Parquet, "from_parquet"),
+    ] {
+        let file = tmp_dir.join(name);
+        let written = manager
+            .export_columnar("tenant", "docs", format, &file)
+            .await
+            .unwrap();
+        assert_eq!(written, 5);
+        let applied = manager
+            .import_columnar("tenant", name, format, &file)
+            .await
+            .unwrap();
+        assert_eq!(applied, 5);
+        let copy = manager.get("tenant", name).await.unwrap();
+        assert_eq!(copy.count(), 5);
+        assert_eq!(copy.metric_name(), col.metric_name());
+        assert_eq!(copy.vector_by_id(3), col.vector_by_id(3));
+        assert_eq!(copy.metadata_by_id(3)["n"], "3");
+
+        // Into an existing collection the rows are upserts
+        manager
+            .import_columnar("tenant", name, format, &file)
+            .await
+            .unwrap();
+        assert_eq!(copy.count(), 5);
+    }
+
+    manager
+        .create_collection("tenant", "narrow", 4, "l2")
+        .await
+        .unwrap();
+    let mismatch = manager
+        .import_columnar(
+            "tenant",
+            "narrow",
+            ColumnarFormat::Parquet,
+            &tmp_dir.join("from_parquet"),
+        )
+        .await;
+    assert!(matches!(mismatch, Err(HyperspaceError::Validation(_))));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_wal_replay_links_points_with_metadata() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_wal_replay_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .create_collection("default_admin", "replay", 8, "l2")
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", "replay").await.unwrap();
+        // Nothing is snapshotted: every write below comes back from the WAL.
+        for i in 0u32..200 {
+            let v = vec![f64::from(i) * 0.004; 8];
+            let meta = HashMap::from([("parity".to_string(), (i % 2).to_string())]);
+            col.insert(&v, i, meta, u64::from(i) + 1, Durability::Default)
+                .await
+                .unwrap();
+        }
+        // An upsert and a delete replayed after their originals.
+        let meta = HashMap::from([("parity".to_string(), "moved".to_string())]);
+        col.insert(&[-0.9; 8], 4, meta, 201, Durability::Default)
+            .await
+            .unwrap();
+        col.delete(6, 202).await.unwrap();
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let col = manager.get("default_admin", "replay").await.unwrap();
+    assert!(!crate::wal_replay::active()
+        .iter()
+        .any(|s| s.collection.ends_with("_replay")));
+
+    let params = hyperspace_core::SearchParams {
+        top_k: 3,
+        ef_search: 64,
+        ..Default::default()
+    };
+    let res = col
+        .search(&[-0.9; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert_eq!(res[0].0, 4, "{res:?}");
+    let all = col
+        .search(&[0.024; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert!(all.iter().all(|r| r.0 != 6), "{all:?}");
+    let even = HashMap::from([("parity".to_string(), "0".to_string())]);
+    let res = col.search(&[0.032; 8], &even, &[], &params).await.unwrap();
+    assert_eq!(res.len(), 3, "{res:?}");
+    assert_eq!(res[0].0, 8, "{res:?}");
+    assert!(res.iter().all(|r| r.2["parity"] == "0"), "{res:?}");
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_mixed_batch_survives_replay() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_mixed_batch_{uuid}"));
+    fs::create_dir_all(&tmp_dir).unwrap();
+    let chunk = |id: u32, doc: &str| {
+        let meta = HashMap::from([("doc".to_string(), doc.to_string())]);
+        (vec![f64::from(id) * 0.01; 8], id, meta)
+    };
+
+    {
+        let (tx, _rx) = broadcast::channel(100);
+        let manager = CollectionManager::new(tmp_dir.clone(), tx);
+        manager
+            .create_collection("default_admin", "mixed", 8, "l2")
+            .await
+            .unwrap();
+        let col = manager.get("default_admin", "mixed").await.unwrap();
+        col.insert_batch(
+            vec![chunk(1, "v1"), chunk(2, "v1"), chunk(3, "v1")],
+            1,
+            Durability::Default,
+        )
+        .await
+        .unwrap();
+        // Re-chunk the document: three old chunks become two new ones.
+        col.apply_batch(
+            vec![1, 2, 3],
+            vec![chunk(2, "v2"), chunk(10, "v2")],
+            2,
+            Durability::Default,
+        )
+        .await
+        .unwrap();
+        assert!(!col.contains(1));
+        assert!(col.contains(10));
+    }
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let col = manager.get("default_admin", "mixed").await.unwrap();
+    assert!(!col.contains(1));
+    assert!(!col.contains(3));
+    assert_eq!(col.metadata_by_id(2)["doc"], "v2");
+    assert_eq!(col.metadata_by_id(10)["doc"], "v2");
+    assert_eq!(col.indexing_progress().written_clock, 2);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_projection_selects_payload_and_vector() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        metadata_value, InsertRequest, MetadataValue, SearchRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_projection_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 7,
+            vector: vec![0.25; 8],
+            metadata: HashMap::from([
+                ("lang".to_string(), "en".to_string()),
+                ("body".to_string(), "a long chunk of text".to_string()),
+            ]),
+            typed_metadata: HashMap::from([(
+                "year".to_string(),
+                MetadataValue {
+                    kind: Some(metadata_value::Kind::IntValue(2024)),
+                },
+            )]),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let search = |with_vector, with_payload: &[&str]| SearchRequest {
+        collection: "docs".into(),
+        vector: vec![0.25; 8],
+        top_k: 1,
+        with_vector,
+        with_payload: with_payload.iter().map(ToString::to_string).collect(),
+        ..Default::default()
+    };
+
+    let hit = &service
+        .search(tonic::Request::new(search(false, &[])))
+        .await
+        .unwrap()
+        .into_inner()
+        .results[0];
+    assert_eq!(hit.metadata.len(), 3);
+    assert_eq!(hit.vector, Vec::<f64>::new());
+
+    let hit = &service
+        .search(tonic::Request::new(search(true, &["lang", "year"])))
+        .await
+        .unwrap()
+        .into_inner()
+        .results[0];
+    assert_eq!(hit.id, 7);
+    let mut keys: Vec<_> = hit.metadata.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["lang", "year"]);
+    assert!(hit.typed_metadata.contains_key("year"));
+    // Stored vectors come back after quantization.
+    assert_eq!(hit.vector.len(), 8);
+    assert!(hit.vector.iter().all(|x| (x - 0.25).abs() < 0.01));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_joins_parent_metadata() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        metadata_value, InsertRequest, MetadataJoin, MetadataValue, SearchRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_join_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    for name in ["docs", "chunks"] {
+        service
+            .manager
+            .create_collection("default_admin", name, 8, "l2")
+            .await
+            .unwrap();
+    }
+    service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 1,
+            vector: vec![0.5; 8],
+            metadata: HashMap::from([
+                ("doc_id".to_string(), "d1".to_string()),
+                ("title".to_string(), "Install guide".to_string()),
+            ]),
+            typed_metadata: HashMap::from([(
+                "year".to_string(),
+                MetadataValue {
+                    kind: Some(metadata_value::Kind::IntValue(2024)),
+                },
+            )]),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    for (id, doc) in [(10, "d1"), (11, "d9")] {
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "chunks".into(),
+                id,
+                vector: vec![0.1 * f64::from(id); 8],
+                metadata: HashMap::from([
+                    ("doc_id".to_string(), doc.to_string()),
+                    ("body".to_string(), "step one".to_string()),
+                ]),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    for name in ["docs", "chunks"] {
+        let col = service.manager.get("default_admin", name).await.unwrap();
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    let search = |collection: &str| SearchRequest {
+        collection: "chunks".into(),
+        vector: vec![1.0; 8],
+        top_k: 2,
+        with_payload: vec!["body".into()],
+        joins: vec![MetadataJoin {
+            collection: collection.into(),
+            key: "doc_id".into(),
+            fields: vec!["title".into(), "year".into()],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let results = service
+        .search(tonic::Request::new(search("docs")))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(results[0].id, 10);
+    assert_eq!(results[0].metadata["docs.title"], "Install guide");
+    assert!(results[0].typed_metadata.contains_key("docs.year"));
+    assert!(!results[0].metadata.contains_key("docs.doc_id"));
+    assert!(!results[0].metadata.contains_key("doc_id"));
+    // No parent: the hit comes back as it is.
+    assert_eq!(results[1].id, 11);
+    assert_eq!(results[1].metadata.len(), 1);
+
+    let err = service
+        .search(tonic::Request::new(search("missing")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_parents_groups_chunks_by_document() {
+    use super::manager::CollectionOptions;
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{InsertRequest, ParentSearch, SearchRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_parents_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    let chunk_options = |parent: &str| CollectionOptions {
+        parent: Some(parent.to_string()),
+        ..Default::default()
+    };
+    assert!(service
+        .manager
+        .create_collection_with_options("default_admin", "chunks", 8, "l2", chunk_options("docs"))
+        .await
+        .is_err());
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    service
+        .manager
+        .create_collection_with_options("default_admin", "chunks", 8, "l2", chunk_options("docs"))
+        .await
+        .unwrap();
+
+    for (id, title) in [(1, "Install guide"), (2, "Release notes")] {
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "docs".into(),
+                id,
+                vector: vec![0.0; 8],
+                metadata: HashMap::from([("title".to_string(), title.to_string())]),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    // Chunks of document 1 are closest to the query, then those of 2; 9 has
+    // no document and 14 no parent at all.
+    for (id, parent) in [
+        (10, "1"),
+        (11, "2"),
+        (12, "1"),
+        (13, "9"),
+        (14, ""),
+        (15, "2"),
+    ] {
+        let mut metadata = HashMap::from([("body".to_string(), format!("chunk {id}"))]);
+        if !parent.is_empty() {
+            metadata.insert("parent_id".to_string(), parent.to_string());
+        }
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "chunks".into(),
+                id,
+                vector: vec![0.1 * f64::from(id - 10); 8],
+                metadata,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    for name in ["docs", "chunks"] {
+        let col = service.manager.get("default_admin", name).await.unwrap();
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    let search = |collection: &str| SearchRequest {
+        collection: collection.into(),
+        vector: vec![0.0; 8],
+        top_k: 5,
+        parents: Some(ParentSearch {
+            chunks_per_parent: 1,
+        }),
+        ..Default::default()
+    };
+    let response = service
+        .search(tonic::Request::new(search("chunks")))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.results.is_empty());
+    let parents: Vec<(u32, Vec<u32>)> = response
+        .parents
+        .iter()
+        .map(|p| (p.id, p.chunks.iter().map(|c| c.id).collect()))
+        .collect();
+    assert_eq!(parents, vec![(1, vec![10]), (2, vec![11])]);
+    assert_eq!(response.parents[0].metadata["title"], "Install guide");
+    assert_eq!(response.parents[0].distance, 0.0);
+
+    let err = service
+        .search(tonic::Request::new(search("docs")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_snapshot_history_serves_older_generations() {
+    use crate::snapshot_history::SnapshotRef;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_history_{uuid}"));
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let options = crate::manager::CollectionOptions {
+        snapshot: crate::snapshot::SnapshotPolicy {
+            interval_sec: Some(3600),
+            history: Some(2),
+            ..Default::default()
+        },
+        // Exact distances below
+        quantization: Some("none".to_string()),
+        ..Default::default()
+    };
+    manager
+        .create_collection_with_options("default_admin", "history", 8, "l2", options)
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "history").await.unwrap();
+    let settle = |col: std::sync::Arc<dyn hyperspace_core::Collection>| async move {
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        col.snapshot().await.unwrap();
+    };
+
+    col.insert(&[0.1; 8], 1, HashMap::new(), 1, Durability::Default)
+        .await
+        .unwrap();
+    settle(col.clone()).await;
+    // Nothing written since: no new generation
+    settle(col.clone()).await;
+    let first = manager
+        .snapshot_generations("default_admin", "history")
+        .unwrap();
+    assert_eq!(first.len(), 1);
+
+    // Moves point 1 in place and adds point 2
+    col.insert(&[0.9; 8], 1, HashMap::new(), 2, Durability::Default)
+        .await
+        .unwrap();
+    col.insert(&[0.5; 8], 2, HashMap::new(), 3, Durability::Default)
+        .await
+        .unwrap();
+    settle(col.clone()).await;
+    assert_eq!(
+        manager
+            .snapshot_generations("default_admin", "history")
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let params = hyperspace_core::SearchParams {
+        top_k: 5,
+        ef_search: 64,
+        ..Default::default()
+    };
+    let old = manager
+        .historical("default_admin", "history", SnapshotRef::Id(first[0]))
+        .await
+        .unwrap();
+    let hits = old
+        .search(&[0.1; 8], &HashMap::new(), &[], &params)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].0, 1);
+    assert!(hits[0].1 < 1e-6);
+    let latest = manager
+        .historical(
+            "default_admin",
+            "history",
+            SnapshotRef::AsOf(u64::MAX / 1000),
+        )
+        .await
+        .unwrap();
+    assert_eq!(latest.peek(10, 0).len(), 2);
+    assert!(manager
+        .historical("default_admin", "history", SnapshotRef::AsOf(0))
+        .await
+        .is_err());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_purge_erases_snapshot_history() {
+    use crate::snapshot_history::SnapshotRef;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_purge_history_{uuid}"));
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let options = crate::manager::CollectionOptions {
+        snapshot: crate::snapshot::SnapshotPolicy {
+            interval_sec: Some(3600),
+            history: Some(5),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    manager
+        .create_collection_with_options("default_admin", "erased", 8, "l2", options)
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "erased").await.unwrap();
+    for (id, owner) in [(1u32, "alice"), (2, "bob")] {
+        col.insert(
+            &[f64::from(id) * 0.1; 8],
+            id,
+            HashMap::from([("owner".to_string(), owner.to_string())]),
+            u64::from(id),
+            Durability::Default,
+        )
+        .await
+        .unwrap();
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        col.snapshot().await.unwrap();
+    }
+    let before = manager
+        .snapshot_generations("default_admin", "erased")
+        .unwrap();
+    assert_eq!(before.len(), 2);
+    // Open, so the purge has to close it
+    let old = manager
+        .historical("default_admin", "erased", SnapshotRef::Id(before[1]))
+        .await
+        .unwrap();
+    assert!(old.contains(1));
+    drop(old);
+
+    let filter = HashMap::from([("owner".to_string(), "alice".to_string())]);
+    let evidence = crate::purge::run(&manager, "erased", col.as_ref(), &filter, &[])
+        .await
+        .unwrap();
+    assert_eq!(evidence.purged_ids, vec![1]);
+    assert_eq!(evidence.history_generations_removed, 2);
+
+    let after = manager
+        .snapshot_generations("default_admin", "erased")
+        .unwrap();
+    assert_eq!(after.len(), 1);
+    assert!(!before.contains(&after[0]));
+    assert!(manager
+        .historical("default_admin", "erased", SnapshotRef::Id(before[1]))
+        .await
+        .is_err());
+    let fresh = manager
+        .historical("default_admin", "erased", SnapshotRef::Id(after[0]))
+        .await
+        .unwrap();
+    assert!(!fresh.contains(1));
+    assert!(fresh.contains(2));
+    drop(fresh);
+
+    // No rollback to a generation from before the purge
+    for version in before {
+        let err = manager
+            .rollback_collection("default_admin", "erased", version)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, hyperspace_core::HyperspaceError::NotFound(_)));
+    }
+    assert!(!col.contains(1));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_rollback_collection_and_forward_again() {
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_rollback_{uuid}"));
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let options = crate::manager::CollectionOptions {
+        snapshot: crate::snapshot::SnapshotPolicy {
+            interval_sec: Some(3600),
+            history: Some(5),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    manager
+        .create_collection_with_options("default_admin", "versions", 8, "l2", options)
+        .await
+        .unwrap();
+    for id in 1u32..=2 {
+        let col = manager.get("default_admin", "versions").await.unwrap();
+        col.insert(
+            &[f64::from(id) * 0.1; 8],
+            id,
+            HashMap::new(),
+            u64::from(id),
+            Durability::Default,
+        )
+        .await
+        .unwrap();
+        let start = std::time::Instant::now();
+        while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        col.snapshot().await.unwrap();
+    }
+    let versions = manager
+        .snapshot_generations("default_admin", "versions")
+        .unwrap();
+    assert_eq!(versions.len(), 2);
+
+    manager
+        .rollback_collection("default_admin", "versions", versions[0])
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "versions").await.unwrap();
+    assert_eq!(col.count(), 1);
+    assert!(!col.contains(2));
+    drop(col);
+    // The state rolled back from was already the newest version
+    assert_eq!(
+        manager
+            .snapshot_generations("default_admin", "versions")
+            .unwrap(),
+        versions
+    );
+
+    manager
+        .rollback_collection("default_admin", "versions", versions[1])
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "versions").await.unwrap();
+    assert_eq!(col.count(), 2);
+    drop(col);
+
+    let err = manager
+        .rollback_collection("default_admin", "versions", 1)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, hyperspace_core::HyperspaceError::NotFound(_)));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_wal_shipping_replays_primary_writes_on_standby() {
+    use super::wal_shipping::{self, Position, DEFAULT_MAX_BYTES};
+    use hyperspace_store::wal::Wal;
+
+    let uuid = Uuid::new_v4();
+    let primary_dir = env::temp_dir().join(format!("hyperspace_test_ship_primary_{uuid}"));
+    let standby_dir = env::temp_dir().join(format!("hyperspace_test_ship_standby_{uuid}"));
+    let (tx, _rx) = broadcast::channel(100);
+    let primary = CollectionManager::new(primary_dir.clone(), tx.clone());
+    let standby = CollectionManager::new(standby_dir.clone(), tx);
+    for manager in [&primary, &standby] {
+        manager
+            .create_collection("default_admin", "shipped", 8, "l2")
+            .await
+            .unwrap();
+    }
+
+    let col = primary.get("default_admin", "shipped").await.unwrap();
+    for id in 1u32..=3 {
+        col.insert(
+            &[f64::from(id) * 0.1; 8],
+            id,
+            HashMap::from([("n".to_string(), id.to_string())]),
+            u64::from(id),
+            Durability::Strict,
+        )
+        .await
+        .unwrap();
+    }
+    col.delete(2, 4).await.unwrap();
+
+    let target = standby.get("default_admin", "shipped").await.unwrap();
+    let mut position = Position::default();
+    loop {
+        let range = primary
+            .wal_range(
+                "default_admin",
+                "shipped",
+                position.after_segment,
+                position.offset,
+                DEFAULT_MAX_BYTES,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let (entries, consumed) = Wal::decode_records(&range.data).unwrap();
+        assert_eq!(consumed, range.data.len());
+        wal_shipping::apply(target.as_ref(), entries).await.unwrap();
+        position = position.advance(&range);
+        if !range.sealed && range.data.is_empty() {
+            break;
+        }
+    }
+    // Metadata is readable once the standby has indexed the points
+    let start = std::time::Instant::now();
+    while target.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    // `count` includes the deleted slot, as on the primary
+    assert_eq!(target.count(), col.count());
+    assert!(target.contains(1) && target.contains(3));
+    assert!(!target.contains(2));
+    assert_eq!(
+        target.metadata_by_id(3).get("n").map(String::as_str),
+        Some("3")
+    );
+
+    // Positions past the log are refused instead of misread
+    assert!(primary
+        .wal_range("default_admin", "shipped", 0, 1 << 30, DEFAULT_MAX_BYTES)
+        .await
+        .unwrap()
+        .is_none());
+    // Nothing to promote on a node that isn't a standby
+    assert_eq!(wal_shipping::promote(&standby).await, None);
+
+    let _ = fs::remove_dir_all(&primary_dir);
+    let _ = fs::remove_dir_all(&standby_dir);
+}
+
+#[tokio::test]
+async fn test_search_score_threshold_drops_distant_hits() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{InsertRequest, SearchRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_threshold_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    for id in 1..=4u32 {
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "docs".into(),
+                id,
+                vector: vec![0.2 * f64::from(id); 8],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let search = |score_threshold| SearchRequest {
+        collection: "docs".into(),
+        vector: vec![0.0; 8],
+        top_k: 4,
+        score_threshold,
+        ..Default::default()
+    };
+    let all = service
+        .search(tonic::Request::new(search(None)))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(all.len(), 4);
+
+    let cutoff = f64::midpoint(all[1].distance, all[2].distance);
+    let near = service
+        .search(tonic::Request::new(search(Some(cutoff))))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(
+        near.iter().map(|r| r.id).collect::<Vec<_>>(),
+        vec![all[0].id, all[1].id]
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_metadata_schema_strict_and_lenient() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        CreateCollectionRequest, InsertErrorCode, InsertErrorDetail, InsertRequest,
+        MetadataFieldType, SchemaMode,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_schema_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx.clone(),
+    );
+    for (name, mode) in [
+        ("strict", SchemaMode::SchemaStrict),
+        ("lenient", SchemaMode::SchemaLenient),
+    ] {
+        service
+            .create_collection(tonic::Request::new(CreateCollectionRequest {
+                name: name.into(),
+                dimension: 8,
+                metric: "l2".into(),
+                metadata_schema: HashMap::from([(
+                    "year".to_string(),
+                    MetadataFieldType::MetadataInt as i32,
+                )]),
+                schema_mode: mode as i32,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    let insert = |collection: &str, year: &str| InsertRequest {
+        collection: collection.into(),
+        id: 1,
+        vector: vec![0.1; 8],
+        metadata: HashMap::from([
+            ("year".to_string(), year.to_string()),
+            ("title".to_string(), "x".to_string()),
+        ]),
+        ..Default::default()
+    };
+
+    let status = service
+        .insert(tonic::Request::new(insert("strict", "banana")))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let detail = InsertErrorDetail::from_status(&status).unwrap();
+    assert_eq!(detail.code(), InsertErrorCode::MetadataTypeMismatch);
+    assert_eq!(detail.field, "metadata.year");
+    assert_eq!(detail.expected_type, "int");
+    service
+        .insert(tonic::Request::new(insert("strict", "1999")))
+        .await
+        .unwrap();
+
+    service
+        .insert(tonic::Request::new(insert("lenient", "banana")))
+        .await
+        .unwrap();
+    let col = service
+        .manager
+        .get("default_admin", "lenient")
+        .await
+        .unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let meta = col.metadata_by_id(1);
+    assert_eq!(meta.get("title").map(String::as_str), Some("x"));
+    assert_eq!(meta.get("year"), None);
+    drop(col);
+    drop(service);
+
+    // The schema is kept in meta.json.
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    let col = manager.get("default_admin", "strict").await.unwrap();
+    assert_eq!(
+        col.metadata_schema().map(|s| s.mode),
+        Some(hyperspace_core::SchemaMode::Strict)
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_normalization_policy_reject_and_project() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        CollectionStatsRequest, CreateCollectionRequest, InsertErrorCode, InsertErrorDetail,
+        InsertRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_normalization_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    let create = |name: &str, metric: &str, normalization: &str| CreateCollectionRequest {
+        name: name.into(),
+        dimension: 8,
+        metric: metric.into(),
+        normalization: normalization.into(),
+        projection_epsilon: Some(0.01),
+        ..Default::default()
+    };
+    let status = service
+        .create_collection(tonic::Request::new(create("bad", "cosine", "project")))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    service
+        .create_collection(tonic::Request::new(create(
+            "strict_cos",
+            "cosine",
+            "reject",
+        )))
+        .await
+        .unwrap();
+    service
+        .create_collection(tonic::Request::new(create("ball", "poincare", "project")))
+        .await
+        .unwrap();
+    // The smallest supported dimension; only the first two coordinates are set
+    let insert = |collection: &str, head: [f64; 2]| {
+        let mut vector = vec![0.0; 8];
+        vector[..2].copy_from_slice(&head);
+        InsertRequest {
+            collection: collection.into(),
+            id: 1,
+            vector,
+            ..Default::default()
+        }
+    };
+
+    let status = service
+        .insert(tonic::Request::new(insert("strict_cos", [3.0, 4.0])))
+        .await
+        .unwrap_err();
+    let detail = InsertErrorDetail::from_status(&status).unwrap();
+    assert_eq!(detail.code(), InsertErrorCode::NotNormalized);
+    assert!((detail.norm - 5.0).abs() < 1e-9);
+    service
+        .insert(tonic::Request::new(insert("strict_cos", [0.6, 0.8])))
+        .await
+        .unwrap();
+
+    service
+        .insert(tonic::Request::new(insert("ball", [0.8, 0.8])))
+        .await
+        .unwrap();
+    let col = service.manager.get("default_admin", "ball").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let norm = col
+        .vector_by_id(1)
+        .unwrap()
+        .iter()
+        .map(|x| x * x)
+        .sum::<f64>()
+        .sqrt();
+    assert!(norm < 1.0 && norm > 0.95, "projected norm {norm}");
+
+    let collection_stats = service
+        .get_collection_stats(tonic::Request::new(CollectionStatsRequest {
+            name: "ball".into(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(collection_stats.normalization, "project");
+    assert!((collection_stats.projection_epsilon - 0.01).abs() < 1e-12);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_fuses_multiple_queries() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{InsertRequest, QueryFusion, QueryVector, SearchRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_fusion_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    // Two clusters: ids 1-3 near -0.5, ids 11-13 near 0.5.
+    for (id, x) in [
+        (1, -0.5),
+        (2, -0.45),
+        (3, -0.4),
+        (11, 0.5),
+        (12, 0.45),
+        (13, 0.4),
+    ] {
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "docs".into(),
+                id,
+                vector: vec![x; 8],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let search = |fusion: QueryFusion| SearchRequest {
+        collection: "docs".into(),
+        vector: vec![-0.5; 8],
+        queries: vec![QueryVector {
+            values: vec![0.5; 8],
+        }],
+        query_fusion: fusion as i32,
+        top_k: 2,
+        ..Default::default()
+    };
+    let mut ids: Vec<u32> = service
+        .search(tonic::Request::new(search(QueryFusion::MaxSim)))
+        .await
+        .unwrap()
+        .into_inner()
+        .results
+        .iter()
+        .map(|r| r.id)
+        .collect();
+    ids.sort_unstable();
+    // Each query's nearest point wins, so both clusters are represented.
+    assert_eq!(ids, vec![1, 11]);
+
+    let rrf = service
+        .search(tonic::Request::new(search(QueryFusion::Rrf)))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(rrf.len(), 2);
+    assert!(rrf.iter().all(|r| r.distance < 1.0));
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_reranks_by_token_vectors() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        BatchInsertRequest, InsertRequest, SearchRequest, TokenVectors, VectorData,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_tokens_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let tokens = |values: &[f32]| {
+        Some(TokenVectors {
+            dimension: 2,
+            values: values.to_vec(),
+        })
+    };
+
+    // By the pooled vector 3 is nearest, then 1, then 2.
+    service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 1,
+            vector: vec![0.2; 8],
+            token_vectors: tokens(&[1.0, 0.0]),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    service
+        .batch_insert(tonic::Request::new(BatchInsertRequest {
+            collection: "docs".into(),
+            vectors: vec![
+                VectorData {
+                    id: 2,
+                    vector: vec![0.3; 8],
+                    token_vectors: tokens(&[1.0, 0.0, 0.0, 1.0]),
+                    ..Default::default()
+                },
+                VectorData {
+                    id: 3,
+                    vector: vec![0.1; 8],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    let err = service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 4,
+            vector: vec![0.4; 8],
+            token_vectors: tokens(&[1.0, 0.0, 0.5]),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let results = service
+        .search(tonic::Request::new(SearchRequest {
+            collection: "docs".into(),
+            vector: vec![0.1; 8],
+            query_tokens: tokens(&[1.0, 0.0, 0.0, 1.0]),
+            top_k: 3,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    let ids: Vec<u32> = results.iter().map(|r| r.id).collect();
+    // 2 matches both query tokens, 1 only the first, 3 has no tokens.
+    assert_eq!(ids, vec![2, 1, 3]);
+    assert!(results[0].distance.abs() < 1e-6);
+    assert!((results[1].distance - 1.0).abs() < 1e-6);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_experiment_routes_sessions_and_logs() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        DeleteExperimentRequest, Experiment, GetExperimentRequest, InsertRequest,
+        PutExperimentRequest, SearchArm, SearchRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_experiment_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 1,
+            vector: vec![0.1; 8],
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    let put = |fraction: f64| PutExperimentRequest {
+        collection: "docs".into(),
+        experiment: Some(Experiment {
+            name: "ef-sweep".into(),
+            arm_a: Some(SearchArm::default()),
+            arm_b: Some(SearchArm {
+                ef_search: Some(400),
+                ..Default::default()
+            }),
+            arm_b_fraction: fraction,
+        }),
+    };
+    let err = service
+        .put_experiment(tonic::Request::new(put(2.0)))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    service
+        .put_experiment(tonic::Request::new(put(0.5)))
+        .await
+        .unwrap();
+
+    let search = |session: &str| SearchRequest {
+        collection: "docs".into(),
+        vector: vec![0.1; 8],
+        top_k: 1,
+        session_key: session.into(),
+        ..Default::default()
+    };
+    let mut arms = Vec::new();
+    for i in 0..20 {
+        let resp = service
+            .search(tonic::Request::new(search(&format!("user-{i}"))))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(resp.results.len(), 1);
+        arms.push(resp.experiment_arm);
+    }
+    // The same session always lands in the same arm.
+    let again = service
+        .search(tonic::Request::new(search("user-3")))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(again.experiment_arm, arms[3]);
+    assert!(arms.iter().any(|a| a == "a") && arms.iter().any(|a| a == "b"));
+    let plain = service
+        .search(tonic::Request::new(search("")))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(plain.experiment_arm, "");
+
+    let stats = service
+        .get_experiment(tonic::Request::new(GetExperimentRequest {
+            collection: "docs".into(),
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .stats;
+    assert_eq!(stats.iter().map(|s| s.queries).sum::<u64>(), 21);
+    let dir = tmp_dir.join(CollectionManager::get_internal_name(
+        "default_admin",
+        "docs",
+    ));
+    let log = fs::read_to_string(dir.join(crate::experiments::EXPERIMENT_LOG_FILE)).unwrap();
+    assert_eq!(log.lines().count(), 21);
+    assert!(log.contains(r#""session":"user-3""#));
+
+    service
+        .delete_experiment(tonic::Request::new(DeleteExperimentRequest {
+            collection: "docs".into(),
+        }))
+        .await
+        .unwrap();
+    let after = service
+        .search(tonic::Request::new(search("user-3")))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(after.experiment_arm, "");
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_slow_search_is_logged_with_visited_nodes() {
+    use crate::slow_queries::SlowQueryLog;
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{InsertRequest, SearchRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_slow_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let mut manager = CollectionManager::new(tmp_dir.clone(), tx.clone());
+    // Every search counts as slow.
+    manager.slow_queries = Arc::new(SlowQueryLog::new(Some(Duration::from_nanos(1)), 8));
+    let service = test_service(Arc::new(manager), tx);
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    for id in 0..20u32 {
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "docs".into(),
+                id,
+                vector: vec![f64::from(id) / 40.0; 8],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Rejected requests never run, so they are not logged.
+    service
+        .search(tonic::Request::new(SearchRequest {
+            collection: "docs".into(),
+            vector: vec![0.2; 8],
+            top_k: 3,
+            filter_expr: Some("lang = \"en\" OR lang = \"de\"".into()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    service
+        .search(tonic::Request::new(SearchRequest {
+            collection: "docs".into(),
+            vector: vec![0.2; 8],
+            top_k: 3,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    let entries = service
+        .manager
+        .slow_queries
+        .entries(Some("default_admin"), 10);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].collection, "docs");
+    assert_eq!(entries[0].top_k, 3);
+    assert_eq!(entries[0].results, 3);
+    assert!(entries[0].visited >= 3, "visited {}", entries[0].visited);
+    assert_eq!(
+        service.manager.slow_queries.entries(Some("someone"), 10),
+        Vec::new()
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_search_explain_reports_phases_and_filter() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{InsertRequest, SearchRequest};
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_explain_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx,
+    );
+    service
+        .manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    for id in 0..40u32 {
+        let lang = if id % 4 == 0 { "de" } else { "en" };
+        service
+            .insert(tonic::Request::new(InsertRequest {
+                collection: "docs".into(),
+                id,
+                vector: vec![f64::from(id) / 80.0; 8],
+                metadata: HashMap::from([("lang".to_string(), lang.to_string())]),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+    let col = service.manager.get("default_admin", "docs").await.unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let search = |filter_expr: Option<&str>, explain: bool| SearchRequest {
+        collection: "docs".into(),
+        vector: vec![0.2; 8],
+        top_k: 3,
+        filter_expr: filter_expr.map(str::to_string),
+        explain,
+        ..Default::default()
+    };
+    let plain = service
+        .search(tonic::Request::new(search(None, false)))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(plain.explain.is_none());
+
+    let unfiltered = service
+        .search(tonic::Request::new(search(None, true)))
+        .await
+        .unwrap()
+        .into_inner();
+    let explain = unfiltered.explain.unwrap();
+    assert_eq!(unfiltered.results.len(), 3);
+    assert!(explain.visited_nodes >= 3, "{explain:?}");
+    assert!(explain.layers_traversed >= 1);
+    assert!(explain.total_ms >= explain.layer0_ms);
+    assert_eq!(explain.filter_selectivity, None);
+    assert!(!explain.filter_bruteforce);
+
+    let filtered = service
+        .search(tonic::Request::new(search(Some("lang = \"de\""), true)))
+        .await
+        .unwrap()
+        .into_inner();
+    let explain = filtered.explain.unwrap();
+    assert_eq!(filtered.results.len(), 3);
+    assert_eq!(explain.filter_matches, 10);
+    assert_eq!(explain.filter_selectivity, Some(0.25));
+    // Ten matches are far below the brute-force threshold.
+    assert!(explain.filter_bruteforce);
+    assert_eq!(explain.visited_nodes, 10);
+
+    let nothing = service
+        .search(tonic::Request::new(search(Some("lang = \"fr\""), true)))
+        .await
+        .unwrap()
+        .into_inner();
+    let explain = nothing.explain.unwrap();
+    assert_eq!(nothing.results, Vec::new());
+    assert!(explain.filter_short_circuit);
+    assert_eq!(explain.visited_nodes, 0);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_apply_collection_spec_converges_and_rejects_drift() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        ApplyCollectionSpecRequest, CollectionSpec, HnswParams, InsertRequest,
+        ListCollectionSpecsRequest, SearchRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_specs_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone())),
+        tx.clone(),
+    );
+    let spec = CollectionSpec {
+        name: "docs_v1".into(),
+        dimension: 8,
+        metric: "l2".into(),
+        quantization: "none".into(),
+        hnsw: Some(HnswParams {
+            m: Some(8),
+            ef_construction: None,
+        }),
+        aliases: vec!["docs".into()],
+        description: "product docs".into(),
+        labels: HashMap::from([("team".to_string(), "search".to_string())]),
+    };
+    let apply = |spec: CollectionSpec, dry_run: bool| {
+        tonic::Request::new(ApplyCollectionSpecRequest {
+            spec: Some(spec),
+            dry_run,
+        })
+    };
+
+    let created = service
+        .apply_collection_spec(apply(spec.clone(), false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(created.created);
+    assert!(
+        created.changes[0].starts_with("create docs_v1"),
+        "{created:?}"
+    );
+
+    // The alias serves data-plane calls for the collection it points to.
+    service
+        .insert(tonic::Request::new(InsertRequest {
+            collection: "docs".into(),
+            id: 1,
+            vector: vec![0.1; 8],
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    let col = service
+        .manager
+        .get("default_admin", "docs_v1")
+        .await
+        .unwrap();
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let found = service
+        .search(tonic::Request::new(SearchRequest {
+            collection: "docs".into(),
+            vector: vec![0.1; 8],
+            top_k: 1,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(found.results[0].id, 1);
+
+    let again = service
+        .apply_collection_spec(apply(spec.clone(), false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!again.created);
+    assert_eq!(again.changes, Vec::<String>::new());
+
+    let mut relabeled = spec.clone();
+    relabeled.labels.insert("tier".into(), "gold".into());
+    let planned = service
+        .apply_collection_spec(apply(relabeled.clone(), true))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(planned.changes.len(), 1);
+    assert!(planned.changes[0].starts_with("labels"), "{planned:?}");
+    let listed = service
+        .list_collection_specs(tonic::Request::new(ListCollectionSpecsRequest::default()))
+        .await
+        .unwrap()
+        .into_inner()
+        .specs;
+    assert_eq!(listed, vec![spec.clone()]);
+
+    service
+        .apply_collection_spec(apply(relabeled.clone(), false))
+        .await
+        .unwrap();
+
+    let mut resized = relabeled.clone();
+    resized.dimension = 16;
+    let err = service
+        .apply_collection_spec(apply(resized, false))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    assert!(err.message().contains("dimension 8 -> 16"), "{err:?}");
+
+    // Aliases and build parameters survive a restart.
+    drop(service);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    assert!(manager.get("default_admin", "docs").await.is_some());
+    assert_eq!(
+        manager.collection_specs("default_admin", ""),
+        vec![super::collection_spec::CollectionSpec {
+            name: "docs_v1".into(),
+            dimension: 8,
+            metric: "l2".into(),
+            quantization: "none".into(),
+            hnsw: super::collection_spec::HnswParams {
+                m: Some(8),
+                ef_construction: None,
+            },
+            aliases: vec!["docs".into()],
+            description: "product docs".into(),
+            labels: relabeled.labels.into_iter().collect(),
+        }]
+    );
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_storage_alerts_reach_their_owner_only() {
+    use super::ReplicationFeed;
+    use futures::StreamExt;
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        event_message::Payload, EventSubscriptionRequest, EventType, StorageAlertEvent,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_alerts_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let feed = ReplicationFeed::from(tx);
+    let service = test_service(
+        Arc::new(CollectionManager::new(tmp_dir.clone(), feed.clone())),
+        feed.clone(),
+    );
+    let mut events = service
+        .subscribe_to_events(tonic::Request::new(EventSubscriptionRequest {
+            types: vec![EventType::StorageAlert as i32],
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let alert = |collection: &str| StorageAlertEvent {
+        collection: collection.into(),
+        error: "Capacity exceeded: disk full".into(),
+        consecutive_failures: 3,
+        read_only: true,
+    };
+    // Both receivers exist once the handler returns.
+    feed.alert(alert("tenant_b_docs"));
+    feed.alert(alert("default_admin_team~docs"));
+
+    let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.r#type, EventType::StorageAlert as i32);
+    let Some(Payload::StorageAlert(received)) = event.payload else {
+        panic!("unexpected payload: {event:?}");
+    };
+    assert_eq!(received.collection, "team/docs");
+    assert!(received.read_only);
+    assert_eq!(received.consecutive_failures, 3);
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn test_scrub_finds_and_repairs_corrupt_vectors() {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_scrub_{uuid}"));
+    let (tx, _rx) = broadcast::channel(1024);
+    let manager = CollectionManager::new(tmp_dir.clone(), tx);
+    manager
+        .create_collection("default_admin", "docs", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "docs").await.unwrap();
+    for id in 0..20u32 {
+        col.insert(
+            &[f64::from(id) / 40.0; 8],
+            id,
+            HashMap::new(),
+            u64::from(id) + 1,
+            Durability::Default,
+        )
+        .await
+        .unwrap();
+    }
+    let start = std::time::Instant::now();
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(col.scrub_report().is_none());
+
+    let clean = col.scrub(false).await.unwrap();
+    assert_eq!(clean.checked, 20);
+    assert_eq!(clean.corrupt, Vec::<u32>::new());
+    assert_eq!((clean.storage_count, clean.graph_nodes), (20, 20));
+
+    // Rot one byte of vector 5 on disk.
+    let chunk = tmp_dir.join("default_admin_docs").join("chunk_0.hyp");
+    let element_size = fs::metadata(&chunk).unwrap().len() / 65_536;
+    let mut file = fs::OpenOptions::new().write(true).open(&chunk).unwrap();
+    file.seek(SeekFrom::Start(5 * element_size)).unwrap();
+    file.write_all(&[0xA5]).unwrap();
+    file.sync_all().unwrap();
+
+    let found = col.scrub(false).await.unwrap();
+    assert_eq!(found.corrupt, vec![5]);
+    assert!(!found.repaired);
+    assert!(col.contains(5));
+    assert_eq!(col.scrub_report(), Some(found));
+
+    let repaired = col.scrub(true).await.unwrap();
+    assert!(repaired.repaired);
+    assert!(!col.contains(5));
+    // Deleted slots are no longer reported.
+    assert_eq!(col.scrub(false).await.unwrap().corrupt, Vec::<u32>::new());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+#[tokio::test]
+async fn read_only_key_is_limited_to_read_methods() {
+    use crate::auth;
+    use tonic::codegen::http;
+    use tower::{Service, ServiceBuilder, ServiceExt};
+
+    let interceptor = crate::AuthInterceptor {
+        expected_hash: Some(auth::hash_key("admin-key")),
+        read_hash: Some(auth::hash_key("viewer-key")),
+    };
+    // Same stack as the gRPC server: path tagging in front of the interceptor.
+    let mut service = ServiceBuilder::new()
+        .map_request(auth::tag_grpc_path)
+        .service(tonic::service::interceptor::InterceptedService::new(
+            tower::service_fn(|_: http::Request<()>| async {
+                Ok::<_, std::convert::Infallible>(http::Response::new(tonic::body::empty_body()))
+            }),
+            interceptor,
+        ));
+
+    let mut call = async |key: &str, method: &str| {
+        let request = http::Request::builder()
+            .uri(format!("/hyperspace.Database/{method}"))
+            .header("x-api-key", key)
+            .body(())
+            .unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        response
+            .headers()
+            .get("grpc-status")
+            .map(|s| tonic::Code::from_bytes(s.as_bytes()))
+    };
+
+    assert_eq!(call("viewer-key", "Search").await, None);
+    assert_eq!(call("viewer-key", "Traverse").await, None);
+    assert_eq!(
+        call("viewer-key", "Insert").await,
+        Some(tonic::Code::PermissionDenied)
+    );
+    assert_eq!(call("admin-key", "Insert").await, None);
+    assert_eq!(
+        call("wrong-key", "Search").await,
+        Some(tonic::Code::Unauthenticated)
+    );
+}
+
+#[tokio::test]
+async fn test_csv_ingest_job_maps_columns_and_reports_bad_rows() {
+    use super::ingest::{self, ColumnMapping, IngestRequest, JobState, TextEmbedder};
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_ingest_{uuid}"));
+    let import_dir = tmp_dir.join("import");
+    fs::create_dir_all(&import_dir).unwrap();
+    fs::write(
+        import_dir.join("points.csv"),
+        "id,embedding,color\n\
+         1,\"[0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1]\",red\n\
+         2,0.2 0.2 0.2 0.2 0.2 0.2 0.2 0.2,blue\n\
+         x,0.3 0.3 0.3 0.3 0.3 0.3 0.3 0.3,red\n\
+         4,0.4 0.4,red\n\
+         5,\"0.5,0.5,0.5,0.5,0.5,0.5,0.5,0.5\",\n",
+    )
+    .unwrap();
+    env::set_var("HS_INGEST_DIR", &import_dir);
+
+    let (tx, _rx) = broadcast::channel(100);
+    let manager = CollectionManager::new(tmp_dir.join("data"), tx);
+    manager
+        .create_collection("default_admin", "csv", 8, "l2")
+        .await
+        .unwrap();
+    let col = manager.get("default_admin", "csv").await.unwrap();
+
+    let request = IngestRequest {
+        path: "points.csv".into(),
+        format: None,
+        columns: Some(ColumnMapping {
+            id: "id".into(),
+            vector: Some("embedding".into()),
+            text: None,
+            metadata: None,
+        }),
+        first_id: None,
+        batch_size: 2,
+        delimiter: ',',
+    };
+    let job = ingest::start(
+        &manager.ingest_jobs,
+        manager.meter.clone(),
+        "default_admin",
+        "csv",
+        col.clone(),
+        0,
+        request,
+        TextEmbedder::default(),
+    )
+    .await
+    .unwrap();
+
+    let start = std::time::Instant::now();
+    let status = loop {
+        let status = manager.ingest_jobs.get("default_admin", &job.id).unwrap();
+        if status.state != JobState::Running || start.elapsed() > Duration::from_secs(10) {
+            break status;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(status.state, JobState::Done);
+    assert_eq!((status.rows, status.inserted, status.failed), (5, 3, 2));
+    assert_eq!(status.errors.len(), 2);
+    assert!(manager.ingest_jobs.get("tenant", &job.id).is_none());
+    assert_eq!(manager.ingest_jobs.list("default_admin").len(), 1);
+
+    while col.queue_size() > 0 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(col.count(), 3);
+    let meta = col.metadata_by_id(1);
+    assert_eq!(meta.get("color").map(String::as_str), Some("red"));
+    assert!(col.metadata_by_id(5).is_empty());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
+
+/// Runs `writes` random writes over collections "a" and "b" on a leader
+/// and three followers, then heals the network.
+async fn simulate(seed: u64, faults: Faults, writes: usize) -> Cluster {
+    let mut cluster = Cluster::start(seed, 3, faults).await;
+    // Created on a healthy network, so followers have them before faults start
+    cluster.heal().await;
+    cluster.create_collection("a", 8).await;
+    cluster.create_collection("b", 16).await;
+    cluster.heal().await;
+    for i in 0..writes {
+        cluster.write(if i % 3 == 0 { "b" } else { "a" }).await;
+        cluster.tick().await;
+    }
+    cluster.heal().await;
+    cluster
+}
+
+#[tokio::test]
+async fn test_simulated_replication_converges_without_faults() {
+    let mut cluster = simulate(7, Faults::none(), 200).await;
+    assert!(
+        cluster.divergence().await.is_empty(),
+        "{:?}",
+        cluster.divergence().await
+    );
+    assert_eq!(cluster.repair().await, 0);
+}
+
+#[tokio::test]
+async fn test_simulated_faults_converge_after_repair() {
+    for seed in 0..4 {
+        let mut cluster = simulate(seed, Faults::default(), 400).await;
+        cluster.repair().await;
+        let divergence = cluster.divergence().await;
+        assert!(
+            divergence.is_empty(),
+            "seed {seed}: {divergence:?}\n{}",
+            cluster.trace.join("\n")
+        );
+        assert_eq!(
+            cluster.repair().await,
+            0,
+            "seed {seed}: repair is not idempotent"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_simulation_same_seed_replays_same_schedule() {
+    let first = simulate(42, Faults::default(), 150).await;
+    let second = simulate(42, Faults::default(), 150).await;
+    assert_eq!(first.trace, second.trace);
+    for name in ["a", "b"] {
+        let (a, b) = (
+            first.leader().get("default_admin", name).await.unwrap(),
+            second.leader().get("default_admin", name).await.unwrap(),
+        );
+        assert_eq!(a.buckets(), b.buckets());
+    }
+}
+
+#[test]
+fn test_http_data_writes_are_fenced() {
+    use super::http_server::is_data_write;
+    use axum::http::Method;
+
+    for (method, path) in [
+        (Method::POST, "/api/collections/docs/insert"),
+        (Method::POST, "/api/collections/docs/bulk"),
+        (Method::POST, "/api/collections/docs/ingest"),
+        (Method::POST, "/api/collections/docs/purge"),
+        (Method::POST, "/api/collections/docs/snapshot"),
+        (Method::POST, "/api/collections/docs/scrub"),
+        (Method::POST, "/api/collections/docs/rebuild"),
+        (Method::PUT, "/api/collections/docs/synonyms"),
+        (Method::PUT, "/api/specs"),
+        (Method::POST, "/api/collections"),
+        (Method::DELETE, "/api/collections/docs"),
+    ] {
+        assert!(is_data_write(&method, path), "{method} {path}");
+    }
+    for (method, path) in [
+        (Method::GET, "/api/collections/docs"),
+        (Method::GET, "/api/collections/docs/snapshot"),
+        (Method::GET, "/api/collections/docs/scrub"),
+        (Method::GET, "/api/collections/docs/synonyms"),
+        (Method::GET, "/api/specs"),
+        (Method::GET, "/api/collections"),
+        (Method::POST, "/api/collections/docs/search"),
+    ] {
+        assert!(!is_data_write(&method, path), "{method} {path}");
+    }
+}
+
+#[tokio::test]
+async fn test_fenced_leader_rejects_collection_admin_writes() {
+    use hyperspace_proto::hyperspace::database_server::Database;
+    use hyperspace_proto::hyperspace::{
+        ApplyCollectionSpecRequest, CollectionSpec, CreateCollectionRequest, NamespaceRequest,
+        RestoreCollectionRequest,
+    };
+    use std::sync::Arc;
+
+    let uuid = Uuid::new_v4();
+    let tmp_dir = env::temp_dir().join(format!("hyperspace_test_fenced_admin_{uuid}"));
+    let (tx, _rx) = broadcast::channel(16);
+    let manager = Arc::new(CollectionManager::new(tmp_dir.clone(), tx.clone()));
+    manager
+        .create_collection("default_admin", "team/docs", 8, "l2")
+        .await
+        .unwrap();
+    // A newer leader took over while this node's lease was still running.
+    manager.fencing.lead(1, u64::MAX);
+    assert!(manager.fencing.observe(2));
+    let service = test_service(manager.clone(), tx);
+
+    let status = service
+        .delete_namespace(tonic::Request::new(NamespaceRequest {
+            namespace: "team".into(),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let status = service
+        .restore_collection(tonic::Request::new(RestoreCollectionRequest {
+            name: "team/old".into(),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let status = service
+        .apply_collection_spec(tonic::Request::new(ApplyCollectionSpecRequest {
+            spec: Some(CollectionSpec {
+                name: "team/new".into(),
+                dimension: 8,
+                metric: "l2".into(),
+                ..Default::default()
+            }),
+            dry_run: false,
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    let status = service
+        .create_collection(tonic::Request::new(CreateCollectionRequest {
+            name: "team/created".into(),
+            dimension: 8,
+            metric: "l2".into(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+    assert!(manager.get("default_admin", "team/docs").await.is_some());
+    assert!(manager.get("default_admin", "team/new").await.is_none());
+    assert!(manager.get("default_admin", "team/created").await.is_none());
+
+    let _ = fs::remove_dir_all(&tmp_dir);
+}
@@ -0,0 +1,688 @@
+//! Collections without vectors (dimension 0).
+//!
+//! A text collection is the metadata side of an index on its own: tags,
+//! numeric ranges and BM25 statistics in a [`MetadataIndex`], with no vector
+//! store or graph. Searches rank `hybrid_query` by BM25 over the documents
+//! passing the filters; without a query they list the matches by ID.
+//!
+//! Writes go to a WAL of vector-less records before the index. A snapshot
+//! writes every stored row to `docs.json` and starts an empty WAL, so
+//! opening the collection replays at most the writes since.
+
+use crate::collection::bm25_params_from_env;
+use crate::limits::{CollectionLimits, LimitGuard};
+use crate::replication::ReplicationFeed;
+use crate::snapshot::SnapshotPolicy;
+use hyperspace_core::bm25::Bm25Params;
+use hyperspace_core::{
+    Collection, Durability, FilterExpr, GraphLayer, HyperspaceError, HyperspaceResult,
+    IndexingProgress, MetadataKeyStats, MetadataSchema, PurgeOutcome, QuantizationMode,
+    SearchParams, SearchResult, SynonymMap,
+};
+use hyperspace_index::MetadataIndex;
+use hyperspace_proto::hyperspace::{replication_log, InsertOp, PurgeOp, ReplicationLog};
+use hyperspace_store::wal::{Wal, WalEntry, WalSyncMode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const DOCS_FILE: &str = "docs.json";
+
+/// Contents of `docs.json`.
+#[derive(Default, Serialize, Deserialize)]
+struct DocsSnapshot {
+    clock: u64,
+    docs: BTreeMap<u32, HashMap<String, String>>,
+}
+
+pub struct TextCollection {
+    name: String,
+    node_id: String,
+    data_dir: PathBuf,
+    metadata: MetadataIndex,
+    bm25: Bm25Params,
+    // Held across the index update too, so a snapshot never misses a write
+    // whose WAL record it discards.
+    wal: tokio::sync::Mutex<Wal>,
+    replication_tx: ReplicationFeed,
+    snapshot_policy: SnapshotPolicy,
+    limits: LimitGuard,
+    schema: Option<MetadataSchema>,
+    last_clock: AtomicU64,
+    ops_since_snapshot: AtomicU64,
+}
+
+impl TextCollection {
+    /// Opens the collection in `data_dir`: loads `docs.json`, then replays
+    /// the WAL.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(
+        name: String,
+        node_id: String,
+        data_dir: PathBuf,
+        replication_tx: ReplicationFeed,
+        snapshot_policy: SnapshotPolicy,
+        limits: CollectionLimits,
+        schema: Option<MetadataSchema>,
+        wal_sync_mode: Option<WalSyncMode>,
+        synonyms: SynonymMap,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let bm25 = bm25_params_from_env();
+        let metadata = MetadataIndex::default();
+        if let Some(schema) = &schema {
+            metadata.set_unindexed_keys(schema.unindexed.iter().cloned().collect());
+        }
+        metadata.set_synonyms(synonyms);
+        let mut last_clock = 0;
+
+        let docs_path = data_dir.join(DOCS_FILE);
+        if docs_path.exists() {
+            let snapshot: DocsSnapshot = serde_json::from_slice(&std::fs::read(&docs_path)?)?;
+            last_clock = snapshot.clock;
+            for (id, meta) in snapshot.docs {
+                metadata.index_doc(id, meta, &bm25);
+            }
+        }
+        // Segments left by a snapshot that crashed after writing docs.json
+        remove_frozen_segments(&data_dir)?;
+
+        let wal_path = data_dir.join("wal.log");
+        let mut replayed = 0;
+        Wal::replay(&wal_path, |entry| {
+            replayed += 1;
+            match entry {
+                WalEntry::Insert {
+                    id,
+                    metadata: meta,
+                    logical_clock,
+                    ..
+                } => {
+                    metadata.remove_doc(id, &bm25);
+                    metadata.index_doc(id, meta, &bm25);
+                    last_clock = last_clock.max(logical_clock);
+                }
+                WalEntry::Delete { id, logical_clock } => {
+                    metadata.remove_doc(id, &bm25);
+                    last_clock = last_clock.max(logical_clock);
+                }
+            }
+        })?;
+        if replayed > 0 {
+            println!("📜 Replayed {replayed} WAL record(s) into text collection '{name}'");
+        }
+        let wal = Wal::new(
+            &wal_path,
+            wal_sync_mode.unwrap_or_else(WalSyncMode::from_env),
+        )?;
+
+        Ok(Self {
+            limits: LimitGuard::new(limits, data_dir.clone()),
+            name,
+            node_id,
+            data_dir,
+            metadata,
+            bm25,
+            wal: tokio::sync::Mutex::new(wal),
+            replication_tx,
+            snapshot_policy,
+            schema,
+            last_clock: AtomicU64::new(last_clock),
+            ops_since_snapshot: AtomicU64::new(replayed),
+        })
+    }
+
+    fn no_vectors(&self) -> HyperspaceError {
+        HyperspaceError::Validation(format!(
+            "'{}' is a text-only collection and stores no vectors",
+            self.name
+        ))
+    }
+
+    /// Writes `docs.json` under the WAL lock and starts an empty WAL.
+    /// Returns the insert records of `redact` the discarded WAL held.
+    fn checkpoint(&self, wal: &mut Wal, redact: &HashSet<u32>) -> HyperspaceResult<u64> {
+        let snapshot = DocsSnapshot {
+            clock: self.last_clock.load(Ordering::Relaxed),
+            docs: self
+                .metadata
+                .forward
+                .ids()
+                .into_iter()
+                .filter_map(|id| self.metadata.forward.get(id).map(|meta| (id, meta)))
+                .collect(),
+        };
+        let bytes = serde_json::to_vec(&snapshot)
+            .map_err(|e| HyperspaceError::Internal(format!("Snapshot encoding failed: {e}")))?;
+        let tmp = self.data_dir.join(format!("{DOCS_FILE}.tmp"));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, self.data_dir.join(DOCS_FILE))?;
+
+        let frozen = wal.rotate()?;
+        let removed = if redact.is_empty() {
+            0
+        } else {
+            Wal::redact(&frozen, redact)?
+        };
+        std::fs::remove_file(&frozen)?;
+        self.ops_since_snapshot.store(0, Ordering::Relaxed);
+        Ok(removed)
+    }
+
+    /// Snapshots once the policy's write count or WAL size is reached.
+    fn maybe_checkpoint(&self, wal: &mut Wal) -> HyperspaceResult<()> {
+        let ops = self.ops_since_snapshot.fetch_add(1, Ordering::Relaxed) + 1;
+        let due = self.snapshot_policy.every_ops().is_some_and(|n| ops >= n)
+            || self
+                .snapshot_policy
+                .wal_bytes()
+                .is_some_and(|n| wal.size() >= n);
+        if due {
+            self.checkpoint(wal, &HashSet::new())?;
+        }
+        Ok(())
+    }
+
+    fn publish(&self, clock: u64, operation: replication_log::Operation) {
+        if self.replication_tx.is_active() {
+            self.replication_tx.publish(ReplicationLog {
+                logical_clock: clock,
+                origin_node_id: self.node_id.clone(),
+                collection: self.name.clone(),
+                operation: Some(operation),
+                ..Default::default()
+            });
+        }
+    }
+
+    fn hit(&self, id: u32, distance: f64) -> SearchResult {
+        (id, distance, self.metadata_by_id(id))
+    }
+}
+
+/// Removes `wal.frozen.*` files from `dir`.
+fn remove_frozen_segments(dir: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let frozen = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("wal.frozen."));
+        if frozen {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl Collection for TextCollection {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn insert(
+        &self,
+        vector: &[f64],
+        id: u32,
+        metadata: HashMap<String, String>,
+        clock: u64,
+        durability: Durability,
+    ) -> HyperspaceResult<()> {
+        if !vector.is_empty() {
+            return Err(self.no_vectors());
+        }
+        let new_points = usize::from(!self.contains(id));
+        self.limits.check(self.count(), new_points)?;
+
+        let mut wal = self.wal.lock().await;
+        wal.append(id, &[], &metadata, clock)?;
+        if durability == Durability::Strict {
+            wal.sync()?;
+        }
+        self.last_clock.fetch_max(clock, Ordering::Relaxed);
+        self.metadata.remove_doc(id, &self.bm25);
+        self.metadata.index_doc(id, metadata.clone(), &self.bm25);
+        self.maybe_checkpoint(&mut wal)?;
+        drop(wal);
+
+        self.publish(
+            clock,
+            replication_log::Operation::Insert(InsertOp {
+                id,
+                vector: Vec::new(),
+                metadata,
+                typed_metadata: HashMap::new(),
+            }),
+        );
+        Ok(())
+    }
+
+    // Unlike inserts, deletes are replicated by the API layer.
+    async fn delete(&self, id: u32, clock: u64) -> HyperspaceResult<()> {
+        let mut wal = self.wal.lock().await;
+        wal.append_delete(id, clock)?;
+        self.last_clock.fetch_max(clock, Ordering::Relaxed);
+        self.metadata.remove_doc(id, &self.bm25);
+        self.maybe_checkpoint(&mut wal)
+    }
+
+    async fn update_vector_delta(
+        &self,
+        _id: u32,
+        _indices: &[u32],
+        _values: &[f64],
+        _clock: u64,
+        _durability: Durability,
+    ) -> HyperspaceResult<()> {
+        Err(self.no_vectors())
+    }
+
+    async fn search(
+        &self,
+        vector: &[f64],
+        filter: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        params: &SearchParams,
+    ) -> HyperspaceResult<Vec<SearchResult>> {
+        if !vector.is_empty() {
+            return Err(HyperspaceError::Validation(format!(
+                "'{}' is a text-only collection; search it with hybrid_query and no vector",
+                self.name
+            )));
+        }
+        if complex_filters.iter().any(|f| {
+            matches!(
+                f,
+                FilterExpr::InBox { .. } | FilterExpr::InCone { .. } | FilterExpr::InBall { .. }
+            )
+        }) {
+            return Err(HyperspaceError::Validation(
+                "Geometric filters need vectors; text-only collections support match and range filters".into(),
+            ));
+        }
+
+        let text = params.hybrid_query.as_deref().unwrap_or_default();
+        let allowed = match (
+            self.metadata
+                .filter_bitmap(filter, complex_filters, &self.bm25),
+            self.metadata
+                .text_match_bitmap(text, params.text_match, &self.bm25),
+        ) {
+            (Some(bitmap), Some(matches)) => Some(bitmap & matches),
+            (allowed, None) => allowed,
+            (None, matches) => matches,
+        };
+        let tokens: HashSet<String> = self
+            .metadata
+            .query_tokens(text, &self.bm25)
+            .into_iter()
+            .collect();
+        if tokens.is_empty() {
+            let ids: Vec<u32> = if let Some(bitmap) = &allowed {
+                bitmap.iter().take(params.top_k).collect()
+            } else {
+                let mut ids = self.metadata.forward.ids();
+                ids.sort_unstable();
+                ids.truncate(params.top_k);
+                ids
+            };
+            return Ok(ids.into_iter().map(|id| self.hit(id, 0.0)).collect());
+        }
+
+        let bm25 = params
+            .bm25_options
+            .clone()
+            .unwrap_or_else(|| self.bm25.clone());
+        let scores = self
+            .metadata
+            .bm25_scores(tokens, &bm25, self.count(), |id| {
+                allowed.as_ref().is_none_or(|bitmap| bitmap.contains(id))
+            });
+        // Smaller is closer, as for vector distances.
+        Ok(scores
+            .into_iter()
+            .take(params.top_k)
+            .map(|(id, score)| self.hit(id, 1.0 / (1.0 + score)))
+            .collect())
+    }
+
+    fn count(&self) -> usize {
+        self.metadata.forward.len()
+    }
+
+    fn dimension(&self) -> usize {
+        0
+    }
+
+    fn metric_name(&self) -> &'static str {
+        "bm25"
+    }
+
+    // Delta sync compares vector hashes; replication carries every text write.
+    fn state_hash(&self) -> u64 {
+        0
+    }
+
+    fn buckets(&self) -> Vec<u64> {
+        vec![0; 256]
+    }
+
+    fn queue_size(&self) -> u64 {
+        0
+    }
+
+    fn indexing_progress(&self) -> IndexingProgress {
+        let clock = self.last_clock.load(Ordering::Relaxed);
+        IndexingProgress {
+            indexed_clock: clock,
+            written_clock: clock,
+            queue_depth: 0,
+        }
+    }
+
+    async fn snapshot(&self) -> HyperspaceResult<()> {
+        let mut wal = self.wal.lock().await;
+        self.checkpoint(&mut wal, &HashSet::new()).map(|_| ())
+    }
+
+    async fn set_wal_sync_mode(&self, mode: Durability) -> HyperspaceResult<()> {
+        let mode = match mode {
+            Durability::Strict => WalSyncMode::Strict,
+            Durability::Batch => WalSyncMode::Batch,
+            Durability::Async => WalSyncMode::Async,
+            Durability::Default => WalSyncMode::from_env(),
+        };
+        self.wal.lock().await.set_mode(mode)?;
+        Ok(())
+    }
+
+    fn peek(&self, limit: usize, offset: usize) -> Vec<(u32, Vec<f64>, HashMap<String, String>)> {
+        let mut ids = self.metadata.forward.ids();
+        ids.sort_unstable();
+        ids.into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|id| (id, Vec::new(), self.metadata_by_id(id)))
+            .collect()
+    }
+
+    fn graph_neighbors(
+        &self,
+        _id: u32,
+        _layer: usize,
+        _limit: usize,
+    ) -> HyperspaceResult<Vec<u32>> {
+        Err(self.no_vectors())
+    }
+
+    fn graph_neighbor_distances(
+        &self,
+        _source_id: u32,
+        _neighbor_ids: &[u32],
+    ) -> HyperspaceResult<Vec<f64>> {
+        Err(self.no_vectors())
+    }
+
+    fn graph_traverse(
+        &self,
+        _start_id: u32,
+        _layer: usize,
+        _max_depth: usize,
+        _max_nodes: usize,
+    ) -> HyperspaceResult<Vec<u32>> {
+        Err(self.no_vectors())
+    }
+
+    fn graph_clusters(
+        &self,
+        _layer: usize,
+        _min_cluster_size: usize,
+        _max_clusters: usize,
+        _max_nodes: usize,
+    ) -> HyperspaceResult<Vec<Vec<u32>>> {
+        Err(self.no_vectors())
+    }
+
+    fn graph_max_layer(&self) -> usize {
+        0
+    }
+
+    fn graph_layer(&self, _layer: usize) -> HyperspaceResult<GraphLayer> {
+        Err(self.no_vectors())
+    }
+
+    fn metadata_by_id(&self, id: u32) -> HashMap<String, String> {
+        self.metadata.forward.get(id).unwrap_or_default()
+    }
+
+    fn ids_with_metadata(&self, key: &str, value: &str) -> Vec<u32> {
+        let filter = HashMap::from([(key.to_string(), value.to_string())]);
+        self.metadata
+            .filter_bitmap(&filter, &[], &self.bm25)
+            .map_or_else(Vec::new, |ids| ids.iter().collect())
+    }
+
+    fn vector_by_id(&self, _id: u32) -> Option<Vec<f64>> {
+        None
+    }
+
+    fn contains(&self, id: u32) -> bool {
+        self.metadata.forward.with(id, |_| ()).is_some()
+    }
+
+    async fn purge(
+        &self,
+        filter: &HashMap<String, String>,
+        complex_filters: &[FilterExpr],
+        clock: u64,
+    ) -> HyperspaceResult<PurgeOutcome> {
+        let Some(matched) = self
+            .metadata
+            .filter_bitmap(filter, complex_filters, &self.bm25)
+        else {
+            return Err(HyperspaceError::Validation(
+                "Purge needs a filter; delete the collection to erase all of it".into(),
+            ));
+        };
+        let ids: Vec<u32> = matched.iter().collect();
+        self.purge_ids(&ids, clock).await
+    }
+
+    async fn purge_ids(&self, ids: &[u32], clock: u64) -> HyperspaceResult<PurgeOutcome> {
+        let purged: Vec<u32> = ids
+            .iter()
+            .copied()
+            .filter(|&id| self.contains(id))
+            .collect();
+        if purged.is_empty() {
+            return Ok(PurgeOutcome::default());
+        }
+        let set: HashSet<u32> = purged.iter().copied().collect();
+
+        // The snapshot drops the WAL, so no tombstones are needed.
+        let wal_records_removed = {
+            let mut wal = self.wal.lock().await;
+            self.last_clock.fetch_max(clock, Ordering::Relaxed);
+            for &id in &purged {
+                self.metadata.remove_doc(id, &self.bm25);
+            }
+            self.checkpoint(&mut wal, &set)?
+        };
+        let journal_entries_removed = self.replication_tx.redact(&self.name, &set)?;
+
+        self.publish(
+            clock,
+            replication_log::Operation::Purge(PurgeOp {
+                ids: purged.clone(),
+            }),
+        );
+        println!(
+            "🧹 Purged {} document(s) from '{}' ({wal_records_removed} WAL record(s), {journal_entries_removed} journal entries)",
+            purged.len(),
+            self.name
+        );
+        Ok(PurgeOutcome {
+            purged,
+            wal_records_removed,
+            journal_entries_removed,
+            ..PurgeOutcome::default()
+        })
+    }
+
+    fn metadata_schema(&self) -> Option<&MetadataSchema> {
+        self.schema.as_ref()
+    }
+
+    fn metadata_key_stats(&self) -> Vec<MetadataKeyStats> {
+        self.metadata.key_stats()
+    }
+
+    fn metadata_distinct_values(&self) -> HashMap<String, u64> {
+        self.metadata.distinct_values()
+    }
+
+    fn set_synonyms(&self, synonyms: SynonymMap) {
+        let reindex = synonyms.expand_at_index || self.metadata.synonyms().expand_at_index;
+        self.metadata.set_synonyms(synonyms);
+        if reindex {
+            self.metadata.rebuild_lexical_stats(&self.bm25);
+        }
+    }
+
+    fn quantization_mode(&self) -> QuantizationMode {
+        QuantizationMode::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hyperspace_text_{tag}_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn open(dir: &Path) -> TextCollection {
+        TextCollection::open(
+            "docs".into(),
+            "node".into(),
+            dir.to_path_buf(),
+            ReplicationFeed::from(tokio::sync::broadcast::channel(16).0),
+            SnapshotPolicy::default(),
+            CollectionLimits::default(),
+            None,
+            None,
+            SynonymMap::default(),
+        )
+        .unwrap()
+    }
+
+    fn meta(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    fn query(text: &str) -> SearchParams {
+        SearchParams {
+            top_k: 10,
+            hybrid_query: Some(text.into()),
+            ..SearchParams::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn keyword_search_with_filters() {
+        let dir = temp_dir("search");
+        let col = open(&dir);
+        let docs = [
+            (1, "rust vector database", "db"),
+            (2, "hyperbolic geometry notes", "math"),
+            (3, "rust borrow checker", "lang"),
+        ];
+        for (id, body, kind) in docs {
+            col.insert(
+                &[],
+                id,
+                meta(&[("body", body), ("kind", kind)]),
+                1,
+                Durability::Default,
+            )
+            .await
+            .unwrap();
+        }
+
+        let hits = col
+            .search(&[], &HashMap::new(), &[], &query("rust"))
+            .await
+            .unwrap();
+        let mut ids: Vec<u32> = hits.iter().map(|h| h.0).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 3]);
+
+        let filter = meta(&[("kind", "lang")]);
+        let hits = col.search(&[], &filter, &[], &query("rust")).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, 3);
+        assert_eq!(hits[0].2["kind"], "lang");
+
+        // No query text lists the filter matches.
+        let list = SearchParams {
+            top_k: 10,
+            ..SearchParams::default()
+        };
+        let hits = col.search(&[], &filter, &[], &list).await.unwrap();
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), vec![3]);
+
+        assert!(col
+            .search(&[0.1], &filter, &[], &query("rust"))
+            .await
+            .is_err());
+        assert!(col
+            .insert(&[0.1], 4, HashMap::new(), 2, Durability::Default)
+            .await
+            .is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reopen_replays_wal_and_snapshot() {
+        let dir = temp_dir("reopen");
+        {
+            let col = open(&dir);
+            col.insert(&[], 1, meta(&[("body", "alpha")]), 1, Durability::Default)
+                .await
+                .unwrap();
+            col.snapshot().await.unwrap();
+            col.insert(
+                &[],
+                2,
+                meta(&[("body", "alpha beta")]),
+                2,
+                Durability::Default,
+            )
+            .await
+            .unwrap();
+            col.insert(&[], 1, meta(&[("body", "gamma")]), 3, Durability::Default)
+                .await
+                .unwrap();
+            col.delete(2, 4).await.unwrap();
+        }
+
+        let col = open(&dir);
+        assert_eq!(col.count(), 1);
+        assert_eq!(col.metadata_by_id(1)["body"], "gamma");
+        assert_eq!(col.indexing_progress().written_clock, 4);
+        let hits = col
+            .search(&[], &HashMap::new(), &[], &query("alpha"))
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,160 @@
+//! Soft deletion of collections.
+//!
+//! With `HS_TRASH_RETENTION_SEC` above zero, deleting a collection moves its
+//! directory into `<data>/.trash/` instead of removing it. There it can be
+//! restored until the retention window passes, after which a background task
+//! purges it. Entries are named `{deleted_at_secs}_{internal_name}`, so the
+//! same collection can sit in the trash more than once; a restore takes the
+//! newest copy.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Directory under the data path holding trashed collections.
+pub const TRASH_DIR: &str = ".trash";
+
+static PURGED: AtomicU64 = AtomicU64::new(0);
+
+/// Process-wide number of trashed collections purged after retention.
+pub fn purged() -> u64 {
+    PURGED.load(Ordering::Relaxed)
+}
+
+/// Retention window from `HS_TRASH_RETENTION_SEC` (default 0); `None` when
+/// `0`, which keeps deletes immediate.
+pub fn retention_from_env() -> Option<Duration> {
+    let secs = std::env::var("HS_TRASH_RETENTION_SEC")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// A collection directory waiting in the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// `{user}_{name}` as on disk.
+    pub internal_name: String,
+    pub deleted_at: u64,
+    pub path: PathBuf,
+}
+
+/// Moves `internal_name`'s directory into the trash.
+pub fn move_to_trash(base: &Path, internal_name: &str) -> io::Result<()> {
+    let trash = base.join(TRASH_DIR);
+    fs::create_dir_all(&trash)?;
+    let mut deleted_at = now_secs();
+    // Deleting, restoring and deleting again within a second must not collide.
+    while trash.join(format!("{deleted_at}_{internal_name}")).exists() {
+        deleted_at += 1;
+    }
+    fs::rename(
+        base.join(internal_name),
+        trash.join(format!("{deleted_at}_{internal_name}")),
+    )
+}
+
+/// Every trashed collection, oldest first.
+pub fn list(base: &Path) -> Vec<TrashEntry> {
+    let Ok(entries) = fs::read_dir(base.join(TRASH_DIR)) else {
+        return Vec::new();
+    };
+    let mut list: Vec<TrashEntry> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let (secs, internal_name) = file_name.split_once('_')?;
+            Some(TrashEntry {
+                internal_name: internal_name.to_string(),
+                deleted_at: secs.parse().ok()?,
+                path: entry.path(),
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| (a.deleted_at, &a.internal_name).cmp(&(b.deleted_at, &b.internal_name)));
+    list
+}
+
+/// Moves the newest trashed copy of `internal_name` back into place. Returns
+/// `Ok(false)` when the trash holds none.
+pub fn restore(base: &Path, internal_name: &str) -> io::Result<bool> {
+    let Some(entry) = list(base)
+        .into_iter()
+        .rev()
+        .find(|e| e.internal_name == internal_name)
+    else {
+        return Ok(false);
+    };
+    fs::rename(entry.path, base.join(internal_name))?;
+    Ok(true)
+}
+
+/// Removes trashed collections older than `retention`; returns how many.
+pub fn purge_expired(base: &Path, retention: Duration) -> usize {
+    let cutoff = now_secs().saturating_sub(retention.as_secs());
+    let mut purged = 0;
+    for entry in list(base) {
+        if entry.deleted_at > cutoff {
+            break;
+        }
+        match fs::remove_dir_all(&entry.path) {
+            Ok(()) => purged += 1,
+            Err(e) => eprintln!("⚠️ Trash: cannot purge '{}': {e}", entry.path.display()),
+        }
+    }
+    PURGED.fetch_add(purged as u64, Ordering::Relaxed);
+    purged
+}
+
+/// Spawns the purge loop, checking at least once a minute.
+pub fn spawn_purger(base: PathBuf, retention: Duration) {
+    tokio::spawn(async move {
+        let every = retention.min(Duration::from_mins(1));
+        loop {
+            tokio::time::sleep(every).await;
+            let n = purge_expired(&base, retention);
+            if n > 0 {
+                println!("🗑️ Trash: purged {n} expired collection(s)");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trash_restore_and_purge() {
+        let base = std::env::temp_dir().join(format!("hs_trash_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(base.join("u_docs")).unwrap();
+        fs::write(base.join("u_docs").join("meta.json"), "{}").unwrap();
+
+        move_to_trash(&base, "u_docs").unwrap();
+        assert!(!base.join("u_docs").exists());
+        fs::create_dir_all(base.join("u_docs")).unwrap();
+        move_to_trash(&base, "u_docs").unwrap();
+        let entries = list(&base);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].deleted_at < entries[1].deleted_at);
+
+        // The newest copy comes back; the older one stays until purged.
+        assert!(restore(&base, "u_docs").unwrap());
+        assert!(!base.join("u_docs").join("meta.json").exists());
+        assert!(!restore(&base, "u_other").unwrap());
+
+        assert_eq!(purge_expired(&base, Duration::from_secs(3600)), 0);
+        assert_eq!(purge_expired(&base, Duration::ZERO), 1);
+        assert_eq!(list(&base), Vec::new());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,132 @@
+//! Progress of WAL replay while a collection opens.
+//!
+//! Replay reads the WAL into storage first, then links the recovered points
+//! into the graph, which is where the time goes. The linking phase reports
+//! progress in the log every few seconds and through `/api/status` until it
+//! finishes.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+const LOG_EVERY: Duration = Duration::from_secs(5);
+
+static ACTIVE: LazyLock<Mutex<Vec<Arc<Progress>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug)]
+struct Progress {
+    collection: String,
+    started: Instant,
+    total: u64,
+    done: AtomicU64,
+    last_log: Mutex<Instant>,
+}
+
+/// One replay in `/api/status`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReplayStatus {
+    pub collection: String,
+    pub done: u64,
+    pub total: u64,
+    pub entries_per_sec: f64,
+    pub eta_sec: Option<u64>,
+}
+
+impl Progress {
+    fn status(&self) -> ReplayStatus {
+        let done = self.done.load(Ordering::Relaxed);
+        let secs = self.started.elapsed().as_secs_f64();
+        let rate = if secs > 0.0 { done as f64 / secs } else { 0.0 };
+        let eta_sec = (rate > 0.0).then(|| ((self.total - done) as f64 / rate).ceil() as u64);
+        ReplayStatus {
+            collection: self.collection.clone(),
+            done,
+            total: self.total,
+            entries_per_sec: rate,
+            eta_sec,
+        }
+    }
+}
+
+/// Linking phase of one collection's replay; unregistered on drop.
+pub struct ReplayTracker {
+    progress: Arc<Progress>,
+}
+
+impl ReplayTracker {
+    pub fn start(collection: &str, total: u64) -> Self {
+        let progress = Arc::new(Progress {
+            collection: collection.to_string(),
+            started: Instant::now(),
+            total,
+            done: AtomicU64::new(0),
+            last_log: Mutex::new(Instant::now()),
+        });
+        ACTIVE.lock().push(progress.clone());
+        Self { progress }
+    }
+
+    /// Counts one linked point and logs when the last line is old enough.
+    pub fn advance(&self) {
+        let p = &self.progress;
+        p.done.fetch_add(1, Ordering::Relaxed);
+        let Some(mut last) = p.last_log.try_lock() else {
+            return;
+        };
+        if last.elapsed() < LOG_EVERY {
+            return;
+        }
+        *last = Instant::now();
+        let s = p.status();
+        println!(
+            "⏳ WAL replay [{}]: {}/{} entries ({:.0}/s, ETA {}s)",
+            s.collection,
+            s.done,
+            s.total,
+            s.entries_per_sec,
+            s.eta_sec.unwrap_or(0)
+        );
+    }
+}
+
+impl Drop for ReplayTracker {
+    fn drop(&mut self) {
+        ACTIVE.lock().retain(|p| !Arc::ptr_eq(p, &self.progress));
+        let s = self.progress.status();
+        if s.total > 0 {
+            println!(
+                "✅ WAL replay [{}]: {} entries in {:.1}s ({:.0}/s)",
+                s.collection,
+                s.done,
+                self.progress.started.elapsed().as_secs_f64(),
+                s.entries_per_sec
+            );
+        }
+    }
+}
+
+/// Replays in progress, in start order.
+pub fn active() -> Vec<ReplayStatus> {
+    ACTIVE.lock().iter().map(|p| p.status()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_is_listed_until_dropped() {
+        let name = format!("replay_{}", uuid::Uuid::new_v4());
+        let find = || active().into_iter().find(|s| s.collection == name);
+        let tracker = ReplayTracker::start(&name, 4);
+        tracker.advance();
+        tracker.advance();
+        let status = find().unwrap();
+        assert_eq!((status.done, status.total), (2, 4));
+        assert!(status.eta_sec.is_some());
+        drop(tracker);
+        assert_eq!(find(), None);
+    }
+}
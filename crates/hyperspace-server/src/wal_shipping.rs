@@ -0,0 +1,525 @@
+//! WAL shipping to a warm standby.
+//!
+//! A node started with `--standby-of <primary>` (`HS_STANDBY_OF`) copies the
+//! primary's write-ahead log rather than its replication stream. Every
+//! `HS_STANDBY_POLL_MS` it lists the primary's collections, creates the ones
+//! it lacks and calls `PullWal` for each. Records arrive exactly as the
+//! primary wrote them, checksums included, and are applied in log order with
+//! the primary's clocks, so the standby's data is what a restart of the
+//! primary would replay.
+//!
+//! A position is the newest sealed segment applied (the rotation time in its
+//! `wal.frozen.<ms>` name) and the bytes applied of the segment after it. The
+//! standby keeps it in `standby.json` in the collection's directory and
+//! resumes from there after a restart. The primary's rotation history
+//! (`wal.rotations`) tells which file that next segment is by now, so an
+//! offset is never applied to a different segment. A record applied twice after a crash
+//! is harmless: inserts upsert and deletes are idempotent.
+//!
+//! `PromoteStandby` waits for the pull in progress, stops pulling and makes
+//! the node a writable leader.
+//!
+//! Only writes to points go through the WAL. Collections deleted or
+//! reconfigured on the primary are left as they are. In tiered mode a sealed
+//! segment flushed into a chunk before the standby read it can't be shipped
+//! any more; `PullWal` then answers `OUT_OF_RANGE` and the standby has to be
+//! reseeded, e.g. with `ExportCollection`.
+
+use crate::manager::{ClusterRole, CollectionManager};
+use hyperspace_core::{Collection, Durability};
+use hyperspace_proto::hyperspace::database_client::DatabaseClient;
+use hyperspace_proto::hyperspace::{ListCollectionsRequest, PullWalRequest};
+use hyperspace_store::wal::{Wal, WalEntry};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Weak;
+use std::time::Duration;
+use tonic::transport::Channel;
+
+/// Name of the segment a collection appends to.
+pub const ACTIVE_SEGMENT: &str = "wal.log";
+
+/// Prefix of sealed segments, followed by their rotation time.
+const FROZEN_PREFIX: &str = "wal.frozen.";
+
+/// Standby's position in a collection's log.
+pub const POSITION_FILE: &str = "standby.json";
+
+/// `PullWal` reply size when the request leaves it at 0, and its cap.
+pub const DEFAULT_MAX_BYTES: usize = 4 << 20;
+pub const MAX_BYTES: usize = 64 << 20;
+
+/// Poll interval from `HS_STANDBY_POLL_MS` (default 500).
+pub fn poll_interval_from_env() -> Duration {
+    let ms = std::env::var("HS_STANDBY_POLL_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&ms| ms > 0)
+        .unwrap_or(500);
+    Duration::from_millis(ms)
+}
+
+/// Records of one segment read for a standby.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WalRange {
+    /// Rotation time of a sealed segment, 0 for the active one.
+    pub segment: u64,
+    pub offset: u64,
+    pub data: Vec<u8>,
+    pub sealed: bool,
+}
+
+/// Sealed segments in `col_dir`, oldest first.
+fn sealed_segments(col_dir: &Path) -> Vec<(u64, PathBuf)> {
+    let mut segments: Vec<(u64, PathBuf)> = std::fs::read_dir(col_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let ts = name.to_str()?.strip_prefix(FROZEN_PREFIX)?.parse().ok()?;
+            Some((ts, entry.path()))
+        })
+        .collect();
+    segments.sort_unstable_by_key(|(ts, _)| *ts);
+    segments
+}
+
+/// The segment that began when `after_segment` was sealed (0: the first
+/// one), as its rotation time (0 while active) and path. The path may no
+/// longer exist.
+fn next_segment(col_dir: &Path, after_segment: u64) -> io::Result<(u64, PathBuf)> {
+    let active = col_dir.join(ACTIVE_SEGMENT);
+    let history = Wal::rotations(&active)?;
+    let sealed = sealed_segments(col_dir);
+    let recorded = if after_segment == 0 {
+        sealed.iter().all(|(ts, _)| history.contains(ts))
+    } else {
+        history.contains(&after_segment)
+    };
+    if recorded {
+        return Ok(match history.iter().find(|&&ts| ts > after_segment) {
+            Some(&ts) => (ts, col_dir.join(format!("{FROZEN_PREFIX}{ts}"))),
+            None => (0, active),
+        });
+    }
+    // Sealed before rotations were recorded: the oldest segment left.
+    Ok(sealed
+        .into_iter()
+        .find(|(ts, _)| *ts > after_segment)
+        .unwrap_or((0, active)))
+}
+
+/// Blocking: whole records of the collection in `col_dir` from the position
+/// `(after_segment, offset)` on, at most `max_bytes` unless the first record
+/// alone is larger. `None` when the position is no longer in the log.
+pub fn read_range(
+    col_dir: &Path,
+    after_segment: u64,
+    offset: u64,
+    max_bytes: usize,
+) -> io::Result<Option<WalRange>> {
+    // The active segment may be sealed between the listing and the read, so
+    // a position past its end is looked up once more.
+    for _ in 0..2 {
+        let (segment, path) = next_segment(col_dir, after_segment)?;
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            // Sealed and flushed meanwhile
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        if offset > len {
+            continue;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        (&mut file)
+            .take(max_bytes.max(9) as u64)
+            .read_to_end(&mut data)?;
+        let (_, mut consumed) = Wal::decode_records(&data)?;
+        if consumed == 0 && data.len() >= 9 {
+            let record = 9 + u64::from(u32::from_le_bytes([data[1], data[2], data[3], data[4]]));
+            file.take(record.saturating_sub(data.len() as u64))
+                .read_to_end(&mut data)?;
+            consumed = Wal::decode_records(&data)?.1;
+        }
+        data.truncate(consumed);
+        return Ok(Some(WalRange {
+            segment,
+            offset,
+            sealed: segment != 0 && offset + consumed as u64 == len,
+            data,
+        }));
+    }
+    Ok(None)
+}
+
+/// Where a standby continues in one collection's log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub after_segment: u64,
+    pub offset: u64,
+}
+
+impl Position {
+    pub fn load(col_dir: &Path) -> Self {
+        std::fs::read(col_dir.join(POSITION_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(self, col_dir: &Path) -> io::Result<()> {
+        let tmp = col_dir.join(format!("{POSITION_FILE}.tmp"));
+        std::fs::write(&tmp, serde_json::to_vec(&self)?)?;
+        std::fs::rename(tmp, col_dir.join(POSITION_FILE))
+    }
+
+    /// The position after applying `range`.
+    pub fn advance(self, range: &WalRange) -> Self {
+        if range.sealed {
+            Self {
+                after_segment: range.segment,
+                offset: 0,
+            }
+        } else {
+            Self {
+                after_segment: self.after_segment,
+                offset: range.offset + range.data.len() as u64,
+            }
+        }
+    }
+}
+
+/// Applies decoded records to `col` in log order.
+pub async fn apply(
+    col: &dyn Collection,
+    entries: Vec<WalEntry>,
+) -> hyperspace_core::HyperspaceResult<()> {
+    for entry in entries {
+        match entry {
+            WalEntry::Insert {
+                id,
+                vector,
+                metadata,
+                logical_clock,
+            } => {
+                col.insert(&vector, id, metadata, logical_clock, Durability::Batch)
+                    .await?;
+            }
+            WalEntry::Delete { id, logical_clock } => col.delete(id, logical_clock).await?,
+        }
+    }
+    Ok(())
+}
+
+/// Standby state of this node.
+#[derive(Default)]
+pub struct WalStandby {
+    // Primary pulled from; `None` once promoted or when not a standby
+    primary: parking_lot::Mutex<Option<String>>,
+    // Held through each pull round, so promotion waits for it
+    round: tokio::sync::Mutex<()>,
+    applied_bytes: AtomicU64,
+}
+
+impl WalStandby {
+    pub fn primary(&self) -> Option<String> {
+        self.primary.lock().clone()
+    }
+
+    /// Record bytes applied since startup.
+    pub fn applied_bytes(&self) -> u64 {
+        self.applied_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Stops pulling once the round in progress is done. Returns the primary
+    /// this node stood by for, `None` if it wasn't a standby.
+    pub async fn promote(&self) -> Option<String> {
+        let _round = self.round.lock().await;
+        self.primary.lock().take()
+    }
+}
+
+/// Pulls and applies one collection's log up to what the primary has.
+async fn ship_collection(
+    client: &mut DatabaseClient<Channel>,
+    api_key: &str,
+    user_id: &str,
+    name: &str,
+    col: &dyn Collection,
+    col_dir: &Path,
+    standby: &WalStandby,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut position = Position::load(col_dir);
+    loop {
+        let reply = client
+            .pull_wal(crate::anti_entropy::request(
+                PullWalRequest {
+                    collection: name.to_string(),
+                    after_segment: position.after_segment,
+                    offset: position.offset,
+                    max_bytes: 0,
+                },
+                api_key,
+                user_id,
+            ))
+            .await?
+            .into_inner();
+        let range = WalRange {
+            segment: reply.segment,
+            offset: reply.offset,
+            data: reply.data,
+            sealed: reply.sealed,
+        };
+        let (entries, consumed) = Wal::decode_records(&range.data)?;
+        if consumed != range.data.len() {
+            return Err("primary sent a partial record".into());
+        }
+        apply(col, entries).await?;
+        standby
+            .applied_bytes
+            .fetch_add(consumed as u64, Ordering::Relaxed);
+        let next = position.advance(&range);
+        if next != position {
+            next.save(col_dir)?;
+        }
+        // Caught up with the active segment
+        if !range.sealed && range.data.is_empty() {
+            return Ok(());
+        }
+        position = next;
+    }
+}
+
+/// Spawns the pull loop against `primary`; it ends once the node is
+/// promoted or shut down.
+pub fn spawn(
+    manager: Weak<CollectionManager>,
+    primary: String,
+    api_key: String,
+    user_id: String,
+    every: Duration,
+) {
+    if let Some(mgr) = manager.upgrade() {
+        *mgr.standby.primary.lock() = Some(primary.clone());
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        let mut client: Option<DatabaseClient<Channel>> = None;
+        loop {
+            ticker.tick().await;
+            let Some(mgr) = manager.upgrade() else {
+                break;
+            };
+            let _round = mgr.standby.round.lock().await;
+            if mgr.standby.primary().is_none() {
+                break;
+            }
+            if client.is_none() {
+                match DatabaseClient::connect(primary.clone()).await {
+                    Ok(c) => client = Some(c),
+                    Err(e) => {
+                        eprintln!("⚠️ Standby: cannot reach primary {primary}: {e}");
+                        continue;
+                    }
+                }
+            }
+            let Some(conn) = client.as_mut() else {
+                continue;
+            };
+            let listed = conn
+                .list_collections(crate::anti_entropy::request(
+                    ListCollectionsRequest::default(),
+                    &api_key,
+                    &user_id,
+                ))
+                .await;
+            let summaries = match listed {
+                Ok(reply) => reply.into_inner().collections,
+                Err(e) => {
+                    eprintln!("⚠️ Standby: listing collections on {primary} failed: {e}");
+                    client = None;
+                    continue;
+                }
+            };
+            for summary in summaries {
+                let internal = CollectionManager::get_internal_name(&user_id, &summary.name);
+                if mgr.get_internal(&internal).await.is_none() {
+                    println!("Standby: creating collection {}", summary.name);
+                    if let Err(e) = mgr
+                        .create_collection_from_replication(
+                            &internal,
+                            summary.dimension,
+                            &summary.metric,
+                            crate::manager::CollectionInfo {
+                                description: summary.description,
+                                labels: summary.labels.into_iter().collect(),
+                            },
+                        )
+                        .await
+                    {
+                        eprintln!("⚠️ Standby: cannot create '{}': {e}", summary.name);
+                        continue;
+                    }
+                }
+                let Some(col) = mgr.get_internal(&internal).await else {
+                    continue;
+                };
+                let col_dir = mgr.internal_dir(&internal);
+                if let Err(e) = ship_collection(
+                    conn,
+                    &api_key,
+                    &user_id,
+                    &summary.name,
+                    col.as_ref(),
+                    &col_dir,
+                    &mgr.standby,
+                )
+                .await
+                {
+                    eprintln!("⚠️ Standby: '{}' failed: {e}", summary.name);
+                }
+            }
+        }
+    });
+}
+
+/// Switches a standby to leader: pulling stops after the round in progress
+/// and writes are accepted. Returns the former primary, `None` if this node
+/// wasn't a standby.
+pub async fn promote(manager: &CollectionManager) -> Option<String> {
+    let primary = manager.standby.promote().await?;
+    let mut state = manager.cluster_state.write().await;
+    state.role = ClusterRole::Leader;
+    state.upstream_peer = None;
+    println!("👑 Standby: promoted to leader (was standing by for {primary})");
+    Some(primary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperspace_store::wal::WalSyncMode;
+    use std::collections::HashMap;
+
+    #[test]
+    fn reads_sealed_then_active_segments() {
+        let dir = std::env::temp_dir().join(format!("hs_ship_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut wal = Wal::new(&dir.join(ACTIVE_SEGMENT), WalSyncMode::Strict).unwrap();
+        let meta = HashMap::new();
+        wal.append(1, &[0.1, 0.2], &meta, 1).unwrap();
+        wal.append(2, &[0.3, 0.4], &meta, 2).unwrap();
+        let frozen = wal.rotate().unwrap();
+        wal.append_delete(1, 3).unwrap();
+        wal.sync().unwrap();
+        let sealed: u64 = frozen
+            .to_str()
+            .and_then(|p| p.rsplit('.').next())
+            .and_then(|ts| ts.parse().ok())
+            .unwrap();
+
+        // Small replies still carry one whole record
+        let first = read_range(&dir, 0, 0, 1).unwrap().unwrap();
+        assert_eq!(first.segment, sealed);
+        assert_eq!(Wal::decode_records(&first.data).unwrap().0.len(), 1);
+        assert!(!first.sealed);
+
+        let mut position = Position::default().advance(&first);
+        let rest = read_range(
+            &dir,
+            position.after_segment,
+            position.offset,
+            DEFAULT_MAX_BYTES,
+        )
+        .unwrap()
+        .unwrap();
+        assert!(rest.sealed);
+        position = position.advance(&rest);
+        assert_eq!(
+            position,
+            Position {
+                after_segment: sealed,
+                offset: 0
+            }
+        );
+
+        let active = read_range(&dir, sealed, 0, DEFAULT_MAX_BYTES)
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.segment, 0);
+        let (entries, _) = Wal::decode_records(&active.data).unwrap();
+        assert!(matches!(
+            entries[..],
+            [WalEntry::Delete {
+                id: 1,
+                logical_clock: 3
+            }]
+        ));
+        position = position.advance(&active);
+        position.save(&dir).unwrap();
+        assert_eq!(Position::load(&dir), position);
+
+        // Past the end of the log, e.g. after the segment was flushed away
+        assert_eq!(
+            read_range(&dir, sealed, 1 << 20, DEFAULT_MAX_BYTES).unwrap(),
+            None
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn segment_flushed_under_a_standby_is_out_of_range() {
+        let dir = std::env::temp_dir().join(format!("hs_ship_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut wal = Wal::new(&dir.join(ACTIVE_SEGMENT), WalSyncMode::Strict).unwrap();
+        let meta = HashMap::new();
+        wal.append(1, &[0.1, 0.2], &meta, 1).unwrap();
+        wal.sync().unwrap();
+        let position = Position::default()
+            .advance(&read_range(&dir, 0, 0, DEFAULT_MAX_BYTES).unwrap().unwrap());
+        assert!(position.offset > 0);
+
+        // The standby's segment is sealed and flushed into a chunk; the new
+        // active segment is long enough to hold its offset.
+        let frozen = wal.rotate().unwrap();
+        std::fs::remove_file(frozen).unwrap();
+        for id in 2..6 {
+            wal.append(id, &[0.1, 0.2], &meta, u64::from(id)).unwrap();
+        }
+        wal.sync().unwrap();
+        assert_eq!(
+            read_range(
+                &dir,
+                position.after_segment,
+                position.offset,
+                DEFAULT_MAX_BYTES
+            )
+            .unwrap(),
+            None
+        );
+
+        // Also when a later sealed segment is still there.
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        wal.rotate().unwrap();
+        assert_eq!(
+            read_range(
+                &dir,
+                position.after_segment,
+                position.offset,
+                DEFAULT_MAX_BYTES
+            )
+            .unwrap(),
+            None
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
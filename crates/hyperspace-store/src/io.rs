@@ -0,0 +1,233 @@
+//! File output for the WAL and snapshots.
+//!
+//! Built with the `io-uring` feature on Linux, writes go through an
+//! `io_uring` ring with a registered buffer, and a write that must be durable
+//! is submitted linked to its fsync: one syscall where std IO needs a
+//! `write` and an `fsync`, which is what bounds Strict-mode WAL throughput.
+//! `HS_IO_URING=false` turns it off at runtime. Without the feature, on other
+//! systems, or when the kernel refuses to set up a ring (too old, seccomp),
+//! plain std IO is used.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// A file being appended to, with writes buffered until `flush` or `sync`.
+#[derive(Debug)]
+pub(crate) enum Output {
+    Std(BufWriter<File>),
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    Ring(Box<uring::RingFile>),
+}
+
+impl Output {
+    /// Wraps `file`, whose next write lands at `offset`.
+    pub(crate) fn new(file: File, offset: u64) -> Self {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        if uring::enabled() {
+            match file
+                .try_clone()
+                .and_then(|clone| uring::RingFile::new(clone, offset))
+            {
+                Ok(ring) => return Self::Ring(Box::new(ring)),
+                Err(e) => uring::fall_back(&e),
+            }
+        }
+        let _ = offset;
+        Self::Std(BufWriter::new(file))
+    }
+
+    /// Writes out buffered data and fsyncs it.
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        match self {
+            Self::Std(writer) => {
+                writer.flush()?;
+                writer.get_ref().sync_all()
+            }
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Ring(ring) => ring.flush_and_sync(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Std(writer) => writer.write(buf),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Ring(ring) => ring.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Std(writer) => writer.flush(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Ring(ring) => ring.flush(),
+        }
+    }
+}
+
+/// Creates or truncates `path`, writes `bytes` and fsyncs them.
+pub fn write_file(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut output = Output::new(File::create(path)?, 0);
+    output.write_all(bytes)?;
+    output.sync()
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring {
+    use io_uring::{opcode, squeue, types, IoUring};
+    use std::fs::File;
+    use std::io::{self, Write};
+    use std::os::fd::AsRawFd;
+
+    const BUFFER_SIZE: usize = 1 << 20;
+    const WRITE: u64 = 1;
+    const FSYNC: u64 = 2;
+
+    pub(super) fn enabled() -> bool {
+        !std::env::var("HS_IO_URING").is_ok_and(|v| v.to_lowercase() == "false")
+    }
+
+    pub(super) fn fall_back(error: &io::Error) {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        WARNED.call_once(|| eprintln!("⚠️ io_uring unavailable ({error}), using std IO"));
+    }
+
+    /// Appends to a file from one registered buffer.
+    pub(crate) struct RingFile {
+        // Dropped first: closing the ring unregisters `buf`.
+        ring: IoUring,
+        buf: Box<[u8]>,
+        len: usize,
+        offset: u64,
+        file: File,
+    }
+
+    impl std::fmt::Debug for RingFile {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("RingFile")
+                .field("offset", &self.offset)
+                .field("buffered", &self.len)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl RingFile {
+        pub(crate) fn new(file: File, offset: u64) -> io::Result<Self> {
+            let ring = IoUring::new(4)?;
+            let mut buf = vec![0u8; BUFFER_SIZE].into_boxed_slice();
+            let iovec = libc::iovec {
+                iov_base: buf.as_mut_ptr().cast(),
+                iov_len: buf.len(),
+            };
+            // SAFETY: `buf` is heap memory owned by the returned value and
+            // outlives the ring, which is dropped first.
+            unsafe { ring.submitter().register_buffers(&[iovec])? };
+            Ok(Self {
+                ring,
+                buf,
+                len: 0,
+                offset,
+                file,
+            })
+        }
+
+        pub(crate) fn flush_and_sync(&mut self) -> io::Result<()> {
+            self.submit(true)
+        }
+
+        /// Writes the buffer out, linked to an fsync when `sync` is set.
+        /// A short write cancels the linked fsync; the rest is then written
+        /// and the fsync retried.
+        fn submit(&mut self, sync: bool) -> io::Result<()> {
+            let fd = types::Fd(self.file.as_raw_fd());
+            let mut start = 0;
+            loop {
+                let pending = self.len - start;
+                let mut submitted = 0;
+                if pending > 0 {
+                    // At most BUFFER_SIZE
+                    let len = u32::try_from(pending).unwrap_or(u32::MAX);
+                    let write = opcode::WriteFixed::new(fd, self.buf[start..].as_ptr(), len, 0)
+                        .offset(self.offset)
+                        .build()
+                        .user_data(WRITE);
+                    let write = if sync {
+                        write.flags(squeue::Flags::IO_LINK)
+                    } else {
+                        write
+                    };
+                    // SAFETY: the buffer is registered and not touched until
+                    // the completion is reaped below.
+                    unsafe { self.ring.submission().push(&write) }
+                        .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+                    submitted += 1;
+                }
+                if sync {
+                    let fsync = opcode::Fsync::new(fd).build().user_data(FSYNC);
+                    // SAFETY: an fsync references no memory.
+                    unsafe { self.ring.submission().push(&fsync) }
+                        .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+                    submitted += 1;
+                }
+                if submitted == 0 {
+                    return Ok(());
+                }
+                self.ring.submit_and_wait(submitted)?;
+
+                let mut synced = !sync;
+                let mut failure = None;
+                for cqe in self.ring.completion() {
+                    let result = cqe.result();
+                    if result < 0 {
+                        // A fsync cancelled by a short write is retried.
+                        if !(cqe.user_data() == FSYNC && result == -libc::ECANCELED) {
+                            failure = Some(io::Error::from_raw_os_error(-result));
+                        }
+                    } else if cqe.user_data() == FSYNC {
+                        synced = true;
+                    } else if result == 0 {
+                        failure = Some(io::ErrorKind::WriteZero.into());
+                    } else {
+                        let written = result.unsigned_abs();
+                        start += written as usize;
+                        self.offset += u64::from(written);
+                    }
+                }
+                if let Some(e) = failure {
+                    self.buf.copy_within(start..self.len, 0);
+                    self.len -= start;
+                    return Err(e);
+                }
+                if start == self.len && synced {
+                    self.len = 0;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    impl Write for RingFile {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            if self.len == self.buf.len() {
+                self.submit(false)?;
+            }
+            let n = data.len().min(self.buf.len() - self.len);
+            self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.submit(false)
+        }
+    }
+
+    impl Drop for RingFile {
+        fn drop(&mut self) {
+            let _ = self.submit(false);
+        }
+    }
+}
@@ -4,9 +4,20 @@
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::missing_panics_doc)]
 
+pub mod io;
 #[cfg(feature = "mmap")]
 pub mod wal;
 
+/// How a stored vector compares with the checksum taken when it was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    Match,
+    Mismatch,
+    /// No checksum recorded: written before checksums existed, past the end
+    /// of the store, or kept in RAM.
+    Missing,
+}
+
 #[cfg(feature = "mmap")]
 mod mmap_impl;
 #[cfg(feature = "mmap")]
@@ -1,5 +1,7 @@
 #![allow(clippy::cast_possible_truncation)]
+use crate::ChecksumStatus;
 use arc_swap::ArcSwap;
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
 use memmap2::{Mmap, MmapMut, MmapOptions};
 use parking_lot::Mutex;
 use std::fs::{File, OpenOptions};
@@ -10,6 +12,9 @@ use std::sync::Arc;
 const CHUNK_SIZE: usize = 65536; // 2^16
 const CHUNK_SHIFT: usize = 16;
 const CHUNK_MASK: usize = 0xFFFF;
+/// Free space a new segment must leave on the volume, so the WAL and
+/// snapshots can still be written after the store grows.
+const DISK_HEADROOM: u64 = 64 << 20;
 
 #[derive(Debug)]
 struct Segment {
@@ -17,10 +22,36 @@ struct Segment {
     write_mmap: Mutex<MmapMut>,
     #[allow(dead_code)]
     file: File,
+    // One CRC32 per slot (`chunk_N.crc`); locked after `write_mmap`
+    checksums: Mutex<MmapMut>,
+    #[allow(dead_code)]
+    checksum_file: File,
+}
+
+impl Segment {
+    /// Writes `bytes` into slot `local_idx` and records its checksum.
+    fn write_slot(&self, local_idx: usize, element_size: usize, bytes: &[u8]) {
+        let mut guard = self.write_mmap.lock();
+        let start = local_idx * element_size;
+        guard[start..start + element_size].copy_from_slice(bytes);
+        self.record_checksum(local_idx, bytes);
+    }
+
+    fn record_checksum(&self, local_idx: usize, bytes: &[u8]) {
+        let mut checksums = self.checksums.lock();
+        let at = local_idx * 4;
+        checksums[at..at + 4].copy_from_slice(&slot_checksum(bytes).to_le_bytes());
+    }
+}
+
+/// CRC32 of a slot; 0 is reserved for slots written before checksums existed.
+fn slot_checksum(bytes: &[u8]) -> u32 {
+    crc32fast::hash(bytes).max(1)
 }
 
 /// Persistent vector storage using memory-mapped files.
-/// Data is split into 64K chunks (`chunk_N.hyp`).
+/// Data is split into 64K chunks (`chunk_N.hyp`), each with a `chunk_N.crc`
+/// sidecar holding a checksum per vector for [`VectorStore::verify`].
 #[derive(Debug)]
 pub struct VectorStore {
     segments: ArcSwap<Vec<Arc<Segment>>>,
@@ -81,6 +112,15 @@ impl VectorStore {
         let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
         let read_mmap = unsafe { MmapOptions::new().map(&file)? };
 
+        let checksum_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.with_extension("crc"))?;
+        checksum_file.set_len((CHUNK_SIZE * 4) as u64)?;
+        let checksums = unsafe { MmapOptions::new().map_mut(&checksum_file)? };
+
         let addr = read_mmap.as_ptr();
         let offset = addr.align_offset(64);
         if offset != 0 {
@@ -98,38 +138,37 @@ impl VectorStore {
             read_mmap,
             write_mmap: Mutex::new(mmap),
             file,
+            checksums: Mutex::new(checksums),
+            checksum_file,
         })
     }
 
     /// Appends a vector to the end of the store. Returns the new ID.
-    pub fn append(&self, vector_bytes: &[u8]) -> Result<u32, String> {
+    pub fn append(&self, vector_bytes: &[u8]) -> HyperspaceResult<u32> {
         if vector_bytes.len() != self.element_size {
-            return Err(format!(
+            return Err(HyperspaceError::Validation(format!(
                 "Vector size mismatch: {} vs {}",
                 vector_bytes.len(),
                 self.element_size
-            ));
+            )));
         }
 
         let id = self.count.fetch_add(1, Ordering::SeqCst);
         let segment_idx = id >> CHUNK_SHIFT;
         let local_idx = id & CHUNK_MASK;
 
-        self.ensure_segment(segment_idx)?;
-
-        {
-            let segs = self.segments.load();
-            let segment = &segs[segment_idx];
-            let start = local_idx * self.element_size;
-
-            let mut guard = segment.write_mmap.lock();
-            let ptr = unsafe { guard.as_mut_ptr().add(start) };
-
-            unsafe {
-                std::ptr::copy_nonoverlapping(vector_bytes.as_ptr(), ptr, self.element_size);
-            }
+        if let Err(e) = self.ensure_segment(segment_idx) {
+            // Give the slot back so the next append retries it. Callers
+            // serialize appends, so nobody has taken a later id meanwhile.
+            let _ = self
+                .count
+                .compare_exchange(id + 1, id, Ordering::SeqCst, Ordering::SeqCst);
+            return Err(e);
         }
 
+        let segs = self.segments.load();
+        segs[segment_idx].write_slot(local_idx, self.element_size, vector_bytes);
+
         Ok(id as u32)
     }
 
@@ -160,13 +199,13 @@ impl VectorStore {
     }
 
     /// Updates an existing vector in place.
-    pub fn update(&self, id: u32, vector_bytes: &[u8]) -> Result<(), String> {
+    pub fn update(&self, id: u32, vector_bytes: &[u8]) -> HyperspaceResult<()> {
         if vector_bytes.len() != self.element_size {
-            return Err(format!(
+            return Err(HyperspaceError::Validation(format!(
                 "Vector size mismatch: {} vs {}",
                 vector_bytes.len(),
                 self.element_size
-            ));
+            )));
         }
 
         let id_val = id as usize;
@@ -175,20 +214,57 @@ impl VectorStore {
 
         let segs = self.segments.load();
         if segment_idx >= segs.len() {
-            return Err(format!("VectorStore: ID {id} out of bounds"));
+            return Err(HyperspaceError::NotFound(format!(
+                "VectorStore: ID {id} out of bounds"
+            )));
         }
-        let segment = &segs[segment_idx];
-
-        let start = local_idx * self.element_size;
+        segs[segment_idx].write_slot(local_idx, self.element_size, vector_bytes);
 
-        let mut guard = segment.write_mmap.lock();
-        let ptr = unsafe { guard.as_mut_ptr().add(start) };
+        Ok(())
+    }
 
-        unsafe {
-            std::ptr::copy_nonoverlapping(vector_bytes.as_ptr(), ptr, self.element_size);
+    /// Checks slot `id` against the checksum recorded when it was written.
+    /// Holds the segment's write lock, so a concurrent update can't be
+    /// mistaken for corruption.
+    pub fn verify(&self, id: u32) -> ChecksumStatus {
+        let id_val = id as usize;
+        if id_val >= self.count() {
+            return ChecksumStatus::Missing;
         }
+        let segs = self.segments.load();
+        let Some(segment) = segs.get(id_val >> CHUNK_SHIFT) else {
+            return ChecksumStatus::Missing;
+        };
+        let local_idx = id_val & CHUNK_MASK;
+        let guard = segment.write_mmap.lock();
+        let start = local_idx * self.element_size;
+        let actual = slot_checksum(&guard[start..start + self.element_size]);
+        let checksums = segment.checksums.lock();
+        let at = local_idx * 4;
+        let mut stored = [0u8; 4];
+        stored.copy_from_slice(&checksums[at..at + 4]);
+        match u32::from_le_bytes(stored) {
+            0 => ChecksumStatus::Missing,
+            stored if stored == actual => ChecksumStatus::Match,
+            _ => ChecksumStatus::Mismatch,
+        }
+    }
 
-        Ok(())
+    /// Records a checksum for slot `id` as it is now, for slots written
+    /// before checksums existed.
+    pub fn seal(&self, id: u32) {
+        let id_val = id as usize;
+        if id_val >= self.count() {
+            return;
+        }
+        let segs = self.segments.load();
+        let Some(segment) = segs.get(id_val >> CHUNK_SHIFT) else {
+            return;
+        };
+        let local_idx = id_val & CHUNK_MASK;
+        let guard = segment.write_mmap.lock();
+        let start = local_idx * self.element_size;
+        segment.record_checksum(local_idx, &guard[start..start + self.element_size]);
     }
 
     pub fn segment_count(&self) -> usize {
@@ -204,10 +280,25 @@ impl VectorStore {
         segs.len() * segment_capacity
     }
 
+    /// Bytes of the segments holding `count` vectors of `element_size` bytes.
+    pub fn segment_bytes_for(count: usize, element_size: usize) -> u64 {
+        (count.div_ceil(CHUNK_SIZE).max(1) * CHUNK_SIZE * element_size) as u64
+    }
+
+    /// Disk taken by `count` vectors: their segments plus checksum files.
+    pub fn disk_bytes_for(count: usize, element_size: usize) -> u64 {
+        Self::segment_bytes_for(count, element_size + 4)
+    }
+
     pub fn count(&self) -> usize {
         self.count.load(Ordering::Relaxed)
     }
 
+    /// Bytes per stored vector.
+    pub fn element_size(&self) -> usize {
+        self.element_size
+    }
+
     pub fn set_count(&self, c: usize) {
         self.count.store(c, Ordering::Relaxed);
     }
@@ -269,6 +360,12 @@ impl VectorStore {
                 let ptr = mmap_guard.as_mut_ptr();
                 std::ptr::copy_nonoverlapping(data[offset..].as_ptr(), ptr, to_copy);
             }
+            for (local_idx, bytes) in data[offset..offset + to_copy]
+                .chunks_exact(element_size)
+                .enumerate()
+            {
+                segment.record_checksum(local_idx, bytes);
+            }
 
             offset += to_copy;
             segment_idx += 1;
@@ -277,7 +374,21 @@ impl VectorStore {
         store
     }
 
-    fn ensure_segment(&self, segment_idx: usize) -> Result<(), String> {
+    /// Segments are sparse files, so running out of disk would only show up
+    /// as a fault when the mapped pages are first written. Refuse to grow
+    /// unless the volume can hold the whole segment plus `DISK_HEADROOM`.
+    fn check_free_space(&self) -> HyperspaceResult<()> {
+        let needed = ((self.element_size + 4) * CHUNK_SIZE) as u64 + DISK_HEADROOM;
+        match available_space(&self.base_path) {
+            Some(free) if free < needed => Err(HyperspaceError::Capacity(format!(
+                "Not enough disk space to grow storage in {}: {free} bytes free, {needed} needed",
+                self.base_path.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_segment(&self, segment_idx: usize) -> HyperspaceResult<()> {
         if segment_idx < self.segments.load().len() {
             return Ok(());
         }
@@ -293,8 +404,14 @@ impl VectorStore {
         while segment_idx >= next.len() {
             let new_chunk_id = next.len();
             let path = self.base_path.join(format!("chunk_{new_chunk_id}.hyp"));
-            let seg = Self::create_segment(&path, self.element_size)
-                .map_err(|e| format!("Failed to grow storage: {e}"))?;
+            self.check_free_space()?;
+            let seg = Self::create_segment(&path, self.element_size).map_err(|e| {
+                if e.kind() == std::io::ErrorKind::StorageFull {
+                    HyperspaceError::Capacity(format!("Failed to grow storage: {e}"))
+                } else {
+                    HyperspaceError::Io(e)
+                }
+            })?;
             next.push(Arc::new(seg));
         }
 
@@ -302,3 +419,22 @@ impl VectorStore {
         Ok(())
     }
 }
+
+/// Bytes available to unprivileged writers on the volume holding `path`.
+#[cfg(unix)]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is NUL-terminated and `stat` is a valid out-pointer.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &raw mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
@@ -1,3 +1,5 @@
+use crate::ChecksumStatus;
+use hyperspace_core::{HyperspaceError, HyperspaceResult};
 use parking_lot::RwLock;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -27,9 +29,9 @@ impl VectorStore {
         }
     }
 
-    pub fn append(&self, vector_bytes: &[u8]) -> Result<u32, String> {
+    pub fn append(&self, vector_bytes: &[u8]) -> HyperspaceResult<u32> {
         if vector_bytes.len() != self.element_size {
-            return Err("Vector size mismatch".into());
+            return Err(HyperspaceError::Validation("Vector size mismatch".into()));
         }
 
         let id = self.count.fetch_add(1, Ordering::SeqCst);
@@ -88,9 +90,9 @@ impl VectorStore {
         }
     }
 
-    pub fn update(&self, id: u32, vector_bytes: &[u8]) -> Result<(), String> {
+    pub fn update(&self, id: u32, vector_bytes: &[u8]) -> HyperspaceResult<()> {
         if vector_bytes.len() != self.element_size {
-            return Err("Size mismatch".into());
+            return Err(HyperspaceError::Validation("Size mismatch".into()));
         }
         let id_val = id as usize;
         let segment_idx = id_val / CHUNK_SIZE;
@@ -98,7 +100,7 @@ impl VectorStore {
 
         let segs = self.segments.read();
         if segment_idx >= segs.len() {
-            return Err("OOB".into());
+            return Err(HyperspaceError::NotFound(format!("ID {id} out of bounds")));
         }
 
         let segment = &segs[segment_idx];
@@ -108,6 +110,13 @@ impl VectorStore {
         Ok(())
     }
 
+    /// RAM segments keep no checksums; there is no disk to rot.
+    pub fn verify(&self, _id: u32) -> ChecksumStatus {
+        ChecksumStatus::Missing
+    }
+
+    pub fn seal(&self, _id: u32) {}
+
     pub fn segment_count(&self) -> usize {
         self.segments.read().len()
     }
@@ -116,10 +125,25 @@ impl VectorStore {
         self.segments.read().len() * CHUNK_SIZE * self.element_size
     }
 
+    /// Bytes of the segments holding `count` vectors of `element_size` bytes.
+    pub fn segment_bytes_for(count: usize, element_size: usize) -> u64 {
+        (count.div_ceil(CHUNK_SIZE).max(1) * CHUNK_SIZE * element_size) as u64
+    }
+
+    /// Nothing is written to disk.
+    pub fn disk_bytes_for(_count: usize, _element_size: usize) -> u64 {
+        0
+    }
+
     pub fn count(&self) -> usize {
         self.count.load(Ordering::Relaxed)
     }
 
+    /// Bytes per stored vector.
+    pub fn element_size(&self) -> usize {
+        self.element_size
+    }
+
     pub fn set_count(&self, c: usize) {
         self.count.store(c, Ordering::Relaxed);
     }
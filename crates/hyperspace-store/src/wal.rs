@@ -1,10 +1,12 @@
 #![allow(clippy::cast_possible_truncation)]
+use crate::io::Output;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use crc32fast::Hasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
-use std::io::{self, BufReader, BufWriter, Cursor, Read, Write};
+use std::io::{self, BufReader, Cursor, Read, Write};
 use std::path::Path;
+use std::sync::Arc;
 
 const WAL_V3_MAGIC: u8 = 0xFF;
 
@@ -19,11 +21,39 @@ pub enum WalSyncMode {
     Batch,
 }
 
+impl WalSyncMode {
+    /// Parses `strict` (alias `fsync`), `batch` or `async`, case-insensitively.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "strict" | "fsync" => Some(Self::Strict),
+            "batch" => Some(Self::Batch),
+            "async" => Some(Self::Async),
+            _ => None,
+        }
+    }
+
+    /// Process-wide default from `HYPERSPACE_WAL_SYNC_MODE` (default `async`).
+    pub fn from_env() -> Self {
+        std::env::var("HYPERSPACE_WAL_SYNC_MODE")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Self::Async)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Strict => "strict",
+            Self::Async => "async",
+            Self::Batch => "batch",
+        }
+    }
+}
+
 /// Write-Ahead Log implementation for durability.
 /// Appends operations to a log file with CRC32 checksums.
 #[derive(Debug)]
 pub struct Wal {
-    file: BufWriter<File>,
+    file: Output,
     mode: WalSyncMode,
     path: std::path::PathBuf,
     current_size: u64,
@@ -33,6 +63,11 @@ pub struct Wal {
     last_fsync_time: std::time::Instant,
     /// Batch mode fsync interval in milliseconds
     batch_fsync_interval_ms: u64,
+    /// Strict mode leaves the fsync to the caller (group commit).
+    group_commit: bool,
+    /// Second handle to the active segment so fsyncs can run outside the
+    /// writer's lock.
+    sync_file: Arc<File>,
 }
 
 /// Represents an operation stored in the WAL.
@@ -44,6 +79,22 @@ pub enum WalEntry {
         metadata: HashMap<String, String>,
         logical_clock: u64,
     },
+    /// Tombstone for a user ID; replay removes it from the ID map and marks
+    /// the node deleted so it does not resurrect without a fresh snapshot.
+    Delete { id: u32, logical_clock: u64 },
+}
+
+/// One operation of a mixed batch written with [`Wal::append_mixed`].
+#[derive(Debug, Clone, Copy)]
+pub enum WalOp<'a> {
+    Insert {
+        id: u32,
+        vector: &'a [f64],
+        metadata: &'a HashMap<String, String>,
+    },
+    Delete {
+        id: u32,
+    },
 }
 
 impl Wal {
@@ -58,8 +109,9 @@ impl Wal {
             .ok()
             .and_then(|v| v.parse::<u64>().ok())
             .unwrap_or(100);
+        let sync_file = Arc::new(file.try_clone()?);
         Ok(Self {
-            file: BufWriter::new(file),
+            file: Output::new(file, current_size),
             mode,
             path: path.to_owned(),
             current_size,
@@ -67,9 +119,43 @@ impl Wal {
             pending_entries: 0,
             last_fsync_time: std::time::Instant::now(),
             batch_fsync_interval_ms,
+            group_commit: false,
+            sync_file,
         })
     }
 
+    pub fn mode(&self) -> WalSyncMode {
+        self.mode
+    }
+
+    /// Switches the sync mode for subsequent writes. Moving to `Strict`
+    /// fsyncs immediately so earlier writes get the same guarantee.
+    pub fn set_mode(&mut self, mode: WalSyncMode) -> io::Result<()> {
+        if mode == WalSyncMode::Strict && self.mode != WalSyncMode::Strict {
+            self.sync()?;
+        }
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// With group commit on, `Strict` appends only flush to the OS; the
+    /// caller fsyncs [`Wal::sync_file`] after releasing its lock so one fsync
+    /// can cover many concurrent writers.
+    pub fn set_group_commit(&mut self, enabled: bool) {
+        self.group_commit = enabled;
+    }
+
+    /// Whether appends leave a required fsync to the caller.
+    pub fn defers_sync(&self) -> bool {
+        self.group_commit && self.mode == WalSyncMode::Strict
+    }
+
+    /// Handle to the active segment for out-of-lock fsyncs. Segments are
+    /// fsynced on rotation, so a handle to a rotated one stays correct.
+    pub fn sync_file(&self) -> Arc<File> {
+        self.sync_file.clone()
+    }
+
     pub fn set_size_limit(&mut self, limit_bytes: u64) {
         self.size_limit = limit_bytes;
     }
@@ -87,17 +173,22 @@ impl Wal {
     }
 
     pub fn rotate(&mut self) -> io::Result<std::path::PathBuf> {
-        self.file.flush()?;
-        self.file.get_ref().sync_all()?;
+        self.file.sync()?;
 
         // Rename the old file to a frozen state based on current time
-        let frozen_path = self.path.with_extension(format!(
-            "frozen.{}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis()
-        ));
+        let rotated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let frozen_path = self.path.with_extension(format!("frozen.{rotated_at}"));
+        // Recorded first: a crash in between leaves a rotation without its
+        // segment, which readers take as lost rather than misplacing bytes.
+        let mut history = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::history_path(&self.path))?;
+        history.write_u64::<LittleEndian>(rotated_at)?;
+        history.sync_all()?;
         std::fs::rename(&self.path, &frozen_path)?;
 
         let file = OpenOptions::new()
@@ -105,13 +196,37 @@ impl Wal {
             .truncate(true)
             .write(true)
             .open(&self.path)?;
-        self.file = BufWriter::new(file);
+        self.sync_file = Arc::new(file.try_clone()?);
+        self.file = Output::new(file, 0);
         self.current_size = 0;
         self.pending_entries = 0;
         self.last_fsync_time = std::time::Instant::now(); // Reset fsync timer for new WAL
         Ok(frozen_path)
     }
 
+    fn history_path(path: &Path) -> std::path::PathBuf {
+        path.with_extension("rotations")
+    }
+
+    /// Rotation times of the log at `path`, oldest first: the `frozen.<ms>`
+    /// names of its sealed segments, including segments removed since. The
+    /// segment sealed at a rotation is the one that began at the previous
+    /// rotation. Empty for a log never rotated by a version recording them.
+    pub fn rotations(path: &Path) -> io::Result<Vec<u64>> {
+        let data = match std::fs::read(Self::history_path(path)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        // A torn last entry is ignored
+        Ok(data
+            .as_chunks::<8>()
+            .0
+            .iter()
+            .map(|ts| u64::from_le_bytes(*ts))
+            .collect())
+    }
+
     fn serialize_entry(
         id: u32,
         vector: &[f64],
@@ -144,6 +259,17 @@ impl Wal {
         Ok(buf)
     }
 
+    /// Size on disk of one insert record: header, a `dimension`-long vector
+    /// and `metadata_keys` entries whose keys and values total
+    /// `metadata_bytes`.
+    pub fn insert_record_bytes(
+        dimension: usize,
+        metadata_keys: usize,
+        metadata_bytes: usize,
+    ) -> u64 {
+        (9 + 1 + 4 + 8 + 4 + 8 * dimension + 4 + 8 * metadata_keys + metadata_bytes) as u64
+    }
+
     fn write_packet_internal(&mut self, payload: &[u8]) -> io::Result<()> {
         let len = payload.len() as u32;
         let mut hasher = Hasher::new();
@@ -174,13 +300,61 @@ impl Wal {
     ) -> io::Result<()> {
         let payload = Self::serialize_entry(id, vector, metadata, logical_clock)?;
         self.write_packet_internal(&payload)?;
-        self.file.flush()?;
+        self.commit()
+    }
+
+    fn serialize_delete(id: u32, logical_clock: u64) -> io::Result<Vec<u8>> {
+        let mut payload = Vec::with_capacity(13);
+        // Internal Format: OpCode 4 (Delete with clock)
+        payload.write_u8(4)?;
+        payload.write_u32::<LittleEndian>(id)?;
+        payload.write_u64::<LittleEndian>(logical_clock)?;
+        Ok(payload)
+    }
+
+    /// Appends a delete tombstone for user ID `id`.
+    pub fn append_delete(&mut self, id: u32, logical_clock: u64) -> io::Result<()> {
+        let payload = Self::serialize_delete(id, logical_clock)?;
+        self.write_packet_internal(&payload)?;
+        self.commit()
+    }
+
+    /// Appends deletes and inserts as one record under one checksum, so
+    /// replay applies either all of them, in order, or none.
+    pub fn append_mixed(&mut self, ops: &[WalOp<'_>], logical_clock: u64) -> io::Result<()> {
+        let mut payload = Vec::new();
+        // Internal Format: OpCode 5 (Mixed batch), then length-prefixed
+        // OpCode 3/4 bodies
+        payload.write_u8(5)?;
+        payload.write_u32::<LittleEndian>(ops.len() as u32)?;
+        for op in ops {
+            let body = match *op {
+                WalOp::Insert {
+                    id,
+                    vector,
+                    metadata,
+                } => Self::serialize_entry(id, vector, metadata, logical_clock)?,
+                WalOp::Delete { id } => Self::serialize_delete(id, logical_clock)?,
+            };
+            payload.write_u32::<LittleEndian>(body.len() as u32)?;
+            payload.write_all(&body)?;
+        }
+        self.write_packet_internal(&payload)?;
+        self.commit()
+    }
 
+    /// Flushes buffered packets and applies the sync mode. Writes that need
+    /// an fsync go out together with it (one submission with `io_uring`).
+    fn commit(&mut self) -> io::Result<()> {
         // P0: Async fsync for Batch mode - only fsync if interval elapsed
         match self.mode {
+            WalSyncMode::Strict if self.group_commit => {
+                // Group commit: the caller fsyncs via sync_file()
+                self.file.flush()?;
+            }
             WalSyncMode::Strict => {
                 // Strict: fsync on every write (safest, slowest)
-                self.file.get_ref().sync_all()?;
+                self.file.sync()?;
                 self.last_fsync_time = std::time::Instant::now();
             }
             WalSyncMode::Batch => {
@@ -188,13 +362,16 @@ impl Wal {
                 if self.last_fsync_time.elapsed().as_millis()
                     >= u128::from(self.batch_fsync_interval_ms)
                 {
-                    self.file.get_ref().sync_all()?;
+                    self.file.sync()?;
                     self.last_fsync_time = std::time::Instant::now();
+                } else {
+                    self.file.flush()?;
                 }
             }
             WalSyncMode::Async => {
                 // Async: rely on OS cache flush (fastest, least durable)
                 // No explicit fsync
+                self.file.flush()?;
             }
         }
         Ok(())
@@ -209,33 +386,12 @@ impl Wal {
             let payload = Self::serialize_entry(*id, vector, metadata, logical_clock)?;
             self.write_packet_internal(&payload)?;
         }
-        self.file.flush()?;
-
-        // P0: Async fsync for Batch mode - only fsync if interval elapsed
-        match self.mode {
-            WalSyncMode::Strict => {
-                self.file.get_ref().sync_all()?;
-                self.last_fsync_time = std::time::Instant::now();
-            }
-            WalSyncMode::Batch => {
-                if self.last_fsync_time.elapsed().as_millis()
-                    >= u128::from(self.batch_fsync_interval_ms)
-                {
-                    self.file.get_ref().sync_all()?;
-                    self.last_fsync_time = std::time::Instant::now();
-                }
-            }
-            WalSyncMode::Async => {
-                // No explicit fsync
-            }
-        }
-        Ok(())
+        self.commit()
     }
 
     /// Force sync all changes to disk immediately.
     pub fn sync(&mut self) -> io::Result<()> {
-        self.file.flush()?;
-        self.file.get_ref().sync_all()?;
+        self.file.sync()?;
         self.last_fsync_time = std::time::Instant::now();
         Ok(())
     }
@@ -285,9 +441,21 @@ impl Wal {
 
                 // Parse Payload
                 let mut cursor = Cursor::new(payload);
-                match Self::parse_entry(&mut cursor) {
-                    Ok(entry) => callback(entry),
-                    Err(e) => eprintln!("⚠️ Failed to parse WAL entry body: {e}"),
+                if cursor.get_ref().first() == Some(&5) {
+                    // A mixed batch is delivered only once all of it parsed.
+                    match Self::parse_mixed(&mut cursor) {
+                        Ok(entries) => {
+                            for entry in entries {
+                                callback(entry);
+                            }
+                        }
+                        Err(e) => eprintln!("⚠️ Failed to parse WAL batch body: {e}"),
+                    }
+                } else {
+                    match Self::parse_entry(&mut cursor) {
+                        Ok(entry) => callback(entry),
+                        Err(e) => eprintln!("⚠️ Failed to parse WAL entry body: {e}"),
+                    }
                 }
 
                 // Update valid position (Magic(1) + Len(4) + CRC(4) + Payload(len))
@@ -319,6 +487,28 @@ impl Wal {
         Ok(())
     }
 
+    fn parse_mixed(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Vec<WalEntry>> {
+        cursor.read_u8()?;
+        let count = cursor.read_u32::<LittleEndian>()?;
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let len = cursor.read_u32::<LittleEndian>()?;
+            let mut body = vec![0u8; len as usize];
+            cursor.read_exact(&mut body)?;
+            let entry = match body.first() {
+                Some(3 | 4) => Self::parse_entry(&mut Cursor::new(body))?,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Unknown OpCode in batch",
+                    ))
+                }
+            };
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
     fn parse_entry(cursor: &mut Cursor<Vec<u8>>) -> io::Result<WalEntry> {
         let opcode = cursor.read_u8()?;
         match opcode {
@@ -353,6 +543,11 @@ impl Wal {
                     logical_clock,
                 })
             }
+            4 => {
+                let id = cursor.read_u32::<LittleEndian>()?;
+                let logical_clock = cursor.read_u64::<LittleEndian>()?;
+                Ok(WalEntry::Delete { id, logical_clock })
+            }
             2 => {
                 let id = cursor.read_u32::<LittleEndian>()?;
                 let vec_len = cursor.read_u32::<LittleEndian>()?;
@@ -466,6 +661,145 @@ impl Wal {
         }
     }
 
+    /// Rewrites the closed segment at `path` without the inserts of user IDs
+    /// in `ids`, keeping every other record (tombstones included) in order.
+    /// Returns how many inserts were dropped; the file is only replaced when
+    /// that is more than zero. Must not be called on the active segment.
+    pub fn redact(path: &Path, ids: &HashSet<u32>) -> io::Result<u64> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut out = Vec::new();
+        let mut dropped = 0u64;
+        let redacted = |body: &[u8]| {
+            matches!(body.first(), Some(2 | 3))
+                && body.len() >= 5
+                && ids.contains(&u32::from_le_bytes([body[1], body[2], body[3], body[4]]))
+        };
+
+        loop {
+            let magic = match reader.read_u8() {
+                Ok(b) => b,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+            let payload = if magic == WAL_V3_MAGIC {
+                let (Ok(len), Ok(stored_crc)) = (
+                    reader.read_u32::<LittleEndian>(),
+                    reader.read_u32::<LittleEndian>(),
+                ) else {
+                    break;
+                };
+                let mut payload = vec![0u8; len as usize];
+                if reader.read_exact(&mut payload).is_err()
+                    || crc32fast::hash(&payload) != stored_crc
+                {
+                    break; // Torn tail; replay would drop it anyway
+                }
+                payload
+            } else {
+                // Legacy records are carried over as V3 inserts.
+                let Ok((
+                    WalEntry::Insert {
+                        id,
+                        vector,
+                        metadata,
+                        ..
+                    },
+                    _,
+                )) = Self::parse_legacy_entry(magic, &mut reader)
+                else {
+                    break;
+                };
+                Self::serialize_entry(id, &vector, &metadata, 0)?
+            };
+
+            let payload = if payload.first() == Some(&5) {
+                let mut cursor = Cursor::new(&payload[1..]);
+                let count = cursor.read_u32::<LittleEndian>()?;
+                let mut bodies = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let len = cursor.read_u32::<LittleEndian>()?;
+                    let mut body = vec![0u8; len as usize];
+                    cursor.read_exact(&mut body)?;
+                    if redacted(&body) {
+                        dropped += 1;
+                    } else {
+                        bodies.push(body);
+                    }
+                }
+                if bodies.is_empty() {
+                    continue;
+                }
+                let mut batch = vec![5u8];
+                batch.write_u32::<LittleEndian>(bodies.len() as u32)?;
+                for body in bodies {
+                    batch.write_u32::<LittleEndian>(body.len() as u32)?;
+                    batch.write_all(&body)?;
+                }
+                batch
+            } else if redacted(&payload) {
+                dropped += 1;
+                continue;
+            } else {
+                payload
+            };
+
+            out.write_u8(WAL_V3_MAGIC)?;
+            out.write_u32::<LittleEndian>(payload.len() as u32)?;
+            out.write_u32::<LittleEndian>(crc32fast::hash(&payload))?;
+            out.write_all(&payload)?;
+        }
+
+        if dropped > 0 {
+            let mut tmp = path.as_os_str().to_owned();
+            tmp.push(".redact");
+            let tmp = std::path::PathBuf::from(tmp);
+            crate::io::write_file(&tmp, &out)?;
+            std::fs::rename(&tmp, path)?;
+        }
+        Ok(dropped)
+    }
+
+    /// Decodes the whole V3 records at the start of `data`, e.g. a range of a
+    /// segment shipped to a standby. Stops before a record that is cut off
+    /// and returns the entries with the number of bytes they span. A checksum
+    /// mismatch or a legacy record is an error, since a shipped range can't
+    /// be healed by truncating it.
+    pub fn decode_records(data: &[u8]) -> io::Result<(Vec<WalEntry>, usize)> {
+        let mut entries = Vec::new();
+        let mut pos = 0usize;
+        while data.len() - pos >= 9 {
+            if data[pos] != WAL_V3_MAGIC {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("No V3 record at offset {pos}"),
+                ));
+            }
+            let mut header = Cursor::new(&data[pos + 1..pos + 9]);
+            let len = header.read_u32::<LittleEndian>()? as usize;
+            let stored_crc = header.read_u32::<LittleEndian>()?;
+            let Some(payload) = data.get(pos + 9..pos + 9 + len) else {
+                break;
+            };
+            if crc32fast::hash(payload) != stored_crc {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("CRC mismatch in record at offset {pos}"),
+                ));
+            }
+            let mut cursor = Cursor::new(payload.to_vec());
+            if payload.first() == Some(&5) {
+                entries.extend(Self::parse_mixed(&mut cursor)?);
+            } else {
+                entries.push(Self::parse_entry(&mut cursor)?);
+            }
+            pos += 9 + len;
+        }
+        Ok((entries, pos))
+    }
+
     /// Quickly counts entries in a frozen WAL file without full deserialization.
     pub fn pending_entries_at_path(path: &Path) -> u64 {
         let mut count = 0;
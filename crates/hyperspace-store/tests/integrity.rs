@@ -1,4 +1,4 @@
-use hyperspace_store::wal::{Wal, WalSyncMode};
+use hyperspace_store::wal::{Wal, WalEntry, WalOp, WalSyncMode};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 
@@ -140,3 +140,217 @@ fn test_wal_rotation_crash_recovery() {
         "Should recover exactly 5 records from active WAL"
     );
 }
+
+#[test]
+fn test_wal_delete_replays_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal_delete.log");
+
+    {
+        let mut wal = Wal::new(&path, WalSyncMode::Async).unwrap();
+        wal.append(1, &[0.1f64; 4], &HashMap::new(), 1).unwrap();
+        wal.append_delete(1, 2).unwrap();
+        wal.append(1, &[0.2f64; 4], &HashMap::new(), 3).unwrap();
+    }
+
+    let mut ops = Vec::new();
+    Wal::replay(&path, |entry| {
+        ops.push(match entry {
+            WalEntry::Insert {
+                id, logical_clock, ..
+            } => ("insert", id, logical_clock),
+            WalEntry::Delete { id, logical_clock } => ("delete", id, logical_clock),
+        });
+    })
+    .unwrap();
+
+    assert_eq!(
+        ops,
+        vec![("insert", 1, 1), ("delete", 1, 2), ("insert", 1, 3)]
+    );
+    assert_eq!(Wal::pending_entries_at_path(&path), 3);
+}
+
+#[test]
+fn test_wal_mixed_batch_is_all_or_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal_mixed.log");
+    let meta = HashMap::from([("doc".to_string(), "a".to_string())]);
+
+    {
+        let mut wal = Wal::new(&path, WalSyncMode::Async).unwrap();
+        wal.append(1, &[0.1f64; 4], &meta, 1).unwrap();
+        wal.append_mixed(
+            &[
+                WalOp::Delete { id: 1 },
+                WalOp::Insert {
+                    id: 2,
+                    vector: &[0.2f64; 4],
+                    metadata: &meta,
+                },
+                WalOp::Insert {
+                    id: 3,
+                    vector: &[0.3f64; 4],
+                    metadata: &meta,
+                },
+            ],
+            2,
+        )
+        .unwrap();
+    }
+
+    let replayed = |path: &std::path::Path| {
+        let mut ops = Vec::new();
+        Wal::replay(path, |entry| {
+            ops.push(match entry {
+                WalEntry::Insert {
+                    id,
+                    logical_clock,
+                    metadata,
+                    ..
+                } => {
+                    assert_eq!(metadata["doc"], "a");
+                    ("insert", id, logical_clock)
+                }
+                WalEntry::Delete { id, logical_clock } => ("delete", id, logical_clock),
+            });
+        })
+        .unwrap();
+        ops
+    };
+
+    assert_eq!(
+        replayed(&path),
+        vec![
+            ("insert", 1, 1),
+            ("delete", 1, 2),
+            ("insert", 2, 2),
+            ("insert", 3, 2)
+        ]
+    );
+
+    // A torn batch loses all of its operations, never only the tail.
+    let full_len = fs::metadata(&path).unwrap().len();
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(full_len - 10).unwrap();
+    drop(file);
+    assert_eq!(replayed(&path), vec![("insert", 1, 1)]);
+}
+
+#[test]
+fn test_wal_redact_drops_inserts_of_purged_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal_redact.log");
+    let meta = HashMap::from([("email".to_string(), "a@example.com".to_string())]);
+
+    {
+        let mut wal = Wal::new(&path, WalSyncMode::Async).unwrap();
+        wal.append(1, &[0.1f64; 4], &meta, 1).unwrap();
+        wal.append(2, &[0.2f64; 4], &meta, 2).unwrap();
+        wal.append_mixed(
+            &[
+                WalOp::Delete { id: 3 },
+                WalOp::Insert {
+                    id: 1,
+                    vector: &[0.3f64; 4],
+                    metadata: &meta,
+                },
+                WalOp::Insert {
+                    id: 4,
+                    vector: &[0.4f64; 4],
+                    metadata: &meta,
+                },
+            ],
+            3,
+        )
+        .unwrap();
+        wal.append_mixed(
+            &[WalOp::Insert {
+                id: 1,
+                vector: &[0.5f64; 4],
+                metadata: &meta,
+            }],
+            4,
+        )
+        .unwrap();
+        wal.append_delete(1, 5).unwrap();
+    }
+
+    let ids = std::collections::HashSet::from([1]);
+    assert_eq!(Wal::redact(&path, &ids).unwrap(), 3);
+    let mut ops = Vec::new();
+    Wal::replay(&path, |entry| {
+        ops.push(match entry {
+            WalEntry::Insert { id, .. } => ("insert", id),
+            WalEntry::Delete { id, .. } => ("delete", id),
+        });
+    })
+    .unwrap();
+    assert_eq!(
+        ops,
+        vec![("insert", 2), ("delete", 3), ("insert", 4), ("delete", 1)]
+    );
+    // Nothing left to drop: the file is not rewritten.
+    assert_eq!(Wal::redact(&path, &ids).unwrap(), 0);
+}
+
+#[test]
+fn test_failed_growth_does_not_consume_ids() {
+    use hyperspace_store::VectorStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = VectorStore::new(dir.path(), 4);
+    for i in 0..65_536u32 {
+        assert_eq!(store.append(&i.to_le_bytes()).unwrap(), i);
+    }
+
+    // A directory where the next chunk file goes makes growth fail.
+    let blocker = dir.path().join("chunk_1.hyp");
+    fs::create_dir(&blocker).unwrap();
+    assert!(store.append(&[1; 4]).is_err());
+    assert!(store.append(&[1; 4]).is_err());
+    assert_eq!(store.count(), 65_536);
+
+    fs::remove_dir(&blocker).unwrap();
+    assert_eq!(store.append(&[7; 4]).unwrap(), 65_536);
+    assert_eq!(store.get(65_536), &[7; 4]);
+    assert_eq!(store.count(), 65_537);
+}
+
+#[test]
+fn test_checksums_catch_bytes_changed_on_disk() {
+    use hyperspace_store::{ChecksumStatus, VectorStore};
+    use std::io::{Seek, SeekFrom, Write};
+
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let store = VectorStore::new(dir.path(), 8);
+        for i in 0..4u64 {
+            store.append(&i.to_le_bytes()).unwrap();
+        }
+        store.update(3, &[9; 8]).unwrap();
+        for id in 0..4 {
+            assert_eq!(store.verify(id), ChecksumStatus::Match);
+        }
+        assert_eq!(store.verify(4), ChecksumStatus::Missing);
+
+        // Flip a byte of vector 2 behind the store's back.
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(dir.path().join("chunk_0.hyp"))
+            .unwrap();
+        file.seek(SeekFrom::Start(2 * 8)).unwrap();
+        file.write_all(&[0xFF]).unwrap();
+        file.sync_all().unwrap();
+        assert_eq!(store.verify(2), ChecksumStatus::Mismatch);
+        assert_eq!(store.verify(1), ChecksumStatus::Match);
+    }
+
+    // A store from before checksums has no sidecar; sealing backfills it.
+    fs::remove_file(dir.path().join("chunk_0.crc")).unwrap();
+    let store = VectorStore::new(dir.path(), 8);
+    store.set_count(4);
+    assert_eq!(store.verify(0), ChecksumStatus::Missing);
+    store.seal(0);
+    assert_eq!(store.verify(0), ChecksumStatus::Match);
+}
@@ -0,0 +1,53 @@
+use hyperspace_store::io::write_file;
+use hyperspace_store::wal::{Wal, WalEntry, WalSyncMode};
+use std::collections::HashMap;
+
+#[test]
+fn test_write_file_replaces_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.snap");
+    std::fs::write(&path, vec![7u8; 10_000]).unwrap();
+
+    // Larger than one io_uring buffer, so it goes out in several writes.
+    let bytes: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+    write_file(&path, &bytes).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+    write_file(&path, b"short").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"short");
+}
+
+#[test]
+fn test_strict_wal_survives_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal.log");
+    let meta = HashMap::from([("k".to_string(), "v".to_string())]);
+
+    let mut wal = Wal::new(&path, WalSyncMode::Strict).unwrap();
+    wal.append(1, &[0.1, 0.2], &meta, 1).unwrap();
+    let frozen = wal.rotate().unwrap();
+    wal.append(2, &[0.3, 0.4], &meta, 2).unwrap();
+    wal.append_delete(1, 3).unwrap();
+    drop(wal);
+
+    let mut ids = Vec::new();
+    for file in [&frozen, &path] {
+        Wal::replay(file, |entry| match entry {
+            WalEntry::Insert { id, metadata, .. } => {
+                assert_eq!(metadata, meta);
+                ids.push(id);
+            }
+            WalEntry::Delete { id, .. } => ids.push(id + 100),
+        })
+        .unwrap();
+    }
+    assert_eq!(ids, [1, 2, 101]);
+
+    // Appending after reopening continues at the end of the file.
+    let mut wal = Wal::new(&path, WalSyncMode::Async).unwrap();
+    wal.append(3, &[0.5, 0.6], &meta, 4).unwrap();
+    drop(wal);
+    let mut count = 0;
+    Wal::replay(&path, |_| count += 1).unwrap();
+    assert_eq!(count, 3);
+}
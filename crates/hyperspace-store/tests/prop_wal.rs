@@ -1,4 +1,4 @@
-use hyperspace_store::wal::{Wal, WalEntry, WalSyncMode};
+use hyperspace_store::wal::{Wal, WalEntry, WalOp, WalSyncMode};
 use proptest::prelude::*;
 use std::collections::HashMap;
 use tempfile::tempdir;
@@ -24,6 +24,60 @@ struct TestEntry {
     metadata: HashMap<String, String>,
 }
 
+/// A write as the collection issues it: one clock per call.
+#[derive(Debug, Clone)]
+enum Write {
+    Insert(TestEntry),
+    Delete(u32),
+    Mixed(Vec<(u32, Option<Vec<f64>>)>),
+}
+
+fn arb_writes() -> impl Strategy<Value = Vec<Write>> {
+    // Few ids, so writes overwrite and delete each other
+    let id = 0..8u32;
+    let write = prop_oneof![
+        4 => (id.clone(), arb_vector(), arb_metadata()).prop_map(|(id, vector, metadata)| {
+            Write::Insert(TestEntry { id, vector, metadata })
+        }),
+        2 => id.clone().prop_map(Write::Delete),
+        1 => proptest::collection::vec((id, proptest::option::of(arb_vector())), 1..4)
+            .prop_map(Write::Mixed),
+    ];
+    proptest::collection::vec(write, 1..40)
+}
+
+/// Live points by id and the highest clock applied.
+type State = (HashMap<u32, Vec<u64>>, u64);
+
+/// Applies one replayed entry the way collection recovery does: only
+/// entries newer than the persisted clock count.
+fn fold(state: &mut State, loaded_clock: u64, entry: WalEntry) {
+    let (points, last_clock) = state;
+    match entry {
+        WalEntry::Insert {
+            id,
+            vector,
+            logical_clock,
+            ..
+        } if logical_clock > loaded_clock => {
+            // Bit patterns, so NaN coordinates still compare equal
+            points.insert(id, vector.iter().map(|x| x.to_bits()).collect());
+            *last_clock = (*last_clock).max(logical_clock);
+        }
+        WalEntry::Delete { id, logical_clock } if logical_clock > loaded_clock => {
+            points.remove(&id);
+            *last_clock = (*last_clock).max(logical_clock);
+        }
+        _ => {}
+    }
+}
+
+fn replay_onto(path: &std::path::Path, mut state: State) -> State {
+    let loaded_clock = state.1;
+    Wal::replay(path, |entry| fold(&mut state, loaded_clock, entry)).unwrap();
+    state
+}
+
 fn arb_entries() -> impl Strategy<Value = Vec<TestEntry>> {
     proptest::collection::vec(
         (any::<u32>(), arb_vector(), arb_metadata()).prop_map(|(id, vector, metadata)| TestEntry {
@@ -63,7 +117,9 @@ proptest! {
                 vector,
                 metadata,
                 ..
-            } = entry;
+            } = entry else {
+                panic!("expected insert entry");
+            };
             replayed.push(TestEntry { id, vector, metadata });
         }).unwrap();
 
@@ -108,7 +164,9 @@ proptest! {
                  vector,
                  metadata,
                  ..
-             } = entry;
+             } = entry else {
+                 panic!("expected insert entry");
+             };
              replayed.push(TestEntry { id, vector, metadata });
         });
 
@@ -122,4 +180,101 @@ proptest! {
             assert_eq!(entries[i].metadata, replayed[i].metadata);
         }
     }
+
+    #[test]
+    fn test_wal_decode_records_stops_at_cut(
+        entries in arb_entries(),
+        cut_bytes in 0usize..100usize
+    ) {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("wal_ship.hyp");
+        {
+            let mut wal = Wal::new(&wal_path, WalSyncMode::Async).unwrap();
+            for entry in &entries {
+                wal.append(entry.id, &entry.vector, &entry.metadata, 7)
+                    .unwrap();
+            }
+            wal.sync().unwrap();
+        }
+
+        // A range shipped while the last record was still being written
+        let data = std::fs::read(&wal_path).unwrap();
+        let shipped = &data[..data.len().saturating_sub(cut_bytes)];
+        let (decoded, consumed) = Wal::decode_records(shipped).unwrap();
+        assert!(consumed <= shipped.len());
+        if cut_bytes == 0 {
+            assert_eq!(consumed, data.len());
+            assert_eq!(decoded.len(), entries.len());
+        }
+        for (original, entry) in entries.iter().zip(&decoded) {
+            let WalEntry::Insert { id, vector, logical_clock, .. } = entry else {
+                panic!("expected insert entry");
+            };
+            assert_eq!(original.id, *id);
+            assert_eq!(&original.vector, vector);
+            assert_eq!(*logical_clock, 7);
+        }
+
+        // The rest decodes from where the first range stopped
+        let (rest, _) = Wal::decode_records(&data[consumed..]).unwrap();
+        assert_eq!(decoded.len() + rest.len(), entries.len());
+    }
+
+    #[test]
+    fn test_wal_replay_is_idempotent(
+        writes in arb_writes(),
+        persisted in any::<prop::sample::Index>(),
+    ) {
+        let dir = tempdir().unwrap();
+        let wal_path = dir.path().join("wal_idempotent.hyp");
+        let empty = HashMap::new();
+        {
+            let mut wal = Wal::new(&wal_path, WalSyncMode::Async).unwrap();
+            for (clock, write) in (1u64..).zip(&writes) {
+                match write {
+                    Write::Insert(e) => wal.append(e.id, &e.vector, &e.metadata, clock).unwrap(),
+                    Write::Delete(id) => wal.append_delete(*id, clock).unwrap(),
+                    Write::Mixed(ops) => {
+                        let ops: Vec<WalOp<'_>> = ops
+                            .iter()
+                            .map(|(id, vector)| match vector {
+                                Some(vector) => WalOp::Insert {
+                                    id: *id,
+                                    vector,
+                                    metadata: &empty,
+                                },
+                                None => WalOp::Delete { id: *id },
+                            })
+                            .collect();
+                        wal.append_mixed(&ops, clock).unwrap();
+                    }
+                }
+            }
+            wal.sync().unwrap();
+        }
+
+        let once = replay_onto(&wal_path, State::default());
+        prop_assert_eq!(once.1, writes.len() as u64);
+
+        // Replaying onto the state it produced changes nothing
+        let twice = replay_onto(&wal_path, once.clone());
+        prop_assert_eq!(&twice, &once);
+
+        // Nor does replaying onto a state persisted partway through the log,
+        // as after a crash between a snapshot and the WAL rotation
+        let cut = persisted.index(writes.len() + 1);
+        let mut prefix = State::default();
+        Wal::replay(&wal_path, |entry| {
+            let clock = match &entry {
+                WalEntry::Insert { logical_clock, .. } | WalEntry::Delete { logical_clock, .. } => {
+                    *logical_clock
+                }
+            };
+            if clock <= cut as u64 {
+                fold(&mut prefix, 0, entry);
+            }
+        })
+        .unwrap();
+        prop_assert_eq!(&replay_onto(&wal_path, prefix), &once);
+    }
 }
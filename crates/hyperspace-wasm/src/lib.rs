@@ -10,6 +10,9 @@ use hyperspace_index::HnswIndex;
 use hyperspace_store::VectorStore;
 use rexie::{ObjectStore, Rexie, TransactionMode};
 
+/// Metadata key that `insertWithText` stores the searchable text under.
+const TEXT_KEY: &str = "text";
+
 /// Number of sync buckets — must match `crate::sync::SYNC_BUCKETS` on the server.
 const SYNC_BUCKETS: usize = 256;
 
@@ -92,6 +95,39 @@ impl HyperspaceDB {
     /// # Errors
     /// Returns error on dimension mismatch or duplicate ID.
     pub fn insert(&self, id: u32, vector: &[f64]) -> Result<(), JsValue> {
+        self.insert_with_metadata(id, vector, HashMap::new())
+    }
+
+    /// Inserts a vector with text for `hybridSearch`. The text is indexed for
+    /// BM25 under the `text` key alongside `metadata`, an optional object of
+    /// string values.
+    ///
+    /// # Errors
+    /// Returns error on dimension mismatch, duplicate ID or invalid metadata.
+    #[wasm_bindgen(js_name = insertWithText)]
+    pub fn insert_with_text(
+        &self,
+        id: u32,
+        vector: &[f64],
+        text: &str,
+        metadata: JsValue,
+    ) -> Result<(), JsValue> {
+        let mut meta: HashMap<String, String> = if metadata.is_undefined() || metadata.is_null() {
+            HashMap::new()
+        } else {
+            serde_wasm_bindgen::from_value(metadata)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?
+        };
+        meta.insert(TEXT_KEY.to_string(), text.to_string());
+        self.insert_with_metadata(id, vector, meta)
+    }
+
+    fn insert_with_metadata(
+        &self,
+        id: u32,
+        vector: &[f64],
+        metadata: HashMap<String, String>,
+    ) -> Result<(), JsValue> {
         if vector.len() != self.dimension {
             return Err(JsValue::from_str(&format!(
                 "Dimension mismatch: expected {}.",
@@ -108,8 +144,8 @@ impl HyperspaceDB {
 
         macro_rules! insert_impl {
             ($idx:expr) => {
-                $idx.insert(vector, HashMap::new())
-                    .map_err(|e| JsValue::from_str(&e))?
+                $idx.insert(vector, metadata)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?
             };
         }
 
@@ -143,23 +179,54 @@ impl HyperspaceDB {
     /// # Errors
     /// Returns error on dimension mismatch.
     pub fn search(&self, vector: &[f64], k: usize) -> Result<JsValue, JsValue> {
+        self.run_search(vector, k, None, None)
+    }
+
+    /// Fuses vector similarity with BM25 over text added via `insertWithText`.
+    /// `alpha` is the vector weight in `[0, 1]`; `1 - alpha` goes to keywords.
+    ///
+    /// # Errors
+    /// Returns error on dimension mismatch.
+    #[wasm_bindgen(js_name = hybridSearch)]
+    pub fn hybrid_search(
+        &self,
+        vector: &[f64],
+        text: &str,
+        alpha: f32,
+        k: usize,
+    ) -> Result<JsValue, JsValue> {
+        self.run_search(vector, k, Some(text), Some(alpha))
+    }
+
+    fn run_search(
+        &self,
+        vector: &[f64],
+        k: usize,
+        hybrid_query: Option<&str>,
+        hybrid_alpha: Option<f32>,
+    ) -> Result<JsValue, JsValue> {
         if vector.len() != self.dimension {
             return Err(JsValue::from_str("Dimension mismatch"));
         }
 
+        let params = hyperspace_core::SearchParams {
+            top_k: k,
+            ef_search: 100,
+            hybrid_query: hybrid_query.map(str::to_string),
+            hybrid_alpha,
+            use_wasserstein: false,
+            bm25_options: None,
+            // Weighted fusion makes `alpha` a vector weight, as on the server API.
+            fusion_method: hybrid_query.map(|_| "weighted".to_string()),
+            text_match: hyperspace_core::TextMatch::Any,
+            trace: None,
+            num_probes: 0,
+        };
+
         macro_rules! search_impl {
-            ($idx:expr) => {{
-                let params = hyperspace_core::SearchParams {
-                    top_k: k,
-                    ef_search: 100,
-                    hybrid_query: None,
-                    hybrid_alpha: None,
-                    use_wasserstein: false,
-                    bm25_options: None,
-                    fusion_method: None,
-                };
+            ($idx:expr) => {
                 $idx.search(vector, &HashMap::new(), &[], &params)
-            }};
+            };
         }
 
         let results = match &self.index {
@@ -234,7 +301,7 @@ impl HyperspaceDB {
             serde_wasm_bindgen::from_value(data).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
         let mut applied = 0u32;
-        for entry in &entries {
+        for entry in entries {
             if entry.vector.len() != self.dimension {
                 continue;
             }
@@ -245,8 +312,8 @@ impl HyperspaceDB {
                     continue;
                 }
             }
-            // Insert
-            if let Ok(()) = self.insert(entry.id, &entry.vector) {
+            // Insert; metadata text becomes searchable via `hybridSearch`
+            if let Ok(()) = self.insert_with_metadata(entry.id, &entry.vector, entry.metadata) {
                 applied += 1;
             }
         }
@@ -294,7 +361,8 @@ impl HyperspaceDB {
         // 2. Export Index (Bytes)
         macro_rules! save_impl {
             ($idx:expr) => {
-                $idx.save_to_bytes().map_err(|e| JsValue::from_str(&e))?
+                $idx.save_to_bytes()
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?
             };
         }
 
@@ -406,35 +474,35 @@ impl HyperspaceDB {
         let new_index_wrapper = match &self.index {
             IndexWrapper::L2Dim384(_) => IndexWrapper::L2Dim384(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::CosineDim384(_) => IndexWrapper::CosineDim384(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::L2Dim768(_) => IndexWrapper::L2Dim768(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::CosineDim768(_) => IndexWrapper::CosineDim768(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::L2Dim1024(_) => IndexWrapper::L2Dim1024(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::CosineDim1024(_) => IndexWrapper::CosineDim1024(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::L2Dim1536(_) => IndexWrapper::L2Dim1536(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
             IndexWrapper::CosineDim1536(_) => IndexWrapper::CosineDim1536(Arc::new(
                 HnswIndex::load_from_bytes(&index_bytes, storage, mode, config)
-                    .map_err(|e| JsValue::from_str(&e))?,
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?,
             )),
         };
 
@@ -485,14 +553,10 @@ impl Drop for HyperspaceDB {
 }
 
 /// Deserialization struct for vectors received from the sync pull endpoint.
-/// `metadata` is accepted from the server JSON but not yet stored in the WASM
-/// in-memory index (stored for forward-compatibility).
 #[derive(serde::Deserialize)]
 struct SyncEntry {
     id: u32,
     vector: Vec<f64>,
-    /// Accepted from server response; not yet persisted in WASM RAM index.
     #[serde(default)]
-    #[allow(dead_code)]
     metadata: HashMap<String, String>,
 }
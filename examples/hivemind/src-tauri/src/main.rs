@@ -3,11 +3,12 @@
 use tauri::State;
 use std::sync::Arc;
 use serde::Serialize;
-use std::collections::HashMap;
 
 use hyperspace_index::HnswIndex;
 use hyperspace_core::{EuclideanMetric, GlobalConfig, QuantizationMode};
 use hyperspace_store::VectorStore;
+use hyperspace_embed::extract::{self, DocumentFormat};
+use hyperspace_embed::ChunkingConfig;
 
 type LocalIndex = HnswIndex<1024, EuclideanMetric>;
 
@@ -29,34 +30,29 @@ fn get_stats(state: State<AppState>) -> (usize, usize) {
 
 #[tauri::command]
 async fn ingest_pdf(path: String, state: State<'_, AppState>) -> Result<u32, String> {
-    // 1. Read PDF
-    let _text = pdf_extract::extract_text(&path).map_err(|e| e.to_string())?;
-    
-    // 2. Chunck & Embed (Using dummy embedding here as no Python/API)
-    // In real app, we'd call ONNX runtimes or API.
-    // Here we generate random vector to simulate flow.
-    let _vec = vec![0.0f32; 1024]; // Dummy
-    // Convert to internal representation (f32 -> f32).
-    
-    // Insert into Index
-    // Assume auto-increment ID? Or derive from hash?
-    let _id = state.index.count_nodes() as u32;
-    
-    // HnswIndex uses generic internal vector logic.
-    // We assume input is handled by caller logic usually, but here...
-    // HnswIndex::insert takes &[T] where T depends on quantization mode?
-    // No, insert takes `&[f64]` in WASM wrapper, but checking `HnswIndex::insert` signature...
-    // It takes `&[f32]` or `&[f64]`?
-    // It takes `&[f64]` and converts internally.
-    // Or generic `InputVector`.
-    
-    // Let's use `vec![0.1; 1024]` f64 for simplicity as core handles conversion.
-    let input_vec = vec![0.1f64; 1024];
-    
-    let internal_id = state.index.insert(&input_vec, HashMap::new())
-        .map_err(|e| e.to_string())?;
-        
-    Ok(internal_id)
+    // 1. Read the PDF and split it into chunks that carry title, page and position
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let document = extract::extract(&bytes, DocumentFormat::Pdf).map_err(|e| e.to_string())?;
+    let config = ChunkingConfig {
+        chunk_size: 256,
+        overlap: 0.1,
+    };
+
+    // 2. Embed & insert each chunk. There is no model in this demo, so every
+    // chunk gets the same placeholder vector; the metadata is real.
+    let mut inserted = 0;
+    for chunk in document.chunks(Some(&config)) {
+        let mut metadata = chunk.metadata;
+        metadata.insert("source".to_string(), path.clone());
+        let input_vec = vec![0.1f64; 1024];
+        state
+            .index
+            .insert(&input_vec, metadata)
+            .map_err(|e| e.to_string())?;
+        inserted += 1;
+    }
+
+    Ok(inserted)
 }
 
 fn main() {
@@ -0,0 +1,10 @@
+//! Parses arbitrary text as a filter query; malformed input must come back
+//! as an error, not a panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = hyperspace_core::parse_filter(input);
+});
@@ -0,0 +1,55 @@
+//! Runs arbitrary inserts, deletes and purges against a small index and
+//! checks the graph invariants after every one.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use hyperspace_core::vector::HyperVector;
+use hyperspace_core::{EuclideanMetric, GlobalConfig, QuantizationMode};
+use hyperspace_index::test_utils::check_graph;
+use hyperspace_index::HnswIndex;
+use hyperspace_store::VectorStore;
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const D: usize = 4;
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Insert([i8; D]),
+    Delete(u8),
+    Purge(u8),
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let dir = tempfile::tempdir().unwrap();
+    let config = GlobalConfig::default();
+    config.set_m(4);
+    config.set_ef_construction(16);
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        HyperVector::<D>::SIZE,
+    ));
+    let index =
+        HnswIndex::<D, EuclideanMetric>::new(storage, QuantizationMode::None, Arc::new(config));
+
+    let mut ids = Vec::new();
+    for op in ops.iter().take(256) {
+        match *op {
+            // Small integer coordinates, so duplicates and ties are common
+            Op::Insert(coords) => {
+                let vector = coords.map(f64::from);
+                ids.push(index.insert(&vector, HashMap::new()).unwrap());
+            }
+            Op::Delete(at) if !ids.is_empty() => index.delete(ids[at as usize % ids.len()]),
+            Op::Purge(at) if !ids.is_empty() => {
+                index.purge(&[ids[at as usize % ids.len()]]).unwrap();
+            }
+            _ => {}
+        }
+        if let Err(e) = check_graph(&index) {
+            panic!("{e} after {op:?}");
+        }
+    }
+});
@@ -0,0 +1,38 @@
+//! Loads arbitrary bytes as an index snapshot. Corrupt files must be
+//! rejected with an error; a file that loads must also survive a search.
+
+#![no_main]
+
+use hyperspace_core::vector::HyperVector;
+use hyperspace_core::{EuclideanMetric, GlobalConfig, QuantizationMode, SearchParams};
+use hyperspace_index::HnswIndex;
+use hyperspace_store::VectorStore;
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const D: usize = 4;
+
+fuzz_target!(|data: &[u8]| {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.snap");
+    std::fs::write(&path, data).unwrap();
+    let storage = Arc::new(VectorStore::new(
+        &dir.path().join("vectors"),
+        HyperVector::<D>::SIZE,
+    ));
+    let Ok(index) = HnswIndex::<D, EuclideanMetric>::load_snapshot(
+        &path,
+        storage,
+        QuantizationMode::None,
+        Arc::new(GlobalConfig::default()),
+    ) else {
+        return;
+    };
+    let params = SearchParams {
+        top_k: 5,
+        ef_search: 16,
+        ..Default::default()
+    };
+    let _ = index.search(&[0.0; D], &HashMap::new(), &[], &params);
+});
@@ -0,0 +1,22 @@
+//! Decodes arbitrary bytes as a shipped WAL range, then replays them from a
+//! file. Neither may panic, and the decoded prefix must decode again whole.
+
+#![no_main]
+
+use hyperspace_store::wal::Wal;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((entries, consumed)) = Wal::decode_records(data) else {
+        return;
+    };
+    assert!(consumed <= data.len());
+    let (again, reconsumed) = Wal::decode_records(&data[..consumed]).unwrap();
+    assert_eq!(reconsumed, consumed);
+    assert_eq!(again.len(), entries.len());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal.log");
+    std::fs::write(&path, data).unwrap();
+    let _ = Wal::replay(&path, |_| {});
+});